@@ -39,6 +39,8 @@ mod transaction_input;
 mod transaction_kernel;
 mod transaction_output;
 mod unblinded_output;
+mod utxo_mined_info;
+mod utxo_stats;
 
 use std::convert::TryFrom;
 
@@ -64,6 +66,8 @@ pub use self::{
     transaction_input::*,
     transaction_kernel::*,
     transaction_output::*,
+    utxo_mined_info::*,
+    utxo_stats::*,
 };
 use crate::{tari_rpc as grpc, tari_rpc::BlockGroupRequest};
 
@@ -22,20 +22,36 @@
 
 use std::convert::TryFrom;
 
+use tari_common_types::types::{FixedHash, PublicKey};
 use tari_core::transactions::transaction_components::SideChainFeatures;
+use tari_utilities::ByteArray;
 
 use crate::tari_rpc as grpc;
 
 impl From<SideChainFeatures> for grpc::SideChainFeatures {
-    fn from(_value: SideChainFeatures) -> Self {
-        Self {}
+    fn from(value: SideChainFeatures) -> Self {
+        Self {
+            contract_id: value.contract_id.to_vec(),
+            checkpoint_merkle_root: value.checkpoint_merkle_root.to_vec(),
+            committee: value.committee.iter().map(|pk| pk.as_bytes().to_vec()).collect(),
+        }
     }
 }
 
 impl TryFrom<grpc::SideChainFeatures> for SideChainFeatures {
     type Error = String;
 
-    fn try_from(_features: grpc::SideChainFeatures) -> Result<Self, Self::Error> {
-        Ok(Self {})
+    fn try_from(features: grpc::SideChainFeatures) -> Result<Self, Self::Error> {
+        let contract_id = FixedHash::try_from(features.contract_id)
+            .map_err(|_| "Invalid contract id: must be 32 bytes".to_string())?;
+        let checkpoint_merkle_root = FixedHash::try_from(features.checkpoint_merkle_root)
+            .map_err(|_| "Invalid checkpoint Merkle root: must be 32 bytes".to_string())?;
+        let committee = features
+            .committee
+            .iter()
+            .map(|pk| PublicKey::from_bytes(pk).map_err(|err| format!("Invalid committee public key: {:?}", err)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(contract_id, checkpoint_merkle_root, committee))
     }
 }
@@ -46,6 +46,10 @@ pub struct CommonCliArgs {
     #[clap()]
     pub log_level: Option<Level>,
 
+    /// A path to the network definition file to load for the custom network (`--network custom`)
+    #[clap(long, alias = "network_file", env = "TARI_NETWORK_FILE")]
+    pub network_file: Option<PathBuf>,
+
     /// Overrides for properties in the config file, e.g. -p base_node.netwok=esmeralda
     #[clap(short = 'p', parse(try_from_str = parse_key_val), multiple_occurrences(true))]
     pub config_property_overrides: Vec<(String, String)>,
@@ -102,6 +106,19 @@ impl CommonCliArgs {
             path
         }
     }
+
+    /// The network definition file path, resolved relative to the base path if it is not absolute
+    pub fn network_file_path(&self) -> Option<PathBuf> {
+        self.network_file.as_ref().map(|network_file| {
+            if network_file.is_absolute() {
+                network_file.clone()
+            } else {
+                let mut base_path = PathBuf::from(&self.base_path);
+                base_path.push(network_file);
+                base_path
+            }
+        })
+    }
 }
 
 impl ConfigOverrideProvider for CommonCliArgs {
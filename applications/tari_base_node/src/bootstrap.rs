@@ -28,7 +28,12 @@ use tari_common::{
     configuration::bootstrap::ApplicationType,
     exit_codes::{ExitCode, ExitError},
 };
-use tari_comms::{peer_manager::Peer, protocol::rpc::RpcServer, NodeIdentity, UnspawnedCommsNode};
+use tari_comms::{
+    peer_manager::Peer,
+    protocol::rpc::{interceptor::CallMetricsLayer, RpcServer},
+    NodeIdentity,
+    UnspawnedCommsNode,
+};
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node,
@@ -164,7 +169,7 @@ where B: BlockchainBackend + 'static
         // Save final node identity after comms has initialized. This is required because the public_address can be
         // changed by comms during initialization when using tor.
         match p2p_config.transport.transport_type {
-            TransportType::Tcp => {}, // Do not overwrite TCP public_address in the base_node_id!
+            TransportType::Tcp | TransportType::Quic => {}, // Do not overwrite the public_address in the base_node_id!
             _ => {
                 identity_management::save_as_json(&base_node_config.identity_file, &*comms.node_identity())
                     .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?;
@@ -191,6 +196,7 @@ where B: BlockchainBackend + 'static
         let rpc_server = RpcServer::builder()
             .with_maximum_simultaneous_sessions(config.rpc_max_simultaneous_sessions)
             .with_maximum_sessions_per_client(config.rpc_max_sessions_per_peer)
+            .with_interceptor(CallMetricsLayer::new())
             .finish();
 
         // Add your RPC services here ‍🏴‍☠️️☮️🌊
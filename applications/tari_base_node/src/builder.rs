@@ -31,7 +31,14 @@ use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, Com
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, ChainStorageError, LMDBDatabase, Validators},
+    chain_storage::{
+        create_lmdb_database,
+        create_sled_database,
+        BlockchainBackend,
+        BlockchainDatabase,
+        ChainStorageError,
+        Validators,
+    },
     consensus::ConsensusManager,
     mempool::{service::LocalMempoolService, Mempool},
     proof_of_work::randomx_factory::RandomXFactory,
@@ -53,7 +60,11 @@ use tari_service_framework::ServiceHandles;
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::watch;
 
-use crate::{bootstrap::BaseNodeBootstrapper, config::DatabaseType, ApplicationConfig};
+use crate::{
+    bootstrap::BaseNodeBootstrapper,
+    config::{BaseNodeConfig, DatabaseType},
+    ApplicationConfig,
+};
 
 const LOG_TARGET: &str = "c::bn::initialization";
 
@@ -63,7 +74,7 @@ const LOG_TARGET: &str = "c::bn::initialization";
 pub struct BaseNodeContext {
     config: Arc<ApplicationConfig>,
     consensus_rules: ConsensusManager,
-    blockchain_db: BlockchainDatabase<LMDBDatabase>,
+    blockchain_db: BlockchainDatabase<Box<dyn BlockchainBackend>>,
     base_node_comms: CommsNode,
     base_node_dht: Dht,
     base_node_handles: ServiceHandles,
@@ -132,7 +143,7 @@ impl BaseNodeContext {
     }
 
     /// Returns a BlockchainDatabase handle
-    pub fn blockchain_db(&self) -> BlockchainDatabase<LMDBDatabase> {
+    pub fn blockchain_db(&self) -> BlockchainDatabase<Box<dyn BlockchainBackend>> {
         self.blockchain_db.clone()
     }
 
@@ -171,17 +182,23 @@ pub async fn configure_and_initialize_node(
     node_identity: Arc<NodeIdentity>,
     interrupt_signal: ShutdownSignal,
 ) -> Result<BaseNodeContext, ExitError> {
-    let result = match &app_config.base_node.db_type {
-        DatabaseType::Lmdb => {
-            let backend = create_lmdb_database(
-                app_config.base_node.lmdb_path.as_path(),
-                app_config.base_node.lmdb.clone(),
-            )
-            .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?;
-            build_node_context(backend, app_config, node_identity, interrupt_signal).await?
-        },
+    let backend = create_backend(&app_config.base_node)?;
+    build_node_context(backend, app_config, node_identity, interrupt_signal).await
+}
+
+/// Opens the blockchain backend selected by `db_type` in the base node config
+pub fn create_backend(config: &BaseNodeConfig) -> Result<Box<dyn BlockchainBackend>, ExitError> {
+    let backend: Box<dyn BlockchainBackend> = match &config.db_type {
+        DatabaseType::Lmdb => Box::new(
+            create_lmdb_database(config.lmdb_path.as_path(), config.lmdb.clone())
+                .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?,
+        ),
+        DatabaseType::Sled => Box::new(
+            create_sled_database(config.sled_path.as_path(), config.sled)
+                .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?,
+        ),
     };
-    Ok(result)
+    Ok(backend)
 }
 
 /// Constructs the base node context, this includes setting up the consensus manager, mempool, base node
@@ -196,7 +213,7 @@ pub async fn configure_and_initialize_node(
 /// ## Returns
 /// Result containing the BaseNodeContext, String will contain the reason on error
 async fn build_node_context(
-    backend: LMDBDatabase,
+    backend: Box<dyn BlockchainBackend>,
     app_config: Arc<ApplicationConfig>,
     base_node_identity: Arc<NodeIdentity>,
     interrupt_signal: ShutdownSignal,
@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::path::PathBuf;
+
 use clap::Parser;
 use tari_app_utilities::common_cli_args::CommonCliArgs;
 use tari_common::configuration::{ConfigOverrideProvider, Network};
@@ -41,6 +43,16 @@ pub(crate) struct Cli {
     // TODO: Should be a command rather
     #[clap(long, alias = "rebuild_db")]
     pub rebuild_db: bool,
+    /// Import a chain snapshot created with the export-snapshot command into an empty database, then exit
+    #[clap(long, value_name = "PATH")]
+    pub import_snapshot: Option<PathBuf>,
+    /// Run the chain simulation scenario in the given file under the consensus rules of the network, print a report of
+    /// block times, difficulty and emission, then exit
+    #[clap(long, value_name = "PATH")]
+    pub simulate_chain: Option<PathBuf>,
+    /// Write the simulated chain to a CSV file (used with --simulate-chain)
+    #[clap(long, value_name = "PATH", requires = "simulate_chain")]
+    pub simulation_csv: Option<PathBuf>,
     /// Run in non-interactive mode, with no UI.
     #[clap(short, long, alias = "non-interactive", env = "TARI_NON_INTERACTIVE")]
     pub non_interactive_mode: bool,
@@ -0,0 +1,124 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use clap::Parser;
+use log::*;
+use tari_core::chain_storage::snapshot::{export_snapshot, SnapshotSummary};
+use tari_utilities::hex::Hex;
+use tokio::task;
+
+use super::{CommandContext, HandleCommand};
+use crate::LOG_TARGET;
+
+/// Exports the chain state up to a height to a snapshot file that another node can import with --import-snapshot
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The file to write the snapshot to
+    path: PathBuf,
+    /// The height of the snapshot. Defaults to the current tip.
+    #[clap(long)]
+    height: Option<u64>,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.export_snapshot(args.path, args.height).await
+    }
+}
+
+impl CommandContext {
+    /// Starts the snapshot export in the background, as it can take longer than the command timeout
+    pub async fn export_snapshot(&self, path: PathBuf, height: Option<u64>) -> Result<(), Error> {
+        let height = match height {
+            Some(height) => height,
+            None => self.blockchain_db.get_chain_metadata().await?.height_of_longest_chain(),
+        };
+        // The snapshot is only moved to the requested path once it is complete, so a failed export does not leave a
+        // partial snapshot behind
+        let partial_path = partial_path(&path)?;
+        let file = File::create(&partial_path)?;
+        let db = self.blockchain_db.inner().clone();
+        println!(
+            "Exporting snapshot at height {} to '{}' in the background",
+            height,
+            path.display()
+        );
+        task::spawn_blocking(move || {
+            let result = export_snapshot(&db, height, BufWriter::new(file))
+                .map_err(Error::from)
+                .and_then(|summary| {
+                    fs::rename(&partial_path, &path)?;
+                    Ok(summary)
+                });
+            if result.is_err() {
+                if let Err(err) = fs::remove_file(&partial_path) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to remove partial snapshot '{}': {}",
+                        partial_path.display(),
+                        err
+                    );
+                }
+            }
+            report_export(&path, result);
+        });
+        Ok(())
+    }
+}
+
+fn partial_path(path: &Path) -> Result<PathBuf, Error> {
+    let mut file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("'{}' is not a file path", path.display()))?
+        .to_os_string();
+    file_name.push(".partial");
+    Ok(path.with_file_name(file_name))
+}
+
+fn report_export(path: &Path, result: Result<SnapshotSummary, Error>) {
+    match result {
+        Ok(summary) => println!(
+            "Exported snapshot at height {} (best block {}) to '{}'. {} kernel(s), {} unspent output(s), {} pruned \
+             output(s).",
+            summary.height,
+            summary.best_block.to_hex(),
+            path.display(),
+            summary.num_kernels,
+            summary.num_unspent_outputs,
+            summary.num_pruned_outputs
+        ),
+        Err(err) => {
+            error!(target: LOG_TARGET, "Snapshot export failed: {}", err);
+            println!("Snapshot export to '{}' failed: {}", path.display(), err);
+        },
+    }
+}
@@ -23,6 +23,8 @@
 use anyhow::Error;
 use async_trait::async_trait;
 use clap::Parser;
+use tari_core::validation::block_validators::ASSUME_VALID_SKIPPED_CHECKS;
+use tari_utilities::hex::Hex;
 
 use super::{CommandContext, HandleCommand};
 
@@ -33,14 +35,36 @@ pub struct Args {}
 #[async_trait]
 impl HandleCommand<Args> for CommandContext {
     async fn handle_command(&mut self, _: Args) -> Result<(), Error> {
-        self.state_info()
+        self.state_info().await
     }
 }
 
 impl CommandContext {
     /// Function to process the get-state-info command
-    pub fn state_info(&self) -> Result<(), Error> {
-        println!("Current state machine state:\n{}", *self.state_machine_info.borrow());
+    pub async fn state_info(&self) -> Result<(), Error> {
+        let status = self.state_machine_info.borrow().clone();
+        println!("Current state machine state:\n{}", status);
+
+        let assume_valid_block = self
+            .config
+            .base_node
+            .state_machine
+            .blockchain_sync_config
+            .assume_valid_block;
+        if let Some(hash) = assume_valid_block {
+            match self.blockchain_db.fetch_chain_header_by_block_hash(hash).await? {
+                Some(header) => println!(
+                    "Assume-valid block #{} `{}`: block sync skips {} for blocks at or below this height",
+                    header.height(),
+                    hash.to_hex(),
+                    ASSUME_VALID_SKIPPED_CHECKS.join(", ")
+                ),
+                None => println!(
+                    "Assume-valid block `{}` is not in the header chain yet, blocks are fully validated",
+                    hash.to_hex()
+                ),
+            }
+        }
         Ok(())
     }
 }
@@ -25,8 +25,11 @@ mod ban_peer;
 mod block_timing;
 mod check_db;
 mod check_for_updates;
+mod clear_script_index;
+mod clear_utxo_stats;
 mod dial_peer;
 mod discover_peer;
+mod export_snapshot;
 mod get_block;
 mod get_chain_metadata;
 mod get_db_stats;
@@ -44,6 +47,7 @@ mod list_reorgs;
 mod period_stats;
 mod ping_peer;
 mod quit;
+mod replay_reorg;
 mod reset_offline_peers;
 mod rewind_blockchain;
 mod search_kernel;
@@ -74,7 +78,7 @@ use tari_comms_dht::{DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface},
     blocks::ChainHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, LMDBDatabase},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     consensus::ConsensusManager,
     mempool::service::LocalMempoolService,
 };
@@ -117,10 +121,14 @@ pub enum Command {
     ListConnections(list_connections::Args),
     ListHeaders(list_headers::Args),
     CheckDb(check_db::Args),
+    ExportSnapshot(export_snapshot::Args),
     PeriodStats(period_stats::Args),
     HeaderStats(header_stats::Args),
     BlockTiming(block_timing::Args),
     ListReorgs(list_reorgs::Args),
+    ReplayReorg(replay_reorg::Args),
+    ClearUtxoStats(clear_utxo_stats::Args),
+    ClearScriptIndex(clear_script_index::Args),
     DiscoverPeer(discover_peer::Args),
     GetBlock(get_block::Args),
     SearchUtxo(search_utxo::Args),
@@ -150,7 +158,7 @@ pub trait HandleCommand<T> {
 pub struct CommandContext {
     pub config: Arc<ApplicationConfig>,
     consensus_rules: ConsensusManager,
-    blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
+    blockchain_db: AsyncBlockchainDb<Box<dyn BlockchainBackend>>,
     discovery_service: DhtDiscoveryRequester,
     dht_metrics_collector: MetricsCollectorHandle,
     rpc_server: RpcServerHandle,
@@ -234,10 +242,14 @@ impl HandleCommand<Command> for CommandContext {
             Command::UnbanAllPeers(args) => self.handle_command(args).await,
             Command::ListHeaders(args) => self.handle_command(args).await,
             Command::CheckDb(args) => self.handle_command(args).await,
+            Command::ExportSnapshot(args) => self.handle_command(args).await,
             Command::PeriodStats(args) => self.handle_command(args).await,
             Command::HeaderStats(args) => self.handle_command(args).await,
             Command::BlockTiming(args) => self.handle_command(args).await,
             Command::ListReorgs(args) => self.handle_command(args).await,
+            Command::ReplayReorg(args) => self.handle_command(args).await,
+            Command::ClearUtxoStats(args) => self.handle_command(args).await,
+            Command::ClearScriptIndex(args) => self.handle_command(args).await,
             Command::DiscoverPeer(args) => self.handle_command(args).await,
             Command::GetBlock(args) => self.handle_command(args).await,
             Command::SearchUtxo(args) => self.handle_command(args).await,
@@ -0,0 +1,197 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use clap::Parser;
+use log::*;
+use tari_core::{
+    chain_storage::{
+        create_lmdb_database,
+        BlockchainBackend,
+        BlockchainDatabase,
+        BlockchainDatabaseConfig,
+        LMDBDatabase,
+        Reorg,
+        Validators,
+    },
+    consensus::ConsensusManager,
+    proof_of_work::randomx_factory::RandomXFactory,
+    reorg_replay::{replay_reorg, ReorgReplayReport, ReorgScenario},
+    transactions::CryptoFactories,
+    validation::{
+        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        header_validator::HeaderValidator,
+        DifficultyCalculator,
+    },
+};
+use tari_storage::lmdb_store::LMDBConfig;
+use tokio::task;
+
+use super::{CommandContext, HandleCommand};
+use crate::LOG_TARGET;
+
+/// Replays a tracked reorg against a fresh database and mempool,
+/// checking that reorged transactions are restored and that the output
+/// statuses given to wallets converge. The replay database is created in
+/// the `reorg_replay` folder of the node's data directory and removed
+/// afterwards. The blocks up to the fork are copied into it from the
+/// main chain and the reorged out blocks must still be in the orphan
+/// pool.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The number of the reorg as shown by list-reorgs
+    index: usize,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.replay_reorg(args.index).await
+    }
+}
+
+impl CommandContext {
+    /// Starts the replay in the background, as it can take longer than the command timeout
+    pub async fn replay_reorg(&self, index: usize) -> Result<(), Error> {
+        if !self.config.base_node.storage.track_reorgs {
+            println!(
+                "Reorg tracking is turned off. Add `track_reorgs = true` to the [base_node] section of your config to \
+                 turn it on."
+            );
+            return Ok(());
+        }
+        let reorgs = self.blockchain_db.inner().fetch_all_reorgs()?;
+        let reorg = index
+            .checked_sub(1)
+            .and_then(|i| reorgs.get(i))
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no reorg #{}, {} reorg(s) have been recorded",
+                    index,
+                    reorgs.len()
+                )
+            })?;
+
+        let db = self.blockchain_db.inner().clone();
+        let replay = ReplayDatabase {
+            path: self.config.base_node.data_dir.join("reorg_replay"),
+            lmdb: self.config.base_node.lmdb.clone(),
+            rules: self.consensus_rules.clone(),
+            max_randomx_vms: self.config.base_node.max_randomx_vms,
+            bypass_range_proof_verification: self.config.base_node.bypass_range_proof_verification,
+        };
+        println!(
+            "Replaying reorg #{} from #{} to #{} in the background",
+            index, reorg.prev_height, reorg.new_height
+        );
+        task::spawn(async move {
+            match replay_recorded_reorg(db, reorg, replay).await {
+                Ok(report) => println!("Replay of reorg #{}\n{}", index, report),
+                Err(err) => {
+                    error!(target: LOG_TARGET, "Replay of reorg #{} failed: {}", index, err);
+                    println!("Replay of reorg #{} failed: {}", index, err);
+                },
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Where and how the database a reorg is replayed against is created
+struct ReplayDatabase {
+    path: PathBuf,
+    lmdb: LMDBConfig,
+    rules: ConsensusManager,
+    max_randomx_vms: usize,
+    bypass_range_proof_verification: bool,
+}
+
+impl ReplayDatabase {
+    fn open(&self) -> Result<BlockchainDatabase<LMDBDatabase>, Error> {
+        let backend = open_empty_lmdb_database(&self.path, self.lmdb.clone())?;
+        let validators = Validators::new(
+            BodyOnlyValidator::new(self.rules.clone()),
+            HeaderValidator::new(self.rules.clone()),
+            OrphanBlockValidator::new(
+                self.rules.clone(),
+                self.bypass_range_proof_verification,
+                CryptoFactories::default(),
+            ),
+        );
+        let db = BlockchainDatabase::new(
+            backend,
+            self.rules.clone(),
+            validators,
+            BlockchainDatabaseConfig::default(),
+            DifficultyCalculator::new(self.rules.clone(), RandomXFactory::new(self.max_randomx_vms)),
+        )?;
+        Ok(db)
+    }
+
+    fn remove(&self) {
+        if let Err(err) = fs::remove_dir_all(&self.path) {
+            warn!(
+                target: LOG_TARGET,
+                "Could not remove the reorg replay database at {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Opens the LMDB database at `path`, recreating it if a replay that did not finish left blocks behind. Opening fails
+/// while another replay holds the database lock.
+fn open_empty_lmdb_database(path: &Path, config: LMDBConfig) -> Result<LMDBDatabase, Error> {
+    let backend = create_lmdb_database(path, config.clone())?;
+    if backend.is_empty()? {
+        return Ok(backend);
+    }
+    warn!(
+        target: LOG_TARGET,
+        "Removing the reorg replay database left behind at {}",
+        path.display()
+    );
+    drop(backend);
+    fs::remove_dir_all(path)?;
+    Ok(create_lmdb_database(path, config)?)
+}
+
+async fn replay_recorded_reorg(
+    db: BlockchainDatabase<Box<dyn BlockchainBackend>>,
+    reorg: Reorg,
+    replay: ReplayDatabase,
+) -> Result<ReorgReplayReport, Error> {
+    let source = db.clone();
+    let scenario = task::spawn_blocking(move || ReorgScenario::from_recorded_reorg(&source, &reorg)).await??;
+    let replay_db = replay.open()?;
+    let result = replay_reorg(&scenario, &db, replay_db).await;
+    replay.remove();
+    Ok(result?)
+}
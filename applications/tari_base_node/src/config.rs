@@ -37,7 +37,7 @@ use tari_common::{
 use tari_comms::multiaddr::Multiaddr;
 use tari_core::{
     base_node::BaseNodeStateMachineConfig,
-    chain_storage::BlockchainDatabaseConfig,
+    chain_storage::{BlockchainDatabaseConfig, SledConfig},
     mempool::MempoolConfig,
 };
 use tari_p2p::{auto_update::AutoUpdateConfig, P2pConfig, PeerSeedsConfig};
@@ -100,6 +100,10 @@ pub struct BaseNodeConfig {
     pub data_dir: PathBuf,
     /// The relative path to store the lmbd data
     pub lmdb_path: PathBuf,
+    /// The sled config settings
+    pub sled: SledConfig,
+    /// The relative path to store the sled data
+    pub sled_path: PathBuf,
     /// The maximum amount of VMs that RandomX will be use
     // TODO: This is a potential conflict with 'BaseNodeStateMachineConfig::max_randomx_vms'
     pub max_randomx_vms: usize,
@@ -153,6 +157,8 @@ impl Default for BaseNodeConfig {
             lmdb: Default::default(),
             data_dir: PathBuf::from("data/base_node"),
             lmdb_path: PathBuf::from("db"),
+            sled: Default::default(),
+            sled_path: PathBuf::from("sled_db"),
             max_randomx_vms: 5,
             bypass_range_proof_verification: false,
             force_sync_peers: StringList::default(),
@@ -189,6 +195,9 @@ impl BaseNodeConfig {
         if !self.lmdb_path.is_absolute() {
             self.lmdb_path = self.data_dir.join(self.lmdb_path.as_path());
         }
+        if !self.sled_path.is_absolute() {
+            self.sled_path = self.data_dir.join(self.sled_path.as_path());
+        }
         self.p2p.set_base_path(base_path);
     }
 }
@@ -197,4 +206,5 @@ impl BaseNodeConfig {
 #[serde(rename_all = "snake_case")]
 pub enum DatabaseType {
     Lmdb,
+    Sled,
 }
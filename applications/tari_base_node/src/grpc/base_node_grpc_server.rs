@@ -33,7 +33,7 @@ use tari_app_grpc::{
     tari_rpc::{CalcType, Sorting},
 };
 use tari_app_utilities::consts;
-use tari_common_types::types::{Commitment, Signature};
+use tari_common_types::types::{Commitment, FixedHash, PublicKey, Signature};
 use tari_comms::{Bytes, CommsNode};
 use tari_core::{
     base_node::{
@@ -43,7 +43,7 @@ use tari_core::{
         StateMachineHandle,
     },
     blocks::{Block, BlockHeader, NewBlockTemplate},
-    chain_storage::ChainStorageError,
+    chain_storage::{ChainStorageError, ScriptIndexKey},
     consensus::{emission::Emission, ConsensusDecoding, ConsensusEncoding, ConsensusManager, NetworkConsensus},
     iterators::NonOverlappingIntegerPairIter,
     mempool::{service::LocalMempoolService, TxStorageResponse},
@@ -52,13 +52,14 @@ use tari_core::{
 };
 use tari_p2p::{auto_update::SoftwareUpdaterHandle, services::liveness::LivenessHandle};
 use tari_utilities::{hex::Hex, message_format::MessageFormat, ByteArray};
-use tokio::task;
-use tonic::{Request, Response, Status};
+use tokio::{sync::broadcast::error::RecvError, task};
+use tonic::{Request, Response, Status, Streaming};
 
 use crate::{
     builder::BaseNodeContext,
     grpc::{
         blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        chain_events::ChainEventFilter,
         hash_rate::HashRateMovingAverage,
         helpers::{mean, median},
     },
@@ -72,6 +73,13 @@ const GET_TOKENS_IN_CIRCULATION_PAGE_SIZE: usize = 1_000;
 // number here to keep the node busy
 const GET_DIFFICULTY_MAX_HEIGHTS: u64 = 10_000;
 const GET_DIFFICULTY_PAGE_SIZE: usize = 1_000;
+// The maximum number of blocks for which UTXO stats can be requested at a time
+const GET_UTXO_STATS_MAX_HEIGHTS: u64 = 100_000;
+const GET_UTXO_STATS_PAGE_SIZE: usize = 1_000;
+// The number of outputs to fetch from the script index at a time
+const SEARCH_OUTPUTS_BY_SCRIPT_PAGE_SIZE: usize = 1_000;
+// The number of chain events that can be buffered for a subscriber before block events are no longer processed for it
+const CHAIN_EVENTS_BUFFER_SIZE: usize = 1_000;
 // The maximum number of headers a client can request at a time. If the client requests more than
 // this, this is the maximum that will be returned.
 const LIST_HEADERS_MAX_NUM_HEADERS: u64 = 10_000;
@@ -139,9 +147,12 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type GetNetworkDifficultyStream = mpsc::Receiver<Result<tari_rpc::NetworkDifficultyResponse, Status>>;
     type GetPeersStream = mpsc::Receiver<Result<tari_rpc::GetPeersResponse, Status>>;
     type GetTokensInCirculationStream = mpsc::Receiver<Result<tari_rpc::ValueAtHeightResponse, Status>>;
+    type GetUtxoStatsStream = mpsc::Receiver<Result<tari_rpc::UtxoStatsResponse, Status>>;
     type ListHeadersStream = mpsc::Receiver<Result<tari_rpc::BlockHeaderResponse, Status>>;
     type SearchKernelsStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
+    type SearchOutputsByScriptStream = mpsc::Receiver<Result<tari_rpc::SearchOutputsByScriptResponse, Status>>;
     type SearchUtxosStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
+    type SubscribeChainEventsStream = mpsc::Receiver<Result<tari_rpc::ChainEvent, Status>>;
 
     async fn get_network_difficulty(
         &self,
@@ -244,6 +255,70 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(rx))
     }
 
+    async fn get_utxo_stats(
+        &self,
+        request: Request<tari_rpc::HeightRequest>,
+    ) -> Result<Response<Self::GetUtxoStatsStream>, Status> {
+        let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for GetUtxoStats: from_tip: {:?} start_height: {:?} end_height: {:?}",
+            request.from_tip,
+            request.start_height,
+            request.end_height
+        );
+        let mut handler = self.node_service.clone();
+        let (start_height, end_height) = get_heights(&request, handler.clone()).await?;
+        // Overflow safety: checked in get_heights
+        let num_requested = end_height - start_height;
+        if num_requested > GET_UTXO_STATS_MAX_HEIGHTS {
+            return Err(report_error(
+                report_error_flag,
+                Status::invalid_argument(format!(
+                    "Number of blocks requested exceeds maximum. Expected less than {} but got {}",
+                    GET_UTXO_STATS_MAX_HEIGHTS, num_requested
+                )),
+            ));
+        }
+        let (mut tx, rx) = mpsc::channel(cmp::min(num_requested as usize + 1, GET_UTXO_STATS_PAGE_SIZE));
+
+        task::spawn(async move {
+            let page_iter = NonOverlappingIntegerPairIter::new(start_height, end_height + 1, GET_UTXO_STATS_PAGE_SIZE);
+            for (start, end) in page_iter {
+                let stats = match handler.get_utxo_stats(start..=end).await {
+                    Ok(stats) => stats,
+                    Err(CommsInterfaceError::ChainStorageError(ChainStorageError::InvalidOperation(msg))) => {
+                        let _ = tx
+                            .send(Err(report_error(report_error_flag, Status::failed_precondition(msg))))
+                            .await;
+                        return;
+                    },
+                    Err(err) => {
+                        warn!(target: LOG_TARGET, "Base node service error: {:?}", err);
+                        let _ = tx
+                            .send(Err(report_error(
+                                report_error_flag,
+                                Status::internal("Internal error when fetching UTXO stats"),
+                            )))
+                            .await;
+                        return;
+                    },
+                };
+
+                for stats in stats {
+                    if let Err(err) = tx.send(Ok(stats.into())).await {
+                        warn!(target: LOG_TARGET, "Error sending UTXO stats via GRPC:  {}", err);
+                        return;
+                    }
+                }
+            }
+        });
+
+        debug!(target: LOG_TARGET, "Sending GetUtxoStats response stream to client");
+        Ok(Response::new(rx))
+    }
+
     async fn get_mempool_transactions(
         &self,
         request: Request<tari_rpc::GetMempoolTransactionsRequest>,
@@ -750,6 +825,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             TxStorageResponse::NotStored |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredConsensus |
+            TxStorageResponse::NotStoredReplacementRejected |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
                 result: tari_rpc::SubmitTransactionResult::Rejected.into(),
             },
@@ -791,14 +867,14 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         let mut mem_handler = self.mempool_service.clone();
 
         let base_node_response = node_handler
-            .get_kernel_by_excess_sig(excess_sig.clone())
+            .get_header_by_kernel_excess_sig(excess_sig.clone())
             .await
             .map_err(|e| {
                 error!(target: LOG_TARGET, "Error submitting query:{}", e);
                 report_error(report_error_flag, Status::internal(e.to_string()))
             })?;
 
-        if !base_node_response.is_empty() {
+        if base_node_response.is_some() {
             let response = tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::Mined.into(),
             };
@@ -831,6 +907,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             TxStorageResponse::NotStored |
             TxStorageResponse::NotStoredConsensus |
             TxStorageResponse::NotStoredOrphan |
+            TxStorageResponse::NotStoredReplacementRejected |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::NotStored.into(),
             },
@@ -1201,6 +1278,166 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(rx))
     }
 
+    async fn search_outputs_by_script(
+        &self,
+        request: Request<tari_rpc::SearchOutputsByScriptRequest>,
+    ) -> Result<Response<Self::SearchOutputsByScriptStream>, Status> {
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SearchOutputsByScript");
+        let request = request.into_inner();
+
+        let key = match (
+            request.script_hash.is_empty(),
+            request.sender_offset_public_key.is_empty(),
+        ) {
+            (false, true) => FixedHash::try_from(request.script_hash.as_slice())
+                .map(ScriptIndexKey::ScriptHash)
+                .map_err(|_| report_error(report_error_flag, Status::invalid_argument("Malformed script hash")))?,
+            (true, false) => PublicKey::from_bytes(&request.sender_offset_public_key)
+                .map(ScriptIndexKey::SenderOffsetPublicKey)
+                .map_err(|_| {
+                    report_error(
+                        report_error_flag,
+                        Status::invalid_argument("Malformed sender offset public key"),
+                    )
+                })?,
+            _ => {
+                return Err(report_error(
+                    report_error_flag,
+                    Status::invalid_argument("Exactly one of script_hash and sender_offset_public_key must be set"),
+                ))
+            },
+        };
+
+        let mut handler = self.node_service.clone();
+        let tip_height = handler
+            .get_metadata()
+            .await
+            .map_err(|err| report_error(report_error_flag, Status::internal(err.to_string())))?
+            .height_of_longest_chain();
+        let end_height = if request.end_height == 0 {
+            tip_height
+        } else {
+            cmp::min(request.end_height, tip_height)
+        };
+
+        let (mut tx, rx) = mpsc::channel(SEARCH_OUTPUTS_BY_SCRIPT_PAGE_SIZE);
+        task::spawn(async move {
+            let mut start_height = request.start_height;
+            while start_height <= end_height {
+                let outputs = match handler
+                    .get_outputs_by_script_index(
+                        key.clone(),
+                        start_height,
+                        end_height,
+                        SEARCH_OUTPUTS_BY_SCRIPT_PAGE_SIZE,
+                    )
+                    .await
+                {
+                    Ok(outputs) => outputs,
+                    Err(CommsInterfaceError::ChainStorageError(ChainStorageError::InvalidOperation(msg))) => {
+                        let _ = tx
+                            .send(Err(report_error(report_error_flag, Status::failed_precondition(msg))))
+                            .await;
+                        return;
+                    },
+                    Err(err) => {
+                        warn!(target: LOG_TARGET, "Base node service error: {:?}", err);
+                        let _ = tx
+                            .send(Err(report_error(
+                                report_error_flag,
+                                Status::internal("Internal error when searching the script index"),
+                            )))
+                            .await;
+                        return;
+                    },
+                };
+                // A page always ends at a block boundary, so a short page means there are no more matching outputs
+                start_height = match outputs.last() {
+                    Some(last) if outputs.len() >= SEARCH_OUTPUTS_BY_SCRIPT_PAGE_SIZE => last.mined_height + 1,
+                    _ => end_height + 1,
+                };
+
+                for output in outputs {
+                    if let Err(err) = tx.send(Ok(output.into())).await {
+                        warn!(target: LOG_TARGET, "Error sending output via GRPC:  {}", err);
+                        return;
+                    }
+                }
+            }
+        });
+
+        debug!(
+            target: LOG_TARGET,
+            "Sending SearchOutputsByScript response stream to client"
+        );
+        Ok(Response::new(rx))
+    }
+
+    async fn subscribe_chain_events(
+        &self,
+        request: Request<Streaming<tari_rpc::SubscribeChainEventsRequest>>,
+    ) -> Result<Response<Self::SubscribeChainEventsStream>, Status> {
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SubscribeChainEvents");
+        let mut requests = request.into_inner();
+        let mut block_events = self.node_service.get_block_event_stream();
+
+        let (mut tx, rx) = mpsc::channel(CHAIN_EVENTS_BUFFER_SIZE);
+        task::spawn(async move {
+            let mut filter = ChainEventFilter::default();
+            let mut requests_open = true;
+            loop {
+                let events = tokio::select! {
+                    request = requests.message(), if requests_open => {
+                        match request {
+                            Ok(Some(request)) => {
+                                if let Err(status) = filter.update(request) {
+                                    let _ = tx.send(Err(report_error(report_error_flag, status))).await;
+                                    return;
+                                }
+                            },
+                            // The client has finished updating its watch lists but still wants events
+                            Ok(None) => requests_open = false,
+                            Err(status) => {
+                                debug!(
+                                    target: LOG_TARGET,
+                                    "Chain event subscription request stream failed: {}", status
+                                );
+                                return;
+                            },
+                        }
+                        continue;
+                    },
+                    event = block_events.recv() => match event {
+                        Ok(event) => filter.process(&event),
+                        Err(RecvError::Lagged(num_skipped)) => {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Chain event subscriber fell behind and missed {} block event(s)", num_skipped
+                            );
+                            vec![ChainEventFilter::lagged(num_skipped)]
+                        },
+                        Err(RecvError::Closed) => return,
+                    },
+                };
+
+                for event in events {
+                    if let Err(err) = tx.send(Ok(event)).await {
+                        debug!(target: LOG_TARGET, "Chain event subscriber disconnected: {}", err);
+                        return;
+                    }
+                }
+            }
+        });
+
+        debug!(
+            target: LOG_TARGET,
+            "Sending SubscribeChainEvents response stream to client"
+        );
+        Ok(Response::new(rx))
+    }
+
     async fn get_block_timing(
         &self,
         request: Request<tari_rpc::HeightRequest>,
@@ -1572,6 +1809,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             unconfirmed_txs: mempool_stats.unconfirmed_txs as u64,
             reorg_txs: mempool_stats.reorg_txs as u64,
             unconfirmed_weight: mempool_stats.unconfirmed_weight,
+            replaced_txs: mempool_stats.replaced_txs,
         };
 
         Ok(Response::new(response))
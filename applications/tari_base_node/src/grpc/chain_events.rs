@@ -0,0 +1,416 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
+
+use tari_app_grpc::tari_rpc::{self, chain_event::Event};
+use tari_common_types::types::{Commitment, HashOutput, Signature};
+use tari_core::{base_node::comms_interface::BlockEvent, blocks::ChainBlock, chain_storage::BlockAddResult};
+use tari_utilities::ByteArray;
+use tonic::Status;
+
+/// The maximum number of kernels and output commitments that a single subscription can watch
+const MAX_WATCHED_ITEMS: usize = 100_000;
+
+/// The kernels and outputs watched by a chain event subscription. Block events are turned into the chain events that
+/// are sent to the subscriber.
+#[derive(Debug, Default)]
+pub struct ChainEventFilter {
+    kernels: HashSet<Vec<u8>>,
+    commitments: HashSet<Vec<u8>>,
+    /// The commitments of watched outputs that have been mined, by output hash. Spends of these outputs by compact
+    /// inputs, which do not include the commitment, are matched using this.
+    mined_outputs: HashMap<HashOutput, Commitment>,
+}
+
+impl ChainEventFilter {
+    /// Applies a watch list update from the subscriber
+    pub fn update(&mut self, request: tari_rpc::SubscribeChainEventsRequest) -> Result<(), Status> {
+        for sig in request.unwatch_kernel_excess_sigs {
+            self.kernels.remove(&kernel_key(&parse_signature(sig)?));
+        }
+        for commitment in request.unwatch_output_commitments {
+            self.mined_outputs.retain(|_, c| c.as_bytes() != commitment.as_slice());
+            self.commitments.remove(&commitment);
+        }
+        for sig in request.watch_kernel_excess_sigs {
+            self.kernels.insert(kernel_key(&parse_signature(sig)?));
+        }
+        for commitment in request.watch_output_commitments {
+            Commitment::from_bytes(&commitment).map_err(|_| Status::invalid_argument("Malformed output commitment"))?;
+            self.commitments.insert(commitment);
+        }
+
+        if self.kernels.len() + self.commitments.len() > MAX_WATCHED_ITEMS {
+            return Err(Status::resource_exhausted(format!(
+                "A subscription can watch at most {} kernels and outputs",
+                MAX_WATCHED_ITEMS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the chain events for a block event, in the order that they should be sent
+    pub fn process(&mut self, event: &BlockEvent) -> Vec<tari_rpc::ChainEvent> {
+        let mut events = Vec::new();
+        match event {
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::Ok(block)) => {
+                self.block_added(block, &mut events);
+                events.push(chain_event(Event::NewTip(height_and_hash(block))));
+            },
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }) => {
+                self.blocks_removed(removed, &mut events);
+                for block in added {
+                    self.block_added(block, &mut events);
+                }
+                if let Some(tip) = added.last() {
+                    events.push(chain_event(Event::NewTip(height_and_hash(tip))));
+                }
+            },
+            BlockEvent::BlockSyncRewind(removed) => self.blocks_removed(removed, &mut events),
+            // Block sync announces each block as it is added, so the tip of a completed sync has already been sent
+            BlockEvent::ValidBlockAdded(..) |
+            BlockEvent::BlockSyncComplete(_) |
+            BlockEvent::AddBlockValidationFailed { .. } |
+            BlockEvent::AddBlockErrored { .. } => {},
+        }
+        events
+    }
+
+    /// Returns the event sent when the subscription falls behind and `num_skipped` block events are dropped
+    pub fn lagged(num_skipped: u64) -> tari_rpc::ChainEvent {
+        chain_event(Event::EventsLagged(num_skipped))
+    }
+
+    fn block_added(&mut self, block: &ChainBlock, events: &mut Vec<tari_rpc::ChainEvent>) {
+        let (kernels, outputs) = self.matches(block);
+        events.extend(kernels.into_iter().map(|m| chain_event(Event::KernelMined(m))));
+        events.extend(outputs.into_iter().map(|m| chain_event(Event::OutputMined(m))));
+    }
+
+    fn blocks_removed(&mut self, removed: &[Arc<ChainBlock>], events: &mut Vec<tari_rpc::ChainEvent>) {
+        let fork_block = match removed.last() {
+            Some(block) => tari_rpc::BlockHeightAndHash {
+                height: block.height().saturating_sub(1),
+                hash: block.header().prev_hash.to_vec(),
+            },
+            None => return,
+        };
+        events.push(chain_event(Event::Reorg(tari_rpc::ChainReorgEvent {
+            removed_blocks: removed.iter().map(|b| height_and_hash(b)).collect(),
+            fork_block: Some(fork_block),
+        })));
+        for block in removed {
+            let (kernels, outputs) = self.matches(block);
+            events.extend(kernels.into_iter().map(|m| chain_event(Event::KernelRewound(m))));
+            events.extend(outputs.into_iter().map(|m| chain_event(Event::OutputRewound(m))));
+        }
+    }
+
+    fn matches(&mut self, block: &ChainBlock) -> (Vec<tari_rpc::KernelMatch>, Vec<tari_rpc::OutputMatch>) {
+        let body = &block.block().body;
+        let kernels = body
+            .kernels()
+            .iter()
+            .filter(|kernel| self.kernels.contains(&kernel_key(&kernel.excess_sig)))
+            .map(|kernel| tari_rpc::KernelMatch {
+                excess_sig: Some(kernel.excess_sig.clone().into()),
+                excess: kernel.excess.to_vec(),
+                height: block.height(),
+                block_hash: block.hash().to_vec(),
+            })
+            .collect();
+
+        let mut outputs = Vec::new();
+        for output in body.outputs() {
+            if self.commitments.contains(output.commitment.as_bytes()) {
+                let output_hash = output.hash();
+                self.mined_outputs.insert(output_hash, output.commitment.clone());
+                outputs.push(output_match(block, &output.commitment, output_hash, false));
+            }
+        }
+        for input in body.inputs() {
+            let output_hash = input.output_hash();
+            let commitment = match input.commitment() {
+                Ok(commitment) if self.commitments.contains(commitment.as_bytes()) => Some(commitment),
+                Ok(_) => None,
+                Err(_) => self.mined_outputs.get(&output_hash),
+            };
+            if let Some(commitment) = commitment {
+                outputs.push(output_match(block, commitment, output_hash, true));
+            }
+        }
+        (kernels, outputs)
+    }
+}
+
+fn parse_signature(sig: tari_rpc::Signature) -> Result<Signature, Status> {
+    Signature::try_from(sig).map_err(|_| Status::invalid_argument("Malformed kernel excess signature"))
+}
+
+fn kernel_key(sig: &Signature) -> Vec<u8> {
+    let mut key = sig.get_public_nonce().to_vec();
+    key.extend_from_slice(sig.get_signature().as_bytes());
+    key
+}
+
+fn chain_event(event: Event) -> tari_rpc::ChainEvent {
+    tari_rpc::ChainEvent { event: Some(event) }
+}
+
+fn height_and_hash(block: &ChainBlock) -> tari_rpc::BlockHeightAndHash {
+    tari_rpc::BlockHeightAndHash {
+        height: block.height(),
+        hash: block.hash().to_vec(),
+    }
+}
+
+fn output_match(
+    block: &ChainBlock,
+    commitment: &Commitment,
+    output_hash: HashOutput,
+    spent: bool,
+) -> tari_rpc::OutputMatch {
+    tari_rpc::OutputMatch {
+        commitment: commitment.to_vec(),
+        output_hash: output_hash.to_vec(),
+        spent,
+        height: block.height(),
+        block_hash: block.hash().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::{
+        blocks::{Block, BlockHeader, BlockHeaderAccumulatedData},
+        transactions::{
+            aggregated_body::AggregateBody,
+            tari_amount::MicroTari,
+            test_helpers::{create_test_kernel, TestParams, UtxoTestParams},
+            transaction_components::{KernelFeatures, TransactionInput, TransactionKernel, TransactionOutput},
+            CryptoFactories,
+        },
+    };
+
+    use super::*;
+
+    fn chain_block(prev: Option<&ChainBlock>, body: AggregateBody) -> Arc<ChainBlock> {
+        let mut header = BlockHeader::new(0);
+        if let Some(prev) = prev {
+            header.height = prev.height() + 1;
+            header.prev_hash = *prev.hash();
+        }
+        let block = Block::new(header, body);
+        let accumulated_data = BlockHeaderAccumulatedData {
+            hash: block.hash(),
+            ..Default::default()
+        };
+        Arc::new(ChainBlock::try_construct(Arc::new(block), accumulated_data).unwrap())
+    }
+
+    fn block_added(block: &Arc<ChainBlock>) -> BlockEvent {
+        BlockEvent::ValidBlockAdded(block.to_arc_block(), BlockAddResult::Ok(block.clone()))
+    }
+
+    /// Returns an output together with an input that spends it
+    fn create_output() -> (TransactionOutput, TransactionInput) {
+        let (input, unblinded) = TestParams::new().create_input(UtxoTestParams::with_value(MicroTari(100)));
+        let output = unblinded.as_transaction_output(&CryptoFactories::default()).unwrap();
+        (output, input)
+    }
+
+    fn create_kernel() -> TransactionKernel {
+        create_test_kernel(MicroTari(5), 0, KernelFeatures::default())
+    }
+
+    fn watching(kernels: &[&TransactionKernel], outputs: &[&TransactionOutput]) -> ChainEventFilter {
+        let mut filter = ChainEventFilter::default();
+        filter
+            .update(tari_rpc::SubscribeChainEventsRequest {
+                watch_kernel_excess_sigs: kernels.iter().map(|k| k.excess_sig.clone().into()).collect(),
+                watch_output_commitments: outputs.iter().map(|o| o.commitment.to_vec()).collect(),
+                ..Default::default()
+            })
+            .unwrap();
+        filter
+    }
+
+    fn kernel_match(kernel: &TransactionKernel, block: &ChainBlock) -> tari_rpc::KernelMatch {
+        tari_rpc::KernelMatch {
+            excess_sig: Some(kernel.excess_sig.clone().into()),
+            excess: kernel.excess.to_vec(),
+            height: block.height(),
+            block_hash: block.hash().to_vec(),
+        }
+    }
+
+    #[test]
+    fn it_matches_watched_kernels_and_outputs_in_a_new_block() {
+        let kernel = create_kernel();
+        let (output, _) = create_output();
+        let (other_output, _) = create_output();
+        let mut filter = watching(&[&kernel], &[&output]);
+
+        let block = chain_block(
+            None,
+            AggregateBody::new(vec![], vec![output.clone(), other_output], vec![
+                kernel.clone(),
+                create_kernel(),
+            ]),
+        );
+        let events = filter.process(&block_added(&block));
+
+        assert_eq!(events, vec![
+            chain_event(Event::KernelMined(kernel_match(&kernel, &block))),
+            chain_event(Event::OutputMined(output_match(
+                &block,
+                &output.commitment,
+                output.hash(),
+                false
+            ))),
+            chain_event(Event::NewTip(height_and_hash(&block))),
+        ]);
+    }
+
+    #[test]
+    fn it_matches_spends_of_mined_outputs_by_compact_inputs() {
+        let (output, input) = create_output();
+        let (unmined_output, unmined_input) = create_output();
+        let mut filter = watching(&[], &[&output, &unmined_output]);
+
+        let block1 = chain_block(None, AggregateBody::new(vec![], vec![output.clone()], vec![]));
+        filter.process(&block_added(&block1));
+        // The commitment of an output that was not seen being mined cannot be recovered from a compact input
+        let block2 = chain_block(
+            Some(&block1),
+            AggregateBody::new(vec![input.to_compact(), unmined_input.to_compact()], vec![], vec![]),
+        );
+        let events = filter.process(&block_added(&block2));
+
+        assert_eq!(events, vec![
+            chain_event(Event::OutputMined(output_match(
+                &block2,
+                &output.commitment,
+                output.hash(),
+                true
+            ))),
+            chain_event(Event::NewTip(height_and_hash(&block2))),
+        ]);
+    }
+
+    #[test]
+    fn it_sends_the_reorg_before_rewound_matches_and_the_new_tip() {
+        let kernel = create_kernel();
+        let (output, _) = create_output();
+        let mut filter = watching(&[&kernel], &[&output]);
+
+        let genesis = chain_block(None, AggregateBody::empty());
+        let block1 = chain_block(
+            Some(&genesis),
+            AggregateBody::new(vec![], vec![output.clone()], vec![kernel.clone()]),
+        );
+        filter.process(&block_added(&block1));
+
+        // The kernel is mined again in the second block of the new chain
+        let block1b = chain_block(
+            Some(&genesis),
+            AggregateBody::new(vec![], vec![], vec![create_kernel()]),
+        );
+        let block2b = chain_block(Some(&block1b), AggregateBody::new(vec![], vec![], vec![kernel.clone()]));
+        let event = BlockEvent::ValidBlockAdded(block2b.to_arc_block(), BlockAddResult::ChainReorg {
+            added: vec![block1b, block2b.clone()],
+            removed: vec![block1.clone()],
+        });
+        let events = filter.process(&event);
+
+        assert_eq!(events, vec![
+            chain_event(Event::Reorg(tari_rpc::ChainReorgEvent {
+                removed_blocks: vec![height_and_hash(&block1)],
+                fork_block: Some(height_and_hash(&genesis)),
+            })),
+            chain_event(Event::KernelRewound(kernel_match(&kernel, &block1))),
+            chain_event(Event::OutputRewound(output_match(
+                &block1,
+                &output.commitment,
+                output.hash(),
+                false
+            ))),
+            chain_event(Event::KernelMined(kernel_match(&kernel, &block2b))),
+            chain_event(Event::NewTip(height_and_hash(&block2b))),
+        ]);
+    }
+
+    #[test]
+    fn it_limits_the_number_of_watched_items() {
+        let (output, _) = create_output();
+        let mut filter = ChainEventFilter {
+            commitments: (0..MAX_WATCHED_ITEMS).map(|i| i.to_le_bytes().to_vec()).collect(),
+            ..Default::default()
+        };
+
+        let err = filter
+            .update(tari_rpc::SubscribeChainEventsRequest {
+                watch_output_commitments: vec![output.commitment.to_vec()],
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+
+        // Unwatching in the same update makes room
+        filter
+            .update(tari_rpc::SubscribeChainEventsRequest {
+                watch_output_commitments: vec![output.commitment.to_vec()],
+                unwatch_output_commitments: vec![0usize.to_le_bytes().to_vec(), 1usize.to_le_bytes().to_vec()],
+                ..Default::default()
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn it_stops_matching_unwatched_items() {
+        let kernel = create_kernel();
+        let (output, input) = create_output();
+        let mut filter = watching(&[&kernel], &[&output]);
+        let block1 = chain_block(None, AggregateBody::new(vec![], vec![output.clone()], vec![]));
+        filter.process(&block_added(&block1));
+
+        filter
+            .update(tari_rpc::SubscribeChainEventsRequest {
+                unwatch_kernel_excess_sigs: vec![kernel.excess_sig.clone().into()],
+                unwatch_output_commitments: vec![output.commitment.to_vec()],
+                ..Default::default()
+            })
+            .unwrap();
+        let block2 = chain_block(
+            Some(&block1),
+            AggregateBody::new(vec![input.to_compact()], vec![], vec![kernel]),
+        );
+        let events = filter.process(&block_added(&block2));
+
+        assert_eq!(events, vec![chain_event(Event::NewTip(height_and_hash(&block2)))]);
+    }
+}
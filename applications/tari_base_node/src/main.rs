@@ -83,6 +83,8 @@ mod grpc;
 #[cfg(feature = "metrics")]
 mod metrics;
 mod recovery;
+mod simulation;
+mod snapshot;
 mod utils;
 
 use std::{env, process, str::FromStr, sync::Arc};
@@ -105,6 +107,7 @@ use tari_comms::{
     utils::multiaddr::multiaddr_to_socketaddr,
     NodeIdentity,
 };
+use tari_core::consensus::ConsensusManager;
 #[cfg(all(unix, feature = "libtor"))]
 use tari_libtor::tor::Tor;
 use tari_shutdown::{Shutdown, ShutdownSignal};
@@ -150,8 +153,28 @@ fn main_inner() -> Result<(), ExitError> {
     if let Some(network) = &cli.network {
         config.base_node.network = Network::from_str(network)?;
     }
+    if let Some(network_file) = cli.common.network_file_path() {
+        let custom_network = ConsensusManager::load_custom_network(&network_file)
+            .map_err(|err| ExitError::new(ExitCode::ConfigError, err))?;
+        info!(
+            target: LOG_TARGET,
+            "Loaded network definition '{}' from {}",
+            custom_network.name(),
+            network_file.display()
+        );
+    } else if config.base_node.network == Network::Custom {
+        return Err(ExitError::new(
+            ExitCode::ConfigError,
+            "The custom network requires a network definition file (--network-file)",
+        ));
+    }
     debug!(target: LOG_TARGET, "Using base node configuration: {:?}", config);
 
+    if let Some(path) = cli.simulate_chain.as_ref() {
+        return simulation::run_simulation(config.base_node.network, path, cli.simulation_csv.as_deref())
+            .map_err(|e| ExitError::new(ExitCode::UnknownError, e));
+    }
+
     // Load or create the Node identity
     let node_identity = setup_node_identity(
         &config.base_node.identity_file,
@@ -226,6 +249,14 @@ async fn run_node(
         return Ok(());
     };
 
+    if let Some(path) = cli.import_snapshot.as_ref() {
+        info!(target: LOG_TARGET, "Importing chain snapshot from '{}'", path.display());
+        snapshot::run_snapshot_import(&config.base_node, path)
+            .await
+            .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?;
+        return Ok(());
+    }
+
     // Build, node, build!
     let ctx = builder::configure_and_initialize_node(config.clone(), node_identity, shutdown.to_signal()).await?;
 
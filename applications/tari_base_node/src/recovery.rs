@@ -39,6 +39,8 @@ use tari_core::{
         async_db::AsyncBlockchainDb,
         create_lmdb_database,
         create_recovery_lmdb_database,
+        create_recovery_sled_database,
+        create_sled_database,
         BlockchainBackend,
         BlockchainDatabase,
         BlockchainDatabaseConfig,
@@ -68,27 +70,47 @@ pub fn initiate_recover_db(config: &BaseNodeConfig) -> Result<(), ExitError> {
                 ExitError::new(ExitCode::UnknownError, err)
             })?;
         },
+        DatabaseType::Sled => {
+            create_recovery_sled_database(config.sled_path.as_path()).map_err(|err| {
+                error!(target: LOG_TARGET, "{}", err);
+                ExitError::new(ExitCode::UnknownError, err)
+            })?;
+        },
     };
     Ok(())
 }
 
 pub async fn run_recovery(node_config: &BaseNodeConfig) -> Result<(), anyhow::Error> {
     println!("Starting recovery mode");
-    let (temp_db, main_db, temp_path) = match &node_config.db_type {
-        DatabaseType::Lmdb => {
-            let backend = create_lmdb_database(&node_config.lmdb_path, node_config.lmdb.clone()).map_err(|e| {
-                error!(target: LOG_TARGET, "Error opening db: {}", e);
-                anyhow!("Could not open DB: {}", e)
-            })?;
-            let temp_path = temp_dir().join("temp_recovery");
+    let (temp_db, main_db, temp_path): (Box<dyn BlockchainBackend>, Box<dyn BlockchainBackend>, _) =
+        match &node_config.db_type {
+            DatabaseType::Lmdb => {
+                let backend = create_lmdb_database(&node_config.lmdb_path, node_config.lmdb.clone()).map_err(|e| {
+                    error!(target: LOG_TARGET, "Error opening db: {}", e);
+                    anyhow!("Could not open DB: {}", e)
+                })?;
+                let temp_path = temp_dir().join("temp_recovery");
 
-            let temp = create_lmdb_database(&temp_path, node_config.lmdb.clone()).map_err(|e| {
-                error!(target: LOG_TARGET, "Error opening recovery db: {}", e);
-                anyhow!("Could not open recovery DB: {}", e)
-            })?;
-            (temp, backend, temp_path)
-        },
-    };
+                let temp = create_lmdb_database(&temp_path, node_config.lmdb.clone()).map_err(|e| {
+                    error!(target: LOG_TARGET, "Error opening recovery db: {}", e);
+                    anyhow!("Could not open recovery DB: {}", e)
+                })?;
+                (Box::new(temp), Box::new(backend), temp_path)
+            },
+            DatabaseType::Sled => {
+                let backend = create_sled_database(&node_config.sled_path, node_config.sled).map_err(|e| {
+                    error!(target: LOG_TARGET, "Error opening db: {}", e);
+                    anyhow!("Could not open DB: {}", e)
+                })?;
+                let temp_path = node_config.sled_path.join("temp_recovery");
+
+                let temp = create_sled_database(&temp_path, node_config.sled).map_err(|e| {
+                    error!(target: LOG_TARGET, "Error opening recovery db: {}", e);
+                    anyhow!("Could not open recovery DB: {}", e)
+                })?;
+                (Box::new(temp), Box::new(backend), temp_path)
+            },
+        };
     let rules = ConsensusManager::builder(node_config.network).build();
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(node_config.max_randomx_vms);
@@ -0,0 +1,61 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::anyhow;
+use log::*;
+use tari_common::configuration::Network;
+use tari_core::{
+    consensus::ConsensusManager,
+    simulation::{ChainSimulator, SimulationConfig, SimulationReport},
+};
+
+pub const LOG_TARGET: &str = "base_node::app::simulation";
+
+/// Runs a chain simulation scenario under the consensus rules of `network` and prints the report. The simulated main
+/// chain is written to `csv_path` if given.
+pub fn run_simulation(network: Network, scenario_path: &Path, csv_path: Option<&Path>) -> Result<(), anyhow::Error> {
+    let config = SimulationConfig::load(scenario_path)
+        .map_err(|e| anyhow!("Could not load scenario '{}': {}", scenario_path.display(), e))?;
+    let rules = ConsensusManager::builder(network).build();
+    println!(
+        "Simulating {} blocks with {} miner(s) on {}",
+        config.num_blocks,
+        config.miners.len(),
+        network
+    );
+
+    let result = ChainSimulator::new(rules.clone(), config.clone())?.run().map_err(|e| {
+        error!(target: LOG_TARGET, "Chain simulation failed: {}", e);
+        anyhow!("Chain simulation failed: {}", e)
+    })?;
+
+    if let Some(path) = csv_path {
+        let file = File::create(path).map_err(|e| anyhow!("Could not create '{}': {}", path.display(), e))?;
+        result.write_csv(BufWriter::new(file))?;
+        println!("Simulated chain written to '{}'", path.display());
+    }
+    println!();
+    println!("{}", SimulationReport::new(&rules, &config, &result));
+    Ok(())
+}
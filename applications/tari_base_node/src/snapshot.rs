@@ -0,0 +1,97 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs::File, path::Path};
+
+use anyhow::anyhow;
+use log::*;
+use tari_core::{
+    chain_storage::{snapshot::import_snapshot, BlockchainDatabase, Validators},
+    consensus::ConsensusManager,
+    proof_of_work::randomx_factory::RandomXFactory,
+    transactions::CryptoFactories,
+    validation::{
+        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        header_validator::HeaderValidator,
+        ChainBalanceValidator,
+        DifficultyCalculator,
+    },
+};
+use tari_utilities::hex::Hex;
+use tokio::task;
+
+use crate::{builder::create_backend, config::BaseNodeConfig};
+
+pub const LOG_TARGET: &str = "base_node::app::snapshot";
+
+/// Imports a chain snapshot into the configured database. The database must not contain any blocks other than the
+/// genesis block.
+pub async fn run_snapshot_import(node_config: &BaseNodeConfig, path: &Path) -> Result<(), anyhow::Error> {
+    println!("Importing chain snapshot from '{}'", path.display());
+    let file = File::open(path).map_err(|e| anyhow!("Could not open snapshot '{}': {}", path.display(), e))?;
+    let backend = create_backend(node_config)?;
+    let rules = ConsensusManager::builder(node_config.network).build();
+    let factories = CryptoFactories::default();
+    let randomx_factory = RandomXFactory::new(node_config.max_randomx_vms);
+    let validators = Validators::new(
+        BodyOnlyValidator::new(rules.clone()),
+        HeaderValidator::new(rules.clone()),
+        OrphanBlockValidator::new(
+            rules.clone(),
+            node_config.bypass_range_proof_verification,
+            factories.clone(),
+        ),
+    );
+    let db = BlockchainDatabase::new(
+        backend,
+        rules.clone(),
+        validators,
+        node_config.storage,
+        DifficultyCalculator::new(rules.clone(), randomx_factory),
+    )?;
+
+    let summary = task::spawn_blocking(move || {
+        let validator = ChainBalanceValidator::new(rules, factories.clone());
+        import_snapshot(&db, file, &validator, &factories)
+    })
+    .await?
+    .map_err(|e| {
+        error!(target: LOG_TARGET, "Snapshot import failed: {}", e);
+        anyhow!("Could not import snapshot: {}", e)
+    })?;
+
+    info!(
+        target: LOG_TARGET,
+        "Imported snapshot at height {} ({})",
+        summary.height,
+        summary.best_block.to_hex()
+    );
+    println!(
+        "Imported snapshot at height {} (best block {}). {} kernel(s), {} unspent output(s), {} pruned output(s).",
+        summary.height,
+        summary.best_block.to_hex(),
+        summary.num_kernels,
+        summary.num_unspent_outputs,
+        summary.num_pruned_outputs
+    );
+    Ok(())
+}
@@ -38,11 +38,12 @@ use opentelemetry::{self, global, KeyValue};
 use recovery::prompt_private_key_from_seed_words;
 use tari_app_utilities::consts;
 use tari_common::{
-    configuration::bootstrap::ApplicationType,
+    configuration::{bootstrap::ApplicationType, Network},
     exit_codes::{ExitCode, ExitError},
     initialize_logging,
     load_configuration,
 };
+use tari_core::consensus::ConsensusManager;
 use tari_key_manager::cipher_seed::CipherSeed;
 #[cfg(all(unix, feature = "libtor"))]
 use tari_libtor::tor::Tor;
@@ -101,6 +102,21 @@ fn main_inner() -> Result<(), ExitError> {
 
     #[cfg_attr(not(all(unix, feature = "libtor")), allow(unused_mut))]
     let mut config = ApplicationConfig::load_from(&cfg)?;
+    if let Some(network_file) = cli.common.network_file_path() {
+        let custom_network = ConsensusManager::load_custom_network(&network_file)
+            .map_err(|err| ExitError::new(ExitCode::ConfigError, err))?;
+        info!(
+            target: LOG_TARGET,
+            "Loaded network definition '{}' from {}",
+            custom_network.name(),
+            network_file.display()
+        );
+    } else if config.wallet.network == Network::Custom {
+        return Err(ExitError::new(
+            ExitCode::ConfigError,
+            "The custom network requires a network definition file (--network-file)",
+        ));
+    }
 
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
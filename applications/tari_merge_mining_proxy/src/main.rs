@@ -47,7 +47,7 @@ use tari_app_grpc::{authentication::ClientAuthenticationInterceptor, tari_rpc as
 use tari_app_utilities::consts;
 use tari_common::{initialize_logging, load_configuration, DefaultConfigLoader};
 use tari_comms::utils::multiaddr::multiaddr_to_socketaddr;
-use tari_core::proof_of_work::randomx_factory::RandomXFactory;
+use tari_core::{consensus::ConsensusManager, proof_of_work::randomx_factory::RandomXFactory};
 use tokio::time::Duration;
 use tonic::{
     codegen::InterceptedService,
@@ -97,6 +97,15 @@ async fn main() -> Result<(), anyhow::Error> {
     )?;
 
     let config = MergeMiningProxyConfig::load_from(&cfg)?;
+    if let Some(network_file) = cli.common.network_file_path() {
+        let custom_network = ConsensusManager::load_custom_network(&network_file)?;
+        info!(
+            target: LOG_TARGET,
+            "Loaded network definition '{}' from {}",
+            custom_network.name(),
+            network_file.display()
+        );
+    }
 
     info!(target: LOG_TARGET, "Configuration: {:?}", config);
     let client = reqwest::Client::builder()
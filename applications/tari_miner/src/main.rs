@@ -46,7 +46,7 @@ use tari_common::{
     DefaultConfigLoader,
 };
 use tari_comms::utils::multiaddr::multiaddr_to_socketaddr;
-use tari_core::blocks::BlockHeader;
+use tari_core::{blocks::BlockHeader, consensus::ConsensusManager};
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_utilities::hex::Hex;
 use tokio::{runtime::Runtime, time::sleep};
@@ -100,6 +100,16 @@ async fn main_inner() -> Result<(), ExitError> {
         include_str!("../log4rs_sample.yml"),
     )?;
     let config = MinerConfig::load_from(&cfg).expect("Failed to load config");
+    if let Some(network_file) = cli.common.network_file_path() {
+        let custom_network = ConsensusManager::load_custom_network(&network_file)
+            .map_err(|err| ExitError::new(ExitCode::ConfigError, err))?;
+        info!(
+            target: LOG_TARGET_FILE,
+            "Loaded network definition '{}' from {}",
+            custom_network.name(),
+            network_file.display()
+        );
+    }
     debug!(target: LOG_TARGET_FILE, "{:?}", config);
 
     if !config.mining_wallet_address.is_empty() && !config.mining_pool_address.is_empty() {
@@ -34,6 +34,7 @@ use tokio::sync::broadcast;
 pub struct PeerChainMetadata {
     node_id: NodeId,
     chain_metadata: ChainMetadata,
+    kernel_pruned_height: u64,
     latency: Option<Duration>,
 }
 
@@ -42,10 +43,16 @@ impl PeerChainMetadata {
         Self {
             node_id,
             chain_metadata,
+            kernel_pruned_height: 0,
             latency,
         }
     }
 
+    pub fn with_kernel_pruned_height(mut self, kernel_pruned_height: u64) -> Self {
+        self.kernel_pruned_height = kernel_pruned_height;
+        self
+    }
+
     pub fn node_id(&self) -> &NodeId {
         &self.node_id
     }
@@ -54,6 +61,12 @@ impl PeerChainMetadata {
         &self.chain_metadata
     }
 
+    /// The height up to and including which the peer claims to have pruned kernels, or 0 if it has not pruned any
+    /// kernels
+    pub fn kernel_pruned_height(&self) -> u64 {
+        self.kernel_pruned_height
+    }
+
     pub fn latency(&self) -> Option<Duration> {
         self.latency
     }
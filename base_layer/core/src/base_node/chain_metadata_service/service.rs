@@ -148,7 +148,10 @@ impl ChainMetadataService {
     /// Tack this node's metadata on to ping/pongs sent by the liveness service
     async fn update_liveness_chain_metadata(&mut self) -> Result<(), ChainMetadataSyncError> {
         let chain_metadata = self.base_node.get_metadata().await?;
-        let bytes = proto::ChainMetadata::from(chain_metadata).to_encoded_bytes();
+        let mut chain_metadata = proto::ChainMetadata::from(chain_metadata);
+        // Peers use this to avoid selecting this node for horizon sync if it cannot provide kernels
+        chain_metadata.kernel_pruned_height = self.base_node.get_kernel_pruned_height().await?;
+        let bytes = chain_metadata.to_encoded_bytes();
         self.liveness
             .set_metadata_entry(MetadataKey::ChainMetadata, bytes)
             .await?;
@@ -242,7 +245,9 @@ impl ChainMetadataService {
             .get(MetadataKey::ChainMetadata)
             .ok_or(ChainMetadataSyncError::NoChainMetadata)?;
 
-        let chain_metadata = ChainMetadata::try_from(proto::ChainMetadata::decode(chain_metadata_bytes.as_slice())?)
+        let chain_metadata = proto::ChainMetadata::decode(chain_metadata_bytes.as_slice())?;
+        let kernel_pruned_height = chain_metadata.kernel_pruned_height;
+        let chain_metadata = ChainMetadata::try_from(chain_metadata)
             .map_err(|err| ChainMetadataSyncError::ReceivedInvalidChainMetadata(event.node_id.clone(), err))?;
         debug!(
             target: LOG_TARGET,
@@ -260,11 +265,10 @@ impl ChainMetadataService {
             self.peer_chain_metadata.remove(pos);
         }
 
-        self.peer_chain_metadata.push(PeerChainMetadata::new(
-            event.node_id.clone(),
-            chain_metadata,
-            event.latency,
-        ));
+        self.peer_chain_metadata.push(
+            PeerChainMetadata::new(event.node_id.clone(), chain_metadata, event.latency)
+                .with_kernel_pruned_height(kernel_pruned_height),
+        );
         Ok(())
     }
 }
@@ -317,6 +321,7 @@ mod test {
             pruned_height: 0,
             accumulated_difficulty: diff.to_be_bytes().to_vec(),
             timestamp: Some(0),
+            kernel_pruned_height: 0,
         }
     }
 
@@ -361,6 +366,11 @@ mod test {
                     .reply(Ok(NodeCommsResponse::ChainMetadata(chain_metadata)))
                     .unwrap();
             }
+            if let Some(base_node_req) = base_node_receiver.next().await {
+                base_node_req
+                    .reply(Ok(NodeCommsResponse::KernelPrunedHeight(100)))
+                    .unwrap();
+            }
         });
 
         service.update_liveness_chain_metadata().await.unwrap();
@@ -372,13 +382,15 @@ mod test {
         assert_eq!(metadata_key, MetadataKey::ChainMetadata);
         let chain_metadata = proto::ChainMetadata::decode(data.as_slice()).unwrap();
         assert_eq!(chain_metadata.height_of_longest_chain, Some(123));
+        assert_eq!(chain_metadata.kernel_pruned_height, 100);
     }
     #[tokio::test]
     async fn handle_liveness_event_ok() {
         let (mut service, _, _, _) = setup();
 
         let mut metadata = Metadata::new();
-        let proto_chain_metadata = create_sample_proto_chain_metadata();
+        let mut proto_chain_metadata = create_sample_proto_chain_metadata();
+        proto_chain_metadata.kernel_pruned_height = 10;
         metadata.insert(MetadataKey::ChainMetadata, proto_chain_metadata.to_encoded_bytes());
 
         let node_id = NodeId::new();
@@ -400,6 +412,7 @@ mod test {
             metadata.claimed_chain_metadata().height_of_longest_chain(),
             proto_chain_metadata.height_of_longest_chain.unwrap()
         );
+        assert_eq!(metadata.kernel_pruned_height(), 10);
     }
 
     #[tokio::test]
@@ -29,7 +29,11 @@ use serde::{Deserialize, Serialize};
 use tari_common_types::types::{Commitment, HashOutput, PrivateKey, Signature};
 use tari_utilities::hex::Hex;
 
-use crate::{blocks::NewBlockTemplate, chain_storage::MmrTree, proof_of_work::PowAlgorithm};
+use crate::{
+    blocks::NewBlockTemplate,
+    chain_storage::{MmrTree, ScriptIndexKey},
+    proof_of_work::PowAlgorithm,
+};
 
 /// A container for the parameters required for a FetchMmrState request.
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,6 +47,7 @@ pub struct MmrStateRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub enum NodeCommsRequest {
     GetChainMetadata,
+    GetKernelPrunedHeight,
     FetchHeaders(RangeInclusive<u64>),
     FetchHeadersByHashes(Vec<HashOutput>),
     FetchMatchingUtxos(Vec<HashOutput>),
@@ -55,7 +60,17 @@ pub enum NodeCommsRequest {
     GetNewBlockTemplate(GetNewBlockTemplateRequest),
     GetNewBlock(NewBlockTemplate),
     FetchKernelByExcessSig(Signature),
-    FetchMempoolTransactionsByExcessSigs { excess_sigs: Vec<PrivateKey> },
+    FetchHeaderByKernelExcessSig(Signature),
+    FetchMempoolTransactionsByExcessSigs {
+        excess_sigs: Vec<PrivateKey>,
+    },
+    FetchUtxoStats(RangeInclusive<u64>),
+    FetchOutputsByScriptIndex {
+        key: ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -70,6 +85,7 @@ impl Display for NodeCommsRequest {
         use NodeCommsRequest::*;
         match self {
             GetChainMetadata => write!(f, "GetChainMetadata"),
+            GetKernelPrunedHeight => write!(f, "GetKernelPrunedHeight"),
             FetchHeaders(range) => {
                 write!(f, "FetchHeaders ({:?})", range)
             },
@@ -91,9 +107,26 @@ impl Display for NodeCommsRequest {
                 s.get_public_nonce().to_hex(),
                 s.get_signature().to_hex()
             ),
+            FetchHeaderByKernelExcessSig(s) => write!(
+                f,
+                "FetchHeaderByKernelExcessSig (signature=({}, {}))",
+                s.get_public_nonce().to_hex(),
+                s.get_signature().to_hex()
+            ),
             FetchMempoolTransactionsByExcessSigs { .. } => {
                 write!(f, "FetchMempoolTransactionsByExcessSigs")
             },
+            FetchUtxoStats(range) => write!(f, "FetchUtxoStats ({:?})", range),
+            FetchOutputsByScriptIndex {
+                start_height,
+                end_height,
+                limit,
+                ..
+            } => write!(
+                f,
+                "FetchOutputsByScriptIndex ({}..={}, limit={})",
+                start_height, end_height, limit
+            ),
         }
     }
 }
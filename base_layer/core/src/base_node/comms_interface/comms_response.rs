@@ -32,7 +32,7 @@ use tari_common_types::{
 
 use crate::{
     blocks::{Block, ChainHeader, HistoricalBlock, NewBlockTemplate},
-    chain_storage::UtxoMinedInfo,
+    chain_storage::{BlockUtxoStats, UtxoMinedInfo},
     proof_of_work::Difficulty,
     transactions::transaction_components::{Transaction, TransactionKernel, TransactionOutput},
 };
@@ -41,6 +41,7 @@ use crate::{
 #[derive(Debug, Clone)]
 pub enum NodeCommsResponse {
     ChainMetadata(ChainMetadata),
+    KernelPrunedHeight(u64),
     TransactionKernels(Vec<TransactionKernel>),
     BlockHeaders(Vec<ChainHeader>),
     BlockHeader(Option<ChainHeader>),
@@ -71,6 +72,10 @@ pub enum NodeCommsResponse {
     FetchOutputsByContractIdResponse {
         outputs: Vec<UtxoMinedInfo>,
     },
+    UtxoStats(Vec<BlockUtxoStats>),
+    FetchOutputsByScriptIndexResponse {
+        outputs: Vec<UtxoMinedInfo>,
+    },
 }
 
 impl Display for NodeCommsResponse {
@@ -79,6 +84,7 @@ impl Display for NodeCommsResponse {
         use NodeCommsResponse::*;
         match self {
             ChainMetadata(_) => write!(f, "ChainMetadata"),
+            KernelPrunedHeight(_) => write!(f, "KernelPrunedHeight"),
             TransactionKernels(_) => write!(f, "TransactionKernel"),
             BlockHeaders(_) => write!(f, "BlockHeaders"),
             BlockHeader(_) => write!(f, "BlockHeader"),
@@ -109,6 +115,10 @@ impl Display for NodeCommsResponse {
             ),
             FetchOutputsForBlockResponse { .. } => write!(f, "FetchConstitutionsResponse"),
             FetchOutputsByContractIdResponse { .. } => write!(f, "FetchOutputsByContractIdResponse"),
+            UtxoStats(stats) => write!(f, "UtxoStats({})", stats.len()),
+            FetchOutputsByScriptIndexResponse { outputs } => {
+                write!(f, "FetchOutputsByScriptIndexResponse({})", outputs.len())
+            },
         }
     }
 }
@@ -29,7 +29,10 @@ use std::{
 use log::*;
 use strum_macros::Display;
 use tari_common_types::types::{BlockHash, HashOutput};
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, ReputationEvent},
+};
 use tari_utilities::hex::Hex;
 use tokio::sync::Semaphore;
 
@@ -117,6 +120,9 @@ where B: BlockchainBackend + 'static
             NodeCommsRequest::GetChainMetadata => Ok(NodeCommsResponse::ChainMetadata(
                 self.blockchain_db.get_chain_metadata().await?,
             )),
+            NodeCommsRequest::GetKernelPrunedHeight => Ok(NodeCommsResponse::KernelPrunedHeight(
+                self.blockchain_db.fetch_kernel_pruned_height().await?,
+            )),
             NodeCommsRequest::FetchHeaders(range) => {
                 let headers = self.blockchain_db.fetch_chain_headers(range).await?;
                 Ok(NodeCommsResponse::BlockHeaders(headers))
@@ -354,6 +360,17 @@ where B: BlockchainBackend + 'static
 
                 Ok(NodeCommsResponse::TransactionKernels(kernels))
             },
+            NodeCommsRequest::FetchHeaderByKernelExcessSig(signature) => {
+                let header = match self
+                    .blockchain_db
+                    .fetch_block_hash_by_kernel_excess_sig(signature)
+                    .await?
+                {
+                    Some(hash) => self.blockchain_db.fetch_chain_header_by_block_hash(hash).await?,
+                    None => None,
+                };
+                Ok(NodeCommsResponse::BlockHeader(header))
+            },
             NodeCommsRequest::FetchMempoolTransactionsByExcessSigs { excess_sigs } => {
                 let (transactions, not_found) = self.mempool.retrieve_by_excess_sigs(excess_sigs).await?;
                 Ok(NodeCommsResponse::FetchMempoolTransactionsByExcessSigsResponse(
@@ -363,6 +380,25 @@ where B: BlockchainBackend + 'static
                     },
                 ))
             },
+            NodeCommsRequest::FetchUtxoStats(range) => {
+                let stats = self
+                    .blockchain_db
+                    .fetch_utxo_stats(*range.start(), *range.end())
+                    .await?;
+                Ok(NodeCommsResponse::UtxoStats(stats))
+            },
+            NodeCommsRequest::FetchOutputsByScriptIndex {
+                key,
+                start_height,
+                end_height,
+                limit,
+            } => {
+                let outputs = self
+                    .blockchain_db
+                    .fetch_outputs_by_script_index(key, start_height, end_height, limit)
+                    .await?;
+                Ok(NodeCommsResponse::FetchOutputsByScriptIndexResponse { outputs })
+            },
         }
     }
 
@@ -642,6 +678,15 @@ where B: BlockchainBackend + 'static
                 self.publish_block_event(BlockEvent::ValidBlockAdded(block.clone(), block_add_result));
 
                 if should_propagate {
+                    if let Some(ref source_peer) = source_peer {
+                        if let Err(e) = self
+                            .connectivity
+                            .record_reputation_event(source_peer.clone(), ReputationEvent::UsefulBlock)
+                            .await
+                        {
+                            error!(target: LOG_TARGET, "Failed to record reputation event: {}", e);
+                        }
+                    }
                     debug!(
                         target: LOG_TARGET,
                         "Propagate block ({}) to network.",
@@ -38,6 +38,7 @@ use crate::{
         NodeCommsResponse,
     },
     blocks::{Block, ChainHeader, HistoricalBlock, NewBlockTemplate},
+    chain_storage::{BlockUtxoStats, ScriptIndexKey, UtxoMinedInfo},
     proof_of_work::PowAlgorithm,
     transactions::transaction_components::{TransactionKernel, TransactionOutput},
 };
@@ -80,6 +81,19 @@ impl LocalNodeCommsInterface {
         }
     }
 
+    /// Request the height up to and including which the local node has pruned kernels, or 0 if no kernels have been
+    /// pruned.
+    pub async fn get_kernel_pruned_height(&mut self) -> Result<u64, CommsInterfaceError> {
+        match self
+            .request_sender
+            .call(NodeCommsRequest::GetKernelPrunedHeight)
+            .await??
+        {
+            NodeCommsResponse::KernelPrunedHeight(height) => Ok(height),
+            _ => Err(CommsInterfaceError::UnexpectedApiResponse),
+        }
+    }
+
     /// Request the block headers within the given range
     pub async fn get_blocks(
         &mut self,
@@ -257,7 +271,8 @@ impl LocalNodeCommsInterface {
         }
     }
 
-    /// Searches for a kernel via the excess sig
+    /// Searches for a kernel via the excess sig. Kernels that have been pruned are not returned, use
+    /// `get_header_by_kernel_excess_sig` to check whether a kernel has been mined.
     pub async fn get_kernel_by_excess_sig(
         &mut self,
         kernel: Signature,
@@ -271,4 +286,60 @@ impl LocalNodeCommsInterface {
             _ => Err(CommsInterfaceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the header of the block containing the kernel with the given excess sig, including kernels that have
+    /// been pruned. If the kernel has not been mined `Ok(None)` is returned.
+    pub async fn get_header_by_kernel_excess_sig(
+        &mut self,
+        excess_sig: Signature,
+    ) -> Result<Option<ChainHeader>, CommsInterfaceError> {
+        match self
+            .request_sender
+            .call(NodeCommsRequest::FetchHeaderByKernelExcessSig(excess_sig))
+            .await??
+        {
+            NodeCommsResponse::BlockHeader(header) => Ok(header),
+            _ => Err(CommsInterfaceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Request the UTXO statistics for the given range of heights, ordered from lowest to highest block height. This
+    /// requires the UTXO statistics index to be enabled on the base node.
+    pub async fn get_utxo_stats(
+        &mut self,
+        range: RangeInclusive<u64>,
+    ) -> Result<Vec<BlockUtxoStats>, CommsInterfaceError> {
+        match self
+            .request_sender
+            .call(NodeCommsRequest::FetchUtxoStats(range))
+            .await??
+        {
+            NodeCommsResponse::UtxoStats(stats) => Ok(stats),
+            _ => Err(CommsInterfaceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Request a page of the outputs matching `key` that were mined from `start_height` to `end_height` inclusive,
+    /// ordered by mined height. This requires the script index to be enabled on the base node.
+    pub async fn get_outputs_by_script_index(
+        &mut self,
+        key: ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<UtxoMinedInfo>, CommsInterfaceError> {
+        match self
+            .request_sender
+            .call(NodeCommsRequest::FetchOutputsByScriptIndex {
+                key,
+                start_height,
+                end_height,
+                limit,
+            })
+            .await??
+        {
+            NodeCommsResponse::FetchOutputsByScriptIndexResponse { outputs } => Ok(outputs),
+            _ => Err(CommsInterfaceError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -78,6 +78,7 @@ impl From<ChainMetadata> for proto::ChainMetadata {
             pruned_height: metadata.pruned_height(),
             accumulated_difficulty,
             timestamp: Some(metadata.timestamp()),
+            kernel_pruned_height: 0,
         }
     }
 }
@@ -277,3 +277,24 @@ impl proto::sync_utxo::Utxo {
         }
     }
 }
+
+#[cfg(feature = "tari_mmr")]
+impl From<tari_mmr::MerkleBatchProof> for proto::MerkleBatchProof {
+    fn from(proof: tari_mmr::MerkleBatchProof) -> Self {
+        Self {
+            mmr_size: proof.mmr_size() as u64,
+            path: proof.path().to_vec(),
+            peaks: proof.peaks().to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "tari_mmr")]
+impl TryFrom<proto::MerkleBatchProof> for tari_mmr::MerkleBatchProof {
+    type Error = String;
+
+    fn try_from(proof: proto::MerkleBatchProof) -> Result<Self, Self::Error> {
+        let mmr_size = usize::try_from(proof.mmr_size).map_err(|_| "MMR size overflowed usize".to_string())?;
+        Ok(Self::from_raw_parts(mmr_size, proof.path, proof.peaks))
+    }
+}
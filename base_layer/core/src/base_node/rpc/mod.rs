@@ -45,8 +45,12 @@ use crate::{
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            MmrProofRequest,
+            MmrProofResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
+            ScriptIndexQueryRequest,
+            ScriptIndexQueryResponse,
             Signatures,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
@@ -119,6 +123,29 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
         &self,
         request: Request<GetMempoolFeePerGramStatsRequest>,
     ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus>;
+
+    /// Returns a single batch inclusion proof for the requested output hashes against the output MMR root at the tip.
+    #[rpc(method = 13)]
+    async fn fetch_output_mmr_proof(
+        &self,
+        request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus>;
+
+    /// Returns a single batch inclusion proof for the requested kernel hashes against the kernel MMR root at the tip.
+    #[rpc(method = 14)]
+    async fn fetch_kernel_mmr_proof(
+        &self,
+        request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus>;
+
+    /// Returns a page of the outputs matching a script hash or sender offset public key, ordered by mined height.
+    /// Requires the script index to be enabled on the base node. The mined block of each output and the best block
+    /// are returned so that the caller can detect a reorg between pages.
+    #[rpc(method = 15)]
+    async fn query_outputs_by_script(
+        &self,
+        request: Request<ScriptIndexQueryRequest>,
+    ) -> Result<Response<ScriptIndexQueryResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
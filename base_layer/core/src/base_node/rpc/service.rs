@@ -21,13 +21,20 @@
 // OTHERWISE) ARISING IN ANY WAY OUT OF THE  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH
 // DAMAGE.
 
-use std::convert::{TryFrom, TryInto};
+use std::{
+    cmp,
+    convert::{TryFrom, TryInto},
+    sync::{Arc, Weak},
+};
 
 use log::*;
-use tari_common_types::types::{FixedHash, Signature};
-use tari_comms::protocol::rpc::{Request, Response, RpcStatus, RpcStatusResultExt, Streaming};
-use tari_utilities::hex::Hex;
-use tokio::sync::mpsc;
+use tari_common_types::types::{FixedHash, PublicKey, Signature};
+use tari_comms::{
+    peer_manager::NodeId,
+    protocol::rpc::{Request, Response, RpcStatus, RpcStatusResultExt, Streaming, RPC_MAX_FRAME_SIZE},
+};
+use tari_utilities::{hex::Hex, ByteArray};
+use tokio::sync::{mpsc, Mutex, Semaphore, SemaphorePermit};
 
 use crate::{
     base_node::{
@@ -35,7 +42,14 @@ use crate::{
         state_machine_service::states::StateInfo,
         StateMachineHandle,
     },
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, PrunedOutput},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BlockchainBackend,
+        ChainStorageError,
+        PrunedOutput,
+        ScriptIndexKey,
+        UtxoMinedInfo,
+    },
     mempool::{service::MempoolHandle, TxStorageResponse},
     proto,
     proto::{
@@ -44,8 +58,13 @@ use crate::{
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            MmrProofLeaf,
+            MmrProofRequest,
+            MmrProofResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
+            ScriptIndexQueryRequest,
+            ScriptIndexQueryResponse,
             Signatures as SignaturesProto,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
@@ -66,11 +85,19 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "c::base_node::rpc";
+/// Generating an MMR proof rebuilds the MMR from every block, so only this many are generated at a time across all
+/// peers, and no more than one at a time for each peer
+const MAX_CONCURRENT_MMR_PROOFS: usize = 4;
+/// The largest deleted bitmap that is sent with an output MMR proof, leaving room in the frame for the rest of the
+/// response
+const MAX_DELETED_BITMAP_SIZE: usize = RPC_MAX_FRAME_SIZE / 2;
 
 pub struct BaseNodeWalletRpcService<B> {
     db: AsyncBlockchainDb<B>,
     mempool: MempoolHandle,
     state_machine: StateMachineHandle,
+    mmr_proof_permits: Arc<Semaphore>,
+    mmr_proof_peers: Mutex<Vec<Weak<NodeId>>>,
 }
 
 impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
@@ -79,6 +106,8 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             db,
             mempool,
             state_machine,
+            mmr_proof_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_MMR_PROOFS)),
+            mmr_proof_peers: Mutex::new(Vec::new()),
         }
     }
 
@@ -100,13 +129,14 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
     async fn fetch_kernel(&self, signature: Signature) -> Result<TxQueryResponse, RpcStatus> {
         let db = self.db();
         let chain_metadata = db.get_chain_metadata().await.rpc_status_internal_error(LOG_TARGET)?;
+        // The block hash is looked up rather than the kernel itself so that pruned kernels are still reported as mined
         match db
-            .fetch_kernel_by_excess_sig(signature.clone())
+            .fetch_block_hash_by_kernel_excess_sig(signature.clone())
             .await
             .rpc_status_internal_error(LOG_TARGET)?
         {
             None => (),
-            Some((_, block_hash)) => {
+            Some(block_hash) => {
                 match db
                     .fetch_header_by_block_hash(block_hash)
                     .await
@@ -149,6 +179,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadySpent |
             TxStorageResponse::NotStoredConsensus |
+            TxStorageResponse::NotStoredReplacementRejected |
             TxStorageResponse::NotStored => TxQueryResponse {
                 location: TxLocation::NotStored as i32,
                 block_hash: None,
@@ -160,6 +191,65 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
         };
         Ok(mempool_response)
     }
+
+    /// Acquires one of the MMR proof permits for the peer. The permit and the peer's token are both released once they
+    /// are dropped.
+    async fn try_acquire_mmr_proof_permit(
+        &self,
+        peer: &NodeId,
+    ) -> Result<(SemaphorePermit<'_>, Arc<NodeId>), RpcStatus> {
+        let mut lock = self.mmr_proof_peers.lock().await;
+        *lock = lock.drain(..).filter(|p| p.strong_count() > 0).collect();
+        if lock.iter().any(|p| p.upgrade().filter(|p| **p == *peer).is_some()) {
+            return Err(RpcStatus::conflict(
+                "An MMR proof is already being generated for this client, try again later",
+            ));
+        }
+        let permit = self
+            .mmr_proof_permits
+            .try_acquire()
+            .map_err(|_| RpcStatus::conflict("The base node is busy generating other MMR proofs, try again later"))?;
+        let token = Arc::new(peer.clone());
+        lock.push(Arc::downgrade(&token));
+        Ok((permit, token))
+    }
+
+    fn parse_mmr_proof_request(request: MmrProofRequest) -> Result<Vec<FixedHash>, RpcStatus> {
+        if request.hashes.is_empty() {
+            return Err(RpcStatus::bad_request("Empty hashes"));
+        }
+        // Generating a proof rebuilds the MMR, so the proven set is kept small
+        const MAX_ALLOWED_PROOF_HASHES: usize = 512;
+        if request.hashes.len() > MAX_ALLOWED_PROOF_HASHES {
+            return Err(RpcStatus::bad_request(&format!(
+                "Exceeded maximum allowed query hashes. Max: {}",
+                MAX_ALLOWED_PROOF_HASHES
+            )));
+        }
+        request
+            .hashes
+            .into_iter()
+            .map(FixedHash::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|_| RpcStatus::bad_request("Malformed hash received"))
+    }
+
+    fn parse_script_index_key(request: &ScriptIndexQueryRequest) -> Result<ScriptIndexKey, RpcStatus> {
+        match (
+            request.script_hash.is_empty(),
+            request.sender_offset_public_key.is_empty(),
+        ) {
+            (false, true) => FixedHash::try_from(request.script_hash.as_slice())
+                .map(ScriptIndexKey::ScriptHash)
+                .map_err(|_| RpcStatus::bad_request("Malformed script hash received")),
+            (true, false) => PublicKey::from_bytes(&request.sender_offset_public_key)
+                .map(ScriptIndexKey::SenderOffsetPublicKey)
+                .map_err(|_| RpcStatus::bad_request("Malformed sender offset public key received")),
+            _ => Err(RpcStatus::bad_request(
+                "Exactly one of script_hash and sender_offset_public_key must be set",
+            )),
+        }
+    }
 }
 
 #[tari_comms::async_trait]
@@ -207,6 +297,11 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
             },
+            TxStorageResponse::NotStoredReplacementRejected => TxSubmissionResponse {
+                accepted: false,
+                rejection_reason: TxSubmissionRejectionReason::DoubleSpend.into(),
+                is_synced,
+            },
             TxStorageResponse::NotStoredAlreadySpent | TxStorageResponse::ReorgPool => {
                 // Is this transaction a double spend or has this transaction been mined?
                 match transaction.first_kernel_excess_sig() {
@@ -220,7 +315,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                         // already exists in the chain, otherwise it is a double spend
                         let db = self.db();
                         match db
-                            .fetch_kernel_by_excess_sig(s.clone())
+                            .fetch_block_hash_by_kernel_excess_sig(s.clone())
                             .await
                             .rpc_status_internal_error(LOG_TARGET)?
                         {
@@ -396,21 +491,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
         Ok(Response::new(UtxoQueryResponses {
             height_of_longest_chain: metadata.height_of_longest_chain(),
             best_block: metadata.best_block().to_vec(),
-            responses: mined_info_resp
-                .into_iter()
-                .flatten()
-                .map(|utxo| UtxoQueryResponse {
-                    mmr_position: utxo.mmr_position.into(),
-                    mined_height: utxo.mined_height,
-                    mined_in_block: utxo.header_hash.to_vec(),
-                    output_hash: utxo.output.hash().to_vec(),
-                    output: match utxo.output {
-                        PrunedOutput::Pruned { .. } => None,
-                        PrunedOutput::NotPruned { output } => Some(output.into()),
-                    },
-                    mined_timestamp: utxo.mined_timestamp,
-                })
-                .collect(),
+            responses: mined_info_resp.into_iter().flatten().map(utxo_query_response).collect(),
         }))
     }
 
@@ -643,4 +724,157 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
 
         Ok(Response::new(stats.into()))
     }
+
+    async fn fetch_output_mmr_proof(
+        &self,
+        request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus> {
+        let peer = request.context().peer_node_id().clone();
+        let request = request.into_message();
+        let exclude_deleted_bitmap = request.exclude_deleted_bitmap;
+        let hashes = Self::parse_mmr_proof_request(request)?;
+        let _permit = self.try_acquire_mmr_proof_permit(&peer).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Generating output MMR proof for {} hash(es)",
+            hashes.len()
+        );
+        let proof = self
+            .db()
+            .generate_output_mmr_proof(hashes)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        let deleted = proof.proof.deleted_bitmap().rpc_status_internal_error(LOG_TARGET)?;
+        // The deleted flags of the leaves can only be checked against the deleted bitmap that the proof commits to
+        let deleted_bitmap = if exclude_deleted_bitmap {
+            Vec::new()
+        } else {
+            let bytes = proof.proof.deleted_bytes();
+            if bytes.len() > MAX_DELETED_BITMAP_SIZE {
+                return Err(RpcStatus::bad_request(&format!(
+                    "The deleted bitmap is too large to send ({} bytes), it must be excluded",
+                    bytes.len()
+                )));
+            }
+            bytes.to_vec()
+        };
+
+        Ok(Response::new(MmrProofResponse {
+            best_block: proof.header_hash.to_vec(),
+            height_of_longest_chain: proof.height,
+            leaves: proof
+                .leaves
+                .into_iter()
+                .map(|(hash, leaf_index)| MmrProofLeaf {
+                    hash: hash.to_vec(),
+                    leaf_index: leaf_index.into(),
+                    deleted: deleted.contains(leaf_index),
+                })
+                .collect(),
+            deleted_bitmap,
+            proof: Some(proof.proof.proof().clone().into()),
+        }))
+    }
+
+    async fn fetch_kernel_mmr_proof(
+        &self,
+        request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus> {
+        let peer = request.context().peer_node_id().clone();
+        let hashes = Self::parse_mmr_proof_request(request.into_message())?;
+        let _permit = self.try_acquire_mmr_proof_permit(&peer).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Generating kernel MMR proof for {} hash(es)",
+            hashes.len()
+        );
+        let proof = self
+            .db()
+            .generate_kernel_mmr_proof(hashes)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        Ok(Response::new(MmrProofResponse {
+            best_block: proof.header_hash.to_vec(),
+            height_of_longest_chain: proof.height,
+            leaves: proof
+                .leaves
+                .into_iter()
+                .map(|(hash, leaf_index)| MmrProofLeaf {
+                    hash: hash.to_vec(),
+                    leaf_index: leaf_index.into(),
+                    deleted: false,
+                })
+                .collect(),
+            proof: Some(proof.proof.into()),
+            deleted_bitmap: Vec::new(),
+        }))
+    }
+
+    async fn query_outputs_by_script(
+        &self,
+        request: Request<ScriptIndexQueryRequest>,
+    ) -> Result<Response<ScriptIndexQueryResponse>, RpcStatus> {
+        // The number of outputs to aim for per page. A page always ends at a block boundary, so it may be larger.
+        const PAGE_SIZE: usize = 100;
+
+        let message = request.into_message();
+        let key = Self::parse_script_index_key(&message)?;
+        let metadata = self
+            .db
+            .get_chain_metadata()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let tip_height = metadata.height_of_longest_chain();
+        let end_height = if message.end_height == 0 {
+            tip_height
+        } else {
+            cmp::min(message.end_height, tip_height)
+        };
+        debug!(
+            target: LOG_TARGET,
+            "Querying script index from height {} to {}", message.start_height, end_height
+        );
+
+        let outputs = if message.start_height > end_height {
+            Vec::new()
+        } else {
+            self.db()
+                .fetch_outputs_by_script_index(key, message.start_height, end_height, PAGE_SIZE)
+                .await
+                .map_err(|err| match err {
+                    ChainStorageError::InvalidOperation(msg) => RpcStatus::not_implemented(&msg),
+                    err => {
+                        error!(target: LOG_TARGET, "Failed to query script index: {}", err);
+                        RpcStatus::general_default()
+                    },
+                })?
+        };
+        let next_height = match outputs.last() {
+            Some(last) if outputs.len() >= PAGE_SIZE && last.mined_height < end_height => last.mined_height + 1,
+            _ => 0,
+        };
+
+        Ok(Response::new(ScriptIndexQueryResponse {
+            outputs: outputs.into_iter().map(utxo_query_response).collect(),
+            next_height,
+            best_block: metadata.best_block().to_vec(),
+            height_of_longest_chain: tip_height,
+        }))
+    }
+}
+
+fn utxo_query_response(utxo: UtxoMinedInfo) -> UtxoQueryResponse {
+    UtxoQueryResponse {
+        mmr_position: utxo.mmr_position.into(),
+        mined_height: utxo.mined_height,
+        mined_in_block: utxo.header_hash.to_vec(),
+        output_hash: utxo.output.hash().to_vec(),
+        output: match utxo.output {
+            PrunedOutput::Pruned { .. } => None,
+            PrunedOutput::NotPruned { output } => Some(output.into()),
+        },
+        mined_timestamp: utxo.mined_timestamp,
+    }
 }
@@ -55,6 +55,8 @@ impl BlockSync {
             shared.connectivity.clone(),
             mem::take(&mut self.sync_peers),
             shared.sync_validators.block_body.clone(),
+            shared.sync_validators.assume_valid_block_body.clone(),
+            shared.sync_validators.verification_pool.clone(),
         );
 
         let status_event_sender = shared.status_event_sender.clone();
@@ -30,6 +30,7 @@ use log::*;
 use num_format::{Locale, ToFormattedString};
 use serde::{Deserialize, Serialize};
 use tari_common_types::chain_metadata::ChainMetadata;
+use tari_comms::PeerManager;
 use tari_utilities::epoch_time::EpochTime;
 use tokio::sync::broadcast;
 
@@ -231,7 +232,7 @@ impl Listening {
                     };
                     log_mdc::extend(mdc.clone());
 
-                    let sync_mode = determine_sync_mode(
+                    let mut sync_mode = determine_sync_mode(
                         shared.config.blocks_behind_before_considered_lagging,
                         &local_metadata,
                         best_metadata,
@@ -239,6 +240,7 @@ impl Listening {
                     );
 
                     if sync_mode.is_lagging() {
+                        set_sync_peer_reputations(&shared.peer_manager, &mut sync_mode).await;
                         return StateEvent::FallenBehind(sync_mode);
                     }
 
@@ -311,6 +313,25 @@ fn select_sync_peers<'a>(
         .collect()
 }
 
+/// Sets the locally observed reputation of each sync peer so that reputable peers are preferred for sync.
+async fn set_sync_peer_reputations(peer_manager: &PeerManager, sync_mode: &mut SyncStatus) {
+    if let SyncStatus::Lagging { sync_peers, .. } = sync_mode {
+        let node_ids = sync_peers.iter().map(|p| p.node_id().clone()).collect::<Vec<_>>();
+        match peer_manager.reputation_tiers(&node_ids).await {
+            Ok(tiers) => {
+                for sync_peer in sync_peers {
+                    if let Some(tier) = tiers.get(sync_peer.node_id()) {
+                        sync_peer.set_reputation(*tier);
+                    }
+                }
+            },
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to fetch sync peer reputations: {}", err);
+            },
+        }
+    }
+}
+
 /// Determine the best metadata claimed from a set of metadata received from the network.
 fn best_claimed_metadata<'a>(metadata_list: &[&'a PeerChainMetadata]) -> Option<&'a ChainMetadata> {
     metadata_list
@@ -20,8 +20,6 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::cmp::Ordering;
-
 use log::*;
 
 use crate::{
@@ -65,13 +63,11 @@ impl DecideNextSync {
             };
 
             let horizon_sync_height = local_metadata.horizon_block(last_header.height);
-            // Filter sync peers that claim to be able to provide blocks up until our pruned height
             let sync_peers = self
                 .sync_peers
                 .drain(..)
                 .filter(|sync_peer| {
-                    let remote_metadata = sync_peer.claimed_chain_metadata();
-                    remote_metadata.height_of_longest_chain() >= horizon_sync_height
+                    can_provide_horizon_sync(sync_peer, horizon_sync_height, local_metadata.pruned_height())
                 })
                 .collect::<Vec<_>>();
 
@@ -119,6 +115,13 @@ impl DecideNextSync {
     }
 }
 
+/// Returns true if the peer claims to be able to provide blocks up until our horizon sync height, and still has the
+/// kernels that we need from above our current pruned height
+fn can_provide_horizon_sync(sync_peer: &SyncPeer, horizon_sync_height: u64, local_pruned_height: u64) -> bool {
+    sync_peer.claimed_chain_metadata().height_of_longest_chain() >= horizon_sync_height &&
+        sync_peer.kernel_pruned_height() <= local_pruned_height
+}
+
 impl From<HeaderSyncState> for DecideNextSync {
     fn from(sync: HeaderSyncState) -> Self {
         sync.into_sync_peers().into()
@@ -127,13 +130,7 @@ impl From<HeaderSyncState> for DecideNextSync {
 
 impl From<Vec<SyncPeer>> for DecideNextSync {
     fn from(mut sync_peers: Vec<SyncPeer>) -> Self {
-        sync_peers.sort_by(|a, b| match (a.latency(), b.latency()) {
-            (None, None) => Ordering::Equal,
-            // No latency goes to the end
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(la), Some(lb)) => la.cmp(&lb),
-        });
+        sync_peers.sort_by(SyncPeer::cmp_preference);
         Self { sync_peers }
     }
 }
@@ -148,6 +145,12 @@ mod test {
     use super::*;
 
     mod sort_by_latency {
+        use tari_comms::{
+            peer_manager::{NodeId, ReputationTier},
+            types::CommsPublicKey,
+        };
+        use tari_crypto::keys::PublicKey;
+
         use super::*;
         use crate::base_node::chain_metadata_service::PeerChainMetadata;
 
@@ -171,5 +174,48 @@ mod test {
             let decide = DecideNextSync::from(shuffled);
             assert_eq!(decide.sync_peers, peers);
         }
+
+        #[test]
+        fn it_prefers_reputable_peers() {
+            let mut peers = (0..3)
+                .map(|i| {
+                    let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+                    PeerChainMetadata::new(
+                        NodeId::from_key(&public_key),
+                        ChainMetadata::empty(),
+                        Some(Duration::from_millis(i)),
+                    )
+                    .into()
+                })
+                .collect::<Vec<SyncPeer>>();
+            peers[0].set_reputation(ReputationTier::Poor);
+            peers[2].set_reputation(ReputationTier::Reliable);
+            let expected = vec![peers[2].clone(), peers[1].clone(), peers[0].clone()];
+
+            let decide = DecideNextSync::from(peers);
+            assert_eq!(decide.sync_peers, expected);
+        }
+    }
+
+    mod horizon_sync_peers {
+        use tari_common_types::types::FixedHash;
+
+        use super::*;
+        use crate::base_node::chain_metadata_service::PeerChainMetadata;
+
+        fn sync_peer(height: u64, kernel_pruned_height: u64) -> SyncPeer {
+            let metadata = ChainMetadata::new(height, FixedHash::zero(), 10, height - 10, 1, 0);
+            PeerChainMetadata::new(Default::default(), metadata, None)
+                .with_kernel_pruned_height(kernel_pruned_height)
+                .into()
+        }
+
+        #[test]
+        fn it_skips_peers_that_have_pruned_the_kernels_we_need() {
+            assert!(can_provide_horizon_sync(&sync_peer(100, 0), 90, 0));
+            assert!(!can_provide_horizon_sync(&sync_peer(100, 50), 90, 0));
+            assert!(can_provide_horizon_sync(&sync_peer(100, 50), 90, 50));
+            assert!(!can_provide_horizon_sync(&sync_peer(80, 0), 90, 0));
+        }
     }
 }
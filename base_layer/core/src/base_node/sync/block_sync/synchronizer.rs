@@ -22,6 +22,7 @@
 
 use std::{
     convert::{TryFrom, TryInto},
+    mem,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -29,9 +30,10 @@ use std::{
 use futures::StreamExt;
 use log::*;
 use num_format::{Locale, ToFormattedString};
+use tari_common_types::types::HashOutput;
 use tari_comms::{
     connectivity::ConnectivityRequester,
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent},
     protocol::rpc::{RpcClient, RpcError},
     PeerConnection,
 };
@@ -44,12 +46,21 @@ use crate::{
         sync::{hooks::Hooks, rpc, SyncPeer},
         BlockchainSyncConfig,
     },
-    blocks::{Block, BlockValidationError, ChainBlock},
+    blocks::{Block, BlockHeaderAccumulatedData, BlockValidationError, ChainBlock},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     common::rolling_avg::RollingAverageTime,
     proto::base_node::SyncBlocksRequest,
     transactions::aggregated_body::AggregateBody,
-    validation::{BlockSyncBodyValidation, ValidationError},
+    validation::{
+        block_validators::{
+            BlockVerificationError,
+            BlockVerificationPool,
+            PendingVerification,
+            ASSUME_VALID_SKIPPED_CHECKS,
+        },
+        BlockSyncBodyValidation,
+        ValidationError,
+    },
 };
 
 const LOG_TARGET: &str = "c::bn::block_sync";
@@ -60,6 +71,8 @@ pub struct BlockSynchronizer<B> {
     connectivity: ConnectivityRequester,
     sync_peers: Vec<SyncPeer>,
     block_validator: Arc<dyn BlockSyncBodyValidation>,
+    assume_valid_block_validator: Arc<dyn BlockSyncBodyValidation>,
+    verification_pool: Option<BlockVerificationPool>,
     hooks: Hooks,
 }
 
@@ -70,6 +83,8 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         connectivity: ConnectivityRequester,
         sync_peers: Vec<SyncPeer>,
         block_validator: Arc<dyn BlockSyncBodyValidation>,
+        assume_valid_block_validator: Arc<dyn BlockSyncBodyValidation>,
+        verification_pool: Option<BlockVerificationPool>,
     ) -> Self {
         Self {
             config,
@@ -77,6 +92,8 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
             connectivity,
             sync_peers,
             block_validator,
+            assume_valid_block_validator,
+            verification_pool,
             hooks: Default::default(),
         }
     }
@@ -141,6 +158,8 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
             );
             match self.synchronize_blocks(sync_peer, client, max_latency).await {
                 Ok(_) => {
+                    self.record_reputation_event(node_id, ReputationEvent::UsefulBlock)
+                        .await;
                     self.db.cleanup_orphans().await?;
                     return Ok(());
                 },
@@ -169,6 +188,9 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 Err(err @ BlockSyncError::RpcError(RpcError::ReplyTimeout)) |
                 Err(err @ BlockSyncError::MaxLatencyExceeded { .. }) => {
                     warn!(target: LOG_TARGET, "{}", err);
+                    if matches!(err, BlockSyncError::RpcError(RpcError::ReplyTimeout)) {
+                        self.record_reputation_event(node_id, ReputationEvent::RpcTimeout).await;
+                    }
                     if i == self.sync_peers.len() - 1 {
                         return Err(BlockSyncError::AllSyncPeersExceedLatency);
                     }
@@ -191,6 +213,35 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         Ok(connection)
     }
 
+    /// Returns the height of the configured assume-valid block if it is in the synced header chain. Blocks at or below
+    /// this height are validated without the assume-valid skipped checks.
+    async fn fetch_assume_valid_height(&self) -> Result<Option<u64>, BlockSyncError> {
+        let hash = match self.config.assume_valid_block {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        match self.db.fetch_chain_header_by_block_hash(hash).await? {
+            Some(header) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Assume-valid block #{} `{}` found in the header chain. Skipping {} for blocks up to this height.",
+                    header.height(),
+                    hash.to_hex(),
+                    ASSUME_VALID_SKIPPED_CHECKS.join(", ")
+                );
+                Ok(Some(header.height()))
+            },
+            None => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Assume-valid block `{}` is not in the synced header chain. All blocks will be fully validated.",
+                    hash.to_hex()
+                );
+                Ok(None)
+            },
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn synchronize_blocks(
         &mut self,
@@ -232,10 +283,15 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
             end_hash: tip_hash.to_vec(),
         };
 
+        let assume_valid_height = self.fetch_assume_valid_height().await?;
+        let verification_pool = self.verification_pool.clone();
         let mut block_stream = client.sync_blocks(request).await?;
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
+        let mut window = Vec::with_capacity(self.config.block_verification_window);
+        let mut pending_window = None;
         let mut last_sync_timer = Instant::now();
+        let mut last_block_timer = Instant::now();
         let mut avg_latency = RollingAverageTime::new(20);
         while let Some(block) = block_stream.next().await {
             let latency = last_sync_timer.elapsed();
@@ -255,7 +311,6 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
 
             let current_height = header.height();
             let header_hash = *header.hash();
-            let timestamp = header.timestamp();
 
             if header.header().prev_hash != prev_hash {
                 return Err(BlockSyncError::PeerSentBlockThatDidNotFormAChain {
@@ -272,89 +327,25 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 .ok_or_else(|| BlockSyncError::ProtocolViolation("Block body was empty".to_string()))?
                 .map_err(BlockSyncError::ProtocolViolation)?;
 
+            let is_assumed_valid = assume_valid_height.map_or(false, |height| current_height <= height);
             debug!(
                 target: LOG_TARGET,
-                "Validating block body #{} (PoW = {}, {}, latency: {:.2?})",
+                "Received block body #{} (PoW = {}, {}, latency: {:.2?}{})",
                 current_height,
                 header.header().pow_algo(),
                 body.to_counts_string(),
-                latency
-            );
-
-            let timer = Instant::now();
-            let (header, header_accum_data) = header.into_parts();
-
-            let block = match self.block_validator.validate_body(Block::new(header, body)).await {
-                Ok(block) => block,
-                Err(err @ ValidationError::BadBlockFound { .. }) |
-                Err(err @ ValidationError::FatalStorageError(_)) |
-                Err(err @ ValidationError::AsyncTaskFailed(_)) |
-                Err(err @ ValidationError::CustomError(_)) => return Err(err.into()),
-                Err(err) => {
-                    // Add to bad blocks
-                    if let Err(err) = self
-                        .db
-                        .write_transaction()
-                        .insert_bad_block(header_hash, current_height)
-                        .commit()
-                        .await
-                    {
-                        error!(target: LOG_TARGET, "Failed to insert bad block: {}", err);
-                    }
-                    return Err(err.into());
-                },
-            };
-
-            let block = ChainBlock::try_construct(Arc::new(block), header_accum_data)
-                .map(Arc::new)
-                .ok_or(BlockSyncError::FailedToConstructChainBlock)?;
-
-            debug!(
-                target: LOG_TARGET,
-                "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
-                timer.elapsed(),
-                block.header().height,
-                block.header().pow_algo(),
-                block.block().body.to_counts_string(),
+                latency,
+                if is_assumed_valid { ", assume-valid" } else { "" }
             );
 
-            let timer = Instant::now();
-            self.db
-                .write_transaction()
-                .insert_block_body(block.clone())
-                .set_best_block(
-                    block.height(),
-                    header_hash,
-                    block.accumulated_data().total_accumulated_difficulty,
-                    block.header().prev_hash,
-                    timestamp,
-                )
-                .commit()
-                .await?;
-
             // Average time between receiving blocks from the peer - used to detect a slow sync peer
             let last_avg_latency = avg_latency.calculate_average_with_min_samples(5);
             if let Some(latency) = last_avg_latency {
                 sync_peer.set_latency(latency);
             }
-            // Includes time to add block to database, used to show blocks/s on status line
-            sync_peer.add_sample(last_sync_timer.elapsed());
-            self.hooks
-                .call_on_progress_block_hooks(block.clone(), tip_height, &sync_peer);
-
-            debug!(
-                target: LOG_TARGET,
-                "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}, latency: {:.2?}",
-                block.height(),
-                timer.elapsed(),
-                block
-                    .accumulated_data()
-                    .total_accumulated_difficulty
-                    .to_formatted_string(&Locale::en),
-                block.accumulated_data().accumulated_monero_difficulty,
-                block.accumulated_data().accumulated_sha_difficulty,
-                latency
-            );
+            // Includes time to validate and store blocks, used to show blocks/s on status line
+            sync_peer.add_sample(last_block_timer.elapsed());
+            last_block_timer = Instant::now();
             if let Some(avg_latency) = last_avg_latency {
                 if avg_latency > max_latency {
                     return Err(BlockSyncError::MaxLatencyExceeded {
@@ -365,10 +356,50 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 }
             }
 
-            current_block = Some(block);
+            let (header, header_accum_data) = header.into_parts();
+            let block = Block::new(header, body);
+            match verification_pool.as_ref() {
+                // Assume-valid blocks skip the checks done by the verification pool
+                Some(pool) if !is_assumed_valid => {
+                    window.push((block, header_accum_data));
+                    if window.len() >= self.config.block_verification_window {
+                        // Verify this window while the previous one is validated and stored
+                        let next_window = PendingWindow::start(pool, mem::take(&mut window));
+                        if let Some(pending) = pending_window.replace(next_window) {
+                            current_block = self
+                                .finish_window(pending, tip_height, &sync_peer)
+                                .await?
+                                .or(current_block);
+                        }
+                    }
+                },
+                _ => {
+                    let validator = if is_assumed_valid {
+                        &self.assume_valid_block_validator
+                    } else {
+                        &self.block_validator
+                    };
+                    let block = self
+                        .validate_and_store_block(block, header_accum_data, &**validator, tip_height, &sync_peer)
+                        .await?;
+                    current_block = Some(block);
+                },
+            }
+
             last_sync_timer = Instant::now();
         }
 
+        let last_window = verification_pool
+            .as_ref()
+            .filter(|_| !window.is_empty())
+            .map(|pool| PendingWindow::start(pool, window));
+        for pending in pending_window.into_iter().chain(last_window) {
+            current_block = self
+                .finish_window(pending, tip_height, &sync_peer)
+                .await?
+                .or(current_block);
+        }
+
         if let Some(block) = current_block {
             self.hooks.call_on_complete_hooks(block);
         }
@@ -378,7 +409,162 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         Ok(())
     }
 
+    /// Waits for the verification of a window of blocks, then validates and stores each block. If a block in the
+    /// window is invalid, the blocks before it are still stored. If the failure could not be attributed to a block,
+    /// no blocks are stored. Returns the last stored block.
+    async fn finish_window(
+        &self,
+        window: PendingWindow,
+        tip_height: u64,
+        sync_peer: &SyncPeer,
+    ) -> Result<Option<Arc<ChainBlock>>, BlockSyncError> {
+        let PendingWindow {
+            blocks,
+            accumulated_data,
+            verification,
+        } = window;
+        let (num_verified, verification_error) = match verification.wait().await {
+            Ok(()) => (accumulated_data.len(), None),
+            Err(BlockVerificationError::InvalidBlock { block_index, error }) => (block_index, Some(error)),
+            Err(BlockVerificationError::InvalidWindow { error }) => {
+                // No block can be blamed, so none of the blocks in the window are stored
+                warn!(
+                    target: LOG_TARGET,
+                    "Window of {} block(s) failed verification: {}",
+                    accumulated_data.len(),
+                    error
+                );
+                return Err(error.into());
+            },
+            Err(BlockVerificationError::AsyncTaskFailed(err)) => {
+                return Err(ValidationError::AsyncTaskFailed(err).into())
+            },
+        };
+
+        // The verification has completed, so this should be the only reference to the blocks
+        let blocks = Arc::try_unwrap(blocks).unwrap_or_else(|blocks| (*blocks).clone());
+        let mut blocks = blocks.into_iter().zip(accumulated_data);
+        let mut last_block = None;
+        for (block, accumulated_data) in blocks.by_ref().take(num_verified) {
+            let block = self
+                .validate_and_store_block(block, accumulated_data, &*self.block_validator, tip_height, sync_peer)
+                .await?;
+            last_block = Some(block);
+        }
+
+        match verification_error {
+            Some(err) => {
+                if let Some((block, accumulated_data)) = blocks.next() {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block #{} `{}` failed verification: {}",
+                        block.header.height,
+                        accumulated_data.hash.to_hex(),
+                        err
+                    );
+                    self.insert_bad_block(accumulated_data.hash, block.header.height).await;
+                }
+                Err(err.into())
+            },
+            None => Ok(last_block),
+        }
+    }
+
+    async fn validate_and_store_block(
+        &self,
+        block: Block,
+        header_accum_data: BlockHeaderAccumulatedData,
+        validator: &dyn BlockSyncBodyValidation,
+        tip_height: u64,
+        sync_peer: &SyncPeer,
+    ) -> Result<Arc<ChainBlock>, BlockSyncError> {
+        let timer = Instant::now();
+        let header_hash = header_accum_data.hash;
+        let current_height = block.header.height;
+        let timestamp = block.header.timestamp.as_u64();
+
+        let block = match validator.validate_body(block).await {
+            Ok(block) => block,
+            Err(err @ ValidationError::BadBlockFound { .. }) |
+            Err(err @ ValidationError::FatalStorageError(_)) |
+            Err(err @ ValidationError::AsyncTaskFailed(_)) |
+            Err(err @ ValidationError::CustomError(_)) => return Err(err.into()),
+            Err(err) => {
+                // Add to bad blocks
+                self.insert_bad_block(header_hash, current_height).await;
+                return Err(err.into());
+            },
+        };
+
+        let block = ChainBlock::try_construct(Arc::new(block), header_accum_data)
+            .map(Arc::new)
+            .ok_or(BlockSyncError::FailedToConstructChainBlock)?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
+            timer.elapsed(),
+            block.header().height,
+            block.header().pow_algo(),
+            block.block().body.to_counts_string(),
+        );
+
+        let timer = Instant::now();
+        self.db
+            .write_transaction()
+            .insert_block_body(block.clone())
+            .set_best_block(
+                block.height(),
+                header_hash,
+                block.accumulated_data().total_accumulated_difficulty,
+                block.header().prev_hash,
+                timestamp,
+            )
+            .commit()
+            .await?;
+
+        self.hooks
+            .call_on_progress_block_hooks(block.clone(), tip_height, sync_peer);
+
+        debug!(
+            target: LOG_TARGET,
+            "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}",
+            block.height(),
+            timer.elapsed(),
+            block
+                .accumulated_data()
+                .total_accumulated_difficulty
+                .to_formatted_string(&Locale::en),
+            block.accumulated_data().accumulated_monero_difficulty,
+            block.accumulated_data().accumulated_sha_difficulty,
+        );
+        Ok(block)
+    }
+
+    async fn insert_bad_block(&self, hash: HashOutput, height: u64) {
+        if let Err(err) = self
+            .db
+            .write_transaction()
+            .insert_bad_block(hash, height)
+            .commit()
+            .await
+        {
+            error!(target: LOG_TARGET, "Failed to insert bad block: {}", err);
+        }
+    }
+
+    async fn record_reputation_event(&mut self, node_id: &NodeId, event: ReputationEvent) {
+        if let Err(err) = self.connectivity.record_reputation_event(node_id.clone(), event).await {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record reputation event {} for sync peer `{}`: {}", event, node_id, err
+            );
+        }
+    }
+
     async fn ban_peer<T: ToString>(&mut self, node_id: &NodeId, reason: T) -> Result<(), BlockSyncError> {
+        self.record_reputation_event(node_id, ReputationEvent::SyncValidationFailed)
+            .await;
         let reason = reason.to_string();
         if self.config.forced_sync_peers.contains(node_id) {
             debug!(
@@ -404,3 +590,154 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         Ok(())
     }
 }
+
+/// A window of synced blocks whose range proofs and signatures are being verified by the [BlockVerificationPool]
+struct PendingWindow {
+    blocks: Arc<Vec<Block>>,
+    accumulated_data: Vec<BlockHeaderAccumulatedData>,
+    verification: PendingVerification,
+}
+
+impl PendingWindow {
+    fn start(pool: &BlockVerificationPool, window: Vec<(Block, BlockHeaderAccumulatedData)>) -> Self {
+        let (blocks, accumulated_data): (Vec<_>, Vec<_>) = window.into_iter().unzip();
+        let blocks = Arc::new(blocks);
+        Self {
+            verification: pool.start_verification(blocks.clone()),
+            blocks,
+            accumulated_data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::chain_metadata::ChainMetadata;
+    use tari_comms::{
+        peer_manager::PeerFeatures,
+        protocol::rpc::{mock::MockRpcServer, NamedProtocolService},
+        test_utils::{mocks::create_connectivity_mock, node_identity::build_node_identity},
+    };
+    use tari_service_framework::reply_channel;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::{
+        base_node::{
+            chain_metadata_service::PeerChainMetadata,
+            sync::rpc::create_base_node_sync_rpc_service,
+            LocalNodeCommsInterface,
+        },
+        block_specs,
+        test_helpers::blockchain::{create_chained_blocks, create_new_blockchain},
+        transactions::CryptoFactories,
+        validation::mocks::MockValidator,
+    };
+
+    #[tokio::test]
+    async fn it_stores_the_blocks_before_an_invalid_block_in_a_window() {
+        // The sync peer serves a chain in which the kernel signature of block C is invalid
+        let peer_db = create_new_blockchain();
+        let genesis = peer_db
+            .fetch_block(0)
+            .unwrap()
+            .try_into_chain_block()
+            .map(Arc::new)
+            .unwrap();
+        let (names, chain) =
+            create_chained_blocks(block_specs!(["A->GB"], ["B->A"], ["C->B"], ["D->C"], ["E->D"]), genesis);
+        let mut bad_block = chain["C"].block().clone();
+        let mut kernels = bad_block.body.kernels().clone();
+        kernels[0].lock_height += 1;
+        bad_block.body = AggregateBody::new(
+            bad_block.body.inputs().clone(),
+            bad_block.body.outputs().clone(),
+            kernels,
+        );
+        for name in &names {
+            let block = if name == "C" {
+                Arc::new(bad_block.clone())
+            } else {
+                chain[name].to_arc_block()
+            };
+            peer_db.add_block(block).unwrap();
+        }
+
+        let peer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let (req_tx, _) = reply_channel::unbounded();
+        let (block_tx, _) = reply_channel::unbounded();
+        let (block_event_tx, _) = broadcast::channel(1);
+        let server = create_base_node_sync_rpc_service(
+            peer_db.clone().into(),
+            LocalNodeCommsInterface::new(req_tx, block_tx, block_event_tx),
+        );
+        let protocol_name = server.as_protocol_name();
+        let mut mock_server = MockRpcServer::new(server, peer_identity.clone());
+        mock_server.serve();
+        let connection = mock_server
+            .create_connection(peer_identity.to_peer(), protocol_name.into())
+            .await;
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        let connectivity_state = connectivity_mock.spawn();
+        connectivity_state.add_active_connection(connection).await;
+
+        // The headers have been synced, so only the block bodies are missing
+        let db = create_new_blockchain();
+        let headers = (1..=5).map(|height| peer_db.fetch_chain_header(height).unwrap());
+        db.insert_valid_headers(headers.collect()).unwrap();
+        let tip = peer_db.fetch_chain_header(5).unwrap();
+        let metadata = ChainMetadata::new(
+            tip.height(),
+            *tip.hash(),
+            0,
+            0,
+            tip.accumulated_data().total_accumulated_difficulty,
+            tip.header().timestamp.as_u64(),
+        );
+        let sync_peers = vec![
+            SyncPeer::from(PeerChainMetadata::new(
+                peer_identity.node_id().clone(),
+                metadata.clone(),
+                None,
+            )),
+            // A peer is only banned if another sync peer remains
+            SyncPeer::from(PeerChainMetadata::new(NodeId::new(), metadata, None)),
+        ];
+
+        // Blocks A to D are verified as one window
+        let config = BlockchainSyncConfig {
+            block_verification_window: 4,
+            ..Default::default()
+        };
+        let mut synchronizer = BlockSynchronizer::new(
+            config,
+            db.clone().into(),
+            connectivity,
+            sync_peers,
+            Arc::new(MockValidator::new(true)),
+            Arc::new(MockValidator::new(true)),
+            Some(BlockVerificationPool::new(CryptoFactories::default(), 2, false)),
+        );
+        let err = synchronizer.synchronize().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BlockSyncError::ValidationError(ValidationError::InvalidKernel { .. })
+            ),
+            "{}",
+            err
+        );
+
+        assert_eq!(db.get_height().unwrap(), 2);
+        assert!(db.bad_block_exists(*chain["C"].hash()).unwrap());
+        // Dial, reputation event and ban
+        connectivity_state.await_call_count(3).await;
+        let banned_peers = connectivity_state.take_banned_peers().await;
+        assert_eq!(banned_peers.len(), 1);
+        assert_eq!(&banned_peers[0].0, peer_identity.node_id());
+        assert!(connectivity_state
+            .take_reputation_events()
+            .await
+            .contains(&(peer_identity.node_id().clone(), ReputationEvent::SyncValidationFailed)));
+    }
+}
@@ -24,6 +24,7 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
+use tari_common_types::types::BlockHash;
 use tari_comms::peer_manager::NodeId;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,9 +49,17 @@ pub struct BlockchainSyncConfig {
     pub forced_sync_peers: Vec<NodeId>,
     /// Number of threads to use for validation
     pub validation_concurrency: usize,
+    /// Number of blocks for which range proofs and signatures are verified together during block sync, ahead of the
+    /// rest of the block validation
+    pub block_verification_window: usize,
     /// The RPC deadline to set on sync clients. If this deadline is reached, a new sync peer will be selected for
     /// sync.
     pub rpc_deadline: Duration,
+    /// Opt-in "assume-valid" fast sync. Blocks at or below this trusted block in the synced header chain skip the
+    /// expensive body checks (range proofs, signatures, scripts and covenants) during block sync. Header PoW, MMR
+    /// roots and the balance of each block are still checked.
+    #[serde(default, with = "optional_block_hash")]
+    pub assume_valid_block: Option<BlockHash>,
 }
 
 impl Default for BlockchainSyncConfig {
@@ -62,7 +71,33 @@ impl Default for BlockchainSyncConfig {
             short_ban_period: Duration::from_secs(60),
             forced_sync_peers: Default::default(),
             validation_concurrency: 6,
+            block_verification_window: 10,
             rpc_deadline: Duration::from_secs(10),
+            assume_valid_block: None,
+        }
+    }
+}
+
+mod optional_block_hash {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use tari_common_types::types::BlockHash;
+    use tari_utilities::hex::Hex;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<BlockHash>, D::Error>
+    where D: Deserializer<'de> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(hash) if !hash.is_empty() => BlockHash::from_hex(&hash)
+                .map(Some)
+                .map_err(|e| D::Error::custom(format!("Invalid block hash '{}': {}", hash, e))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn serialize<S>(hash: &Option<BlockHash>, s: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        match hash {
+            Some(hash) => s.serialize_str(&hash.to_hex()),
+            None => s.serialize_none(),
         }
     }
 }
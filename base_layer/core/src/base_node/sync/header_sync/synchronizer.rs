@@ -30,7 +30,7 @@ use log::*;
 use tari_common_types::{chain_metadata::ChainMetadata, types::HashOutput};
 use tari_comms::{
     connectivity::ConnectivityRequester,
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent},
     protocol::rpc::{RpcClient, RpcError, RpcHandshakeError},
     PeerConnection,
 };
@@ -167,6 +167,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
 
                 Err(err @ BlockHeaderSyncError::RpcError(RpcError::HandshakeError(RpcHandshakeError::TimedOut))) => {
                     warn!(target: LOG_TARGET, "{}", err);
+                    self.record_reputation_event(node_id, ReputationEvent::RpcTimeout).await;
                     self.ban_peer_short(node_id, BanReason::RpcNegotiationTimedOut).await?;
                 },
                 Err(BlockHeaderSyncError::ValidationFailed(err)) => {
@@ -216,6 +217,9 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
                 Err(err @ BlockHeaderSyncError::RpcError(RpcError::ReplyTimeout)) |
                 Err(err @ BlockHeaderSyncError::MaxLatencyExceeded { .. }) => {
                     warn!(target: LOG_TARGET, "{}", err);
+                    if matches!(err, BlockHeaderSyncError::RpcError(RpcError::ReplyTimeout)) {
+                        self.record_reputation_event(node_id, ReputationEvent::RpcTimeout).await;
+                    }
                     if i == self.sync_peers.len() - 1 {
                         return Err(BlockHeaderSyncError::AllSyncPeersExceedLatency);
                     }
@@ -248,9 +252,20 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
     }
 
     async fn ban_peer_long(&mut self, node_id: &NodeId, reason: BanReason) -> Result<(), BlockHeaderSyncError> {
+        self.record_reputation_event(node_id, ReputationEvent::SyncValidationFailed)
+            .await;
         self.ban_peer_for(node_id, reason, self.config.ban_period).await
     }
 
+    async fn record_reputation_event(&mut self, node_id: &NodeId, event: ReputationEvent) {
+        if let Err(err) = self.connectivity.record_reputation_event(node_id.clone(), event).await {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record reputation event {} for sync peer `{}`: {}", event, node_id, err
+            );
+        }
+    }
+
     async fn ban_peer_short(&mut self, node_id: &NodeId, reason: BanReason) -> Result<(), BlockHeaderSyncError> {
         self.ban_peer_for(node_id, reason, self.config.short_ban_period).await
     }
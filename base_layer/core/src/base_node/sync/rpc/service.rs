@@ -481,6 +481,17 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
             .rpc_status_internal_error(LOG_TARGET)?
             .ok_or_else(|| RpcStatus::not_found("Unknown end header"))?;
 
+        let kernel_pruned_height = db
+            .fetch_kernel_pruned_height()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        if kernel_pruned_height > 0 && start_header.height <= kernel_pruned_height {
+            return Err(RpcStatus::bad_request(&format!(
+                "Requested kernels from height {}, however this node has pruned kernels up to height {}",
+                start_header.height, kernel_pruned_height
+            )));
+        }
+
         let mut current_height = start_header.height;
         let end_height = end_header.height;
         let mut current_mmr_position = start_header.kernel_mmr_size;
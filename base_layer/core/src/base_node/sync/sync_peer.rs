@@ -21,12 +21,13 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    cmp::Ordering,
     fmt::{Display, Formatter},
     time::Duration,
 };
 
 use tari_common_types::chain_metadata::ChainMetadata;
-use tari_comms::peer_manager::NodeId;
+use tari_comms::peer_manager::{NodeId, ReputationTier};
 
 use crate::{base_node::chain_metadata_service::PeerChainMetadata, common::rolling_avg::RollingAverageTime};
 
@@ -34,6 +35,7 @@ use crate::{base_node::chain_metadata_service::PeerChainMetadata, common::rollin
 pub struct SyncPeer {
     peer_metadata: PeerChainMetadata,
     avg_latency: RollingAverageTime,
+    reputation: ReputationTier,
 }
 
 impl SyncPeer {
@@ -45,6 +47,10 @@ impl SyncPeer {
         self.peer_metadata.claimed_chain_metadata()
     }
 
+    pub fn kernel_pruned_height(&self) -> u64 {
+        self.peer_metadata.kernel_pruned_height()
+    }
+
     pub fn latency(&self) -> Option<Duration> {
         self.peer_metadata.latency()
     }
@@ -66,6 +72,30 @@ impl SyncPeer {
     pub fn calc_avg_latency(&self) -> Option<Duration> {
         self.avg_latency.calculate_average()
     }
+
+    /// The reputation of the peer at the time it was selected for sync
+    pub fn reputation(&self) -> ReputationTier {
+        self.reputation
+    }
+
+    pub(crate) fn set_reputation(&mut self, reputation: ReputationTier) -> &mut Self {
+        self.reputation = reputation;
+        self
+    }
+
+    /// Orders sync peers by preference: peers with a better reputation first, then lowest to highest latency. Peers
+    /// without a known latency go to the end of their reputation tier.
+    pub fn cmp_preference(&self, other: &Self) -> Ordering {
+        other
+            .reputation
+            .cmp(&self.reputation)
+            .then_with(|| match (self.latency(), other.latency()) {
+                (None, None) => Ordering::Equal,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(la), Some(lb)) => la.cmp(&lb),
+            })
+    }
 }
 
 impl From<PeerChainMetadata> for SyncPeer {
@@ -73,6 +103,7 @@ impl From<PeerChainMetadata> for SyncPeer {
         Self {
             peer_metadata,
             avg_latency: RollingAverageTime::new(20),
+            reputation: ReputationTier::default(),
         }
     }
 }
@@ -27,7 +27,7 @@ use crate::{
     consensus::ConsensusManager,
     transactions::CryptoFactories,
     validation::{
-        block_validators::BlockValidator,
+        block_validators::{BlockValidator, BlockVerificationPool},
         BlockSyncBodyValidation,
         ChainBalanceValidator,
         FinalHorizonStateValidation,
@@ -36,19 +36,29 @@ use crate::{
 
 #[derive(Clone)]
 pub struct SyncValidators<B> {
+    /// Validates the bodies of synced blocks. If `verification_pool` is set, this validator may skip the checks that
+    /// the pool has already done.
     pub block_body: Arc<dyn BlockSyncBodyValidation>,
+    /// Validates the bodies of blocks at or below the configured assume-valid block
+    pub assume_valid_block_body: Arc<dyn BlockSyncBodyValidation>,
     pub final_horizon_state: Arc<dyn FinalHorizonStateValidation<B>>,
+    /// Verifies range proofs and signatures for a window of synced blocks before `block_body` is used
+    pub verification_pool: Option<BlockVerificationPool>,
 }
 
 impl<B: BlockchainBackend + 'static> SyncValidators<B> {
+    /// Creates validators that fully validate every block body, regardless of the assume-valid block
     pub fn new<TBody, TFinal>(block_body: TBody, final_state: TFinal) -> Self
     where
         TBody: BlockSyncBodyValidation + 'static,
         TFinal: FinalHorizonStateValidation<B> + 'static,
     {
+        let block_body = Arc::new(block_body);
         Self {
-            block_body: Arc::new(block_body),
+            block_body: block_body.clone(),
+            assume_valid_block_body: block_body,
             final_horizon_state: Arc::new(final_state),
+            verification_pool: None,
         }
     }
 
@@ -59,16 +69,32 @@ impl<B: BlockchainBackend + 'static> SyncValidators<B> {
         bypass_range_proof_verification: bool,
         concurrency: usize,
     ) -> Self {
-        Self::new(
-            BlockValidator::new(
-                db,
-                rules.clone(),
-                factories.clone(),
-                bypass_range_proof_verification,
-                concurrency,
-            ),
-            ChainBalanceValidator::<B>::new(rules, factories),
+        let block_body = BlockValidator::new(
+            db.clone(),
+            rules.clone(),
+            factories.clone(),
+            bypass_range_proof_verification,
+            concurrency,
         )
+        .without_proof_and_signature_checks();
+        let assume_valid_block_body = BlockValidator::new(
+            db,
+            rules.clone(),
+            factories.clone(),
+            bypass_range_proof_verification,
+            concurrency,
+        )
+        .assume_valid();
+        Self {
+            block_body: Arc::new(block_body),
+            assume_valid_block_body: Arc::new(assume_valid_block_body),
+            final_horizon_state: Arc::new(ChainBalanceValidator::<B>::new(rules, factories.clone())),
+            verification_pool: Some(BlockVerificationPool::new(
+                factories,
+                concurrency,
+                bypass_range_proof_verification,
+            )),
+        }
     }
 }
 
@@ -30,6 +30,7 @@ use tari_script::script;
 
 use crate::{
     blocks::{block::Block, BlockHeader, BlockHeaderAccumulatedData, ChainBlock},
+    consensus::custom_network::expect_custom_network,
     covenants::Covenant,
     proof_of_work::{PowAlgorithm, ProofOfWork},
     transactions::{
@@ -51,7 +52,7 @@ use crate::{
 
 /// Returns the genesis block for the selected network.
 pub fn get_genesis_block(network: Network) -> ChainBlock {
-    use Network::{Dibbler, Esmeralda, Igor, LocalNet, MainNet, Ridcully, Stibbons, Weatherwax};
+    use Network::{Custom, Dibbler, Esmeralda, Igor, LocalNet, MainNet, Ridcully, Stibbons, Weatherwax};
     match network {
         MainNet => get_mainnet_genesis_block(),
         Igor => get_igor_genesis_block(),
@@ -61,6 +62,7 @@ pub fn get_genesis_block(network: Network) -> ChainBlock {
         Ridcully => unimplemented!("Ridcully is longer supported"),
         Stibbons => unimplemented!("Stibbons is longer supported"),
         Weatherwax => unimplemented!("Weatherwax longer supported"),
+        Custom => get_custom_genesis_block(),
     }
 }
 
@@ -68,6 +70,21 @@ pub fn get_mainnet_genesis_block() -> ChainBlock {
     unimplemented!()
 }
 
+/// Returns the genesis block of the loaded custom network definition. Panics if no definition has been loaded.
+pub fn get_custom_genesis_block() -> ChainBlock {
+    let block = expect_custom_network().genesis_block().clone();
+    let accumulated_data = BlockHeaderAccumulatedData {
+        hash: block.hash(),
+        total_kernel_offset: block.header.total_kernel_offset.clone(),
+        achieved_difficulty: 1.into(),
+        total_accumulated_difficulty: 1,
+        accumulated_monero_difficulty: 1.into(),
+        accumulated_sha_difficulty: 1.into(),
+        target_difficulty: 1.into(),
+    };
+    ChainBlock::try_construct(Arc::new(block), accumulated_data).unwrap()
+}
+
 pub fn get_igor_genesis_block() -> ChainBlock {
     // lets get the block
     let block = get_igor_genesis_block_raw();
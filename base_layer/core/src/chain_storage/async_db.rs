@@ -48,6 +48,7 @@ use crate::{
         blockchain_database::MmrRoots,
         utxo_mined_info::UtxoMinedInfo,
         BlockAddResult,
+        BlockUtxoStats,
         BlockchainBackend,
         BlockchainDatabase,
         ChainStorageError,
@@ -55,8 +56,11 @@ use crate::{
         DbTotalSizeStats,
         DbTransaction,
         HorizonData,
+        KernelMmrProof,
         MmrTree,
+        OutputMmrProof,
         PrunedOutput,
+        ScriptIndexKey,
         TargetDifficulties,
     },
     common::rolling_vec::RollingVec,
@@ -168,13 +172,21 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     //---------------------------------- Kernel --------------------------------------------//
     make_async_fn!(fetch_kernel_by_excess_sig(excess_sig: Signature) -> Option<(TransactionKernel, HashOutput)>, "fetch_kernel_by_excess_sig");
 
+    make_async_fn!(fetch_block_hash_by_kernel_excess_sig(excess_sig: Signature) -> Option<HashOutput>, "fetch_block_hash_by_kernel_excess_sig");
+
     make_async_fn!(fetch_kernels_in_block(hash: HashOutput) -> Vec<TransactionKernel>, "fetch_kernels_in_block");
 
+    make_async_fn!(fetch_kernel_pruned_height() -> u64, "fetch_kernel_pruned_height");
+
     //---------------------------------- MMR --------------------------------------------//
     make_async_fn!(prepare_new_block(template: NewBlockTemplate) -> Block, "prepare_new_block");
 
     make_async_fn!(fetch_mmr_size(tree: MmrTree) -> u64, "fetch_mmr_size");
 
+    make_async_fn!(generate_output_mmr_proof(hashes: Vec<HashOutput>) -> OutputMmrProof, "generate_output_mmr_proof");
+
+    make_async_fn!(generate_kernel_mmr_proof(hashes: Vec<HashOutput>) -> KernelMmrProof, "generate_kernel_mmr_proof");
+
     make_async_fn!(calculate_mmr_roots(block: Block) -> (Block, MmrRoots), "calculate_mmr_roots");
 
     //---------------------------------- Headers --------------------------------------------//
@@ -264,6 +276,14 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     make_async_fn!(get_stats() -> DbBasicStats, "get_stats");
 
     make_async_fn!(fetch_total_size_stats() -> DbTotalSizeStats, "fetch_total_size_stats");
+
+    make_async_fn!(fetch_utxo_stats(start_height: u64, end_height: u64) -> Vec<BlockUtxoStats>, "fetch_utxo_stats");
+
+    make_async_fn!(clear_all_utxo_stats() -> (), "clear_all_utxo_stats");
+
+    make_async_fn!(fetch_outputs_by_script_index(key: ScriptIndexKey, start_height: u64, end_height: u64, limit: usize) -> Vec<UtxoMinedInfo>, "fetch_outputs_by_script_index");
+
+    make_async_fn!(clear_script_index() -> (), "clear_script_index");
 }
 
 impl<B: BlockchainBackend + 'static> From<BlockchainDatabase<B>> for AsyncBlockchainDb<B> {
@@ -19,6 +19,7 @@ use crate::{
     },
     chain_storage::{
         pruned_output::PrunedOutput,
+        BlockUtxoStats,
         ChainStorageError,
         DbBasicStats,
         DbKey,
@@ -28,6 +29,8 @@ use crate::{
         HorizonData,
         MmrTree,
         Reorg,
+        ScriptIndexBlock,
+        ScriptIndexKey,
         UtxoMinedInfo,
     },
     transactions::transaction_components::{TransactionInput, TransactionKernel},
@@ -102,6 +105,13 @@ pub trait BlockchainBackend: Send + Sync {
         excess_sig: &Signature,
     ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
 
+    /// Returns the hash of the block containing a kernel with this excess signature. Unlike
+    /// `fetch_kernel_by_excess_sig`, this also finds kernels that have been pruned.
+    fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError>;
+
     /// Fetch all UTXOs and spends in the block
     fn fetch_utxos_in_block(
         &self,
@@ -145,6 +155,8 @@ pub trait BlockchainBackend: Send + Sync {
     fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError>;
     /// Returns the stored chain metadata.
     fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError>;
+    /// Returns the height up to and including which kernels have been pruned, or 0 if no kernels have been pruned.
+    fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError>;
     /// Returns the UTXO count
     fn utxo_count(&self) -> Result<usize, ChainStorageError>;
     /// Returns the kernel count
@@ -191,4 +203,262 @@ pub trait BlockchainBackend: Send + Sync {
 
     /// Fetches all tracked reorgs
     fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError>;
+
+    /// Fetches the UTXO statistics index entries from `start_height` to `end_height` inclusive, ordered by height.
+    /// Heights without an entry are skipped.
+    fn fetch_utxo_stats(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockUtxoStats>, ChainStorageError>;
+
+    /// Fetches the UTXO statistics index entry with the greatest height, if any
+    fn fetch_last_utxo_stats(&self) -> Result<Option<BlockUtxoStats>, ChainStorageError>;
+
+    /// Fetches the script index record of the block at `height`, if any
+    fn fetch_script_index_block(&self, height: u64) -> Result<Option<ScriptIndexBlock>, ChainStorageError>;
+
+    /// Fetches the script index record with the greatest height, if any
+    fn fetch_last_script_index_block(&self) -> Result<Option<ScriptIndexBlock>, ChainStorageError>;
+
+    /// Fetches the mined heights and hashes of the outputs matching `key` that were mined from `start_height` to
+    /// `end_height` inclusive, ordered by height. At most `limit` outputs are returned, except that all of the matching
+    /// outputs at the height of the last output returned are included.
+    fn fetch_outputs_by_script_index(
+        &self,
+        key: &ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError>;
+}
+
+/// Forwards to the boxed backend, allowing the backend to be chosen at runtime e.g. `Box<dyn BlockchainBackend>`
+#[allow(clippy::ptr_arg)]
+impl<B: BlockchainBackend + ?Sized> BlockchainBackend for Box<B> {
+    fn write(&mut self, tx: DbTransaction) -> Result<(), ChainStorageError> {
+        (**self).write(tx)
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        (**self).fetch(key)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        (**self).contains(key)
+    }
+
+    fn fetch_chain_header_by_height(&self, height: u64) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_chain_header_by_height(height)
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError> {
+        (**self).fetch_header_accumulated_data(hash)
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_chain_header_in_all_chains(hash)
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_header_containing_kernel_mmr(mmr_position)
+    }
+
+    fn fetch_header_containing_utxo_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_header_containing_utxo_mmr(mmr_position)
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        (**self).is_empty()
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        (**self).fetch_block_accumulated_data(header_hash)
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        (**self).fetch_block_accumulated_data_by_height(height)
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        (**self).fetch_kernels_in_block(header_hash)
+    }
+
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &[u8],
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        (**self).fetch_kernel_by_excess(excess)
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        (**self).fetch_kernel_by_excess_sig(excess_sig)
+    }
+
+    fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        (**self).fetch_block_hash_by_kernel_excess_sig(excess_sig)
+    }
+
+    fn fetch_utxos_in_block(
+        &self,
+        header_hash: &HashOutput,
+        deleted: Option<&Bitmap>,
+    ) -> Result<(Vec<PrunedOutput>, Bitmap), ChainStorageError> {
+        (**self).fetch_utxos_in_block(header_hash, deleted)
+    }
+
+    fn fetch_output(&self, output_hash: &HashOutput) -> Result<Option<UtxoMinedInfo>, ChainStorageError> {
+        (**self).fetch_output(output_hash)
+    }
+
+    fn fetch_unspent_output_hash_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        (**self).fetch_unspent_output_hash_by_commitment(commitment)
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        (**self).fetch_outputs_in_block(header_hash)
+    }
+
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        (**self).fetch_inputs_in_block(header_hash)
+    }
+
+    fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
+        (**self).fetch_mmr_size(tree)
+    }
+
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &HashOutput) -> Result<Option<u32>, ChainStorageError> {
+        (**self).fetch_mmr_leaf_index(tree, hash)
+    }
+
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).orphan_count()
+    }
+
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        (**self).fetch_last_header()
+    }
+
+    fn clear_all_pending_headers(&self) -> Result<usize, ChainStorageError> {
+        (**self).clear_all_pending_headers()
+    }
+
+    fn fetch_last_chain_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_last_chain_header()
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_tip_header()
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        (**self).fetch_chain_metadata()
+    }
+
+    fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        (**self).fetch_kernel_pruned_height()
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).utxo_count()
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).kernel_count()
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        (**self).fetch_orphan_chain_tip_by_hash(hash)
+    }
+
+    fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        (**self).fetch_orphan_children_of(hash)
+    }
+
+    fn fetch_orphan_chain_block(&self, hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError> {
+        (**self).fetch_orphan_chain_block(hash)
+    }
+
+    fn fetch_deleted_bitmap(&self) -> Result<DeletedBitmap, ChainStorageError> {
+        (**self).fetch_deleted_bitmap()
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        horizon_height: u64,
+        orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError> {
+        (**self).delete_oldest_orphans(horizon_height, orphan_storage_capacity)
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
+        (**self).fetch_monero_seed_first_seen_height(seed)
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        (**self).fetch_horizon_data()
+    }
+
+    fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
+        (**self).get_stats()
+    }
+
+    fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
+        (**self).fetch_total_size_stats()
+    }
+
+    fn fetch_header_hash_by_deleted_mmr_positions(
+        &self,
+        mmr_positions: Vec<u32>,
+    ) -> Result<Vec<Option<(u64, HashOutput)>>, ChainStorageError> {
+        (**self).fetch_header_hash_by_deleted_mmr_positions(mmr_positions)
+    }
+
+    fn bad_block_exists(&self, block_hash: HashOutput) -> Result<bool, ChainStorageError> {
+        (**self).bad_block_exists(block_hash)
+    }
+
+    fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
+        (**self).fetch_all_reorgs()
+    }
+
+    fn fetch_utxo_stats(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockUtxoStats>, ChainStorageError> {
+        (**self).fetch_utxo_stats(start_height, end_height)
+    }
+
+    fn fetch_last_utxo_stats(&self) -> Result<Option<BlockUtxoStats>, ChainStorageError> {
+        (**self).fetch_last_utxo_stats()
+    }
+
+    fn fetch_script_index_block(&self, height: u64) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        (**self).fetch_script_index_block(height)
+    }
+
+    fn fetch_last_script_index_block(&self) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        (**self).fetch_last_script_index_block()
+    }
+
+    fn fetch_outputs_by_script_index(
+        &self,
+        key: &ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError> {
+        (**self).fetch_outputs_by_script_index(key, start_height, end_height, limit)
+    }
 }
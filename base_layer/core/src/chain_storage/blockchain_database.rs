@@ -28,6 +28,7 @@ use std::{
     mem,
     ops::{Bound, RangeBounds},
     sync::{atomic, atomic::AtomicBool, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread,
     time::Instant,
 };
 
@@ -63,17 +64,24 @@ use crate::{
         },
         db_transaction::{DbKey, DbTransaction, DbValue},
         error::ChainStorageError,
+        mmr_proof,
         pruned_output::PrunedOutput,
         utxo_mined_info::UtxoMinedInfo,
         BlockAddResult,
+        BlockUtxoStats,
         BlockchainBackend,
         DbBasicStats,
         DbTotalSizeStats,
         HorizonData,
+        KernelMmrProof,
         MmrTree,
         Optional,
         OrNotFound,
+        OutputMmrProof,
         Reorg,
+        ScriptIndexBlock,
+        ScriptIndexKey,
+        ScriptIndexedOutput,
         TargetDifficulties,
     },
     common::rolling_vec::RollingVec,
@@ -94,6 +102,10 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "c::cs::database";
+/// The number of blocks that the UTXO statistics index is built for each time the write lock is held
+const UTXO_STATS_BATCH_SIZE: u64 = 1000;
+/// The number of blocks that the script index is built for each time the write lock is held
+const SCRIPT_INDEX_BATCH_SIZE: u64 = 1000;
 
 /// Configuration for the BlockchainDatabase.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -102,8 +114,14 @@ pub struct BlockchainDatabaseConfig {
     pub orphan_storage_capacity: usize,
     pub pruning_horizon: u64,
     pub pruning_interval: u64,
+    /// The chain data that a pruned node removes below its pruned height
+    pub pruning_level: PruningLevel,
     pub track_reorgs: bool,
     pub cleanup_orphans_at_startup: bool,
+    /// Maintain an index of UTXO set statistics for every block on the main chain
+    pub track_utxo_stats: bool,
+    /// Maintain an index of main chain outputs by script hash and sender offset public key
+    pub track_script_index: bool,
 }
 
 impl Default for BlockchainDatabaseConfig {
@@ -112,12 +130,29 @@ impl Default for BlockchainDatabaseConfig {
             orphan_storage_capacity: BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY,
             pruning_horizon: BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
             pruning_interval: BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
+            pruning_level: PruningLevel::Outputs,
             track_reorgs: false,
             cleanup_orphans_at_startup: false,
+            track_utxo_stats: false,
+            track_script_index: false,
         }
     }
 }
 
+/// The chain data that a pruned node removes from blocks below its pruned height. Headers and the accumulated data of
+/// every block (the kernel and output sums and MMR peaks) are always kept, so that new blocks can be validated and
+/// pruned outputs can still be served for horizon sync.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruningLevel {
+    /// Spent outputs and the inputs that spend them are pruned
+    Outputs,
+    /// Kernels are pruned as well. Kernel excess signatures are kept so that kernels can still be checked for
+    /// uniqueness, but the node can no longer serve kernels for horizon sync or kernel MMR proofs. The kernel pruned
+    /// height is advertised with the node's chain metadata so that peers do not select it for horizon sync.
+    Kernels,
+}
+
 /// A placeholder struct that contains the two validators that the database uses to decide whether or not a block is
 /// eligible to be added to the database. The `block` validator should perform a full consensus check. The `orphan`
 /// validator needs to check that the block is internally consistent, but can't know whether the PoW is sufficient,
@@ -196,6 +231,8 @@ pub struct BlockchainDatabase<B> {
     consensus_manager: ConsensusManager,
     difficulty_calculator: Arc<DifficultyCalculator>,
     disable_add_block_flag: Arc<AtomicBool>,
+    utxo_stats_ready_flag: Arc<AtomicBool>,
+    script_index_ready_flag: Arc<AtomicBool>,
 }
 
 #[allow(clippy::ptr_arg)]
@@ -209,7 +246,10 @@ where B: BlockchainBackend
         validators: Validators<B>,
         config: BlockchainDatabaseConfig,
         difficulty_calculator: DifficultyCalculator,
-    ) -> Result<Self, ChainStorageError> {
+    ) -> Result<Self, ChainStorageError>
+    where
+        B: 'static,
+    {
         debug!(target: LOG_TARGET, "BlockchainDatabase config: {:?}", config);
         let is_empty = db.is_empty()?;
         let blockchain_db = BlockchainDatabase {
@@ -219,6 +259,8 @@ where B: BlockchainBackend
             consensus_manager,
             difficulty_calculator: Arc::new(difficulty_calculator),
             disable_add_block_flag: Arc::new(AtomicBool::new(false)),
+            utxo_stats_ready_flag: Arc::new(AtomicBool::new(false)),
+            script_index_ready_flag: Arc::new(AtomicBool::new(false)),
         };
         let genesis_block = Arc::new(blockchain_db.consensus_manager.get_genesis_block());
         if is_empty {
@@ -279,6 +321,17 @@ where B: BlockchainBackend
             blockchain_db.clear_all_reorgs()?;
         }
 
+        if config.track_utxo_stats {
+            blockchain_db.start_building_index("UTXO statistics index", Self::update_utxo_stats_batch)?;
+        }
+        if config.track_script_index {
+            blockchain_db.start_building_index("script index", Self::update_script_index_batch)?;
+        }
+        {
+            let mut db = blockchain_db.db_write_access()?;
+            blockchain_db.update_indexes(&mut *db)?;
+        }
+
         Ok(blockchain_db)
     }
 
@@ -338,9 +391,72 @@ where B: BlockchainBackend
         self.disable_add_block_flag.store(false, atomic::Ordering::Release);
     }
 
+    fn is_utxo_stats_ready(&self) -> bool {
+        self.utxo_stats_ready_flag.load(atomic::Ordering::Acquire)
+    }
+
+    fn is_script_index_ready(&self) -> bool {
+        self.script_index_ready_flag.load(atomic::Ordering::Acquire)
+    }
+
+    /// Calculates the first batch of an optional index using `update_batch` and, if the index has not caught up with
+    /// the main chain after that, builds the rest of it in the background. The write lock is released between batches,
+    /// so the index can be built for the whole chain while the node is running. Until it has caught up, the index is
+    /// not updated when blocks are added.
+    fn start_building_index(
+        &self,
+        index_name: &'static str,
+        update_batch: fn(&Self) -> Result<bool, ChainStorageError>,
+    ) -> Result<(), ChainStorageError>
+    where
+        B: 'static,
+    {
+        if update_batch(self)? {
+            return Ok(());
+        }
+        info!(target: LOG_TARGET, "Building the {} in the background", index_name);
+        let db = self.clone();
+        thread::spawn(move || loop {
+            match update_batch(&db) {
+                Ok(true) => {
+                    info!(target: LOG_TARGET, "The {} is up to date", index_name);
+                    break;
+                },
+                Ok(false) => thread::yield_now(),
+                Err(err) => {
+                    error!(target: LOG_TARGET, "Failed to build the {}: {}", index_name, err);
+                    break;
+                },
+            }
+        });
+        Ok(())
+    }
+
+    /// Calculates the next batch of the UTXO statistics index and returns true once it has caught up with the main
+    /// chain
+    fn update_utxo_stats_batch(&self) -> Result<bool, ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        let is_ready = update_utxo_stats(&mut *db, UTXO_STATS_BATCH_SIZE)?;
+        if is_ready {
+            self.utxo_stats_ready_flag.store(true, atomic::Ordering::Release);
+        }
+        Ok(is_ready)
+    }
+
+    /// Indexes the next batch of blocks in the script index and returns true once it has caught up with the main chain
+    fn update_script_index_batch(&self) -> Result<bool, ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        let is_ready = update_script_index(&mut *db, SCRIPT_INDEX_BATCH_SIZE)?;
+        if is_ready {
+            self.script_index_ready_flag.store(true, atomic::Ordering::Release);
+        }
+        Ok(is_ready)
+    }
+
     pub fn write(&self, transaction: DbTransaction) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
-        db.write(transaction)
+        db.write(transaction)?;
+        self.update_indexes(&mut *db)
     }
 
     /// Returns the height of the current longest chain. This method will only fail if there's a fairly serious
@@ -424,11 +540,27 @@ where B: BlockchainBackend
         db.fetch_kernel_by_excess_sig(&excess_sig)
     }
 
+    /// Returns the hash of the block containing a kernel with this excess signature, including kernels that have been
+    /// pruned.
+    pub fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_block_hash_by_kernel_excess_sig(&excess_sig)
+    }
+
     pub fn fetch_kernels_in_block(&self, hash: HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.fetch_kernels_in_block(&hash)
     }
 
+    /// Returns the height up to and including which kernels have been pruned, or 0 if no kernels have been pruned.
+    pub fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_kernel_pruned_height()
+    }
+
     pub fn fetch_utxos_in_block(
         &self,
         hash: HashOutput,
@@ -838,6 +970,31 @@ where B: BlockchainBackend
         db.fetch_mmr_size(tree)
     }
 
+    /// Returns a batch inclusion proof for the given output hashes against the output MMR root at the tip. Hashes that
+    /// are not in the output MMR are left out of the proof.
+    ///
+    /// This rebuilds the output MMR from every block in the chain, so is expensive. The read lock is released between
+    /// blocks, so the proof fails if the chain reorgs below the tip while it is generated.
+    pub fn generate_output_mmr_proof(&self, hashes: Vec<HashOutput>) -> Result<OutputMmrProof, ChainStorageError> {
+        mmr_proof::generate_output_proof(self, &hashes)
+    }
+
+    /// Returns a batch inclusion proof for the given kernel hashes against the kernel MMR root at the tip. Hashes that
+    /// are not in the kernel MMR are left out of the proof.
+    ///
+    /// As with `generate_output_mmr_proof`, this rebuilds the kernel MMR from every block in the chain, so is
+    /// expensive. It is not available once kernels have been pruned.
+    pub fn generate_kernel_mmr_proof(&self, hashes: Vec<HashOutput>) -> Result<KernelMmrProof, ChainStorageError> {
+        let kernel_pruned_height = self.fetch_kernel_pruned_height()?;
+        if kernel_pruned_height > 0 {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Kernel MMR proofs are not available because kernels are pruned up to height {}",
+                kernel_pruned_height
+            )));
+        }
+        mmr_proof::generate_kernel_proof(self, &hashes)
+    }
+
     /// Tries to add a block to the longest chain.
     ///
     /// The block is added to the longest chain if and only if
@@ -925,8 +1082,10 @@ where B: BlockchainBackend
                 "Best chain is now at height: {}",
                 db.fetch_chain_metadata()?.height_of_longest_chain()
             );
+            // Update the indexes before pruning so that the new blocks' outputs are still available
+            self.update_indexes(&mut *db)?;
             // If blocks were added and the node is in pruned mode, perform pruning
-            prune_database_if_needed(&mut *db, self.config.pruning_horizon, self.config.pruning_interval)?;
+            prune_database_if_needed(&mut *db, &self.config)?;
         }
 
         if let Err(e) = cleanup_orphans(&mut *db, self.config.orphan_storage_capacity) {
@@ -980,7 +1139,7 @@ where B: BlockchainBackend
     /// Prunes the blockchain up to and including the given height
     pub fn prune_to_height(&self, height: u64) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
-        prune_to_height(&mut *db, height)
+        prune_to_height(&mut *db, height, self.config.pruning_level)
     }
 
     /// Fetch a block from the blockchain database.
@@ -1078,7 +1237,9 @@ where B: BlockchainBackend
     /// * The block height is in the future
     pub fn rewind_to_height(&self, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_height(&mut *db, height)
+        let removed_blocks = rewind_to_height(&mut *db, height)?;
+        self.update_indexes(&mut *db)?;
+        Ok(removed_blocks)
     }
 
     /// Rewind the blockchain state to the block hash making the block at that hash the new tip.
@@ -1089,7 +1250,9 @@ where B: BlockchainBackend
     /// * The block hash is before the horizon block height determined by the pruning horizon
     pub fn rewind_to_hash(&self, hash: BlockHash) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_hash(&mut *db, hash)
+        let removed_blocks = rewind_to_hash(&mut *db, hash)?;
+        self.update_indexes(&mut *db)?;
+        Ok(removed_blocks)
     }
 
     pub fn fetch_horizon_data(&self) -> Result<HorizonData, ChainStorageError> {
@@ -1163,6 +1326,97 @@ where B: BlockchainBackend
         txn.clear_all_reorgs();
         db.write(txn)
     }
+
+    /// Returns the UTXO statistics for each main chain block from `start_height` to `end_height` inclusive. The
+    /// operation will fail if the UTXO statistics index is not enabled with `track_utxo_stats` or is still being built.
+    pub fn fetch_utxo_stats(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<BlockUtxoStats>, ChainStorageError> {
+        if !self.config.track_utxo_stats {
+            return Err(ChainStorageError::InvalidOperation(
+                "The UTXO statistics index is not enabled. Set track_utxo_stats to true to enable it.".to_string(),
+            ));
+        }
+        if !self.is_utxo_stats_ready() {
+            return Err(ChainStorageError::InvalidOperation(
+                "The UTXO statistics index is still being built. Try again later.".to_string(),
+            ));
+        }
+        let db = self.db_read_access()?;
+        db.fetch_utxo_stats(start_height, end_height)
+    }
+
+    /// Removes the UTXO statistics index. The index is kept when `track_utxo_stats` is turned off, so that it does not
+    /// have to be built for the whole chain again when it is turned back on, and can only be removed while it is off.
+    pub fn clear_all_utxo_stats(&self) -> Result<(), ChainStorageError> {
+        if self.config.track_utxo_stats {
+            return Err(ChainStorageError::InvalidOperation(
+                "The UTXO statistics index cannot be cleared while track_utxo_stats is enabled".to_string(),
+            ));
+        }
+        let mut db = self.db_write_access()?;
+        let mut txn = DbTransaction::new();
+        txn.clear_all_utxo_stats();
+        db.write(txn)
+    }
+
+    /// Returns the outputs matching `key` that were mined from `start_height` to `end_height` inclusive, ordered by
+    /// mined height. At most `limit` outputs are returned, except that all of the matching outputs at the height of
+    /// the last output returned are included, so the next page can start at the height after it. The operation will
+    /// fail if the script index is not enabled with `track_script_index` or is still being built.
+    pub fn fetch_outputs_by_script_index(
+        &self,
+        key: ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<UtxoMinedInfo>, ChainStorageError> {
+        if !self.config.track_script_index {
+            return Err(ChainStorageError::InvalidOperation(
+                "The script index is not enabled. Set track_script_index to true to enable it.".to_string(),
+            ));
+        }
+        if !self.is_script_index_ready() {
+            return Err(ChainStorageError::InvalidOperation(
+                "The script index is still being built. Try again later.".to_string(),
+            ));
+        }
+        let db = self.db_read_access()?;
+        db.fetch_outputs_by_script_index(&key, start_height, end_height, limit)?
+            .into_iter()
+            .map(|(_, hash)| {
+                db.fetch_output(&hash)
+                    .or_not_found("TransactionOutput", "hash", hash.to_hex())
+            })
+            .collect()
+    }
+
+    /// Removes the script index. As with the UTXO statistics index, the script index is kept when `track_script_index`
+    /// is turned off and can only be removed while it is off.
+    pub fn clear_script_index(&self) -> Result<(), ChainStorageError> {
+        if self.config.track_script_index {
+            return Err(ChainStorageError::InvalidOperation(
+                "The script index cannot be cleared while track_script_index is enabled".to_string(),
+            ));
+        }
+        let mut db = self.db_write_access()?;
+        let mut txn = DbTransaction::new();
+        txn.clear_script_index();
+        db.write(txn)
+    }
+
+    /// Brings the enabled optional indexes in line with the main chain
+    fn update_indexes(&self, db: &mut B) -> Result<(), ChainStorageError> {
+        if self.config.track_utxo_stats && self.is_utxo_stats_ready() {
+            update_utxo_stats(db, u64::MAX)?;
+        }
+        if self.config.track_script_index && self.is_script_index_ready() {
+            update_script_index(db, u64::MAX)?;
+        }
+        Ok(())
+    }
 }
 
 fn unexpected_result<T>(request: DbKey, response: DbValue) -> Result<T, ChainStorageError> {
@@ -1584,11 +1838,10 @@ fn fetch_block_with_kernel<T: BlockchainBackend>(
     db: &T,
     excess_sig: Signature,
 ) -> Result<Option<HistoricalBlock>, ChainStorageError> {
-    match db.fetch_kernel_by_excess_sig(&excess_sig) {
-        Ok(kernel) => match kernel {
-            Some((_kernel, hash)) => fetch_block_by_hash(db, hash),
-            None => Ok(None),
-        },
+    // Look up the block hash rather than the kernel so that blocks containing pruned kernels are also found
+    match db.fetch_block_hash_by_kernel_excess_sig(&excess_sig) {
+        Ok(Some(hash)) => fetch_block_by_hash(db, hash),
+        Ok(None) => Ok(None),
         Err(_) => Err(ChainStorageError::ValueNotFound {
             entity: "Kernel",
             field: "Excess sig",
@@ -2252,10 +2505,172 @@ fn cleanup_orphans<T: BlockchainBackend>(db: &mut T, orphan_storage_capacity: us
     db.delete_oldest_orphans(horizon_height, orphan_storage_capacity)
 }
 
+/// Brings the UTXO statistics index in line with the main chain, calculating the entries for at most `max_blocks`
+/// blocks. Entries above the tip or for blocks that are no longer on the main chain are removed, and entries are then
+/// calculated for the blocks after the last remaining entry. Blocks at or below the pruned height of a pruned node are
+/// not indexed, as a horizon synced node does not have their data. Returns true if the index has reached the tip.
+fn update_utxo_stats<T: BlockchainBackend>(db: &mut T, max_blocks: u64) -> Result<bool, ChainStorageError> {
+    const BATCH_SIZE: usize = 1000;
+
+    let metadata = db.fetch_chain_metadata()?;
+    let tip_height = metadata.height_of_longest_chain();
+    let mut txn = DbTransaction::new();
+    let mut last_stats = None;
+    let mut next_height = db.fetch_last_utxo_stats()?.map(|s| s.height + 1).unwrap_or_default();
+    while next_height > 0 {
+        let height = next_height - 1;
+        match db.fetch_utxo_stats(height, height)?.pop() {
+            Some(stats)
+                if height <= tip_height && *db.fetch_chain_header_by_height(height)?.hash() == stats.block_hash =>
+            {
+                last_stats = Some(stats);
+                break;
+            },
+            Some(_) => {
+                txn.delete_utxo_stats(height);
+            },
+            None => {},
+        }
+        next_height = height;
+    }
+    if metadata.pruned_height() > 0 {
+        next_height = cmp::max(next_height, metadata.pruned_height() + 1);
+    }
+
+    let end_height = cmp::min(tip_height, next_height.saturating_add(max_blocks.saturating_sub(1)));
+    if next_height <= end_height {
+        debug!(
+            target: LOG_TARGET,
+            "Calculating UTXO stats for blocks #{} to #{}", next_height, end_height
+        );
+        let mut total_spent = outputs_spent_before(db, next_height, tip_height, last_stats.as_ref())?;
+        for height in next_height..=end_height {
+            let outputs_spent = fetch_outputs_spent_in_block(db, height)?;
+            total_spent += outputs_spent;
+            let header = db.fetch_chain_header_by_height(height)?;
+            let outputs = db.fetch_outputs_in_block(header.hash())?;
+            txn.insert_utxo_stats(BlockUtxoStats::from_block_outputs(
+                header.header(),
+                *header.hash(),
+                &outputs,
+                outputs_spent,
+                total_spent,
+            ));
+
+            if txn.operations().len() >= BATCH_SIZE {
+                db.write(mem::take(&mut txn))?;
+                debug!(target: LOG_TARGET, "UTXO stats calculated up to block #{}", height);
+            }
+        }
+    }
+
+    if !txn.operations().is_empty() {
+        db.write(txn)?;
+    }
+    Ok(end_height == tip_height)
+}
+
+/// Returns the number of outputs spent on the main chain before the block at `height`, using the index entry of the
+/// previous block if there is one. Otherwise, as the deleted bitmap of each block only contains the outputs spent in
+/// that block, it is worked out from the deleted bitmap at the tip.
+fn outputs_spent_before<T: BlockchainBackend>(
+    db: &T,
+    height: u64,
+    tip_height: u64,
+    prev_stats: Option<&BlockUtxoStats>,
+) -> Result<u64, ChainStorageError> {
+    if height == 0 {
+        return Ok(0);
+    }
+    if let Some(stats) = prev_stats.filter(|stats| stats.height + 1 == height) {
+        let header = db.fetch_chain_header_by_height(stats.height)?;
+        return Ok(header.header().output_mmr_size.saturating_sub(stats.utxo_set_size));
+    }
+    let spent_since = (height..=tip_height)
+        .map(|height| fetch_outputs_spent_in_block(db, height))
+        .sum::<Result<u64, _>>()?;
+    Ok(db
+        .fetch_deleted_bitmap()?
+        .bitmap()
+        .cardinality()
+        .saturating_sub(spent_since))
+}
+
+fn fetch_outputs_spent_in_block<T: BlockchainBackend>(db: &T, height: u64) -> Result<u64, ChainStorageError> {
+    db.fetch_block_accumulated_data_by_height(height)
+        .or_not_found("BlockAccumulatedData", "height", height.to_string())
+        .map(|data| data.deleted().cardinality())
+}
+
+/// Brings the script index in line with the main chain in the same way as [update_utxo_stats], indexing at most
+/// `max_blocks` blocks. Outputs that have already been pruned cannot be indexed, so they are left out. Returns true if
+/// the index has reached the tip.
+fn update_script_index<T: BlockchainBackend>(db: &mut T, max_blocks: u64) -> Result<bool, ChainStorageError> {
+    const BATCH_SIZE: usize = 1000;
+
+    let metadata = db.fetch_chain_metadata()?;
+    let tip_height = metadata.height_of_longest_chain();
+    let mut txn = DbTransaction::new();
+    let mut next_height = db
+        .fetch_last_script_index_block()?
+        .map(|b| b.height + 1)
+        .unwrap_or_default();
+    while next_height > 0 {
+        let height = next_height - 1;
+        match db.fetch_script_index_block(height)? {
+            Some(block)
+                if height <= tip_height && *db.fetch_chain_header_by_height(height)?.hash() == block.block_hash =>
+            {
+                break;
+            },
+            Some(_) => {
+                txn.delete_script_index_block(height);
+            },
+            None => {},
+        }
+        next_height = height;
+    }
+    if metadata.pruned_height() > 0 {
+        next_height = cmp::max(next_height, metadata.pruned_height() + 1);
+    }
+
+    let end_height = cmp::min(tip_height, next_height.saturating_add(max_blocks.saturating_sub(1)));
+    if next_height <= end_height {
+        debug!(
+            target: LOG_TARGET,
+            "Indexing output scripts for blocks #{} to #{}", next_height, end_height
+        );
+    }
+    for height in next_height..=end_height {
+        let header = db.fetch_chain_header_by_height(height)?;
+        let outputs = db
+            .fetch_outputs_in_block(header.hash())?
+            .iter()
+            .filter_map(PrunedOutput::as_transaction_output)
+            .map(ScriptIndexedOutput::try_from_output)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ChainStorageError::ConversionError(e.to_string()))?;
+        txn.insert_script_index_block(ScriptIndexBlock {
+            height,
+            block_hash: *header.hash(),
+            outputs,
+        });
+
+        if txn.operations().len() >= BATCH_SIZE {
+            db.write(mem::take(&mut txn))?;
+            debug!(target: LOG_TARGET, "Output scripts indexed up to block #{}", height);
+        }
+    }
+
+    if !txn.operations().is_empty() {
+        db.write(txn)?;
+    }
+    Ok(end_height == tip_height)
+}
+
 fn prune_database_if_needed<T: BlockchainBackend>(
     db: &mut T,
-    pruning_horizon: u64,
-    pruning_interval: u64,
+    config: &BlockchainDatabaseConfig,
 ) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     if !metadata.is_pruned_node() {
@@ -2263,7 +2678,8 @@ fn prune_database_if_needed<T: BlockchainBackend>(
     }
 
     let db_height = metadata.height_of_longest_chain();
-    let abs_pruning_horizon = db_height.saturating_sub(pruning_horizon);
+    let abs_pruning_horizon = db_height.saturating_sub(config.pruning_horizon);
+    let pruning_interval = config.pruning_interval;
 
     debug!(
         target: LOG_TARGET,
@@ -2273,13 +2689,17 @@ fn prune_database_if_needed<T: BlockchainBackend>(
         pruning_interval,
     );
     if metadata.pruned_height() < abs_pruning_horizon.saturating_sub(pruning_interval) {
-        prune_to_height(db, abs_pruning_horizon)?;
+        prune_to_height(db, abs_pruning_horizon, config.pruning_level)?;
     }
 
     Ok(())
 }
 
-fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64) -> Result<(), ChainStorageError> {
+fn prune_to_height<T: BlockchainBackend>(
+    db: &mut T,
+    target_horizon_height: u64,
+    pruning_level: PruningLevel,
+) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     let last_pruned = metadata.pruned_height();
     if target_horizon_height < last_pruned {
@@ -2340,11 +2760,38 @@ fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64)
     }
 
     txn.set_pruned_height(target_horizon_height);
+    if pruning_level == PruningLevel::Kernels {
+        prune_kernels_to_height(&*db, &mut txn, target_horizon_height)?;
+    }
 
     db.write(txn)?;
     Ok(())
 }
 
+/// Prunes the kernels of every block up to and including the given height that have not been pruned yet. This also
+/// catches up on blocks that were pruned before kernel pruning was enabled.
+fn prune_kernels_to_height<T: BlockchainBackend>(
+    db: &T,
+    txn: &mut DbTransaction,
+    target_height: u64,
+) -> Result<(), ChainStorageError> {
+    let kernel_pruned_height = db.fetch_kernel_pruned_height()?;
+    if target_height <= kernel_pruned_height {
+        return Ok(());
+    }
+
+    debug!(
+        target: LOG_TARGET,
+        "Pruning kernels at height {} (was={})", target_height, kernel_pruned_height
+    );
+    for height in (kernel_pruned_height + 1)..=target_height {
+        let header = db.fetch_chain_header_by_height(height)?;
+        txn.prune_kernels_in_block(*header.hash());
+    }
+    txn.set_kernel_pruned_height(target_height);
+    Ok(())
+}
+
 fn log_error<T>(req: DbKey, err: ChainStorageError) -> Result<T, ChainStorageError> {
     error!(
         target: LOG_TARGET,
@@ -2364,6 +2811,8 @@ impl<T> Clone for BlockchainDatabase<T> {
             consensus_manager: self.consensus_manager.clone(),
             difficulty_calculator: self.difficulty_calculator.clone(),
             disable_add_block_flag: self.disable_add_block_flag.clone(),
+            utxo_stats_ready_flag: self.utxo_stats_ready_flag.clone(),
+            script_index_ready_flag: self.script_index_ready_flag.clone(),
         }
     }
 }
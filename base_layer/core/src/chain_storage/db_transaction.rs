@@ -32,7 +32,7 @@ use tari_utilities::hex::Hex;
 
 use crate::{
     blocks::{Block, BlockHeader, BlockHeaderAccumulatedData, ChainBlock, ChainHeader, UpdateBlockAccumulatedData},
-    chain_storage::{error::ChainStorageError, HorizonData, Reorg},
+    chain_storage::{error::ChainStorageError, BlockUtxoStats, HorizonData, Reorg, ScriptIndexBlock},
     transactions::transaction_components::{TransactionKernel, TransactionOutput},
 };
 
@@ -158,6 +158,13 @@ impl DbTransaction {
         self
     }
 
+    /// Deletes the kernels in the given block. The kernel excess signature index is kept so that kernels can still be
+    /// checked for uniqueness.
+    pub fn prune_kernels_in_block(&mut self, block_hash: BlockHash) -> &mut Self {
+        self.operations.push(WriteOperation::PruneKernelsInBlock { block_hash });
+        self
+    }
+
     pub fn update_block_accumulated_data(
         &mut self,
         header_hash: HashOutput,
@@ -256,6 +263,11 @@ impl DbTransaction {
         self
     }
 
+    pub fn set_kernel_pruned_height(&mut self, height: u64) -> &mut Self {
+        self.operations.push(WriteOperation::SetKernelPrunedHeight { height });
+        self
+    }
+
     pub fn set_horizon_data(&mut self, kernel_sum: Commitment, utxo_sum: Commitment) -> &mut Self {
         self.operations.push(WriteOperation::SetHorizonData {
             horizon_data: HorizonData::new(kernel_sum, utxo_sum),
@@ -283,6 +295,36 @@ impl DbTransaction {
         self.operations.push(WriteOperation::ClearAllReorgs);
         self
     }
+
+    pub fn insert_utxo_stats(&mut self, stats: BlockUtxoStats) -> &mut Self {
+        self.operations.push(WriteOperation::InsertUtxoStats { stats });
+        self
+    }
+
+    pub fn delete_utxo_stats(&mut self, height: u64) -> &mut Self {
+        self.operations.push(WriteOperation::DeleteUtxoStats { height });
+        self
+    }
+
+    pub fn clear_all_utxo_stats(&mut self) -> &mut Self {
+        self.operations.push(WriteOperation::ClearAllUtxoStats);
+        self
+    }
+
+    pub fn insert_script_index_block(&mut self, block: ScriptIndexBlock) -> &mut Self {
+        self.operations.push(WriteOperation::InsertScriptIndexBlock { block });
+        self
+    }
+
+    pub fn delete_script_index_block(&mut self, height: u64) -> &mut Self {
+        self.operations.push(WriteOperation::DeleteScriptIndexBlock { height });
+        self
+    }
+
+    pub fn clear_script_index(&mut self) -> &mut Self {
+        self.operations.push(WriteOperation::ClearScriptIndex);
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -339,6 +381,9 @@ pub enum WriteOperation {
     DeleteAllInputsInBlock {
         block_hash: BlockHash,
     },
+    PruneKernelsInBlock {
+        block_hash: BlockHash,
+    },
     SetAccumulatedDataForOrphan(BlockHeaderAccumulatedData),
     SetBestBlock {
         height: u64,
@@ -351,6 +396,9 @@ pub enum WriteOperation {
     SetPrunedHeight {
         height: u64,
     },
+    SetKernelPrunedHeight {
+        height: u64,
+    },
     SetHorizonData {
         horizon_data: HorizonData,
     },
@@ -358,6 +406,20 @@ pub enum WriteOperation {
         reorg: Reorg,
     },
     ClearAllReorgs,
+    InsertUtxoStats {
+        stats: BlockUtxoStats,
+    },
+    DeleteUtxoStats {
+        height: u64,
+    },
+    ClearAllUtxoStats,
+    InsertScriptIndexBlock {
+        block: ScriptIndexBlock,
+    },
+    DeleteScriptIndexBlock {
+        height: u64,
+    },
+    ClearScriptIndex,
 }
 
 impl fmt::Display for WriteOperation {
@@ -429,6 +491,7 @@ impl fmt::Display for WriteOperation {
             },
             PruneOutputsAtMmrPositions { output_positions } => write!(f, "Prune {} output(s)", output_positions.len()),
             DeleteAllInputsInBlock { block_hash } => write!(f, "Delete outputs in block {}", block_hash.to_hex()),
+            PruneKernelsInBlock { block_hash } => write!(f, "Prune kernels in block {}", block_hash.to_hex()),
             SetAccumulatedDataForOrphan(accumulated_data) => {
                 write!(f, "Set accumulated data for orphan {}", accumulated_data)
             },
@@ -448,12 +511,24 @@ impl fmt::Display for WriteOperation {
             ),
             SetPruningHorizonConfig(pruning_horizon) => write!(f, "Set config: pruning horizon to {}", pruning_horizon),
             SetPrunedHeight { height, .. } => write!(f, "Set pruned height to {}", height),
+            SetKernelPrunedHeight { height } => write!(f, "Set kernel pruned height to {}", height),
             DeleteHeader(height) => write!(f, "Delete header at height: {}", height),
             DeleteOrphan(hash) => write!(f, "Delete orphan with hash: {}", hash.to_hex()),
             InsertBadBlock { hash, height } => write!(f, "Insert bad block #{} {}", height, hash.to_hex()),
             SetHorizonData { .. } => write!(f, "Set horizon data"),
             InsertReorg { .. } => write!(f, "Insert reorg"),
             ClearAllReorgs => write!(f, "Clear all reorgs"),
+            InsertUtxoStats { stats } => write!(f, "Insert UTXO stats for height {}", stats.height),
+            DeleteUtxoStats { height } => write!(f, "Delete UTXO stats for height {}", height),
+            ClearAllUtxoStats => write!(f, "Clear all UTXO stats"),
+            InsertScriptIndexBlock { block } => write!(
+                f,
+                "Insert {} script indexed output(s) for height {}",
+                block.outputs.len(),
+                block.height
+            ),
+            DeleteScriptIndexBlock { height } => write!(f, "Delete script index entries for height {}", height),
+            ClearScriptIndex => write!(f, "Clear script index"),
         }
     }
 }
@@ -167,6 +167,15 @@ impl From<lmdb_zero::Error> for ChainStorageError {
     }
 }
 
+impl From<sled::Error> for ChainStorageError {
+    fn from(err: sled::Error) -> Self {
+        match err {
+            sled::Error::Io(err) => ChainStorageError::IoError(err),
+            err => ChainStorageError::AccessError(err.to_string()),
+        }
+    }
+}
+
 pub trait Optional<U> {
     fn optional(self) -> Result<Option<U>, ChainStorageError>;
 }
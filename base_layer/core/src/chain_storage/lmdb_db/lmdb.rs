@@ -326,6 +326,46 @@ where
     Ok(result)
 }
 
+/// Returns the values of the entries from `start_key` onwards, in key order, for as long as their keys start with
+/// `key_prefix` and `predicate` returns true for their values
+pub fn lmdb_fetch_matching_from<V, F>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    key_prefix: &[u8],
+    start_key: &[u8],
+    mut predicate: F,
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+    F: FnMut(&V) -> bool,
+{
+    let access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get read cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+    let iter = CursorIter::new(
+        MaybeOwned::Borrowed(&mut cursor),
+        &access,
+        |c, a| c.seek_range_k(a, start_key),
+        Cursor::next::<[u8], [u8]>,
+    )?;
+
+    let mut result = vec![];
+    for row in iter {
+        let (key, val) = row?;
+        if !key.starts_with(key_prefix) {
+            break;
+        }
+        let val = deserialize::<V>(val)?;
+        if !predicate(&val) {
+            break;
+        }
+        result.push(val);
+    }
+    Ok(result)
+}
+
 pub fn lmdb_first_after<K, V>(
     txn: &ConstTransaction<'_>,
     db: &Database,
@@ -66,6 +66,7 @@ use crate::{
                 lmdb_delete_keys_starting_with,
                 lmdb_exists,
                 lmdb_fetch_matching_after,
+                lmdb_fetch_matching_from,
                 lmdb_filter_map_values,
                 lmdb_first_after,
                 lmdb_get,
@@ -81,8 +82,10 @@ use crate::{
             TransactionKernelRowData,
             TransactionOutputRowData,
         },
+        script_index::script_index_page_filter,
         stats::DbTotalSizeStats,
         utxo_mined_info::UtxoMinedInfo,
+        BlockUtxoStats,
         BlockchainBackend,
         DbBasicStats,
         DbSize,
@@ -90,6 +93,8 @@ use crate::{
         MmrTree,
         PrunedOutput,
         Reorg,
+        ScriptIndexBlock,
+        ScriptIndexKey,
     },
     transactions::{
         aggregated_body::AggregateBody,
@@ -128,6 +133,9 @@ const LMDB_DB_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
 const LMDB_DB_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
 const LMDB_DB_BAD_BLOCK_LIST: &str = "bad_blocks";
 const LMDB_DB_REORGS: &str = "reorgs";
+const LMDB_DB_UTXO_STATS: &str = "utxo_stats";
+const LMDB_DB_SCRIPT_INDEX: &str = "script_index";
+const LMDB_DB_SCRIPT_INDEX_BLOCKS: &str = "script_index_blocks";
 
 pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Result<LMDBDatabase, ChainStorageError> {
     let flags = db::CREATE;
@@ -166,6 +174,9 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
         .add_database(LMDB_DB_ORPHAN_PARENT_MAP_INDEX, flags | db::DUPSORT)
         .add_database(LMDB_DB_BAD_BLOCK_LIST, flags)
         .add_database(LMDB_DB_REORGS, flags | db::INTEGERKEY)
+        .add_database(LMDB_DB_UTXO_STATS, flags | db::INTEGERKEY)
+        .add_database(LMDB_DB_SCRIPT_INDEX, flags)
+        .add_database(LMDB_DB_SCRIPT_INDEX_BLOCKS, flags | db::INTEGERKEY)
         .build()
         .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))?;
     debug!(target: LOG_TARGET, "LMDB database creation successful");
@@ -224,6 +235,12 @@ pub struct LMDBDatabase {
     bad_blocks: DatabaseRef,
     /// Stores reorgs by epochtime and Reorg
     reorgs: DatabaseRef,
+    /// Stores UTXO set statistics by block height
+    utxo_stats: DatabaseRef,
+    /// Maps script hashes and sender offset public keys to the heights and hashes of the outputs that use them
+    script_index: DatabaseRef,
+    /// Stores the outputs indexed in the script index by block height
+    script_index_blocks: DatabaseRef,
     _file_lock: Arc<File>,
 }
 
@@ -259,6 +276,9 @@ impl LMDBDatabase {
             orphan_parent_map_index: get_database(store, LMDB_DB_ORPHAN_PARENT_MAP_INDEX)?,
             bad_blocks: get_database(store, LMDB_DB_BAD_BLOCK_LIST)?,
             reorgs: get_database(store, LMDB_DB_REORGS)?,
+            utxo_stats: get_database(store, LMDB_DB_UTXO_STATS)?,
+            script_index: get_database(store, LMDB_DB_SCRIPT_INDEX)?,
+            script_index_blocks: get_database(store, LMDB_DB_SCRIPT_INDEX_BLOCKS)?,
             env,
             env_config: store.env_config(),
             _file_lock: Arc::new(file_lock),
@@ -385,6 +405,9 @@ impl LMDBDatabase {
                 DeleteAllInputsInBlock { block_hash } => {
                     self.delete_all_inputs_in_block(&write_txn, block_hash)?;
                 },
+                PruneKernelsInBlock { block_hash } => {
+                    self.prune_block_kernels(&write_txn, block_hash.as_slice())?;
+                },
                 SetBestBlock {
                     height,
                     hash,
@@ -444,6 +467,13 @@ impl LMDBDatabase {
                         &MetadataValue::PrunedHeight(*height),
                     )?;
                 },
+                SetKernelPrunedHeight { height } => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::KernelPrunedHeight,
+                        &MetadataValue::KernelPrunedHeight(*height),
+                    )?;
+                },
                 SetHorizonData { horizon_data } => {
                     self.set_metadata(
                         &write_txn,
@@ -460,6 +490,25 @@ impl LMDBDatabase {
                 ClearAllReorgs => {
                     lmdb_clear(&write_txn, &self.reorgs)?;
                 },
+                InsertUtxoStats { stats } => {
+                    lmdb_replace(&write_txn, &self.utxo_stats, &stats.height, &stats)?;
+                },
+                DeleteUtxoStats { height } => {
+                    lmdb_delete(&write_txn, &self.utxo_stats, height, LMDB_DB_UTXO_STATS)?;
+                },
+                ClearAllUtxoStats => {
+                    lmdb_clear(&write_txn, &self.utxo_stats)?;
+                },
+                InsertScriptIndexBlock { block } => {
+                    self.insert_script_index_block(&write_txn, block)?;
+                },
+                DeleteScriptIndexBlock { height } => {
+                    self.delete_script_index_block(&write_txn, *height)?;
+                },
+                ClearScriptIndex => {
+                    lmdb_clear(&write_txn, &self.script_index)?;
+                    lmdb_clear(&write_txn, &self.script_index_blocks)?;
+                },
             }
         }
         write_txn.commit()?;
@@ -467,7 +516,7 @@ impl LMDBDatabase {
         Ok(())
     }
 
-    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 24] {
+    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 27] {
         [
             ("metadata_db", &self.metadata_db),
             ("headers_db", &self.headers_db),
@@ -499,6 +548,9 @@ impl LMDBDatabase {
             ("orphan_parent_map_index", &self.orphan_parent_map_index),
             ("bad_blocks", &self.bad_blocks),
             ("reorgs", &self.reorgs),
+            ("utxo_stats", &self.utxo_stats),
+            ("script_index", &self.script_index),
+            ("script_index_blocks", &self.script_index_blocks),
         ]
     }
 
@@ -1047,6 +1099,19 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    /// Deletes the kernels in a block. Unlike `delete_block_kernels`, the excess and excess signature index entries
+    /// are kept, as they are what rejects a new kernel that reuses an existing excess or excess signature.
+    fn prune_block_kernels(&self, txn: &WriteTransaction<'_>, block_hash: &[u8]) -> Result<(), ChainStorageError> {
+        let kernels = lmdb_delete_keys_starting_with::<TransactionKernelRowData>(txn, &self.kernels_db, block_hash)?;
+        debug!(
+            target: LOG_TARGET,
+            "Pruned {} kernel(s) in block {}",
+            kernels.len(),
+            to_hex(block_hash)
+        );
+        Ok(())
+    }
+
     fn delete_orphan(&self, txn: &WriteTransaction<'_>, hash: &HashOutput) -> Result<(), ChainStorageError> {
         let orphan = match lmdb_get::<_, Block>(txn, &self.orphans_db, hash.as_slice())? {
             Some(orphan) => orphan,
@@ -1456,6 +1521,29 @@ impl LMDBDatabase {
         lmdb_last(txn, &self.headers_db)
     }
 
+    fn insert_script_index_block(
+        &self,
+        txn: &WriteTransaction<'_>,
+        block: &ScriptIndexBlock,
+    ) -> Result<(), ChainStorageError> {
+        for (key, value) in block.index_entries() {
+            lmdb_replace(txn, &self.script_index, key.as_slice(), &value)?;
+        }
+        lmdb_replace(txn, &self.script_index_blocks, &block.height, block)
+    }
+
+    fn delete_script_index_block(&self, txn: &WriteTransaction<'_>, height: u64) -> Result<(), ChainStorageError> {
+        let block = lmdb_get::<_, ScriptIndexBlock>(txn, &self.script_index_blocks, &height).or_not_found(
+            "ScriptIndexBlock",
+            "height",
+            height.to_string(),
+        )?;
+        for (key, _) in block.index_entries() {
+            lmdb_delete(txn, &self.script_index, key.as_slice(), LMDB_DB_SCRIPT_INDEX)?;
+        }
+        lmdb_delete(txn, &self.script_index_blocks, &height, LMDB_DB_SCRIPT_INDEX_BLOCKS)
+    }
+
     fn insert_bad_block_and_cleanup(
         &self,
         txn: &WriteTransaction<'_>,
@@ -1560,7 +1648,7 @@ pub fn create_recovery_lmdb_database<P: AsRef<Path>>(path: P) -> Result<(), Chai
     Ok(())
 }
 
-fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, ChainStorageError> {
+pub(crate) fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, ChainStorageError> {
     let lock_file_path = db_path.join(".chain_storage_file.lock");
 
     let file = File::create(lock_file_path)?;
@@ -1891,6 +1979,20 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut key = Vec::<u8>::new();
+        key.extend(excess_sig.get_public_nonce().as_bytes());
+        key.extend(excess_sig.get_signature().as_bytes());
+        Ok(
+            lmdb_get::<_, (HashOutput, u32, HashOutput)>(&txn, &self.kernel_excess_sig_index, key.as_slice())?
+                .map(|(header_hash, _, _)| header_hash),
+        )
+    }
+
     fn fetch_utxos_in_block(
         &self,
         header_hash: &HashOutput,
@@ -2065,6 +2167,11 @@ impl BlockchainBackend for LMDBDatabase {
         Ok(metadata)
     }
 
+    fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        fetch_kernel_pruned_height(&txn, &self.metadata_db)
+    }
+
     fn utxo_count(&self) -> Result<usize, ChainStorageError> {
         let txn = self.read_transaction()?;
         lmdb_len(&txn, &self.utxo_commitment_index)
@@ -2293,6 +2400,52 @@ impl BlockchainBackend for LMDBDatabase {
         let txn = self.read_transaction()?;
         lmdb_filter_map_values(&txn, &self.reorgs, Some)
     }
+
+    fn fetch_utxo_stats(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockUtxoStats>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let mut stats = Vec::new();
+        for height in start_height..=end_height {
+            if let Some(entry) = lmdb_get(&txn, &self.utxo_stats, &height)? {
+                stats.push(entry);
+            }
+        }
+        Ok(stats)
+    }
+
+    fn fetch_last_utxo_stats(&self) -> Result<Option<BlockUtxoStats>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_last(&txn, &self.utxo_stats)
+    }
+
+    fn fetch_script_index_block(&self, height: u64) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_get(&txn, &self.script_index_blocks, &height)
+    }
+
+    fn fetch_last_script_index_block(&self) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_last(&txn, &self.script_index_blocks)
+    }
+
+    fn fetch_outputs_by_script_index(
+        &self,
+        key: &ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let prefix = key.to_key_prefix();
+        let mut start_key = prefix.clone();
+        start_key.extend_from_slice(&start_height.to_be_bytes());
+        lmdb_fetch_matching_from(
+            &txn,
+            &self.script_index,
+            &prefix,
+            &start_key,
+            script_index_page_filter(end_height, limit),
+        )
+    }
 }
 
 // Fetch the chain metadata
@@ -2331,6 +2484,16 @@ fn fetch_pruned_height(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64,
     }
 }
 
+/// Fetches the height up to which kernels have been pruned from the provided metadata db.
+fn fetch_kernel_pruned_height(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64, ChainStorageError> {
+    let k = MetadataKey::KernelPrunedHeight;
+    let val: Option<MetadataValue> = lmdb_get(txn, db, &k.as_u32())?;
+    match val {
+        Some(MetadataValue::KernelPrunedHeight(height)) => Ok(height),
+        _ => Ok(0),
+    }
+}
+
 /// Fetches the horizon data from the provided metadata db.
 fn fetch_horizon_data(txn: &ConstTransaction<'_>, db: &Database) -> Result<HorizonData, ChainStorageError> {
     let k = MetadataKey::HorizonData;
@@ -2423,7 +2586,7 @@ fn get_database(store: &LMDBStore, name: &str) -> Result<DatabaseRef, ChainStora
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
-enum MetadataKey {
+pub(crate) enum MetadataKey {
     ChainHeight,
     BestBlock,
     AccumulatedWork,
@@ -2432,6 +2595,7 @@ enum MetadataKey {
     HorizonData,
     DeletedBitmap,
     BestBlockTimestamp,
+    KernelPrunedHeight,
 }
 
 impl MetadataKey {
@@ -2452,13 +2616,14 @@ impl fmt::Display for MetadataKey {
             MetadataKey::HorizonData => f.write_str("Database info"),
             MetadataKey::DeletedBitmap => f.write_str("Deleted bitmap"),
             MetadataKey::BestBlockTimestamp => f.write_str("Chain tip block timestamp"),
+            MetadataKey::KernelPrunedHeight => f.write_str("Kernel pruned height"),
         }
     }
 }
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
-enum MetadataValue {
+pub(crate) enum MetadataValue {
     ChainHeight(u64),
     BestBlock(BlockHash),
     AccumulatedWork(u128),
@@ -2467,6 +2632,7 @@ enum MetadataValue {
     HorizonData(HorizonData),
     DeletedBitmap(DeletedBitmap),
     BestBlockTimestamp(u64),
+    KernelPrunedHeight(u64),
 }
 
 impl fmt::Display for MetadataValue {
@@ -2482,6 +2648,7 @@ impl fmt::Display for MetadataValue {
                 write!(f, "Deleted Bitmap ({} indexes)", deleted.bitmap().cardinality())
             },
             MetadataValue::BestBlockTimestamp(timestamp) => write!(f, "Chain tip block timestamp is {}", timestamp),
+            MetadataValue::KernelPrunedHeight(height) => write!(f, "Kernel pruned height is {}", height),
         }
     }
 }
@@ -2549,7 +2716,7 @@ impl<'a, 'b> DeletedBitmapModel<'a, WriteTransaction<'b>> {
     }
 }
 
-struct CompositeKey {
+pub(crate) struct CompositeKey {
     key: Vec<u8>,
 }
 
@@ -2572,6 +2739,6 @@ impl CompositeKey {
     }
 }
 
-type InputKey = CompositeKey;
-type KernelKey = CompositeKey;
-type OutputKey = CompositeKey;
+pub(crate) type InputKey = CompositeKey;
+pub(crate) type KernelKey = CompositeKey;
+pub(crate) type OutputKey = CompositeKey;
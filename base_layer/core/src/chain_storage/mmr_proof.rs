@@ -0,0 +1,213 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, convert::TryFrom};
+
+use log::*;
+use tari_common_types::types::{BlockHash, HashOutput};
+use tari_mmr::{MerkleBatchProof, MutableMmrBatchProof};
+use tari_utilities::hex::Hex;
+
+use crate::{
+    blocks::ChainHeader,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError, MmrTree},
+    KernelMmr,
+    MutableOutputMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::mmr_proof";
+
+/// A batch inclusion proof for a set of leaves in one of the chain's MMRs, as at the block given by `header_hash`.
+/// The proof is checked against the corresponding MMR root in that block's header.
+#[derive(Debug, Clone)]
+pub struct ChainMmrProof<P> {
+    /// The hash of the block header that contains the MMR root this proof verifies against
+    pub header_hash: BlockHash,
+    pub height: u64,
+    /// The (hash, leaf index) of each requested leaf that was found. Requested hashes that are not in the MMR are
+    /// omitted.
+    pub leaves: Vec<(HashOutput, u32)>,
+    pub proof: P,
+}
+
+/// A batch proof for outputs, verifiable against the `output_mr` of the header. The proof includes the deleted bitmap,
+/// so it also proves whether each output has been spent.
+pub type OutputMmrProof = ChainMmrProof<MutableMmrBatchProof>;
+
+/// A batch proof for kernels, verifiable against the `kernel_mr` of the header.
+pub type KernelMmrProof = ChainMmrProof<MerkleBatchProof>;
+
+/// Builds a batch proof for the given output hashes against the output MMR at the tip.
+///
+/// Only the MMR peaks are stored per block, so this rebuilds the full output MMR from the leaf hashes of every block in
+/// the chain. It is expensive and callers should limit how often it is done. The read lock is only held while each
+/// block's leaves are fetched, so the chain can be extended while the MMR is rebuilt.
+pub(super) fn generate_output_proof<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    hashes: &[HashOutput],
+) -> Result<OutputMmrProof, ChainStorageError> {
+    let (tip_header, deleted) = {
+        let db = db.db_read_access()?;
+        (db.fetch_tip_header()?, db.fetch_deleted_bitmap()?.into_bitmap())
+    };
+    let mut output_mmr = MutableOutputMmr::new(Vec::new(), deleted)?;
+    let leaves = rebuild_mmr(
+        db,
+        tip_header.height(),
+        hashes,
+        |header_hash| {
+            let hashes = db
+                .db_read_access()?
+                .fetch_outputs_in_block(header_hash)?
+                .iter()
+                .map(|output| output.hash())
+                .collect();
+            Ok(hashes)
+        },
+        |hash| {
+            output_mmr.push(hash.to_vec())?;
+            Ok(output_mmr.get_leaf_count())
+        },
+    )?;
+    output_mmr.compress();
+
+    let root = output_mmr.get_merkle_root()?;
+    if root.as_slice() != tip_header.header().output_mr.as_slice() {
+        check_tip_unchanged(db, &tip_header)?;
+        error!(
+            target: LOG_TARGET,
+            "Rebuilt output MMR root {} does not match the output_mr {} at the tip (height {})",
+            root.to_hex(),
+            tip_header.header().output_mr.to_hex(),
+            tip_header.height()
+        );
+        return Err(ChainStorageError::MismatchedMmrRoot(MmrTree::Utxo));
+    }
+
+    let leaf_indices = leaves.iter().map(|(_, index)| *index).collect::<Vec<_>>();
+    let proof = MutableMmrBatchProof::for_leaf_nodes(&output_mmr, &leaf_indices)?;
+    Ok(ChainMmrProof {
+        header_hash: *tip_header.hash(),
+        height: tip_header.height(),
+        leaves,
+        proof,
+    })
+}
+
+/// Builds a batch proof for the given kernel hashes against the kernel MMR at the tip.
+///
+/// As with [generate_output_proof], this rebuilds the full kernel MMR and is expensive.
+pub(super) fn generate_kernel_proof<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    hashes: &[HashOutput],
+) -> Result<KernelMmrProof, ChainStorageError> {
+    let tip_header = db.fetch_tip_header()?;
+    let mut kernel_mmr = KernelMmr::new(Vec::new());
+    let leaves = rebuild_mmr(
+        db,
+        tip_header.height(),
+        hashes,
+        |header_hash| {
+            let hashes = db
+                .db_read_access()?
+                .fetch_kernels_in_block(header_hash)?
+                .iter()
+                .map(|kernel| kernel.hash())
+                .collect();
+            Ok(hashes)
+        },
+        |hash| {
+            kernel_mmr.push(hash.to_vec())?;
+            Ok(kernel_mmr.get_leaf_count()?)
+        },
+    )?;
+
+    let root = kernel_mmr.get_merkle_root()?;
+    if root.as_slice() != tip_header.header().kernel_mr.as_slice() {
+        check_tip_unchanged(db, &tip_header)?;
+        error!(
+            target: LOG_TARGET,
+            "Rebuilt kernel MMR root {} does not match the kernel_mr {} at the tip (height {})",
+            root.to_hex(),
+            tip_header.header().kernel_mr.to_hex(),
+            tip_header.height()
+        );
+        return Err(ChainStorageError::MismatchedMmrRoot(MmrTree::Kernel));
+    }
+
+    let leaf_indices = leaves.iter().map(|(_, index)| *index as usize).collect::<Vec<_>>();
+    let proof = MerkleBatchProof::for_leaf_nodes(&kernel_mmr, &leaf_indices)?;
+    Ok(ChainMmrProof {
+        header_hash: *tip_header.hash(),
+        height: tip_header.height(),
+        leaves,
+        proof,
+    })
+}
+
+/// Pushes the leaf hashes of every block up to and including `tip_height` using `push_leaf`, which must return the new
+/// leaf count. Returns the (hash, leaf index) of each of the `requested` hashes that were pushed.
+fn rebuild_mmr<B, FetchFn, PushFn>(
+    db: &BlockchainDatabase<B>,
+    tip_height: u64,
+    requested: &[HashOutput],
+    fetch_block_leaves: FetchFn,
+    mut push_leaf: PushFn,
+) -> Result<Vec<(HashOutput, u32)>, ChainStorageError>
+where
+    B: BlockchainBackend,
+    FetchFn: Fn(&HashOutput) -> Result<Vec<HashOutput>, ChainStorageError>,
+    PushFn: FnMut(&HashOutput) -> Result<usize, ChainStorageError>,
+{
+    let mut requested = requested.iter().collect::<HashSet<_>>();
+    let mut leaves = Vec::with_capacity(requested.len());
+    for height in 0..=tip_height {
+        let header = db.fetch_chain_header(height)?;
+        for hash in fetch_block_leaves(header.hash())? {
+            let leaf_count = push_leaf(&hash)?;
+            if requested.remove(&hash) {
+                let leaf_index = u32::try_from(leaf_count - 1)
+                    .map_err(|_| ChainStorageError::CriticalError("MMR leaf index overflows u32".to_string()))?;
+                leaves.push((hash, leaf_index));
+            }
+        }
+    }
+    Ok(leaves)
+}
+
+/// The lock is released between blocks while an MMR is rebuilt, so a reorg can change the blocks it is built from. In
+/// that case the rebuilt root is not expected to match, and the caller can simply ask for a new proof.
+fn check_tip_unchanged<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    tip_header: &ChainHeader,
+) -> Result<(), ChainStorageError> {
+    let current_tip = db.fetch_tip_header()?;
+    if current_tip.height() < tip_header.height() ||
+        db.fetch_chain_header(tip_header.height())?.hash() != tip_header.hash()
+    {
+        return Err(ChainStorageError::InvalidOperation(format!(
+            "The chain reorged below height {} while the MMR proof was generated",
+            tip_header.height()
+        )));
+    }
+    Ok(())
+}
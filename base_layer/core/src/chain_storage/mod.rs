@@ -43,6 +43,7 @@ pub use blockchain_database::{
     BlockchainDatabase,
     BlockchainDatabaseConfig,
     MmrRoots,
+    PruningLevel,
     Validators,
 };
 
@@ -54,6 +55,9 @@ mod consts;
 mod db_transaction;
 pub use db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation};
 
+mod mmr_proof;
+pub use mmr_proof::{ChainMmrProof, KernelMmrProof, OutputMmrProof};
+
 mod mmr_tree;
 pub use mmr_tree::MmrTree;
 
@@ -72,6 +76,14 @@ pub use reorg::Reorg;
 mod lmdb_db;
 pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
 
+mod sled_db;
+pub use sled_db::{create_recovery_sled_database, create_sled_database, SledConfig, SledDatabase};
+
+pub mod snapshot;
+
+mod script_index;
+pub use script_index::{script_hash, ScriptIndexBlock, ScriptIndexKey, ScriptIndexedOutput};
+
 mod stats;
 pub use stats::{DbBasicStats, DbSize, DbStat, DbTotalSizeStats};
 
@@ -79,3 +91,6 @@ mod target_difficulties;
 mod utxo_mined_info;
 pub use target_difficulties::TargetDifficulties;
 pub use utxo_mined_info::*;
+
+mod utxo_stats;
+pub use utxo_stats::{script_type, BlockUtxoStats};
@@ -0,0 +1,168 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{FixedHash, HashOutput, PublicKey};
+use tari_crypto::hash::blake2::Blake256;
+use tari_script::{ScriptError, TariScript};
+use tari_utilities::ByteArray;
+
+use crate::transactions::transaction_components::TransactionOutput;
+
+/// A key that outputs can be looked up by in the optional script index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScriptIndexKey {
+    /// The Blake256 hash of the output script, as returned by `TariScript::as_hash::<Blake256>()`
+    ScriptHash(FixedHash),
+    SenderOffsetPublicKey(PublicKey),
+}
+
+impl ScriptIndexKey {
+    pub fn from_script(script: &TariScript) -> Result<Self, ScriptError> {
+        Ok(ScriptIndexKey::ScriptHash(script_hash(script)?))
+    }
+
+    /// Returns the bytes that all index entries for this key start with
+    pub(crate) fn to_key_prefix(&self) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(33);
+        match self {
+            ScriptIndexKey::ScriptHash(hash) => {
+                prefix.push(0);
+                prefix.extend_from_slice(hash.as_slice());
+            },
+            ScriptIndexKey::SenderOffsetPublicKey(public_key) => {
+                prefix.push(1);
+                prefix.extend_from_slice(public_key.as_bytes());
+            },
+        }
+        prefix
+    }
+
+    /// Returns the index key of an output matching this key that was mined at `height`. Index keys sort by height for
+    /// each key, which allows a query to seek to its start height.
+    pub(crate) fn to_index_key(&self, height: u64, output_hash: &HashOutput) -> Vec<u8> {
+        let mut key = self.to_key_prefix();
+        key.extend_from_slice(&height.to_be_bytes());
+        key.extend_from_slice(output_hash.as_slice());
+        key
+    }
+}
+
+/// Returns the hash of a script as used by the script index
+pub fn script_hash(script: &TariScript) -> Result<FixedHash, ScriptError> {
+    script.as_hash::<Blake256>().map(FixedHash::from)
+}
+
+/// The outputs of a main chain block as recorded in the optional script index. The record is kept so that the index
+/// entries of the block can be removed when the block is reorged out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScriptIndexBlock {
+    pub height: u64,
+    pub block_hash: HashOutput,
+    /// The indexed outputs created in the block. Outputs that had already been pruned when the block was indexed are
+    /// not included.
+    pub outputs: Vec<ScriptIndexedOutput>,
+}
+
+impl ScriptIndexBlock {
+    /// Returns the index key and value of every index entry of the block
+    pub(crate) fn index_entries(&self) -> impl Iterator<Item = (Vec<u8>, (u64, HashOutput))> + '_ {
+        self.outputs.iter().flat_map(move |output| {
+            output.index_keys().into_iter().map(move |key| {
+                (
+                    key.to_index_key(self.height, &output.output_hash),
+                    (self.height, output.output_hash),
+                )
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScriptIndexedOutput {
+    pub output_hash: HashOutput,
+    pub script_hash: FixedHash,
+    pub sender_offset_public_key: PublicKey,
+}
+
+impl ScriptIndexedOutput {
+    pub fn try_from_output(output: &TransactionOutput) -> Result<Self, ScriptError> {
+        Ok(Self {
+            output_hash: output.hash(),
+            script_hash: script_hash(&output.script)?,
+            sender_offset_public_key: output.sender_offset_public_key.clone(),
+        })
+    }
+
+    /// Returns the keys the output can be looked up by
+    pub fn index_keys(&self) -> Vec<ScriptIndexKey> {
+        vec![
+            ScriptIndexKey::ScriptHash(self.script_hash),
+            ScriptIndexKey::SenderOffsetPublicKey(self.sender_offset_public_key.clone()),
+        ]
+    }
+}
+
+/// Returns a filter that accepts the entries of a script index query, in index order, until an entry is above
+/// `end_height` or `limit` entries have been accepted. Once the limit is reached the remaining entries at the height of
+/// the last accepted entry are still accepted, so that a page never ends part way through a block.
+pub(crate) fn script_index_page_filter(end_height: u64, limit: usize) -> impl FnMut(&(u64, HashOutput)) -> bool {
+    let mut count = 0;
+    let mut last_height = None;
+    move |(height, _)| {
+        if *height > end_height || (count >= limit && last_height != Some(*height)) {
+            return false;
+        }
+        count += 1;
+        last_height = Some(*height);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_ends_pages_at_a_block_boundary() {
+        let entries = [1u64, 2, 2, 2, 3, 4, 5]
+            .iter()
+            .map(|height| (*height, HashOutput::zero()))
+            .collect::<Vec<_>>();
+
+        let mut filter = script_index_page_filter(4, 2);
+        let heights = entries
+            .iter()
+            .take_while(|entry| filter(entry))
+            .map(|(height, _)| *height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![1, 2, 2, 2]);
+
+        let mut filter = script_index_page_filter(4, 10);
+        let heights = entries
+            .iter()
+            .take_while(|entry| filter(entry))
+            .map(|(height, _)| *height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![1, 2, 2, 2, 3, 4]);
+    }
+}
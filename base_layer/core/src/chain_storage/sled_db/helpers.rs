@@ -0,0 +1,241 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::Debug;
+
+use log::*;
+use serde::{de::DeserializeOwned, Serialize};
+use sled::Tree;
+use tari_utilities::hex::to_hex;
+
+use crate::chain_storage::{
+    error::ChainStorageError,
+    lmdb_db::helpers::serialize,
+    sled_db::transaction::SledTransaction,
+};
+
+pub const LOG_TARGET: &str = "c::cs::sled_db::helpers";
+
+pub fn deserialize<T>(buf_bytes: &[u8]) -> Result<T, ChainStorageError>
+where T: DeserializeOwned {
+    bincode::deserialize(buf_bytes).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not deserialize value from sled: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })
+}
+
+/// Inserts the value at the given key. An error is returned if the key already exists
+pub fn sled_insert<V>(
+    txn: &SledTransaction,
+    tree: &Tree,
+    key: &[u8],
+    val: &V,
+    table_name: &'static str,
+) -> Result<(), ChainStorageError>
+where
+    V: Serialize + Debug,
+{
+    if txn.contains_key(tree, key)? {
+        return Err(ChainStorageError::KeyExists {
+            table_name,
+            key: to_hex(key),
+        });
+    }
+    let val_buf = serialize(val)?;
+    trace!(
+        target: LOG_TARGET,
+        "Sled: {} bytes inserted into {}",
+        val_buf.len(),
+        table_name
+    );
+    txn.insert(tree, key, val_buf);
+    Ok(())
+}
+
+/// Inserts or replaces the item at the given key. If the key does not exist, a new entry is created
+pub fn sled_replace<V>(txn: &SledTransaction, tree: &Tree, key: &[u8], val: &V) -> Result<(), ChainStorageError>
+where V: Serialize {
+    txn.insert(tree, key, serialize(val)?);
+    Ok(())
+}
+
+/// Deletes the given key. An error is returned if the key does not exist
+pub fn sled_delete(
+    txn: &SledTransaction,
+    tree: &Tree,
+    key: &[u8],
+    table_name: &'static str,
+) -> Result<(), ChainStorageError> {
+    if !txn.contains_key(tree, key)? {
+        return Err(ChainStorageError::ValueNotFound {
+            entity: table_name,
+            field: "<unknown>",
+            value: to_hex(key),
+        });
+    }
+    txn.remove(tree, key);
+    Ok(())
+}
+
+/// Deletes every key that starts with the given prefix, returning the deleted values in key order
+pub fn sled_delete_keys_starting_with<V>(
+    txn: &SledTransaction,
+    tree: &Tree,
+    prefix: &[u8],
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    txn.scan_prefix(tree, prefix)?
+        .into_iter()
+        .map(|(key, value)| {
+            txn.remove(tree, &key);
+            deserialize(&value)
+        })
+        .collect()
+}
+
+pub fn sled_get<V>(txn: &SledTransaction, tree: &Tree, key: &[u8]) -> Result<Option<V>, ChainStorageError>
+where V: DeserializeOwned {
+    txn.get(tree, key)?.map(|v| deserialize(&v)).transpose()
+}
+
+pub fn sled_exists(txn: &SledTransaction, tree: &Tree, key: &[u8]) -> Result<bool, ChainStorageError> {
+    txn.contains_key(tree, key)
+}
+
+pub fn sled_len(txn: &SledTransaction, tree: &Tree) -> Result<usize, ChainStorageError> {
+    txn.len(tree)
+}
+
+/// Returns the value with the largest key in the tree
+pub fn sled_last<V>(txn: &SledTransaction, tree: &Tree) -> Result<Option<V>, ChainStorageError>
+where V: DeserializeOwned {
+    txn.last(tree)?.map(|(_, v)| deserialize(&v)).transpose()
+}
+
+/// Returns the values of every key that starts with the given prefix, in key order
+pub fn sled_fetch_matching_after<V>(
+    txn: &SledTransaction,
+    tree: &Tree,
+    prefix: &[u8],
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    txn.scan_prefix(tree, prefix)?
+        .into_iter()
+        .map(|(_, v)| deserialize(&v))
+        .collect()
+}
+
+/// Returns the values of the entries from `start_key` onwards, in key order, for as long as their keys start with
+/// `prefix` and `predicate` returns true for their values
+pub fn sled_fetch_matching_from<V, F>(
+    txn: &SledTransaction,
+    tree: &Tree,
+    prefix: &[u8],
+    start_key: &[u8],
+    mut predicate: F,
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+    F: FnMut(&V) -> bool,
+{
+    let mut result = vec![];
+    for (_, v) in txn.scan_prefix_from(tree, prefix, start_key)? {
+        let val = deserialize(&v)?;
+        if !predicate(&val) {
+            break;
+        }
+        result.push(val);
+    }
+    Ok(result)
+}
+
+/// Returns the value of the first key that is greater than or equal to the given key
+pub fn sled_first_after<V>(txn: &SledTransaction, tree: &Tree, key: &[u8]) -> Result<Option<V>, ChainStorageError>
+where V: DeserializeOwned {
+    txn.first_from(tree, key)?.map(|(_, v)| deserialize(&v)).transpose()
+}
+
+pub fn sled_filter_map_values<F, V, R>(txn: &SledTransaction, tree: &Tree, f: F) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(V) -> Option<R>,
+    V: DeserializeOwned,
+{
+    let mut result = vec![];
+    for (_, v) in txn.entries(tree)? {
+        if let Some(r) = f(deserialize(&v)?) {
+            result.push(r);
+        }
+    }
+    Ok(result)
+}
+
+/// Deletes each entry for which the predicate returns `Some(true)`, stopping at the first entry for which it returns
+/// `None`. Returns the number of deleted entries.
+pub fn sled_delete_each_where<V, F>(
+    txn: &SledTransaction,
+    tree: &Tree,
+    mut predicate: F,
+) -> Result<usize, ChainStorageError>
+where
+    V: DeserializeOwned,
+    F: FnMut(&[u8], V) -> Option<bool>,
+{
+    let mut num_deleted = 0;
+    for (k, v) in txn.entries(tree)? {
+        match predicate(&k, deserialize(&v)?) {
+            Some(true) => {
+                txn.remove(tree, &k);
+                num_deleted += 1;
+            },
+            Some(false) => continue,
+            None => break,
+        }
+    }
+    Ok(num_deleted)
+}
+
+pub fn sled_clear(txn: &SledTransaction, tree: &Tree) -> Result<usize, ChainStorageError> {
+    let entries = txn.entries(tree)?;
+    for (k, _) in &entries {
+        txn.remove(tree, k);
+    }
+    Ok(entries.len())
+}
+
+/// Fetches the size of all key/values in the given tree. Returns the number of entries, the total size of all the
+/// keys and values in bytes.
+pub fn fetch_tree_entry_sizes(tree: &Tree) -> Result<(u64, u64, u64), ChainStorageError> {
+    let mut num_entries = 0;
+    let mut total_key_size = 0;
+    let mut total_value_size = 0;
+    for item in tree.iter() {
+        let (key, value) = item?;
+        num_entries += 1;
+        total_key_size += key.len() as u64;
+        total_value_size += value.len() as u64;
+    }
+    Ok((num_entries, total_key_size, total_value_size))
+}
@@ -0,0 +1,2226 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Because we use dynamically sized u8 vectors for hash types through the type alias HashOutput,
+// let's ignore this clippy error in this module
+
+#![allow(clippy::ptr_arg)]
+
+use std::{convert::TryFrom, fs, fs::File, path::Path, sync::Arc, time::Instant};
+
+use croaring::Bitmap;
+use log::*;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    types::{BlockHash, Commitment, HashOutput, Signature},
+};
+use tari_utilities::{
+    hex::{to_hex, Hex},
+    ByteArray,
+};
+
+use crate::{
+    blocks::{
+        Block,
+        BlockAccumulatedData,
+        BlockHeader,
+        BlockHeaderAccumulatedData,
+        ChainBlock,
+        ChainHeader,
+        DeletedBitmap,
+        UpdateBlockAccumulatedData,
+    },
+    chain_storage::{
+        db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
+        error::{ChainStorageError, OrNotFound},
+        lmdb_db::{
+            acquire_exclusive_file_lock,
+            InputKey,
+            KernelKey,
+            MetadataKey,
+            MetadataValue,
+            OutputKey,
+            TransactionInputRowData,
+            TransactionInputRowDataRef,
+            TransactionKernelRowData,
+            TransactionOutputRowData,
+        },
+        script_index::script_index_page_filter,
+        sled_db::{
+            helpers::{
+                fetch_tree_entry_sizes,
+                sled_clear,
+                sled_delete,
+                sled_delete_each_where,
+                sled_delete_keys_starting_with,
+                sled_exists,
+                sled_fetch_matching_after,
+                sled_fetch_matching_from,
+                sled_filter_map_values,
+                sled_first_after,
+                sled_get,
+                sled_insert,
+                sled_last,
+                sled_len,
+                sled_replace,
+            },
+            transaction::SledTransaction,
+        },
+        stats::DbTotalSizeStats,
+        utxo_mined_info::UtxoMinedInfo,
+        BlockUtxoStats,
+        BlockchainBackend,
+        DbBasicStats,
+        DbSize,
+        HorizonData,
+        MmrTree,
+        PrunedOutput,
+        Reorg,
+        ScriptIndexBlock,
+        ScriptIndexKey,
+    },
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction_components::{TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
+    },
+    MutablePrunedOutputMmr,
+    PrunedKernelMmr,
+    PrunedWitnessMmr,
+};
+
+pub const LOG_TARGET: &str = "c::cs::sled_db::sled_db";
+
+const SLED_DB_METADATA: &str = "metadata";
+const SLED_DB_HEADERS: &str = "headers";
+const SLED_DB_HEADER_ACCUMULATED_DATA: &str = "header_accumulated_data";
+const SLED_DB_BLOCK_ACCUMULATED_DATA: &str = "mmr_peak_data";
+const SLED_DB_BLOCK_HASHES: &str = "block_hashes";
+const SLED_DB_UTXOS: &str = "utxos";
+const SLED_DB_INPUTS: &str = "inputs";
+const SLED_DB_TXOS_HASH_TO_INDEX: &str = "txos_hash_to_index";
+const SLED_DB_KERNELS: &str = "kernels";
+const SLED_DB_KERNEL_EXCESS_INDEX: &str = "kernel_excess_index";
+const SLED_DB_KERNEL_EXCESS_SIG_INDEX: &str = "kernel_excess_sig_index";
+const SLED_DB_KERNEL_MMR_SIZE_INDEX: &str = "kernel_mmr_size_index";
+const SLED_DB_UTXO_MMR_SIZE_INDEX: &str = "utxo_mmr_size_index";
+const SLED_DB_DELETED_TXO_MMR_POSITION_TO_HEIGHT_INDEX: &str = "deleted_txo_mmr_position_to_height_index";
+const SLED_DB_UTXO_COMMITMENT_INDEX: &str = "utxo_commitment_index";
+const SLED_DB_ORPHANS: &str = "orphans";
+const SLED_DB_MONERO_SEED_HEIGHT: &str = "monero_seed_height";
+const SLED_DB_ORPHAN_HEADER_ACCUMULATED_DATA: &str = "orphan_accumulated_data";
+const SLED_DB_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
+const SLED_DB_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
+const SLED_DB_BAD_BLOCK_LIST: &str = "bad_blocks";
+const SLED_DB_REORGS: &str = "reorgs";
+const SLED_DB_UTXO_STATS: &str = "utxo_stats";
+const SLED_DB_SCRIPT_INDEX: &str = "script_index";
+const SLED_DB_SCRIPT_INDEX_BLOCKS: &str = "script_index_blocks";
+
+/// Configuration for the sled chain storage backend
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SledConfig {
+    /// The maximum size of the in-memory page cache in bytes
+    pub cache_capacity_bytes: u64,
+    /// How often buffered writes are flushed to disk in milliseconds. If not set, writes are only flushed when the
+    /// database is closed.
+    pub flush_every_ms: Option<u64>,
+}
+
+impl Default for SledConfig {
+    fn default() -> Self {
+        Self {
+            cache_capacity_bytes: 1024 * 1024 * 1024,
+            flush_every_ms: Some(500),
+        }
+    }
+}
+
+pub fn create_sled_database<P: AsRef<Path>>(path: P, config: SledConfig) -> Result<SledDatabase, ChainStorageError> {
+    debug!(target: LOG_TARGET, "Creating sled database at {:?}", path.as_ref());
+    fs::create_dir_all(&path)?;
+
+    let file_lock = acquire_exclusive_file_lock(path.as_ref())?;
+
+    let db = sled::Config::new()
+        .path(path.as_ref().join("chain"))
+        .cache_capacity(config.cache_capacity_bytes)
+        .flush_every_ms(config.flush_every_ms)
+        .open()
+        .map_err(|err| ChainStorageError::CriticalError(format!("Could not create sled store:{}", err)))?;
+    debug!(target: LOG_TARGET, "Sled database creation successful");
+    SledDatabase::new(db, file_lock)
+}
+
+/// Moves the chain data at `path` into a `temp_recovery` directory so that it can be replayed into a fresh database
+pub fn create_recovery_sled_database<P: AsRef<Path>>(path: P) -> Result<(), ChainStorageError> {
+    let new_path = path.as_ref().join("temp_recovery");
+    fs::create_dir_all(&new_path)?;
+
+    fs::rename(path.as_ref().join("chain"), new_path.join("chain"))
+        .map_err(|err| ChainStorageError::CriticalError(format!("Could not move sled store:{}", err)))?;
+    Ok(())
+}
+
+/// This is a sled-based blockchain database for persistent storage of the chain state. It stores the same tables as
+/// [LMDBDatabase](crate::chain_storage::LMDBDatabase), each in its own sled tree. Integer keys are stored big-endian
+/// so that the trees iterate in numeric order.
+pub struct SledDatabase {
+    db: Db,
+    metadata_db: Tree,
+    /// Maps height -> BlockHeader
+    headers_db: Tree,
+    /// Maps height -> BlockHeaderAccumulatedData
+    header_accumulated_data_db: Tree,
+    /// Maps height -> BlockAccumulatedData
+    block_accumulated_data_db: Tree,
+    /// Maps block_hash -> height
+    block_hashes_db: Tree,
+    /// Maps OutputKey -> TransactionOutputRowData
+    utxos_db: Tree,
+    /// Maps InputKey -> TransactionInputRowData
+    inputs_db: Tree,
+    /// Maps OutputHash -> <mmr_pos, OutputKey>
+    txos_hash_to_index_db: Tree,
+    /// Maps KernelKey -> TransactionKernelRowData
+    kernels_db: Tree,
+    /// Maps excess -> <block_hash, mmr_pos, kernel_hash>
+    kernel_excess_index: Tree,
+    /// Maps excess_sig -> <block_hash, mmr_pos, kernel_hash>
+    kernel_excess_sig_index: Tree,
+    /// Maps kernel_mmr_size -> height
+    kernel_mmr_size_index: Tree,
+    /// Maps output_mmr_size -> <height, block_hash>
+    output_mmr_size_index: Tree,
+    /// Maps commitment -> output_hash
+    utxo_commitment_index: Tree,
+    /// Maps output_mmr_pos -> <height, block_hash>
+    deleted_txo_mmr_position_to_height_index: Tree,
+    /// Maps block_hash -> Block
+    orphans_db: Tree,
+    /// Maps randomx_seed -> height
+    monero_seed_height_db: Tree,
+    /// Maps block_hash -> BlockHeaderAccumulatedData
+    orphan_header_accumulated_data_db: Tree,
+    /// Stores the orphan tip block hashes
+    orphan_chain_tips_db: Tree,
+    /// Maps <parent_block_hash, block_hash> -> block_hash
+    orphan_parent_map_index: Tree,
+    /// Stores bad blocks by block_hash and height
+    bad_blocks: Tree,
+    /// Stores reorgs by epochtime and Reorg
+    reorgs: Tree,
+    /// Stores UTXO set statistics by block height
+    utxo_stats: Tree,
+    /// Maps script hashes and sender offset public keys to the heights and hashes of the outputs that use them
+    script_index: Tree,
+    /// Stores the outputs indexed in the script index by block height
+    script_index_blocks: Tree,
+    _file_lock: Arc<File>,
+}
+
+impl SledDatabase {
+    pub fn new(db: Db, file_lock: File) -> Result<Self, ChainStorageError> {
+        Ok(Self {
+            metadata_db: db.open_tree(SLED_DB_METADATA)?,
+            headers_db: db.open_tree(SLED_DB_HEADERS)?,
+            header_accumulated_data_db: db.open_tree(SLED_DB_HEADER_ACCUMULATED_DATA)?,
+            block_accumulated_data_db: db.open_tree(SLED_DB_BLOCK_ACCUMULATED_DATA)?,
+            block_hashes_db: db.open_tree(SLED_DB_BLOCK_HASHES)?,
+            utxos_db: db.open_tree(SLED_DB_UTXOS)?,
+            inputs_db: db.open_tree(SLED_DB_INPUTS)?,
+            txos_hash_to_index_db: db.open_tree(SLED_DB_TXOS_HASH_TO_INDEX)?,
+            kernels_db: db.open_tree(SLED_DB_KERNELS)?,
+            kernel_excess_index: db.open_tree(SLED_DB_KERNEL_EXCESS_INDEX)?,
+            kernel_excess_sig_index: db.open_tree(SLED_DB_KERNEL_EXCESS_SIG_INDEX)?,
+            kernel_mmr_size_index: db.open_tree(SLED_DB_KERNEL_MMR_SIZE_INDEX)?,
+            output_mmr_size_index: db.open_tree(SLED_DB_UTXO_MMR_SIZE_INDEX)?,
+            utxo_commitment_index: db.open_tree(SLED_DB_UTXO_COMMITMENT_INDEX)?,
+            deleted_txo_mmr_position_to_height_index: db.open_tree(SLED_DB_DELETED_TXO_MMR_POSITION_TO_HEIGHT_INDEX)?,
+            orphans_db: db.open_tree(SLED_DB_ORPHANS)?,
+            monero_seed_height_db: db.open_tree(SLED_DB_MONERO_SEED_HEIGHT)?,
+            orphan_header_accumulated_data_db: db.open_tree(SLED_DB_ORPHAN_HEADER_ACCUMULATED_DATA)?,
+            orphan_chain_tips_db: db.open_tree(SLED_DB_ORPHAN_CHAIN_TIPS)?,
+            orphan_parent_map_index: db.open_tree(SLED_DB_ORPHAN_PARENT_MAP_INDEX)?,
+            bad_blocks: db.open_tree(SLED_DB_BAD_BLOCK_LIST)?,
+            reorgs: db.open_tree(SLED_DB_REORGS)?,
+            utxo_stats: db.open_tree(SLED_DB_UTXO_STATS)?,
+            script_index: db.open_tree(SLED_DB_SCRIPT_INDEX)?,
+            script_index_blocks: db.open_tree(SLED_DB_SCRIPT_INDEX_BLOCKS)?,
+            db,
+            _file_lock: Arc::new(file_lock),
+        })
+    }
+
+    /// Returns a new transaction. Reads see the committed state of the database plus any writes made through the
+    /// transaction, which are only persisted once it is committed.
+    fn transaction(&self) -> SledTransaction {
+        SledTransaction::new()
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn apply_db_transaction(&self, txn: &DbTransaction) -> Result<(), ChainStorageError> {
+        #[allow(clippy::enum_glob_use)]
+        use WriteOperation::*;
+        let write_txn = self.transaction();
+        for op in txn.operations() {
+            trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {}", op);
+            match op {
+                InsertOrphanBlock(block) => self.insert_orphan_block(&write_txn, block)?,
+                InsertChainHeader { header } => {
+                    self.insert_header(&write_txn, header.header(), header.accumulated_data())?;
+                },
+                InsertBlockBody { block } => {
+                    self.insert_block_body(&write_txn, block.header(), block.block().body.clone())?;
+                },
+                InsertKernel {
+                    header_hash,
+                    kernel,
+                    mmr_position,
+                } => {
+                    self.insert_kernel(&write_txn, header_hash, &**kernel, *mmr_position)?;
+                },
+                InsertOutput {
+                    header_hash,
+                    header_height,
+                    output,
+                    mmr_position,
+                    timestamp,
+                } => {
+                    self.insert_output(
+                        &write_txn,
+                        header_hash,
+                        *header_height,
+                        &*output,
+                        *mmr_position,
+                        *timestamp,
+                    )?;
+                },
+                InsertPrunedOutput {
+                    header_hash,
+                    header_height,
+                    output_hash,
+                    witness_hash,
+                    mmr_position,
+                    timestamp,
+                } => {
+                    self.insert_pruned_output(
+                        &write_txn,
+                        header_hash,
+                        *header_height,
+                        output_hash,
+                        witness_hash,
+                        *mmr_position,
+                        *timestamp,
+                    )?;
+                },
+                DeleteHeader(height) => {
+                    self.delete_header(&write_txn, *height)?;
+                },
+                DeleteOrphan(hash) => {
+                    self.delete_orphan(&write_txn, hash)?;
+                },
+                DeleteOrphanChainTip(hash) => {
+                    sled_delete(
+                        &write_txn,
+                        &self.orphan_chain_tips_db,
+                        hash.as_slice(),
+                        "orphan_chain_tips_db",
+                    )?;
+                },
+                InsertOrphanChainTip(hash) => {
+                    sled_insert(
+                        &write_txn,
+                        &self.orphan_chain_tips_db,
+                        hash.as_slice(),
+                        hash,
+                        "orphan_chain_tips_db",
+                    )?;
+                },
+                DeleteBlock(hash) => {
+                    self.delete_block_body(&write_txn, hash)?;
+                },
+                InsertMoneroSeedHeight(data, height) => {
+                    self.insert_monero_seed_height(&write_txn, data, *height)?;
+                },
+                SetAccumulatedDataForOrphan(accumulated_data) => {
+                    self.set_accumulated_data_for_orphan(&write_txn, accumulated_data)?;
+                },
+                InsertChainOrphanBlock(chain_block) => {
+                    self.insert_orphan_block(&write_txn, chain_block.block())?;
+                    self.set_accumulated_data_for_orphan(&write_txn, chain_block.accumulated_data())?;
+                },
+                UpdateBlockAccumulatedData { header_hash, values } => {
+                    self.update_block_accumulated_data(&write_txn, header_hash, values.clone())?;
+                },
+                UpdateDeletedBitmap { deleted } => {
+                    let mut bitmap = fetch_deleted_bitmap(&write_txn, &self.metadata_db)?;
+                    bitmap.bitmap_mut().or_inplace(deleted);
+                    self.save_deleted_bitmap(&write_txn, bitmap)?;
+                },
+                PruneOutputsAtMmrPositions { output_positions } => {
+                    self.prune_outputs_at_positions(&write_txn, output_positions)?;
+                },
+                DeleteAllInputsInBlock { block_hash } => {
+                    self.delete_all_inputs_in_block(&write_txn, block_hash)?;
+                },
+                PruneKernelsInBlock { block_hash } => {
+                    self.prune_block_kernels(&write_txn, block_hash.as_slice())?;
+                },
+                SetBestBlock {
+                    height,
+                    hash,
+                    accumulated_difficulty,
+                    expected_prev_best_block,
+                    timestamp,
+                } => {
+                    // for security we check that the best block does exist, and we check the previous value
+                    // we dont want to check this if the prev block has never been set, this means a empty hash of 32
+                    // bytes.
+                    if *height > 0 {
+                        let prev = fetch_best_block(&write_txn, &self.metadata_db)?;
+                        if *expected_prev_best_block != prev {
+                            return Err(ChainStorageError::InvalidOperation(format!(
+                                "There was a change in best_block, the best block is suppose to be: ({}), but it \
+                                 currently is: ({})",
+                                expected_prev_best_block.to_hex(),
+                                prev.to_hex(),
+                            )));
+                        };
+                    }
+                    if !sled_exists(&write_txn, &self.block_hashes_db, hash.as_slice())? {
+                        // we dont care about the header or the height, we just want to know its there.
+                        return Err(ChainStorageError::InvalidOperation(format!(
+                            "There is no Blockheader hash ({}) in db",
+                            expected_prev_best_block.to_hex(),
+                        )));
+                    };
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::ChainHeight,
+                        &MetadataValue::ChainHeight(*height),
+                    )?;
+                    self.set_metadata(&write_txn, MetadataKey::BestBlock, &MetadataValue::BestBlock(*hash))?;
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::AccumulatedWork,
+                        &MetadataValue::AccumulatedWork(*accumulated_difficulty),
+                    )?;
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::BestBlockTimestamp,
+                        &MetadataValue::BestBlockTimestamp(*timestamp),
+                    )?;
+                },
+                SetPruningHorizonConfig(pruning_horizon) => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::PruningHorizon,
+                        &MetadataValue::PruningHorizon(*pruning_horizon),
+                    )?;
+                },
+                SetPrunedHeight { height } => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::PrunedHeight,
+                        &MetadataValue::PrunedHeight(*height),
+                    )?;
+                },
+                SetKernelPrunedHeight { height } => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::KernelPrunedHeight,
+                        &MetadataValue::KernelPrunedHeight(*height),
+                    )?;
+                },
+                SetHorizonData { horizon_data } => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::HorizonData,
+                        &MetadataValue::HorizonData(horizon_data.clone()),
+                    )?;
+                },
+                InsertBadBlock { hash, height } => {
+                    self.insert_bad_block_and_cleanup(&write_txn, hash, *height)?;
+                },
+                InsertReorg { reorg } => {
+                    sled_replace(
+                        &write_txn,
+                        &self.reorgs,
+                        &reorg.local_time.timestamp().to_be_bytes(),
+                        &reorg,
+                    )?;
+                },
+                ClearAllReorgs => {
+                    sled_clear(&write_txn, &self.reorgs)?;
+                },
+                InsertUtxoStats { stats } => {
+                    sled_replace(&write_txn, &self.utxo_stats, &stats.height.to_be_bytes(), &stats)?;
+                },
+                DeleteUtxoStats { height } => {
+                    sled_delete(&write_txn, &self.utxo_stats, &height.to_be_bytes(), SLED_DB_UTXO_STATS)?;
+                },
+                ClearAllUtxoStats => {
+                    sled_clear(&write_txn, &self.utxo_stats)?;
+                },
+                InsertScriptIndexBlock { block } => {
+                    self.insert_script_index_block(&write_txn, block)?;
+                },
+                DeleteScriptIndexBlock { height } => {
+                    self.delete_script_index_block(&write_txn, *height)?;
+                },
+                ClearScriptIndex => {
+                    sled_clear(&write_txn, &self.script_index)?;
+                    sled_clear(&write_txn, &self.script_index_blocks)?;
+                },
+            }
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    fn all_trees(&self) -> [(&'static str, &Tree); 25] {
+        [
+            ("metadata_db", &self.metadata_db),
+            ("headers_db", &self.headers_db),
+            ("header_accumulated_data_db", &self.header_accumulated_data_db),
+            ("block_accumulated_data_db", &self.block_accumulated_data_db),
+            ("block_hashes_db", &self.block_hashes_db),
+            ("utxos_db", &self.utxos_db),
+            ("inputs_db", &self.inputs_db),
+            ("txos_hash_to_index_db", &self.txos_hash_to_index_db),
+            ("kernels_db", &self.kernels_db),
+            ("kernel_excess_index", &self.kernel_excess_index),
+            ("kernel_excess_sig_index", &self.kernel_excess_sig_index),
+            ("kernel_mmr_size_index", &self.kernel_mmr_size_index),
+            ("output_mmr_size_index", &self.output_mmr_size_index),
+            ("utxo_commitment_index", &self.utxo_commitment_index),
+            (
+                "deleted_txo_mmr_position_to_height_index",
+                &self.deleted_txo_mmr_position_to_height_index,
+            ),
+            ("orphans_db", &self.orphans_db),
+            (
+                "orphan_header_accumulated_data_db",
+                &self.orphan_header_accumulated_data_db,
+            ),
+            ("monero_seed_height_db", &self.monero_seed_height_db),
+            ("orphan_chain_tips_db", &self.orphan_chain_tips_db),
+            ("orphan_parent_map_index", &self.orphan_parent_map_index),
+            ("bad_blocks", &self.bad_blocks),
+            ("reorgs", &self.reorgs),
+            ("utxo_stats", &self.utxo_stats),
+            ("script_index", &self.script_index),
+            ("script_index_blocks", &self.script_index_blocks),
+        ]
+    }
+
+    fn prune_output(&self, txn: &SledTransaction, key: &OutputKey) -> Result<TransactionOutput, ChainStorageError> {
+        let mut output: TransactionOutputRowData =
+            sled_get(txn, &self.utxos_db, key.as_bytes()).or_not_found("TransactionOutput", "key", key.to_hex())?;
+        let pruned_output = output
+            .output
+            .take()
+            .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+                function: "prune_output",
+                details: format!(
+                    "Attempt to prune output that has already been pruned for key {}",
+                    key.to_hex()
+                ),
+            })?;
+        // output.output is None
+        sled_replace(txn, &self.utxos_db, key.as_bytes(), &output)?;
+        Ok(pruned_output)
+    }
+
+    fn insert_output(
+        &self,
+        txn: &SledTransaction,
+        header_hash: &HashOutput,
+        header_height: u64,
+        output: &TransactionOutput,
+        mmr_position: u32,
+        timestamp: u64,
+    ) -> Result<(), ChainStorageError> {
+        let output_hash = output.hash();
+        let witness_hash = output.witness_hash();
+
+        let output_key = OutputKey::new(header_hash.as_slice(), mmr_position, &[]);
+
+        sled_insert(
+            txn,
+            &self.utxo_commitment_index,
+            output.commitment.as_bytes(),
+            &output_hash,
+            "utxo_commitment_index",
+        )?;
+        sled_insert(
+            txn,
+            &self.txos_hash_to_index_db,
+            output_hash.as_slice(),
+            &(mmr_position, output_key.as_bytes()),
+            "txos_hash_to_index_db",
+        )?;
+        sled_insert(
+            txn,
+            &self.utxos_db,
+            output_key.as_bytes(),
+            &TransactionOutputRowData {
+                output: Some(output.clone()),
+                header_hash: *header_hash,
+                mmr_position,
+                hash: output_hash,
+                witness_hash,
+                mined_height: header_height,
+                mined_timestamp: timestamp,
+            },
+            "utxos_db",
+        )
+    }
+
+    fn insert_pruned_output(
+        &self,
+        txn: &SledTransaction,
+        header_hash: &HashOutput,
+        header_height: u64,
+        output_hash: &HashOutput,
+        witness_hash: &HashOutput,
+        mmr_position: u32,
+        timestamp: u64,
+    ) -> Result<(), ChainStorageError> {
+        if !sled_exists(txn, &self.block_hashes_db, header_hash.as_slice())? {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Unable to insert pruned output because header {} does not exist",
+                header_hash.to_hex(),
+            )));
+        }
+        let key = OutputKey::new(header_hash.as_slice(), mmr_position, &[]);
+        sled_insert(
+            txn,
+            &self.txos_hash_to_index_db,
+            output_hash.as_slice(),
+            &(mmr_position, key.as_bytes()),
+            "txos_hash_to_index_db",
+        )?;
+        sled_insert(
+            txn,
+            &self.utxos_db,
+            key.as_bytes(),
+            &TransactionOutputRowData {
+                output: None,
+                header_hash: *header_hash,
+                mmr_position,
+                hash: *output_hash,
+                witness_hash: *witness_hash,
+                mined_height: header_height,
+                mined_timestamp: timestamp,
+            },
+            "utxos_db",
+        )
+    }
+
+    fn insert_kernel(
+        &self,
+        txn: &SledTransaction,
+        header_hash: &HashOutput,
+        kernel: &TransactionKernel,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        let hash = kernel.hash();
+        let key = KernelKey::new(header_hash.as_slice(), mmr_position, hash.as_slice());
+
+        sled_insert(
+            txn,
+            &self.kernel_excess_index,
+            kernel.excess.as_bytes(),
+            &(*header_hash, mmr_position, hash),
+            "kernel_excess_index",
+        )?;
+        sled_insert(
+            txn,
+            &self.kernel_excess_sig_index,
+            &excess_sig_key(&kernel.excess_sig),
+            &(*header_hash, mmr_position, hash),
+            "kernel_excess_sig_index",
+        )?;
+        sled_insert(
+            txn,
+            &self.kernels_db,
+            key.as_bytes(),
+            &TransactionKernelRowData {
+                kernel: kernel.clone(),
+                header_hash: *header_hash,
+                mmr_position,
+                hash,
+            },
+            "kernels_db",
+        )
+    }
+
+    fn insert_input(
+        &self,
+        txn: &SledTransaction,
+        height: u64,
+        header_hash: &HashOutput,
+        input: &TransactionInput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        sled_delete(
+            txn,
+            &self.utxo_commitment_index,
+            input.commitment()?.as_bytes(),
+            "utxo_commitment_index",
+        )
+        .or_else(|err| match err {
+            // The commitment may not yet be included in the DB in the 0-conf transaction case
+            ChainStorageError::ValueNotFound { .. } => Ok(()),
+            _ => Err(err),
+        })?;
+        sled_insert(
+            txn,
+            &self.deleted_txo_mmr_position_to_height_index,
+            &mmr_position.to_be_bytes(),
+            &(height, header_hash),
+            "deleted_txo_mmr_position_to_height_index",
+        )?;
+
+        let hash = input.canonical_hash()?;
+        let key = InputKey::new(header_hash.as_slice(), mmr_position, hash.as_slice());
+        sled_insert(
+            txn,
+            &self.inputs_db,
+            key.as_bytes(),
+            &TransactionInputRowDataRef {
+                input: &input.to_compact(),
+                header_hash,
+                mmr_position,
+                hash: &hash,
+            },
+            "inputs_db",
+        )
+    }
+
+    fn set_metadata(&self, txn: &SledTransaction, k: MetadataKey, v: &MetadataValue) -> Result<(), ChainStorageError> {
+        sled_replace(txn, &self.metadata_db, &k.as_u32().to_be_bytes(), v)
+    }
+
+    fn save_deleted_bitmap(&self, txn: &SledTransaction, mut bitmap: DeletedBitmap) -> Result<(), ChainStorageError> {
+        bitmap.bitmap_mut().run_optimize();
+        self.set_metadata(txn, MetadataKey::DeletedBitmap, &MetadataValue::DeletedBitmap(bitmap))
+    }
+
+    fn insert_orphan_block(&self, txn: &SledTransaction, block: &Block) -> Result<(), ChainStorageError> {
+        let k = block.hash();
+        sled_replace(
+            txn,
+            &self.orphan_parent_map_index,
+            &orphan_parent_key(&block.header.prev_hash, &k),
+            &k,
+        )?;
+        sled_insert(txn, &self.orphans_db, k.as_slice(), &block, "orphans_db")
+    }
+
+    fn set_accumulated_data_for_orphan(
+        &self,
+        txn: &SledTransaction,
+        accumulated_data: &BlockHeaderAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        if !sled_exists(txn, &self.orphans_db, accumulated_data.hash.as_slice())? {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "set_accumulated_data_for_orphan: orphan {} does not exist",
+                accumulated_data.hash.to_hex()
+            )));
+        }
+
+        sled_insert(
+            txn,
+            &self.orphan_header_accumulated_data_db,
+            accumulated_data.hash.as_slice(),
+            &accumulated_data,
+            "orphan_header_accumulated_data_db",
+        )
+    }
+
+    /// Inserts the header and header accumulated data.
+    fn insert_header(
+        &self,
+        txn: &SledTransaction,
+        header: &BlockHeader,
+        accum_data: &BlockHeaderAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        let height_key = header.height.to_be_bytes();
+        if let Some(current_header_at_height) = sled_get::<BlockHeader>(txn, &self.headers_db, &height_key)? {
+            let hash = current_header_at_height.hash();
+            if hash != accum_data.hash {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "There is a different header stored at height {} already. New header ({}), current header: ({})",
+                    header.height,
+                    accum_data.hash.to_hex(),
+                    hash.to_hex(),
+                )));
+            }
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "The header at height {} already exists. Existing header hash: {}",
+                header.height,
+                hash.to_hex()
+            )));
+        }
+
+        // Check that the current height is still header.height - 1 and that no other threads have inserted
+        match self.fetch_last_header_in_txn(txn)? {
+            Some(last_header) => {
+                if last_header.height != header.height.saturating_sub(1) {
+                    return Err(ChainStorageError::InvalidOperation(format!(
+                        "Attempted to insert a header out of order. The last header height is {} but attempted to \
+                         insert a header with height {}",
+                        last_header.height, header.height,
+                    )));
+                }
+
+                let hash = last_header.hash();
+                if hash != header.prev_hash {
+                    return Err(ChainStorageError::InvalidOperation(format!(
+                        "Attempted to insert a block header at height {} that didn't form a chain. Previous block \
+                         hash:{}, new block's previous hash:{}",
+                        header.height,
+                        hash.to_hex(),
+                        header.prev_hash.to_hex()
+                    )));
+                }
+            },
+            None => {
+                if header.height != 0 {
+                    return Err(ChainStorageError::InvalidOperation(format!(
+                        "The first header inserted must have height 0. Height provided: {}",
+                        header.height
+                    )));
+                }
+            },
+        }
+
+        sled_insert(
+            txn,
+            &self.header_accumulated_data_db,
+            &height_key,
+            &accum_data,
+            "header_accumulated_data_db",
+        )?;
+        sled_insert(
+            txn,
+            &self.block_hashes_db,
+            header.hash().as_slice(),
+            &header.height,
+            "block_hashes_db",
+        )?;
+        sled_insert(txn, &self.headers_db, &height_key, header, "headers_db")?;
+        sled_insert(
+            txn,
+            &self.kernel_mmr_size_index,
+            &header.kernel_mmr_size.to_be_bytes(),
+            &header.height,
+            "kernel_mmr_size_index",
+        )?;
+        sled_insert(
+            txn,
+            &self.output_mmr_size_index,
+            &header.output_mmr_size.to_be_bytes(),
+            &(header.height, header.hash().as_slice()),
+            "output_mmr_size_index",
+        )
+    }
+
+    fn delete_header(&self, txn: &SledTransaction, height: u64) -> Result<(), ChainStorageError> {
+        if self.fetch_block_accumulated_data_in_txn(txn, height)?.is_some() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Attempted to delete header at height {} while block accumulated data still exists",
+                height
+            )));
+        }
+
+        let header =
+            self.fetch_last_header_in_txn(txn)
+                .or_not_found("BlockHeader", "height", "last_header".to_string())?;
+        if header.height != height {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Attempted to delete a header at height {} that was not the last header (which is at height {}). \
+                 Headers must be deleted in reverse order.",
+                height, header.height
+            )));
+        }
+
+        let hash = header.hash();
+
+        // Check that there are no utxos or kernels linked to this.
+        if !sled_fetch_matching_after::<TransactionKernelRowData>(txn, &self.kernels_db, hash.as_slice())?.is_empty() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Cannot delete header {} ({}) because there are kernels linked to it",
+                header.height,
+                hash.to_hex()
+            )));
+        }
+        if !sled_fetch_matching_after::<TransactionOutputRowData>(txn, &self.utxos_db, hash.as_slice())?.is_empty() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Cannot delete header at height {} ({}) because there are UTXOs linked to it",
+                height,
+                hash.to_hex()
+            )));
+        }
+
+        let height_key = height.to_be_bytes();
+        sled_delete(txn, &self.block_hashes_db, hash.as_slice(), "block_hashes_db")?;
+        sled_delete(txn, &self.headers_db, &height_key, "headers_db")?;
+        sled_delete(
+            txn,
+            &self.header_accumulated_data_db,
+            &height_key,
+            "header_accumulated_data_db",
+        )?;
+        sled_delete(
+            txn,
+            &self.kernel_mmr_size_index,
+            &header.kernel_mmr_size.to_be_bytes(),
+            "kernel_mmr_size_index",
+        )?;
+        sled_delete(
+            txn,
+            &self.output_mmr_size_index,
+            &header.output_mmr_size.to_be_bytes(),
+            "output_mmr_size_index",
+        )
+    }
+
+    fn delete_block_body(&self, txn: &SledTransaction, block_hash: &HashOutput) -> Result<(), ChainStorageError> {
+        let hash_hex = block_hash.to_hex();
+        debug!(target: LOG_TARGET, "Deleting block `{}`", hash_hex);
+        let height = self
+            .fetch_height_from_hash(txn, block_hash)
+            .or_not_found("Block", "hash", hash_hex)?;
+        let block_accum_data = self.fetch_block_accumulated_data_in_txn(txn, height).or_not_found(
+            "BlockAccumulatedData",
+            "height",
+            height.to_string(),
+        )?;
+        let mut bitmap = fetch_deleted_bitmap(txn, &self.metadata_db)?;
+        bitmap.bitmap_mut().andnot_inplace(block_accum_data.deleted());
+        self.save_deleted_bitmap(txn, bitmap)?;
+
+        sled_delete(
+            txn,
+            &self.block_accumulated_data_db,
+            &height.to_be_bytes(),
+            "block_accumulated_data_db",
+        )?;
+
+        self.delete_block_inputs_outputs(txn, block_hash.as_slice())?;
+        self.delete_block_kernels(txn, block_hash.as_slice())
+    }
+
+    fn delete_block_inputs_outputs(&self, txn: &SledTransaction, block_hash: &[u8]) -> Result<(), ChainStorageError> {
+        let output_rows = sled_delete_keys_starting_with::<TransactionOutputRowData>(txn, &self.utxos_db, block_hash)?;
+        debug!(target: LOG_TARGET, "Deleted {} outputs...", output_rows.len());
+        let inputs = sled_delete_keys_starting_with::<TransactionInputRowData>(txn, &self.inputs_db, block_hash)?;
+        debug!(target: LOG_TARGET, "Deleted {} input(s)...", inputs.len());
+
+        for utxo in &output_rows {
+            trace!(target: LOG_TARGET, "Deleting UTXO `{}`", to_hex(utxo.hash.as_slice()));
+            sled_delete(
+                txn,
+                &self.txos_hash_to_index_db,
+                utxo.hash.as_slice(),
+                "txos_hash_to_index_db",
+            )?;
+            if let Some(ref output) = utxo.output {
+                let output_hash = output.hash();
+                // if an output was already spent in the block, it was never created as unspent, so dont delete it as it
+                // does not exist here
+                if inputs.iter().any(|r| r.input.output_hash() == output_hash) {
+                    continue;
+                }
+                sled_delete(
+                    txn,
+                    &self.utxo_commitment_index,
+                    output.commitment.as_bytes(),
+                    "utxo_commitment_index",
+                )?;
+            }
+        }
+        // Move inputs in this block back into the unspent set, any outputs spent within this block they will be removed
+        // by deleting all the block's outputs above
+        for row in inputs {
+            // If input spends an output in this block, don't add it to the utxo set
+            let output_hash = row.input.output_hash();
+            if output_rows.iter().any(|r| r.hash == output_hash) {
+                continue;
+            }
+            let mut input = row.input.clone();
+
+            let utxo_mined_info = self.fetch_output_in_txn(txn, output_hash.as_slice()).or_not_found(
+                "UTXO",
+                "hash",
+                output_hash.to_hex(),
+            )?;
+
+            match utxo_mined_info.output {
+                PrunedOutput::Pruned { .. } => {
+                    debug!(target: LOG_TARGET, "Output Transaction Input is spending is pruned");
+                    return Err(ChainStorageError::TransactionError(
+                        TransactionError::MissingTransactionInputData,
+                    ));
+                },
+                PrunedOutput::NotPruned { output } => {
+                    input.add_output_data(
+                        output.version,
+                        output.features,
+                        output.commitment,
+                        output.script,
+                        output.sender_offset_public_key,
+                        output.covenant,
+                        output.encrypted_value,
+                        output.minimum_value_promise,
+                    );
+                },
+            }
+
+            trace!(target: LOG_TARGET, "Input moved to UTXO set: {}", input);
+            sled_insert(
+                txn,
+                &self.utxo_commitment_index,
+                input.commitment()?.as_bytes(),
+                &input.output_hash(),
+                "utxo_commitment_index",
+            )?;
+            sled_delete(
+                txn,
+                &self.deleted_txo_mmr_position_to_height_index,
+                &row.mmr_position.to_be_bytes(),
+                "deleted_txo_mmr_position_to_height_index",
+            )?;
+        }
+        Ok(())
+    }
+
+    fn delete_block_kernels(&self, txn: &SledTransaction, block_hash: &[u8]) -> Result<(), ChainStorageError> {
+        let kernels = sled_delete_keys_starting_with::<TransactionKernelRowData>(txn, &self.kernels_db, block_hash)?;
+        debug!(target: LOG_TARGET, "Deleted {} kernels...", kernels.len());
+        for kernel in kernels {
+            trace!(
+                target: LOG_TARGET,
+                "Deleting excess `{}`",
+                kernel.kernel.excess.to_hex()
+            );
+            sled_delete(
+                txn,
+                &self.kernel_excess_index,
+                kernel.kernel.excess.as_bytes(),
+                "kernel_excess_index",
+            )?;
+            let excess_sig_key = excess_sig_key(&kernel.kernel.excess_sig);
+            trace!(
+                target: LOG_TARGET,
+                "Deleting excess signature `{}`",
+                to_hex(&excess_sig_key)
+            );
+            sled_delete(
+                txn,
+                &self.kernel_excess_sig_index,
+                &excess_sig_key,
+                "kernel_excess_sig_index",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the kernels in a block. Unlike `delete_block_kernels`, the excess and excess signature index entries
+    /// are kept, as they are what rejects a new kernel that reuses an existing excess or excess signature.
+    fn prune_block_kernels(&self, txn: &SledTransaction, block_hash: &[u8]) -> Result<(), ChainStorageError> {
+        let kernels = sled_delete_keys_starting_with::<TransactionKernelRowData>(txn, &self.kernels_db, block_hash)?;
+        debug!(
+            target: LOG_TARGET,
+            "Pruned {} kernel(s) in block {}",
+            kernels.len(),
+            to_hex(block_hash)
+        );
+        Ok(())
+    }
+
+    fn insert_script_index_block(
+        &self,
+        txn: &SledTransaction,
+        block: &ScriptIndexBlock,
+    ) -> Result<(), ChainStorageError> {
+        for (key, value) in block.index_entries() {
+            sled_replace(txn, &self.script_index, &key, &value)?;
+        }
+        sled_replace(txn, &self.script_index_blocks, &block.height.to_be_bytes(), block)
+    }
+
+    fn delete_script_index_block(&self, txn: &SledTransaction, height: u64) -> Result<(), ChainStorageError> {
+        let block = sled_get::<ScriptIndexBlock>(txn, &self.script_index_blocks, &height.to_be_bytes()).or_not_found(
+            "ScriptIndexBlock",
+            "height",
+            height.to_string(),
+        )?;
+        for (key, _) in block.index_entries() {
+            sled_delete(txn, &self.script_index, &key, SLED_DB_SCRIPT_INDEX)?;
+        }
+        sled_delete(
+            txn,
+            &self.script_index_blocks,
+            &height.to_be_bytes(),
+            SLED_DB_SCRIPT_INDEX_BLOCKS,
+        )
+    }
+
+    fn delete_orphan(&self, txn: &SledTransaction, hash: &HashOutput) -> Result<(), ChainStorageError> {
+        let orphan = match sled_get::<Block>(txn, &self.orphans_db, hash.as_slice())? {
+            Some(orphan) => orphan,
+            None => {
+                // delete_orphan is idempotent
+                debug!(
+                    target: LOG_TARGET,
+                    "delete_orphan: request to delete orphan block {} that was not found.",
+                    hash.to_hex()
+                );
+                return Ok(());
+            },
+        };
+
+        let parent_hash = orphan.header.prev_hash;
+        sled_delete(
+            txn,
+            &self.orphan_parent_map_index,
+            &orphan_parent_key(&parent_hash, hash),
+            "orphan_parent_map_index",
+        )?;
+
+        // Orphan is a tip hash
+        if sled_exists(txn, &self.orphan_chain_tips_db, hash.as_slice())? {
+            sled_delete(txn, &self.orphan_chain_tips_db, hash.as_slice(), "orphan_chain_tips_db")?;
+
+            // Parent becomes a tip hash
+            if sled_exists(txn, &self.orphans_db, parent_hash.as_slice())? {
+                sled_insert(
+                    txn,
+                    &self.orphan_chain_tips_db,
+                    parent_hash.as_slice(),
+                    &parent_hash,
+                    "orphan_chain_tips_db",
+                )?;
+            }
+        }
+
+        if sled_exists(txn, &self.orphan_header_accumulated_data_db, hash.as_slice())? {
+            sled_delete(
+                txn,
+                &self.orphan_header_accumulated_data_db,
+                hash.as_slice(),
+                "orphan_header_accumulated_data_db",
+            )?;
+        }
+        sled_delete(txn, &self.orphans_db, hash.as_slice(), "orphans_db")
+    }
+
+    // Break function up into smaller pieces
+    #[allow(clippy::too_many_lines)]
+    fn insert_block_body(
+        &self,
+        txn: &SledTransaction,
+        header: &BlockHeader,
+        body: AggregateBody,
+    ) -> Result<(), ChainStorageError> {
+        let block_hash = header.hash();
+        debug!(
+            target: LOG_TARGET,
+            "Inserting block body for header `{}`: {}",
+            block_hash.to_hex(),
+            body.to_counts_string()
+        );
+
+        // Check that the database has not been changed by another thread
+        // 1. The header we are inserting for matches the header at that height
+        let current_header_at_height = sled_get::<BlockHeader>(txn, &self.headers_db, &header.height.to_be_bytes())
+            .or_not_found("BlockHeader", "height", header.height.to_string())?;
+        let hash = current_header_at_height.hash();
+        if hash != block_hash {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Could not insert this block body because there is a different header stored at height {}. New header \
+                 ({}), current header: ({})",
+                header.height,
+                hash.to_hex(),
+                block_hash.to_hex()
+            )));
+        }
+
+        let (inputs, outputs, kernels) = body.dissolve();
+
+        let data = if header.height == 0 {
+            BlockAccumulatedData::default()
+        } else {
+            self.fetch_block_accumulated_data_in_txn(txn, header.height - 1)
+                .or_not_found("BlockAccumulatedData", "height", (header.height - 1).to_string())?
+        };
+
+        let mut total_kernel_sum = Commitment::default();
+        let BlockAccumulatedData {
+            kernels: pruned_kernel_set,
+            outputs: pruned_output_set,
+            witness: pruned_proof_set,
+            ..
+        } = data;
+
+        let mut kernel_mmr = PrunedKernelMmr::new(pruned_kernel_set);
+
+        for kernel in kernels {
+            total_kernel_sum = &total_kernel_sum + &kernel.excess;
+            let pos = kernel_mmr.push(kernel.hash().to_vec())?;
+            trace!(
+                target: LOG_TARGET,
+                "Inserting kernel `{}`",
+                kernel.excess_sig.get_signature().to_hex()
+            );
+            let pos = u32::try_from(pos).map_err(|_| {
+                ChainStorageError::InvalidOperation(format!("Kernel MMR node count ({}) is greater than u32::MAX", pos))
+            })?;
+            self.insert_kernel(txn, &block_hash, &kernel, pos)?;
+        }
+        let mut output_mmr = MutablePrunedOutputMmr::new(pruned_output_set, Bitmap::create())?;
+        let mut witness_mmr = PrunedWitnessMmr::new(pruned_proof_set);
+
+        let leaf_count = witness_mmr.get_leaf_count()?;
+
+        // Output hashes added before inputs so that inputs can spend outputs in this transaction (0-conf and combined)
+        let mut burned_outputs = Vec::new();
+        let outputs = outputs
+            .into_iter()
+            .enumerate()
+            .map(|(i, output)| {
+                output_mmr.push(output.hash().to_vec())?;
+                witness_mmr.push(output.witness_hash().to_vec())?;
+                if output.is_burned() {
+                    let index = output_mmr.find_leaf_index(output.hash().as_slice())?.ok_or_else(|| {
+                        ChainStorageError::UnexpectedResult(
+                            "Output MMR did not contain the expected output".to_string(),
+                        )
+                    })?;
+                    debug!(target: LOG_TARGET, "Output {} burned in current block", output);
+                    burned_outputs.push(output.commitment.clone());
+                    // We need to mark this as spent as well.
+                    if !output_mmr.delete(index) {
+                        return Err(ChainStorageError::InvalidOperation(format!(
+                            "Could not delete index {} from the output MMR",
+                            index
+                        )));
+                    }
+                }
+                Ok((output, leaf_count + i + 1))
+            })
+            .collect::<Result<Vec<_>, ChainStorageError>>()?;
+
+        let mut spent_zero_conf_commitments = Vec::new();
+        for input in &inputs {
+            let output_hash = input.output_hash();
+            let index = match self.fetch_mmr_leaf_index_in_txn(txn, MmrTree::Utxo, &output_hash)? {
+                Some(index) => index,
+                None => match output_mmr.find_leaf_index(output_hash.as_slice())? {
+                    Some(index) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Input {} spends output from current block (0-conf)", input
+                        );
+                        spent_zero_conf_commitments.push(input.commitment()?);
+                        index
+                    },
+                    None => return Err(ChainStorageError::UnspendableInput),
+                },
+            };
+            if !output_mmr.delete(index) {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Could not delete index {} from the output MMR",
+                    index
+                )));
+            }
+            trace!(target: LOG_TARGET, "Inserting input `{}`", input.commitment()?.to_hex());
+            self.insert_input(txn, current_header_at_height.height, &block_hash, input, index)?;
+        }
+
+        for (output, mmr_count) in outputs {
+            trace!(target: LOG_TARGET, "Inserting output `{}`", output.commitment.to_hex());
+            let mmr_count = u32::try_from(mmr_count).map(|c| c - 1).map_err(|_| {
+                ChainStorageError::InvalidOperation(format!(
+                    "Output MMR node count ({}) is greater than u32::MAX",
+                    mmr_count
+                ))
+            })?;
+            self.insert_output(
+                txn,
+                &block_hash,
+                header.height,
+                &output,
+                mmr_count,
+                header.timestamp().as_u64(),
+            )?;
+        }
+
+        for commitment in spent_zero_conf_commitments.iter().chain(&burned_outputs) {
+            sled_delete(
+                txn,
+                &self.utxo_commitment_index,
+                commitment.as_bytes(),
+                "utxo_commitment_index",
+            )?;
+        }
+
+        // Merge the deletions at the current height into the blockchain deleted bitmap
+        let deleted_at_current_height = output_mmr.deleted().clone();
+        let mut deleted_bitmap = fetch_deleted_bitmap(txn, &self.metadata_db)?;
+        deleted_bitmap.bitmap_mut().or_inplace(&deleted_at_current_height);
+
+        // Set the output MMR to the complete map so that the complete state can be committed to in the final MR
+        output_mmr.set_deleted(deleted_bitmap.bitmap().clone());
+        output_mmr.compress();
+
+        self.save_deleted_bitmap(txn, deleted_bitmap)?;
+
+        sled_insert(
+            txn,
+            &self.block_accumulated_data_db,
+            &header.height.to_be_bytes(),
+            &BlockAccumulatedData::new(
+                kernel_mmr.get_pruned_hash_set()?,
+                output_mmr.mmr().get_pruned_hash_set()?,
+                witness_mmr.get_pruned_hash_set()?,
+                deleted_at_current_height,
+                total_kernel_sum,
+            ),
+            "block_accumulated_data_db",
+        )
+    }
+
+    fn update_block_accumulated_data(
+        &self,
+        txn: &SledTransaction,
+        header_hash: &HashOutput,
+        values: UpdateBlockAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        let height =
+            self.fetch_height_from_hash(txn, header_hash)
+                .or_not_found("BlockHash", "hash", header_hash.to_hex())?;
+
+        let mut block_accum_data = self
+            .fetch_block_accumulated_data_in_txn(txn, height)?
+            .unwrap_or_default();
+
+        if let Some(deleted_diff) = values.deleted_diff {
+            block_accum_data.deleted = deleted_diff;
+        }
+        if let Some(kernel_sum) = values.kernel_sum {
+            block_accum_data.kernel_sum = kernel_sum;
+        }
+        if let Some(kernel_hash_set) = values.kernel_hash_set {
+            block_accum_data.kernels = kernel_hash_set;
+        }
+        if let Some(utxo_hash_set) = values.utxo_hash_set {
+            block_accum_data.outputs = utxo_hash_set;
+        }
+        if let Some(witness_hash_set) = values.witness_hash_set {
+            block_accum_data.witness = witness_hash_set;
+        }
+
+        sled_replace(
+            txn,
+            &self.block_accumulated_data_db,
+            &height.to_be_bytes(),
+            &block_accum_data,
+        )
+    }
+
+    fn insert_monero_seed_height(
+        &self,
+        txn: &SledTransaction,
+        seed: &[u8],
+        height: u64,
+    ) -> Result<(), ChainStorageError> {
+        let current_height = sled_get(txn, &self.monero_seed_height_db, seed)?.unwrap_or(std::u64::MAX);
+        if height < current_height {
+            sled_replace(txn, &self.monero_seed_height_db, seed, &height)?;
+        };
+        Ok(())
+    }
+
+    fn delete_all_inputs_in_block(
+        &self,
+        txn: &SledTransaction,
+        block_hash: &BlockHash,
+    ) -> Result<(), ChainStorageError> {
+        let inputs =
+            sled_delete_keys_starting_with::<TransactionInputRowData>(txn, &self.inputs_db, block_hash.as_slice())?;
+        debug!(target: LOG_TARGET, "Deleted {} input(s)", inputs.len());
+        Ok(())
+    }
+
+    fn prune_outputs_at_positions(
+        &self,
+        txn: &SledTransaction,
+        output_positions: &[u32],
+    ) -> Result<(), ChainStorageError> {
+        for pos in output_positions {
+            let (_height, hash) =
+                sled_first_after::<(u64, Vec<u8>)>(txn, &self.output_mmr_size_index, &u64::from(pos + 1).to_be_bytes())
+                    .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
+            let key = OutputKey::new(&hash, *pos, &[]);
+            debug!(target: LOG_TARGET, "Pruning output: {}", key.to_hex());
+            self.prune_output(txn, &key)?;
+        }
+
+        Ok(())
+    }
+
+    fn fetch_mmr_leaf_index_in_txn(
+        &self,
+        txn: &SledTransaction,
+        tree: MmrTree,
+        hash: &HashOutput,
+    ) -> Result<Option<u32>, ChainStorageError> {
+        match tree {
+            MmrTree::Utxo => Ok(
+                sled_get::<(u32, Vec<u8>)>(txn, &self.txos_hash_to_index_db, hash.as_slice())?.map(|(index, _)| index),
+            ),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn fetch_orphan(&self, txn: &SledTransaction, hash: &HashOutput) -> Result<Option<Block>, ChainStorageError> {
+        sled_get(txn, &self.orphans_db, hash.as_slice())
+    }
+
+    fn fetch_block_accumulated_data_in_txn(
+        &self,
+        txn: &SledTransaction,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        sled_get(txn, &self.block_accumulated_data_db, &height.to_be_bytes())
+    }
+
+    fn fetch_height_from_hash(
+        &self,
+        txn: &SledTransaction,
+        header_hash: &HashOutput,
+    ) -> Result<Option<u64>, ChainStorageError> {
+        sled_get(txn, &self.block_hashes_db, header_hash.as_slice())
+    }
+
+    fn fetch_header_accumulated_data_by_height(
+        &self,
+        txn: &SledTransaction,
+        height: u64,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError> {
+        sled_get(txn, &self.header_accumulated_data_db, &height.to_be_bytes())
+    }
+
+    fn fetch_last_header_in_txn(&self, txn: &SledTransaction) -> Result<Option<BlockHeader>, ChainStorageError> {
+        sled_last(txn, &self.headers_db)
+    }
+
+    fn fetch_chain_header_in_txn(&self, txn: &SledTransaction, height: u64) -> Result<ChainHeader, ChainStorageError> {
+        let header: BlockHeader = sled_get(txn, &self.headers_db, &height.to_be_bytes()).or_not_found(
+            "BlockHeader",
+            "height",
+            height.to_string(),
+        )?;
+        let accum_data = self.fetch_header_accumulated_data_by_height(txn, height).or_not_found(
+            "BlockHeaderAccumulatedData",
+            "height",
+            height.to_string(),
+        )?;
+
+        ChainHeader::try_construct(header, accum_data).ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+            function: "fetch_chain_header_in_txn",
+            details: format!("Accumulated data mismatch at height #{}", height),
+        })
+    }
+
+    fn insert_bad_block_and_cleanup(
+        &self,
+        txn: &SledTransaction,
+        hash: &HashOutput,
+        height: u64,
+    ) -> Result<(), ChainStorageError> {
+        #[cfg(test)]
+        const CLEAN_BAD_BLOCKS_BEFORE_REL_HEIGHT: u64 = 10000;
+        #[cfg(not(test))]
+        const CLEAN_BAD_BLOCKS_BEFORE_REL_HEIGHT: u64 = 0;
+
+        sled_replace(txn, &self.bad_blocks, hash.as_slice(), &height)?;
+        // Clean up bad blocks that are far from the tip
+        let metadata = fetch_metadata(txn, &self.metadata_db)?;
+        let deleted_before_height = metadata
+            .height_of_longest_chain()
+            .saturating_sub(CLEAN_BAD_BLOCKS_BEFORE_REL_HEIGHT);
+        if deleted_before_height == 0 {
+            return Ok(());
+        }
+
+        let num_deleted =
+            sled_delete_each_where::<u64, _>(txn, &self.bad_blocks, |_, v| Some(v < deleted_before_height))?;
+        debug!(target: LOG_TARGET, "Cleaned out {} stale bad blocks", num_deleted);
+
+        Ok(())
+    }
+
+    fn fetch_output_in_txn(
+        &self,
+        txn: &SledTransaction,
+        output_hash: &[u8],
+    ) -> Result<Option<UtxoMinedInfo>, ChainStorageError> {
+        let key = match sled_get::<(u32, Vec<u8>)>(txn, &self.txos_hash_to_index_db, output_hash)? {
+            Some((_, key)) => key,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Fetch output: {} NOT found in index",
+                    to_hex(output_hash)
+                );
+                return Ok(None);
+            },
+        };
+
+        Ok(
+            sled_get::<TransactionOutputRowData>(txn, &self.utxos_db, &key)?.map(|row| UtxoMinedInfo {
+                output: match row.output {
+                    Some(output) => PrunedOutput::NotPruned { output },
+                    None => PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        witness_hash: row.witness_hash,
+                    },
+                },
+                mmr_position: row.mmr_position,
+                mined_height: row.mined_height,
+                header_hash: row.header_hash,
+                mined_timestamp: row.mined_timestamp,
+            }),
+        )
+    }
+}
+
+impl BlockchainBackend for SledDatabase {
+    fn write(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        if txn.operations().is_empty() {
+            return Ok(());
+        }
+
+        let mark = Instant::now();
+        let num_operations = txn.operations().len();
+        match self.apply_db_transaction(&txn) {
+            Ok(_) => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Database completed {} operation(s) in {:.0?}",
+                    num_operations,
+                    mark.elapsed()
+                );
+                Ok(())
+            },
+            Err(e) => {
+                error!(target: LOG_TARGET, "Failed to apply DB transaction: {:?}", e);
+                Err(e)
+            },
+        }
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        let txn = self.transaction();
+        let res = match key {
+            DbKey::BlockHeader(k) => sled_get::<BlockHeader>(&txn, &self.headers_db, &k.to_be_bytes())?
+                .map(|val| DbValue::BlockHeader(Box::new(val))),
+            DbKey::BlockHash(hash) => match self.fetch_height_from_hash(&txn, hash)? {
+                Some(k) => sled_get::<BlockHeader>(&txn, &self.headers_db, &k.to_be_bytes())?
+                    .map(|val| DbValue::BlockHash(Box::new(val))),
+                None => None,
+            },
+            DbKey::OrphanBlock(k) => self
+                .fetch_orphan(&txn, k)?
+                .map(|val| DbValue::OrphanBlock(Box::new(val))),
+        };
+        Ok(res)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        let txn = self.transaction();
+        match key {
+            DbKey::BlockHeader(k) => sled_exists(&txn, &self.headers_db, &k.to_be_bytes()),
+            DbKey::BlockHash(h) => sled_exists(&txn, &self.block_hashes_db, h.as_slice()),
+            DbKey::OrphanBlock(k) => sled_exists(&txn, &self.orphans_db, k.as_slice()),
+        }
+    }
+
+    fn fetch_chain_header_by_height(&self, height: u64) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        self.fetch_chain_header_in_txn(&txn, height)
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError> {
+        let txn = self.transaction();
+        match self.fetch_height_from_hash(&txn, hash)? {
+            Some(height) => self.fetch_header_accumulated_data_by_height(&txn, height),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        if let Some(height) = self.fetch_height_from_hash(&txn, hash)? {
+            return self.fetch_chain_header_in_txn(&txn, height);
+        }
+
+        let orphan_accum: Option<BlockHeaderAccumulatedData> =
+            sled_get(&txn, &self.orphan_header_accumulated_data_db, hash.as_slice())?;
+        if let Some(accum) = orphan_accum {
+            let orphan =
+                self.fetch_orphan(&txn, hash)?
+                    .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+                        function: "fetch_chain_header_in_all_chains",
+                        details: format!(
+                            "Orphan accumulated data exists but the corresponding orphan header {} does not",
+                            hash.to_hex()
+                        ),
+                    })?;
+            let chain_header = ChainHeader::try_construct(orphan.header, accum).ok_or_else(|| {
+                ChainStorageError::DataInconsistencyDetected {
+                    function: "fetch_chain_header_in_all_chains",
+                    details: format!("accumulated data mismatch for orphan header {}", hash.to_hex()),
+                }
+            })?;
+            return Ok(chain_header);
+        }
+
+        Err(ChainStorageError::ValueNotFound {
+            entity: "chain header (in chain_header_in_all_chains)",
+            field: "hash",
+            value: hash.to_hex(),
+        })
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        // The index maps the MMR size at each height, so we have to offset the position by 1 so that the mmr_position
+        // arg is an index starting from 0
+        let mmr_position = mmr_position + 1;
+
+        let height = sled_first_after::<u64>(&txn, &self.kernel_mmr_size_index, &mmr_position.to_be_bytes())
+            .or_not_found("kernel_mmr_size_index", "mmr_position", mmr_position.to_string())?;
+        self.fetch_chain_header_in_txn(&txn, height)
+    }
+
+    fn fetch_header_containing_utxo_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        // The index maps the MMR size at each height, so we have to offset the position by 1 so that the mmr_position
+        // arg is an index starting from 0
+        let mmr_position = mmr_position + 1;
+
+        let (height, _hash) =
+            sled_first_after::<(u64, Vec<u8>)>(&txn, &self.output_mmr_size_index, &mmr_position.to_be_bytes())
+                .or_not_found("output_mmr_size_index", "mmr_position", mmr_position.to_string())?;
+        self.fetch_chain_header_in_txn(&txn, height)
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        Ok(self.headers_db.is_empty())
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        let txn = self.transaction();
+        match self.fetch_height_from_hash(&txn, header_hash)? {
+            Some(height) => self.fetch_block_accumulated_data_in_txn(&txn, height),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        let txn = self.transaction();
+        self.fetch_block_accumulated_data_in_txn(&txn, height)
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let txn = self.transaction();
+        Ok(
+            sled_fetch_matching_after::<TransactionKernelRowData>(&txn, &self.kernels_db, header_hash.as_slice())?
+                .into_iter()
+                .map(|row| row.kernel)
+                .collect(),
+        )
+    }
+
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &[u8],
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        let txn = self.transaction();
+        match sled_get::<(HashOutput, u32, HashOutput)>(&txn, &self.kernel_excess_index, excess)? {
+            Some((header_hash, mmr_position, hash)) => {
+                let key = KernelKey::new(header_hash.as_slice(), mmr_position, hash.as_slice());
+                Ok(sled_get(&txn, &self.kernels_db, key.as_bytes())?
+                    .map(|row: TransactionKernelRowData| (row.kernel, header_hash)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        let txn = self.transaction();
+        match sled_get::<(HashOutput, u32, HashOutput)>(
+            &txn,
+            &self.kernel_excess_sig_index,
+            &excess_sig_key(excess_sig),
+        )? {
+            Some((header_hash, mmr_position, hash)) => {
+                let key = KernelKey::new(header_hash.as_slice(), mmr_position, hash.as_slice());
+                Ok(sled_get(&txn, &self.kernels_db, key.as_bytes())?
+                    .map(|row: TransactionKernelRowData| (row.kernel, header_hash)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        let txn = self.transaction();
+        Ok(
+            sled_get::<(HashOutput, u32, HashOutput)>(
+                &txn,
+                &self.kernel_excess_sig_index,
+                &excess_sig_key(excess_sig),
+            )?
+            .map(|(header_hash, _, _)| header_hash),
+        )
+    }
+
+    fn fetch_utxos_in_block(
+        &self,
+        header_hash: &HashOutput,
+        deleted: Option<&Bitmap>,
+    ) -> Result<(Vec<PrunedOutput>, Bitmap), ChainStorageError> {
+        let txn = self.transaction();
+
+        let utxos =
+            sled_fetch_matching_after::<TransactionOutputRowData>(&txn, &self.utxos_db, header_hash.as_slice())?
+                .into_iter()
+                .map(|row| match row.output {
+                    Some(output) if !deleted.map(|b| b.contains(row.mmr_position)).unwrap_or(false) => {
+                        PrunedOutput::NotPruned { output }
+                    },
+                    _ => PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        witness_hash: row.witness_hash,
+                    },
+                })
+                .collect();
+
+        let height =
+            self.fetch_height_from_hash(&txn, header_hash)
+                .or_not_found("BlockHeader", "hash", header_hash.to_hex())?;
+
+        // Builds a BitMap of the deleted UTXO MMR indexes that occurred at the current height
+        let acc_data = self.fetch_block_accumulated_data_in_txn(&txn, height).or_not_found(
+            "BlockAccumulatedData",
+            "height",
+            height.to_string(),
+        )?;
+
+        let mut difference_bitmap = Bitmap::create();
+        difference_bitmap.or_inplace(acc_data.deleted());
+
+        Ok((utxos, difference_bitmap))
+    }
+
+    fn fetch_output(&self, output_hash: &HashOutput) -> Result<Option<UtxoMinedInfo>, ChainStorageError> {
+        debug!(target: LOG_TARGET, "Fetch output: {}", output_hash.to_hex());
+        let txn = self.transaction();
+        self.fetch_output_in_txn(&txn, output_hash.as_slice())
+    }
+
+    fn fetch_unspent_output_hash_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        let txn = self.transaction();
+        sled_get(&txn, &self.utxo_commitment_index, commitment.as_bytes())
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        let txn = self.transaction();
+        Ok(
+            sled_fetch_matching_after::<TransactionOutputRowData>(&txn, &self.utxos_db, header_hash.as_slice())?
+                .into_iter()
+                .map(|row| match row.output {
+                    Some(output) => PrunedOutput::NotPruned { output },
+                    None => PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        witness_hash: row.witness_hash,
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        let txn = self.transaction();
+        Ok(
+            sled_fetch_matching_after::<TransactionInputRowData>(&txn, &self.inputs_db, header_hash.as_slice())?
+                .into_iter()
+                .map(|row| row.input)
+                .collect(),
+        )
+    }
+
+    fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
+        match tree {
+            MmrTree::Kernel => Ok(self.kernels_db.len() as u64),
+            MmrTree::Witness | MmrTree::Utxo => Ok(self.utxos_db.len() as u64),
+        }
+    }
+
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &HashOutput) -> Result<Option<u32>, ChainStorageError> {
+        let txn = self.transaction();
+        self.fetch_mmr_leaf_index_in_txn(&txn, tree, hash)
+    }
+
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.orphans_db.len())
+    }
+
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        let txn = self.transaction();
+        self.fetch_last_header_in_txn(&txn)?.ok_or_else(|| {
+            ChainStorageError::InvalidOperation("Cannot fetch last header because database is empty".to_string())
+        })
+    }
+
+    fn clear_all_pending_headers(&self) -> Result<usize, ChainStorageError> {
+        let txn = self.transaction();
+        let last_header = match self.fetch_last_header_in_txn(&txn)? {
+            Some(h) => h,
+            None => return Ok(0),
+        };
+        let metadata = fetch_metadata(&txn, &self.metadata_db)?;
+
+        if metadata.height_of_longest_chain() == last_header.height {
+            return Ok(0);
+        }
+
+        let start = metadata.height_of_longest_chain() + 1;
+        let end = last_header.height;
+
+        let mut num_deleted = 0;
+        for h in (start..=end).rev() {
+            self.delete_header(&txn, h)?;
+            num_deleted += 1;
+        }
+        txn.commit()?;
+        Ok(num_deleted)
+    }
+
+    fn fetch_last_chain_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        let header = self.fetch_last_header_in_txn(&txn)?.ok_or_else(|| {
+            ChainStorageError::InvalidOperation("Cannot fetch last header because database is empty".to_string())
+        })?;
+        self.fetch_chain_header_in_txn(&txn, header.height)
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        let txn = self.transaction();
+        let metadata = fetch_metadata(&txn, &self.metadata_db)?;
+        self.fetch_chain_header_in_txn(&txn, metadata.height_of_longest_chain())
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        let txn = self.transaction();
+        fetch_metadata(&txn, &self.metadata_db)
+    }
+
+    fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        let txn = self.transaction();
+        match fetch_metadata_value(&txn, &self.metadata_db, MetadataKey::KernelPrunedHeight)? {
+            Some(MetadataValue::KernelPrunedHeight(height)) => Ok(height),
+            _ => Ok(0),
+        }
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.utxo_commitment_index.len())
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.kernels_db.len())
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        trace!(target: LOG_TARGET, "Call to fetch_orphan_chain_tips()");
+        let txn = self.transaction();
+        if !sled_exists(&txn, &self.orphan_chain_tips_db, hash.as_slice())? {
+            return Ok(None);
+        }
+
+        let orphan = self
+            .fetch_orphan(&txn, hash)
+            .or_not_found("Orphan", "hash", hash.to_hex())?;
+        let accumulated_data = sled_get(&txn, &self.orphan_header_accumulated_data_db, hash.as_slice()).or_not_found(
+            "Orphan accumulated data",
+            "hash",
+            hash.to_hex(),
+        )?;
+        let height = orphan.header.height;
+        let chain_header = ChainHeader::try_construct(orphan.header, accumulated_data).ok_or_else(|| {
+            ChainStorageError::DataInconsistencyDetected {
+                function: "fetch_orphan_chain_tip_by_hash",
+                details: format!("Accumulated data mismatch at height #{}", height),
+            }
+        })?;
+        Ok(Some(chain_header))
+    }
+
+    fn fetch_orphan_children_of(&self, parent_hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        trace!(
+            target: LOG_TARGET,
+            "Call to fetch_orphan_children_of({})",
+            parent_hash.to_hex()
+        );
+        let txn = self.transaction();
+        let orphan_hashes: Vec<HashOutput> =
+            sled_fetch_matching_after(&txn, &self.orphan_parent_map_index, parent_hash.as_slice())?;
+        orphan_hashes
+            .into_iter()
+            .map(|hash| {
+                self.fetch_orphan(&txn, &hash)
+                    .or_not_found("Orphan", "hash", hash.to_hex())
+            })
+            .collect()
+    }
+
+    fn fetch_orphan_chain_block(&self, hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError> {
+        let txn = self.transaction();
+        let block = match self.fetch_orphan(&txn, &hash)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let accumulated_data = match sled_get::<BlockHeaderAccumulatedData>(
+            &txn,
+            &self.orphan_header_accumulated_data_db,
+            hash.as_slice(),
+        )? {
+            Some(accumulated_data) => accumulated_data,
+            None => return Ok(None),
+        };
+        let chain_block = ChainBlock::try_construct(Arc::new(block), accumulated_data).ok_or_else(|| {
+            ChainStorageError::DataInconsistencyDetected {
+                function: "fetch_orphan_chain_block",
+                details: format!("Accumulated data mismatch for hash {}", hash.to_hex()),
+            }
+        })?;
+        Ok(Some(chain_block))
+    }
+
+    fn fetch_deleted_bitmap(&self) -> Result<DeletedBitmap, ChainStorageError> {
+        let txn = self.transaction();
+        fetch_deleted_bitmap(&txn, &self.metadata_db)
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        horizon_height: u64,
+        orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError> {
+        let orphan_count = self.orphan_count()?;
+        let num_over_limit = orphan_count.saturating_sub(orphan_storage_capacity);
+        if num_over_limit == 0 {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Orphan block storage limit of {} reached, performing cleanup of {} entries.",
+            orphan_storage_capacity,
+            num_over_limit,
+        );
+
+        let mut orphans = sled_filter_map_values(&self.transaction(), &self.orphans_db, |block: Block| {
+            Some((block.header.height, block.hash()))
+        })?;
+
+        orphans.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut txn = DbTransaction::new();
+        for (removed_count, (height, block_hash)) in orphans.into_iter().enumerate() {
+            if height > horizon_height && removed_count >= num_over_limit {
+                break;
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Discarding orphan block #{} ({}).",
+                height,
+                block_hash.to_hex()
+            );
+            txn.delete_orphan(block_hash);
+        }
+        self.write(txn)
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
+        let txn = self.transaction();
+        Ok(sled_get(&txn, &self.monero_seed_height_db, seed)?.unwrap_or(0))
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        let txn = self.transaction();
+        match fetch_metadata_value(&txn, &self.metadata_db, MetadataKey::HorizonData)? {
+            Some(MetadataValue::HorizonData(data)) => Ok(Some(data)),
+            None => Err(ChainStorageError::ValueNotFound {
+                entity: "HorizonData",
+                field: "metadata",
+                value: "".to_string(),
+            }),
+            Some(k) => Err(ChainStorageError::DataInconsistencyDetected {
+                function: "fetch_horizon_data",
+                details: format!("Received incorrect value {:?} for key horizon data", k),
+            }),
+        }
+    }
+
+    fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
+        let txn = self.transaction();
+        let entry_counts = self
+            .all_trees()
+            .iter()
+            .map(|(name, tree)| sled_len(&txn, tree).map(|len| (*name, len)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(DbBasicStats::from_entry_counts(self.db.size_on_disk()?, entry_counts))
+    }
+
+    fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
+        self.all_trees()
+            .iter()
+            .map(|(name, tree)| {
+                fetch_tree_entry_sizes(tree).map(|(num_entries, total_key_size, total_value_size)| DbSize {
+                    name,
+                    num_entries,
+                    total_key_size,
+                    total_value_size,
+                })
+            })
+            .collect()
+    }
+
+    fn fetch_header_hash_by_deleted_mmr_positions(
+        &self,
+        mmr_positions: Vec<u32>,
+    ) -> Result<Vec<Option<(u64, HashOutput)>>, ChainStorageError> {
+        let txn = self.transaction();
+        mmr_positions
+            .iter()
+            .map(|pos| sled_get(&txn, &self.deleted_txo_mmr_position_to_height_index, &pos.to_be_bytes()))
+            .collect()
+    }
+
+    fn bad_block_exists(&self, block_hash: HashOutput) -> Result<bool, ChainStorageError> {
+        let txn = self.transaction();
+        sled_exists(&txn, &self.bad_blocks, block_hash.as_slice())
+    }
+
+    fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
+        let txn = self.transaction();
+        sled_filter_map_values(&txn, &self.reorgs, Some)
+    }
+
+    fn fetch_utxo_stats(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockUtxoStats>, ChainStorageError> {
+        let txn = self.transaction();
+        let mut stats = Vec::new();
+        for height in start_height..=end_height {
+            if let Some(entry) = sled_get(&txn, &self.utxo_stats, &height.to_be_bytes())? {
+                stats.push(entry);
+            }
+        }
+        Ok(stats)
+    }
+
+    fn fetch_last_utxo_stats(&self) -> Result<Option<BlockUtxoStats>, ChainStorageError> {
+        let txn = self.transaction();
+        sled_last(&txn, &self.utxo_stats)
+    }
+
+    fn fetch_script_index_block(&self, height: u64) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        let txn = self.transaction();
+        sled_get(&txn, &self.script_index_blocks, &height.to_be_bytes())
+    }
+
+    fn fetch_last_script_index_block(&self) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        let txn = self.transaction();
+        sled_last(&txn, &self.script_index_blocks)
+    }
+
+    fn fetch_outputs_by_script_index(
+        &self,
+        key: &ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError> {
+        let txn = self.transaction();
+        let prefix = key.to_key_prefix();
+        let mut start_key = prefix.clone();
+        start_key.extend_from_slice(&start_height.to_be_bytes());
+        sled_fetch_matching_from(
+            &txn,
+            &self.script_index,
+            &prefix,
+            &start_key,
+            script_index_page_filter(end_height, limit),
+        )
+    }
+}
+
+/// The orphan parent index stores one entry per child, keyed by the parent hash followed by the child hash, so that
+/// the children of a block can be found with a prefix scan.
+fn orphan_parent_key(parent_hash: &HashOutput, hash: &HashOutput) -> Vec<u8> {
+    let mut key = Vec::with_capacity(parent_hash.as_slice().len() + hash.as_slice().len());
+    key.extend_from_slice(parent_hash.as_slice());
+    key.extend_from_slice(hash.as_slice());
+    key
+}
+
+fn excess_sig_key(excess_sig: &Signature) -> Vec<u8> {
+    let mut key = Vec::with_capacity(32 * 2);
+    key.extend(excess_sig.get_public_nonce().as_bytes());
+    key.extend(excess_sig.get_signature().as_bytes());
+    key
+}
+
+fn fetch_metadata_value(
+    txn: &SledTransaction,
+    tree: &Tree,
+    key: MetadataKey,
+) -> Result<Option<MetadataValue>, ChainStorageError> {
+    sled_get(txn, tree, &key.as_u32().to_be_bytes())
+}
+
+// Fetches the chain metadata from the provided metadata tree.
+fn fetch_metadata(txn: &SledTransaction, tree: &Tree) -> Result<ChainMetadata, ChainStorageError> {
+    Ok(ChainMetadata::new(
+        fetch_chain_height(txn, tree)?,
+        fetch_best_block(txn, tree)?,
+        fetch_pruning_horizon(txn, tree)?,
+        fetch_pruned_height(txn, tree)?,
+        fetch_accumulated_work(txn, tree)?,
+        fetch_best_block_timestamp(txn, tree)?,
+    ))
+}
+
+// Fetches the chain height from the provided metadata tree.
+fn fetch_chain_height(txn: &SledTransaction, tree: &Tree) -> Result<u64, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::ChainHeight)? {
+        Some(MetadataValue::ChainHeight(height)) => Ok(height),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "ChainHeight",
+            value: "".to_string(),
+        }),
+    }
+}
+
+// Fetches the effective pruned height from the provided metadata tree.
+fn fetch_pruned_height(txn: &SledTransaction, tree: &Tree) -> Result<u64, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::PrunedHeight)? {
+        Some(MetadataValue::PrunedHeight(height)) => Ok(height),
+        _ => Ok(0),
+    }
+}
+
+// Fetches the best block hash from the provided metadata tree.
+fn fetch_best_block(txn: &SledTransaction, tree: &Tree) -> Result<BlockHash, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::BestBlock)? {
+        Some(MetadataValue::BestBlock(best_block)) => Ok(best_block),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "BestBlock",
+            value: "".to_string(),
+        }),
+    }
+}
+
+// Fetches the timestamp of the best block from the provided metadata tree.
+fn fetch_best_block_timestamp(txn: &SledTransaction, tree: &Tree) -> Result<u64, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::BestBlockTimestamp)? {
+        Some(MetadataValue::BestBlockTimestamp(timestamp)) => Ok(timestamp),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "BestBlockTimestamp",
+            value: "".to_string(),
+        }),
+    }
+}
+
+// Fetches the accumulated work from the provided metadata tree.
+fn fetch_accumulated_work(txn: &SledTransaction, tree: &Tree) -> Result<u128, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::AccumulatedWork)? {
+        Some(MetadataValue::AccumulatedWork(accumulated_difficulty)) => Ok(accumulated_difficulty),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "AccumulatedWork",
+            value: "".to_string(),
+        }),
+    }
+}
+
+// Fetches the deleted bitmap from the provided metadata tree.
+fn fetch_deleted_bitmap(txn: &SledTransaction, tree: &Tree) -> Result<DeletedBitmap, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::DeletedBitmap)? {
+        Some(MetadataValue::DeletedBitmap(bitmap)) => Ok(bitmap),
+        None => Ok(Bitmap::create().into()),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "DeletedBitmap",
+            value: "".to_string(),
+        }),
+    }
+}
+
+// Fetches the pruning horizon from the provided metadata tree.
+fn fetch_pruning_horizon(txn: &SledTransaction, tree: &Tree) -> Result<u64, ChainStorageError> {
+    match fetch_metadata_value(txn, tree, MetadataKey::PruningHorizon)? {
+        Some(MetadataValue::PruningHorizon(pruning_horizon)) => Ok(pruning_horizon),
+        _ => Ok(0),
+    }
+}
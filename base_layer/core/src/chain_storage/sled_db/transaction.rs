@@ -0,0 +1,271 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cell::RefCell, cmp::Ordering, collections::BTreeMap};
+
+use sled::{
+    transaction::{TransactionError, TransactionResult},
+    Batch,
+    Transactional,
+    Tree,
+};
+
+use crate::chain_storage::ChainStorageError;
+
+type Entry = (Vec<u8>, Vec<u8>);
+
+/// Reads and writes to the chain database trees as a single atomic unit.
+///
+/// Writes are buffered in memory and are visible to any later read made through the same transaction. Nothing is
+/// written to the trees until `commit` is called, at which point the buffered writes for every tree are applied in a
+/// single sled transaction. Dropping the transaction without committing discards its writes.
+///
+/// The backend is only ever written to while `BlockchainDatabase` holds its exclusive write lock, so there is never
+/// more than one open transaction with pending writes.
+#[derive(Default)]
+pub struct SledTransaction {
+    pending: RefCell<BTreeMap<Vec<u8>, PendingWrites>>,
+}
+
+struct PendingWrites {
+    tree: Tree,
+    /// Maps key -> new value, or None if the key is removed
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl SledTransaction {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, tree: &Tree, key: &[u8]) -> Result<Option<Vec<u8>>, ChainStorageError> {
+        if let Some(value) = self.pending_value(tree, key) {
+            return Ok(value);
+        }
+        Ok(tree.get(key)?.map(|v| v.to_vec()))
+    }
+
+    pub fn contains_key(&self, tree: &Tree, key: &[u8]) -> Result<bool, ChainStorageError> {
+        if let Some(value) = self.pending_value(tree, key) {
+            return Ok(value.is_some());
+        }
+        Ok(tree.contains_key(key)?)
+    }
+
+    pub fn insert(&self, tree: &Tree, key: &[u8], value: Vec<u8>) {
+        self.write(tree, key, Some(value));
+    }
+
+    pub fn remove(&self, tree: &Tree, key: &[u8]) {
+        self.write(tree, key, None);
+    }
+
+    /// Returns the number of entries in the tree, including the writes pending in this transaction.
+    pub fn len(&self, tree: &Tree) -> Result<usize, ChainStorageError> {
+        let pending = self.pending.borrow();
+        let mut len = tree.len();
+        if let Some(p) = pending.get(tree.name().as_ref()) {
+            for (key, value) in &p.writes {
+                match (tree.contains_key(key)?, value.is_some()) {
+                    (false, true) => len += 1,
+                    (true, false) => len -= 1,
+                    _ => {},
+                }
+            }
+        }
+        Ok(len)
+    }
+
+    /// Returns every entry whose key starts with `prefix`, in key order.
+    pub fn scan_prefix(&self, tree: &Tree, prefix: &[u8]) -> Result<Vec<Entry>, ChainStorageError> {
+        self.scan_from(tree, prefix, |key| key.starts_with(prefix), None)
+    }
+
+    /// Returns every entry from `start` onwards whose key starts with `prefix`, in key order.
+    pub fn scan_prefix_from(&self, tree: &Tree, prefix: &[u8], start: &[u8]) -> Result<Vec<Entry>, ChainStorageError> {
+        self.scan_from(tree, start, |key| key.starts_with(prefix), None)
+    }
+
+    /// Returns the entry with the smallest key that is greater than or equal to `key`.
+    pub fn first_from(&self, tree: &Tree, key: &[u8]) -> Result<Option<Entry>, ChainStorageError> {
+        Ok(self.scan_from(tree, key, |_| true, Some(1))?.pop())
+    }
+
+    /// Returns every entry in the tree, in key order.
+    pub fn entries(&self, tree: &Tree) -> Result<Vec<Entry>, ChainStorageError> {
+        self.scan_from(tree, &[], |_| true, None)
+    }
+
+    /// Returns the entry with the largest key in the tree.
+    pub fn last(&self, tree: &Tree) -> Result<Option<Entry>, ChainStorageError> {
+        let pending = self.pending.borrow();
+        let writes = pending.get(tree.name().as_ref()).map(|p| &p.writes);
+
+        let mut committed_last = None;
+        for item in tree.iter().rev() {
+            let (key, value) = item?;
+            match writes.and_then(|w| w.get(key.as_ref())) {
+                // Removed in this transaction
+                Some(None) => continue,
+                Some(Some(value)) => {
+                    committed_last = Some((key.to_vec(), value.clone()));
+                    break;
+                },
+                None => {
+                    committed_last = Some((key.to_vec(), value.to_vec()));
+                    break;
+                },
+            }
+        }
+        let pending_last = writes.and_then(|w| {
+            w.iter()
+                .rev()
+                .find_map(|(key, value)| value.as_ref().map(|v| (key.clone(), v.clone())))
+        });
+
+        match (committed_last, pending_last) {
+            (Some(committed), Some(pending)) => Ok(Some(if pending.0 >= committed.0 { pending } else { committed })),
+            (committed, pending) => Ok(committed.or(pending)),
+        }
+    }
+
+    /// Atomically applies every pending write to the trees.
+    pub fn commit(self) -> Result<(), ChainStorageError> {
+        let pending = self.pending.into_inner();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let (trees, batches): (Vec<Tree>, Vec<Batch>) = pending
+            .into_values()
+            .map(|p| {
+                let mut batch = Batch::default();
+                for (key, value) in p.writes {
+                    match value {
+                        Some(value) => batch.insert(key, value),
+                        None => batch.remove(key),
+                    }
+                }
+                (p.tree, batch)
+            })
+            .unzip();
+
+        let result: TransactionResult<(), ()> = trees.as_slice().transaction(|views| {
+            for (view, batch) in views.iter().zip(&batches) {
+                view.apply_batch(batch)?;
+            }
+            Ok(())
+        });
+
+        result.map_err(|err| match err {
+            TransactionError::Abort(()) => ChainStorageError::AccessError("Sled transaction was aborted".to_string()),
+            TransactionError::Storage(err) => err.into(),
+        })
+    }
+
+    fn pending_value(&self, tree: &Tree, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        self.pending
+            .borrow()
+            .get(tree.name().as_ref())
+            .and_then(|p| p.writes.get(key))
+            .cloned()
+    }
+
+    fn write(&self, tree: &Tree, key: &[u8], value: Option<Vec<u8>>) {
+        self.pending
+            .borrow_mut()
+            .entry(tree.name().to_vec())
+            .or_insert_with(|| PendingWrites {
+                tree: tree.clone(),
+                writes: BTreeMap::new(),
+            })
+            .writes
+            .insert(key.to_vec(), value);
+    }
+
+    /// Merges the committed entries with the pending writes, starting at `start` and continuing in key order for as
+    /// long as `take_while` returns true or until `limit` entries have been returned.
+    fn scan_from<F>(
+        &self,
+        tree: &Tree,
+        start: &[u8],
+        take_while: F,
+        limit: Option<usize>,
+    ) -> Result<Vec<Entry>, ChainStorageError>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        let pending = self.pending.borrow();
+        let mut committed = tree.range(start..);
+        let mut buffered = pending
+            .get(tree.name().as_ref())
+            .into_iter()
+            .flat_map(|p| p.writes.range(start.to_vec()..));
+
+        let mut next_committed = committed.next().transpose()?;
+        let mut next_buffered = buffered.next();
+        let mut entries = Vec::new();
+        loop {
+            let (key, value) = match (next_committed.take(), next_buffered.take()) {
+                (None, None) => break,
+                (Some((key, value)), None) => {
+                    next_committed = committed.next().transpose()?;
+                    (key.to_vec(), Some(value.to_vec()))
+                },
+                (None, Some((key, value))) => {
+                    next_buffered = buffered.next();
+                    (key.clone(), value.clone())
+                },
+                (Some((c_key, c_value)), Some((b_key, b_value))) => match c_key.as_ref().cmp(b_key.as_slice()) {
+                    Ordering::Less => {
+                        next_committed = committed.next().transpose()?;
+                        next_buffered = Some((b_key, b_value));
+                        (c_key.to_vec(), Some(c_value.to_vec()))
+                    },
+                    Ordering::Greater => {
+                        next_committed = Some((c_key, c_value));
+                        next_buffered = buffered.next();
+                        (b_key.clone(), b_value.clone())
+                    },
+                    // The pending write replaces the committed value
+                    Ordering::Equal => {
+                        next_committed = committed.next().transpose()?;
+                        next_buffered = buffered.next();
+                        (b_key.clone(), b_value.clone())
+                    },
+                },
+            };
+
+            if !take_while(&key) {
+                break;
+            }
+            if let Some(value) = value {
+                entries.push((key, value));
+                if limit.map(|l| entries.len() >= l).unwrap_or(false) {
+                    break;
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+}
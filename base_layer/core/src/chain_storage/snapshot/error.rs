@@ -0,0 +1,101 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::io;
+
+use tari_common::configuration::Network;
+use tari_common_types::types::HashOutput;
+use tari_crypto::errors::RangeProofError;
+use tari_mmr::error::MerkleMountainRangeError;
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::{
+    chain_storage::{ChainStorageError, MmrTree},
+    transactions::transaction_components::TransactionError,
+    validation::ValidationError,
+};
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Chain storage error: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("Snapshot could not be encoded or decoded: {0}")]
+    EncodingError(#[from] bincode::Error),
+    #[error("The file is not a chain snapshot")]
+    InvalidMagic,
+    #[error("Snapshot format version {0} is not supported")]
+    UnsupportedVersion(u16),
+    #[error("Snapshot checksum mismatch. Expected {expected_hex} but the contents hash to {actual_hex}")]
+    ChecksumMismatch { expected_hex: String, actual_hex: String },
+    #[error("Snapshot is for network {snapshot} but this node is running on {local}")]
+    NetworkMismatch { snapshot: Network, local: Network },
+    #[error("Snapshot genesis block {snapshot_hex} does not match the local genesis block {local_hex}")]
+    GenesisMismatch { snapshot_hex: String, local_hex: String },
+    #[error("Snapshots can only be imported into an empty database but the local chain is at height {0}")]
+    DatabaseNotEmpty(u64),
+    #[error("Cannot export height {height}: {reason}")]
+    HeightNotAvailable { height: u64, reason: String },
+    #[error("The chain was reorged at or below height {0} during the export")]
+    ChainChanged(u64),
+    #[error("Invalid block at height {height}: {details}")]
+    InvalidBlock { height: u64, details: String },
+    #[error("MMR did not match for {mmr_tree} at height {at_height}. Expected {actual_hex} to equal {expected_hex}")]
+    InvalidMmrRoot {
+        mmr_tree: MmrTree,
+        at_height: u64,
+        expected_hex: String,
+        actual_hex: String,
+    },
+    #[error("Snapshot best block {snapshot_hex} does not match the imported chain tip {actual_hex}")]
+    BestBlockMismatch { snapshot_hex: String, actual_hex: String },
+    #[error("Invalid kernel signature: {0}")]
+    InvalidKernelSignature(TransactionError),
+    #[error("An invalid transaction has been encountered: {0}")]
+    TransactionError(#[from] TransactionError),
+    #[error("A range proof verification has produced an error: {0}")]
+    RangeProofError(#[from] RangeProofError),
+    #[error("MerkleMountainRangeError: {0}")]
+    MerkleMountainRangeError(#[from] MerkleMountainRangeError),
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+    #[error("Final state validation failed: {0}")]
+    FinalStateValidationFailed(ValidationError),
+}
+
+impl SnapshotError {
+    pub(super) fn invalid_block<T: ToString>(height: u64, details: T) -> Self {
+        SnapshotError::InvalidBlock {
+            height,
+            details: details.to_string(),
+        }
+    }
+
+    pub(super) fn best_block_mismatch(snapshot: &HashOutput, actual: &HashOutput) -> Self {
+        SnapshotError::BestBlockMismatch {
+            snapshot_hex: snapshot.to_hex(),
+            actual_hex: actual.to_hex(),
+        }
+    }
+}
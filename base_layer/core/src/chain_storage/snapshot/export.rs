@@ -0,0 +1,133 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{io::Write, sync::Arc};
+
+use croaring::Bitmap;
+use log::*;
+
+use crate::chain_storage::{
+    snapshot::{
+        format::{write_preamble, write_record, ChecksumWriter, SnapshotBlock, SnapshotHeader, SnapshotSummary},
+        SnapshotError,
+    },
+    BlockchainBackend,
+    BlockchainDatabase,
+};
+
+const LOG_TARGET: &str = "c::cs::snapshot::export";
+
+/// Writes the chain state from genesis up to and including `height` to `writer` as a snapshot. Outputs that are spent
+/// at `height` are written as pruned outputs, so the snapshot contains the full UTXO set at that height.
+///
+/// The height must be between the pruned height and the tip of the local chain. The export fails if the chain is
+/// reorged below `height` while it is running.
+pub fn export_snapshot<B: BlockchainBackend, W: Write>(
+    db: &BlockchainDatabase<B>,
+    height: u64,
+    writer: W,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let metadata = db.get_chain_metadata()?;
+    if height > metadata.height_of_longest_chain() {
+        return Err(SnapshotError::HeightNotAvailable {
+            height,
+            reason: format!("the chain tip is at height {}", metadata.height_of_longest_chain()),
+        });
+    }
+    if height < metadata.pruned_height() {
+        return Err(SnapshotError::HeightNotAvailable {
+            height,
+            reason: format!("the chain is pruned up to height {}", metadata.pruned_height()),
+        });
+    }
+    let kernel_pruned_height = db.fetch_kernel_pruned_height()?;
+    if kernel_pruned_height > 0 {
+        return Err(SnapshotError::HeightNotAvailable {
+            height,
+            reason: format!("kernels are pruned up to height {}", kernel_pruned_height),
+        });
+    }
+
+    let best_block = *db.fetch_chain_header(height)?.hash();
+    let deleted_at_height = Arc::new(fetch_deleted_bitmap_at_height(db, height)?);
+
+    let mut writer = ChecksumWriter::new(writer);
+    write_preamble(&mut writer)?;
+    write_record(&mut writer, &SnapshotHeader {
+        network: db.rules().network().as_network(),
+        height,
+        best_block,
+    })?;
+
+    let mut summary = SnapshotSummary::new(height, best_block);
+    for h in 0..=height {
+        let chain_header = db.fetch_chain_header(h)?;
+        let hash = *chain_header.hash();
+        let kernels = db.fetch_kernels_in_block(hash)?;
+        let (outputs, deleted_diff) = db.fetch_utxos_in_block(hash, Some(deleted_at_height.clone()))?;
+        let (header, accumulated_data) = chain_header.into_parts();
+        let block = SnapshotBlock {
+            header,
+            accumulated_data,
+            kernels,
+            outputs,
+            deleted_diff: deleted_diff.into(),
+        };
+        write_record(&mut writer, &block)?;
+        summary.add_block(&block);
+
+        if h % 1000 == 0 {
+            debug!(target: LOG_TARGET, "Exported snapshot up to height {}/{}", h, height);
+        }
+    }
+
+    // Make sure that we have not exported part of a chain that has since been reorged out
+    if *db.fetch_chain_header(height)?.hash() != best_block {
+        return Err(SnapshotError::ChainChanged(height));
+    }
+
+    writer.finish()?;
+    info!(
+        target: LOG_TARGET,
+        "Exported snapshot at height {} ({} kernels, {} unspent outputs, {} pruned outputs)",
+        height,
+        summary.num_kernels,
+        summary.num_unspent_outputs,
+        summary.num_pruned_outputs
+    );
+    Ok(summary)
+}
+
+/// The deleted bitmap at the tip contains spends from blocks above `height`, so the bitmap at `height` is rebuilt
+/// from the per-block differences.
+fn fetch_deleted_bitmap_at_height<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    height: u64,
+) -> Result<Bitmap, SnapshotError> {
+    let mut deleted = Bitmap::create();
+    for h in 0..=height {
+        let data = db.fetch_block_accumulated_data_by_height(h)?;
+        deleted.or_inplace(data.deleted());
+    }
+    deleted.run_optimize();
+    Ok(deleted)
+}
@@ -0,0 +1,183 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::io::{self, Read, Write};
+
+use bincode::Options;
+use digest::Digest;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_common_types::types::HashOutput;
+use tari_crypto::hash::blake2::Blake256;
+
+use crate::{
+    blocks::{BlockHeader, BlockHeaderAccumulatedData, DeletedBitmap},
+    chain_storage::{snapshot::SnapshotError, PrunedOutput},
+    transactions::transaction_components::TransactionKernel,
+};
+
+/// Every snapshot file starts with these bytes
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"TARISNAP";
+/// The current snapshot format version. This must be incremented whenever the layout of the snapshot changes.
+pub const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+/// The size of the Blake256 checksum that ends every snapshot file
+pub const SNAPSHOT_CHECKSUM_SIZE: usize = 32;
+/// The maximum encoded size of a single snapshot record. This prevents a malformed snapshot from causing arbitrarily
+/// large allocations.
+const MAX_RECORD_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Describes the chain contained in a snapshot. It follows the magic bytes and format version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    pub network: Network,
+    pub height: u64,
+    pub best_block: HashOutput,
+}
+
+/// The chain state of a single block. A snapshot contains one of these for every height from genesis up to and
+/// including the snapshot height. Outputs that are spent at the snapshot height are pruned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SnapshotBlock {
+    pub header: BlockHeader,
+    pub accumulated_data: BlockHeaderAccumulatedData,
+    pub kernels: Vec<TransactionKernel>,
+    pub outputs: Vec<PrunedOutput>,
+    pub deleted_diff: DeletedBitmap,
+}
+
+/// A summary of the contents of a snapshot that was exported or imported
+#[derive(Debug, Clone)]
+pub struct SnapshotSummary {
+    pub height: u64,
+    pub best_block: HashOutput,
+    pub num_kernels: u64,
+    pub num_unspent_outputs: u64,
+    pub num_pruned_outputs: u64,
+}
+
+impl SnapshotSummary {
+    pub(super) fn new(height: u64, best_block: HashOutput) -> Self {
+        Self {
+            height,
+            best_block,
+            num_kernels: 0,
+            num_unspent_outputs: 0,
+            num_pruned_outputs: 0,
+        }
+    }
+
+    pub(super) fn add_block(&mut self, block: &SnapshotBlock) {
+        self.num_kernels += block.kernels.len() as u64;
+        for output in &block.outputs {
+            if output.is_pruned() {
+                self.num_pruned_outputs += 1;
+            } else {
+                self.num_unspent_outputs += 1;
+            }
+        }
+    }
+}
+
+fn record_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_RECORD_SIZE)
+}
+
+pub(super) fn write_record<W: Write, T: Serialize>(writer: &mut W, record: &T) -> Result<(), SnapshotError> {
+    record_options().serialize_into(writer, record)?;
+    Ok(())
+}
+
+pub(super) fn read_record<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<T, SnapshotError> {
+    Ok(record_options().deserialize_from(reader)?)
+}
+
+/// Writes the magic bytes and format version
+pub(super) fn write_preamble<W: Write>(writer: &mut W) -> Result<(), SnapshotError> {
+    writer.write_all(&SNAPSHOT_MAGIC)?;
+    writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_le_bytes())?;
+    Ok(())
+}
+
+/// Reads and checks the magic bytes and format version
+pub(super) fn read_preamble<R: Read>(reader: &mut R) -> Result<(), SnapshotError> {
+    let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != SNAPSHOT_FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+/// A writer that hashes everything written through it so that the checksum can be appended once the snapshot is
+/// complete.
+pub(super) struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Blake256,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Blake256::new(),
+        }
+    }
+
+    /// Appends the checksum of everything written so far and flushes the underlying writer
+    pub fn finish(mut self) -> Result<W, SnapshotError> {
+        let checksum = self.hasher.finalize();
+        self.inner.write_all(checksum.as_slice())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Hashes `len` bytes from the reader and returns the checksum
+pub(super) fn calculate_checksum<R: Read>(reader: R, len: u64) -> Result<Vec<u8>, SnapshotError> {
+    let mut writer = ChecksumWriter::new(io::sink());
+    let copied = io::copy(&mut reader.take(len), &mut writer)?;
+    if copied != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(writer.hasher.finalize().to_vec())
+}
@@ -0,0 +1,471 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+};
+
+use croaring::Bitmap;
+use log::*;
+use tari_common_types::types::{Commitment, FixedHash};
+use tari_crypto::commitment::HomomorphicCommitment;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    blocks::{ChainHeader, UpdateBlockAccumulatedData},
+    chain_storage::{
+        snapshot::{
+            format::{calculate_checksum, read_preamble, read_record, SnapshotBlock, SnapshotHeader, SnapshotSummary},
+            SnapshotError,
+            SNAPSHOT_CHECKSUM_SIZE,
+        },
+        BlockchainBackend,
+        BlockchainDatabase,
+        DbTransaction,
+        MmrTree,
+        PrunedOutput,
+    },
+    transactions::{
+        transaction_components::{transaction_output::batch_verify_range_proofs, TransactionKernel},
+        CryptoFactories,
+    },
+    validation::{helpers, FinalHorizonStateValidation},
+    MutablePrunedOutputMmr,
+    PrunedKernelMmr,
+    PrunedOutputMmr,
+    PrunedWitnessMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::snapshot::import";
+
+/// Imports a chain snapshot into an empty database, leaving it in the same state as a pruned node that has completed
+/// horizon sync at the snapshot height.
+///
+/// Every block is checked against its header's MMR roots before it is written, in the same way as blocks received
+/// during horizon sync. Once all blocks are written, the resulting UTXO set is checked by `validator` before the chain
+/// metadata is updated, so a failed import never moves the local tip. If the import fails after blocks have been
+/// written, they are removed again so that the database is left empty and the import can be retried. Headers are
+/// linked by hash but proof of work is not re-validated, so snapshots should only be imported from a trusted source.
+pub fn import_snapshot<B: BlockchainBackend, R: Read + Seek>(
+    db: &BlockchainDatabase<B>,
+    mut reader: R,
+    validator: &dyn FinalHorizonStateValidation<B>,
+    factories: &CryptoFactories,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let content_len = verify_checksum(&mut reader)?;
+    let mut reader = BufReader::new(reader.take(content_len));
+
+    read_preamble(&mut reader)?;
+    let snapshot_header: SnapshotHeader = read_record(&mut reader)?;
+    let local_network = db.rules().network().as_network();
+    if snapshot_header.network != local_network {
+        return Err(SnapshotError::NetworkMismatch {
+            snapshot: snapshot_header.network,
+            local: local_network,
+        });
+    }
+
+    let metadata = db.get_chain_metadata()?;
+    let last_header = db.fetch_last_header()?;
+    if metadata.height_of_longest_chain() > 0 || last_header.height > 0 {
+        return Err(SnapshotError::DatabaseNotEmpty(
+            metadata.height_of_longest_chain().max(last_header.height),
+        ));
+    }
+
+    match import_blocks(db, &mut reader, &snapshot_header, validator, factories) {
+        Ok(summary) => Ok(summary),
+        Err(err) => {
+            warn!(
+                target: LOG_TARGET,
+                "Snapshot import failed because '{}'. Removing imported blocks.", err
+            );
+            remove_imported_blocks(db)?;
+            Err(err)
+        },
+    }
+}
+
+/// Imports the blocks of a snapshot, after the snapshot header has been read and the database checked to be empty
+fn import_blocks<B: BlockchainBackend, R: BufRead>(
+    db: &BlockchainDatabase<B>,
+    reader: &mut R,
+    snapshot_header: &SnapshotHeader,
+    validator: &dyn FinalHorizonStateValidation<B>,
+    factories: &CryptoFactories,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let genesis: SnapshotBlock = read_record(reader)?;
+    let mut prev_header = db.fetch_chain_header(0)?;
+    if genesis.header.hash() != *prev_header.hash() {
+        return Err(SnapshotError::GenesisMismatch {
+            snapshot_hex: genesis.header.hash().to_hex(),
+            local_hex: prev_header.hash().to_hex(),
+        });
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Importing snapshot for {} up to height {}", snapshot_header.network, snapshot_header.height
+    );
+    let mut state = ImportState {
+        full_bitmap: db.fetch_deleted_bitmap_at_tip()?.into_bitmap(),
+        summary: SnapshotSummary::new(snapshot_header.height, snapshot_header.best_block),
+        unspent: Vec::new(),
+        kernel_sum: HomomorphicCommitment::default(),
+        burned_sum: HomomorphicCommitment::default(),
+    };
+    state.summary.add_block(&genesis);
+    state.add_genesis_outputs(&genesis)?;
+
+    for height in 1..=snapshot_header.height {
+        let block: SnapshotBlock = read_record(reader)?;
+        prev_header = import_block(db, &prev_header, block, &mut state, factories)?;
+        if height % 1000 == 0 {
+            debug!(
+                target: LOG_TARGET,
+                "Imported snapshot up to height {}/{}", height, snapshot_header.height
+            );
+        }
+    }
+
+    if !reader.fill_buf()?.is_empty() {
+        return Err(SnapshotError::invalid_block(
+            snapshot_header.height,
+            "the snapshot contains data after the last block",
+        ));
+    }
+    if *prev_header.hash() != snapshot_header.best_block {
+        return Err(SnapshotError::best_block_mismatch(
+            &snapshot_header.best_block,
+            prev_header.hash(),
+        ));
+    }
+
+    let (utxo_sum, prune_positions) = state.calculate_utxo_sum();
+    validator
+        .validate(
+            &*db.db_read_access()?,
+            prev_header.height(),
+            &utxo_sum,
+            &state.kernel_sum,
+            &state.burned_sum,
+        )
+        .map_err(SnapshotError::FinalStateValidationFailed)?;
+
+    // Spent outputs are pruned in the same transaction that sets the tip so that a failed import only has to remove
+    // the imported blocks
+    let mut txn = DbTransaction::new();
+    if !prune_positions.is_empty() {
+        debug!(target: LOG_TARGET, "Pruning {} spent outputs", prune_positions.len());
+        txn.prune_outputs_at_positions(prune_positions);
+    }
+    txn.set_best_block(
+        prev_header.height(),
+        *prev_header.hash(),
+        prev_header.accumulated_data().total_accumulated_difficulty,
+        *db.get_chain_metadata()?.best_block(),
+        prev_header.timestamp(),
+    )
+    .set_pruned_height(prev_header.height())
+    .set_horizon_data(state.kernel_sum, utxo_sum);
+    db.write(txn)?;
+
+    info!(
+        target: LOG_TARGET,
+        "Imported snapshot at height {} ({} kernels, {} unspent outputs, {} pruned outputs)",
+        state.summary.height,
+        state.summary.num_kernels,
+        state.summary.num_unspent_outputs,
+        state.summary.num_pruned_outputs
+    );
+    Ok(state.summary)
+}
+
+/// Removes every block above the genesis block, along with its header. The database is checked to be empty before an
+/// import starts, so all of these blocks were written by the failed import.
+fn remove_imported_blocks<B: BlockchainBackend>(db: &BlockchainDatabase<B>) -> Result<(), SnapshotError> {
+    let last_header = db.fetch_last_header()?;
+    let mut txn = DbTransaction::new();
+    for height in (1..=last_header.height).rev() {
+        let hash = *db.fetch_chain_header(height)?.hash();
+        txn.delete_block(hash).delete_header(height);
+    }
+    db.write(txn)?;
+    debug!(
+        target: LOG_TARGET,
+        "Removed {} block(s) written by a failed snapshot import", last_header.height
+    );
+    Ok(())
+}
+
+/// Running totals kept while blocks are imported
+struct ImportState {
+    full_bitmap: Bitmap,
+    summary: SnapshotSummary,
+    /// The MMR position and commitment of every unpruned output in the snapshot
+    unspent: Vec<(u32, Commitment)>,
+    kernel_sum: Commitment,
+    burned_sum: Commitment,
+}
+
+impl ImportState {
+    /// The genesis block is already in the database, but its outputs and kernels still count towards the totals.
+    fn add_genesis_outputs(&mut self, genesis: &SnapshotBlock) -> Result<(), SnapshotError> {
+        for (i, output) in genesis.outputs.iter().enumerate() {
+            if let PrunedOutput::NotPruned { output } = output {
+                self.unspent
+                    .push((to_mmr_position(0, i as u64)?, output.commitment.clone()));
+            }
+        }
+        for kernel in &genesis.kernels {
+            self.add_kernel_to_sums(kernel)?;
+        }
+        Ok(())
+    }
+
+    fn add_kernel_to_sums(&mut self, kernel: &TransactionKernel) -> Result<(), SnapshotError> {
+        self.kernel_sum = &kernel.excess + &self.kernel_sum;
+        if kernel.is_burned() {
+            self.burned_sum = kernel.get_burn_commitment()? + &self.burned_sum;
+        }
+        Ok(())
+    }
+
+    /// Returns the sum of the unspent output commitments and the positions of unpruned outputs that are spent at the
+    /// snapshot height.
+    fn calculate_utxo_sum(&self) -> (Commitment, Vec<u32>) {
+        let mut utxo_sum = HomomorphicCommitment::default();
+        let mut prune_positions = vec![];
+        for (pos, commitment) in &self.unspent {
+            if self.full_bitmap.contains(*pos) {
+                prune_positions.push(*pos);
+            } else {
+                utxo_sum = commitment + &utxo_sum;
+            }
+        }
+        (utxo_sum, prune_positions)
+    }
+}
+
+/// Reads the whole snapshot once to check the trailing checksum and returns the length of the checksummed contents.
+fn verify_checksum<R: Read + Seek>(reader: &mut R) -> Result<u64, SnapshotError> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let content_len = len
+        .checked_sub(SNAPSHOT_CHECKSUM_SIZE as u64)
+        .ok_or(SnapshotError::InvalidMagic)?;
+
+    reader.seek(SeekFrom::Start(0))?;
+    let actual = calculate_checksum(&mut *reader, content_len)?;
+    let mut expected = [0u8; SNAPSHOT_CHECKSUM_SIZE];
+    reader.read_exact(&mut expected)?;
+    if actual.as_slice() != expected {
+        return Err(SnapshotError::ChecksumMismatch {
+            expected_hex: expected.to_vec().to_hex(),
+            actual_hex: actual.to_hex(),
+        });
+    }
+
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(content_len)
+}
+
+/// Validates a single block against the previous header and its own MMR roots and writes it to the database
+#[allow(clippy::too_many_lines)]
+fn import_block<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    prev: &ChainHeader,
+    block: SnapshotBlock,
+    state: &mut ImportState,
+    factories: &CryptoFactories,
+) -> Result<ChainHeader, SnapshotError> {
+    let height = prev.height() + 1;
+    state.summary.add_block(&block);
+    let SnapshotBlock {
+        header,
+        accumulated_data,
+        kernels,
+        outputs,
+        deleted_diff,
+    } = block;
+
+    if header.height != height {
+        return Err(SnapshotError::invalid_block(
+            height,
+            format!("expected the block at height {} but got {}", height, header.height),
+        ));
+    }
+    if header.prev_hash != *prev.hash() {
+        return Err(SnapshotError::invalid_block(
+            height,
+            "the block does not build on the previous block",
+        ));
+    }
+    if accumulated_data.total_kernel_offset !=
+        &prev.accumulated_data().total_kernel_offset + &header.total_kernel_offset
+    {
+        return Err(SnapshotError::invalid_block(height, "invalid total kernel offset"));
+    }
+    if accumulated_data.total_accumulated_difficulty <= prev.accumulated_data().total_accumulated_difficulty {
+        return Err(SnapshotError::invalid_block(
+            height,
+            "the accumulated difficulty does not increase",
+        ));
+    }
+    let num_kernels = header.kernel_mmr_size.checked_sub(prev.header().kernel_mmr_size);
+    if num_kernels != Some(kernels.len() as u64) {
+        return Err(SnapshotError::invalid_block(
+            height,
+            format!("the header kernel MMR size does not match {} kernel(s)", kernels.len()),
+        ));
+    }
+    let num_outputs = header.output_mmr_size.checked_sub(prev.header().output_mmr_size);
+    if num_outputs != Some(outputs.len() as u64) {
+        return Err(SnapshotError::invalid_block(
+            height,
+            format!("the header output MMR size does not match {} output(s)", outputs.len()),
+        ));
+    }
+    if deleted_diff
+        .bitmap()
+        .maximum()
+        .map(|max| u64::from(max) >= header.output_mmr_size)
+        .unwrap_or(false)
+    {
+        return Err(SnapshotError::invalid_block(
+            height,
+            "the deleted bitmap contains an output that does not exist",
+        ));
+    }
+
+    let hash = header.hash();
+    let timestamp = header.timestamp.as_u64();
+    let chain_header = ChainHeader::try_construct(header, accumulated_data)
+        .ok_or_else(|| SnapshotError::invalid_block(height, "the accumulated data does not match the header hash"))?;
+    let header = chain_header.header();
+
+    let (kernel_pruned_set, output_pruned_set, witness_pruned_set, _) =
+        db.fetch_block_accumulated_data(*prev.hash())?.dissolve();
+    let mut kernel_mmr = PrunedKernelMmr::new(kernel_pruned_set);
+    let mut output_mmr = PrunedOutputMmr::new(output_pruned_set);
+    let mut witness_mmr = PrunedWitnessMmr::new(witness_pruned_set);
+
+    let mut txn = DbTransaction::new();
+    txn.insert_chain_header(chain_header.clone());
+
+    let constants = db.rules().consensus_constants(height);
+    let mut block_kernel_sum = HomomorphicCommitment::default();
+    let mut kernel_pos = prev.header().kernel_mmr_size;
+    for kernel in kernels {
+        kernel
+            .verify_signature()
+            .map_err(SnapshotError::InvalidKernelSignature)?;
+        kernel_mmr.push(kernel.hash().to_vec())?;
+        block_kernel_sum = &kernel.excess + &block_kernel_sum;
+        state.add_kernel_to_sums(&kernel)?;
+        txn.insert_kernel(kernel, hash, to_mmr_position(height, kernel_pos)?);
+        kernel_pos += 1;
+    }
+
+    let mut unpruned_outputs = Vec::new();
+    let mut output_pos = prev.header().output_mmr_size;
+    for output in outputs {
+        let mmr_position = to_mmr_position(height, output_pos)?;
+        match output {
+            PrunedOutput::NotPruned { output } => {
+                helpers::check_tari_script_byte_size(&output.script, constants.get_max_script_byte_size())?;
+                helpers::check_sidechain_features(&output, constants.get_max_sidechain_committee_size())?;
+                output_mmr.push(output.hash().to_vec())?;
+                witness_mmr.push(output.witness_hash().to_vec())?;
+                state.unspent.push((mmr_position, output.commitment.clone()));
+                unpruned_outputs.push(output.clone());
+                txn.insert_utxo(output, hash, height, mmr_position, timestamp);
+            },
+            PrunedOutput::Pruned {
+                output_hash,
+                witness_hash,
+            } => {
+                output_mmr.push(output_hash.to_vec())?;
+                witness_mmr.push(witness_hash.to_vec())?;
+                txn.insert_pruned_utxo(output_hash, witness_hash, hash, height, mmr_position, timestamp);
+            },
+        }
+        output_pos += 1;
+    }
+    batch_verify_range_proofs(&factories.range_proof, &unpruned_outputs.iter().collect::<Vec<_>>())?;
+
+    let diff = deleted_diff.into_bitmap();
+    state.full_bitmap.or_inplace(&diff);
+    state.full_bitmap.run_optimize();
+
+    check_mmr_root(
+        MmrTree::Kernel,
+        height,
+        &header.kernel_mr,
+        kernel_mmr.get_merkle_root()?,
+    )?;
+    let output_hash_set = output_mmr.get_pruned_hash_set()?;
+    let mutable_output_mmr = MutablePrunedOutputMmr::new(output_hash_set.clone(), state.full_bitmap.clone())?;
+    check_mmr_root(
+        MmrTree::Utxo,
+        height,
+        &header.output_mr,
+        mutable_output_mmr.get_merkle_root()?,
+    )?;
+    check_mmr_root(
+        MmrTree::Witness,
+        height,
+        &header.witness_mr,
+        witness_mmr.get_merkle_root()?,
+    )?;
+
+    txn.update_deleted_bitmap(diff.clone());
+    txn.update_block_accumulated_data(hash, UpdateBlockAccumulatedData {
+        kernel_hash_set: Some(kernel_mmr.get_pruned_hash_set()?),
+        utxo_hash_set: Some(output_hash_set),
+        witness_hash_set: Some(witness_mmr.get_pruned_hash_set()?),
+        deleted_diff: Some(diff.into()),
+        kernel_sum: Some(block_kernel_sum),
+    });
+    db.write(txn)?;
+
+    Ok(chain_header)
+}
+
+fn check_mmr_root(mmr_tree: MmrTree, height: u64, expected: &FixedHash, actual: Vec<u8>) -> Result<(), SnapshotError> {
+    if actual.as_slice() == expected.as_slice() {
+        Ok(())
+    } else {
+        Err(SnapshotError::InvalidMmrRoot {
+            mmr_tree,
+            at_height: height,
+            expected_hex: expected.to_hex(),
+            actual_hex: actual.to_hex(),
+        })
+    }
+}
+
+/// The deleted bitmap is a 32-bit croaring bitmap, so MMR positions must fit in a u32
+fn to_mmr_position(height: u64, position: u64) -> Result<u32, SnapshotError> {
+    u32::try_from(position)
+        .map_err(|_| SnapshotError::invalid_block(height, format!("MMR position {} is too large", position)))
+}
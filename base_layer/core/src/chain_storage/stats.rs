@@ -47,6 +47,34 @@ impl DbBasicStats {
         }
     }
 
+    /// Constructs stats for a backend that only tracks its total size on disk and the number of entries in each table.
+    /// Page information is not available, so the size on disk is reported as single byte leaf pages of the root.
+    pub(super) fn from_entry_counts<I: IntoIterator<Item = (&'static str, usize)>>(
+        size_on_disk: u64,
+        entry_counts: I,
+    ) -> Self {
+        let db_stats = entry_counts
+            .into_iter()
+            .map(|(name, entries)| DbStat::from_entries(name, entries))
+            .collect::<Vec<_>>();
+        let root = DbStat {
+            leaf_pages: size_on_disk as usize,
+            psize: 1,
+            ..DbStat::from_entries("[root]", db_stats.iter().map(|s| s.entries).sum())
+        };
+        Self {
+            root,
+            env_info: EnvInfo {
+                mapsize: size_on_disk as usize,
+                last_pgno: 0,
+                last_txnid: 0,
+                maxreaders: 0,
+                numreaders: 0,
+            },
+            db_stats,
+        }
+    }
+
     pub fn root(&self) -> &DbStat {
         &self.root
     }
@@ -90,6 +118,18 @@ pub struct DbStat {
 }
 
 impl DbStat {
+    fn from_entries(name: &'static str, entries: usize) -> Self {
+        Self {
+            name,
+            psize: 0,
+            depth: 0,
+            branch_pages: 0,
+            leaf_pages: 0,
+            overflow_pages: 0,
+            entries,
+        }
+    }
+
     /// Returns the total size in bytes of all pages
     pub fn total_page_size(&self) -> usize {
         self.psize as usize * (self.leaf_pages + self.branch_pages + self.overflow_pages)
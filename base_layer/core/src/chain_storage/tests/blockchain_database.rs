@@ -610,6 +610,82 @@ mod fetch_header_containing_kernel_mmr {
     }
 }
 
+mod generate_mmr_proofs {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+    use crate::{KernelMmrHasherBlake256, OutputMmrHasherBlake256};
+
+    #[test]
+    fn it_proves_outputs_and_their_spent_status() {
+        let db = setup();
+        let genesis = db.fetch_block(0).unwrap();
+        let (blocks, outputs) = add_many_chained_blocks(2, &db);
+        let (txns, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])]);
+        let (block, _) = create_next_block(&db, &blocks[1], txns);
+        db.add_block(block).unwrap().assert_added();
+
+        let spent_hash = blocks[0].body.outputs()[0].hash();
+        let unspent_hash = blocks[1].body.outputs()[0].hash();
+        let genesis_hash = genesis.block().body.outputs()[0].hash();
+        let proof = db
+            .generate_output_mmr_proof(vec![unspent_hash, FixedHash::zero(), spent_hash, genesis_hash])
+            .unwrap();
+        let tip = db.fetch_tip_header().unwrap();
+        assert_eq!(proof.header_hash, *tip.hash());
+        assert_eq!(proof.leaves.len(), 3);
+
+        let leaves = proof
+            .leaves
+            .iter()
+            .map(|(hash, index)| (*index, hash.as_slice()))
+            .collect::<Vec<_>>();
+        proof
+            .proof
+            .verify_leaves::<OutputMmrHasherBlake256>(tip.header().output_mr.as_slice(), &leaves)
+            .unwrap();
+
+        let leaf_index = |hash| proof.leaves.iter().find(|(h, _)| *h == hash).unwrap().1;
+        assert!(proof.proof.is_deleted(leaf_index(spent_hash)).unwrap());
+        assert!(!proof.proof.is_deleted(leaf_index(unspent_hash)).unwrap());
+        assert!(!proof.proof.is_deleted(leaf_index(genesis_hash)).unwrap());
+    }
+
+    #[test]
+    fn it_proves_kernels() {
+        let db = setup();
+        let genesis = db.fetch_block(0).unwrap();
+        let (blocks, _) = add_many_chained_blocks(3, &db);
+
+        let hashes = vec![
+            genesis.block().body.kernels()[1].hash(),
+            blocks[2].body.kernels()[0].hash(),
+            blocks[0].body.kernels()[0].hash(),
+        ];
+        let proof = db.generate_kernel_mmr_proof(hashes.clone()).unwrap();
+        let tip = db.fetch_tip_header().unwrap();
+        assert_eq!(proof.height, 3);
+        assert_eq!(proof.leaves.len(), 3);
+        assert!(proof.leaves.iter().all(|(hash, _)| hashes.contains(hash)));
+
+        let mut leaves = proof
+            .leaves
+            .iter()
+            .map(|(hash, index)| (*index as usize, hash.as_slice()))
+            .collect::<Vec<_>>();
+        proof
+            .proof
+            .verify_leaves::<KernelMmrHasherBlake256>(tip.header().kernel_mr.as_slice(), &leaves)
+            .unwrap();
+
+        leaves.pop();
+        assert!(proof
+            .proof
+            .verify_leaves::<KernelMmrHasherBlake256>(tip.header().kernel_mr.as_slice(), &leaves)
+            .is_err());
+    }
+}
+
 mod clear_all_pending_headers {
     use super::*;
 
@@ -0,0 +1,152 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::HashOutput;
+use tari_script::TariScript;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    blocks::BlockHeader,
+    chain_storage::PrunedOutput,
+    transactions::{tari_amount::MicroTari, transaction_components::OutputType},
+};
+
+/// UTXO set statistics for a single block on the main chain, as stored in the optional UTXO statistics index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockUtxoStats {
+    pub height: u64,
+    pub block_hash: HashOutput,
+    /// The number of outputs created in the block, including pruned outputs and burned outputs
+    pub outputs_created: u64,
+    /// The number of outputs removed from the UTXO set by the block. Burned outputs never enter the UTXO set, so they
+    /// are counted as spent in the block that created them.
+    pub outputs_spent: u64,
+    /// The number of outputs created in the block that had already been pruned when the entry was calculated. These
+    /// outputs are not included in the output type and script type counts.
+    pub pruned_outputs: u64,
+    /// The size of the UTXO set once the block has been applied
+    pub utxo_set_size: u64,
+    /// The number of outputs created in the block for each output type
+    pub output_types: HashMap<OutputType, u64>,
+    /// The number of outputs created in the block for each script type. See [script_type] for how a script type is
+    /// determined.
+    pub script_types: HashMap<String, u64>,
+    /// The sum of the minimum value promises of the burn outputs created in the block. The actual value of a burn
+    /// output is hidden, so this is a lower bound of the value burned.
+    pub burned_value: MicroTari,
+}
+
+impl BlockUtxoStats {
+    /// Calculates the statistics for a block from the outputs it created, the number of outputs it spent and the total
+    /// number of outputs spent on the chain up to and including the block.
+    pub(crate) fn from_block_outputs(
+        header: &BlockHeader,
+        block_hash: HashOutput,
+        outputs: &[PrunedOutput],
+        outputs_spent: u64,
+        total_outputs_spent: u64,
+    ) -> Self {
+        let mut stats = Self {
+            height: header.height,
+            block_hash,
+            outputs_created: outputs.len() as u64,
+            outputs_spent,
+            pruned_outputs: 0,
+            utxo_set_size: header.output_mmr_size.saturating_sub(total_outputs_spent),
+            output_types: HashMap::new(),
+            script_types: HashMap::new(),
+            burned_value: MicroTari::default(),
+        };
+
+        for output in outputs {
+            let output = match output.as_transaction_output() {
+                Some(output) => output,
+                None => {
+                    stats.pruned_outputs += 1;
+                    continue;
+                },
+            };
+            *stats.output_types.entry(output.features.output_type).or_default() += 1;
+            *stats.script_types.entry(script_type(&output.script)).or_default() += 1;
+            if output.is_burned() {
+                stats.burned_value += output.minimum_value_promise;
+            }
+        }
+
+        stats
+    }
+
+    pub fn num_outputs_of_type(&self, output_type: OutputType) -> u64 {
+        self.output_types.get(&output_type).copied().unwrap_or_default()
+    }
+}
+
+impl Display for BlockUtxoStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} ({}): {} created, {} spent, {} pruned, UTXO set size {}, {} burned",
+            self.height,
+            self.block_hash.to_hex(),
+            self.outputs_created,
+            self.outputs_spent,
+            self.pruned_outputs,
+            self.utxo_set_size,
+            self.burned_value
+        )
+    }
+}
+
+/// Returns the script type of the script, which is the sequence of opcode names in the script with any data they
+/// carry (hashes, public keys, heights) stripped. For example, a one-sided payment script has the type
+/// `PushPubKey` and a standard output has the type `Nop`.
+pub fn script_type(script: &TariScript) -> String {
+    if script.as_slice().is_empty() {
+        return "<empty>".to_string();
+    }
+    script
+        .to_opcodes()
+        .iter()
+        .map(|op| op.split('(').next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+
+    #[test]
+    fn it_strips_data_from_script_types() {
+        assert_eq!(script_type(&script!(Nop)), "Nop");
+        assert_eq!(script_type(&script!(CheckHeight(10) Drop Nop)), "CheckHeight Drop Nop");
+        assert_eq!(script_type(&TariScript::new(vec![])), "<empty>");
+    }
+}
@@ -31,7 +31,11 @@ use tari_script::script;
 use tari_utilities::epoch_time::EpochTime;
 
 use crate::{
-    consensus::{network::NetworkConsensus, ConsensusEncodingSized},
+    consensus::{
+        custom_network::{ConsensusConstantsDefinition, PowAlgorithmDefinition},
+        network::NetworkConsensus,
+        ConsensusEncodingSized,
+    },
     proof_of_work::{Difficulty, PowAlgorithm},
     transactions::{
         tari_amount::{uT, MicroTari, T},
@@ -86,6 +90,8 @@ pub struct ConsensusConstants {
     transaction_weight: TransactionWeight,
     /// Maximum byte size of TariScript
     max_script_byte_size: usize,
+    /// Maximum number of committee members in an output's sidechain features
+    max_sidechain_committee_size: usize,
     /// Range of valid transaction input versions
     input_version_range: RangeInclusive<TransactionInputVersion>,
     /// Range of valid transaction output (and features) versions
@@ -131,6 +137,17 @@ pub struct PowAlgorithmConstants {
     pub target_time: u64,
 }
 
+impl From<&PowAlgorithmDefinition> for PowAlgorithmConstants {
+    fn from(definition: &PowAlgorithmDefinition) -> Self {
+        Self {
+            max_target_time: definition.max_target_time,
+            min_difficulty: definition.min_difficulty.into(),
+            max_difficulty: definition.max_difficulty.into(),
+            target_time: definition.target_time,
+        }
+    }
+}
+
 // The target time used by the difficulty adjustment algorithms, their target time is the target block interval * PoW
 // algorithm count
 impl ConsensusConstants {
@@ -180,6 +197,11 @@ impl ConsensusConstants {
         Utc::now().add(Duration::seconds(self.future_time_limit as i64))
     }
 
+    /// The FTL (Future Time Limit) in seconds, i.e. how far ahead of the current time a block timestamp may be
+    pub fn future_time_limit(&self) -> u64 {
+        self.future_time_limit
+    }
+
     /// When doing difficulty adjustments and FTL calculations this is the amount of blocks we look at.
     pub fn get_difficulty_block_window(&self) -> u64 {
         self.difficulty_block_window
@@ -237,6 +259,11 @@ impl ConsensusConstants {
         self.max_script_byte_size
     }
 
+    /// The maximum number of committee members in an output's sidechain features
+    pub fn get_max_sidechain_committee_size(&self) -> usize {
+        self.max_sidechain_committee_size
+    }
+
     /// This is the min initial difficulty that can be requested for the pow
     pub fn min_pow_difficulty(&self, pow_algo: PowAlgorithm) -> Difficulty {
         match self.proof_of_work.get(&pow_algo) {
@@ -319,6 +346,7 @@ impl ConsensusConstants {
             faucet_value: (10 * 4000) * T,
             transaction_weight: TransactionWeight::latest(),
             max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -359,6 +387,7 @@ impl ConsensusConstants {
             faucet_value: (5000 * 4000) * T,
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -402,6 +431,7 @@ impl ConsensusConstants {
             faucet_value: 0.into(),
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -452,6 +482,7 @@ impl ConsensusConstants {
                 faucet_value: (10 * 4000) * T,
                 transaction_weight: TransactionWeight::v1(),
                 max_script_byte_size: 2048,
+                max_sidechain_committee_size: 64,
                 input_version_range: input_version_range.clone(),
                 output_version_range: output_version_range.clone(),
                 kernel_version_range: kernel_version_range.clone(),
@@ -475,6 +506,7 @@ impl ConsensusConstants {
                 faucet_value: (10 * 4000) * T,
                 transaction_weight: TransactionWeight::v1(),
                 max_script_byte_size: 2048,
+                max_sidechain_committee_size: 64,
                 input_version_range,
                 output_version_range,
                 kernel_version_range,
@@ -523,6 +555,7 @@ impl ConsensusConstants {
             faucet_value: (10 * 4000) * T,
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -564,6 +597,7 @@ impl ConsensusConstants {
             faucet_value: MicroTari::from(0),
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -571,6 +605,47 @@ impl ConsensusConstants {
         }]
     }
 
+    /// Consensus constants for a [Network::Custom] network, built from one entry of a custom network definition file.
+    /// The emission decay and permitted output types must already have been given a `'static` lifetime by the caller.
+    pub(in crate::consensus) fn custom(
+        definition: &ConsensusConstantsDefinition,
+        emission: (MicroTari, &'static [u64], MicroTari),
+        permitted_output_types: &'static [OutputType],
+    ) -> Self {
+        let mut algos = HashMap::new();
+        if let Some(sha3) = &definition.sha3 {
+            algos.insert(PowAlgorithm::Sha3, sha3.into());
+        }
+        if let Some(monero) = &definition.monero {
+            algos.insert(PowAlgorithm::Monero, monero.into());
+        }
+        let (emission_initial, emission_decay, emission_tail) = emission;
+        let (input_version_range, output_version_range, kernel_version_range) = version_zero();
+        ConsensusConstants {
+            effective_from_height: definition.effective_from_height,
+            coinbase_lock_height: definition.coinbase_lock_height,
+            blockchain_version: definition.blockchain_version,
+            valid_blockchain_version_range: definition.min_blockchain_version..=definition.max_blockchain_version,
+            future_time_limit: definition.future_time_limit,
+            difficulty_block_window: definition.difficulty_block_window,
+            max_block_transaction_weight: definition.max_block_transaction_weight,
+            median_timestamp_count: definition.median_timestamp_count,
+            emission_initial,
+            emission_decay,
+            emission_tail,
+            max_randomx_seed_height: definition.max_randomx_seed_height,
+            proof_of_work: algos,
+            faucet_value: definition.faucet_value,
+            transaction_weight: TransactionWeight::v1(),
+            max_script_byte_size: definition.max_script_byte_size,
+            max_sidechain_committee_size: definition.max_sidechain_committee_size,
+            input_version_range,
+            output_version_range,
+            kernel_version_range,
+            permitted_output_types,
+        }
+    }
+
     const fn current_permitted_output_types() -> &'static [OutputType] {
         &[OutputType::Coinbase, OutputType::Standard, OutputType::Burn]
     }
@@ -616,6 +691,11 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_max_sidechain_committee_size(mut self, committee_size: usize) -> Self {
+        self.consensus.max_sidechain_committee_size = committee_size;
+        self
+    }
+
     pub fn with_max_block_transaction_weight(mut self, weight: u64) -> Self {
         self.consensus.max_block_transaction_weight = weight;
         self
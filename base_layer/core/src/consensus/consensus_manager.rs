@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use tari_common::configuration::Network;
 use thiserror::Error;
@@ -34,6 +34,7 @@ use crate::{
 };
 use crate::{
     consensus::{
+        custom_network::{set_custom_network, CustomNetwork, CustomNetworkError},
         emission::{Emission, EmissionSchedule},
         ConsensusConstants,
         NetworkConsensus,
@@ -53,6 +54,8 @@ pub enum ConsensusManagerError {
     PoisonedAccess(String),
     #[error("No Difficulty adjustment manager present")]
     MissingDifficultyAdjustmentManager,
+    #[error("Custom network error: {0}")]
+    CustomNetworkError(#[from] CustomNetworkError),
 }
 
 /// Container struct for consensus rules. This can be cheaply cloned.
@@ -66,6 +69,13 @@ impl ConsensusManager {
         ConsensusManagerBuilder::new(network)
     }
 
+    /// Loads and validates the network definition file at `path` and registers it as the definition for
+    /// [Network::Custom]. This must be called before any consensus manager is built for the custom network.
+    pub fn load_custom_network<P: AsRef<Path>>(path: P) -> Result<&'static CustomNetwork, ConsensusManagerError> {
+        let network = CustomNetwork::load(path)?;
+        Ok(set_custom_network(network)?)
+    }
+
     /// Returns the genesis block for the selected network.
     #[cfg(feature = "base_node")]
     pub fn get_genesis_block(&self) -> ChainBlock {
@@ -0,0 +1,554 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Support for [Network::Custom]. A custom network is described by a versioned network definition file (TOML, or JSON
+//! if the file has a `.json` extension) containing the consensus constants, emission parameters and genesis block. The
+//! file is loaded and validated once at startup and registered process-wide, after which the custom network can be
+//! used like any of the hard-coded networks.
+
+use std::{fs, io, path::Path};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_common_types::types::FixedHash;
+use thiserror::Error;
+
+use crate::{
+    blocks::Block,
+    consensus::ConsensusConstants,
+    transactions::{tari_amount::MicroTari, transaction_components::OutputType},
+};
+
+/// The only network definition file version understood by this node
+pub const CUSTOM_NETWORK_FILE_VERSION: u32 = 1;
+
+static CUSTOM_NETWORK: OnceCell<CustomNetwork> = OnceCell::new();
+
+#[derive(Debug, Error)]
+pub enum CustomNetworkError {
+    #[error("Could not read the network definition file: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Could not parse the network definition file: {0}")]
+    ParseError(String),
+    #[error(
+        "Unsupported network definition file version {0}, expected version {}",
+        CUSTOM_NETWORK_FILE_VERSION
+    )]
+    UnsupportedVersion(u32),
+    #[error("Invalid network definition: {0}")]
+    InvalidDefinition(String),
+    #[error("Invalid genesis block in network definition: {0}")]
+    InvalidGenesisBlock(String),
+    #[error("A different custom network definition ('{0}') has already been loaded")]
+    AlreadyLoaded(String),
+}
+
+/// The on-disk representation of a custom network definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomNetworkDefinition {
+    /// The file format version, must equal [CUSTOM_NETWORK_FILE_VERSION]
+    pub version: u32,
+    /// A human-readable name for the network
+    pub name: String,
+    /// The emission schedule, shared by every set of consensus constants
+    pub emission: EmissionDefinition,
+    /// The consensus constants, in ascending order of the height from which they are effective
+    pub consensus_constants: Vec<ConsensusConstantsDefinition>,
+    /// The genesis block, including its header Merkle roots
+    pub genesis_block: Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmissionDefinition {
+    pub initial: MicroTari,
+    /// See [EmissionSchedule::new](crate::consensus::emission::EmissionSchedule::new). Every value must be less than
+    /// 64.
+    pub decay: Vec<u64>,
+    pub tail: MicroTari,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsensusConstantsDefinition {
+    pub effective_from_height: u64,
+    pub coinbase_lock_height: u64,
+    pub blockchain_version: u16,
+    pub min_blockchain_version: u16,
+    pub max_blockchain_version: u16,
+    pub future_time_limit: u64,
+    pub difficulty_block_window: u64,
+    pub max_block_transaction_weight: u64,
+    pub median_timestamp_count: usize,
+    pub max_randomx_seed_height: u64,
+    pub faucet_value: MicroTari,
+    pub max_script_byte_size: usize,
+    pub max_sidechain_committee_size: usize,
+    pub permitted_output_types: Vec<OutputType>,
+    /// SHA3 proof of work parameters, or `None` if SHA3 mining is not accepted
+    pub sha3: Option<PowAlgorithmDefinition>,
+    /// Monero merge mining parameters, or `None` if merge mining is not accepted
+    pub monero: Option<PowAlgorithmDefinition>,
+}
+
+/// The share of blocks allocated to a proof of work algorithm is expressed through its target time, as for the
+/// hard-coded networks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PowAlgorithmDefinition {
+    pub target_time: u64,
+    pub max_target_time: u64,
+    pub min_difficulty: u64,
+    pub max_difficulty: u64,
+}
+
+/// A validated custom network definition
+#[derive(Debug, Clone)]
+pub struct CustomNetwork {
+    name: String,
+    consensus_constants: Vec<ConsensusConstants>,
+    genesis_block: Block,
+}
+
+impl CustomNetwork {
+    /// Reads, parses and validates the network definition file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CustomNetworkError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        let definition = if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            serde_json::from_str::<CustomNetworkDefinition>(&contents)
+                .map_err(|err| CustomNetworkError::ParseError(err.to_string()))?
+        } else {
+            toml::from_str::<CustomNetworkDefinition>(&contents)
+                .map_err(|err| CustomNetworkError::ParseError(err.to_string()))?
+        };
+        Self::try_from_definition(definition)
+    }
+
+    /// Validates the definition and converts it into consensus constants. The emission decay and permitted output
+    /// types are leaked to give them the `'static` lifetime that [ConsensusConstants] requires, so this should only be
+    /// called once per process for any given definition.
+    pub fn try_from_definition(definition: CustomNetworkDefinition) -> Result<Self, CustomNetworkError> {
+        validate_definition(&definition)?;
+        validate_genesis_block(&definition)?;
+
+        let CustomNetworkDefinition {
+            name,
+            emission,
+            consensus_constants,
+            genesis_block,
+            ..
+        } = definition;
+        let decay: &'static [u64] = Box::leak(emission.decay.into_boxed_slice());
+        let consensus_constants = consensus_constants
+            .iter()
+            .map(|c| {
+                let permitted_output_types: &'static [OutputType] =
+                    Box::leak(c.permitted_output_types.clone().into_boxed_slice());
+                ConsensusConstants::custom(c, (emission.initial, decay, emission.tail), permitted_output_types)
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            consensus_constants,
+            genesis_block,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn consensus_constants(&self) -> &[ConsensusConstants] {
+        &self.consensus_constants
+    }
+
+    pub fn genesis_block(&self) -> &Block {
+        &self.genesis_block
+    }
+}
+
+/// Registers the custom network for this process. Registering the same network (by genesis block hash) more than once
+/// is allowed, registering a different one is an error.
+pub fn set_custom_network(network: CustomNetwork) -> Result<&'static CustomNetwork, CustomNetworkError> {
+    let genesis_hash = network.genesis_block.hash();
+    let registered = CUSTOM_NETWORK.get_or_init(|| network);
+    if registered.genesis_block.hash() == genesis_hash {
+        Ok(registered)
+    } else {
+        Err(CustomNetworkError::AlreadyLoaded(registered.name.clone()))
+    }
+}
+
+/// Returns the registered custom network, if one has been loaded
+pub fn custom_network() -> Option<&'static CustomNetwork> {
+    CUSTOM_NETWORK.get()
+}
+
+/// Returns the registered custom network, panicking if [Network::Custom] is used without loading a definition first
+pub(crate) fn expect_custom_network() -> &'static CustomNetwork {
+    custom_network().unwrap_or_else(|| {
+        panic!(
+            "Network '{}' selected but no network definition file has been loaded",
+            Network::Custom
+        )
+    })
+}
+
+fn validate_definition(definition: &CustomNetworkDefinition) -> Result<(), CustomNetworkError> {
+    use CustomNetworkError::InvalidDefinition;
+
+    if definition.version != CUSTOM_NETWORK_FILE_VERSION {
+        return Err(CustomNetworkError::UnsupportedVersion(definition.version));
+    }
+    if definition.emission.decay.is_empty() {
+        return Err(InvalidDefinition("emission decay must not be empty".to_string()));
+    }
+    if definition.emission.decay.iter().any(|d| *d >= 64) {
+        return Err(InvalidDefinition(
+            "emission decay values must be less than 64".to_string(),
+        ));
+    }
+    if definition.emission.tail > definition.emission.initial {
+        return Err(InvalidDefinition(
+            "tail emission must not exceed the initial emission".to_string(),
+        ));
+    }
+
+    let first = definition
+        .consensus_constants
+        .first()
+        .ok_or_else(|| InvalidDefinition("at least one set of consensus constants is required".to_string()))?;
+    if first.effective_from_height != 0 {
+        return Err(InvalidDefinition(
+            "the first set of consensus constants must be effective from height 0".to_string(),
+        ));
+    }
+    if definition
+        .consensus_constants
+        .windows(2)
+        .any(|w| w[0].effective_from_height >= w[1].effective_from_height)
+    {
+        return Err(InvalidDefinition(
+            "consensus constants must be in strictly ascending order of effective height".to_string(),
+        ));
+    }
+
+    for constants in &definition.consensus_constants {
+        let height = constants.effective_from_height;
+        let invalid = |reason: &str| InvalidDefinition(format!("constants effective from {}: {}", height, reason));
+        if !(constants.min_blockchain_version..=constants.max_blockchain_version)
+            .contains(&constants.blockchain_version)
+        {
+            return Err(invalid("blockchain version is outside of the valid version range"));
+        }
+        if constants.difficulty_block_window == 0 {
+            return Err(invalid("difficulty block window must be greater than 0"));
+        }
+        if constants.median_timestamp_count == 0 {
+            return Err(invalid("median timestamp count must be greater than 0"));
+        }
+        if constants.max_block_transaction_weight == 0 {
+            return Err(invalid("max block transaction weight must be greater than 0"));
+        }
+        if !constants.permitted_output_types.contains(&OutputType::Coinbase) {
+            return Err(invalid("coinbase outputs must be permitted"));
+        }
+        let algos = [("sha3", &constants.sha3), ("monero", &constants.monero)];
+        if algos.iter().all(|(_, algo)| algo.is_none()) {
+            return Err(invalid("at least one proof of work algorithm is required"));
+        }
+        for (name, algo) in algos.iter().filter_map(|(name, algo)| algo.as_ref().map(|a| (name, a))) {
+            if algo.target_time == 0 {
+                return Err(invalid(&format!("{} target time must be greater than 0", name)));
+            }
+            if algo.max_target_time < algo.target_time {
+                return Err(invalid(&format!(
+                    "{} max target time must not be less than its target time",
+                    name
+                )));
+            }
+            if algo.min_difficulty == 0 || algo.min_difficulty > algo.max_difficulty {
+                return Err(invalid(&format!(
+                    "{} difficulty range must be non-empty and start above 0",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_genesis_block(definition: &CustomNetworkDefinition) -> Result<(), CustomNetworkError> {
+    use CustomNetworkError::InvalidGenesisBlock;
+
+    let block = &definition.genesis_block;
+    if block.header.height != 0 {
+        return Err(InvalidGenesisBlock("height must be 0".to_string()));
+    }
+    if block.header.prev_hash != FixedHash::zero() {
+        return Err(InvalidGenesisBlock("previous hash must be zero".to_string()));
+    }
+    if !block.body.inputs().is_empty() {
+        return Err(InvalidGenesisBlock(
+            "genesis block must not spend any inputs".to_string(),
+        ));
+    }
+    if block.header.kernel_mmr_size != block.body.kernels().len() as u64 {
+        return Err(InvalidGenesisBlock(
+            "kernel MMR size does not match the block body".to_string(),
+        ));
+    }
+    if block.header.output_mmr_size != block.body.outputs().len() as u64 {
+        return Err(InvalidGenesisBlock(
+            "output MMR size does not match the block body".to_string(),
+        ));
+    }
+    for kernel in block.body.kernels() {
+        kernel
+            .verify_signature()
+            .map_err(|err| InvalidGenesisBlock(format!("kernel signature is invalid: {}", err)))?;
+    }
+
+    #[cfg(feature = "base_node")]
+    validate_genesis_merkle_roots(block)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "base_node")]
+fn validate_genesis_merkle_roots(block: &Block) -> Result<(), CustomNetworkError> {
+    use croaring::Bitmap;
+
+    use crate::{KernelMmr, MutableOutputMmr, WitnessMmr};
+
+    let mmr_error =
+        |err: tari_mmr::error::MerkleMountainRangeError| CustomNetworkError::InvalidGenesisBlock(err.to_string());
+
+    let mut kernel_mmr = KernelMmr::new(Vec::new());
+    for kernel in block.body.kernels() {
+        kernel_mmr.push(kernel.hash().to_vec()).map_err(mmr_error)?;
+    }
+    let mut witness_mmr = WitnessMmr::new(Vec::new());
+    let mut output_mmr = MutableOutputMmr::new(Vec::new(), Bitmap::create()).map_err(mmr_error)?;
+    for output in block.body.outputs() {
+        witness_mmr.push(output.witness_hash().to_vec()).map_err(mmr_error)?;
+        output_mmr.push(output.hash().to_vec()).map_err(mmr_error)?;
+    }
+
+    let roots = [
+        (
+            "kernel",
+            kernel_mmr.get_merkle_root().map_err(mmr_error)?,
+            &block.header.kernel_mr,
+        ),
+        (
+            "witness",
+            witness_mmr.get_merkle_root().map_err(mmr_error)?,
+            &block.header.witness_mr,
+        ),
+        (
+            "output",
+            output_mmr.get_merkle_root().map_err(mmr_error)?,
+            &block.header.output_mr,
+        ),
+    ];
+    for (name, computed, expected) in &roots {
+        if computed.as_slice() != expected.as_slice() {
+            return Err(CustomNetworkError::InvalidGenesisBlock(format!(
+                "{} Merkle root does not match the block body",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::{
+        blocks::genesis_block::get_igor_genesis_block,
+        consensus::{ConsensusManager, NetworkConsensus},
+        proof_of_work::PowAlgorithm,
+    };
+
+    fn constants_definition(effective_from_height: u64) -> ConsensusConstantsDefinition {
+        ConsensusConstantsDefinition {
+            effective_from_height,
+            coinbase_lock_height: 6,
+            blockchain_version: 0,
+            min_blockchain_version: 0,
+            max_blockchain_version: 0,
+            future_time_limit: 540,
+            difficulty_block_window: 90,
+            max_block_transaction_weight: 127_795,
+            median_timestamp_count: 11,
+            max_randomx_seed_height: 3000,
+            faucet_value: MicroTari::zero(),
+            max_script_byte_size: 2048,
+            max_sidechain_committee_size: 64,
+            permitted_output_types: vec![OutputType::Coinbase, OutputType::Standard],
+            sha3: Some(PowAlgorithmDefinition {
+                target_time: 240,
+                max_target_time: 1440,
+                min_difficulty: 1,
+                max_difficulty: u64::MAX,
+            }),
+            monero: None,
+        }
+    }
+
+    fn definition() -> CustomNetworkDefinition {
+        CustomNetworkDefinition {
+            version: CUSTOM_NETWORK_FILE_VERSION,
+            name: "test".to_string(),
+            emission: EmissionDefinition {
+                initial: 5_000_000.into(),
+                decay: vec![21, 22, 23, 25, 26, 37],
+                tail: 100.into(),
+            },
+            consensus_constants: vec![constants_definition(0), constants_definition(1000)],
+            genesis_block: get_igor_genesis_block().block().clone(),
+        }
+    }
+
+    #[test]
+    fn it_converts_a_valid_definition() {
+        let network = CustomNetwork::try_from_definition(definition()).unwrap();
+        assert_eq!(network.name(), "test");
+        assert_eq!(network.consensus_constants().len(), 2);
+        let constants = &network.consensus_constants()[1];
+        assert_eq!(constants.effective_from_height(), 1000);
+        assert_eq!(constants.get_pow_algo_count(), 1);
+        assert_eq!(constants.get_diff_target_block_interval(PowAlgorithm::Sha3), 240);
+        assert_eq!(constants.permitted_output_types(), &[
+            OutputType::Coinbase,
+            OutputType::Standard
+        ]);
+        let (initial, decay, tail) = constants.emission_amounts();
+        assert_eq!(initial, 5_000_000.into());
+        assert_eq!(decay, &[21, 22, 23, 25, 26, 37]);
+        assert_eq!(tail, 100.into());
+        assert_eq!(network.genesis_block(), get_igor_genesis_block().block());
+    }
+
+    #[test]
+    fn it_loads_a_json_definition_file() {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        file.write_all(serde_json::to_string(&definition()).unwrap().as_bytes())
+            .unwrap();
+        let network = CustomNetwork::load(file.path()).unwrap();
+        assert_eq!(network.consensus_constants().len(), 2);
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"version = 1\nname = \"test\"\n").unwrap();
+        let err = CustomNetwork::load(file.path()).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::ParseError(_)));
+    }
+
+    #[test]
+    fn it_rejects_invalid_definitions() {
+        let mut def = definition();
+        def.version = CUSTOM_NETWORK_FILE_VERSION + 1;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::UnsupportedVersion(_)));
+
+        let mut def = definition();
+        def.consensus_constants.clear();
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+
+        let mut def = definition();
+        def.consensus_constants[0].effective_from_height = 10;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+
+        let mut def = definition();
+        def.consensus_constants[1].effective_from_height = 0;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+
+        let mut def = definition();
+        def.consensus_constants[0].sha3 = None;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+
+        let mut def = definition();
+        def.consensus_constants[1].sha3.as_mut().unwrap().target_time = 0;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+
+        let mut def = definition();
+        def.emission.decay = vec![64];
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidDefinition(_)));
+    }
+
+    #[test]
+    fn it_rejects_an_inconsistent_genesis_block() {
+        let mut def = definition();
+        def.genesis_block.header.height = 1;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidGenesisBlock(_)));
+
+        let mut def = definition();
+        def.genesis_block.header.kernel_mmr_size = 2;
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidGenesisBlock(_)));
+
+        let mut def = definition();
+        def.genesis_block.header.output_mr = FixedHash::zero();
+        let err = CustomNetwork::try_from_definition(def).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::InvalidGenesisBlock(_)));
+    }
+
+    #[test]
+    fn it_registers_the_custom_network() {
+        let network = CustomNetwork::try_from_definition(definition()).unwrap();
+        set_custom_network(network.clone()).unwrap();
+        // Registering the same network again is a no-op
+        set_custom_network(network).unwrap();
+
+        let mut other = definition();
+        other.name = "other".to_string();
+        other.genesis_block.header.timestamp = 1.into();
+        let other = CustomNetwork::try_from_definition(other).unwrap();
+        let err = set_custom_network(other).unwrap_err();
+        assert!(matches!(err, CustomNetworkError::AlreadyLoaded(_)));
+
+        let constants = NetworkConsensus::from(Network::Custom).create_consensus_constants();
+        assert_eq!(constants.len(), 2);
+        let rules = ConsensusManager::builder(Network::Custom).build();
+        assert_eq!(rules.consensus_constants(1500).effective_from_height(), 1000);
+        assert_eq!(rules.get_genesis_block().block(), get_igor_genesis_block().block());
+    }
+}
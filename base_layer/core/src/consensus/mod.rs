@@ -26,6 +26,9 @@ pub(crate) mod chain_strength_comparer;
 pub mod consensus_constants;
 pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder};
 
+pub mod custom_network;
+pub use custom_network::{CustomNetwork, CustomNetworkDefinition, CustomNetworkError};
+
 mod consensus_manager;
 pub use consensus_manager::{ConsensusManager, ConsensusManagerBuilder, ConsensusManagerError};
 
@@ -22,7 +22,7 @@
 
 use tari_common::configuration::Network;
 
-use super::consensus_constants::ConsensusConstants;
+use super::{consensus_constants::ConsensusConstants, custom_network::expect_custom_network};
 
 /// Represents the consensus used for a given network
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -30,7 +30,7 @@ pub struct NetworkConsensus(Network);
 
 impl NetworkConsensus {
     pub fn create_consensus_constants(&self) -> Vec<ConsensusConstants> {
-        use Network::{Dibbler, Esmeralda, Igor, LocalNet, MainNet, Ridcully, Stibbons, Weatherwax};
+        use Network::{Custom, Dibbler, Esmeralda, Igor, LocalNet, MainNet, Ridcully, Stibbons, Weatherwax};
         match self.as_network() {
             MainNet => ConsensusConstants::mainnet(),
             LocalNet => ConsensusConstants::localnet(),
@@ -40,6 +40,7 @@ impl NetworkConsensus {
             Esmeralda => ConsensusConstants::esmeralda(),
             Ridcully => unimplemented!("Ridcully network is no longer supported"),
             Stibbons => unimplemented!("Stibbons network is no longer supported"),
+            Custom => expect_custom_network().consensus_constants().to_vec(),
         }
     }
 
@@ -231,6 +231,25 @@ impl CovenantArg {
     }
 }
 
+macro_rules! from_value_impl {
+    ($ty:ty, $variant:ident) => {
+        impl From<$ty> for CovenantArg {
+            fn from(value: $ty) -> Self {
+                CovenantArg::$variant(value)
+            }
+        }
+    };
+}
+
+from_value_impl!(FixedHash, Hash);
+from_value_impl!(PublicKey, PublicKey);
+from_value_impl!(Commitment, Commitment);
+from_value_impl!(TariScript, TariScript);
+from_value_impl!(Covenant, Covenant);
+from_value_impl!(OutputType, OutputType);
+from_value_impl!(u64, Uint);
+from_value_impl!(Vec<u8>, Bytes);
+
 impl Display for CovenantArg {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         #[allow(clippy::enum_glob_use)]
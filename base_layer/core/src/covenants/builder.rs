@@ -0,0 +1,208 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::FixedHash;
+
+use crate::covenants::{
+    arguments::CovenantArg,
+    covenant::Covenant,
+    fields::OutputField,
+    filters::CovenantFilter,
+    token::CovenantToken,
+};
+
+/// Typed construction of covenants. Each constructor returns a single filter expression, with the arguments and operand
+/// filters that it requires, so that well-formed covenants are built by nesting expressions.
+///
+/// ```rust,ignore
+/// // Before height 42, this may only be spent into an output with a maturity of 100
+/// let covenant = CovenantBuilder::or(
+///     CovenantBuilder::absolute_height(42),
+///     CovenantBuilder::field_eq(OutputField::FeaturesMaturity, 100u64),
+/// )
+/// .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CovenantBuilder {
+    tokens: Vec<CovenantToken>,
+}
+
+impl CovenantBuilder {
+    fn with_filter(filter: CovenantFilter, operands: Vec<CovenantToken>) -> Self {
+        let mut tokens = vec![CovenantToken::Filter(filter)];
+        tokens.extend(operands);
+        Self { tokens }
+    }
+
+    pub fn identity() -> Self {
+        Self::with_filter(CovenantFilter::identity(), vec![])
+    }
+
+    pub fn and(a: CovenantBuilder, b: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::and(), [a.tokens, b.tokens].concat())
+    }
+
+    pub fn or(a: CovenantBuilder, b: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::or(), [a.tokens, b.tokens].concat())
+    }
+
+    pub fn xor(a: CovenantBuilder, b: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::xor(), [a.tokens, b.tokens].concat())
+    }
+
+    pub fn not(a: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::not(), a.tokens)
+    }
+
+    /// Selects the whole output set if `a` selects every output in it, otherwise no outputs
+    pub fn all(a: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::all(), a.tokens)
+    }
+
+    /// Selects the whole output set if `a` selects at least one output in it, otherwise no outputs
+    pub fn any(a: CovenantBuilder) -> Self {
+        Self::with_filter(CovenantFilter::any(), a.tokens)
+    }
+
+    pub fn output_hash_eq(hash: FixedHash) -> Self {
+        Self::with_filter(CovenantFilter::output_hash_eq(), vec![CovenantToken::hash(hash)])
+    }
+
+    pub fn fields_preserved<I: IntoIterator<Item = OutputField>>(fields: I) -> Self {
+        let fields = fields.into_iter().collect();
+        Self::with_filter(CovenantFilter::fields_preserved(), vec![CovenantToken::fields(fields)])
+    }
+
+    pub fn fields_hashed_eq<I: IntoIterator<Item = OutputField>>(fields: I, hash: FixedHash) -> Self {
+        let fields = fields.into_iter().collect();
+        Self::with_filter(CovenantFilter::fields_hashed_eq(), vec![
+            CovenantToken::fields(fields),
+            CovenantToken::hash(hash),
+        ])
+    }
+
+    pub fn field_eq<T: Into<CovenantArg>>(field: OutputField, value: T) -> Self {
+        Self::with_filter(CovenantFilter::field_eq(), vec![
+            CovenantToken::field(field),
+            value.into().into(),
+        ])
+    }
+
+    pub fn absolute_height(height: u64) -> Self {
+        Self::with_filter(CovenantFilter::absolute_height(), vec![CovenantToken::uint(height)])
+    }
+
+    /// Selects the whole output set if it contains between `min` and `max` outputs inclusive, otherwise no outputs
+    pub fn output_count(min: u64, max: u64) -> Self {
+        Self::with_filter(CovenantFilter::output_count(), vec![
+            CovenantToken::uint(min),
+            CovenantToken::uint(max),
+        ])
+    }
+
+    pub fn field_prefix_eq(field: OutputField, prefix: Vec<u8>) -> Self {
+        Self::with_filter(CovenantFilter::field_prefix_eq(), vec![
+            CovenantToken::field(field),
+            CovenantToken::bytes(prefix),
+        ])
+    }
+
+    pub fn build(self) -> Covenant {
+        self.tokens.into_iter().collect()
+    }
+}
+
+impl From<CovenantBuilder> for Covenant {
+    fn from(builder: CovenantBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::test::{create_input, create_outputs},
+        transactions::transaction_components::OutputType,
+    };
+
+    #[test]
+    fn it_builds_the_same_covenant_as_the_macro() {
+        let hash = FixedHash::zero();
+        let covenant = CovenantBuilder::and(
+            CovenantBuilder::xor(
+                CovenantBuilder::output_hash_eq(hash),
+                CovenantBuilder::not(CovenantBuilder::identity()),
+            ),
+            CovenantBuilder::or(
+                CovenantBuilder::fields_hashed_eq(vec![OutputField::Commitment, OutputField::Features], hash),
+                CovenantBuilder::and(
+                    CovenantBuilder::field_eq(OutputField::Script, script!(Nop)),
+                    CovenantBuilder::field_eq(OutputField::FeaturesOutputType, OutputType::Burn),
+                ),
+            ),
+        )
+        .build();
+        let expected = covenant!(and(
+            xor(output_hash_eq(@hash(hash)), not(identity())),
+            or(
+                fields_hashed_eq(@fields(@field::commitment, @field::features), @hash(hash)),
+                and(
+                    field_eq(@field::script, @script(script!(Nop))),
+                    field_eq(@field::features_output_type, @output_type(Burn))
+                )
+            )
+        ));
+        assert_eq!(covenant, expected);
+        assert_eq!(covenant.to_bytes(), expected.to_bytes());
+
+        let covenant = CovenantBuilder::and(
+            CovenantBuilder::all(CovenantBuilder::field_prefix_eq(OutputField::FeaturesMetadata, vec![1])),
+            CovenantBuilder::any(CovenantBuilder::output_count(1, 2)),
+        )
+        .build();
+        let expected = covenant!(and(
+            all(field_prefix_eq(@field::features_metadata, @bytes(vec![1]))),
+            any(output_count(@uint(1), @uint(2)))
+        ));
+        assert_eq!(covenant, expected);
+    }
+
+    #[test]
+    fn it_builds_an_executable_covenant() {
+        let mut outputs = create_outputs(10, Default::default());
+        outputs[4].features.maturity = 42;
+        outputs[5].features.maturity = 42;
+        let mut input = create_input();
+        input.set_maturity(42).unwrap();
+        let covenant: Covenant = CovenantBuilder::and(
+            CovenantBuilder::absolute_height(10),
+            CovenantBuilder::fields_preserved(vec![OutputField::FeaturesMaturity]),
+        )
+        .into();
+        assert_eq!(covenant.execute(10, &input, &outputs).unwrap(), 2);
+        covenant.execute(9, &input, &outputs).unwrap_err();
+    }
+}
@@ -55,17 +55,21 @@ pub(super) fn is_valid_filter_code(code: u8) -> bool {
     ALL_FILTERS.contains(&code)
 }
 
-pub(super) const ALL_FILTERS: [u8; 10] = [
+pub(super) const ALL_FILTERS: [u8; 14] = [
     FILTER_IDENTITY,
     FILTER_AND,
     FILTER_OR,
     FILTER_XOR,
     FILTER_NOT,
+    FILTER_ALL,
+    FILTER_ANY,
     FILTER_OUTPUT_HASH_EQ,
     FILTER_FIELDS_PRESERVED,
     FILTER_FIELDS_HASHED_EQ,
     FILTER_FIELD_EQ,
     FILTER_ABSOLUTE_HEIGHT,
+    FILTER_OUTPUT_COUNT,
+    FILTER_FIELD_PREFIX_EQ,
 ];
 
 pub const FILTER_IDENTITY: u8 = 0x20;
@@ -73,12 +77,16 @@ pub const FILTER_AND: u8 = 0x21;
 pub const FILTER_OR: u8 = 0x22;
 pub const FILTER_XOR: u8 = 0x23;
 pub const FILTER_NOT: u8 = 0x24;
+pub const FILTER_ALL: u8 = 0x25;
+pub const FILTER_ANY: u8 = 0x26;
 
 pub const FILTER_OUTPUT_HASH_EQ: u8 = 0x30;
 pub const FILTER_FIELDS_PRESERVED: u8 = 0x31;
 pub const FILTER_FIELDS_HASHED_EQ: u8 = 0x32;
 pub const FILTER_FIELD_EQ: u8 = 0x33;
 pub const FILTER_ABSOLUTE_HEIGHT: u8 = 0x34;
+pub const FILTER_OUTPUT_COUNT: u8 = 0x35;
+pub const FILTER_FIELD_PREFIX_EQ: u8 = 0x36;
 
 //---------------------------------- FIELD byte codes --------------------------------------------//
 pub const FIELD_COMMITMENT: u8 = 0x00;
@@ -24,7 +24,9 @@ use crate::{
     covenants::{
         arguments::CovenantArg,
         error::CovenantError,
+        explain::ExplainStep,
         filters::CovenantFilter,
+        output_set::OutputSet,
         token::{CovenantToken, CovenantTokenCollection},
     },
     transactions::transaction_components::TransactionInput,
@@ -34,6 +36,15 @@ pub struct CovenantContext<'a> {
     input: &'a TransactionInput,
     tokens: CovenantTokenCollection,
     block_height: u64,
+    trace: Option<Trace>,
+}
+
+/// Filter evaluations recorded while explaining a covenant
+#[derive(Debug, Default)]
+struct Trace {
+    steps: Vec<ExplainStep>,
+    /// Indexes into `steps` of the filters that are currently being evaluated, innermost last
+    open: Vec<usize>,
 }
 
 impl<'a> CovenantContext<'a> {
@@ -42,6 +53,35 @@ impl<'a> CovenantContext<'a> {
             input,
             tokens,
             block_height,
+            trace: None,
+        }
+    }
+
+    /// Record each filter evaluation from here on. The recorded steps are returned by `take_trace`.
+    pub fn enable_trace(&mut self) {
+        self.trace = Some(Trace::default());
+    }
+
+    /// Returns the recorded filter evaluations in the order that the filters were started.
+    pub fn take_trace(&mut self) -> Vec<ExplainStep> {
+        self.trace.take().map(|trace| trace.steps).unwrap_or_default()
+    }
+
+    pub(super) fn trace_enter(&mut self, filter: &CovenantFilter, output_set: &OutputSet<'_>) {
+        if let Some(trace) = self.trace.as_mut() {
+            let step = ExplainStep::new(trace.open.len(), filter.name(), output_set.get_selected_indexes());
+            trace.open.push(trace.steps.len());
+            trace.steps.push(step);
+        }
+    }
+
+    pub(super) fn trace_exit(&mut self, output_set: &OutputSet<'_>, succeeded: bool) {
+        if let Some(trace) = self.trace.as_mut() {
+            if let Some(idx) = trace.open.pop() {
+                if succeeded {
+                    trace.steps[idx].set_output_indexes(output_set.get_selected_indexes());
+                }
+            }
         }
     }
 
@@ -51,7 +91,14 @@ impl<'a> CovenantContext<'a> {
 
     pub fn next_arg(&mut self) -> Result<CovenantArg, CovenantError> {
         match self.tokens.next().ok_or(CovenantError::UnexpectedEndOfTokens)? {
-            CovenantToken::Arg(arg) => Ok(*arg),
+            CovenantToken::Arg(arg) => {
+                if let Some(trace) = self.trace.as_mut() {
+                    if let Some(idx) = trace.open.last() {
+                        trace.steps[*idx].push_arg((*arg).clone());
+                    }
+                }
+                Ok(*arg)
+            },
             CovenantToken::Filter(_) => Err(CovenantError::ExpectedArgButGotFilter),
         }
     }
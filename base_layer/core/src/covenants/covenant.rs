@@ -32,6 +32,7 @@ use crate::{
         decoder::{CovenantDecodeError, CovenantTokenDecoder},
         encoder::CovenantTokenEncoder,
         error::CovenantError,
+        explain::CovenantExplanation,
         filters::Filter,
         output_set::OutputSet,
         token::{CovenantToken, CovenantTokenCollection},
@@ -88,11 +89,40 @@ impl Covenant {
             return Ok(outputs.len());
         }
 
+        let mut cx = self.create_context(input, block_height);
+        Self::execute_with_context(&mut cx, outputs)
+    }
+
+    /// Executes the covenant in the same way as `execute`, additionally recording the output set selected by each
+    /// filter. This is intended for debugging covenants that unexpectedly reject a spend.
+    pub fn explain(
+        &self,
+        block_height: u64,
+        input: &TransactionInput,
+        outputs: &[TransactionOutput],
+    ) -> CovenantExplanation {
+        if self.tokens.is_empty() {
+            return CovenantExplanation::new(Vec::new(), Ok(outputs.len()));
+        }
+
+        let mut cx = self.create_context(input, block_height);
+        cx.enable_trace();
+        let result = Self::execute_with_context(&mut cx, outputs);
+        CovenantExplanation::new(cx.take_trace(), result)
+    }
+
+    fn create_context<'a>(&self, input: &'a TransactionInput, block_height: u64) -> CovenantContext<'a> {
         let tokens = CovenantTokenCollection::from_iter(self.tokens.clone());
-        let mut cx = CovenantContext::new(tokens, input, block_height);
+        CovenantContext::new(tokens, input, block_height)
+    }
+
+    fn execute_with_context(
+        cx: &mut CovenantContext<'_>,
+        outputs: &[TransactionOutput],
+    ) -> Result<usize, CovenantError> {
         let root = cx.require_next_filter()?;
         let mut output_set = OutputSet::new(outputs);
-        root.filter(&mut cx, &mut output_set)?;
+        root.filter(cx, &mut output_set)?;
         if cx.has_more_tokens() {
             return Err(CovenantError::RemainingTokens);
         }
@@ -107,8 +137,7 @@ impl Covenant {
         self.tokens.push(token);
     }
 
-    #[cfg(test)]
-    pub(super) fn tokens(&self) -> &[CovenantToken] {
+    pub fn tokens(&self) -> &[CovenantToken] {
         &self.tokens
     }
 }
@@ -160,6 +189,8 @@ impl FromIterator<CovenantToken> for Covenant {
 
 #[cfg(test)]
 mod test {
+    use tari_script::script;
+
     use super::*;
     use crate::{
         consensus::ToConsensusBytes,
@@ -193,6 +224,41 @@ mod test {
         assert_eq!(num_matching_outputs, 3);
     }
 
+    #[test]
+    fn it_requires_exactly_one_output_back_to_the_same_script() {
+        let mut outputs = create_outputs(3, Default::default());
+        let input = create_input();
+        let covenant = covenant!(and(
+            fields_preserved(@fields(@field::script)),
+            output_count(@uint(1), @uint(1))
+        ));
+        // All outputs have the same script as the input
+        covenant.execute(0, &input, &outputs).unwrap_err();
+
+        outputs[0].script = script!(PushZero);
+        covenant.execute(0, &input, &outputs).unwrap_err();
+        outputs[1].script = script!(PushZero);
+        let num_matching_outputs = covenant.execute(0, &input, &outputs).unwrap();
+        assert_eq!(num_matching_outputs, 1);
+    }
+
+    #[test]
+    fn it_requires_all_or_any_outputs_to_match() {
+        let mut outputs = create_outputs(3, Default::default());
+        let input = create_input();
+        let all = covenant!(all(field_prefix_eq(@field::features_metadata, @bytes(vec![0x01]))));
+        let any = covenant!(any(field_eq(@field::features_maturity, @uint(42))));
+        all.execute(0, &input, &outputs).unwrap_err();
+        any.execute(0, &input, &outputs).unwrap_err();
+
+        outputs[1].features.maturity = 42;
+        outputs
+            .iter_mut()
+            .for_each(|output| output.features.metadata = vec![0x01, 0x02]);
+        assert_eq!(all.execute(0, &input, &outputs).unwrap(), 3);
+        assert_eq!(any.execute(0, &input, &outputs).unwrap(), 3);
+    }
+
     mod consensus_encoding {
         use super::*;
 
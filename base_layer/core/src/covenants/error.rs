@@ -40,4 +40,6 @@ pub enum CovenantError {
     RemainingTokens,
     #[error("Invalid argument for filter {filter}: {details}")]
     InvalidArgument { filter: &'static str, details: String },
+    #[error("Failed to parse covenant at position {position}: {reason}")]
+    ParseError { position: usize, reason: String },
 }
@@ -0,0 +1,231 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::covenants::{arguments::CovenantArg, error::CovenantError, text::fmt_arg};
+
+/// A single filter evaluation recorded by [Covenant::explain](crate::covenants::Covenant::explain).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainStep {
+    depth: usize,
+    filter: &'static str,
+    args: Vec<CovenantArg>,
+    input_indexes: Vec<usize>,
+    output_indexes: Option<Vec<usize>>,
+}
+
+impl ExplainStep {
+    pub(super) fn new(depth: usize, filter: &'static str, input_indexes: Vec<usize>) -> Self {
+        Self {
+            depth,
+            filter,
+            args: Vec::new(),
+            input_indexes,
+            output_indexes: None,
+        }
+    }
+
+    pub(super) fn push_arg(&mut self, arg: CovenantArg) {
+        self.args.push(arg);
+    }
+
+    pub(super) fn set_output_indexes(&mut self, output_indexes: Vec<usize>) {
+        self.output_indexes = Some(output_indexes);
+    }
+
+    /// The nesting depth of the filter. The root filter has a depth of 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The name of the filter, as used in the covenant text form
+    pub fn filter(&self) -> &'static str {
+        self.filter
+    }
+
+    /// The arguments that the filter consumed
+    pub fn args(&self) -> &[CovenantArg] {
+        &self.args
+    }
+
+    /// Indexes of the outputs that were selected before the filter was applied
+    pub fn input_indexes(&self) -> &[usize] {
+        &self.input_indexes
+    }
+
+    /// Indexes of the outputs that were selected after the filter was applied, or None if the filter failed
+    pub fn output_indexes(&self) -> Option<&[usize]> {
+        self.output_indexes.as_deref()
+    }
+
+    /// Indexes of the outputs that the filter removed from the selection. This is empty if the filter failed.
+    pub fn removed_indexes(&self) -> Vec<usize> {
+        match &self.output_indexes {
+            Some(output_indexes) => self
+                .input_indexes
+                .iter()
+                .filter(|idx| !output_indexes.contains(idx))
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Display for ExplainStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:indent$}{}(", "", self.filter, indent = self.depth * 2)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt_arg(arg, f)?;
+        }
+        write!(f, ") {:?} -> ", self.input_indexes)?;
+        match &self.output_indexes {
+            Some(output_indexes) => write!(f, "{:?}", output_indexes),
+            None => f.write_str("failed"),
+        }
+    }
+}
+
+/// The result of executing a covenant together with the output set selected by each filter along the way, in the
+/// order that the filters were started.
+#[derive(Debug)]
+pub struct CovenantExplanation {
+    steps: Vec<ExplainStep>,
+    result: Result<usize, CovenantError>,
+}
+
+impl CovenantExplanation {
+    pub(super) fn new(steps: Vec<ExplainStep>, result: Result<usize, CovenantError>) -> Self {
+        Self { steps, result }
+    }
+
+    pub fn steps(&self) -> &[ExplainStep] {
+        &self.steps
+    }
+
+    /// The result that `Covenant::execute` returns for the same inputs
+    pub fn result(&self) -> &Result<usize, CovenantError> {
+        &self.result
+    }
+
+    pub fn into_result(self) -> Result<usize, CovenantError> {
+        self.result
+    }
+}
+
+impl Display for CovenantExplanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        match &self.result {
+            Ok(num_matching) => write!(f, "Covenant passed with {} matching output(s)", num_matching),
+            Err(err) => write!(f, "Covenant failed: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::test::{create_input, create_outputs},
+    };
+
+    #[test]
+    fn it_records_the_output_set_after_each_filter() {
+        let mut outputs = create_outputs(5, Default::default());
+        outputs[1].features.maturity = 42;
+        outputs[3].features.maturity = 42;
+        outputs[4].features.maturity = 7;
+        let input = create_input();
+        let covenant = covenant!(and(
+            or(
+                field_eq(@field::features_maturity, @uint(42)),
+                field_eq(@field::features_maturity, @uint(7))
+            ),
+            not(field_eq(@field::features_maturity, @uint(7)))
+        ));
+
+        let explanation = covenant.explain(0, &input, &outputs);
+        let steps = explanation
+            .steps()
+            .iter()
+            .map(|step| (step.depth(), step.filter(), step.output_indexes().unwrap().to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(steps, vec![
+            (0, "and", vec![1, 3]),
+            (1, "or", vec![1, 3, 4]),
+            (2, "field_eq", vec![1, 3]),
+            (2, "field_eq", vec![4]),
+            (1, "not", vec![1, 3]),
+            (2, "field_eq", vec![4]),
+        ]);
+        assert_eq!(explanation.steps()[1].removed_indexes(), vec![0, 2]);
+        assert_eq!(explanation.steps()[4].input_indexes(), &[1, 3, 4]);
+        assert_eq!(explanation.steps()[2].args(), &[
+            CovenantArg::OutputField(crate::covenants::OutputField::FeaturesMaturity),
+            CovenantArg::Uint(42)
+        ]);
+        assert_eq!(*explanation.result().as_ref().unwrap(), 2);
+        assert_eq!(
+            covenant.execute(0, &input, &outputs).unwrap(),
+            explanation.into_result().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_explains_failures() {
+        let outputs = create_outputs(3, Default::default());
+        let input = create_input();
+        let covenant = covenant!(and(identity(), absolute_height(@uint(100))));
+        let explanation = covenant.explain(10, &input, &outputs);
+        assert_eq!(explanation.steps()[2].filter(), "absolute_height");
+        assert_eq!(explanation.steps()[2].removed_indexes(), vec![0, 1, 2]);
+        assert!(matches!(explanation.result(), Err(CovenantError::NoMatchingOutputs)));
+        assert_eq!(
+            explanation.to_string(),
+            "and() [0, 1, 2] -> []\n  identity() [0, 1, 2] -> [0, 1, 2]\n  absolute_height(@uint(100)) [0, 1, 2] -> \
+             []\nCovenant failed: Covenant did not match any outputs"
+        );
+
+        let covenant = covenant!(and(identity(), field_eq(@field::features_maturity)));
+        let explanation = covenant.explain(10, &input, &outputs);
+        assert_eq!(explanation.steps().len(), 3);
+        assert_eq!(explanation.steps()[0].output_indexes(), None);
+        assert_eq!(explanation.steps()[1].output_indexes(), Some(&[0, 1, 2][..]));
+        assert_eq!(explanation.steps()[2].output_indexes(), None);
+        assert!(explanation.steps()[2].removed_indexes().is_empty());
+        assert!(matches!(
+            explanation.result(),
+            Err(CovenantError::UnexpectedEndOfTokens)
+        ));
+    }
+}
@@ -238,22 +238,51 @@ impl OutputField {
     }
 }
 
-impl Display for OutputField {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl OutputField {
+    /// All output fields, in byte code order
+    pub const fn all() -> &'static [Self] {
+        #[allow(clippy::enum_glob_use)]
+        use OutputField::*;
+        &[
+            Commitment,
+            Script,
+            SenderOffsetPublicKey,
+            Covenant,
+            Features,
+            FeaturesOutputType,
+            FeaturesMaturity,
+            FeaturesMetadata,
+            FeaturesSideChainFeatures,
+        ]
+    }
+
+    /// The name of this field as used by the `covenant!` macro and the covenant text form (e.g. `@field::script`).
+    pub fn name(self) -> &'static str {
         #[allow(clippy::enum_glob_use)]
         use OutputField::*;
         match self {
-            Commitment => write!(f, "field::commitment"),
-            SenderOffsetPublicKey => write!(f, "field::sender_offset_public_key"),
-            Script => write!(f, "field::script"),
-            Covenant => write!(f, "field::covenant"),
-            Features => write!(f, "field::features"),
-            FeaturesOutputType => write!(f, "field::features_flags"),
-            FeaturesSideChainFeatures => write!(f, "field::features_sidechain_features"),
-            FeaturesMetadata => write!(f, "field::features_metadata"),
-            FeaturesMaturity => write!(f, "field::features_maturity"),
+            Commitment => "commitment",
+            Script => "script",
+            SenderOffsetPublicKey => "sender_offset_public_key",
+            Covenant => "covenant",
+            Features => "features",
+            FeaturesOutputType => "features_output_type",
+            FeaturesMaturity => "features_maturity",
+            FeaturesMetadata => "features_metadata",
+            FeaturesSideChainFeatures => "features_sidechain_features",
         }
     }
+
+    /// Returns the field with the given name, or None if no field has that name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|field| field.name() == name)
+    }
+}
+
+impl Display for OutputField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "field::{}", self.name())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -359,7 +388,7 @@ mod test {
             fn it_returns_true_if_eq() {
                 let output = create_outputs(1, UtxoTestParams {
                     features: OutputFeatures {
-                        sidechain_features: Some(Box::new(SideChainFeatures {})),
+                        sidechain_features: Some(Box::new(SideChainFeatures::default())),
                         ..Default::default()
                     },
                     script: script![Drop Nop],
@@ -378,7 +407,7 @@ mod test {
                     .is_eq(&output, &output.features.output_type)
                     .unwrap());
                 assert!(OutputField::FeaturesSideChainFeatures
-                    .is_eq(&output, &SideChainFeatures {})
+                    .is_eq(&output, &SideChainFeatures::default())
                     .unwrap());
                 assert!(OutputField::FeaturesSideChainFeatures
                     .is_eq(&output, output.features.sidechain_features.as_ref().unwrap())
@@ -395,7 +424,7 @@ mod test {
             fn it_returns_false_if_not_eq() {
                 let output = create_outputs(1, UtxoTestParams {
                     features: OutputFeatures {
-                        sidechain_features: Some(Box::new(SideChainFeatures {})),
+                        sidechain_features: Some(Box::new(SideChainFeatures::default())),
                         ..Default::default()
                     },
                     script: script![Drop Nop],
@@ -482,6 +511,15 @@ mod test {
                 assert!(f.to_string().starts_with("field::"));
             })
         }
+
+        #[test]
+        fn it_returns_the_field_for_each_name() {
+            OutputField::all().iter().for_each(|f| {
+                assert_eq!(OutputField::from_name(f.name()), Some(*f));
+                assert_eq!(f.to_string(), format!("field::{}", f.name()));
+            });
+            assert_eq!(OutputField::from_name("features_flags"), None);
+        }
     }
 
     mod output_fields {
@@ -0,0 +1,73 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::covenants::{context::CovenantContext, error::CovenantError, filters::Filter, output_set::OutputSet};
+
+/// Leaves the output set unchanged if the operand filter selects at least one output in it, otherwise selects no
+/// outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnyFilter;
+
+impl Filter for AnyFilter {
+    fn filter(&self, context: &mut CovenantContext<'_>, output_set: &mut OutputSet<'_>) -> Result<(), CovenantError> {
+        let filter = context.require_next_filter()?;
+        let mut output_set_copy = output_set.clone();
+        filter.filter(context, &mut output_set_copy)?;
+        if output_set_copy.is_empty() {
+            output_set.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::{filters::test::setup_filter_test, test::create_input},
+    };
+
+    #[test]
+    fn it_selects_all_outputs_if_one_output_matches() {
+        let covenant = covenant!(any(field_eq(@field::features_maturity, @uint(42))));
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |outputs| {
+            outputs[3].features.maturity = 42;
+        });
+        let mut output_set = OutputSet::new(&outputs);
+        AnyFilter.filter(&mut context, &mut output_set).unwrap();
+
+        assert_eq!(output_set.len(), 10);
+    }
+
+    #[test]
+    fn it_selects_no_outputs_if_no_output_matches() {
+        let covenant = covenant!(any(field_eq(@field::features_maturity, @uint(42))));
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |_| {});
+        let mut output_set = OutputSet::new(&outputs);
+        AnyFilter.filter(&mut context, &mut output_set).unwrap();
+
+        assert!(output_set.is_empty());
+    }
+}
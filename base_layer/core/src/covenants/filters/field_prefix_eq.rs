@@ -0,0 +1,89 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::covenants::{context::CovenantContext, error::CovenantError, filters::Filter, output_set::OutputSet};
+
+/// Selects the outputs for which the given byte field (e.g. `features_metadata`) starts with the bytes argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPrefixEqFilter;
+
+impl Filter for FieldPrefixEqFilter {
+    fn filter(&self, context: &mut CovenantContext<'_>, output_set: &mut OutputSet<'_>) -> Result<(), CovenantError> {
+        let field = context.next_arg()?.require_outputfield()?;
+        let prefix = context.next_arg()?.require_bytes()?;
+        output_set.retain(|output| match field.get_field_value_ref::<Vec<u8>>(output) {
+            Some(bytes) => Ok(bytes.starts_with(&prefix)),
+            None => Err(CovenantError::InvalidArgument {
+                filter: "field_prefix_eq",
+                details: format!("{} is not a byte field", field),
+            }),
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::{filters::test::setup_filter_test, test::create_input},
+    };
+
+    #[test]
+    fn it_filters_outputs_with_the_prefix() {
+        let covenant = covenant!(field_prefix_eq(@field::features_metadata, @bytes(vec![0xca, 0xfe])));
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |outputs| {
+            outputs[2].features.metadata = vec![0xca, 0xfe];
+            outputs[5].features.metadata = vec![0xca, 0xfe, 0xba, 0xbe];
+            outputs[7].features.metadata = vec![0xca];
+            outputs[8].features.metadata = vec![0xba, 0xbe, 0xca, 0xfe];
+        });
+        let mut output_set = OutputSet::new(&outputs);
+        FieldPrefixEqFilter.filter(&mut context, &mut output_set).unwrap();
+
+        assert_eq!(output_set.get_selected_indexes(), vec![2, 5]);
+    }
+
+    #[test]
+    fn it_selects_all_outputs_for_an_empty_prefix() {
+        let covenant = covenant!(field_prefix_eq(@field::features_metadata, @bytes(vec![])));
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |_| {});
+        let mut output_set = OutputSet::new(&outputs);
+        FieldPrefixEqFilter.filter(&mut context, &mut output_set).unwrap();
+
+        assert_eq!(output_set.len(), 10);
+    }
+
+    #[test]
+    fn it_errors_for_non_byte_fields() {
+        let covenant = covenant!(field_prefix_eq(@field::features_maturity, @bytes(vec![0])));
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |_| {});
+        let mut output_set = OutputSet::new(&outputs);
+        let err = FieldPrefixEqFilter.filter(&mut context, &mut output_set).unwrap_err();
+
+        assert!(matches!(err, CovenantError::InvalidArgument { .. }));
+    }
+}
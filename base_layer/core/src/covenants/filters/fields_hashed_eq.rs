@@ -60,7 +60,7 @@ mod test {
     fn it_filters_outputs_with_fields_that_hash_to_given_hash() {
         let features = OutputFeatures {
             maturity: 42,
-            sidechain_features: Some(Box::new(SideChainFeatures {})),
+            sidechain_features: Some(Box::new(SideChainFeatures::default())),
             ..Default::default()
         };
         let mut hasher = Challenge::new();
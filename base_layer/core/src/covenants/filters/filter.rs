@@ -24,13 +24,17 @@ use std::io;
 
 use super::{
     absolute_height::AbsoluteHeightFilter,
+    all::AllFilter,
     and::AndFilter,
+    any::AnyFilter,
     field_eq::FieldEqFilter,
+    field_prefix_eq::FieldPrefixEqFilter,
     fields_hashed_eq::FieldsHashedEqFilter,
     fields_preserved::FieldsPreservedFilter,
     identity::IdentityFilter,
     not::NotFilter,
     or::OrFilter,
+    output_count::OutputCountFilter,
     output_hash_eq::OutputHashEqFilter,
     xor::XorFilter,
 };
@@ -54,11 +58,15 @@ pub enum CovenantFilter {
     Or(OrFilter),
     Xor(XorFilter),
     Not(NotFilter),
+    All(AllFilter),
+    Any(AnyFilter),
     OutputHashEq(OutputHashEqFilter),
     FieldsPreserved(FieldsPreservedFilter),
     FieldEq(FieldEqFilter),
     FieldsHashedEq(FieldsHashedEqFilter),
     AbsoluteHeight(AbsoluteHeightFilter),
+    OutputCount(OutputCountFilter),
+    FieldPrefixEq(FieldPrefixEqFilter),
 }
 
 impl CovenantFilter {
@@ -82,11 +90,15 @@ impl CovenantFilter {
             Or(_) => FILTER_OR,
             Xor(_) => FILTER_XOR,
             Not(_) => FILTER_NOT,
+            All(_) => FILTER_ALL,
+            Any(_) => FILTER_ANY,
             OutputHashEq(_) => FILTER_OUTPUT_HASH_EQ,
             FieldsPreserved(_) => FILTER_FIELDS_PRESERVED,
             FieldEq(_) => FILTER_FIELD_EQ,
             FieldsHashedEq(_) => FILTER_FIELDS_HASHED_EQ,
             AbsoluteHeight(_) => FILTER_ABSOLUTE_HEIGHT,
+            OutputCount(_) => FILTER_OUTPUT_COUNT,
+            FieldPrefixEq(_) => FILTER_FIELD_PREFIX_EQ,
         }
     }
 
@@ -98,15 +110,63 @@ impl CovenantFilter {
             FILTER_OR => Ok(Self::or()),
             FILTER_XOR => Ok(Self::xor()),
             FILTER_NOT => Ok(Self::not()),
+            FILTER_ALL => Ok(Self::all()),
+            FILTER_ANY => Ok(Self::any()),
             FILTER_OUTPUT_HASH_EQ => Ok(Self::output_hash_eq()),
             FILTER_FIELDS_PRESERVED => Ok(Self::fields_preserved()),
             FILTER_FIELD_EQ => Ok(Self::field_eq()),
             FILTER_FIELDS_HASHED_EQ => Ok(Self::fields_hashed_eq()),
             FILTER_ABSOLUTE_HEIGHT => Ok(Self::absolute_height()),
+            FILTER_OUTPUT_COUNT => Ok(Self::output_count()),
+            FILTER_FIELD_PREFIX_EQ => Ok(Self::field_prefix_eq()),
             _ => Err(CovenantDecodeError::UnknownFilterByteCode { code }),
         }
     }
 
+    /// The name of this filter as used by the `covenant!` macro and the covenant text form
+    pub fn name(&self) -> &'static str {
+        #[allow(clippy::enum_glob_use)]
+        use CovenantFilter::*;
+
+        match self {
+            Identity(_) => "identity",
+            And(_) => "and",
+            Or(_) => "or",
+            Xor(_) => "xor",
+            Not(_) => "not",
+            All(_) => "all",
+            Any(_) => "any",
+            OutputHashEq(_) => "output_hash_eq",
+            FieldsPreserved(_) => "fields_preserved",
+            FieldEq(_) => "field_eq",
+            FieldsHashedEq(_) => "fields_hashed_eq",
+            AbsoluteHeight(_) => "absolute_height",
+            OutputCount(_) => "output_count",
+            FieldPrefixEq(_) => "field_prefix_eq",
+        }
+    }
+
+    /// Returns the filter with the given name, or None if no filter has that name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        byte_codes::ALL_FILTERS
+            .iter()
+            .filter_map(|code| Self::try_from_byte_code(*code).ok())
+            .find(|filter| filter.name() == name)
+    }
+
+    /// The number of tokens (filters or arguments) that immediately follow this filter in a covenant and that it
+    /// consumes when executed.
+    pub fn arity(&self) -> usize {
+        #[allow(clippy::enum_glob_use)]
+        use CovenantFilter::*;
+
+        match self {
+            Identity(_) => 0,
+            Not(_) | All(_) | Any(_) | OutputHashEq(_) | FieldsPreserved(_) | AbsoluteHeight(_) => 1,
+            And(_) | Or(_) | Xor(_) | FieldEq(_) | FieldsHashedEq(_) | OutputCount(_) | FieldPrefixEq(_) => 2,
+        }
+    }
+
     pub fn identity() -> Self {
         CovenantFilter::Identity(IdentityFilter)
     }
@@ -127,6 +187,14 @@ impl CovenantFilter {
         CovenantFilter::Not(NotFilter)
     }
 
+    pub fn all() -> Self {
+        CovenantFilter::All(AllFilter)
+    }
+
+    pub fn any() -> Self {
+        CovenantFilter::Any(AnyFilter)
+    }
+
     pub fn output_hash_eq() -> Self {
         CovenantFilter::OutputHashEq(OutputHashEqFilter)
     }
@@ -146,24 +214,39 @@ impl CovenantFilter {
     pub fn absolute_height() -> Self {
         CovenantFilter::AbsoluteHeight(AbsoluteHeightFilter)
     }
+
+    pub fn output_count() -> Self {
+        CovenantFilter::OutputCount(OutputCountFilter)
+    }
+
+    pub fn field_prefix_eq() -> Self {
+        CovenantFilter::FieldPrefixEq(FieldPrefixEqFilter)
+    }
 }
 
 impl Filter for CovenantFilter {
     fn filter(&self, context: &mut CovenantContext<'_>, output_set: &mut OutputSet<'_>) -> Result<(), CovenantError> {
         #[allow(clippy::enum_glob_use)]
         use CovenantFilter::*;
-        match self {
+        context.trace_enter(self, output_set);
+        let result = match self {
             Identity(identity) => identity.filter(context, output_set),
             And(and) => and.filter(context, output_set),
             Or(or) => or.filter(context, output_set),
             Xor(xor) => xor.filter(context, output_set),
             Not(not) => not.filter(context, output_set),
+            All(all) => all.filter(context, output_set),
+            Any(any) => any.filter(context, output_set),
             OutputHashEq(output_hash_eq) => output_hash_eq.filter(context, output_set),
             FieldsPreserved(fields_preserved) => fields_preserved.filter(context, output_set),
             FieldEq(fields_eq) => fields_eq.filter(context, output_set),
             FieldsHashedEq(fields_hashed_eq) => fields_hashed_eq.filter(context, output_set),
             AbsoluteHeight(abs_height) => abs_height.filter(context, output_set),
-        }
+            OutputCount(output_count) => output_count.filter(context, output_set),
+            FieldPrefixEq(field_prefix_eq) => field_prefix_eq.filter(context, output_set),
+        };
+        context.trace_exit(output_set, result.is_ok());
+        result
     }
 }
 
@@ -179,4 +262,13 @@ mod tests {
             assert_eq!(filter.as_byte_code(), *code);
         })
     }
+
+    #[test]
+    fn it_returns_filter_from_name() {
+        ALL_FILTERS.iter().for_each(|code| {
+            let filter = CovenantFilter::try_from_byte_code(*code).unwrap();
+            assert_eq!(CovenantFilter::from_name(filter.name()), Some(filter));
+        });
+        assert_eq!(CovenantFilter::from_name("nand"), None);
+    }
 }
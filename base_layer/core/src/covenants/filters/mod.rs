@@ -21,24 +21,32 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod absolute_height;
+mod all;
 mod and;
+mod any;
 mod field_eq;
+mod field_prefix_eq;
 mod fields_hashed_eq;
 mod fields_preserved;
 mod identity;
 mod not;
 mod or;
+mod output_count;
 mod output_hash_eq;
 mod xor;
 
 pub use absolute_height::AbsoluteHeightFilter;
+pub use all::AllFilter;
 pub use and::AndFilter;
+pub use any::AnyFilter;
 pub use field_eq::FieldEqFilter;
+pub use field_prefix_eq::FieldPrefixEqFilter;
 pub use fields_hashed_eq::FieldsHashedEqFilter;
 pub use fields_preserved::FieldsPreservedFilter;
 pub use identity::IdentityFilter;
 pub use not::NotFilter;
 pub use or::OrFilter;
+pub use output_count::OutputCountFilter;
 pub use output_hash_eq::OutputHashEqFilter;
 pub use xor::XorFilter;
 
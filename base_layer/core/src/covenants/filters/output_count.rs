@@ -0,0 +1,85 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::covenants::{context::CovenantContext, error::CovenantError, filters::Filter, output_set::OutputSet};
+
+/// Leaves the output set unchanged if the number of outputs in it is within the inclusive range given by the `min`
+/// and `max` uint arguments, otherwise selects no outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputCountFilter;
+
+impl Filter for OutputCountFilter {
+    fn filter(&self, context: &mut CovenantContext<'_>, output_set: &mut OutputSet<'_>) -> Result<(), CovenantError> {
+        let min = context.next_arg()?.require_uint()?;
+        let max = context.next_arg()?.require_uint()?;
+        if min > max {
+            return Err(CovenantError::InvalidArgument {
+                filter: "output_count",
+                details: format!("Minimum count {} is greater than maximum count {}", min, max),
+            });
+        }
+        let count = output_set.len() as u64;
+        if count < min || count > max {
+            output_set.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::{filters::test::setup_filter_test, test::create_input, Covenant},
+    };
+
+    fn filter_with(covenant: &Covenant) -> Result<usize, CovenantError> {
+        let input = create_input();
+        let (mut context, outputs) = setup_filter_test(covenant, &input, 0, |_| {});
+        let mut output_set = OutputSet::new(&outputs);
+        OutputCountFilter.filter(&mut context, &mut output_set)?;
+        Ok(output_set.len())
+    }
+
+    #[test]
+    fn it_selects_all_outputs_if_the_count_is_within_range() {
+        assert_eq!(filter_with(&covenant!(output_count(@uint(10), @uint(10)))).unwrap(), 10);
+        assert_eq!(
+            filter_with(&covenant!(output_count(@uint(0), @uint(u64::MAX)))).unwrap(),
+            10
+        );
+        assert_eq!(filter_with(&covenant!(output_count(@uint(1), @uint(10)))).unwrap(), 10);
+    }
+
+    #[test]
+    fn it_selects_no_outputs_if_the_count_is_out_of_range() {
+        assert_eq!(filter_with(&covenant!(output_count(@uint(11), @uint(20)))).unwrap(), 0);
+        assert_eq!(filter_with(&covenant!(output_count(@uint(1), @uint(9)))).unwrap(), 0);
+    }
+
+    #[test]
+    fn it_errors_if_min_is_greater_than_max() {
+        let err = filter_with(&covenant!(output_count(@uint(2), @uint(1)))).unwrap_err();
+        assert!(matches!(err, CovenantError::InvalidArgument { .. }));
+    }
+}
@@ -27,23 +27,27 @@
 //! <https://rfc.tari.com/RFC-0250_Covenants.html>
 
 mod arguments;
+mod builder;
 mod byte_codes;
 mod context;
 mod covenant;
 mod decoder;
 mod encoder;
 mod error;
+mod explain;
 mod fields;
 mod filters;
 mod output_set;
 mod serde;
+mod text;
 mod token;
 
+pub use arguments::CovenantArg;
+pub use builder::CovenantBuilder;
 pub use covenant::Covenant;
 pub use error::CovenantError;
-// Used in macro
-#[allow(unused_imports)]
-pub(crate) use fields::OutputField;
+pub use explain::{CovenantExplanation, ExplainStep};
+pub use fields::{OutputField, OutputFields};
 pub use token::CovenantToken;
 
 #[macro_use]
@@ -106,7 +106,7 @@ impl<'a> OutputSet<'a> {
             .map(|output| **output)
     }
 
-    #[cfg(test)]
+    /// Returns the indexes of the selected outputs in ascending order
     pub(super) fn get_selected_indexes(&self) -> Vec<usize> {
         self.0.iter().map(|idx| idx.index).collect()
     }
@@ -0,0 +1,461 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Text form of covenants.
+//!
+//! The text form follows the syntax of the `covenant!` macro, with literal values in place of Rust expressions. It is
+//! exactly what the `Display` implementation of [Covenant] produces, so printing a covenant and parsing it back always
+//! yields the same token stream (and therefore the same byte encoding). The grammar is:
+//!
+//! ```text
+//! covenant = [ token { "," token } [ "," ] ]
+//! token    = filter | arg
+//! filter   = name "(" covenant ")"
+//! arg      = "@field::" field
+//!          | "@fields(" [ "@field::" field { "," "@field::" field } ] ")"
+//!          | "@covenant_lit(" covenant ")"
+//!          | "@" arg_name "(" value ")"
+//! ```
+//!
+//! As with the macro, nesting is only a visual aid: tokens are emitted in the order they appear, so `and(identity(),
+//! identity())` and `and(), identity(), identity()` describe the same covenant. Whitespace is permitted between any
+//! two tokens. The literal arguments are:
+//!
+//! | Argument             | Value                                             |
+//! |----------------------|---------------------------------------------------|
+//! | `@hash(h)`           | 32-byte hash as hex                               |
+//! | `@public_key(k)`     | public key as hex                                 |
+//! | `@commitment(c)`     | commitment as hex                                 |
+//! | `@script(s)`         | the text form of a `TariScript`                   |
+//! | `@output_type(t)`    | `Standard`, `Coinbase` or `Burn`                  |
+//! | `@uint(n)`           | unsigned 64-bit integer                           |
+//! | `@bytes(b)`          | bytes as hex                                      |
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    iter::Peekable,
+    str::FromStr,
+};
+
+use tari_common_types::types::{Commitment, FixedHash, PublicKey};
+use tari_script::TariScript;
+use tari_utilities::hex::{from_hex, to_hex, Hex};
+
+use crate::{
+    covenants::{
+        arguments::CovenantArg,
+        covenant::Covenant,
+        error::CovenantError,
+        fields::{OutputField, OutputFields},
+        filters::CovenantFilter,
+        token::CovenantToken,
+    },
+    transactions::transaction_components::OutputType,
+};
+
+impl Display for Covenant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut tokens = self.tokens().iter().peekable();
+        let mut is_first = true;
+        while tokens.peek().is_some() {
+            if !is_first {
+                f.write_str(", ")?;
+            }
+            is_first = false;
+            fmt_next_token(&mut tokens, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the next token, followed by the operands of the token if it is a filter. Missing operands are not written,
+/// so that malformed covenants print as the token stream they contain.
+fn fmt_next_token<'a, I>(tokens: &mut Peekable<I>, f: &mut Formatter<'_>) -> fmt::Result
+where I: Iterator<Item = &'a CovenantToken> {
+    match tokens.next() {
+        Some(CovenantToken::Filter(filter)) => {
+            write!(f, "{}(", filter.name())?;
+            for i in 0..filter.arity() {
+                if tokens.peek().is_none() {
+                    break;
+                }
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                fmt_next_token(tokens, f)?;
+            }
+            f.write_str(")")
+        },
+        Some(CovenantToken::Arg(arg)) => fmt_arg(arg, f),
+        None => Ok(()),
+    }
+}
+
+/// Writes the text form of a single argument
+pub(super) fn fmt_arg(arg: &CovenantArg, f: &mut Formatter<'_>) -> fmt::Result {
+    #[allow(clippy::enum_glob_use)]
+    use CovenantArg::*;
+    match arg {
+        Hash(hash) => write!(f, "@hash({})", to_hex(&hash[..])),
+        PublicKey(public_key) => write!(f, "@public_key({})", public_key.to_hex()),
+        Commitment(commitment) => write!(f, "@commitment({})", commitment.to_hex()),
+        TariScript(script) => write!(f, "@script({})", script),
+        Covenant(covenant) => write!(f, "@covenant_lit({})", covenant),
+        OutputType(output_type) => write!(f, "@output_type({})", output_type),
+        Uint(v) => write!(f, "@uint({})", v),
+        OutputField(field) => write!(f, "@{}", field),
+        OutputFields(fields) => {
+            f.write_str("@fields(")?;
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "@{}", field)?;
+            }
+            f.write_str(")")
+        },
+        Bytes(bytes) => write!(f, "@bytes({})", to_hex(bytes)),
+    }
+}
+
+impl FromStr for Covenant {
+    type Err = CovenantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let tokens = parser.tokens(false)?;
+        Ok(tokens.into_iter().collect())
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, reason: &str) -> CovenantError {
+        Self::error_at(self.pos, reason)
+    }
+
+    fn error_at(position: usize, reason: &str) -> CovenantError {
+        CovenantError::ParseError {
+            position,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), CovenantError> {
+        self.skip_whitespace();
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", s)))
+        }
+    }
+
+    /// Reads a run of alphanumeric or '_' characters
+    fn name(&mut self) -> Result<(&'a str, usize), CovenantError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a filter or argument"));
+        }
+        self.pos += len;
+        Ok((&rest[..len], start))
+    }
+
+    /// Reads a comma-separated list of tokens. A nested list ends before the closing ')', a top-level list at the end
+    /// of the text.
+    fn tokens(&mut self, is_nested: bool) -> Result<Vec<CovenantToken>, CovenantError> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(')') if is_nested => return Ok(tokens),
+                None if is_nested => return Err(self.error("expected ')'")),
+                None => return Ok(tokens),
+                _ => {},
+            }
+            self.token(&mut tokens)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') if is_nested => return Ok(tokens),
+                None if !is_nested => return Ok(tokens),
+                _ if is_nested => return Err(self.error("expected ',' or ')'")),
+                _ => return Err(self.error("expected ','")),
+            }
+        }
+    }
+
+    fn token(&mut self, tokens: &mut Vec<CovenantToken>) -> Result<(), CovenantError> {
+        self.skip_whitespace();
+        if self.peek() == Some('@') {
+            self.pos += 1;
+            let arg = self.arg()?;
+            tokens.push(arg.into());
+            return Ok(());
+        }
+
+        let (name, start) = self.name()?;
+        let filter = CovenantFilter::from_name(name)
+            .ok_or_else(|| Self::error_at(start, &format!("unknown filter '{}'", name)))?;
+        tokens.push(filter.into());
+        self.expect("(")?;
+        tokens.extend(self.tokens(true)?);
+        self.expect(")")
+    }
+
+    fn field(&mut self) -> Result<OutputField, CovenantError> {
+        let (name, start) = self.name()?;
+        OutputField::from_name(name).ok_or_else(|| Self::error_at(start, &format!("unknown field '{}'", name)))
+    }
+
+    fn arg(&mut self) -> Result<CovenantArg, CovenantError> {
+        let (name, start) = self.name()?;
+        match name {
+            "field" => {
+                self.expect("::")?;
+                return Ok(CovenantArg::OutputField(self.field()?));
+            },
+            "fields" => {
+                self.expect("(")?;
+                let mut fields = OutputFields::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(')') {
+                        self.pos += 1;
+                        return Ok(CovenantArg::OutputFields(fields));
+                    }
+                    self.expect("@field::")?;
+                    fields.push(self.field()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(')') => {},
+                        _ => return Err(self.error("expected ',' or ')'")),
+                    }
+                }
+            },
+            "covenant_lit" => {
+                self.expect("(")?;
+                let covenant = self.tokens(true)?.into_iter().collect();
+                self.expect(")")?;
+                return Ok(CovenantArg::Covenant(covenant));
+            },
+            _ => {},
+        }
+
+        self.expect("(")?;
+        self.skip_whitespace();
+        let (value, value_start) = self.value()?;
+        let arg = match name {
+            "hash" => FixedHash::from_hex(value).map(CovenantArg::Hash).ok(),
+            "public_key" => PublicKey::from_hex(value).map(CovenantArg::PublicKey).ok(),
+            "commitment" => Commitment::from_hex(value).map(CovenantArg::Commitment).ok(),
+            "script" => {
+                Some(CovenantArg::TariScript(value.parse::<TariScript>().map_err(|err| {
+                    Self::error_at(value_start, &format!("invalid script: {}", err))
+                })?))
+            },
+            "output_type" => OutputType::all()
+                .iter()
+                .find(|output_type| output_type.to_string() == value)
+                .map(|output_type| CovenantArg::OutputType(*output_type)),
+            "uint" => value.parse().map(CovenantArg::Uint).ok(),
+            "bytes" => from_hex(value).map(CovenantArg::Bytes).ok(),
+            _ => return Err(Self::error_at(start, &format!("unknown argument '@{}'", name))),
+        };
+        let arg =
+            arg.ok_or_else(|| Self::error_at(value_start, &format!("invalid value '{}' for '@{}'", value, name)))?;
+        self.expect(")")?;
+        Ok(arg)
+    }
+
+    /// Reads everything up to the ')' that closes the current argument, allowing for balanced parentheses within the
+    /// value (e.g. in scripts). The value is returned without surrounding whitespace.
+    fn value(&mut self) -> Result<(&'a str, usize), CovenantError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let mut depth = 0usize;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => {
+                    self.pos += i;
+                    return Ok((rest[..i].trim_end(), start));
+                },
+                ')' => depth -= 1,
+                _ => {},
+            }
+        }
+        self.pos += rest.len();
+        Err(self.error("expected ')'"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+    use crate::covenant;
+
+    fn assert_round_trips(covenant: &Covenant) {
+        let text = covenant.to_string();
+        let parsed = text.parse::<Covenant>().unwrap();
+        assert_eq!(parsed, *covenant, "text was '{}'", text);
+        let decoded = Covenant::from_bytes(&parsed.to_bytes()).unwrap();
+        assert_eq!(decoded, *covenant);
+        assert_eq!(decoded.to_string(), text);
+    }
+
+    #[test]
+    fn it_displays_the_macro_syntax() {
+        let covenant = covenant!(and(
+            identity(),
+            or(
+                field_eq(@field::features_maturity, @uint(42)),
+                fields_preserved(@fields(@field::commitment, @field::sender_offset_public_key))
+            )
+        ));
+        assert_eq!(
+            covenant.to_string(),
+            "and(identity(), or(field_eq(@field::features_maturity, @uint(42)), \
+             fields_preserved(@fields(@field::commitment, @field::sender_offset_public_key))))"
+        );
+        assert_eq!(covenant!().to_string(), "");
+    }
+
+    #[test]
+    fn it_round_trips_all_arguments() {
+        let hash = FixedHash::from_hex("53563b674ba8e5166adb57afa8355bcf2ee759941eef8f8959b802367c2558bd").unwrap();
+        let public_key =
+            PublicKey::from_hex("b0c1f788f137ba0cdc0b61e89ee43b80ebf5cca4136d3229561bf11eba347849").unwrap();
+        let script = script!(HashSha256 PushHash(Box::new(*hash)) Equal IfThen PushPubKey(Box::new(public_key.clone())) Else CheckHeightVerify(100) EndIf);
+        let covenant = covenant!(and(
+            xor(
+                output_hash_eq(@hash(hash)),
+                not(field_eq(@field::sender_offset_public_key, @public_key(public_key)))
+            ),
+            and(
+                or(
+                    field_eq(@field::commitment, @commitment(Commitment::default())),
+                    field_eq(@field::script, @script(script))
+                ),
+                and(
+                    or(
+                        field_eq(@field::covenant, @covenant_lit(and(absolute_height(@uint(10)), identity()))),
+                        field_eq(@field::features_output_type, @output_type(Coinbase))
+                    ),
+                    or(
+                        field_eq(@field::features_metadata, @bytes(vec![0xca, 0xfe])),
+                        fields_hashed_eq(@fields(@field::features, @field::features_maturity), @hash(hash))
+                    )
+                )
+            )
+        ));
+        assert_round_trips(&covenant);
+    }
+
+    #[test]
+    fn it_round_trips_malformed_token_streams() {
+        assert_round_trips(&covenant!(and(identity())));
+        assert_round_trips(&covenant!(field_eq(@uint(1))));
+        assert_round_trips(&covenant!(field_eq(@field::covenant, @covenant_lit())));
+        let streams = vec![
+            vec![CovenantToken::identity(), CovenantToken::identity()],
+            vec![CovenantToken::uint(1), CovenantToken::bytes(vec![])],
+            vec![CovenantToken::fields_preserved(), CovenantToken::fields(vec![])],
+            vec![
+                CovenantToken::field_eq(),
+                CovenantToken::script(TariScript::new(vec![])),
+            ],
+        ];
+        for tokens in streams {
+            assert_round_trips(&tokens.into_iter().collect());
+        }
+    }
+
+    #[test]
+    fn it_parses_flexible_whitespace_and_flat_tokens() {
+        let expected = covenant!(and(identity(), field_eq(@field::features_maturity, @uint(42))));
+        let parsed = " and ( identity( ) ,field_eq(\n@field::features_maturity , @uint( 42 ) ,), ) "
+            .parse::<Covenant>()
+            .unwrap();
+        assert_eq!(parsed, expected);
+        let parsed = "and(), identity(), field_eq(), @field::features_maturity, @uint(42)"
+            .parse::<Covenant>()
+            .unwrap();
+        assert_eq!(parsed, expected);
+        assert_eq!("".parse::<Covenant>().unwrap(), covenant!());
+    }
+
+    #[test]
+    fn it_rejects_invalid_text() {
+        let cases: &[(&str, usize)] = &[
+            ("nand(identity())", 0),
+            ("and(identity()", 14),
+            ("and(identity() identity())", 15),
+            ("identity() identity()", 11),
+            ("field_eq(@field::flags, @uint(1))", 17),
+            ("field_eq(@field::features_maturity, @uint(-1))", 42),
+            ("absolute_height(@height(1))", 17),
+            ("output_hash_eq(@hash(abcd))", 21),
+            ("field_eq(@field::script, @script(PushInt(1) Dup Foo))", 33),
+            ("field_eq(@field::features_output_type, @output_type(Standard)", 61),
+            ("@fields(@field::commitment @field::script)", 27),
+            ("identity(", 9),
+            (",", 0),
+        ];
+        for (text, position) in cases {
+            match text.parse::<Covenant>() {
+                Err(CovenantError::ParseError { position: p, .. }) => assert_eq!(p, *position, "for '{}'", text),
+                res => panic!("expected a parse error for '{}' but got {:?}", text, res),
+            }
+        }
+    }
+}
@@ -32,14 +32,18 @@ use crate::{
         fields::OutputField,
         filters::{
             AbsoluteHeightFilter,
+            AllFilter,
             AndFilter,
+            AnyFilter,
             CovenantFilter,
             FieldEqFilter,
+            FieldPrefixEqFilter,
             FieldsHashedEqFilter,
             FieldsPreservedFilter,
             IdentityFilter,
             NotFilter,
             OrFilter,
+            OutputCountFilter,
             OutputHashEqFilter,
             XorFilter,
         },
@@ -122,6 +126,16 @@ impl CovenantToken {
         CovenantFilter::Not(NotFilter).into()
     }
 
+    #[allow(dead_code)]
+    pub fn all() -> Self {
+        CovenantFilter::All(AllFilter).into()
+    }
+
+    #[allow(dead_code)]
+    pub fn any() -> Self {
+        CovenantFilter::Any(AnyFilter).into()
+    }
+
     #[allow(dead_code)]
     pub fn output_hash_eq() -> Self {
         CovenantFilter::OutputHashEq(OutputHashEqFilter).into()
@@ -147,6 +161,16 @@ impl CovenantToken {
         CovenantFilter::AbsoluteHeight(AbsoluteHeightFilter).into()
     }
 
+    #[allow(dead_code)]
+    pub fn output_count() -> Self {
+        CovenantFilter::OutputCount(OutputCountFilter).into()
+    }
+
+    #[allow(dead_code)]
+    pub fn field_prefix_eq() -> Self {
+        CovenantFilter::FieldPrefixEq(FieldPrefixEqFilter).into()
+    }
+
     #[allow(dead_code)]
     pub fn hash(hash: FixedHash) -> Self {
         CovenantArg::Hash(hash).into()
@@ -32,6 +32,10 @@ pub mod covenants;
 pub mod iterators;
 pub mod proof_of_work;
 #[cfg(feature = "base_node")]
+pub mod reorg_replay;
+#[cfg(feature = "base_node")]
+pub mod simulation;
+#[cfg(feature = "base_node")]
 pub mod validation;
 
 #[cfg(any(test, feature = "base_node"))]
@@ -23,7 +23,7 @@
 use std::sync::Arc;
 
 use log::*;
-use tari_common_types::types::{PrivateKey, Signature};
+use tari_common_types::types::{HashOutput, PrivateKey, Signature};
 use tari_utilities::hex::Hex;
 
 use crate::{
@@ -31,8 +31,10 @@ use crate::{
     consensus::ConsensusManager,
     mempool::{
         error::MempoolError,
+        metrics,
+        priority::order_by_dependency,
         reorg_pool::ReorgPool,
-        unconfirmed_pool::UnconfirmedPool,
+        unconfirmed_pool::{InsertionResult, UnconfirmedPool},
         FeePerGramStat,
         MempoolConfig,
         StateResponse,
@@ -86,15 +88,11 @@ impl MempoolStorage {
                     target: LOG_TARGET,
                     "Transaction {} is VALID, inserting in unconfirmed pool", tx_id
                 );
-                let weight = self.get_transaction_weighting(0);
-                self.unconfirmed_pool.insert(tx, None, &weight);
-                TxStorageResponse::UnconfirmedPool
+                self.insert_into_unconfirmed_pool(tx, None)
             },
             Err(ValidationError::UnknownInputs(dependent_outputs)) => {
                 if self.unconfirmed_pool.contains_all_outputs(&dependent_outputs) {
-                    let weight = self.get_transaction_weighting(0);
-                    self.unconfirmed_pool.insert(tx, Some(dependent_outputs), &weight);
-                    TxStorageResponse::UnconfirmedPool
+                    self.insert_into_unconfirmed_pool(tx, Some(dependent_outputs))
                 } else {
                     warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
                     TxStorageResponse::NotStoredOrphan
@@ -126,6 +124,27 @@ impl MempoolStorage {
         }
     }
 
+    fn insert_into_unconfirmed_pool(
+        &mut self,
+        tx: Arc<Transaction>,
+        dependent_outputs: Option<Vec<HashOutput>>,
+    ) -> TxStorageResponse {
+        let weight = self.get_transaction_weighting(0);
+        match self.unconfirmed_pool.insert(tx, dependent_outputs, &weight) {
+            InsertionResult::Inserted { replaced } => {
+                if !replaced.is_empty() {
+                    metrics::replaced_transactions().inc_by(replaced.len() as u64);
+                }
+                TxStorageResponse::UnconfirmedPool
+            },
+            InsertionResult::NotInserted => TxStorageResponse::UnconfirmedPool,
+            InsertionResult::ReplacementRejected(reason) => {
+                warn!(target: LOG_TARGET, "Replacement transaction rejected: {}", reason);
+                TxStorageResponse::NotStoredReplacementRejected
+            },
+        }
+    }
+
     fn get_transaction_weighting(&self, height: u64) -> TransactionWeight {
         *self.rules.consensus_constants(height).transaction_weight()
     }
@@ -192,13 +211,15 @@ impl MempoolStorage {
         // Clear out all transactions from the unconfirmed pool and re-submit them to the unconfirmed mempool for
         // validation. This is important as invalid transactions that have not been mined yet may remain in the mempool
         // after a reorg.
-        let removed_txs = self.unconfirmed_pool.drain_all_mempool_transactions();
-        self.insert_txs(removed_txs);
+        let mut removed_txs = self.unconfirmed_pool.drain_all_mempool_transactions();
         // Remove re-orged transactions from reorg  pool and re-submit them to the unconfirmed mempool
-        let removed_txs = self
-            .reorg_pool
-            .remove_reorged_txs_and_discard_double_spends(removed_blocks, new_blocks);
-        self.insert_txs(removed_txs);
+        removed_txs.extend(
+            self.reorg_pool
+                .remove_reorged_txs_and_discard_double_spends(removed_blocks, new_blocks),
+        );
+        // Unconfirmed children may spend the outputs of re-orged parents (or of each other), parents have to be
+        // resubmitted first so that the packages are rebuilt rather than rejected as orphans.
+        self.insert_txs(order_by_dependency(removed_txs));
         // Update the Mempool based on the received set of new blocks.
         for block in new_blocks {
             self.process_published_block(block)?;
@@ -302,6 +323,7 @@ impl MempoolStorage {
             unconfirmed_txs: self.unconfirmed_pool.len() as u64,
             reorg_txs: self.reorg_pool.len() as u64,
             unconfirmed_weight: self.unconfirmed_pool.calculate_weight(&weighting),
+            replaced_txs: self.unconfirmed_pool.replaced_count(),
         }
     }
 
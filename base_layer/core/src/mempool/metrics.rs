@@ -75,3 +75,15 @@ pub fn reorg_pool_size() -> IntGauge {
 
     METER.clone()
 }
+
+pub fn replaced_transactions() -> IntCounter {
+    static METER: Lazy<IntCounter> = Lazy::new(|| {
+        tari_metrics::register_int_counter(
+            "base_node::mempool::replaced_transactions",
+            "Number of unconfirmed transactions evicted by replace-by-fee transactions",
+        )
+        .unwrap()
+    });
+
+    METER.clone()
+}
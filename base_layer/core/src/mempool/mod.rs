@@ -84,14 +84,15 @@ pub struct StatsResponse {
     pub unconfirmed_txs: u64,
     pub reorg_txs: u64,
     pub unconfirmed_weight: u64,
+    pub replaced_txs: u64,
 }
 
 impl Display for StatsResponse {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         write!(
             fmt,
-            "Mempool stats: Unconfirmed: {}, In Reorg Pool: {}, Total Weight: {}g",
-            self.unconfirmed_txs, self.reorg_txs, self.unconfirmed_weight
+            "Mempool stats: Unconfirmed: {}, In Reorg Pool: {}, Total Weight: {}g, Replaced: {}",
+            self.unconfirmed_txs, self.reorg_txs, self.unconfirmed_weight, self.replaced_txs
         )
     }
 }
@@ -110,6 +111,7 @@ pub enum TxStorageResponse {
     NotStoredTimeLocked,
     NotStoredAlreadySpent,
     NotStoredConsensus,
+    NotStoredReplacementRejected,
     NotStored,
 }
 
@@ -128,6 +130,7 @@ impl Display for TxStorageResponse {
             TxStorageResponse::NotStoredTimeLocked => "Not stored time locked transaction",
             TxStorageResponse::NotStoredAlreadySpent => "Not stored output already spent",
             TxStorageResponse::NotStoredConsensus => "Not stored due to consensus rule",
+            TxStorageResponse::NotStoredReplacementRejected => "Not stored due to replace-by-fee policy",
             TxStorageResponse::NotStored => "Not stored",
         };
         fmt.write_str(storage)
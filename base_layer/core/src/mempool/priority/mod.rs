@@ -20,5 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod package_priority;
+pub use package_priority::{order_by_dependency, PackagePriority};
+
 mod prioritized_transaction;
 pub use prioritized_transaction::{FeePriority, PrioritizedTransaction};
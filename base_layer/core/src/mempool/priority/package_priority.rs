@@ -0,0 +1,118 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, sync::Arc};
+
+use tari_common_types::types::HashOutput;
+
+use crate::transactions::{tari_amount::MicroTari, transaction_components::Transaction};
+
+/// The priority of a package of related unconfirmed transactions, e.g. a child together with the parents whose outputs
+/// it spends. Packages are ranked by their combined fee per gram, so that a child paying a high fee can lift the
+/// priority of a low fee parent (child-pays-for-parent) and a cheap parent drags down the priority of its children.
+/// The fee per gram is kept to three decimal places, in line with [FeePriority](super::FeePriority).
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct PackagePriority(u64);
+
+impl PackagePriority {
+    pub fn new(total_fee: MicroTari, total_weight: u64) -> Self {
+        Self(total_fee.as_u64().saturating_mul(1000) / total_weight.max(1))
+    }
+}
+
+/// Orders transactions so that every transaction comes after the transactions in the set whose outputs it spends,
+/// otherwise a child would be rejected as an orphan when the set is (re)submitted to the mempool. The relative order of
+/// unrelated transactions is preserved.
+pub fn order_by_dependency(transactions: Vec<Arc<Transaction>>) -> Vec<Arc<Transaction>> {
+    let producers = transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(i, tx)| tx.body.outputs().iter().map(move |output| (output.hash(), i)))
+        .collect::<HashMap<HashOutput, usize>>();
+    let parents = transactions
+        .iter()
+        .map(|tx| {
+            tx.body
+                .inputs()
+                .iter()
+                .filter_map(|input| producers.get(&input.output_hash()).copied())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut placed = vec![false; transactions.len()];
+    let mut order = Vec::with_capacity(transactions.len());
+    for root in 0..transactions.len() {
+        // Depth-first, parents before children. The stack holds the index of each transaction together with the
+        // position of the next parent to visit.
+        let mut stack = vec![(root, 0usize)];
+        while let Some((index, next_parent)) = stack.pop() {
+            if placed[index] {
+                continue;
+            }
+            match parents[index].get(next_parent) {
+                Some(parent) => {
+                    let parent = *parent;
+                    stack.push((index, next_parent + 1));
+                    // A transaction cannot (validly) depend on itself, but guard against cycles all the same
+                    if !placed[parent] && stack.iter().all(|(i, _)| *i != parent) {
+                        stack.push((parent, 0));
+                    }
+                },
+                None => {
+                    placed[index] = true;
+                    order.push(index);
+                },
+            }
+        }
+    }
+
+    let mut transactions = transactions.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .filter_map(|index| transactions[index].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{mempool::test_utils::spend_output, tx};
+
+    #[test]
+    fn it_orders_parents_before_children() {
+        let tx1 = tx!(MicroTari(10_000), fee: MicroTari(5), inputs: 1, outputs: 2).0;
+        let mut tx2 = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+        let mut tx3 = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 2, outputs: 1).0;
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0);
+        // tx3 spends outputs of tx1 and tx2, tx2 spends an output of tx1
+        tx2.body.inputs_mut()[0] = spend_output(&tx1, 0);
+        tx3.body.inputs_mut()[0] = spend_output(&tx2, 0);
+        tx3.body.inputs_mut()[1] = spend_output(&tx1, 1);
+        let tx1 = Arc::new(tx1);
+        let tx2 = Arc::new(tx2);
+        let tx3 = Arc::new(tx3);
+
+        let ordered = order_by_dependency(vec![tx3.clone(), tx4.clone(), tx2.clone(), tx1.clone()]);
+        assert_eq!(ordered, vec![tx1, tx2, tx3, tx4]);
+    }
+}
@@ -32,6 +32,7 @@ impl TryFrom<ProtoStatsResponse> for StatsResponse {
             unconfirmed_txs: stats.unconfirmed_txs,
             reorg_txs: stats.reorg_txs,
             unconfirmed_weight: stats.unconfirmed_weight,
+            replaced_txs: stats.replaced_txs,
         })
     }
 }
@@ -42,6 +43,7 @@ impl From<StatsResponse> for ProtoStatsResponse {
             unconfirmed_txs: stats.unconfirmed_txs,
             reorg_txs: stats.reorg_txs,
             unconfirmed_weight: stats.unconfirmed_weight,
+            replaced_txs: stats.replaced_txs,
         }
     }
 }
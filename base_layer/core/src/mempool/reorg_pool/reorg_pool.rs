@@ -219,7 +219,8 @@ impl ReorgPool {
 
     /// Remove double-spends from the ReorgPool. These transactions were orphaned by the provided published
     /// block. Check if any of the transactions in the ReorgPool has inputs that was spent by the provided
-    /// published block.
+    /// published block. Transactions spending the outputs of a discarded transaction can never be valid again and are
+    /// discarded along with it.
     fn discard_double_spends(&mut self, published_block: &Block) {
         let mut to_remove = Vec::new();
         for (id, tx) in &self.tx_by_key {
@@ -230,9 +231,17 @@ impl ReorgPool {
             }
         }
 
-        for id in to_remove {
-            self.remove(id);
-            trace!(target: LOG_TARGET, "Removed double spend tx {} from reorg pool", id);
+        while let Some(id) = to_remove.pop() {
+            if let Some(tx) = self.remove(id) {
+                trace!(target: LOG_TARGET, "Removed double spend tx {} from reorg pool", id);
+                let outputs = tx.body.outputs().iter().map(|o| o.hash()).collect::<HashSet<_>>();
+                to_remove.extend(
+                    self.tx_by_key
+                        .iter()
+                        .filter(|(_, tx)| tx.body.inputs().iter().any(|i| outputs.contains(&i.output_hash())))
+                        .map(|(id, _)| *id),
+                );
+            }
         }
     }
 
@@ -347,6 +356,7 @@ mod test {
     use super::*;
     use crate::{
         consensus::ConsensusManagerBuilder,
+        mempool::test_utils::spend_output,
         test_helpers::create_orphan_block,
         transactions::tari_amount::MicroTari,
         tx,
@@ -436,4 +446,31 @@ mod test {
         assert!(reorg_pool.has_tx_with_excess_sig(&tx5.body.kernels()[0].excess_sig));
         assert!(reorg_pool.has_tx_with_excess_sig(&tx6.body.kernels()[0].excess_sig));
     }
+
+    #[test]
+    fn discard_descendants_of_double_spends() {
+        let network = Network::LocalNet;
+        let consensus = ConsensusManagerBuilder::new(network).build();
+        let tx1 = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(10), inputs: 2, outputs: 1).0);
+        let mut tx2 = tx!(MicroTari(5_000), fee: MicroTari(10), inputs: 1, outputs: 1).0;
+        tx2.body.inputs_mut()[0] = spend_output(&tx1, 0);
+        let tx2 = Arc::new(tx2);
+        let tx3 = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(10), inputs: 2, outputs: 1).0);
+        let mut double_spend = tx!(MicroTari(10_000), fee: MicroTari(10), inputs: 2, outputs: 1).0;
+        double_spend.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+
+        let mut reorg_pool = ReorgPool::new(ReorgPoolConfig { expiry_height: 10 });
+        reorg_pool.insert_all(1, vec![tx1.clone(), tx3.clone()]);
+        reorg_pool.insert_all(2, vec![tx2.clone()]);
+
+        let new_blocks = &[create_orphan_block(3000, vec![double_spend], &consensus).into()];
+        let removed_txs = reorg_pool.remove_reorged_txs_and_discard_double_spends(&[], new_blocks);
+        assert!(removed_txs.is_empty());
+
+        // tx2 spends the output of the double-spent tx1, so it is discarded as well
+        assert_eq!(reorg_pool.len(), 1);
+        assert!(!reorg_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert!(!reorg_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert!(reorg_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+    }
 }
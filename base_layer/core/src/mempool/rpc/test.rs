@@ -52,6 +52,7 @@ mod get_stats {
 
             reorg_txs: 5,
             unconfirmed_weight: 6,
+            replaced_txs: 7,
         };
         mempool.set_get_stats_response(expected_stats.clone()).await;
 
@@ -121,6 +121,7 @@ mod test {
             unconfirmed_txs: 3,
             reorg_txs: 4,
             unconfirmed_weight: 1000,
+            replaced_txs: 2,
         }
     }
 
@@ -61,6 +61,7 @@ impl Default for MempoolMockState {
                 unconfirmed_txs: 0,
                 reorg_txs: 0,
                 unconfirmed_weight: 0,
+                replaced_txs: 0,
             })),
             get_state: Arc::new(Mutex::new(StateResponse {
                 unconfirmed_pool: vec![],
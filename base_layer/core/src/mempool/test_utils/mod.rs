@@ -21,3 +21,18 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod mock;
+
+use tari_common_types::types::ComSignature;
+use tari_script::ExecutionStack;
+
+use crate::transactions::transaction_components::{Transaction, TransactionInput};
+
+/// Returns an input that spends the output of `parent` at `output_index`. Only the output hash is set, which is all
+/// the mempool needs to link a transaction to its parent.
+pub fn spend_output(parent: &Transaction, output_index: usize) -> TransactionInput {
+    TransactionInput::new_with_output_hash(
+        parent.body.outputs()[output_index].hash(),
+        ExecutionStack::default(),
+        ComSignature::default(),
+    )
+}
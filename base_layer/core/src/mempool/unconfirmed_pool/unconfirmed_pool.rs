@@ -22,6 +22,8 @@
 
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    fmt::{Display, Formatter},
     hash::Hash,
     sync::Arc,
 };
@@ -34,7 +36,7 @@ use tari_utilities::hex::Hex;
 use crate::{
     blocks::Block,
     mempool::{
-        priority::{FeePriority, PrioritizedTransaction},
+        priority::{FeePriority, PackagePriority, PrioritizedTransaction},
         unconfirmed_pool::UnconfirmedPoolError,
         FeePerGramStat,
     },
@@ -53,6 +55,16 @@ pub struct UnconfirmedPoolConfig {
     /// The maximum number of transactions that can be skipped when compiling a set of highest priority transactions,
     /// skipping over large transactions are performed in an attempt to fit more transactions into the remaining space.
     pub weight_tx_skip_count: usize,
+    /// Allow a transaction that spends the same inputs as transactions already in the pool to replace them if it pays
+    /// a higher fee. When disabled, conflicting transactions are kept side by side and only one of them will be
+    /// selected for a block.
+    pub replace_by_fee_enabled: bool,
+    /// The amount (in µT) by which the fee per gram of a replacement transaction must exceed the fee per gram of every
+    /// transaction it conflicts with. The total fee must also grow by at least this amount per gram of the
+    /// replacement, so that replacements pay for their own relay.
+    pub replace_by_fee_min_increment: u64,
+    /// The maximum number of descendant transactions that may be evicted along with the transactions being replaced.
+    pub replace_by_fee_max_evictions: usize,
 }
 
 impl Default for UnconfirmedPoolConfig {
@@ -60,6 +72,54 @@ impl Default for UnconfirmedPoolConfig {
         Self {
             storage_capacity: 40_000,
             weight_tx_skip_count: 20,
+            replace_by_fee_enabled: true,
+            replace_by_fee_min_increment: 1,
+            replace_by_fee_max_evictions: 100,
+        }
+    }
+}
+
+/// The outcome of inserting a transaction into the UnconfirmedPool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertionResult {
+    /// The transaction was added to the pool. `replaced` contains every transaction that was evicted to make way for
+    /// it, i.e. the transactions it double-spends and their descendants.
+    Inserted { replaced: Vec<Arc<Transaction>> },
+    /// The transaction is already in the pool, or the pool is full and the transaction has a lower priority than
+    /// every stored transaction.
+    NotInserted,
+    /// The transaction double-spends transactions in the pool and does not satisfy the replace-by-fee policy
+    ReplacementRejected(ReplacementRejection),
+}
+
+/// The reason a replacement transaction was refused by the UnconfirmedPool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplacementRejection {
+    /// The fee per gram does not exceed that of a conflicting transaction by the minimum increment
+    FeePerGramTooLow { required: MicroTari, actual: MicroTari },
+    /// The total fee does not cover the fees of every evicted transaction plus the minimum increment
+    TotalFeeTooLow { required: MicroTari, actual: MicroTari },
+    /// Replacing the conflicting transactions would evict more descendants than allowed
+    TooManyEvictions { max: usize },
+    /// The transaction spends an output of a transaction that it would evict
+    SpendsEvictedOutput,
+}
+
+impl Display for ReplacementRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplacementRejection::FeePerGramTooLow { required, actual } => {
+                write!(f, "fee per gram of {} is below the required {}", actual, required)
+            },
+            ReplacementRejection::TotalFeeTooLow { required, actual } => {
+                write!(f, "total fee of {} is below the required {}", actual, required)
+            },
+            ReplacementRejection::TooManyEvictions { max } => {
+                write!(f, "more than {} descendant transactions would be evicted", max)
+            },
+            ReplacementRejection::SpendsEvictedOutput => {
+                f.write_str("transaction spends an output of a transaction it replaces")
+            },
         }
     }
 }
@@ -78,8 +138,12 @@ pub struct UnconfirmedPool {
     tx_by_key: HashMap<TransactionKey, PrioritizedTransaction>,
     txs_by_signature: HashMap<PrivateKey, Vec<TransactionKey>>,
     tx_by_priority: BTreeMap<FeePriority, TransactionKey>,
+    tx_by_package_priority: BTreeMap<(PackagePriority, FeePriority), TransactionKey>,
+    package_priority_by_key: HashMap<TransactionKey, PackagePriority>,
     txs_by_output: HashMap<HashOutput, Vec<TransactionKey>>,
     txs_by_unique_id: HashMap<[u8; 32], Vec<TransactionKey>>,
+    txs_by_input: HashMap<HashOutput, Vec<TransactionKey>>,
+    replaced_count: u64,
 }
 
 // helper class to reduce type complexity
@@ -97,42 +161,77 @@ impl UnconfirmedPool {
             tx_by_key: HashMap::new(),
             txs_by_signature: HashMap::new(),
             tx_by_priority: BTreeMap::new(),
+            tx_by_package_priority: BTreeMap::new(),
+            package_priority_by_key: HashMap::new(),
             txs_by_output: HashMap::new(),
             txs_by_unique_id: HashMap::new(),
+            txs_by_input: HashMap::new(),
+            replaced_count: 0,
         }
     }
 
     /// Insert a new transaction into the UnconfirmedPool. Low priority transactions will be removed to make space for
-    /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity is
-    /// reached and the new transaction has a higher priority than the currently stored lowest priority transaction.
+    /// higher priority transactions. When the maximum capacity is reached, the transaction that would be selected for
+    /// a block last is removed if the package of the new transaction has a higher priority than it.
+    /// If replace-by-fee is enabled, a transaction that double-spends transactions already in the pool replaces them
+    /// (and their descendants) when it satisfies the replacement policy, otherwise it is rejected.
     pub fn insert(
         &mut self,
         tx: Arc<Transaction>,
         dependent_outputs: Option<Vec<HashOutput>>,
         transaction_weighting: &TransactionWeight,
-    ) {
+    ) -> InsertionResult {
         if tx
             .body
             .kernels()
             .iter()
             .all(|k| self.txs_by_signature.contains_key(k.excess_sig.get_signature()))
         {
-            return;
+            return InsertionResult::NotInserted;
         }
 
         let new_key = self.get_next_key();
         let prioritized_tx = PrioritizedTransaction::new(new_key, transaction_weighting, tx, dependent_outputs);
+
+        let mut to_evict = Vec::new();
+        if self.config.replace_by_fee_enabled {
+            match self.check_replacement(&prioritized_tx) {
+                Ok(keys) => to_evict = keys,
+                Err(reason) => return InsertionResult::ReplacementRejected(reason),
+            }
+        }
+        let replaced = to_evict
+            .into_iter()
+            .filter_map(|key| self.remove_transaction(key))
+            .collect::<Vec<_>>();
+        if !replaced.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction {} replaced {} transaction(s) in the unconfirmed pool",
+                prioritized_tx,
+                replaced.len()
+            );
+            self.replaced_count += replaced.len() as u64;
+        }
+
         if self.tx_by_key.len() >= self.config.storage_capacity {
-            if prioritized_tx.priority < *self.lowest_priority() {
-                return;
+            let (priority, ancestors) = self.ancestor_package(&prioritized_tx);
+            // The new transaction would be orphaned if one of the transactions it spends from were evicted for it
+            match self.lowest_priority_leaf(&ancestors) {
+                Some((lowest, key)) if priority >= lowest => {
+                    self.remove_transaction(key);
+                },
+                _ => return InsertionResult::NotInserted,
             }
-            self.remove_lowest_priority_tx();
         }
 
         self.tx_by_priority.insert(prioritized_tx.priority.clone(), new_key);
         for output in prioritized_tx.transaction.body.outputs() {
             self.txs_by_output.entry(output.hash()).or_default().push(new_key);
         }
+        for input in prioritized_tx.transaction.body.inputs() {
+            self.txs_by_input.entry(input.output_hash()).or_default().push(new_key);
+        }
         for kernel in prioritized_tx.transaction.body.kernels() {
             let sig = kernel.excess_sig.get_signature();
             self.txs_by_signature.entry(sig.clone()).or_default().push(new_key);
@@ -143,6 +242,110 @@ impl UnconfirmedPool {
             "Inserted transaction {} into unconfirmed pool:", prioritized_tx
         );
         self.tx_by_key.insert(new_key, prioritized_tx);
+        // Transactions in the pool that already spend the outputs of the new transaction now have it as an ancestor
+        for key in self.descendants(new_key) {
+            self.update_package_priority(key);
+        }
+        InsertionResult::Inserted { replaced }
+    }
+
+    /// Applies the replace-by-fee policy to a transaction that is about to be inserted. Returns the keys of all the
+    /// transactions that must be evicted for it (empty if it does not conflict with anything in the pool), or the
+    /// reason the replacement is not allowed.
+    fn check_replacement(
+        &self,
+        transaction: &PrioritizedTransaction,
+    ) -> Result<Vec<TransactionKey>, ReplacementRejection> {
+        let conflicts = transaction
+            .transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|input| input.output_hash())
+            .filter_map(|hash| self.txs_by_input.get(&hash))
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+        if conflicts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let min_increment = MicroTari::from(self.config.replace_by_fee_min_increment);
+        let fee_per_gram = transaction.transaction.body.get_total_fee() / transaction.weight;
+        let max_conflict_fee_per_gram = conflicts
+            .iter()
+            .filter_map(|key| self.tx_by_key.get(key))
+            .map(|ptx| ptx.transaction.body.get_total_fee() / ptx.weight)
+            .max()
+            .unwrap_or_else(MicroTari::zero);
+        let required = max_conflict_fee_per_gram + min_increment;
+        if fee_per_gram < required {
+            return Err(ReplacementRejection::FeePerGramTooLow {
+                required,
+                actual: fee_per_gram,
+            });
+        }
+
+        let max_evictions = conflicts.len() + self.config.replace_by_fee_max_evictions;
+        let evicted =
+            self.with_descendants(conflicts, max_evictions)
+                .ok_or(ReplacementRejection::TooManyEvictions {
+                    max: self.config.replace_by_fee_max_evictions,
+                })?;
+
+        let spends_evicted_output = transaction
+            .transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|input| input.output_hash())
+            .filter_map(|hash| self.txs_by_output.get(&hash))
+            .flatten()
+            .any(|key| evicted.contains(key));
+        if spends_evicted_output {
+            return Err(ReplacementRejection::SpendsEvictedOutput);
+        }
+
+        let evicted_fees = evicted
+            .iter()
+            .filter_map(|key| self.tx_by_key.get(key))
+            .map(|ptx| ptx.transaction.body.get_total_fee())
+            .sum::<MicroTari>();
+        let required = evicted_fees + min_increment * transaction.weight;
+        let actual = transaction.transaction.body.get_total_fee();
+        if actual < required {
+            return Err(ReplacementRejection::TotalFeeTooLow { required, actual });
+        }
+
+        Ok(evicted.into_iter().collect())
+    }
+
+    /// Returns all the transactions in the pool that (directly or indirectly) spend the outputs of the given
+    /// transaction, including the transaction itself
+    fn descendants(&self, key: TransactionKey) -> HashSet<TransactionKey> {
+        self.with_descendants(HashSet::from([key]), usize::MAX)
+            .expect("no descendant limit")
+    }
+
+    /// Returns the given transactions together with all the transactions in the pool that (directly or indirectly)
+    /// spend their outputs, or None if that is more than `limit` transactions.
+    fn with_descendants(&self, keys: HashSet<TransactionKey>, limit: usize) -> Option<HashSet<TransactionKey>> {
+        let mut pending = keys.iter().copied().collect::<Vec<_>>();
+        let mut found = keys;
+        while let Some(key) = pending.pop() {
+            let ptx = self.tx_by_key.get(&key).expect("mempool indexes out of sync");
+            for output in ptx.transaction.body.outputs() {
+                for descendant in self.txs_by_input.get(&output.hash()).into_iter().flatten() {
+                    if found.insert(*descendant) {
+                        if found.len() > limit {
+                            return None;
+                        }
+                        pending.push(*descendant);
+                    }
+                }
+            }
+        }
+        Some(found)
     }
 
     /// TThis will search the unconfirmed pool for the set of outputs and return true if all of them are found
@@ -167,7 +370,9 @@ impl UnconfirmedPool {
         self.txs_by_signature.contains_key(excess_sig.get_signature())
     }
 
-    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block
+    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block. Transactions
+    /// are ranked together with the unconfirmed transactions they depend on, so a high fee child will pull its low
+    /// fee parents into the block (child-pays-for-parent).
     pub fn fetch_highest_priority_txs(&mut self, total_weight: u64) -> Result<RetrieveResults, UnconfirmedPoolError> {
         let mut selected_txs = HashMap::new();
         let mut curr_weight = 0;
@@ -175,7 +380,7 @@ impl UnconfirmedPool {
         let mut transactions_to_remove_and_recheck = Vec::new();
         let mut potential_transactions_to_remove_and_recheck = Vec::new();
         let mut unique_ids = HashSet::new();
-        for (_, tx_key) in self.tx_by_priority.iter().rev() {
+        for tx_key in self.tx_by_package_priority.values().rev() {
             if selected_txs.contains_key(tx_key) {
                 continue;
             }
@@ -347,24 +552,80 @@ impl UnconfirmedPool {
         false
     }
 
-    fn lowest_priority(&self) -> &FeePriority {
-        self.tx_by_priority
-            .keys()
-            .next()
-            .expect("lowest_priority called on empty mempool")
+    /// Returns the childless transaction with the lowest package priority, skipping the given transactions. Packages
+    /// are ranked in the same way as for block selection, so this is the transaction that would be selected last.
+    /// Only childless transactions are considered so that evicting one never orphans another; a parent is kept for as
+    /// long as a child that pays for it is in the pool.
+    fn lowest_priority_leaf(&self, skip: &HashSet<TransactionKey>) -> Option<(PackagePriority, TransactionKey)> {
+        self.tx_by_package_priority
+            .iter()
+            .filter(|(_, key)| !skip.contains(key))
+            .find(|(_, key)| {
+                let ptx = self.tx_by_key.get(key).expect("mempool indexes out of sync");
+                ptx.transaction
+                    .body
+                    .outputs()
+                    .iter()
+                    .all(|output| !self.txs_by_input.contains_key(&output.hash()))
+            })
+            .map(|((priority, _), key)| (*priority, *key))
     }
 
-    fn remove_lowest_priority_tx(&mut self) {
-        if let Some(tx_key) = self.tx_by_priority.values().next().copied() {
-            self.remove_transaction(tx_key);
+    /// Returns the priority of the package formed by a transaction and all the transactions in the pool that it
+    /// depends on, together with the keys of those transactions.
+    fn ancestor_package(&self, transaction: &PrioritizedTransaction) -> (PackagePriority, HashSet<TransactionKey>) {
+        let mut fee = transaction.transaction.body.get_total_fee();
+        let mut weight = transaction.weight;
+        let mut ancestors = HashSet::new();
+        let mut pending = self.parents_of(transaction);
+        while let Some(key) = pending.pop() {
+            if key == transaction.key || !ancestors.insert(key) {
+                continue;
+            }
+            let ptx = self.tx_by_key.get(&key).expect("mempool indexes out of sync");
+            fee += ptx.transaction.body.get_total_fee();
+            weight += ptx.weight;
+            pending.extend(self.parents_of(ptx));
+        }
+        (PackagePriority::new(fee, weight), ancestors)
+    }
+
+    /// Returns the keys of the transactions in the pool whose outputs the transaction spends. Dependencies that are no
+    /// longer in the pool are picked up for re-validation during selection.
+    fn parents_of(&self, transaction: &PrioritizedTransaction) -> Vec<TransactionKey> {
+        transaction
+            .dependent_output_hashes
+            .iter()
+            .filter_map(|output_hash| self.txs_by_output.get(output_hash))
+            .map(|keys| {
+                self.find_highest_priority_transaction(keys)
+                    .expect("mempool indexes out of sync")
+                    .key
+            })
+            .collect()
+    }
+
+    /// Recalculates the package priority of a transaction, which changes whenever one of its ancestors is added to or
+    /// removed from the pool
+    fn update_package_priority(&mut self, key: TransactionKey) {
+        let (priority, fee_priority) = match self.tx_by_key.get(&key) {
+            Some(ptx) => (self.ancestor_package(ptx).0, ptx.priority.clone()),
+            None => return,
+        };
+        if let Some(old) = self.package_priority_by_key.insert(key, priority) {
+            self.tx_by_package_priority.remove(&(old, fee_priority.clone()));
         }
+        self.tx_by_package_priority.insert((priority, fee_priority), key);
     }
 
     /// Remove all current mempool transactions from the UnconfirmedPoolStorage, returning that which have been removed
     pub fn drain_all_mempool_transactions(&mut self) -> Vec<Arc<Transaction>> {
         self.txs_by_signature.clear();
         self.tx_by_priority.clear();
+        self.tx_by_package_priority.clear();
+        self.package_priority_by_key.clear();
         self.txs_by_output.clear();
+        self.txs_by_input.clear();
         self.tx_by_key.drain().map(|(_, val)| val.transaction).collect()
     }
 
@@ -440,9 +701,18 @@ impl UnconfirmedPool {
 
     /// Ensures that all transactions are safely deleted in order and from all storage
     fn remove_transaction(&mut self, tx_key: TransactionKey) -> Option<Arc<Transaction>> {
+        if !self.tx_by_key.contains_key(&tx_key) {
+            return None;
+        }
+        let mut descendants = self.descendants(tx_key);
+        descendants.remove(&tx_key);
         let prioritized_transaction = self.tx_by_key.remove(&tx_key)?;
 
         self.tx_by_priority.remove(&prioritized_transaction.priority);
+        if let Some(package_priority) = self.package_priority_by_key.remove(&tx_key) {
+            self.tx_by_package_priority
+                .remove(&(package_priority, prioritized_transaction.priority.clone()));
+        }
 
         for kernel in prioritized_transaction.transaction.body.kernels() {
             let sig = kernel.excess_sig.get_signature();
@@ -467,6 +737,23 @@ impl UnconfirmedPool {
             }
         }
 
+        for input in prioritized_transaction.transaction.body.inputs() {
+            let input_hash = input.output_hash();
+            if let Some(keys) = self.txs_by_input.get_mut(&input_hash) {
+                if let Some(pos) = keys.iter().position(|k| *k == tx_key) {
+                    keys.remove(pos);
+                }
+                if keys.is_empty() {
+                    self.txs_by_input.remove(&input_hash);
+                }
+            }
+        }
+
+        // Transactions that spent the outputs of the removed transaction no longer include it in their package
+        for key in descendants {
+            self.update_package_priority(key);
+        }
+
         trace!(
             target: LOG_TARGET,
             "Deleted transaction: {}",
@@ -495,6 +782,11 @@ impl UnconfirmedPool {
         self.txs_by_signature.len()
     }
 
+    /// Returns the number of transactions that have been evicted from the pool by replace-by-fee transactions.
+    pub fn replaced_count(&self) -> u64 {
+        self.replaced_count
+    }
+
     /// Returns all transaction stored in the UnconfirmedPool.
     pub fn snapshot(&self) -> Vec<Arc<Transaction>> {
         self.tx_by_key.iter().map(|(_, ptx)| ptx.transaction.clone()).collect()
@@ -562,6 +854,14 @@ impl UnconfirmedPool {
     #[cfg(test)]
     fn check_data_consistency(&self) -> bool {
         self.tx_by_priority.len() == self.tx_by_key.len() &&
+            self.tx_by_package_priority.len() == self.tx_by_key.len() &&
+            self.package_priority_by_key.len() == self.tx_by_key.len() &&
+            self.tx_by_package_priority.iter().all(|((priority, _), tx_key)| {
+                self.tx_by_key
+                    .get(tx_key)
+                    .map(|ptx| self.ancestor_package(ptx).0 == *priority)
+                    .unwrap_or(false)
+            }) &&
             self.tx_by_priority
                 .values()
                 .all(|tx_key| self.tx_by_key.contains_key(tx_key)) &&
@@ -572,6 +872,9 @@ impl UnconfirmedPool {
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
             self.txs_by_unique_id
+                .values()
+                .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
+            self.txs_by_input
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key)))
     }
@@ -597,8 +900,10 @@ impl UnconfirmedPool {
 
         let (old, new) = shrink_hashmap(&mut self.tx_by_key);
         shrink_hashmap(&mut self.txs_by_signature);
+        shrink_hashmap(&mut self.package_priority_by_key);
         shrink_hashmap(&mut self.txs_by_output);
         shrink_hashmap(&mut self.txs_by_unique_id);
+        shrink_hashmap(&mut self.txs_by_input);
 
         if old - new > 0 {
             debug!(
@@ -619,6 +924,7 @@ mod test {
     use super::*;
     use crate::{
         consensus::ConsensusManagerBuilder,
+        mempool::test_utils::spend_output,
         test_helpers::{create_consensus_constants, create_consensus_rules, create_orphan_block},
         transactions::{
             fee::Fee,
@@ -662,6 +968,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
 
         let tx_weight = TransactionWeight::latest();
@@ -739,6 +1046,9 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
+            // Keep both double-spends in the pool, the selection must then pick only one of them
+            replace_by_fee_enabled: false,
+            ..Default::default()
         });
 
         let tx_weight = TransactionWeight::latest();
@@ -771,6 +1081,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool.insert_many(
             vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone(), tx5.clone()],
@@ -819,6 +1130,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool.insert_many(
             vec![
@@ -847,6 +1159,220 @@ mod test {
         assert!(unconfirmed_pool.check_data_consistency());
     }
 
+    #[test]
+    fn test_replace_by_fee() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 2, outputs: 1).0);
+        let mut tx2 = tx!(MicroTari(5_000), fee: MicroTari(10), inputs: 2, outputs: 1).0;
+        let mut tx3 = tx!(MicroTari(5_000), fee: MicroTari(10), inputs: 2, outputs: 1).0;
+        let mut tx4 = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+        let mut tx5 = tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0;
+        // tx2, tx3 and tx5 all spend an input of tx1 and tx4 spends the output of tx2
+        tx2.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        tx3.body.inputs_mut()[1] = tx1.body.inputs()[0].clone();
+        tx5.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        tx4.body.inputs_mut()[0] = spend_output(&tx2, 0);
+        let tx2 = Arc::new(tx2);
+        let tx3 = Arc::new(tx3);
+        let tx4 = Arc::new(tx4);
+        let tx5 = Arc::new(tx5);
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        assert_eq!(
+            unconfirmed_pool.insert(tx1.clone(), None, &tx_weight),
+            InsertionResult::Inserted { replaced: vec![] }
+        );
+
+        // A higher fee per gram replaces the conflicting transaction
+        assert_eq!(
+            unconfirmed_pool.insert(tx2.clone(), None, &tx_weight),
+            InsertionResult::Inserted {
+                replaced: vec![tx1.clone()]
+            }
+        );
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.replaced_count(), 1);
+
+        // An equal fee per gram does not
+        assert!(matches!(
+            unconfirmed_pool.insert(tx3.clone(), None, &tx_weight),
+            InsertionResult::ReplacementRejected(ReplacementRejection::FeePerGramTooLow { .. })
+        ));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+
+        let dependent_outputs = vec![tx2.body.outputs()[0].hash()];
+        unconfirmed_pool.insert(tx4.clone(), Some(dependent_outputs), &tx_weight);
+        assert_eq!(unconfirmed_pool.len(), 2);
+
+        // Replacing tx2 also evicts its descendant
+        match unconfirmed_pool.insert(tx5.clone(), None, &tx_weight) {
+            InsertionResult::Inserted { replaced } => {
+                assert_eq!(replaced.len(), 2);
+                assert!(replaced.contains(&tx2));
+                assert!(replaced.contains(&tx4));
+            },
+            result => panic!("Unexpected insertion result: {:?}", result),
+        }
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx5.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.replaced_count(), 3);
+
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[test]
+    fn test_replace_by_fee_policy_limits() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 2, outputs: 1).0);
+        let mut tx2 = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+        let mut tx3 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0;
+        let mut tx4 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0;
+        tx2.body.inputs_mut()[0] = spend_output(&tx1, 0);
+        // tx3 conflicts with tx1 while tx4 conflicts with tx1 and spends the output of tx1
+        tx3.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        tx4.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        tx4.body.inputs_mut()[0] = spend_output(&tx1, 0);
+        tx4.body.inputs_mut()[1] = tx1.body.inputs()[1].clone();
+        let tx2 = Arc::new(tx2);
+        let tx3 = Arc::new(tx3);
+        let tx4 = Arc::new(tx4);
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            replace_by_fee_max_evictions: 0,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(tx1.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(tx2, Some(vec![tx1.body.outputs()[0].hash()]), &tx_weight);
+        assert_eq!(
+            unconfirmed_pool.insert(tx3, None, &tx_weight),
+            InsertionResult::ReplacementRejected(ReplacementRejection::TooManyEvictions { max: 0 })
+        );
+        assert_eq!(unconfirmed_pool.len(), 2);
+        assert_eq!(unconfirmed_pool.replaced_count(), 0);
+        assert!(unconfirmed_pool.check_data_consistency());
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool.insert(tx1, None, &tx_weight);
+        assert_eq!(
+            unconfirmed_pool.insert(tx4, None, &tx_weight),
+            InsertionResult::ReplacementRejected(ReplacementRejection::SpendsEvictedOutput)
+        );
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[test]
+    fn test_child_pays_for_parent() {
+        let parent = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(1), inputs: 1, outputs: 1).0);
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 1, outputs: 1).0;
+        child.body.inputs_mut()[0] = spend_output(&parent, 0);
+        let child = Arc::new(child);
+        let other = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]), &tx_weight);
+        unconfirmed_pool.insert(other.clone(), None, &tx_weight);
+
+        // Only room for two transactions, the parent and child package outranks the other transaction
+        let desired_weight = parent.calculate_weight(&tx_weight) + child.calculate_weight(&tx_weight);
+        let results = unconfirmed_pool.fetch_highest_priority_txs(desired_weight).unwrap();
+        assert_eq!(results.retrieved_transactions.len(), 2);
+        assert!(results.retrieved_transactions.contains(&parent));
+        assert!(results.retrieved_transactions.contains(&child));
+        assert!(results.transactions_to_insert.is_empty());
+    }
+
+    #[test]
+    fn test_cheap_parent_lowers_child_priority() {
+        let parent = Arc::new(tx!(MicroTari(100_000), fee: MicroTari(1), inputs: 1, outputs: 10).0);
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 1, outputs: 1).0;
+        child.body.inputs_mut()[0] = spend_output(&parent, 0);
+        let child = Arc::new(child);
+        let others = (0u64..3)
+            .map(|i| Arc::new(tx!(MicroTari(10_000 + i), fee: MicroTari(20), inputs: 1, outputs: 1).0))
+            .collect::<Vec<_>>();
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]), &tx_weight);
+        unconfirmed_pool.insert_many(others.clone(), &tx_weight);
+
+        // The child pays more per gram than the other transactions, but not enough to pay for its large parent
+        let desired_weight = parent.calculate_weight(&tx_weight) + child.calculate_weight(&tx_weight);
+        let results = unconfirmed_pool.fetch_highest_priority_txs(desired_weight).unwrap();
+        assert!(!results.retrieved_transactions.contains(&child));
+        assert!(results.retrieved_transactions.contains(&others[0]));
+    }
+
+    #[test]
+    fn test_eviction_keeps_parent_paid_for_by_child() {
+        let parent = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(1), inputs: 1, outputs: 1).0);
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 1, outputs: 1).0;
+        child.body.inputs_mut()[0] = spend_output(&parent, 0);
+        let child = Arc::new(child);
+        let low = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(10), inputs: 1, outputs: 1).0);
+        let high = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]), &tx_weight);
+        unconfirmed_pool.insert(low.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(high.clone(), None, &tx_weight);
+
+        assert_eq!(unconfirmed_pool.len(), 3);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&low.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&high.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[test]
+    fn test_eviction_never_removes_ancestor_of_new_transaction() {
+        let parent = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(1), inputs: 1, outputs: 1).0);
+        let other = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 1, outputs: 1).0;
+        child.body.inputs_mut()[0] = spend_output(&parent, 0);
+        let child = Arc::new(child);
+        let parent_output = vec![parent.body.outputs()[0].hash()];
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 2,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight);
+        unconfirmed_pool.insert(other.clone(), None, &tx_weight);
+        // The parent is the lowest priority transaction, but the child would be orphaned without it
+        unconfirmed_pool.insert(child.clone(), Some(parent_output.clone()), &tx_weight);
+
+        assert_eq!(unconfirmed_pool.len(), 2);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&other.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_data_consistency());
+
+        // With nothing else to evict, the child is rejected rather than replacing its own parent
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 1,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight);
+        let result = unconfirmed_pool.insert(child.clone(), Some(parent_output), &tx_weight);
+
+        assert!(matches!(result, InsertionResult::NotInserted));
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
     #[test]
     fn test_multiple_transactions_with_same_outputs_in_mempool() {
         let (tx1, _, _) = tx!(MicroTari(150_000), fee: MicroTari(50), inputs:5, outputs:5);
@@ -866,6 +1392,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         let txns = vec![
             Arc::new(tx1.clone()),
@@ -0,0 +1,935 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Replays a chain reorganisation against a fresh [BlockchainDatabase] and [Mempool].
+//!
+//! A [ReorgScenario] is either taken from a reorg recorded by a base node (see
+//! [ReorgScenario::from_recorded_reorg]) or scripted with the
+//! [TestBlockchain](crate::test_helpers::blockchain::TestBlockchain) block builders. [replay_reorg] copies the blocks
+//! both chains share from a source database, mines the old branch, switches to the new branch and then checks the
+//! outcome against what can be derived directly from the blocks: which transactions the mempool should hold again and
+//! which status a wallet should be given for every output touched by the reorg.
+
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    fmt,
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+use log::*;
+use tari_common_types::types::{BlockHash, HashOutput};
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::{
+    blocks::{Block, BlockError},
+    chain_storage::{BlockAddResult, BlockchainBackend, BlockchainDatabase, ChainStorageError, Reorg},
+    mempool::{Mempool, MempoolConfig, MempoolError, TxStorageResponse},
+    transactions::transaction_components::Transaction,
+    validation::transaction_validators::TxInputAndMaturityValidator,
+};
+
+const LOG_TARGET: &str = "c::reorg_replay";
+/// The number of shared blocks fetched from the source database at a time
+const COMMON_CHAIN_BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Error)]
+pub enum ReorgReplayError {
+    #[error("Chain storage error: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("Mempool error: {0}")]
+    MempoolError(#[from] MempoolError),
+    #[error("Block error: {0}")]
+    BlockError(#[from] BlockError),
+    #[error("Invalid reorg scenario: {0}")]
+    InvalidScenario(String),
+}
+
+/// A reorg that can be replayed on top of the main chain of a source database. The blocks of both branches are in
+/// ascending height order, while the blocks the two chains share are only fetched from the source when replaying.
+#[derive(Debug, Clone)]
+pub struct ReorgScenario {
+    /// The height of the last block shared by the old and the new chain
+    pub fork_height: u64,
+    /// The hash of the last block shared by the old and the new chain
+    pub fork_hash: BlockHash,
+    /// The blocks of the old chain that are reorged out
+    pub removed: Vec<Arc<Block>>,
+    /// The blocks of the new chain that replace them
+    pub added: Vec<Arc<Block>>,
+    /// Transactions submitted to the mempool before the old branch is mined. Parents must come before their children.
+    pub transactions: Vec<Arc<Transaction>>,
+}
+
+impl ReorgScenario {
+    pub fn new(
+        fork_height: u64,
+        fork_hash: BlockHash,
+        removed: Vec<Arc<Block>>,
+        added: Vec<Arc<Block>>,
+        transactions: Vec<Arc<Transaction>>,
+    ) -> Result<Self, ReorgReplayError> {
+        if removed.is_empty() || added.is_empty() {
+            return Err(ReorgReplayError::InvalidScenario(
+                "both the old and the new branch must contain at least one block".to_string(),
+            ));
+        }
+        check_linked("old", (fork_height, fork_hash), &removed)?;
+        check_linked("new", (fork_height, fork_hash), &added)?;
+        Ok(Self {
+            fork_height,
+            fork_hash,
+            removed,
+            added,
+            transactions,
+        })
+    }
+
+    /// Splits two chains that start from the same genesis block at the point where they fork, e.g. branches returned
+    /// by [TestBlockchain::get_branch](super::blockchain::TestBlockchain::get_branch).
+    pub fn from_branches(
+        mut old_chain: Vec<Arc<Block>>,
+        mut new_chain: Vec<Arc<Block>>,
+        transactions: Vec<Arc<Transaction>>,
+    ) -> Result<Self, ReorgReplayError> {
+        let num_common = old_chain
+            .iter()
+            .zip(&new_chain)
+            .take_while(|(old, new)| old.hash() == new.hash())
+            .count();
+        let removed = old_chain.split_off(num_common);
+        let added = new_chain.split_off(num_common);
+        let fork = new_chain.last().ok_or_else(|| {
+            ReorgReplayError::InvalidScenario("the branches do not start from the same genesis block".to_string())
+        })?;
+        Self::new(fork.header.height, fork.hash(), removed, added, transactions)
+    }
+
+    /// Reconstructs a reorg recorded by `db`. The blocks that were reorged out are taken from the orphan pool, so this
+    /// fails once they have been cleaned up, and the blocks of the new branch must still be on the main chain. As the
+    /// individual transactions are no longer known, the non-coinbase part of every reorged out block is submitted to
+    /// the mempool as a single aggregate transaction.
+    pub fn from_recorded_reorg<B: BlockchainBackend>(
+        db: &BlockchainDatabase<B>,
+        reorg: &Reorg,
+    ) -> Result<Self, ReorgReplayError> {
+        let fork_height = reorg.prev_height.checked_sub(reorg.num_blocks_removed).ok_or_else(|| {
+            ReorgReplayError::InvalidScenario(format!(
+                "{} blocks cannot be removed from a chain at height {}",
+                reorg.num_blocks_removed, reorg.prev_height
+            ))
+        })?;
+
+        let fork_hash = db
+            .fetch_header(fork_height)?
+            .map(|header| header.hash())
+            .ok_or_else(|| {
+                ReorgReplayError::InvalidScenario(format!("the main chain does not contain block #{}", fork_height))
+            })?;
+
+        let mut removed = Vec::with_capacity(reorg.num_blocks_removed as usize);
+        let mut hash = reorg.prev_hash;
+        for _ in 0..reorg.num_blocks_removed {
+            let block = db.fetch_orphan(hash).map_err(|_| {
+                ReorgReplayError::InvalidScenario(format!(
+                    "reorged out block {} is no longer in the orphan pool",
+                    hash.to_hex()
+                ))
+            })?;
+            hash = block.header.prev_hash;
+            removed.push(Arc::new(block));
+        }
+        removed.reverse();
+
+        let added = fetch_main_chain(db, fork_height + 1, fork_height + reorg.num_blocks_added)?;
+        if added.iter().all(|block| block.hash() != reorg.new_hash) {
+            return Err(ReorgReplayError::InvalidScenario(format!(
+                "block {} added by the reorg is no longer on the main chain",
+                reorg.new_hash.to_hex()
+            )));
+        }
+        let transactions = removed
+            .iter()
+            .filter_map(|block| aggregate_block_transactions(block))
+            .map(Arc::new)
+            .collect();
+        Self::new(fork_height, fork_hash, removed, added, transactions)
+    }
+
+    /// Every output created or spent by either branch or created by one of the scenario transactions
+    fn tracked_outputs(&self) -> Vec<HashOutput> {
+        let mut seen = HashSet::new();
+        self.removed
+            .iter()
+            .chain(&self.added)
+            .flat_map(|block| {
+                let outputs = block.body.outputs().iter().map(|output| output.hash());
+                let inputs = block.body.inputs().iter().map(|input| input.output_hash());
+                outputs.chain(inputs).collect::<Vec<_>>()
+            })
+            .chain(
+                self.transactions
+                    .iter()
+                    .flat_map(|tx| tx.body.outputs().iter().map(|output| output.hash())),
+            )
+            .filter(|hash| seen.insert(*hash))
+            .collect()
+    }
+
+    /// An empty [ChainOutputs] that records the tracked outputs, the inputs and the kernels of the scenario
+    /// transactions
+    fn watched_chain(&self) -> ChainOutputs {
+        let mut outputs = self.tracked_outputs().into_iter().collect::<HashSet<_>>();
+        outputs.extend(
+            self.transactions
+                .iter()
+                .flat_map(|tx| tx.body.inputs().iter().map(|input| input.output_hash())),
+        );
+        let kernels = self
+            .transactions
+            .iter()
+            .flat_map(|tx| tx.body.kernels().iter().map(|kernel| kernel.hash()))
+            .collect();
+        ChainOutputs::watching(outputs, kernels)
+    }
+
+    fn check_reorg(&self, added: &[HashOutput], removed: &[HashOutput], tip: &BlockHash) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        let expected_removed = self.removed.iter().map(|block| block.hash()).collect::<HashSet<_>>();
+        if removed.iter().copied().collect::<HashSet<_>>() != expected_removed {
+            mismatches.push(format!(
+                "{} block(s) were reorged out but the old branch has {}",
+                removed.len(),
+                expected_removed.len()
+            ));
+        }
+        let expected_added = self.added.iter().map(|block| block.hash()).collect::<HashSet<_>>();
+        if added.iter().copied().collect::<HashSet<_>>() != expected_added {
+            mismatches.push(format!(
+                "{} block(s) were added to the main chain but the new branch has {}",
+                added.len(),
+                expected_added.len()
+            ));
+        }
+        let expected_tip = self.added[self.added.len() - 1].hash();
+        if *tip != expected_tip {
+            mismatches.push(format!(
+                "The chain tip is {} but the new branch ends at {}",
+                tip.to_hex(),
+                expected_tip.to_hex()
+            ));
+        }
+        mismatches
+    }
+}
+
+/// Checks that `blocks` follow on from the block at `parent`, given as its height and hash
+fn check_linked(chain: &str, parent: (u64, BlockHash), blocks: &[Arc<Block>]) -> Result<(), ReorgReplayError> {
+    let (mut height, mut hash) = parent;
+    for block in blocks {
+        if block.header.prev_hash != hash || block.header.height != height + 1 {
+            return Err(ReorgReplayError::InvalidScenario(format!(
+                "block #{} ({}) of the {} chain does not follow on from block #{} ({})",
+                block.header.height,
+                block.hash().to_hex(),
+                chain,
+                height,
+                hash.to_hex()
+            )));
+        }
+        height = block.header.height;
+        hash = block.hash();
+    }
+    Ok(())
+}
+
+fn fetch_main_chain<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    start: u64,
+    end: u64,
+) -> Result<Vec<Arc<Block>>, ReorgReplayError> {
+    if end < start {
+        return Ok(Vec::new());
+    }
+    let blocks = db.fetch_blocks(start..=end)?;
+    if blocks.len() as u64 != end - start + 1 {
+        return Err(ReorgReplayError::InvalidScenario(format!(
+            "the main chain does not contain blocks #{} to #{}",
+            start, end
+        )));
+    }
+    blocks
+        .into_iter()
+        .map(|block| block.try_into_block().map(Arc::new).map_err(ReorgReplayError::from))
+        .collect()
+}
+
+/// Rebuilds the non-coinbase part of a block as a single transaction. The coinbase output and kernel balance each
+/// other, so what remains balances with the block's kernel and script offsets. Returns None if the block has no
+/// transactions or spends an output it created, which the mempool would reject as an orphan.
+fn aggregate_block_transactions(block: &Block) -> Option<Transaction> {
+    let kernels = block
+        .body
+        .kernels()
+        .iter()
+        .filter(|kernel| !kernel.is_coinbase())
+        .cloned()
+        .collect::<Vec<_>>();
+    if kernels.is_empty() {
+        return None;
+    }
+    let outputs = block
+        .body
+        .outputs()
+        .iter()
+        .filter(|output| !output.is_coinbase())
+        .cloned()
+        .collect::<Vec<_>>();
+    let output_hashes = outputs.iter().map(|output| output.hash()).collect::<HashSet<_>>();
+    if block
+        .body
+        .inputs()
+        .iter()
+        .any(|input| output_hashes.contains(&input.output_hash()))
+    {
+        warn!(
+            target: LOG_TARGET,
+            "Block #{} spends outputs it creates, its transactions will not be replayed", block.header.height
+        );
+        return None;
+    }
+    Some(Transaction::new(
+        block.body.inputs().clone(),
+        outputs,
+        kernels,
+        block.header.total_kernel_offset.clone(),
+        block.header.total_script_offset.clone(),
+    ))
+}
+
+/// Replays `scenario` against `db`, which must only contain the genesis block. The blocks up to the fork are copied
+/// from the main chain of `source`, then the old branch is mined with the scenario transactions in the mempool and
+/// the new branch is added until the database reorgs onto it, handing each block add result to the mempool the same
+/// way the mempool service does.
+pub async fn replay_reorg<S: BlockchainBackend, B: BlockchainBackend + 'static>(
+    scenario: &ReorgScenario,
+    source: &BlockchainDatabase<S>,
+    db: BlockchainDatabase<B>,
+) -> Result<ReorgReplayReport, ReorgReplayError> {
+    let tip_height = db.get_chain_metadata()?.height_of_longest_chain();
+    if tip_height != 0 {
+        return Err(ReorgReplayError::InvalidScenario(format!(
+            "reorgs must be replayed against an empty database, but its tip is at height {}",
+            tip_height
+        )));
+    }
+    let genesis = db.fetch_block(0)?.try_into_block()?;
+
+    let mut report = ReorgReplayReport {
+        fork_height: scenario.fork_height,
+        num_blocks_removed: scenario.removed.len(),
+        num_blocks_added: scenario.added.len(),
+        ..Default::default()
+    };
+    let mut common_chain = scenario.watched_chain();
+    common_chain.scan_block(&genesis);
+    add_common_chain(scenario, source, &db, genesis.hash(), &mut common_chain)?;
+
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        db.rules().clone(),
+        Box::new(TxInputAndMaturityValidator::new(db.clone())),
+    );
+    let mut accepted = Vec::with_capacity(scenario.transactions.len());
+    for tx in &scenario.transactions {
+        let response = mempool.insert(tx.clone()).await?;
+        if response.is_stored() {
+            accepted.push(tx.clone());
+        } else {
+            report.rejected_transactions.push((excess_sig_hex(tx), response));
+        }
+    }
+    for block in &scenario.removed {
+        add_to_main_chain(&db, block)?;
+        mempool.process_published_block(block.clone()).await?;
+    }
+
+    let tracked_outputs = scenario.tracked_outputs();
+    let old_chain = common_chain.clone().scan(&scenario.removed);
+    report.output_mismatches = compare_output_statuses(&db, ReplayPhase::BeforeReorg, &old_chain, &tracked_outputs)?;
+
+    let (added, removed) = add_new_branch(&db, &mempool, &scenario.added).await?;
+    report.chain_mismatches = scenario.check_reorg(&added, &removed, db.get_chain_metadata()?.best_block());
+
+    let new_chain = common_chain.scan(&scenario.added);
+    report.mempool_mismatches = check_mempool(&mempool, &new_chain, &accepted).await?;
+    report.output_mismatches.extend(compare_output_statuses(
+        &db,
+        ReplayPhase::AfterReorg,
+        &new_chain,
+        &tracked_outputs,
+    )?);
+
+    Ok(report)
+}
+
+/// Adds the blocks shared by both chains to `db`. They are fetched from the main chain of `source` in batches, so
+/// that a long chain is never held in memory, and only what `chain` watches is kept of them.
+fn add_common_chain<S: BlockchainBackend, B: BlockchainBackend>(
+    scenario: &ReorgScenario,
+    source: &BlockchainDatabase<S>,
+    db: &BlockchainDatabase<B>,
+    genesis_hash: BlockHash,
+    chain: &mut ChainOutputs,
+) -> Result<(), ReorgReplayError> {
+    let mut parent = (0, genesis_hash);
+    let mut start = 1;
+    while start <= scenario.fork_height {
+        let end = cmp::min(start + COMMON_CHAIN_BATCH_SIZE - 1, scenario.fork_height);
+        let blocks = fetch_main_chain(source, start, end)?;
+        check_linked("common", parent, &blocks)?;
+        for block in &blocks {
+            add_to_main_chain(db, block)?;
+            chain.scan_block(block);
+            parent = (block.header.height, block.hash());
+        }
+        start = end + 1;
+    }
+    if parent.1 != scenario.fork_hash {
+        return Err(ReorgReplayError::InvalidScenario(format!(
+            "block #{} of the source database is not the fork block {}",
+            scenario.fork_height,
+            scenario.fork_hash.to_hex()
+        )));
+    }
+    Ok(())
+}
+
+fn add_to_main_chain<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    block: &Arc<Block>,
+) -> Result<(), ReorgReplayError> {
+    if db.add_block(block.clone())?.is_added() {
+        Ok(())
+    } else {
+        Err(ReorgReplayError::InvalidScenario(format!(
+            "block #{} ({}) did not extend the main chain",
+            block.header.height,
+            block.hash().to_hex()
+        )))
+    }
+}
+
+/// Adds the blocks of the new branch, returning the hashes of the blocks added to and removed from the main chain
+async fn add_new_branch<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    mempool: &Mempool,
+    blocks: &[Arc<Block>],
+) -> Result<(Vec<HashOutput>, Vec<HashOutput>), ReorgReplayError> {
+    let mut added = Vec::with_capacity(blocks.len());
+    let mut removed = Vec::new();
+    for block in blocks {
+        match db.add_block(block.clone())? {
+            BlockAddResult::Ok(chain_block) => {
+                added.push(*chain_block.hash());
+                mempool.process_published_block(block.clone()).await?;
+            },
+            BlockAddResult::ChainReorg {
+                added: reorg_added,
+                removed: reorg_removed,
+            } => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Block #{} caused a reorg ({} added, {} removed)",
+                    block.header.height,
+                    reorg_added.len(),
+                    reorg_removed.len()
+                );
+                added.extend(reorg_added.iter().map(|b| *b.hash()));
+                removed.extend(reorg_removed.iter().map(|b| *b.hash()));
+                mempool
+                    .process_reorg(
+                        reorg_removed.iter().map(|b| b.to_arc_block()).collect(),
+                        reorg_added.iter().map(|b| b.to_arc_block()).collect(),
+                    )
+                    .await?;
+            },
+            BlockAddResult::OrphanBlock => {},
+            BlockAddResult::BlockExists => {
+                return Err(ReorgReplayError::InvalidScenario(format!(
+                    "block #{} ({}) of the new branch is already in the database",
+                    block.header.height,
+                    block.hash().to_hex()
+                )));
+            },
+        }
+    }
+    Ok((added, removed))
+}
+
+fn excess_sig_hex(tx: &Transaction) -> String {
+    tx.body
+        .kernels()
+        .first()
+        .map(|kernel| kernel.excess_sig.get_signature().to_hex())
+        .unwrap_or_default()
+}
+
+async fn check_mempool(
+    mempool: &Mempool,
+    chain: &ChainOutputs,
+    transactions: &[Arc<Transaction>],
+) -> Result<Vec<MempoolMismatch>, ReorgReplayError> {
+    let mut mismatches = Vec::new();
+    for (tx, expected) in transactions.iter().zip(expected_mempool_states(chain, transactions)) {
+        let kernel = match tx.body.kernels().first() {
+            Some(kernel) => kernel,
+            None => continue,
+        };
+        let actual = mempool.has_tx_with_excess_sig(kernel.excess_sig.clone()).await?;
+        if !expected.is_met_by(&actual) {
+            mismatches.push(MempoolMismatch {
+                excess_sig: excess_sig_hex(tx),
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Works out where each transaction should end up once the mempool has processed the reorg. Transactions with a
+/// kernel on the new chain are mined, transactions whose inputs are unspent on the new chain (or created by another
+/// pending transaction) are pending and everything else is discarded.
+fn expected_mempool_states(chain: &ChainOutputs, transactions: &[Arc<Transaction>]) -> Vec<ExpectedMempoolState> {
+    let mut states = transactions
+        .iter()
+        .map(|tx| {
+            if tx
+                .body
+                .kernels()
+                .iter()
+                .any(|kernel| chain.kernels.contains(&kernel.hash()))
+            {
+                Some(ExpectedMempoolState::Mined)
+            } else if tx.min_spendable_height() > chain.tip_height + 1 {
+                Some(ExpectedMempoolState::Discarded)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut pending_outputs = HashSet::new();
+    loop {
+        let mut progressed = false;
+        for (state, tx) in states.iter_mut().zip(transactions) {
+            let is_spendable = tx.body.inputs().iter().all(|input| {
+                let hash = input.output_hash();
+                chain.is_unspent(&hash) || pending_outputs.contains(&hash)
+            });
+            if state.is_none() && is_spendable {
+                *state = Some(ExpectedMempoolState::Pending);
+                pending_outputs.extend(tx.body.outputs().iter().map(|output| output.hash()));
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    states
+        .into_iter()
+        .map(|state| state.unwrap_or(ExpectedMempoolState::Discarded))
+        .collect()
+}
+
+/// Determines the status of each output the way a wallet does, using the same queries as the `utxo_query` and
+/// `query_deleted` base node RPC methods.
+fn reported_output_statuses<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    hashes: &[HashOutput],
+) -> Result<Vec<OutputStatus>, ChainStorageError> {
+    let mined_info = db.fetch_utxos_and_mined_info(hashes.to_vec())?;
+    let deleted = db.fetch_deleted_bitmap_at_tip()?;
+    let spent_positions = mined_info
+        .iter()
+        .flatten()
+        .map(|info| info.mmr_position)
+        .filter(|position| deleted.bitmap().contains(*position))
+        .collect::<Vec<_>>();
+    let spent_in = spent_positions
+        .iter()
+        .copied()
+        .zip(db.fetch_header_hash_by_deleted_mmr_positions(spent_positions.clone())?)
+        .collect::<HashMap<_, _>>();
+
+    Ok(mined_info
+        .into_iter()
+        .map(|info| match info {
+            None => OutputStatus::Unmined,
+            Some(info) => match spent_in.get(&info.mmr_position) {
+                Some(spent_in) => OutputStatus::Spent {
+                    mined_height: info.mined_height,
+                    mined_in_block: info.header_hash,
+                    spent_in: *spent_in,
+                },
+                None => OutputStatus::Unspent {
+                    mined_height: info.mined_height,
+                    mined_in_block: info.header_hash,
+                },
+            },
+        })
+        .collect())
+}
+
+fn compare_output_statuses<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    phase: ReplayPhase,
+    chain: &ChainOutputs,
+    hashes: &[HashOutput],
+) -> Result<Vec<OutputStatusMismatch>, ChainStorageError> {
+    let reported = reported_output_statuses(db, hashes)?;
+    Ok(hashes
+        .iter()
+        .zip(reported)
+        .filter_map(|(hash, reported)| {
+            let expected = chain.status(hash);
+            if expected == reported {
+                None
+            } else {
+                Some(OutputStatusMismatch {
+                    phase,
+                    output_hash: *hash,
+                    expected,
+                    reported,
+                })
+            }
+        })
+        .collect())
+}
+
+/// The outputs, spends and kernels of a chain, taken directly from its blocks. Only the watched outputs and kernels
+/// are recorded, so that a chain can be scanned from the genesis block one block at a time.
+#[derive(Clone, Default)]
+struct ChainOutputs {
+    watched_outputs: HashSet<HashOutput>,
+    watched_kernels: HashSet<HashOutput>,
+    mined: HashMap<HashOutput, (u64, BlockHash)>,
+    spent: HashMap<HashOutput, (u64, BlockHash)>,
+    kernels: HashSet<HashOutput>,
+    tip_height: u64,
+}
+
+impl ChainOutputs {
+    fn watching(watched_outputs: HashSet<HashOutput>, watched_kernels: HashSet<HashOutput>) -> Self {
+        Self {
+            watched_outputs,
+            watched_kernels,
+            ..Default::default()
+        }
+    }
+
+    fn scan_block(&mut self, block: &Block) {
+        let location = (block.header.height, block.hash());
+        for hash in block.body.outputs().iter().map(|output| output.hash()) {
+            if self.watched_outputs.contains(&hash) {
+                self.mined.insert(hash, location);
+            }
+        }
+        for hash in block.body.inputs().iter().map(|input| input.output_hash()) {
+            if self.watched_outputs.contains(&hash) {
+                self.spent.insert(hash, location);
+            }
+        }
+        for hash in block.body.kernels().iter().map(|kernel| kernel.hash()) {
+            if self.watched_kernels.contains(&hash) {
+                self.kernels.insert(hash);
+            }
+        }
+        self.tip_height = block.header.height;
+    }
+
+    fn scan<'a, I: IntoIterator<Item = &'a Arc<Block>>>(mut self, blocks: I) -> Self {
+        for block in blocks {
+            self.scan_block(block);
+        }
+        self
+    }
+
+    fn is_unspent(&self, hash: &HashOutput) -> bool {
+        self.mined.contains_key(hash) && !self.spent.contains_key(hash)
+    }
+
+    fn status(&self, hash: &HashOutput) -> OutputStatus {
+        match self.mined.get(hash) {
+            None => OutputStatus::Unmined,
+            Some(&(mined_height, mined_in_block)) => match self.spent.get(hash) {
+                Some(spent_in) => OutputStatus::Spent {
+                    mined_height,
+                    mined_in_block,
+                    spent_in: Some(*spent_in),
+                },
+                None => OutputStatus::Unspent {
+                    mined_height,
+                    mined_in_block,
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayPhase {
+    BeforeReorg,
+    AfterReorg,
+}
+
+impl Display for ReplayPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayPhase::BeforeReorg => write!(f, "before the reorg"),
+            ReplayPhase::AfterReorg => write!(f, "after the reorg"),
+        }
+    }
+}
+
+/// The status of an output as a wallet sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStatus {
+    Unmined,
+    Unspent {
+        mined_height: u64,
+        mined_in_block: BlockHash,
+    },
+    Spent {
+        mined_height: u64,
+        mined_in_block: BlockHash,
+        /// The height and hash of the spending block, None if the database could not say
+        spent_in: Option<(u64, BlockHash)>,
+    },
+}
+
+impl Display for OutputStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputStatus::Unmined => write!(f, "unmined"),
+            OutputStatus::Unspent {
+                mined_height,
+                mined_in_block,
+            } => write!(f, "unspent, mined in #{} ({})", mined_height, mined_in_block.to_hex()),
+            OutputStatus::Spent {
+                mined_height,
+                mined_in_block,
+                spent_in,
+            } => {
+                write!(f, "mined in #{} ({}), spent in ", mined_height, mined_in_block.to_hex())?;
+                match spent_in {
+                    Some((height, hash)) => write!(f, "#{} ({})", height, hash.to_hex()),
+                    None => write!(f, "an unknown block"),
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedMempoolState {
+    /// Mined on the new chain, the transaction must not be in the unconfirmed pool
+    Mined,
+    /// Spendable on the new chain, the transaction must be back in the unconfirmed pool
+    Pending,
+    /// Double spent, orphaned or time locked on the new chain, the transaction must not be stored
+    Discarded,
+}
+
+impl ExpectedMempoolState {
+    pub fn is_met_by(self, response: &TxStorageResponse) -> bool {
+        match self {
+            ExpectedMempoolState::Mined => *response != TxStorageResponse::UnconfirmedPool,
+            ExpectedMempoolState::Pending => *response == TxStorageResponse::UnconfirmedPool,
+            ExpectedMempoolState::Discarded => !response.is_stored(),
+        }
+    }
+}
+
+impl Display for ExpectedMempoolState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpectedMempoolState::Mined => write!(f, "mined"),
+            ExpectedMempoolState::Pending => write!(f, "pending"),
+            ExpectedMempoolState::Discarded => write!(f, "discarded"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MempoolMismatch {
+    pub excess_sig: String,
+    pub expected: ExpectedMempoolState,
+    pub actual: TxStorageResponse,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputStatusMismatch {
+    pub phase: ReplayPhase,
+    pub output_hash: HashOutput,
+    pub expected: OutputStatus,
+    pub reported: OutputStatus,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReorgReplayReport {
+    pub fork_height: u64,
+    pub num_blocks_removed: usize,
+    pub num_blocks_added: usize,
+    /// Differences between the reorg performed by the database and the scenario
+    pub chain_mismatches: Vec<String>,
+    /// Scenario transactions the mempool did not accept before the old branch was mined, these are not checked
+    pub rejected_transactions: Vec<(String, TxStorageResponse)>,
+    pub mempool_mismatches: Vec<MempoolMismatch>,
+    pub output_mismatches: Vec<OutputStatusMismatch>,
+}
+
+impl ReorgReplayReport {
+    pub fn is_consistent(&self) -> bool {
+        self.chain_mismatches.is_empty() && self.mempool_mismatches.is_empty() && self.output_mismatches.is_empty()
+    }
+}
+
+impl Display for ReorgReplayReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Reorg at height {}: {} block(s) removed, {} block(s) added",
+            self.fork_height, self.num_blocks_removed, self.num_blocks_added
+        )?;
+        for mismatch in &self.chain_mismatches {
+            writeln!(f, "Chain: {}", mismatch)?;
+        }
+        for (excess_sig, response) in &self.rejected_transactions {
+            writeln!(
+                f,
+                "Not replayed: transaction {} was rejected ({})",
+                excess_sig, response
+            )?;
+        }
+        for mismatch in &self.mempool_mismatches {
+            writeln!(
+                f,
+                "Mempool: transaction {} should be {} but the mempool reports '{}'",
+                mismatch.excess_sig, mismatch.expected, mismatch.actual
+            )?;
+        }
+        for mismatch in &self.output_mismatches {
+            writeln!(
+                f,
+                "Output {} {}: expected {} but the wallet is told {}",
+                mismatch.output_hash.to_hex(),
+                mismatch.phase,
+                mismatch.expected,
+                mismatch.reported
+            )?;
+        }
+        if self.is_consistent() {
+            write!(
+                f,
+                "The database, mempool and output statuses are consistent with the reorg"
+            )
+        } else {
+            write!(f, "The replay found inconsistencies")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        block_spec,
+        test_helpers::blockchain::{create_custom_blockchain, TestBlockchain},
+        transactions::{
+            tari_amount::{uT, T},
+            test_helpers::schema_to_transaction,
+        },
+        txn_schema,
+    };
+
+    #[tokio::test]
+    async fn it_restores_reorged_transactions_and_converges_output_statuses() {
+        let mut blockchain = TestBlockchain::default();
+        let (_, coinbase_1) = blockchain.append_to_tip(block_spec!("1")).unwrap();
+        let (_, coinbase_2) = blockchain.append_to_tip(block_spec!("2")).unwrap();
+        let (_, coinbase_3) = blockchain.append_to_tip(block_spec!("3")).unwrap();
+        blockchain.append_to_tip(block_spec!("4")).unwrap();
+
+        // Mined on both branches
+        let (tx_a, _) = schema_to_transaction(&[txn_schema!(from: vec![coinbase_1], to: vec![1 * T])]);
+        // Only mined on the old branch, tx_c spends one of its outputs
+        let (tx_b, tx_b_outputs) = schema_to_transaction(&[txn_schema!(from: vec![coinbase_2], to: vec![1 * T])]);
+        let (tx_c, _) =
+            schema_to_transaction(&[txn_schema!(from: vec![tx_b_outputs[0].clone()], to: vec![500_000 * uT])]);
+        // Double spent by tx_e on the new branch
+        let (tx_d, _) = schema_to_transaction(&[txn_schema!(from: vec![coinbase_3.clone()], to: vec![1 * T])]);
+        let (tx_e, _) = schema_to_transaction(&[txn_schema!(from: vec![coinbase_3], to: vec![2 * T])]);
+        let body = |txs: &[&Vec<Arc<Transaction>>]| txs.iter().map(|tx| (*tx[0]).clone()).collect::<Vec<_>>();
+
+        blockchain
+            .append(block_spec!("5a", parent: "4", transactions: body(&[&tx_a, &tx_b])))
+            .unwrap();
+        blockchain
+            .append(block_spec!("6a", parent: "5a", transactions: body(&[&tx_c, &tx_d])))
+            .unwrap();
+        blockchain
+            .append(block_spec!("5b", parent: "4", transactions: body(&[&tx_a, &tx_e])))
+            .unwrap();
+        blockchain.append(block_spec!("6b", parent: "5b")).unwrap();
+        blockchain.append(block_spec!("7b", parent: "6b")).unwrap();
+
+        let transactions = vec![tx_a[0].clone(), tx_b[0].clone(), tx_c[0].clone(), tx_d[0].clone()];
+        let scenario = ReorgScenario::from_branches(
+            blockchain.get_branch("6a"),
+            blockchain.get_branch("7b"),
+            transactions.clone(),
+        )
+        .unwrap();
+        assert_eq!(scenario.fork_height, 4);
+
+        let new_chain = scenario.watched_chain().scan(&blockchain.get_branch("7b"));
+        assert_eq!(expected_mempool_states(&new_chain, &transactions), vec![
+            ExpectedMempoolState::Mined,
+            ExpectedMempoolState::Pending,
+            ExpectedMempoolState::Pending,
+            ExpectedMempoolState::Discarded,
+        ]);
+
+        let db = create_custom_blockchain(blockchain.rules().clone());
+        let report = replay_reorg(&scenario, blockchain.db(), db).await.unwrap();
+        assert_eq!(report.num_blocks_removed, 2);
+        assert_eq!(report.num_blocks_added, 3);
+        assert!(report.rejected_transactions.is_empty(), "{}", report);
+        assert!(report.is_consistent(), "{}", report);
+    }
+}
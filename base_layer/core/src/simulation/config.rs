@@ -0,0 +1,278 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{proof_of_work::PowAlgorithm, simulation::SimulationError};
+
+/// A simulation scenario, usually loaded from a TOML file.
+///
+/// ```toml
+/// seed = 42
+/// num_blocks = 10000
+/// warm_up_blocks = 500
+///
+/// [[miners]]
+/// name = "sha3 pool"
+/// pow_algo = "Sha3"
+/// hash_rate = { type = "step", before = 1_000_000.0, after = 3_000_000.0, at = 864000 }
+///
+/// [[miners]]
+/// name = "merge miners"
+/// pow_algo = "Monero"
+/// hash_rate = { type = "constant", hash_rate = 2000.0 }
+/// strategy = { type = "future_timestamps" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulationConfig {
+    /// Seed for the random number generator. A scenario run with the same seed always produces the same chain.
+    pub seed: u64,
+    /// The number of blocks to produce on top of the genesis block
+    pub num_blocks: u64,
+    /// The number of blocks at the start of the run that are left out of the statistics, giving the difficulty time to
+    /// converge from the minimum difficulty
+    #[serde(default)]
+    pub warm_up_blocks: u64,
+    pub miners: Vec<MinerConfig>,
+}
+
+impl SimulationConfig {
+    /// Loads a scenario from a TOML file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulationError> {
+        let contents = fs::read_to_string(path)?;
+        let config = toml::from_str::<SimulationConfig>(&contents)
+            .map_err(|err| SimulationError::InvalidConfig(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        if self.num_blocks == 0 {
+            return Err(SimulationError::InvalidConfig(
+                "num_blocks must be greater than zero".to_string(),
+            ));
+        }
+        if self.warm_up_blocks >= self.num_blocks {
+            return Err(SimulationError::InvalidConfig(
+                "warm_up_blocks must be less than num_blocks".to_string(),
+            ));
+        }
+        if self.miners.is_empty() {
+            return Err(SimulationError::InvalidConfig(
+                "At least one miner is required".to_string(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for miner in &self.miners {
+            if !names.insert(miner.name.as_str()) {
+                return Err(SimulationError::InvalidConfig(format!(
+                    "Miner name '{}' is used more than once",
+                    miner.name
+                )));
+            }
+            miner.hash_rate.validate().map_err(|err| {
+                SimulationError::InvalidConfig(format!("Invalid hash rate for miner '{}': {}", miner.name, err))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the proof of work algorithms that are mined in this scenario
+    pub fn pow_algos(&self) -> Vec<PowAlgorithm> {
+        let mut algos = Vec::with_capacity(2);
+        for miner in &self.miners {
+            if !algos.contains(&miner.pow_algo) {
+                algos.push(miner.pow_algo);
+            }
+        }
+        algos
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MinerConfig {
+    pub name: String,
+    pub pow_algo: PowAlgorithm,
+    pub hash_rate: HashRateCurve,
+    #[serde(default)]
+    pub strategy: MinerStrategy,
+}
+
+/// The hash rate of a miner, in hashes per second, as a function of the number of seconds since the genesis block.
+/// A difficulty of `d` requires `d` hashes on average to solve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum HashRateCurve {
+    Constant {
+        hash_rate: f64,
+    },
+    /// Changes linearly from `start` to `end` over `duration` seconds, then stays at `end`
+    Linear {
+        start: f64,
+        end: f64,
+        duration: u64,
+    },
+    /// Changes from `before` to `after` at `at` seconds
+    Step {
+        before: f64,
+        after: f64,
+        at: u64,
+    },
+    /// Alternates between `high` for the first half of every `period` seconds and `low` for the second half, e.g. a
+    /// rented hash rate that is switched on and off
+    Periodic {
+        low: f64,
+        high: f64,
+        period: u64,
+    },
+}
+
+impl HashRateCurve {
+    /// Returns the hash rate at `time` seconds after the genesis block
+    pub fn hash_rate_at(&self, time: f64) -> f64 {
+        match self {
+            HashRateCurve::Constant { hash_rate } => *hash_rate,
+            HashRateCurve::Linear { start, end, duration } => {
+                let duration = *duration as f64;
+                if duration <= 0.0 || time >= duration {
+                    *end
+                } else {
+                    start + (end - start) * time / duration
+                }
+            },
+            HashRateCurve::Step { before, after, at } => {
+                let at = *at as f64;
+                if time < at {
+                    *before
+                } else {
+                    *after
+                }
+            },
+            HashRateCurve::Periodic { low, high, period } => {
+                let period = *period as f64;
+                if time % period < period / 2.0 {
+                    *high
+                } else {
+                    *low
+                }
+            },
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let rates = match self {
+            HashRateCurve::Constant { hash_rate } => vec![*hash_rate],
+            HashRateCurve::Linear { start, end, .. } => vec![*start, *end],
+            HashRateCurve::Step { before, after, .. } => vec![*before, *after],
+            HashRateCurve::Periodic { low, high, period } => {
+                if *period == 0 {
+                    return Err("period must be greater than zero".to_string());
+                }
+                vec![*low, *high]
+            },
+        };
+        if rates.iter().any(|rate| !rate.is_finite() || *rate < 0.0) {
+            return Err("hash rates must be finite and not negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// How a miner chooses what to mine and how it stamps its blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MinerStrategy {
+    /// Mines on the public tip and stamps blocks with the current time
+    Honest,
+    /// Stamps blocks with the latest timestamp allowed by the future time limit
+    FutureTimestamps,
+    /// Stamps blocks with the earliest timestamp allowed by the median timestamp rule
+    PastTimestamps,
+    /// Only mines while the target difficulty is at most `max_difficulty`, like a pool that switches to another coin
+    /// when mining becomes unprofitable
+    ProfitSwitching { max_difficulty: u64 },
+    /// Mines on a private chain and only publishes it when the public chain comes within one block of it, i.e. the
+    /// classic selfish mining attack
+    Selfish,
+}
+
+impl Default for MinerStrategy {
+    fn default() -> Self {
+        MinerStrategy::Honest
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_scenario() {
+        let config = toml::from_str::<SimulationConfig>(
+            r#"
+            seed = 42
+            num_blocks = 10000
+            warm_up_blocks = 500
+
+            [[miners]]
+            name = "sha3 pool"
+            pow_algo = "Sha3"
+            hash_rate = { type = "step", before = 1_000_000.0, after = 3_000_000.0, at = 864000 }
+
+            [[miners]]
+            name = "merge miners"
+            pow_algo = "Monero"
+            hash_rate = { type = "constant", hash_rate = 2000.0 }
+            strategy = { type = "profit_switching", max_difficulty = 1000000 }
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.pow_algos(), vec![PowAlgorithm::Sha3, PowAlgorithm::Monero]);
+        assert_eq!(config.miners[0].strategy, MinerStrategy::Honest);
+        assert_eq!(config.miners[0].hash_rate.hash_rate_at(864_000.0), 3_000_000.0);
+        assert_eq!(config.miners[1].strategy, MinerStrategy::ProfitSwitching {
+            max_difficulty: 1_000_000
+        });
+    }
+
+    #[test]
+    fn it_rejects_duplicate_miners() {
+        let miner = MinerConfig {
+            name: "miner".to_string(),
+            pow_algo: PowAlgorithm::Sha3,
+            hash_rate: HashRateCurve::Constant { hash_rate: 1.0 },
+            strategy: MinerStrategy::Honest,
+        };
+        let config = SimulationConfig {
+            seed: 0,
+            num_blocks: 10,
+            warm_up_blocks: 0,
+            miners: vec![miner.clone(), miner],
+        };
+        assert!(config.validate().is_err());
+    }
+}
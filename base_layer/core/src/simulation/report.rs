@@ -0,0 +1,309 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{
+    consensus::ConsensusManager,
+    proof_of_work::PowAlgorithm,
+    simulation::{SimulatedBlock, SimulationConfig, SimulationResult},
+    transactions::tari_amount::MicroTari,
+};
+
+/// Summary statistics for a series of samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let count = samples.len();
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count as f64;
+        Self {
+            count,
+            mean,
+            std_dev: variance.sqrt(),
+            min: samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max: samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.std_dev.powi(2)
+    }
+
+    /// The standard deviation relative to the mean
+    pub fn coefficient_of_variation(&self) -> f64 {
+        if self.mean == 0.0 {
+            0.0
+        } else {
+            self.std_dev / self.mean
+        }
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "mean {:.1}, std dev {:.1}, min {:.1}, max {:.1} (n = {})",
+            self.mean, self.std_dev, self.min, self.max, self.count
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlgorithmReport {
+    pub pow_algo: PowAlgorithm,
+    pub target_time: u64,
+    pub blocks: usize,
+    /// Time between consecutive blocks of this algorithm, according to their header timestamps
+    pub block_time: Summary,
+    /// Time between consecutive blocks of this algorithm, according to when they were actually found
+    pub solve_time: Summary,
+    pub target_difficulty: Summary,
+    /// The mean absolute change in target difficulty from one block of this algorithm to the next, relative to the
+    /// previous target difficulty
+    pub mean_difficulty_change: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MinerReport {
+    pub name: String,
+    pub pow_algo: PowAlgorithm,
+    pub blocks: usize,
+    /// The miner's share of the main chain blocks
+    pub share: f64,
+    pub orphaned: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmissionReport {
+    pub height: u64,
+    /// The height the chain would be at if every block had taken exactly the target time
+    pub expected_height: u64,
+    pub supply: MicroTari,
+    pub expected_supply: MicroTari,
+}
+
+impl EmissionReport {
+    /// How far the actual supply is ahead (positive) or behind (negative) of the expected supply, as a percentage
+    pub fn deviation_percent(&self) -> f64 {
+        let expected = self.expected_supply.as_u64() as f64;
+        if expected == 0.0 {
+            return 0.0;
+        }
+        (self.supply.as_u64() as f64 - expected) / expected * 100.0
+    }
+}
+
+/// The statistics of a simulation run. Block time and difficulty statistics exclude the warm up blocks, while the
+/// emission covers the whole run.
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub blocks: usize,
+    /// The combined target block time of the proof of work algorithms mined in the scenario
+    pub target_block_time: f64,
+    pub block_time: Summary,
+    pub solve_time: Summary,
+    pub algorithms: Vec<AlgorithmReport>,
+    pub miners: Vec<MinerReport>,
+    pub reorgs: usize,
+    pub max_reorg_depth: u64,
+    pub emission: EmissionReport,
+}
+
+impl SimulationReport {
+    pub fn new(rules: &ConsensusManager, config: &SimulationConfig, result: &SimulationResult) -> Self {
+        let height = result.blocks.last().map(|b| b.height).unwrap_or(0);
+        let constants = rules.consensus_constants(height);
+        let warm_up = config.warm_up_blocks;
+        let all_blocks = result.blocks.iter().collect::<Vec<_>>();
+        let measured = result.blocks.iter().filter(|b| b.height > warm_up).collect::<Vec<_>>();
+
+        let algorithms = config
+            .pow_algos()
+            .into_iter()
+            .map(|pow_algo| {
+                let blocks = result
+                    .blocks
+                    .iter()
+                    .filter(|b| b.pow_algo == pow_algo)
+                    .collect::<Vec<_>>();
+                let difficulties = blocks
+                    .iter()
+                    .filter(|b| b.height > warm_up)
+                    .map(|b| b.target_difficulty as f64)
+                    .collect::<Vec<_>>();
+                let changes = measured_pairs(&blocks, warm_up, |prev, block| {
+                    let prev_difficulty = prev.target_difficulty as f64;
+                    (block.target_difficulty as f64 - prev_difficulty).abs() / prev_difficulty
+                });
+                AlgorithmReport {
+                    pow_algo,
+                    target_time: constants.get_diff_target_block_interval(pow_algo),
+                    blocks: difficulties.len(),
+                    block_time: Summary::from_samples(&measured_pairs(&blocks, warm_up, timestamp_interval)),
+                    solve_time: Summary::from_samples(&measured_pairs(&blocks, warm_up, solve_interval)),
+                    target_difficulty: Summary::from_samples(&difficulties),
+                    mean_difficulty_change: Summary::from_samples(&changes).mean,
+                }
+            })
+            .collect();
+
+        let miners = config
+            .miners
+            .iter()
+            .enumerate()
+            .map(|(index, miner)| {
+                let blocks = measured.iter().filter(|b| b.miner == index).count();
+                MinerReport {
+                    name: miner.name.clone(),
+                    pow_algo: miner.pow_algo,
+                    blocks,
+                    share: if measured.is_empty() {
+                        0.0
+                    } else {
+                        blocks as f64 / measured.len() as f64
+                    },
+                    orphaned: result.orphaned[index],
+                }
+            })
+            .collect();
+
+        let target_block_time = 1.0 /
+            config
+                .pow_algos()
+                .into_iter()
+                .map(|pow_algo| 1.0 / constants.get_diff_target_block_interval(pow_algo) as f64)
+                .sum::<f64>();
+        let elapsed = result.blocks.last().map(|b| b.found_at).unwrap_or(0.0);
+        let expected_height = (elapsed / target_block_time).round() as u64;
+
+        SimulationReport {
+            blocks: measured.len(),
+            target_block_time,
+            block_time: Summary::from_samples(&measured_pairs(&all_blocks, warm_up, timestamp_interval)),
+            solve_time: Summary::from_samples(&measured_pairs(&all_blocks, warm_up, solve_interval)),
+            algorithms,
+            miners,
+            reorgs: result.reorg_depths.len(),
+            max_reorg_depth: result.reorg_depths.iter().copied().max().unwrap_or(0),
+            emission: EmissionReport {
+                height,
+                expected_height,
+                supply: rules.get_total_emission_at(height),
+                expected_supply: rules.get_total_emission_at(expected_height),
+            },
+        }
+    }
+}
+
+/// Applies `f` to each pair of consecutive blocks where the later block is not a warm up block
+fn measured_pairs<F>(blocks: &[&SimulatedBlock], warm_up: u64, f: F) -> Vec<f64>
+where F: Fn(&SimulatedBlock, &SimulatedBlock) -> f64 {
+    blocks
+        .windows(2)
+        .filter(|pair| pair[1].height > warm_up)
+        .map(|pair| f(pair[0], pair[1]))
+        .collect()
+}
+
+fn timestamp_interval(prev: &SimulatedBlock, block: &SimulatedBlock) -> f64 {
+    block.timestamp as f64 - prev.timestamp as f64
+}
+
+fn solve_interval(prev: &SimulatedBlock, block: &SimulatedBlock) -> f64 {
+    block.found_at - prev.found_at
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "Blocks measured   : {}", self.blocks)?;
+        writeln!(f, "Target block time : {:.1}s", self.target_block_time)?;
+        writeln!(
+            f,
+            "Block time        : {} (variance {:.1})",
+            self.block_time,
+            self.block_time.variance()
+        )?;
+        writeln!(f, "Solve time        : {}", self.solve_time)?;
+        writeln!(
+            f,
+            "Reorgs            : {} (max depth {})",
+            self.reorgs, self.max_reorg_depth
+        )?;
+        for algo in &self.algorithms {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{} ({} blocks, target time {}s)",
+                algo.pow_algo, algo.blocks, algo.target_time
+            )?;
+            writeln!(
+                f,
+                "  Block time        : {} (variance {:.1})",
+                algo.block_time,
+                algo.block_time.variance()
+            )?;
+            writeln!(f, "  Solve time        : {}", algo.solve_time)?;
+            writeln!(f, "  Target difficulty : {}", algo.target_difficulty)?;
+            writeln!(
+                f,
+                "  Oscillation       : {:.2}% coefficient of variation, {:.2}% mean change per block",
+                algo.target_difficulty.coefficient_of_variation() * 100.0,
+                algo.mean_difficulty_change * 100.0
+            )?;
+        }
+        writeln!(f)?;
+        for miner in &self.miners {
+            writeln!(
+                f,
+                "{} ({}): {} blocks ({:.2}%), {} orphaned",
+                miner.name,
+                miner.pow_algo,
+                miner.blocks,
+                miner.share * 100.0,
+                miner.orphaned
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Emission at #{}   : {} (expected #{}: {}, {:+.3}%)",
+            self.emission.height,
+            self.emission.supply,
+            self.emission.expected_height,
+            self.emission.expected_supply,
+            self.emission.deviation_percent()
+        )
+    }
+}
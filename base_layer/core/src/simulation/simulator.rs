@@ -0,0 +1,502 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cmp, cmp::Ordering, collections::HashMap, io, io::Write};
+
+use log::*;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use tari_common_types::types::FixedHash;
+use tari_utilities::epoch_time::EpochTime;
+
+use crate::{
+    blocks::{BlockHeader, BlockHeaderAccumulatedData, ChainHeader},
+    consensus::ConsensusManager,
+    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithm},
+    simulation::{MinerStrategy, SimulationConfig, SimulationError},
+    validation::helpers::calc_median_timestamp,
+};
+
+const LOG_TARGET: &str = "c::simulation::simulator";
+
+/// If no miner is active for this long, the simulation is aborted
+const MAX_IDLE_TIME: f64 = 30.0 * 24.0 * 60.0 * 60.0;
+/// How far the clock is moved forward while no miner is active
+const IDLE_TIME_STEP: f64 = 60.0;
+
+/// A block on the simulated main chain
+#[derive(Debug, Clone)]
+pub struct SimulatedBlock {
+    pub height: u64,
+    pub pow_algo: PowAlgorithm,
+    /// The index of the miner in the scenario
+    pub miner: usize,
+    /// The timestamp in the block header
+    pub timestamp: u64,
+    /// The simulated time at which the block was found, in seconds since the genesis block
+    pub found_at: f64,
+    pub target_difficulty: u64,
+}
+
+/// The outcome of a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    /// The miner names, in scenario order
+    pub miners: Vec<String>,
+    pub genesis_timestamp: u64,
+    /// The main chain blocks, excluding the genesis block
+    pub blocks: Vec<SimulatedBlock>,
+    /// The number of each miner's blocks that did not end up in the main chain
+    pub orphaned: Vec<u64>,
+    /// The depth of every reorg, in blocks removed from the main chain
+    pub reorg_depths: Vec<u64>,
+}
+
+impl SimulationResult {
+    /// Writes the main chain as CSV, one block per line
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "height,pow_algo,miner,timestamp,found_at,target_difficulty")?;
+        for block in &self.blocks {
+            writeln!(
+                writer,
+                "{},{},{},{},{:.3},{}",
+                block.height,
+                block.pow_algo,
+                self.miners[block.miner],
+                block.timestamp,
+                block.found_at,
+                block.target_difficulty
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Simulates block production for a scenario under the consensus rules of a network.
+///
+/// The target difficulty, timestamp rules, emission and chain selection are all calculated by the same code the base
+/// node uses, so the effect of a consensus change can be seen by running the same scenario before and after making
+/// it. Solve times are drawn from an exponential distribution with a mean of `difficulty / hash_rate`. A miner's hash
+/// rate is sampled each time a block is found and is treated as constant until the next block.
+pub struct ChainSimulator {
+    rules: ConsensusManager,
+    config: SimulationConfig,
+    rng: StdRng,
+    /// The simulated time, in seconds since the genesis block
+    clock: f64,
+    genesis_timestamp: u64,
+    /// The public main chain, starting at the genesis block
+    headers: Vec<ChainHeader>,
+    blocks: Vec<SimulatedBlock>,
+    /// The withheld chain of each selfish miner
+    private_branches: Vec<Option<PrivateBranch>>,
+    orphaned: Vec<u64>,
+    reorg_depths: Vec<u64>,
+}
+
+struct PrivateBranch {
+    fork_height: u64,
+    fork_hash: FixedHash,
+    headers: Vec<ChainHeader>,
+    blocks: Vec<SimulatedBlock>,
+}
+
+impl ChainSimulator {
+    pub fn new(rules: ConsensusManager, config: SimulationConfig) -> Result<Self, SimulationError> {
+        config.validate()?;
+        for pow_algo in config.pow_algos() {
+            if rules.consensus_constants(0).get_diff_target_block_interval(pow_algo) == 0 {
+                return Err(SimulationError::PowAlgorithmNotAccepted(pow_algo));
+            }
+        }
+        let genesis = rules.get_genesis_block().to_chain_header();
+        let num_miners = config.miners.len();
+        Ok(Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            clock: 0.0,
+            genesis_timestamp: genesis.timestamp(),
+            headers: vec![genesis],
+            blocks: Vec::new(),
+            private_branches: (0..num_miners).map(|_| None).collect(),
+            orphaned: vec![0; num_miners],
+            reorg_depths: Vec::new(),
+            rules,
+            config,
+        })
+    }
+
+    /// Produces blocks until the main chain reaches the scenario's `num_blocks`
+    pub fn run(mut self) -> Result<SimulationResult, SimulationError> {
+        let mut idle_since = None;
+        while self.tip_height() < self.config.num_blocks {
+            match self.next_block() {
+                Some((miner, solve_time, target_difficulty)) => {
+                    idle_since = None;
+                    self.clock += solve_time;
+                    self.add_block(miner, target_difficulty)?;
+                },
+                None => {
+                    let idle_since = *idle_since.get_or_insert(self.clock);
+                    if self.clock - idle_since > MAX_IDLE_TIME {
+                        return Err(SimulationError::NoActiveMiners(self.clock as u64));
+                    }
+                    self.clock += IDLE_TIME_STEP;
+                },
+            }
+        }
+        // Publish any private chain that would win, as the selfish miner would
+        for miner in 0..self.config.miners.len() {
+            self.resolve_private_branch(miner);
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Simulated {} blocks in {:.0} seconds with {} reorg(s)",
+            self.blocks.len(),
+            self.clock,
+            self.reorg_depths.len()
+        );
+
+        Ok(SimulationResult {
+            miners: self.config.miners.iter().map(|m| m.name.clone()).collect(),
+            genesis_timestamp: self.genesis_timestamp,
+            blocks: self.blocks,
+            orphaned: self.orphaned,
+            reorg_depths: self.reorg_depths,
+        })
+    }
+
+    fn tip_height(&self) -> u64 {
+        self.headers.len() as u64 - 1
+    }
+
+    /// Draws a solve time for every active miner and returns the (miner, solve time, target difficulty) of the first
+    /// miner to find a block, or `None` if no miner is active.
+    fn next_block(&mut self) -> Option<(usize, f64, Difficulty)> {
+        let mut target_difficulties = HashMap::new();
+        let mut winner: Option<(usize, f64, Difficulty)> = None;
+        for (index, miner) in self.config.miners.iter().enumerate() {
+            let hash_rate = miner.hash_rate.hash_rate_at(self.clock);
+            if hash_rate <= 0.0 {
+                continue;
+            }
+            let branch = self.private_branches[index].as_ref();
+            let tip_hash = *self
+                .chain_rev(branch)
+                .next()
+                .expect("chain always contains the genesis block")
+                .hash();
+            let target_difficulty = *target_difficulties
+                .entry((tip_hash, miner.pow_algo))
+                .or_insert_with(|| self.target_difficulty(branch, miner.pow_algo));
+            if let MinerStrategy::ProfitSwitching { max_difficulty } = miner.strategy {
+                if target_difficulty.as_u64() > max_difficulty {
+                    continue;
+                }
+            }
+            // Inverse transform sampling of the exponential distribution. 1 - U is in (0, 1], so ln never sees 0.
+            let u: f64 = self.rng.gen();
+            let solve_time = -(1.0 - u).ln() * target_difficulty.as_u64() as f64 / hash_rate;
+            if winner.map(|(_, best, _)| solve_time < best).unwrap_or(true) {
+                winner = Some((index, solve_time, target_difficulty));
+            }
+        }
+        winner
+    }
+
+    /// Iterates over the chain a miner is mining on, from its tip back to the genesis block
+    fn chain_rev<'a>(&'a self, branch: Option<&'a PrivateBranch>) -> impl Iterator<Item = &'a ChainHeader> + 'a {
+        let (public, private) = match branch {
+            Some(branch) => (&self.headers[..=branch.fork_height as usize], branch.headers.as_slice()),
+            None => (self.headers.as_slice(), &[][..]),
+        };
+        private.iter().rev().chain(public.iter().rev())
+    }
+
+    /// Calculates the target difficulty for the next block in the same way as
+    /// [fetch_target_difficulty_for_next_block](crate::chain_storage::fetch_target_difficulty_for_next_block)
+    fn target_difficulty(&self, branch: Option<&PrivateBranch>, pow_algo: PowAlgorithm) -> Difficulty {
+        let mut chain = self.chain_rev(branch).peekable();
+        let height = chain.peek().expect("chain always contains the genesis block").height() + 1;
+        let mut target_difficulties = self.rules.new_target_difficulty(pow_algo, height);
+        for header in chain {
+            if target_difficulties.is_full() {
+                break;
+            }
+            if header.header().pow.pow_algo == pow_algo {
+                target_difficulties.add_front(header.header().timestamp(), header.accumulated_data().target_difficulty);
+            }
+        }
+        let constants = self.rules.consensus_constants(height);
+        target_difficulties.calculate(
+            constants.min_pow_difficulty(pow_algo),
+            constants.max_pow_difficulty(pow_algo),
+        )
+    }
+
+    /// The earliest timestamp the header validator accepts for the next block
+    fn min_timestamp(&self, branch: Option<&PrivateBranch>, height: u64) -> u64 {
+        let count = self.rules.consensus_constants(height).get_median_timestamp_count();
+        let mut timestamps = self
+            .chain_rev(branch)
+            .take(count)
+            .map(|header| header.header().timestamp())
+            .collect::<Vec<EpochTime>>();
+        // The validator uses the headers in height order
+        timestamps.reverse();
+        calc_median_timestamp(&timestamps).as_u64()
+    }
+
+    fn add_block(&mut self, miner: usize, target_difficulty: Difficulty) -> Result<(), SimulationError> {
+        let miner_config = &self.config.miners[miner];
+        let pow_algo = miner_config.pow_algo;
+        let strategy = miner_config.strategy.clone();
+        let nonce = self.rng.next_u64();
+        let branch = self.private_branches[miner].as_ref();
+        let tip = self
+            .chain_rev(branch)
+            .next()
+            .expect("chain always contains the genesis block");
+        let height = tip.height() + 1;
+
+        let now = self.genesis_timestamp + self.clock as u64;
+        let min_timestamp = self.min_timestamp(branch, height);
+        let timestamp = match strategy {
+            MinerStrategy::FutureTimestamps => now + self.rules.consensus_constants(height).future_time_limit(),
+            MinerStrategy::PastTimestamps => min_timestamp,
+            MinerStrategy::Honest | MinerStrategy::ProfitSwitching { .. } | MinerStrategy::Selfish => now,
+        };
+        let timestamp = cmp::max(timestamp, min_timestamp);
+
+        let mut header = BlockHeader::from_previous(tip.header());
+        header.timestamp = timestamp.into();
+        header.pow.pow_algo = pow_algo;
+        header.nonce = nonce;
+        let achieved_target = AchievedTargetDifficulty::try_construct(pow_algo, target_difficulty, target_difficulty)
+            .expect("achieved difficulty is equal to the target difficulty");
+        let accumulated_data = BlockHeaderAccumulatedData::builder(tip.accumulated_data())
+            .with_hash(header.hash())
+            .with_achieved_target_difficulty(achieved_target)
+            .with_total_kernel_offset(Default::default())
+            .build()?;
+        let header =
+            ChainHeader::try_construct(header, accumulated_data).expect("accumulated data hash is the header hash");
+        let block = SimulatedBlock {
+            height,
+            pow_algo,
+            miner,
+            timestamp,
+            found_at: self.clock,
+            target_difficulty: target_difficulty.as_u64(),
+        };
+        trace!(
+            target: LOG_TARGET,
+            "{} found block #{} ({}) at {:.0}s",
+            self.config.miners[miner].name,
+            height,
+            pow_algo,
+            self.clock
+        );
+
+        if strategy == MinerStrategy::Selfish {
+            let tip_height = self.tip_height();
+            let tip_hash = *self.headers[self.headers.len() - 1].hash();
+            let branch = self.private_branches[miner].get_or_insert_with(|| PrivateBranch {
+                fork_height: tip_height,
+                fork_hash: tip_hash,
+                headers: Vec::new(),
+                blocks: Vec::new(),
+            });
+            branch.headers.push(header);
+            branch.blocks.push(block);
+        } else {
+            self.headers.push(header);
+            self.blocks.push(block);
+            for selfish_miner in 0..self.config.miners.len() {
+                self.resolve_private_branch(selfish_miner);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decides what a selfish miner does with its private chain after the public chain has changed. The private chain
+    /// is published if it is stronger than the public chain and at most one block ahead of it, abandoned if it is not
+    /// stronger, and otherwise withheld.
+    fn resolve_private_branch(&mut self, miner: usize) {
+        let branch = match self.private_branches[miner].take() {
+            Some(branch) => branch,
+            None => return,
+        };
+        let fork_is_public = self
+            .headers
+            .get(branch.fork_height as usize)
+            .map(|header| *header.hash() == branch.fork_hash)
+            .unwrap_or(false);
+        let private_tip = branch.headers.last().expect("private branches are never empty");
+        let public_tip = &self.headers[self.headers.len() - 1];
+        let ordering = if fork_is_public {
+            self.rules.chain_strength_comparer().compare(private_tip, public_tip)
+        } else {
+            Ordering::Less
+        };
+        let is_about_to_be_caught = private_tip.height() <= public_tip.height() + 1;
+
+        match ordering {
+            Ordering::Greater if is_about_to_be_caught => self.publish(miner, branch),
+            Ordering::Greater => self.private_branches[miner] = Some(branch),
+            Ordering::Less | Ordering::Equal => self.orphaned[miner] += branch.headers.len() as u64,
+        }
+    }
+
+    fn publish(&mut self, miner: usize, branch: PrivateBranch) {
+        let fork_index = branch.fork_height as usize;
+        let depth = self.headers.len() - 1 - fork_index;
+        for block in self.blocks.drain(fork_index..) {
+            self.orphaned[block.miner] += 1;
+        }
+        self.headers.truncate(fork_index + 1);
+        self.headers.extend(branch.headers);
+        self.blocks.extend(branch.blocks);
+        if depth > 0 {
+            debug!(
+                target: LOG_TARGET,
+                "{} published a private chain, causing a reorg of depth {}", self.config.miners[miner].name, depth
+            );
+            self.reorg_depths.push(depth as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+
+    use super::*;
+    use crate::simulation::{HashRateCurve, MinerConfig, SimulationReport};
+
+    fn miner(name: &str, pow_algo: PowAlgorithm, hash_rate: f64, strategy: MinerStrategy) -> MinerConfig {
+        MinerConfig {
+            name: name.to_string(),
+            pow_algo,
+            hash_rate: HashRateCurve::Constant { hash_rate },
+            strategy,
+        }
+    }
+
+    fn run(config: SimulationConfig) -> (ConsensusManager, SimulationResult) {
+        let rules = ConsensusManager::builder(Network::Esmeralda).build();
+        let result = ChainSimulator::new(rules.clone(), config).unwrap().run().unwrap();
+        (rules, result)
+    }
+
+    fn two_algo_config(seed: u64) -> SimulationConfig {
+        SimulationConfig {
+            seed,
+            num_blocks: 2000,
+            warm_up_blocks: 500,
+            miners: vec![
+                miner("sha3", PowAlgorithm::Sha3, 1_000_000.0, MinerStrategy::Honest),
+                miner("monero", PowAlgorithm::Monero, 1_500.0, MinerStrategy::Honest),
+            ],
+        }
+    }
+
+    #[test]
+    fn it_is_deterministic() {
+        let (_, a) = run(two_algo_config(1));
+        let (_, b) = run(two_algo_config(1));
+        let (_, c) = run(two_algo_config(2));
+        let summary = |result: &SimulationResult| {
+            result
+                .blocks
+                .iter()
+                .map(|b| (b.timestamp, b.target_difficulty, b.miner))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(a.blocks.len(), 2000);
+        assert_eq!(summary(&a), summary(&b));
+        assert_ne!(summary(&a), summary(&c));
+    }
+
+    #[test]
+    fn it_converges_to_the_target_block_time() {
+        let config = two_algo_config(1);
+        let (rules, result) = run(config.clone());
+        let report = SimulationReport::new(&rules, &config, &result);
+        assert_eq!(report.blocks, 1500);
+        assert_eq!(report.reorgs, 0);
+        for algo in &report.algorithms {
+            let target_time = algo.target_time as f64;
+            assert!(
+                (algo.solve_time.mean - target_time).abs() < target_time * 0.15,
+                "{} mean solve time {} is too far from the target time {}",
+                algo.pow_algo,
+                algo.solve_time.mean,
+                target_time
+            );
+        }
+    }
+
+    #[test]
+    fn it_keeps_timestamps_valid() {
+        let config = SimulationConfig {
+            seed: 1,
+            num_blocks: 500,
+            warm_up_blocks: 0,
+            miners: vec![
+                miner("honest", PowAlgorithm::Sha3, 1_000_000.0, MinerStrategy::Honest),
+                miner(
+                    "future",
+                    PowAlgorithm::Sha3,
+                    1_000_000.0,
+                    MinerStrategy::FutureTimestamps,
+                ),
+                miner("past", PowAlgorithm::Monero, 1_500.0, MinerStrategy::PastTimestamps),
+            ],
+        };
+        let (rules, result) = run(config);
+        let ftl = rules.consensus_constants(0).future_time_limit();
+        for block in &result.blocks {
+            let found_at = result.genesis_timestamp + block.found_at as u64;
+            assert!(block.timestamp <= found_at + ftl);
+        }
+        assert!(result
+            .blocks
+            .iter()
+            .any(|b| b.miner == 1 && b.timestamp == result.genesis_timestamp + b.found_at as u64 + ftl));
+    }
+
+    #[test]
+    fn selfish_mining_causes_reorgs() {
+        let config = SimulationConfig {
+            seed: 1,
+            num_blocks: 1000,
+            warm_up_blocks: 0,
+            miners: vec![
+                miner("honest", PowAlgorithm::Sha3, 550_000.0, MinerStrategy::Honest),
+                miner("selfish", PowAlgorithm::Sha3, 450_000.0, MinerStrategy::Selfish),
+            ],
+        };
+        let (_, result) = run(config);
+        assert!(!result.reorg_depths.is_empty());
+        assert!(result.orphaned[0] > 0);
+        assert!(result.blocks.iter().any(|b| b.miner == 1));
+    }
+}
@@ -22,6 +22,7 @@
 
 use std::{
     collections::HashMap,
+    env,
     fs,
     ops::Deref,
     path::{Path, PathBuf},
@@ -51,7 +52,9 @@ use crate::{
     },
     chain_storage::{
         create_lmdb_database,
+        create_sled_database,
         BlockAddResult,
+        BlockUtxoStats,
         BlockchainBackend,
         BlockchainDatabase,
         BlockchainDatabaseConfig,
@@ -62,10 +65,12 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
-        LMDBDatabase,
         MmrTree,
         PrunedOutput,
         Reorg,
+        ScriptIndexBlock,
+        ScriptIndexKey,
+        SledConfig,
         UtxoMinedInfo,
         Validators,
     },
@@ -150,26 +155,28 @@ pub fn create_test_db() -> TempDatabase {
     TempDatabase::new()
 }
 
+/// The environment variable used to select the backend of a [TempDatabase]. Set to `sled` to run tests against the
+/// sled backend, otherwise LMDB is used.
+pub const TEST_CHAIN_BACKEND_ENV_VAR: &str = "TARI_TEST_CHAIN_BACKEND";
+
 pub struct TempDatabase {
     path: PathBuf,
-    db: Option<LMDBDatabase>,
+    db: Option<Box<dyn BlockchainBackend>>,
     delete_on_drop: bool,
 }
 
 impl TempDatabase {
     pub fn new() -> Self {
-        let temp_path = create_temporary_data_path();
-
-        Self {
-            db: Some(create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap()),
-            path: temp_path,
-            delete_on_drop: true,
-        }
+        Self::from_path(create_temporary_data_path())
     }
 
     pub fn from_path<P: AsRef<Path>>(temp_path: P) -> Self {
+        let db: Box<dyn BlockchainBackend> = match env::var(TEST_CHAIN_BACKEND_ENV_VAR).as_deref() {
+            Ok("sled") => Box::new(create_sled_database(&temp_path, SledConfig::default()).unwrap()),
+            _ => Box::new(create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap()),
+        };
         Self {
-            db: Some(create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap()),
+            db: Some(db),
             path: temp_path.as_ref().to_path_buf(),
             delete_on_drop: true,
         }
@@ -188,16 +195,16 @@ impl Default for TempDatabase {
 }
 
 impl Deref for TempDatabase {
-    type Target = LMDBDatabase;
+    type Target = dyn BlockchainBackend;
 
     fn deref(&self) -> &Self::Target {
-        self.db.as_ref().unwrap()
+        &**self.db.as_ref().unwrap()
     }
 }
 
 impl Drop for TempDatabase {
     fn drop(&mut self) {
-        // force a drop on the LMDB db
+        // force a drop on the db
         self.db = None;
         if self.delete_on_drop && Path::new(&self.path).exists() {
             fs::remove_dir_all(&self.path).expect("Could not delete temporary file");
@@ -280,6 +287,16 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_kernel_by_excess_sig(excess_sig)
     }
 
+    fn fetch_block_hash_by_kernel_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_block_hash_by_kernel_excess_sig(excess_sig)
+    }
+
     fn fetch_utxos_in_block(
         &self,
         header_hash: &HashOutput,
@@ -342,6 +359,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_chain_metadata()
     }
 
+    fn fetch_kernel_pruned_height(&self) -> Result<u64, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_kernel_pruned_height()
+    }
+
     fn utxo_count(&self) -> Result<usize, ChainStorageError> {
         self.db.as_ref().unwrap().utxo_count()
     }
@@ -410,6 +431,35 @@ impl BlockchainBackend for TempDatabase {
     fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_all_reorgs()
     }
+
+    fn fetch_utxo_stats(&self, start_height: u64, end_height: u64) -> Result<Vec<BlockUtxoStats>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_utxo_stats(start_height, end_height)
+    }
+
+    fn fetch_last_utxo_stats(&self) -> Result<Option<BlockUtxoStats>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_last_utxo_stats()
+    }
+
+    fn fetch_script_index_block(&self, height: u64) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_script_index_block(height)
+    }
+
+    fn fetch_last_script_index_block(&self) -> Result<Option<ScriptIndexBlock>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_last_script_index_block()
+    }
+
+    fn fetch_outputs_by_script_index(
+        &self,
+        key: &ScriptIndexKey,
+        start_height: u64,
+        end_height: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, HashOutput)>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_outputs_by_script_index(key, start_height, end_height, limit)
+    }
 }
 
 pub fn create_chained_blocks<T: Into<BlockSpecs>>(
@@ -575,6 +625,27 @@ impl TestBlockchain {
         self.chain.iter().find(|(n, _)| *n == name).map(|(_, ch)| ch.clone())
     }
 
+    /// Returns the blocks from the genesis block up to and including the named block, in ascending height order
+    pub fn get_branch(&self, tip_name: &'static str) -> Vec<Arc<Block>> {
+        let mut block = self
+            .get_block_by_name(tip_name)
+            .ok_or_else(|| format!("Block not found with name '{}'", tip_name))
+            .unwrap();
+        let mut branch = vec![block.to_arc_block()];
+        while block.header().height > 0 {
+            block = self
+                .chain
+                .iter()
+                .find(|(_, b)| *b.hash() == block.header().prev_hash)
+                .map(|(_, b)| b.clone())
+                .ok_or_else(|| format!("Parent of block {} not found", block.header().height))
+                .unwrap();
+            branch.push(block.to_arc_block());
+        }
+        branch.reverse();
+        branch
+    }
+
     pub fn get_tip_block(&self) -> (&'static str, Arc<ChainBlock>) {
         self.chain.last().cloned().unwrap()
     }
@@ -451,7 +451,9 @@ impl AggregateBody {
         let mut input_keys = PublicKey::default();
         let prev_hash: [u8; 32] = prev_header.unwrap_or_default().as_slice().try_into().unwrap_or([0; 32]);
         for input in &self.inputs {
-            let context = ScriptContext::new(height, &prev_hash, input.commitment()?);
+            // The mined height of the spent output is not known here, so relative height checks are evaluated as if the
+            // output was mined in the genesis block. These are checked again against the chain by the input validators.
+            let context = ScriptContext::new(height, &prev_hash, input.commitment()?, 0);
             input_keys = input_keys + input.run_and_verify_script(factory, Some(context))?;
         }
 
@@ -38,7 +38,7 @@ pub use transaction::Transaction;
 pub use transaction_builder::TransactionBuilder;
 pub use transaction_input::{SpentOutput, TransactionInput};
 pub use transaction_input_version::TransactionInputVersion;
-pub use transaction_kernel::TransactionKernel;
+pub use transaction_kernel::{batch_verify_kernel_signatures, TransactionKernel};
 pub use transaction_kernel_version::TransactionKernelVersion;
 pub use transaction_output::TransactionOutput;
 pub use transaction_output_version::TransactionOutputVersion;
@@ -177,7 +177,7 @@ mod test {
             output_type: OutputType::Standard,
             maturity: u64::MAX,
             metadata: vec![1; 1024],
-            sidechain_features: Some(Box::new(SideChainFeatures {})),
+            sidechain_features: Some(Box::new(SideChainFeatures::default())),
         }
     }
 
@@ -23,16 +23,40 @@
 use std::io::{Error, Read, Write};
 
 use serde::{Deserialize, Serialize};
+use tari_common_types::types::{FixedHash, PublicKey};
 
-use crate::consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized};
+use crate::consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, MaxSizeVec};
 
-#[derive(Debug, Clone, Hash, PartialEq, Deserialize, Serialize, Eq)]
-pub struct SideChainFeatures {}
+/// The largest committee that can be decoded. The consensus limit, which may be lower, is given by
+/// `ConsensusConstants::max_sidechain_committee_size`.
+pub const MAX_DECODED_COMMITTEE_SIZE: usize = 256;
 
-impl SideChainFeatures {}
+/// Sidechain data attached to an output
+#[derive(Debug, Clone, Hash, PartialEq, Deserialize, Serialize, Eq, Default)]
+pub struct SideChainFeatures {
+    /// The id of the contract or asset that this output belongs to
+    pub contract_id: FixedHash,
+    /// The Merkle root of the latest sidechain checkpoint
+    pub checkpoint_merkle_root: FixedHash,
+    /// The public keys of the committee members responsible for the sidechain
+    pub committee: Vec<PublicKey>,
+}
+
+impl SideChainFeatures {
+    pub fn new(contract_id: FixedHash, checkpoint_merkle_root: FixedHash, committee: Vec<PublicKey>) -> Self {
+        Self {
+            contract_id,
+            checkpoint_merkle_root,
+            committee,
+        }
+    }
+}
 
 impl ConsensusEncoding for SideChainFeatures {
-    fn consensus_encode<W: Write>(&self, _writer: &mut W) -> Result<(), Error> {
+    fn consensus_encode<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.contract_id.consensus_encode(writer)?;
+        self.checkpoint_merkle_root.consensus_encode(writer)?;
+        self.committee.consensus_encode(writer)?;
         Ok(())
     }
 }
@@ -40,19 +64,44 @@ impl ConsensusEncoding for SideChainFeatures {
 impl ConsensusEncodingSized for SideChainFeatures {}
 
 impl ConsensusDecoding for SideChainFeatures {
-    fn consensus_decode<R: Read>(_reader: &mut R) -> Result<Self, Error> {
-        Ok(Self {})
+    fn consensus_decode<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        // Changing the order of these operations is consensus breaking
+        let contract_id = FixedHash::consensus_decode(reader)?;
+        let checkpoint_merkle_root = FixedHash::consensus_decode(reader)?;
+        let committee = MaxSizeVec::<PublicKey, MAX_DECODED_COMMITTEE_SIZE>::consensus_decode(reader)?;
+        Ok(Self {
+            contract_id,
+            checkpoint_merkle_root,
+            committee: committee.into(),
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
     use super::*;
-    use crate::consensus::check_consensus_encoding_correctness;
+    use crate::consensus::{check_consensus_encoding_correctness, ToConsensusBytes};
+
+    fn make_features(committee_size: usize) -> SideChainFeatures {
+        let committee = (0..committee_size)
+            .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+            .collect();
+        SideChainFeatures::new(FixedHash::from([1u8; 32]), FixedHash::from([2u8; 32]), committee)
+    }
 
     #[test]
     fn consensus_encoding() {
-        let features = SideChainFeatures {};
-        check_consensus_encoding_correctness(features).unwrap();
+        check_consensus_encoding_correctness(SideChainFeatures::default()).unwrap();
+        check_consensus_encoding_correctness(make_features(3)).unwrap();
+        check_consensus_encoding_correctness(make_features(MAX_DECODED_COMMITTEE_SIZE)).unwrap();
+    }
+
+    #[test]
+    fn it_fails_to_decode_an_oversized_committee() {
+        let bytes = make_features(MAX_DECODED_COMMITTEE_SIZE + 1).to_consensus_bytes();
+        SideChainFeatures::consensus_decode(&mut bytes.as_slice()).unwrap_err();
     }
 }
@@ -38,7 +38,12 @@ use crate::{
         tari_amount::{uT, MicroTari, T},
         test_helpers,
         test_helpers::{create_sender_transaction_protocol_with, create_unblinded_txos, TestParams, UtxoTestParams},
-        transaction_components::{transaction_output::batch_verify_range_proofs, EncryptedValue, OutputFeatures},
+        transaction_components::{
+            batch_verify_kernel_signatures,
+            transaction_output::batch_verify_range_proofs,
+            EncryptedValue,
+            OutputFeatures,
+        },
         transaction_protocol::TransactionProtocolError,
         CryptoFactories,
     },
@@ -194,6 +199,26 @@ fn range_proof_verification_batch() {
     assert!(batch_verify_range_proofs(&factories.range_proof, &outputs).is_err());
 }
 
+#[test]
+fn kernel_signature_verification_batch() {
+    let mut kernels = (0..3)
+        .map(|_| {
+            let (tx, _, _) = test_helpers::create_tx(5000.into(), 3.into(), 1, 1, 1, 1, Default::default());
+            tx.body.kernels()[0].clone()
+        })
+        .collect::<Vec<_>>();
+    assert!(kernels.iter().all(|kernel| kernel.verify_signature().is_ok()));
+
+    // The batch should pass
+    assert!(batch_verify_kernel_signatures(&kernels.iter().collect::<Vec<_>>()).is_ok());
+    assert!(batch_verify_kernel_signatures(&[]).is_ok());
+
+    // The batch should fail after tampering with a single kernel
+    kernels[1].lock_height += 1;
+    assert!(kernels[1].verify_signature().is_err());
+    assert!(batch_verify_kernel_signatures(&kernels.iter().collect::<Vec<_>>()).is_err());
+}
+
 #[test]
 fn sender_signature_verification() {
     let test_params = TestParams::new();
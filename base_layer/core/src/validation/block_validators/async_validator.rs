@@ -56,6 +56,17 @@ use crate::{
     },
 };
 
+/// The checks that are skipped by a [BlockValidator] in assume-valid mode. Header PoW, MMR roots, block weight, UTXO
+/// set and balance (kernel sum and coinbase) checks are always performed.
+pub const ASSUME_VALID_SKIPPED_CHECKS: &[&str] = &[
+    "range proofs",
+    "output metadata signatures",
+    "kernel signatures",
+    "input scripts",
+    "script offset",
+    "covenants",
+];
+
 /// This validator checks whether a block satisfies consensus rules.
 /// It implements two validators: one for the `BlockHeader` and one for `Block`. The `Block` validator ONLY validates
 /// the block body using the header. It is assumed that the `BlockHeader` has already been validated.
@@ -66,6 +77,8 @@ pub struct BlockValidator<B> {
     db: AsyncBlockchainDb<B>,
     concurrency: usize,
     bypass_range_proof_verification: bool,
+    skip_proofs_and_signatures: bool,
+    skip_scripts_and_covenants: bool,
 }
 
 impl<B: BlockchainBackend + 'static> BlockValidator<B> {
@@ -82,9 +95,26 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             db,
             concurrency,
             bypass_range_proof_verification,
+            skip_proofs_and_signatures: false,
+            skip_scripts_and_covenants: false,
         }
     }
 
+    /// Skip the [ASSUME_VALID_SKIPPED_CHECKS] for every block given to this validator. This must only be used for
+    /// blocks that are ancestors of a trusted block.
+    pub fn assume_valid(mut self) -> Self {
+        self.skip_proofs_and_signatures = true;
+        self.skip_scripts_and_covenants = true;
+        self
+    }
+
+    /// Skip range proof, output metadata signature and kernel signature checks. This must only be used for blocks that
+    /// have already been checked by a [BlockVerificationPool](super::BlockVerificationPool).
+    pub fn without_proof_and_signature_checks(mut self) -> Self {
+        self.skip_proofs_and_signatures = true;
+        self
+    }
+
     async fn check_mmr_roots(&self, block: Block) -> Result<Block, ValidationError> {
         let (block, mmr_roots) = self.db.calculate_mmr_roots(block).await?;
         helpers::check_mmr_roots(&block.header, &mmr_roots)?;
@@ -122,11 +152,13 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             outputs_result.coinbase(),
         )?;
 
-        helpers::check_script_offset(
-            &valid_header,
-            &outputs_result.aggregate_offset_pubkey,
-            &inputs_result.aggregate_input_key,
-        )?;
+        if !self.skip_scripts_and_covenants {
+            helpers::check_script_offset(
+                &valid_header,
+                &outputs_result.aggregate_offset_pubkey,
+                &inputs_result.aggregate_input_key,
+            )?;
+        }
 
         helpers::check_kernel_sum(
             &self.factories.commitment,
@@ -141,7 +173,9 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             AggregateBody::new_sorted_unchecked(inputs_result.inputs, outputs_result.outputs, kernels_result.kernels),
         );
 
-        helpers::validate_covenants(&block)?;
+        if !self.skip_scripts_and_covenants {
+            helpers::validate_covenants(&block)?;
+        }
 
         Ok(block)
     }
@@ -160,6 +194,7 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             .commitment
             .commit_value(&total_kernel_offset, total_reward.as_u64());
         let db = self.db.inner().clone();
+        let skip_signatures = self.skip_proofs_and_signatures;
         task::spawn_blocking(move || {
             let db = db.db_read_access()?;
             let timer = Instant::now();
@@ -175,7 +210,9 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                     return Err(ValidationError::UnsortedOrDuplicateKernel);
                 }
 
-                kernel.verify_signature()?;
+                if !skip_signatures {
+                    kernel.verify_signature()?;
+                }
 
                 if kernel.is_coinbase() {
                     if coinbase_index.is_some() {
@@ -188,16 +225,14 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                     coinbase_index = Some(i);
                 }
 
-                if let Some((db_kernel, header_hash)) = db.fetch_kernel_by_excess_sig(&kernel.excess_sig)? {
+                if let Some(header_hash) = db.fetch_block_hash_by_kernel_excess_sig(&kernel.excess_sig)? {
                     let msg = format!(
                         "Block contains kernel excess: {} which matches already existing excess signature in chain \
-                         database block hash: {}. Existing kernel excess: {}, excess sig nonce: {}, excess signature: \
-                         {}",
+                         database block hash: {}. Excess sig nonce: {}, excess signature: {}",
                         kernel.excess.to_hex(),
                         header_hash.to_hex(),
-                        db_kernel.excess.to_hex(),
-                        db_kernel.excess_sig.get_public_nonce().to_hex(),
-                        db_kernel.excess_sig.get_signature().to_hex(),
+                        kernel.excess_sig.get_public_nonce().to_hex(),
+                        kernel.excess_sig.get_signature().to_hex(),
                     );
                     warn!(target: LOG_TARGET, "{}", msg);
                     return Err(ValidationError::ConsensusError(msg));
@@ -248,6 +283,7 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         let db = self.db.inner().clone();
         let prev_hash: [u8; 32] = header.prev_hash.as_slice().try_into().unwrap_or([0; 32]);
         let height = header.height;
+        let skip_scripts = self.skip_scripts_and_covenants;
         task::spawn_blocking(move || {
             let timer = Instant::now();
             let mut aggregate_input_key = PublicKey::default();
@@ -264,10 +300,11 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
 
             for input in &mut inputs {
                 // Read the spent_output for this compact input
-                if input.is_compact() {
+                let utxo_mined_height = if input.is_compact() {
                     let output_mined_info = db
                         .fetch_output(&input.output_hash())?
                         .ok_or(ValidationError::TransactionInputSpentOutputMissing)?;
+                    let mined_height = output_mined_info.mined_height;
 
                     match output_mined_info.output {
                         PrunedOutput::Pruned { .. } => {
@@ -286,7 +323,10 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                             );
                         },
                     }
-                }
+                    mined_height
+                } else {
+                    helpers::fetch_spent_output_mined_height(&*db, input, block_height)?
+                };
 
                 if !input.is_mature_at(block_height)? {
                     warn!(
@@ -319,10 +359,12 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                     Err(e) => return Err(ValidationError::from(e)),
                 };
                 if not_found_inputs.is_empty() {
-                    let context = ScriptContext::new(height, &prev_hash, commitment);
-                    // lets count up the input script public keys
-                    aggregate_input_key =
-                        aggregate_input_key + input.run_and_verify_script(&commitment_factory, Some(context))?;
+                    if !skip_scripts {
+                        let context = ScriptContext::new(height, &prev_hash, commitment, utxo_mined_height);
+                        // lets count up the input script public keys
+                        aggregate_input_key =
+                            aggregate_input_key + input.run_and_verify_script(&commitment_factory, Some(context))?;
+                    }
                     commitment_sum = &commitment_sum + input.commitment()?;
                 }
             }
@@ -356,8 +398,9 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         let num_outputs = outputs.len();
         let concurrency = cmp::min(self.concurrency, num_outputs);
         let output_chunks = into_enumerated_batches(outputs, concurrency);
-        let bypass_range_proof_verification = self.bypass_range_proof_verification;
-        if bypass_range_proof_verification {
+        let skip_signatures = self.skip_proofs_and_signatures;
+        let bypass_range_proof_verification = self.bypass_range_proof_verification || skip_signatures;
+        if self.bypass_range_proof_verification {
             warn!(target: LOG_TARGET, "Range proof verification will be bypassed!")
         }
 
@@ -379,6 +422,7 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                     let mut aggregate_sender_offset = PublicKey::default();
                     let mut commitment_sum = Commitment::default();
                     let max_script_size = constants.get_max_script_byte_size();
+                    let max_committee_size = constants.get_max_sidechain_committee_size();
                     let mut coinbase_index = None;
                     debug!(
                         target: LOG_TARGET,
@@ -404,7 +448,10 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
 
                         helpers::check_permitted_output_types(&constants, output)?;
                         helpers::check_tari_script_byte_size(&output.script, max_script_size)?;
-                        output.verify_metadata_signature()?;
+                        helpers::check_sidechain_features(output, max_committee_size)?;
+                        if !skip_signatures {
+                            output.verify_metadata_signature()?;
+                        }
                         helpers::check_not_duplicate_txo(&*db, output)?;
                         commitment_sum = &commitment_sum + &output.commitment;
                     }
@@ -52,6 +52,7 @@ impl<B: BlockchainBackend> PostOrphanBodyValidation<B> for BodyOnlyValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
     /// 1. Does the block satisfy the stateless checks?
     /// 1. Are all inputs currently in the UTXO set?
+    /// 1. Do all input scripts with relative height checks pass given the mined height of the spent outputs?
     /// 1. Are all inputs and outputs not in the STXO set?
     /// 1. Are all kernels excesses unique?
     /// 1. Are the block header MMR roots valid?
@@ -76,6 +77,7 @@ impl<B: BlockchainBackend> PostOrphanBodyValidation<B> for BodyOnlyValidator {
 
         let block_id = format!("block #{} ({})", block.header().height, block.hash().to_hex());
         helpers::check_inputs_are_utxos(backend, &block.block().body)?;
+        helpers::check_input_relative_heights(backend, &block.block().body, block.height(), &block.header().prev_hash)?;
         helpers::check_outputs(
             backend,
             self.rules.consensus_constants(block.height()),
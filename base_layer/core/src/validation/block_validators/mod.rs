@@ -26,7 +26,7 @@ mod test;
 mod abort_on_drop;
 
 mod async_validator;
-pub use async_validator::BlockValidator;
+pub use async_validator::{BlockValidator, ASSUME_VALID_SKIPPED_CHECKS};
 
 mod orphan;
 pub use orphan::OrphanBlockValidator;
@@ -34,4 +34,7 @@ pub use orphan::OrphanBlockValidator;
 mod body_only;
 pub use body_only::BodyOnlyValidator;
 
+mod verification_pool;
+pub use verification_pool::{BlockVerificationError, BlockVerificationPool, PendingVerification};
+
 const LOG_TARGET: &str = "c::val::block_validators";
@@ -21,9 +21,13 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use std::sync::Arc;
 
+use rand::rngs::OsRng;
 use tari_common::configuration::Network;
+use tari_common_types::types::{ComSignature, FixedHash, PublicKey};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
 use tari_script::script;
 use tari_test_utils::unpack_enum;
+use tari_utilities::hex::Hex;
 
 use crate::{
     block_spec,
@@ -36,13 +40,19 @@ use crate::{
         aggregated_body::AggregateBody,
         tari_amount::T,
         test_helpers::schema_to_transaction,
-        transaction_components::TransactionError,
+        transaction_components::{OutputFeatures, SideChainFeatures, TransactionError},
         CoinbaseBuilder,
         CryptoFactories,
     },
     txn_schema,
     validation::{
-        block_validators::{BlockValidator, BodyOnlyValidator, OrphanBlockValidator},
+        block_validators::{
+            BlockValidator,
+            BlockVerificationError,
+            BlockVerificationPool,
+            BodyOnlyValidator,
+            OrphanBlockValidator,
+        },
         traits::PostOrphanBodyValidation,
         BlockSyncBodyValidation,
         OrphanValidation,
@@ -94,6 +104,71 @@ async fn it_checks_the_coinbase_reward() {
     ));
 }
 
+#[tokio::test]
+async fn it_skips_signature_checks_in_assume_valid_mode() {
+    let (blockchain, validator) = setup();
+    let assume_valid_validator = BlockValidator::new(
+        blockchain.db().clone().into(),
+        blockchain.rules().clone(),
+        CryptoFactories::default(),
+        false,
+        6,
+    )
+    .assume_valid();
+
+    let (mut block, _) = blockchain.create_unmined_block(block_spec!("A", parent: "GB"));
+    block
+        .body
+        .outputs_mut()
+        .iter_mut()
+        .for_each(|o| o.metadata_signature = ComSignature::default());
+
+    let err = validator.validate_block_body(block.clone()).await.unwrap_err();
+    assert!(matches!(err, ValidationError::TransactionError(_)));
+    let out = assume_valid_validator.validate_block_body(block.clone()).await.unwrap();
+    assert_eq!(out, block);
+}
+
+#[tokio::test]
+async fn it_checks_the_coinbase_reward_in_assume_valid_mode() {
+    let (blockchain, validator) = setup();
+    let validator = validator.assume_valid();
+
+    let (block, _) = blockchain.create_chained_block(block_spec!("A", parent: "GB", reward: 10 * T, ));
+    let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::TransactionError(TransactionError::InvalidCoinbase)
+    ));
+}
+
+#[tokio::test]
+async fn it_attributes_verification_pool_failures_to_the_block_and_item() {
+    let (mut blockchain, _) = setup();
+    let pool = BlockVerificationPool::new(CryptoFactories::default(), 2, false);
+
+    let (block_a, _) = blockchain.add_next_tip(block_spec!("A")).unwrap();
+    let (block_b, _) = blockchain.create_next_tip(block_spec!("B"));
+    let blocks = vec![block_a.block().clone(), block_b.block().clone()];
+    pool.verify(Arc::new(blocks.clone())).await.unwrap();
+
+    let mut invalid = blocks.clone();
+    invalid[1].body.outputs_mut()[0].metadata_signature = ComSignature::default();
+    let err = pool.verify(Arc::new(invalid)).await.unwrap_err();
+    unpack_enum!(BlockVerificationError::InvalidBlock { block_index, error } = err);
+    assert_eq!(block_index, 1);
+    unpack_enum!(ValidationError::InvalidOutput { output_hash, .. } = error);
+    assert_eq!(output_hash, blocks[1].body.outputs()[0].hash().to_hex());
+
+    let mut invalid = blocks.clone();
+    invalid[0].body.kernels_mut()[0].excess_sig = blocks[1].body.kernels()[0].excess_sig.clone();
+    let err = pool.verify(Arc::new(invalid)).await.unwrap_err();
+    unpack_enum!(BlockVerificationError::InvalidBlock { block_index, error } = err);
+    assert_eq!(block_index, 0);
+    unpack_enum!(ValidationError::InvalidKernel { excess, .. } = error);
+    assert_eq!(excess, blocks[0].body.kernels()[0].excess.to_hex());
+}
+
 #[tokio::test]
 async fn it_checks_exactly_one_coinbase() {
     let (blockchain, validator) = setup();
@@ -211,6 +286,38 @@ async fn it_limits_the_script_byte_size() {
     assert!(matches!(err, ValidationError::TariScriptExceedsMaxSize { .. }));
 }
 
+#[tokio::test]
+async fn it_limits_the_sidechain_committee_size() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .with_max_sidechain_committee_size(1)
+                .build(),
+        )
+        .build();
+    let (mut blockchain, validator) = setup_with_rules(rules);
+
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).unwrap();
+
+    let committee = (0..2).map(|_| PublicKey::random_keypair(&mut OsRng).1).collect();
+    let features = OutputFeatures {
+        sidechain_features: Some(Box::new(SideChainFeatures::new(
+            FixedHash::from([1u8; 32]),
+            FixedHash::zero(),
+            committee,
+        ))),
+        ..Default::default()
+    };
+    let schema1 = txn_schema!(from: vec![coinbase_a], to: vec![50 * T, 12 * T], features: features);
+    let (txs, _) = schema_to_transaction(&[schema1]);
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block, _) = blockchain.create_next_tip(block_spec!("B", transactions: txs));
+
+    let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
+    assert!(matches!(err, ValidationError::SidechainCommitteeExceedsMaxSize { .. }));
+}
+
 #[tokio::test]
 async fn it_rejects_invalid_input_metadata() {
     let rules = ConsensusManager::builder(Network::LocalNet)
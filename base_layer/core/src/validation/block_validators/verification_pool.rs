@@ -0,0 +1,318 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{cmp, sync::Arc, time::Instant};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use log::*;
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+use tokio::{sync::Semaphore, task};
+
+use super::LOG_TARGET;
+use crate::{
+    blocks::Block,
+    transactions::{
+        transaction_components::{
+            batch_verify_kernel_signatures,
+            transaction_output::batch_verify_range_proofs,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        CryptoFactories,
+    },
+    validation::{block_validators::abort_on_drop::AbortOnDropJoinHandle, ValidationError},
+};
+
+/// The position of an output or kernel in a window of blocks, as (block index, item index)
+type ItemPosition = (usize, usize);
+
+type VerificationResult = Result<(), VerificationFailure>;
+
+type VerificationWork = Box<dyn FnOnce() -> VerificationResult + Send>;
+
+/// A bounded pool of workers that verifies the range proofs, output metadata signatures and kernel signatures of a
+/// window of blocks. These checks do not depend on the chain state, so they can run ahead of the stateful block body
+/// validation, which must be done block by block.
+///
+/// The pool can be cloned and shared. All clones share the same workers, so no more than `num_workers` verification
+/// tasks run at any time.
+#[derive(Clone)]
+pub struct BlockVerificationPool {
+    factories: CryptoFactories,
+    workers: Arc<Semaphore>,
+    num_workers: usize,
+    bypass_range_proof_verification: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum BlockVerificationError {
+    #[error("Block {block_index} of the verification window is invalid: {error}")]
+    InvalidBlock { block_index: usize, error: ValidationError },
+    #[error("The verification window is invalid, but the failure could not be attributed to a block: {error}")]
+    InvalidWindow { error: ValidationError },
+    #[error("Verification task failed: {0}")]
+    AsyncTaskFailed(#[from] task::JoinError),
+}
+
+impl BlockVerificationPool {
+    pub fn new(factories: CryptoFactories, num_workers: usize, bypass_range_proof_verification: bool) -> Self {
+        let num_workers = cmp::max(num_workers, 1);
+        Self {
+            factories,
+            workers: Arc::new(Semaphore::new(num_workers)),
+            num_workers,
+            bypass_range_proof_verification,
+        }
+    }
+
+    /// Starts verifying the blocks in the background. The verification is aborted if the returned
+    /// [PendingVerification] is dropped.
+    pub fn start_verification(&self, blocks: Arc<Vec<Block>>) -> PendingVerification {
+        let pool = self.clone();
+        PendingVerification {
+            handle: task::spawn(async move { pool.verify(blocks).await }).into(),
+        }
+    }
+
+    /// Verifies the range proofs, output metadata signatures and kernel signatures of every block in the window.
+    ///
+    /// The outputs and kernels of the window are split evenly between the workers, so range proofs and kernel
+    /// signatures are batch verified across block boundaries. If a batch fails, its items are verified one at a time
+    /// so that the failure is attributed to the exact block and output or kernel. If more than one block is invalid,
+    /// the error for the earliest block is returned. If a batch fails but none of its items fail on their own, no
+    /// block can be blamed and [BlockVerificationError::InvalidWindow] is returned, unless an earlier block is already
+    /// known to be invalid.
+    pub async fn verify(&self, blocks: Arc<Vec<Block>>) -> Result<(), BlockVerificationError> {
+        let timer = Instant::now();
+        let outputs = item_positions(&blocks, |block| block.body.outputs().len());
+        let kernels = item_positions(&blocks, |block| block.body.kernels().len());
+        let num_outputs = outputs.len();
+        let num_kernels = kernels.len();
+
+        let mut tasks = FuturesUnordered::new();
+        for positions in into_chunks(outputs, self.num_workers) {
+            let blocks = blocks.clone();
+            let factories = self.factories.clone();
+            let bypass_range_proof_verification = self.bypass_range_proof_verification;
+            tasks.push(self.spawn_worker(Box::new(move || {
+                verify_outputs(&blocks, &positions, &factories, bypass_range_proof_verification)
+            })));
+        }
+        for positions in into_chunks(kernels, self.num_workers) {
+            let blocks = blocks.clone();
+            tasks.push(self.spawn_worker(Box::new(move || verify_kernels(&blocks, &positions))));
+        }
+
+        let mut first_failure: Option<VerificationFailure> = None;
+        while let Some(result) = tasks.next().await {
+            if let Err(failure) = result? {
+                if first_failure
+                    .as_ref()
+                    .map_or(true, |first| failure.sort_key() < first.sort_key())
+                {
+                    first_failure = Some(failure);
+                }
+            }
+        }
+
+        match first_failure {
+            Some(VerificationFailure::Item((block_index, _), error)) => {
+                return Err(BlockVerificationError::InvalidBlock { block_index, error });
+            },
+            Some(VerificationFailure::Batch { error, .. }) => {
+                return Err(BlockVerificationError::InvalidWindow { error });
+            },
+            None => {},
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Verified {} output(s) and {} kernel(s) in {} block(s) in {:.2?}",
+            num_outputs,
+            num_kernels,
+            blocks.len(),
+            timer.elapsed()
+        );
+        Ok(())
+    }
+
+    async fn spawn_worker(&self, work: VerificationWork) -> Result<VerificationResult, task::JoinError> {
+        let workers = self.workers.clone();
+        let _permit = workers
+            .acquire_owned()
+            .await
+            .expect("unreachable panic: the verification pool semaphore is never closed");
+        task::spawn_blocking(work).await
+    }
+}
+
+/// A block window verification that is running in the [BlockVerificationPool]
+pub struct PendingVerification {
+    handle: AbortOnDropJoinHandle<Result<(), BlockVerificationError>>,
+}
+
+impl PendingVerification {
+    /// Waits for the verification to complete
+    pub async fn wait(self) -> Result<(), BlockVerificationError> {
+        self.handle.await?
+    }
+}
+
+/// A failure reported by a verification worker
+enum VerificationFailure {
+    /// The output or kernel at this position is invalid
+    Item(ItemPosition, ValidationError),
+    /// A batch failed, but none of its items failed when verified one at a time. The invalid item is at or after
+    /// `first`, the position of the first item in the batch.
+    Batch {
+        first: ItemPosition,
+        error: ValidationError,
+    },
+}
+
+impl VerificationFailure {
+    /// Orders failures by the earliest position that may be invalid. An item failure is known to be invalid, so it
+    /// comes before a batch failure that starts at the same position.
+    fn sort_key(&self) -> (ItemPosition, bool) {
+        match self {
+            VerificationFailure::Item(position, _) => (*position, false),
+            VerificationFailure::Batch { first, .. } => (*first, true),
+        }
+    }
+}
+
+fn item_positions<F>(blocks: &[Block], num_items: F) -> Vec<ItemPosition>
+where F: Fn(&Block) -> usize {
+    blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(block_index, block)| (0..num_items(block)).map(move |i| (block_index, i)))
+        .collect()
+}
+
+fn into_chunks(positions: Vec<ItemPosition>, num_chunks: usize) -> Vec<Vec<ItemPosition>> {
+    if positions.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = (positions.len() + num_chunks - 1) / num_chunks;
+    positions.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect()
+}
+
+fn verify_outputs(
+    blocks: &[Block],
+    positions: &[ItemPosition],
+    factories: &CryptoFactories,
+    bypass_range_proof_verification: bool,
+) -> VerificationResult {
+    let first_invalid_signature = positions.iter().enumerate().find_map(|(i, position)| {
+        output_at(blocks, *position)
+            .verify_metadata_signature()
+            .err()
+            .map(|err| (i, invalid_output(blocks, *position, err.to_string())))
+    });
+    // Range proofs after an invalid signature do not need to be checked because the earlier error is reported
+    let (num_range_proofs, first_invalid_signature) = match first_invalid_signature {
+        Some((i, failure)) => (i, Some(failure)),
+        None => (positions.len(), None),
+    };
+    if bypass_range_proof_verification {
+        return first_invalid_signature.map_or(Ok(()), Err);
+    }
+
+    let positions = &positions[..num_range_proofs];
+    let outputs = positions
+        .iter()
+        .map(|position| output_at(blocks, *position))
+        .collect::<Vec<_>>();
+    if let Err(batch_err) = batch_verify_range_proofs(&factories.range_proof, &outputs) {
+        debug!(
+            target: LOG_TARGET,
+            "Batch range proof verification of {} output(s) failed ({}). Verifying individually.",
+            outputs.len(),
+            batch_err
+        );
+        let first_invalid_proof = positions.iter().zip(&outputs).find_map(|(position, output)| {
+            batch_verify_range_proofs(&factories.range_proof, &[*output])
+                .err()
+                .map(|err| invalid_output(blocks, *position, format!("Invalid range proof: {}", err)))
+        });
+        // The batch failed, so some output must be invalid even if it could not be singled out
+        return Err(first_invalid_proof.unwrap_or_else(|| VerificationFailure::Batch {
+            first: positions[0],
+            error: batch_err.into(),
+        }));
+    }
+
+    first_invalid_signature.map_or(Ok(()), Err)
+}
+
+fn output_at(blocks: &[Block], (block_index, i): ItemPosition) -> &TransactionOutput {
+    &blocks[block_index].body.outputs()[i]
+}
+
+fn invalid_output(blocks: &[Block], position: ItemPosition, details: String) -> VerificationFailure {
+    let error = ValidationError::InvalidOutput {
+        output_hash: output_at(blocks, position).hash().to_hex(),
+        details,
+    };
+    VerificationFailure::Item(position, error)
+}
+
+fn verify_kernels(blocks: &[Block], positions: &[ItemPosition]) -> VerificationResult {
+    let kernels = positions
+        .iter()
+        .map(|position| kernel_at(blocks, *position))
+        .collect::<Vec<_>>();
+    let batch_err = match batch_verify_kernel_signatures(&kernels) {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+    debug!(
+        target: LOG_TARGET,
+        "Batch signature verification of {} kernel(s) failed ({}). Verifying individually.",
+        kernels.len(),
+        batch_err
+    );
+    let first_invalid_signature = positions.iter().find_map(|position| {
+        kernel_at(blocks, *position)
+            .verify_signature()
+            .err()
+            .map(|err| invalid_kernel(blocks, *position, err.to_string()))
+    });
+    // The batch failed, so some signature must be invalid even if it could not be singled out
+    Err(first_invalid_signature.unwrap_or_else(|| VerificationFailure::Batch {
+        first: positions[0],
+        error: batch_err.into(),
+    }))
+}
+
+fn kernel_at(blocks: &[Block], (block_index, i): ItemPosition) -> &TransactionKernel {
+    &blocks[block_index].body.kernels()[i]
+}
+
+fn invalid_kernel(blocks: &[Block], position: ItemPosition, details: String) -> VerificationFailure {
+    let error = ValidationError::InvalidKernel {
+        excess: kernel_at(blocks, position).excess.to_hex(),
+        details,
+    };
+    VerificationFailure::Item(position, error)
+}
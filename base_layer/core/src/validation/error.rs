@@ -111,6 +111,16 @@ pub enum ValidationError {
         max_script_size: usize,
         actual_script_size: usize,
     },
+    #[error(
+        "Sidechain committee exceeded maximum size, expected at most {max_committee_size} members but was \
+         {actual_committee_size}"
+    )]
+    SidechainCommitteeExceedsMaxSize {
+        max_committee_size: usize,
+        actual_committee_size: usize,
+    },
+    #[error("Invalid sidechain features for contract_id {contract_id}: {details}")]
+    InvalidSidechainFeatures { contract_id: FixedHash, details: String },
     #[error("Consensus Error: {0}")]
     ConsensusError(String),
     #[error("Duplicate kernel Error: {0}")]
@@ -132,6 +142,10 @@ pub enum ValidationError {
     InvalidBurnError(String),
     #[error("Output type '{output_type}' is not permitted")]
     OutputTypeNotPermitted { output_type: OutputType },
+    #[error("Output {output_hash} failed verification: {details}")]
+    InvalidOutput { output_hash: String, details: String },
+    #[error("Kernel with excess {excess} failed verification: {details}")]
+    InvalidKernel { excess: String, details: String },
     #[error("FixedHash size error: {0}")]
     FixedHashSizeError(#[from] FixedHashSizeError),
 }
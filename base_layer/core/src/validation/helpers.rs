@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, convert::TryInto};
 
 use log::*;
 use tari_common_types::types::{Commitment, CommitmentFactory, FixedHash, PublicKey};
@@ -29,7 +29,7 @@ use tari_crypto::{
     keys::PublicKey as PublicKeyTrait,
     tari_utilities::{epoch_time::EpochTime, hex::Hex},
 };
-use tari_script::TariScript;
+use tari_script::{Opcode, ScriptContext, TariScript};
 
 use crate::{
     blocks::{Block, BlockHeader, BlockHeaderValidationError, BlockValidationError},
@@ -418,10 +418,63 @@ pub fn check_input_is_utxo<B: BlockchainBackend>(db: &B, input: &TransactionInpu
     Err(ValidationError::UnknownInput)
 }
 
+/// Returns the height at which the output spent by the input was mined. Outputs that are not yet in the database are
+/// being spent in the same block, so `block_height` is returned for these.
+pub fn fetch_spent_output_mined_height<B: BlockchainBackend>(
+    db: &B,
+    input: &TransactionInput,
+    block_height: u64,
+) -> Result<u64, ValidationError> {
+    let mined_height = db
+        .fetch_output(&input.output_hash())?
+        .map(|info| info.mined_height)
+        .unwrap_or(block_height);
+    Ok(mined_height)
+}
+
+/// This function checks that the scripts of inputs containing relative height checks succeed when executed with the
+/// height at which the spent output was mined. Internal consistency checks do not have access to the mined height, so
+/// these scripts must be executed again once the inputs are known to be in the UTXO set.
+pub fn check_input_relative_heights<B: BlockchainBackend>(
+    db: &B,
+    body: &AggregateBody,
+    block_height: u64,
+    prev_hash: &FixedHash,
+) -> Result<(), ValidationError> {
+    let prev_hash: [u8; 32] = prev_hash.as_slice().try_into().unwrap_or([0; 32]);
+    for input in body.inputs() {
+        let has_relative_height_check = input
+            .script()?
+            .as_slice()
+            .iter()
+            .any(|op| matches!(op, Opcode::CheckRelativeHeightVerify(_)));
+        if !has_relative_height_check {
+            continue;
+        }
+
+        let utxo_mined_height = fetch_spent_output_mined_height(db, input, block_height)?;
+        let context = ScriptContext::new(block_height, &prev_hash, input.commitment()?, utxo_mined_height);
+        if let Err(err) = input.run_script(Some(context)) {
+            warn!(
+                target: LOG_TARGET,
+                "Input {} failed relative height check at height {} (mined at height {}): {}",
+                input,
+                block_height,
+                utxo_mined_height,
+                err
+            );
+            return Err(err.into());
+        }
+    }
+
+    Ok(())
+}
+
 /// This function checks:
 /// 1. that the output type is permitted
 /// 2. the byte size of TariScript does not exceed the maximum
-/// 3. that the outputs do not already exist in the UTxO set.
+/// 3. that the sidechain features, if any, are valid
+/// 4. that the outputs do not already exist in the UTxO set.
 pub fn check_outputs<B: BlockchainBackend>(
     db: &B,
     constants: &ConsensusConstants,
@@ -431,6 +484,7 @@ pub fn check_outputs<B: BlockchainBackend>(
     for output in body.outputs() {
         check_permitted_output_types(constants, output)?;
         check_tari_script_byte_size(&output.script, max_script_size)?;
+        check_sidechain_features(output, constants.get_max_sidechain_committee_size())?;
         check_not_duplicate_txo(db, output)?;
     }
     Ok(())
@@ -448,6 +502,51 @@ pub fn check_tari_script_byte_size(script: &TariScript, max_script_size: usize)
     Ok(())
 }
 
+/// Checks the sidechain features of the output, if present. Sidechain features must not be attached to coinbase
+/// outputs, must have a non-zero contract id and a committee of between one and `max_committee_size` unique members.
+pub fn check_sidechain_features(output: &TransactionOutput, max_committee_size: usize) -> Result<(), ValidationError> {
+    let sidechain_features = match output.features.sidechain_features.as_ref() {
+        Some(features) => features,
+        None => return Ok(()),
+    };
+    let contract_id = sidechain_features.contract_id;
+
+    if output.is_coinbase() {
+        return Err(ValidationError::NonContractOutputContainsSidechainFeatures {
+            output_type: output.features.output_type,
+            contract_id,
+        });
+    }
+    if contract_id == FixedHash::zero() {
+        return Err(ValidationError::InvalidSidechainFeatures {
+            contract_id,
+            details: "contract id must not be zero".to_string(),
+        });
+    }
+    let committee = &sidechain_features.committee;
+    if committee.len() > max_committee_size {
+        return Err(ValidationError::SidechainCommitteeExceedsMaxSize {
+            max_committee_size,
+            actual_committee_size: committee.len(),
+        });
+    }
+    if committee.is_empty() {
+        return Err(ValidationError::InvalidSidechainFeatures {
+            contract_id,
+            details: "committee must not be empty".to_string(),
+        });
+    }
+    let mut members = HashSet::with_capacity(committee.len());
+    if let Some(duplicate) = committee.iter().find(|member| !members.insert(*member)) {
+        return Err(ValidationError::InvalidSidechainFeatures {
+            contract_id,
+            details: format!("committee member {} appears more than once", duplicate.to_hex()),
+        });
+    }
+
+    Ok(())
+}
+
 /// This function checks that the outputs do not already exist in the TxO set.
 pub fn check_not_duplicate_txo<B: BlockchainBackend>(
     db: &B,
@@ -565,15 +664,14 @@ pub fn check_not_bad_block<B: BlockchainBackend>(db: &B, hash: FixedHash) -> Res
 /// This checks to ensure that every kernel included in the block is a unique kernel in the block chain.
 pub fn check_unique_kernels<B: BlockchainBackend>(db: &B, block_body: &AggregateBody) -> Result<(), ValidationError> {
     for kernel in block_body.kernels() {
-        if let Some((db_kernel, header_hash)) = db.fetch_kernel_by_excess_sig(&kernel.excess_sig)? {
+        if let Some(header_hash) = db.fetch_block_hash_by_kernel_excess_sig(&kernel.excess_sig)? {
             let msg = format!(
                 "Block contains kernel excess: {} which matches already existing excess signature in chain database \
-                 block hash: {}. Existing kernel excess: {}, excess sig nonce: {}, excess signature: {}",
+                 block hash: {}. Excess sig nonce: {}, excess signature: {}",
                 kernel.excess.to_hex(),
                 header_hash.to_hex(),
-                db_kernel.excess.to_hex(),
-                db_kernel.excess_sig.get_public_nonce().to_hex(),
-                db_kernel.excess_sig.get_signature().to_hex(),
+                kernel.excess_sig.get_public_nonce().to_hex(),
+                kernel.excess_sig.get_signature().to_hex(),
             );
             warn!(target: LOG_TARGET, "{}", msg);
             return Err(ValidationError::ConsensusError(msg));
@@ -917,6 +1015,89 @@ mod test {
         }
     }
 
+    mod check_sidechain_features {
+        use rand::rngs::OsRng;
+
+        use super::*;
+        use crate::transactions::transaction_components::SideChainFeatures;
+
+        fn create_output(features: OutputFeatures) -> TransactionOutput {
+            let (output, _, _) = test_helpers::create_utxo(
+                100.into(),
+                &CryptoFactories::default(),
+                &features,
+                &TariScript::default(),
+                &Covenant::default(),
+                0.into(),
+            );
+            output
+        }
+
+        fn create_sidechain_features(committee_size: usize) -> SideChainFeatures {
+            let committee = (0..committee_size)
+                .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+                .collect();
+            SideChainFeatures::new(FixedHash::from([1u8; 32]), FixedHash::zero(), committee)
+        }
+
+        fn with_sidechain_features(mut features: OutputFeatures, sidechain: SideChainFeatures) -> OutputFeatures {
+            features.sidechain_features = Some(Box::new(sidechain));
+            features
+        }
+
+        #[test]
+        fn it_succeeds_for_valid_sidechain_features() {
+            check_sidechain_features(&create_output(OutputFeatures::default()), 0).unwrap();
+            let output = create_output(with_sidechain_features(
+                OutputFeatures::default(),
+                create_sidechain_features(3),
+            ));
+            check_sidechain_features(&output, 3).unwrap();
+        }
+
+        #[test]
+        fn it_rejects_an_oversized_committee() {
+            let output = create_output(with_sidechain_features(
+                OutputFeatures::default(),
+                create_sidechain_features(4),
+            ));
+            let err = check_sidechain_features(&output, 3).unwrap_err();
+            unpack_enum!(
+                ValidationError::SidechainCommitteeExceedsMaxSize {
+                    actual_committee_size,
+                    ..
+                } = err
+            );
+            assert_eq!(actual_committee_size, 4);
+        }
+
+        #[test]
+        fn it_rejects_invalid_sidechain_features() {
+            let mut duplicated = create_sidechain_features(2);
+            duplicated.committee.push(duplicated.committee[0].clone());
+            let mut zero_contract_id = create_sidechain_features(1);
+            zero_contract_id.contract_id = FixedHash::zero();
+            for sidechain in vec![create_sidechain_features(0), duplicated, zero_contract_id] {
+                let output = create_output(with_sidechain_features(OutputFeatures::default(), sidechain));
+                let err = check_sidechain_features(&output, 10).unwrap_err();
+                assert!(matches!(err, ValidationError::InvalidSidechainFeatures { .. }));
+            }
+        }
+
+        #[test]
+        fn it_rejects_sidechain_features_on_coinbase_outputs() {
+            let output = create_output(with_sidechain_features(
+                OutputFeatures::create_coinbase(0),
+                create_sidechain_features(1),
+            ));
+            let err = check_sidechain_features(&output, 10).unwrap_err();
+            assert!(matches!(
+                err,
+                ValidationError::NonContractOutputContainsSidechainFeatures { .. }
+            ));
+        }
+    }
+
     use crate::{covenants::Covenant, transactions::transaction_components::KernelFeatures};
 
     #[test]
@@ -28,7 +28,13 @@ use crate::{
     consensus::ConsensusConstants,
     transactions::{transaction_components::Transaction, CryptoFactories},
     validation::{
-        helpers::{check_inputs_are_utxos, check_outputs, check_permitted_output_types, check_total_burned},
+        helpers::{
+            check_input_relative_heights,
+            check_inputs_are_utxos,
+            check_outputs,
+            check_permitted_output_types,
+            check_total_burned,
+        },
         MempoolTransactionValidation,
         ValidationError,
     },
@@ -161,16 +167,17 @@ impl<B: BlockchainBackend> TxConsensusValidator<B> {
 
     fn validate_excess_sig_not_in_db(&self, tx: &Transaction) -> Result<(), ValidationError> {
         for kernel in tx.body.kernels() {
-            if let Some((db_kernel, header_hash)) = self.db.fetch_kernel_by_excess_sig(kernel.excess_sig.to_owned())? {
+            if let Some(header_hash) = self
+                .db
+                .fetch_block_hash_by_kernel_excess_sig(kernel.excess_sig.to_owned())?
+            {
                 let msg = format!(
                     "Aggregate body contains kernel excess: {} which matches already existing excess signature in \
-                     chain database block hash: {}. Existing kernel excess: {}, excess sig nonce: {}, excess \
-                     signature: {}",
+                     chain database block hash: {}. Excess sig nonce: {}, excess signature: {}",
                     kernel.excess.to_hex(),
                     header_hash.to_hex(),
-                    db_kernel.excess.to_hex(),
-                    db_kernel.excess_sig.get_public_nonce().to_hex(),
-                    db_kernel.excess_sig.get_signature().to_hex(),
+                    kernel.excess_sig.get_public_nonce().to_hex(),
+                    kernel.excess_sig.get_signature().to_hex(),
                 );
                 return Err(ValidationError::DuplicateKernelError(msg));
             };
@@ -196,7 +203,8 @@ impl<B: BlockchainBackend> MempoolTransactionValidation for TxConsensusValidator
 }
 
 /// This validator assumes that the transaction was already validated and it will skip this step. It will only check, in
-/// order,: All inputs exist in the backend, All timelocks (kernel lock heights and output maturities) have passed
+/// order,: All inputs exist in the backend, All timelocks (kernel lock heights, output maturities and relative height
+/// script checks) have passed
 #[derive(Clone)]
 pub struct TxInputAndMaturityValidator<B> {
     db: BlockchainDatabase<B>,
@@ -215,7 +223,10 @@ impl<B: BlockchainBackend> MempoolTransactionValidation for TxInputAndMaturityVa
             let db = self.db.db_read_access()?;
             check_inputs_are_utxos(&*db, tx.body())?;
             check_outputs(&*db, constants, tx.body())?;
-            db.fetch_chain_metadata()?.height_of_longest_chain()
+            let metadata = db.fetch_chain_metadata()?;
+            let tip_height = metadata.height_of_longest_chain();
+            check_input_relative_heights(&*db, tx.body(), tip_height + 1, metadata.best_block())?;
+            tip_height
         };
 
         verify_timelocks(tx, tip_height)?;
@@ -41,9 +41,10 @@ use tari_core::{
         sync::rpc::BaseNodeSyncRpcService,
     },
     blocks::ChainBlock,
+    chain_storage::DbTransaction,
     consensus::{ConsensusManager, ConsensusManagerBuilder, NetworkConsensus},
     proto::{
-        base_node::{FetchMatchingUtxos, Signatures as SignaturesProto, SyncUtxosByBlockRequest},
+        base_node::{FetchMatchingUtxos, MmrProofRequest, Signatures as SignaturesProto, SyncUtxosByBlockRequest},
         types::{Signature as SignatureProto, Transaction as TransactionProto},
     },
     test_helpers::blockchain::TempDatabase,
@@ -54,7 +55,9 @@ use tari_core::{
         CryptoFactories,
     },
     txn_schema,
+    OutputMmrHasherBlake256,
 };
+use tari_mmr::{MerkleBatchProof, MutableMmrBatchProof};
 use tari_service_framework::reply_channel;
 use tari_test_utils::streams::convert_mpsc_to_stream;
 use tari_utilities::epoch_time::EpochTime;
@@ -272,6 +275,100 @@ async fn test_base_node_wallet_rpc() {
     }
 }
 
+#[tokio::test]
+async fn test_transaction_query_finds_pruned_kernels() {
+    let (service, _, mut base_node, request_mock, consensus_manager, block0, utxo0, _temp_dir) = setup().await;
+
+    let (txs1, _) = schema_to_transaction(&[txn_schema!(from: vec![utxo0], to: vec![1 * T])]);
+    let tx1 = (*txs1[0]).clone();
+    let tx1_sig = tx1.first_kernel_excess_sig().unwrap().clone();
+    let block1 = base_node
+        .blockchain_db
+        .prepare_new_block(chain_block(block0.block(), vec![tx1], &consensus_manager))
+        .unwrap();
+    base_node.local_nci.submit_block(block1.clone()).await.unwrap();
+
+    let mut txn = DbTransaction::new();
+    txn.prune_kernels_in_block(block1.hash()).set_kernel_pruned_height(1);
+    base_node.blockchain_db.commit(txn).unwrap();
+    assert!(base_node
+        .blockchain_db
+        .fetch_kernel_by_excess_sig(tx1_sig.clone())
+        .unwrap()
+        .is_none());
+
+    let msg = SignatureProto::from(tx1_sig.clone());
+    let req = request_mock.request_with_context(Default::default(), msg);
+    let resp = TxQueryResponse::try_from(service.transaction_query(req).await.unwrap().into_message()).unwrap();
+    assert_eq!(resp.location, TxLocation::Mined);
+    assert_eq!(resp.block_hash, Some(block1.hash()));
+
+    let block = base_node
+        .blockchain_db
+        .fetch_block_with_kernel(tx1_sig)
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.hash(), &block1.hash());
+}
+
+#[tokio::test]
+async fn test_fetch_output_mmr_proof() {
+    let factories = CryptoFactories::default();
+    let (service, _, mut base_node, request_mock, consensus_manager, block0, utxo0, _temp_dir) = setup().await;
+
+    let (txs1, utxos1) = schema_to_transaction(&[txn_schema!(from: vec![utxo0.clone()], to: vec![1 * T])]);
+    let block1 = base_node
+        .blockchain_db
+        .prepare_new_block(chain_block(
+            block0.block(),
+            vec![(*txs1[0]).clone()],
+            &consensus_manager,
+        ))
+        .unwrap();
+    base_node.local_nci.submit_block(block1).await.unwrap();
+
+    let spent_hash = utxo0.as_transaction_output(&factories).unwrap().hash();
+    let unspent_hash = utxos1[0].as_transaction_output(&factories).unwrap().hash();
+    let msg = MmrProofRequest {
+        hashes: vec![spent_hash.to_vec(), unspent_hash.to_vec()],
+        exclude_deleted_bitmap: true,
+    };
+    let req = request_mock.request_with_context(Default::default(), msg);
+    let resp = service.fetch_output_mmr_proof(req).await.unwrap().into_message();
+    // Only the (unverifiable) spent status of the requested outputs is sent if the deleted bitmap is excluded
+    assert!(resp.deleted_bitmap.is_empty());
+    assert_eq!(resp.leaves.len(), 2);
+    let is_deleted = |hash: &[u8]| resp.leaves.iter().find(|l| l.hash == hash).unwrap().deleted;
+    assert!(is_deleted(spent_hash.as_slice()));
+    assert!(!is_deleted(unspent_hash.as_slice()));
+
+    let msg = MmrProofRequest {
+        hashes: vec![spent_hash.to_vec(), unspent_hash.to_vec()],
+        exclude_deleted_bitmap: false,
+    };
+    let req = request_mock.request_with_context(Default::default(), msg);
+    let resp = service.fetch_output_mmr_proof(req).await.unwrap().into_message();
+    assert!(!resp.deleted_bitmap.is_empty());
+    let proof = MutableMmrBatchProof::from_raw_parts(
+        MerkleBatchProof::try_from(resp.proof.unwrap()).unwrap(),
+        resp.deleted_bitmap,
+    );
+    let leaves = resp
+        .leaves
+        .iter()
+        .map(|l| (u32::try_from(l.leaf_index).unwrap(), l.hash.as_slice()))
+        .collect::<Vec<_>>();
+    let tip = base_node.blockchain_db.fetch_tip_header().unwrap();
+    proof
+        .verify_leaves::<OutputMmrHasherBlake256>(tip.header().output_mr.as_slice(), &leaves)
+        .unwrap();
+    // The deleted flags agree with the deleted bitmap that the proof was verified against
+    let deleted = proof.deleted_bitmap().unwrap();
+    for leaf in &resp.leaves {
+        assert_eq!(leaf.deleted, deleted.contains(u32::try_from(leaf.leaf_index).unwrap()));
+    }
+}
+
 #[tokio::test]
 async fn test_get_height_at_time() {
     let factories = CryptoFactories::default();
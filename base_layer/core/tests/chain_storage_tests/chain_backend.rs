@@ -22,7 +22,16 @@
 
 use tari_common::configuration::Network;
 use tari_core::{
-    chain_storage::{create_lmdb_database, BlockchainBackend, ChainStorageError, DbKey, DbTransaction, DbValue},
+    chain_storage::{
+        create_lmdb_database,
+        create_sled_database,
+        BlockchainBackend,
+        ChainStorageError,
+        DbKey,
+        DbTransaction,
+        DbValue,
+        SledConfig,
+    },
     consensus::ConsensusManagerBuilder,
     test_helpers::blockchain::create_test_db,
     tx,
@@ -87,3 +96,60 @@ fn lmdb_file_lock() {
         std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
     }
 }
+
+#[test]
+fn sled_insert_contains_delete_and_fetch_orphan() {
+    let network = Network::LocalNet;
+    let consensus = ConsensusManagerBuilder::new(network).build();
+    let temp_path = create_temporary_data_path();
+    {
+        let mut db = create_sled_database(&temp_path, SledConfig::default()).unwrap();
+        let txs = vec![(tx!(1000.into(), fee: 4.into(), inputs: 2, outputs: 1)).0];
+        let orphan = create_orphan_block(10, txs, &consensus);
+        let hash = orphan.hash();
+        assert!(!db.contains(&DbKey::OrphanBlock(hash)).unwrap());
+
+        let mut txn = DbTransaction::new();
+        txn.insert_orphan(orphan.clone().into());
+        db.write(txn).unwrap();
+
+        assert!(db.contains(&DbKey::OrphanBlock(hash)).unwrap());
+        assert_eq!(db.orphan_count().unwrap(), 1);
+        assert_eq!(db.fetch_orphan_children_of(orphan.header.prev_hash).unwrap(), vec![
+            orphan
+        ]);
+
+        let mut txn = DbTransaction::new();
+        txn.delete_orphan(hash);
+        db.write(txn).unwrap();
+        assert!(!db.contains(&DbKey::OrphanBlock(hash)).unwrap());
+        assert_eq!(db.orphan_count().unwrap(), 0);
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+    }
+}
+
+#[test]
+fn sled_file_lock() {
+    let temp_path = create_temporary_data_path();
+
+    {
+        let db = create_sled_database(&temp_path, SledConfig::default()).unwrap();
+
+        match create_sled_database(&temp_path, SledConfig::default()) {
+            Err(ChainStorageError::CannotAcquireFileLock) => {},
+            _ => panic!("Should not be able to make this db"),
+        }
+
+        drop(db);
+
+        let _db2 =
+            create_sled_database(&temp_path, SledConfig::default()).expect("Should be able to make a new db now");
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        std::fs::remove_dir_all(&temp_path).expect("Could not clear temp storage for db");
+    }
+}
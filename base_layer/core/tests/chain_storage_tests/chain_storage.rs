@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::sync::Arc;
+
 use rand::{rngs::OsRng, RngCore};
 use tari_common::configuration::Network;
 use tari_common_types::types::BlockHash;
@@ -34,6 +36,8 @@ use tari_core::{
         ChainStorageError,
         DbTransaction,
         MmrTree,
+        PruningLevel,
+        ScriptIndexKey,
         Validators,
     },
     consensus::{emission::Emission, ConsensusConstantsBuilder, ConsensusManagerBuilder},
@@ -41,6 +45,7 @@ use tari_core::{
     test_helpers::blockchain::{
         create_store_with_consensus,
         create_store_with_consensus_and_validators,
+        create_store_with_consensus_and_validators_and_config,
         create_test_blockchain_db,
         create_test_db,
         TempDatabase,
@@ -52,7 +57,12 @@ use tari_core::{
     },
     tx,
     txn_schema,
-    validation::{mocks::MockValidator, DifficultyCalculator, ValidationError},
+    validation::{
+        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        mocks::MockValidator,
+        DifficultyCalculator,
+        ValidationError,
+    },
 };
 use tari_storage::lmdb_store::LMDBConfig;
 use tari_test_utils::{paths::create_temporary_data_path, unpack_enum};
@@ -63,7 +73,9 @@ use crate::helpers::{
     block_builders::{
         append_block,
         chain_block,
+        chain_block_with_coinbase,
         create_chain_header,
+        create_coinbase,
         create_genesis_block,
         find_header_with_achieved_difficulty,
         generate_new_block,
@@ -385,6 +397,356 @@ fn handle_tip_reorg() {
     assert!(store.fetch_orphan(*blocks[2].hash()).is_ok());
 }
 
+#[test]
+fn utxo_stats_follow_reorgs() {
+    // GB --> A1 --> A2(Low PoW)      [Main Chain]
+    //          \--> B2(Highest PoW)  [Forked Chain]
+    let network = Network::LocalNet;
+    let (_, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let validators = Validators::new(
+        BodyOnlyValidator::new(consensus_manager.clone()),
+        MockValidator::new(true),
+        OrphanBlockValidator::new(consensus_manager.clone(), false, CryptoFactories::default()),
+    );
+    let mut store = create_store_with_consensus_and_validators_and_config(
+        consensus_manager.clone(),
+        validators,
+        BlockchainDatabaseConfig {
+            track_utxo_stats: true,
+            ..Default::default()
+        },
+    );
+    // Block A1
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![10 * T, 10 * T, 10 * T, 10 * T]
+    )];
+    generate_new_block_with_achieved_difficulty(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        Difficulty::from(1),
+        &consensus_manager,
+    )
+    .unwrap();
+    // Block A2
+    let txs = vec![txn_schema!(from: vec![outputs[1][3].clone()], to: vec![6 * T])];
+    generate_new_block_with_achieved_difficulty(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        Difficulty::from(3),
+        &consensus_manager,
+    )
+    .unwrap();
+
+    let stats = store.fetch_utxo_stats(0, 2).unwrap();
+    assert_eq!(stats.len(), 3);
+    for (stats, block) in stats.iter().zip(&blocks) {
+        assert_eq!(stats.block_hash, *block.hash());
+        assert_eq!(stats.outputs_created, block.block().body.outputs().len() as u64);
+        assert_eq!(stats.outputs_spent, block.block().body.inputs().len() as u64);
+        assert_eq!(stats.script_types.values().sum::<u64>(), stats.outputs_created);
+    }
+    assert_eq!(
+        stats[2].utxo_set_size,
+        stats[1].utxo_set_size + stats[2].outputs_created - stats[2].outputs_spent
+    );
+
+    // Create Forked Chain
+    let mut orphan_store = create_store_with_consensus(consensus_manager.clone());
+    orphan_store.add_block(blocks[1].to_arc_block()).unwrap();
+    let mut orphan_blocks = vec![blocks[0].clone(), blocks[1].clone()];
+    let mut orphan_outputs = vec![outputs[0].clone(), outputs[1].clone()];
+    // Block B2
+    let txs = vec![txn_schema!(
+        from: vec![orphan_outputs[1][0].clone(), orphan_outputs[1][1].clone()],
+        to: vec![5 * T]
+    )];
+    generate_new_block_with_achieved_difficulty(
+        &mut orphan_store,
+        &mut orphan_blocks,
+        &mut orphan_outputs,
+        txs,
+        Difficulty::from(7),
+        &consensus_manager,
+    )
+    .unwrap();
+    // The index is only available when it is enabled
+    unpack_enum!(ChainStorageError::InvalidOperation(_) = orphan_store.fetch_utxo_stats(0, 2).unwrap_err());
+
+    store
+        .add_block(orphan_blocks[2].to_arc_block())
+        .unwrap()
+        .assert_reorg(1, 1);
+    let stats = store.fetch_utxo_stats(0, 2).unwrap();
+    assert_eq!(stats.len(), 3);
+    assert_eq!(stats[2].block_hash, *orphan_blocks[2].hash());
+    assert_eq!(stats[2].outputs_spent, 2);
+    assert_eq!(
+        stats[2].utxo_set_size,
+        stats[1].utxo_set_size + stats[2].outputs_created - 2
+    );
+
+    store.rewind_to_height(1).unwrap();
+    let stats = store.fetch_utxo_stats(0, 2).unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[1].block_hash, *blocks[1].hash());
+}
+
+#[test]
+fn utxo_stats_are_kept_while_turned_off() {
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let block0 = genesis_block::get_esmeralda_genesis_block();
+    let rules = ConsensusManagerBuilder::new(Network::LocalNet)
+        .with_block(block0.clone())
+        .build();
+    let temp_path = create_temporary_data_path();
+    let open_db = |track_utxo_stats: bool| {
+        let mut db = TempDatabase::from_path(&temp_path);
+        db.disable_delete_on_drop();
+        BlockchainDatabase::new(
+            db,
+            rules.clone(),
+            validators.clone(),
+            BlockchainDatabaseConfig {
+                track_utxo_stats,
+                ..Default::default()
+            },
+            DifficultyCalculator::new(rules.clone(), Default::default()),
+        )
+        .unwrap()
+    };
+    let last_utxo_stats_height = || {
+        let mut db = TempDatabase::from_path(&temp_path);
+        db.disable_delete_on_drop();
+        db.fetch_last_utxo_stats().unwrap().map(|stats| stats.height)
+    };
+
+    let block1 = {
+        let db = open_db(true);
+        let block1 = append_block(&db, &block0, vec![], &rules, 1.into()).unwrap();
+        assert_eq!(db.fetch_utxo_stats(0, 1).unwrap().len(), 2);
+        block1
+    };
+
+    // The index is neither updated nor removed while it is turned off
+    let block2 = {
+        let db = open_db(false);
+        unpack_enum!(ChainStorageError::InvalidOperation(_) = db.fetch_utxo_stats(0, 1).unwrap_err());
+        append_block(&db, &block1, vec![], &rules, 1.into()).unwrap()
+    };
+    assert_eq!(last_utxo_stats_height(), Some(1));
+
+    {
+        let db = open_db(true);
+        let stats = db.fetch_utxo_stats(0, 2).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[2].block_hash, *block2.hash());
+        unpack_enum!(ChainStorageError::InvalidOperation(_) = db.clear_all_utxo_stats().unwrap_err());
+    }
+    assert_eq!(last_utxo_stats_height(), Some(2));
+
+    open_db(false).clear_all_utxo_stats().unwrap();
+    assert_eq!(last_utxo_stats_height(), None);
+    // Remove the database
+    drop(TempDatabase::from_path(&temp_path));
+}
+
+#[test]
+fn script_index_is_kept_while_turned_off() {
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let block0 = genesis_block::get_esmeralda_genesis_block();
+    let rules = ConsensusManagerBuilder::new(Network::LocalNet)
+        .with_block(block0.clone())
+        .build();
+    let temp_path = create_temporary_data_path();
+    let open_db = |track_script_index: bool| {
+        let mut db = TempDatabase::from_path(&temp_path);
+        db.disable_delete_on_drop();
+        BlockchainDatabase::new(
+            db,
+            rules.clone(),
+            validators.clone(),
+            BlockchainDatabaseConfig {
+                track_script_index,
+                ..Default::default()
+            },
+            DifficultyCalculator::new(rules.clone(), Default::default()),
+        )
+        .unwrap()
+    };
+    let last_script_index_block = || {
+        let mut db = TempDatabase::from_path(&temp_path);
+        db.disable_delete_on_drop();
+        db.fetch_last_script_index_block()
+            .unwrap()
+            .map(|block| (block.height, block.block_hash))
+    };
+    let key = ScriptIndexKey::SenderOffsetPublicKey(Default::default());
+
+    let block1 = {
+        let db = open_db(true);
+        let block1 = append_block(&db, &block0, vec![], &rules, 1.into()).unwrap();
+        db.fetch_outputs_by_script_index(key.clone(), 0, 1, 10).unwrap();
+        block1
+    };
+    assert_eq!(last_script_index_block(), Some((1, *block1.hash())));
+
+    // The index is neither updated nor removed while it is turned off
+    let block2 = {
+        let db = open_db(false);
+        unpack_enum!(
+            ChainStorageError::InvalidOperation(_) =
+                db.fetch_outputs_by_script_index(key.clone(), 0, 1, 10).unwrap_err()
+        );
+        append_block(&db, &block1, vec![], &rules, 1.into()).unwrap()
+    };
+    assert_eq!(last_script_index_block(), Some((1, *block1.hash())));
+
+    {
+        let db = open_db(true);
+        db.fetch_outputs_by_script_index(key, 0, 2, 10).unwrap();
+        unpack_enum!(ChainStorageError::InvalidOperation(_) = db.clear_script_index().unwrap_err());
+    }
+    assert_eq!(last_script_index_block(), Some((2, *block2.hash())));
+
+    open_db(false).clear_script_index().unwrap();
+    assert_eq!(last_script_index_block(), None);
+    // Remove the database
+    drop(TempDatabase::from_path(&temp_path));
+}
+
+#[test]
+fn script_index_follows_reorgs() {
+    // GB --> A1 --> A2(Low PoW)      [Main Chain]
+    //          \--> B2(Highest PoW)  [Forked Chain]
+    let network = Network::LocalNet;
+    let (_, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let validators = Validators::new(
+        BodyOnlyValidator::new(consensus_manager.clone()),
+        MockValidator::new(true),
+        OrphanBlockValidator::new(consensus_manager.clone(), false, CryptoFactories::default()),
+    );
+    let mut store = create_store_with_consensus_and_validators_and_config(
+        consensus_manager.clone(),
+        validators,
+        BlockchainDatabaseConfig {
+            track_script_index: true,
+            ..Default::default()
+        },
+    );
+    // Block A1
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![10 * T, 10 * T, 10 * T, 10 * T]
+    )];
+    generate_new_block_with_achieved_difficulty(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        Difficulty::from(1),
+        &consensus_manager,
+    )
+    .unwrap();
+    // Block A2
+    let txs = vec![txn_schema!(from: vec![outputs[1][3].clone()], to: vec![6 * T])];
+    generate_new_block_with_achieved_difficulty(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        Difficulty::from(3),
+        &consensus_manager,
+    )
+    .unwrap();
+
+    let a2_output = blocks[2].block().body.outputs()[0].clone();
+    let key = ScriptIndexKey::SenderOffsetPublicKey(a2_output.sender_offset_public_key.clone());
+    let found = store.fetch_outputs_by_script_index(key.clone(), 0, 2, 10).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].output.hash(), a2_output.hash());
+    assert_eq!(found[0].mined_height, 2);
+    assert_eq!(found[0].header_hash, *blocks[2].hash());
+    assert!(store
+        .fetch_outputs_by_script_index(key.clone(), 0, 1, 10)
+        .unwrap()
+        .is_empty());
+
+    let script_key = ScriptIndexKey::from_script(&a2_output.script).unwrap();
+    let expected = blocks
+        .iter()
+        .flat_map(|b| b.block().body.outputs().iter().map(move |o| (b.height(), o)))
+        .filter(|(_, o)| o.script == a2_output.script)
+        .map(|(height, o)| (height, o.hash()))
+        .collect::<Vec<_>>();
+    let found = store
+        .fetch_outputs_by_script_index(script_key.clone(), 0, 2, 100)
+        .unwrap()
+        .into_iter()
+        .map(|o| (o.mined_height, o.output.hash()))
+        .collect::<Vec<_>>();
+    assert_eq!(found.len(), expected.len());
+    assert!(found.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert!(expected.iter().all(|e| found.contains(e)));
+    // A page always includes every matching output at the height it ends on
+    let first_height = expected.iter().map(|(height, _)| *height).min().unwrap();
+    let page = store.fetch_outputs_by_script_index(script_key, 0, 2, 1).unwrap();
+    assert!(page.iter().all(|o| o.mined_height == first_height));
+    assert_eq!(
+        page.len(),
+        expected.iter().filter(|(height, _)| *height == first_height).count()
+    );
+
+    // Create Forked Chain
+    let mut orphan_store = create_store_with_consensus(consensus_manager.clone());
+    orphan_store.add_block(blocks[1].to_arc_block()).unwrap();
+    let mut orphan_blocks = vec![blocks[0].clone(), blocks[1].clone()];
+    let mut orphan_outputs = vec![outputs[0].clone(), outputs[1].clone()];
+    // Block B2
+    let txs = vec![txn_schema!(from: vec![orphan_outputs[1][0].clone()], to: vec![5 * T])];
+    generate_new_block_with_achieved_difficulty(
+        &mut orphan_store,
+        &mut orphan_blocks,
+        &mut orphan_outputs,
+        txs,
+        Difficulty::from(7),
+        &consensus_manager,
+    )
+    .unwrap();
+    // The index is only available when it is enabled
+    unpack_enum!(
+        ChainStorageError::InvalidOperation(_) = orphan_store
+            .fetch_outputs_by_script_index(key.clone(), 0, 2, 10)
+            .unwrap_err()
+    );
+
+    store
+        .add_block(orphan_blocks[2].to_arc_block())
+        .unwrap()
+        .assert_reorg(1, 1);
+    assert!(store.fetch_outputs_by_script_index(key, 0, 2, 10).unwrap().is_empty());
+    let b2_output = &orphan_blocks[2].block().body.outputs()[0];
+    let key = ScriptIndexKey::SenderOffsetPublicKey(b2_output.sender_offset_public_key.clone());
+    let found = store.fetch_outputs_by_script_index(key.clone(), 0, 2, 10).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].header_hash, *orphan_blocks[2].hash());
+
+    store.rewind_to_height(1).unwrap();
+    assert!(store.fetch_outputs_by_script_index(key, 0, 2, 10).unwrap().is_empty());
+}
+
 #[test]
 #[allow(clippy::identity_op)]
 #[allow(clippy::too_many_lines)]
@@ -1719,6 +2081,72 @@ fn pruned_mode_cleanup_and_fetch_block() {
     assert_eq!(metadata.pruning_horizon(), 3);
 }
 
+#[test]
+fn pruned_mode_prunes_kernels() {
+    let network = Network::LocalNet;
+    let block0 = genesis_block::get_esmeralda_genesis_block();
+    let consensus_manager = ConsensusManagerBuilder::new(network).with_block(block0.clone()).build();
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let db = create_test_db();
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 3,
+        pruning_interval: 1,
+        pruning_level: PruningLevel::Kernels,
+        ..Default::default()
+    };
+    let store = BlockchainDatabase::new(
+        db,
+        consensus_manager.clone(),
+        validators,
+        config,
+        DifficultyCalculator::new(consensus_manager.clone(), Default::default()),
+    )
+    .unwrap();
+    let block1 = append_block(&store, &block0, vec![], &consensus_manager, 1.into()).unwrap();
+    let block2 = append_block(&store, &block1, vec![], &consensus_manager, 1.into()).unwrap();
+    let block3 = append_block(&store, &block2, vec![], &consensus_manager, 1.into()).unwrap();
+    let block4 = append_block(&store, &block3, vec![], &consensus_manager, 1.into()).unwrap();
+    let block5 = append_block(&store, &block4, vec![], &consensus_manager, 1.into()).unwrap();
+
+    assert_eq!(store.get_chain_metadata().unwrap().pruned_height(), 2);
+    assert_eq!(store.fetch_kernel_pruned_height().unwrap(), 2);
+    assert!(store.fetch_kernels_in_block(*block1.hash()).unwrap().is_empty());
+    assert!(store.fetch_kernels_in_block(*block2.hash()).unwrap().is_empty());
+    assert_eq!(store.fetch_kernels_in_block(*block3.hash()).unwrap().len(), 1);
+
+    // Pruned kernels can still be found by excess signature for uniqueness checks
+    let pruned_kernel = block1.block().body.kernels()[0].clone();
+    assert!(store
+        .fetch_kernel_by_excess_sig(pruned_kernel.excess_sig.clone())
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        store
+            .fetch_block_hash_by_kernel_excess_sig(pruned_kernel.excess_sig.clone())
+            .unwrap(),
+        Some(*block1.hash())
+    );
+    // The accumulated data of pruned blocks is kept
+    assert!(store.fetch_block_accumulated_data(*block1.hash()).is_ok());
+    assert!(store.generate_kernel_mmr_proof(vec![]).is_err());
+
+    // A block that reuses the excess of a pruned kernel is still rejected, as it is by unpruned nodes
+    let factories = CryptoFactories::default();
+    let (coinbase_utxo, _, _) = create_coinbase(&factories, 1.into(), 6);
+    let template = chain_block_with_coinbase(&block5, vec![], coinbase_utxo, pruned_kernel, &consensus_manager);
+    let mut block = store.prepare_new_block(template).unwrap();
+    block.header.nonce = OsRng.next_u64();
+    find_header_with_achieved_difficulty(&mut block.header, 1.into());
+    let err = store.add_block(Arc::new(block)).unwrap_err();
+    unpack_enum!(ChainStorageError::KeyExists { table_name, .. } = err);
+    assert_eq!(table_name, "kernel_excess_index");
+    assert_eq!(store.get_height().unwrap(), 5);
+}
+
 mod malleability {
     use tari_common_types::types::{ComSignature, RangeProof};
     use tari_core::{
@@ -23,3 +23,4 @@
 
 mod chain_backend;
 mod chain_storage;
+mod snapshot;
@@ -0,0 +1,176 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{io::Cursor, sync::Arc};
+
+use tari_common::configuration::Network;
+use tari_core::{
+    chain_storage::snapshot::{export_snapshot, import_snapshot, SnapshotError},
+    test_helpers::blockchain::create_store_with_consensus,
+    transactions::{tari_amount::T, CryptoFactories},
+    txn_schema,
+    validation::mocks::MockValidator,
+};
+use tari_test_utils::unpack_enum;
+
+use crate::helpers::{block_builders::generate_new_block, sample_blockchains::create_new_blockchain};
+
+#[test]
+fn snapshot_round_trip() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T, 5 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager)
+        .unwrap()
+        .assert_added();
+    let txs = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![2 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager)
+        .unwrap()
+        .assert_added();
+    generate_new_block(&mut store, &mut blocks, &mut outputs, vec![], &consensus_manager)
+        .unwrap()
+        .assert_added();
+
+    let mut snapshot = Vec::new();
+    let summary = export_snapshot(&store, 3, &mut snapshot).unwrap();
+    assert_eq!(summary.height, 3);
+    assert_eq!(summary.best_block, *store.fetch_chain_header(3).unwrap().hash());
+    assert!(summary.num_pruned_outputs > 0);
+
+    let factories = CryptoFactories::default();
+    let validator = MockValidator::new(true);
+    let imported = create_store_with_consensus(consensus_manager.clone());
+    let import_summary = import_snapshot(&imported, Cursor::new(&snapshot), &validator, &factories).unwrap();
+    assert_eq!(import_summary.best_block, summary.best_block);
+    assert_eq!(import_summary.num_unspent_outputs, summary.num_unspent_outputs);
+
+    let metadata = imported.get_chain_metadata().unwrap();
+    assert_eq!(metadata.height_of_longest_chain(), 3);
+    assert_eq!(metadata.pruned_height(), 3);
+    assert_eq!(*metadata.best_block(), summary.best_block);
+    let block1_hash = *store.fetch_chain_header(1).unwrap().hash();
+    let deleted = Arc::new(store.fetch_deleted_bitmap_at_tip().unwrap().into_bitmap());
+    let (expected, _) = store.fetch_utxos_in_block(block1_hash, Some(deleted)).unwrap();
+    let (actual, _) = imported.fetch_utxos_in_block(block1_hash, None).unwrap();
+    assert_eq!(
+        actual.iter().map(|o| o.is_pruned()).collect::<Vec<_>>(),
+        expected.iter().map(|o| o.is_pruned()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        imported.fetch_deleted_bitmap_at_tip().unwrap().bitmap(),
+        store.fetch_deleted_bitmap_at_tip().unwrap().bitmap()
+    );
+
+    // Only empty databases can import a snapshot
+    let err = import_snapshot(&imported, Cursor::new(&snapshot), &validator, &factories).unwrap_err();
+    unpack_enum!(SnapshotError::DatabaseNotEmpty(height) = err);
+    assert_eq!(height, 3);
+}
+
+#[test]
+fn snapshot_export_below_tip() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager)
+        .unwrap()
+        .assert_added();
+    // Spending the block 1 output at height 2 must not prune it from a snapshot at height 1
+    let txs = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![2 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager)
+        .unwrap()
+        .assert_added();
+
+    let mut snapshot = Vec::new();
+    let summary = export_snapshot(&store, 1, &mut snapshot).unwrap();
+    assert_eq!(summary.best_block, *store.fetch_chain_header(1).unwrap().hash());
+
+    let imported = create_store_with_consensus(consensus_manager);
+    import_snapshot(
+        &imported,
+        Cursor::new(&snapshot),
+        &MockValidator::new(true),
+        &CryptoFactories::default(),
+    )
+    .unwrap();
+    assert_eq!(imported.get_chain_metadata().unwrap().height_of_longest_chain(), 1);
+    // Only the genesis output spent in block 1 is deleted
+    assert_eq!(
+        imported.fetch_deleted_bitmap_at_tip().unwrap().bitmap().cardinality(),
+        1
+    );
+
+    let err = export_snapshot(&store, 3, &mut Vec::new()).unwrap_err();
+    unpack_enum!(SnapshotError::HeightNotAvailable { height, .. } = err);
+    assert_eq!(height, 3);
+}
+
+#[test]
+fn snapshot_import_rejects_corrupt_or_invalid_snapshots() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager)
+        .unwrap()
+        .assert_added();
+    let mut snapshot = Vec::new();
+    export_snapshot(&store, 1, &mut snapshot).unwrap();
+    let factories = CryptoFactories::default();
+
+    let mut corrupted = snapshot.clone();
+    let mid = corrupted.len() / 2;
+    corrupted[mid] ^= 0xff;
+    let imported = create_store_with_consensus(consensus_manager.clone());
+    let err = import_snapshot(
+        &imported,
+        Cursor::new(&corrupted),
+        &MockValidator::new(true),
+        &factories,
+    )
+    .unwrap_err();
+    unpack_enum!(SnapshotError::ChecksumMismatch { .. } = err);
+
+    let err = import_snapshot(
+        &imported,
+        Cursor::new(&snapshot),
+        &MockValidator::new(false),
+        &factories,
+    )
+    .unwrap_err();
+    unpack_enum!(SnapshotError::FinalStateValidationFailed(_err) = err);
+    assert_eq!(imported.get_chain_metadata().unwrap().height_of_longest_chain(), 0);
+    // The blocks written before the failure must have been removed again
+    let block1_hash = *store.fetch_chain_header(1).unwrap().hash();
+    assert_eq!(imported.fetch_last_header().unwrap().height, 0);
+    assert!(!imported.block_exists(block1_hash).unwrap());
+    assert_eq!(
+        imported.fetch_deleted_bitmap_at_tip().unwrap().bitmap().cardinality(),
+        0
+    );
+
+    // so that the import can be retried
+    let summary = import_snapshot(&imported, Cursor::new(&snapshot), &MockValidator::new(true), &factories).unwrap();
+    assert_eq!(summary.best_block, block1_hash);
+    let metadata = imported.get_chain_metadata().unwrap();
+    assert_eq!(metadata.height_of_longest_chain(), 1);
+    assert_eq!(*metadata.best_block(), block1_hash);
+}
@@ -276,6 +276,39 @@ pub async fn create_network_with_2_base_nodes_with_config<P: AsRef<Path>>(
     (alice_node, bob_node, consensus_manager)
 }
 
+// Creates a network with two Base Nodes where each node in the network knows the other nodes in the network, using the
+// provided mempool config.
+#[allow(dead_code)]
+pub async fn create_network_with_2_base_nodes_with_mempool_config<P: AsRef<Path>>(
+    mempool_config: MempoolConfig,
+    liveness_service_config: LivenessConfig,
+    consensus_manager: ConsensusManager,
+    data_path: P,
+) -> (NodeInterfaces, NodeInterfaces, ConsensusManager) {
+    let alice_node_identity = random_node_identity();
+    let bob_node_identity = random_node_identity();
+    let network = Network::LocalNet;
+    let (alice_node, consensus_manager) = BaseNodeBuilder::new(network.into())
+        .with_node_identity(alice_node_identity.clone())
+        .with_mempool_config(mempool_config.clone())
+        .with_liveness_service_config(liveness_service_config.clone())
+        .with_consensus_manager(consensus_manager)
+        .start(data_path.as_ref().join("alice").as_os_str().to_str().unwrap())
+        .await;
+    let (bob_node, consensus_manager) = BaseNodeBuilder::new(network.into())
+        .with_node_identity(bob_node_identity)
+        .with_peers(vec![alice_node_identity])
+        .with_mempool_config(mempool_config)
+        .with_liveness_service_config(liveness_service_config)
+        .with_consensus_manager(consensus_manager)
+        .start(data_path.as_ref().join("bob").as_os_str().to_str().unwrap())
+        .await;
+
+    wait_until_online(&[&alice_node, &bob_node]).await;
+
+    (alice_node, bob_node, consensus_manager)
+}
+
 // Creates a network with three Base Nodes where each node in the network knows the other nodes in the network.
 #[allow(dead_code)]
 pub async fn create_network_with_3_base_nodes(
@@ -31,7 +31,7 @@ use helpers::{
         generate_block,
         generate_new_block,
     },
-    nodes::{create_network_with_2_base_nodes_with_config, create_network_with_3_base_nodes_with_config},
+    nodes::{create_network_with_2_base_nodes_with_mempool_config, create_network_with_3_base_nodes_with_config},
     sample_blockchains::{create_new_blockchain, create_new_blockchain_with_constants},
 };
 use randomx_rs::RandomXFlag;
@@ -1217,8 +1217,11 @@ async fn block_event_and_reorg_event_handling() {
         .add_consensus_constants(consensus_constants[0].clone())
         .with_block(block0.clone())
         .build();
-    let (mut alice, mut bob, consensus_manager) = create_network_with_2_base_nodes_with_config(
-        MempoolServiceConfig::default(),
+    // The double spends are expected to reach the reorg pool, so they must not be rejected as replacements
+    let mut mempool_config = MempoolConfig::default();
+    mempool_config.unconfirmed_pool.replace_by_fee_enabled = false;
+    let (mut alice, mut bob, consensus_manager) = create_network_with_2_base_nodes_with_mempool_config(
+        mempool_config,
         LivenessConfig::default(),
         consensus_manager,
         temp_dir.path().to_str().unwrap(),
@@ -1349,3 +1352,55 @@ async fn block_event_and_reorg_event_handling() {
         TxStorageResponse::ReorgPool
     );
 }
+
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn replace_by_fee_rejects_and_replaces_double_spends() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T], fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+
+    let tx1 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx1 = Arc::new(spend_utxos(tx1).0);
+    // Same fee per gram as tx1, so it does not pay for its own relay
+    let tx1_same_fee = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx1_same_fee = Arc::new(spend_utxos(tx1_same_fee).0);
+    let tx1_bumped = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 50*uT, lock: 0, features: OutputFeatures::default());
+    let tx1_bumped = Arc::new(spend_utxos(tx1_bumped).0);
+
+    assert_eq!(
+        mempool.insert(tx1.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool.insert(tx1_same_fee.clone()).await.unwrap(),
+        TxStorageResponse::NotStoredReplacementRejected
+    );
+    assert_eq!(
+        mempool.insert(tx1_bumped.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+
+    // The replaced transaction is evicted
+    for (tx, expected) in [
+        (&tx1, TxStorageResponse::NotStored),
+        (&tx1_same_fee, TxStorageResponse::NotStored),
+        (&tx1_bumped, TxStorageResponse::UnconfirmedPool),
+    ] {
+        let excess_sig = tx.body.kernels()[0].excess_sig.clone();
+        assert_eq!(mempool.has_tx_with_excess_sig(excess_sig).await.unwrap(), expected);
+    }
+    let stats = mempool.stats().await.unwrap();
+    assert_eq!(stats.unconfirmed_txs, 1);
+    assert_eq!(stats.replaced_txs, 1);
+}
@@ -137,6 +137,7 @@ pub type HashSlice = [u8];
 
 mod backend;
 mod mem_backend_vec;
+mod merkle_batch_proof;
 mod merkle_mountain_range;
 mod merkle_proof;
 mod serde_support;
@@ -152,6 +153,8 @@ pub mod pruned_hashset;
 pub use backend::{ArrayLike, ArrayLikeExt};
 /// MemBackendVec is a shareable, memory only, vector that can be be used with MmrCache to store checkpoints.
 pub use mem_backend_vec::MemBackendVec;
+/// A data structure for proving the inclusion of a set of hashes in an MMR
+pub use merkle_batch_proof::MerkleBatchProof;
 /// An immutable, append-only Merkle Mountain range (MMR) data structure
 pub use merkle_mountain_range::MerkleMountainRange;
 /// A data structure for proving a hash inclusion in an MMR
@@ -171,6 +174,7 @@ if_native_bitmap! {
     mod mmr_cache;
     mod mutable_mmr;
     mod mutable_mmr_leaf_nodes;
+    mod mutable_mmr_batch_proof;
     pub mod functions;
 
     /// A Merkle checkpoint contains the set of hash additions and deletion indices.
@@ -182,4 +186,6 @@ if_native_bitmap! {
     pub use mutable_mmr::MutableMmr;
     /// A data structure for storing all the data required to restore the state of an MMR.
     pub use mutable_mmr_leaf_nodes::MutableMmrLeafNodes;
+    /// A data structure for proving the inclusion, and deletion status, of a set of hashes in a MutableMmr
+    pub use mutable_mmr_batch_proof::MutableMmrBatchProof;
 }
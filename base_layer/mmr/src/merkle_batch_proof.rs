@@ -0,0 +1,215 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use tari_common::DomainDigest;
+
+use crate::{
+    backend::ArrayLike,
+    common::{family, find_peaks, hash_together, is_leaf, is_left_sibling, node_index},
+    merkle_proof::MerkleProofError,
+    serde_support,
+    Hash,
+    HashSlice,
+    MerkleMountainRange,
+};
+
+/// A Merkle proof that proves a set of elements exist in an MMR.
+///
+/// Unlike a set of individual [MerkleProof](crate::MerkleProof)s, every sibling hash appears in the proof at most
+/// once: siblings that are shared between the proven leaves, or that can be calculated from other proven leaves, are
+/// left out. Likewise, only the peaks that cannot be calculated from the proven leaves are included.
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Default)]
+pub struct MerkleBatchProof {
+    /// The size of the MMR at the time the proof was created.
+    mmr_size: usize,
+    /// The sibling hashes that cannot be calculated from the proven leaves, in the order they are needed when walking
+    /// up the trees one height at a time.
+    #[serde(with = "serde_support::hash")]
+    path: Vec<Hash>,
+    /// The MMR peaks that do not contain any of the proven leaves
+    #[serde(with = "serde_support::hash")]
+    peaks: Vec<Hash>,
+}
+
+impl MerkleBatchProof {
+    /// Build a batch proof for the given *leaf* positions in the MMR. The leaf indices must be unique.
+    pub fn for_leaf_nodes<D, B>(
+        mmr: &MerkleMountainRange<D, B>,
+        leaf_indices: &[usize],
+    ) -> Result<MerkleBatchProof, MerkleProofError>
+    where
+        D: Digest + DomainDigest,
+        B: ArrayLike<Value = Hash>,
+    {
+        let mmr_size = mmr.len()?;
+        let leaves = leaf_indices
+            .iter()
+            .map(|leaf_index| {
+                let pos = node_index(*leaf_index);
+                let hash = mmr.get_node_hash(pos)?.ok_or(MerkleProofError::HashNotFound(pos))?;
+                Ok((pos, hash))
+            })
+            .collect::<Result<Vec<_>, MerkleProofError>>()?;
+
+        let mut path = Vec::new();
+        let local_peaks = walk_to_peaks::<D, _>(mmr_size, leaves, |pos| {
+            let hash = mmr.get_node_hash(pos)?.ok_or(MerkleProofError::HashNotFound(pos))?;
+            path.push(hash.clone());
+            Ok(hash)
+        })?;
+
+        let mut peaks = Vec::new();
+        for peak_index in find_peaks(mmr_size) {
+            if !local_peaks.contains_key(&peak_index) {
+                let hash = mmr
+                    .get_node_hash(peak_index)?
+                    .ok_or(MerkleProofError::HashNotFound(peak_index))?;
+                peaks.push(hash);
+            }
+        }
+
+        Ok(MerkleBatchProof { mmr_size, path, peaks })
+    }
+
+    /// Reassemble a proof from its parts, e.g. after receiving it over the wire.
+    pub fn from_raw_parts(mmr_size: usize, path: Vec<Hash>, peaks: Vec<Hash>) -> Self {
+        Self { mmr_size, path, peaks }
+    }
+
+    pub fn mmr_size(&self) -> usize {
+        self.mmr_size
+    }
+
+    pub fn path(&self) -> &[Hash] {
+        &self.path
+    }
+
+    pub fn peaks(&self) -> &[Hash] {
+        &self.peaks
+    }
+
+    /// Verifies the proof against the provided root hash for the given set of (leaf index, leaf hash) pairs. The
+    /// leaves must be exactly the set the proof was created for, although their order does not matter.
+    pub fn verify_leaves<D: Digest + DomainDigest>(
+        &self,
+        root: &HashSlice,
+        leaves: &[(usize, &HashSlice)],
+    ) -> Result<(), MerkleProofError> {
+        let calculated_root = self.calculate_root::<D>(leaves)?;
+        if root == calculated_root.as_slice() {
+            Ok(())
+        } else {
+            Err(MerkleProofError::RootMismatch)
+        }
+    }
+
+    /// Calculates the MMR root implied by this proof and the given set of (leaf index, leaf hash) pairs.
+    pub fn calculate_root<D: Digest + DomainDigest>(
+        &self,
+        leaves: &[(usize, &HashSlice)],
+    ) -> Result<Hash, MerkleProofError> {
+        let leaves = leaves
+            .iter()
+            .map(|(leaf_index, hash)| (node_index(*leaf_index), hash.to_vec()))
+            .collect();
+        let mut path = self.path.iter();
+        let local_peaks = walk_to_peaks::<D, _>(self.mmr_size, leaves, |_| {
+            path.next().cloned().ok_or(MerkleProofError::IncorrectSiblingCount)
+        })?;
+        if path.next().is_some() {
+            return Err(MerkleProofError::IncorrectSiblingCount);
+        }
+
+        let mut peaks = self.peaks.iter();
+        let mut hasher = D::new();
+        for peak_index in find_peaks(self.mmr_size) {
+            let hash = match local_peaks.get(&peak_index) {
+                Some(hash) => hash,
+                None => peaks.next().ok_or(MerkleProofError::IncorrectPeakMap)?,
+            };
+            hasher.update(hash);
+        }
+        if peaks.next().is_some() {
+            return Err(MerkleProofError::IncorrectPeakMap);
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+}
+
+/// Walks up from the given leaf nodes to their local peaks one tree height at a time, so that any node that can be
+/// calculated from the leaf set is always known before it is needed as a sibling. `sibling_hash` is called in a
+/// deterministic order for every sibling that cannot be calculated. Returns the hashes of the peaks that were reached,
+/// keyed by MMR index.
+fn walk_to_peaks<D, F>(
+    mmr_size: usize,
+    leaves: Vec<(usize, Hash)>,
+    mut sibling_hash: F,
+) -> Result<BTreeMap<usize, Hash>, MerkleProofError>
+where
+    D: Digest + DomainDigest,
+    F: FnMut(usize) -> Result<Hash, MerkleProofError>,
+{
+    let peaks = find_peaks(mmr_size);
+    // Nodes that still need to be walked up, keyed by (height, MMR index)
+    let mut pending = BTreeMap::new();
+    for (pos, hash) in leaves {
+        if !is_leaf(pos) {
+            return Err(MerkleProofError::NonLeafNode);
+        }
+        if pos >= mmr_size {
+            return Err(MerkleProofError::HashNotFound(pos));
+        }
+        if pending.insert((0usize, pos), hash).is_some() {
+            return Err(MerkleProofError::DuplicateLeafNode(pos));
+        }
+    }
+
+    let mut local_peaks = BTreeMap::new();
+    while let Some((height, pos)) = pending.keys().next().copied() {
+        let hash = pending
+            .remove(&(height, pos))
+            .expect("key was taken from the map, so must exist");
+        if peaks.contains(&pos) {
+            local_peaks.insert(pos, hash);
+            continue;
+        }
+        let (parent_pos, sibling_pos) = family(pos)?;
+        if parent_pos >= mmr_size {
+            return Err(MerkleProofError::Unexpected);
+        }
+        let sibling = match pending.remove(&(height, sibling_pos)) {
+            Some(sibling) => sibling,
+            None => sibling_hash(sibling_pos)?,
+        };
+        let parent = if is_left_sibling(pos) {
+            hash_together::<D>(&hash, &sibling)
+        } else {
+            hash_together::<D>(&sibling, &hash)
+        };
+        pending.insert((height + 1, parent_pos), parent);
+    }
+    Ok(local_peaks)
+}
@@ -50,6 +50,12 @@ pub enum MerkleProofError {
     HashNotFound(usize),
     #[error("The list of peak hashes provided in the proof has an error")]
     IncorrectPeakMap,
+    #[error("The batch proof does not contain the expected number of sibling hashes")]
+    IncorrectSiblingCount,
+    #[error("The leaf node at position `{0}` was given more than once")]
+    DuplicateLeafNode(usize),
+    #[error("The deleted bitmap in the proof could not be deserialized")]
+    InvalidDeletedBitmap,
     #[error("Unexpected error")]
     Unexpected,
     #[error("Merkle mountain range error: `{0}`")]
@@ -0,0 +1,105 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use croaring::Bitmap;
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use tari_common::DomainDigest;
+
+use crate::{backend::ArrayLike, Hash, HashSlice, MerkleBatchProof, MerkleProofError, MutableMmr};
+
+/// A batch proof for a set of leaves in a [MutableMmr].
+///
+/// The merkle(ish) root of a `MutableMmr` commits to the deleted bitmap as well as to the MMR, so the proof carries
+/// the serialised bitmap. Once the proof has been verified, the bitmap can be trusted to tell whether each proven leaf
+/// has been marked as deleted (e.g. whether a UTXO has been spent).
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Default)]
+pub struct MutableMmrBatchProof {
+    proof: MerkleBatchProof,
+    /// The deleted bitmap, serialised exactly as it is hashed into the root
+    deleted: Vec<u8>,
+}
+
+impl MutableMmrBatchProof {
+    /// Build a batch proof for the given leaf positions. As with [MutableMmr::get_merkle_root], the deleted bitmap
+    /// should have been compressed first, otherwise the proof will not match the root. Leaves that have been marked
+    /// as deleted can still be proven.
+    pub fn for_leaf_nodes<D, B>(
+        mmr: &MutableMmr<D, B>,
+        leaf_indices: &[u32],
+    ) -> Result<MutableMmrBatchProof, MerkleProofError>
+    where
+        D: Digest + DomainDigest,
+        B: ArrayLike<Value = Hash>,
+    {
+        let leaf_indices = leaf_indices.iter().map(|i| *i as usize).collect::<Vec<_>>();
+        Ok(MutableMmrBatchProof {
+            proof: MerkleBatchProof::for_leaf_nodes(&mmr.mmr, &leaf_indices)?,
+            deleted: mmr.deleted.serialize(),
+        })
+    }
+
+    /// Reassemble a proof from its parts, e.g. after receiving it over the wire.
+    pub fn from_raw_parts(proof: MerkleBatchProof, deleted: Vec<u8>) -> Self {
+        Self { proof, deleted }
+    }
+
+    pub fn proof(&self) -> &MerkleBatchProof {
+        &self.proof
+    }
+
+    /// The serialised deleted bitmap
+    pub fn deleted_bytes(&self) -> &[u8] {
+        &self.deleted
+    }
+
+    /// Verifies the proof against the `MutableMmr` root for the given set of (leaf index, leaf hash) pairs.
+    pub fn verify_leaves<D: Digest + DomainDigest>(
+        &self,
+        root: &HashSlice,
+        leaves: &[(u32, &HashSlice)],
+    ) -> Result<(), MerkleProofError> {
+        let leaves = leaves
+            .iter()
+            .map(|(leaf_index, hash)| (*leaf_index as usize, *hash))
+            .collect::<Vec<_>>();
+        let mmr_root = self.proof.calculate_root::<D>(&leaves)?;
+        let mut hasher = D::new();
+        hasher.update(&mmr_root);
+        hasher.update(&self.deleted);
+        if root == hasher.finalize().as_slice() {
+            Ok(())
+        } else {
+            Err(MerkleProofError::RootMismatch)
+        }
+    }
+
+    /// Returns the deleted bitmap carried by the proof
+    pub fn deleted_bitmap(&self) -> Result<Bitmap, MerkleProofError> {
+        Bitmap::try_deserialize(&self.deleted).ok_or(MerkleProofError::InvalidDeletedBitmap)
+    }
+
+    /// Returns true if the leaf has been marked as deleted. This is only meaningful once the proof has been verified.
+    pub fn is_deleted(&self, leaf_index: u32) -> Result<bool, MerkleProofError> {
+        Ok(self.deleted_bitmap()?.contains(leaf_index))
+    }
+}
@@ -0,0 +1,175 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+#[allow(dead_code)]
+mod support;
+
+use tari_mmr::{MerkleBatchProof, MerkleProof, MerkleProofError, MutableMmrBatchProof};
+
+use crate::support::{create_mmr, create_mutable_mmr, int_to_hash, MmrTestHasherBlake256};
+
+fn leaves(indices: &[usize]) -> Vec<(usize, Vec<u8>)> {
+    indices.iter().map(|i| (*i, int_to_hash(*i))).collect()
+}
+
+fn as_refs(leaves: &[(usize, Vec<u8>)]) -> Vec<(usize, &[u8])> {
+    leaves.iter().map(|(i, h)| (*i, h.as_slice())).collect()
+}
+
+/// Check every pair of leaves, and a few larger sets, in various MMR sizes
+#[test]
+fn batch_proof_small_mmrs() {
+    for size in 1..24 {
+        let mmr = create_mmr(size);
+        let root = mmr.get_merkle_root().unwrap();
+        for a in 0..size {
+            for b in a..size {
+                let indices = if a == b { vec![a] } else { vec![a, b] };
+                let proof = MerkleBatchProof::for_leaf_nodes(&mmr, &indices).unwrap();
+                let leaves = leaves(&indices);
+                proof
+                    .verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&leaves))
+                    .unwrap();
+            }
+        }
+        let all = (0..size).collect::<Vec<_>>();
+        let proof = MerkleBatchProof::for_leaf_nodes(&mmr, &all).unwrap();
+        assert!(proof.path().is_empty());
+        assert!(proof.peaks().is_empty());
+        proof
+            .verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&leaves(&all)))
+            .unwrap();
+    }
+}
+
+#[test]
+fn batch_proof_is_smaller_than_individual_proofs() {
+    let mmr = create_mmr(1000);
+    let root = mmr.get_merkle_root().unwrap();
+    let indices = [10, 11, 12, 13, 500, 501, 998];
+    let proof = MerkleBatchProof::for_leaf_nodes(&mmr, &indices).unwrap();
+    // Leaves may be given in any order
+    let mut leaves = leaves(&indices);
+    leaves.reverse();
+    proof
+        .verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&leaves))
+        .unwrap();
+
+    let individual_hashes: usize = indices
+        .iter()
+        .map(|i| {
+            let proof = MerkleProof::for_leaf_node(&mmr, *i).unwrap();
+            let json = serde_json::to_value(&proof).unwrap();
+            json["path"].as_array().unwrap().len() + json["peaks"].as_array().unwrap().len()
+        })
+        .sum();
+    assert!(proof.path().len() + proof.peaks().len() < individual_hashes);
+}
+
+#[test]
+fn batch_proof_rejects_wrong_leaves() {
+    let mmr = create_mmr(100);
+    let root = mmr.get_merkle_root().unwrap();
+    let proof = MerkleBatchProof::for_leaf_nodes(&mmr, &[3, 40, 77]).unwrap();
+
+    // Wrong hash for a leaf
+    let mut wrong = leaves(&[3, 40, 77]);
+    wrong[1].1 = int_to_hash(41);
+    assert_eq!(
+        proof.verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&wrong)),
+        Err(MerkleProofError::RootMismatch)
+    );
+
+    // A leaf is missing, so the proof has too few siblings
+    let missing = leaves(&[3, 40]);
+    assert!(proof
+        .verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&missing))
+        .is_err());
+
+    // A leaf is given twice
+    let duplicate = leaves(&[3, 40, 40]);
+    assert_eq!(
+        proof.verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&duplicate)),
+        Err(MerkleProofError::DuplicateLeafNode(78))
+    );
+
+    // Leaves outside of the MMR
+    assert_eq!(
+        MerkleBatchProof::for_leaf_nodes(&mmr, &[100]),
+        Err(MerkleProofError::HashNotFound(197))
+    );
+}
+
+#[test]
+fn batch_proof_serialisation() {
+    let mmr = create_mmr(50);
+    let root = mmr.get_merkle_root().unwrap();
+    let proof = MerkleBatchProof::for_leaf_nodes(&mmr, &[1, 2, 33]).unwrap();
+    let json = serde_json::to_string(&proof).unwrap();
+    let proof: MerkleBatchProof = serde_json::from_str(&json).unwrap();
+    let proof = MerkleBatchProof::from_raw_parts(proof.mmr_size(), proof.path().to_vec(), proof.peaks().to_vec());
+    proof
+        .verify_leaves::<MmrTestHasherBlake256>(&root, &as_refs(&leaves(&[1, 2, 33])))
+        .unwrap();
+}
+
+#[test]
+fn mutable_mmr_batch_proof() {
+    let mut mmr = create_mutable_mmr(64);
+    assert!(mmr.delete(5));
+    assert!(mmr.delete(30));
+    mmr.compress();
+    let root = mmr.get_merkle_root().unwrap();
+
+    let proof = MutableMmrBatchProof::for_leaf_nodes(&mmr, &[5, 6, 40]).unwrap();
+    let leaves = [5u32, 6, 40]
+        .iter()
+        .map(|i| (*i, int_to_hash(*i as usize)))
+        .collect::<Vec<_>>();
+    let refs = leaves.iter().map(|(i, h)| (*i, h.as_slice())).collect::<Vec<_>>();
+    proof.verify_leaves::<MmrTestHasherBlake256>(&root, &refs).unwrap();
+    assert!(proof.is_deleted(5).unwrap());
+    assert!(!proof.is_deleted(6).unwrap());
+    assert!(!proof.is_deleted(40).unwrap());
+
+    // The proof commits to the deleted bitmap, so it cannot be swapped for another one
+    let mut other = create_mutable_mmr(64);
+    other.compress();
+    let forged = MutableMmrBatchProof::from_raw_parts(
+        proof.proof().clone(),
+        MutableMmrBatchProof::for_leaf_nodes(&other, &[5])
+            .unwrap()
+            .deleted_bytes()
+            .to_vec(),
+    );
+    assert_eq!(
+        forged.verify_leaves::<MmrTestHasherBlake256>(&root, &refs),
+        Err(MerkleProofError::RootMismatch)
+    );
+
+    // The plain MMR root is not accepted in place of the merkle(ish) root
+    let mmr_root = mmr.get_mmr_only_root().unwrap();
+    assert_eq!(
+        proof.verify_leaves::<MmrTestHasherBlake256>(&mmr_root, &refs),
+        Err(MerkleProofError::RootMismatch)
+    );
+}
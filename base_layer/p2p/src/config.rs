@@ -31,7 +31,7 @@ use tari_common::{
     DnsNameServer,
     SubConfigPath,
 };
-use tari_comms::multiaddr::Multiaddr;
+use tari_comms::{multiaddr::Multiaddr, resource_manager::ResourceManagerConfig};
 use tari_comms_dht::{DbConnectionUrl, DhtConfig};
 
 use crate::{transport::TransportConfig, DEFAULT_DNS_NAME_SERVER};
@@ -119,6 +119,8 @@ pub struct P2pConfig {
     /// The maximum allowed RPC sessions per peer.
     /// Default: 10
     pub rpc_max_sessions_per_peer: usize,
+    /// Per-peer inbound resource budgets for messaging, RPC and substreams. Disabled by default.
+    pub resource_manager: ResourceManagerConfig,
 }
 
 impl Default for P2pConfig {
@@ -142,6 +144,7 @@ impl Default for P2pConfig {
             auxiliary_tcp_listener_address: None,
             rpc_max_simultaneous_sessions: 100,
             rpc_max_sessions_per_peer: 10,
+            resource_manager: Default::default(),
         }
     }
 }
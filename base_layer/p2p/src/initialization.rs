@@ -51,7 +51,14 @@ use tari_comms::{
     },
     tor,
     tor::HiddenServiceControllerError,
-    transports::{predicate::FalsePredicate, MemoryTransport, SocksConfig, SocksTransport, TcpWithTorTransport},
+    transports::{
+        predicate::FalsePredicate,
+        MemoryTransport,
+        QuicTransport,
+        SocksConfig,
+        SocksTransport,
+        TcpWithTorTransport,
+    },
     utils::cidr::parse_cidrs,
     CommsBuilder,
     CommsBuilderError,
@@ -266,6 +273,14 @@ pub async fn spawn_comms_using_transport(
                 .spawn_with_transport(transport)
                 .await?
         },
+        TransportType::Quic => {
+            debug!(target: LOG_TARGET, "Building QUIC comms stack");
+            let transport = QuicTransport::new()?;
+            comms
+                .with_listener_address(transport_config.quic.listener_address)
+                .spawn_with_transport(transport)
+                .await?
+        },
     };
 
     Ok(comms)
@@ -319,6 +334,7 @@ async fn configure_comms_and_dht(
         .with_listener_liveness_max_sessions(config.listener_liveness_max_sessions)
         .with_listener_liveness_allowlist_cidrs(listener_liveness_allowlist_cidrs)
         .with_dial_backoff(ConstantBackoff::new(Duration::from_millis(500)))
+        .with_resource_manager_config(config.resource_manager.clone())
         .with_peer_storage(peer_database, Some(file_lock));
 
     let mut comms = match config.auxiliary_tcp_listener_address {
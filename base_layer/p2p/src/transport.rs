@@ -42,6 +42,7 @@ pub struct TransportConfig {
     pub tor: TorTransportConfig,
     pub socks: Socks5TransportConfig,
     pub memory: MemoryTransportConfig,
+    pub quic: QuicTransportConfig,
 }
 
 impl TransportConfig {
@@ -61,6 +62,14 @@ impl TransportConfig {
         }
     }
 
+    pub fn new_quic(config: QuicTransportConfig) -> Self {
+        Self {
+            transport_type: TransportType::Quic,
+            quic: config,
+            ..Default::default()
+        }
+    }
+
     pub fn new_tor(config: TorTransportConfig) -> Self {
         Self {
             transport_type: TransportType::Tor,
@@ -99,6 +108,9 @@ pub enum TransportType {
     Tor,
     /// Use a SOCKS5 proxy transport. This transport allows any addresses supported by the proxy.
     Socks5,
+    /// Use QUIC to join the Tari network. This transport can only contact peers with /ip4 or /ip6 QUIC addresses in
+    /// the form '/ip4/x.x.x.x/udp/x/quic'.
+    Quic,
 }
 
 impl Default for TransportType {
@@ -226,6 +238,21 @@ impl Default for Socks5TransportConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuicTransportConfig {
+    /// Socket to bind the QUIC listener
+    pub listener_address: Multiaddr,
+}
+
+impl Default for QuicTransportConfig {
+    fn default() -> Self {
+        Self {
+            listener_address: "/ip4/0.0.0.0/udp/18189/quic".parse().unwrap(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MemoryTransportConfig {
@@ -135,6 +135,10 @@ pub enum OutputManagerRequest {
     },
 
     ReinstateCancelledInboundTx(TxId),
+    ReassignEncumberedOutputs {
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+    },
     SetCoinbaseAbandoned(TxId, bool),
     CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroTari),
     CreateHtlcRefundTransaction(HashOutput, MicroTari),
@@ -215,6 +219,9 @@ impl fmt::Display for OutputManagerRequest {
             },
             CreatePayToSelfWithOutputs { .. } => write!(f, "CreatePayToSelfWithOutputs"),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
+            ReassignEncumberedOutputs { from_tx_id, to_tx_id } => {
+                write!(f, "ReassignEncumberedOutputs ({} -> {})", from_tx_id, to_tx_id)
+            },
             SetCoinbaseAbandoned(_, _) => write!(f, "SetCoinbaseAbandoned"),
             CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => write!(
                 f,
@@ -267,6 +274,7 @@ pub enum OutputManagerResponse {
     CreateOutputWithFeatures { output: Box<UnblindedOutputBuilder> },
     CreatePayToSelfWithOutputs { transaction: Box<Transaction>, tx_id: TxId },
     ReinstatedCancelledInboundTx,
+    EncumberedOutputsReassigned,
     CoinbaseAbandonedSet,
     ClaimHtlcTransaction((TxId, MicroTari, MicroTari, Transaction)),
     OutputStatusesByTxId(OutputStatusesByTxId),
@@ -888,6 +896,22 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Move the encumberance of the outputs spent by `from_tx_id` over to `to_tx_id`, without releasing them in between
+    pub async fn reassign_encumbered_outputs(
+        &mut self,
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ReassignEncumberedOutputs { from_tx_id, to_tx_id })
+            .await??
+        {
+            OutputManagerResponse::EncumberedOutputsReassigned => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn set_coinbase_abandoned(&mut self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -25,7 +25,7 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-use tari_common_types::types::Commitment;
+use tari_common_types::{transaction::TxId, types::Commitment};
 
 #[derive(Debug, Clone, Default)]
 pub struct UtxoSelectionCriteria {
@@ -59,6 +59,14 @@ impl UtxoSelectionCriteria {
             ..Default::default()
         }
     }
+
+    pub fn encumbered_by(tx_id: TxId) -> Self {
+        Self {
+            filter: UtxoSelectionFilter::EncumberedBy { tx_id },
+            ordering: UtxoSelectionOrdering::Default,
+            ..Default::default()
+        }
+    }
 }
 
 impl Display for UtxoSelectionCriteria {
@@ -103,6 +111,9 @@ pub enum UtxoSelectionFilter {
     Standard,
     /// Selects specific outputs. All outputs must be exist and be spendable.
     SpecificOutputs { commitments: Vec<Commitment> },
+    /// Selects the outputs that are already encumbered to be spent by the given transaction, so that a replacement can
+    /// be built without releasing them first
+    EncumberedBy { tx_id: TxId },
 }
 impl UtxoSelectionFilter {
     pub fn is_standard(&self) -> bool {
@@ -125,6 +136,9 @@ impl Display for UtxoSelectionFilter {
             UtxoSelectionFilter::SpecificOutputs { commitments: outputs } => {
                 write!(f, "Specific({} output(s))", outputs.len())
             },
+            UtxoSelectionFilter::EncumberedBy { tx_id } => {
+                write!(f, "EncumberedBy({})", tx_id)
+            },
         }
     }
 }
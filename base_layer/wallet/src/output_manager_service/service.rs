@@ -437,6 +437,9 @@ where
             OutputManagerRequest::ReinstateCancelledInboundTx(tx_id) => self
                 .reinstate_cancelled_inbound_transaction_outputs(tx_id)
                 .map(|_| OutputManagerResponse::ReinstatedCancelledInboundTx),
+            OutputManagerRequest::ReassignEncumberedOutputs { from_tx_id, to_tx_id } => self
+                .reassign_encumbered_outputs(from_tx_id, to_tx_id)
+                .map(|_| OutputManagerResponse::EncumberedOutputsReassigned),
             OutputManagerRequest::CreateOutputWithFeatures { value, features } => {
                 let unblinded_output = self.create_output_with_features(value, *features).await?;
                 Ok(OutputManagerResponse::CreateOutputWithFeatures {
@@ -1385,6 +1388,18 @@ where
         Ok(())
     }
 
+    /// Move the encumberance of the outputs spent by one transaction over to another, so that the outputs are never
+    /// available to other transactions in between (used when replacing a transaction).
+    fn reassign_encumbered_outputs(&mut self, from_tx_id: TxId, to_tx_id: TxId) -> Result<(), OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Reassigning encumbered outputs from TxId: {} to TxId: {}", from_tx_id, to_tx_id
+        );
+        self.resources.db.reassign_encumbered_outputs(from_tx_id, to_tx_id)?;
+
+        Ok(())
+    }
+
     /// Select which unspent transaction outputs to use to send a transaction of the specified amount. Use the specified
     /// selection strategy to choose the outputs. It also determines if a change output is required.
     #[allow(clippy::too_many_lines)]
@@ -95,6 +95,9 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError>;
     /// Reinstate a cancelled inbound output
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// Atomically move the encumberance of the outputs to be spent by `from_tx_id` over to `to_tx_id`. The outputs
+    /// that `from_tx_id` is to receive are left as they are.
+    fn reassign_encumbered_outputs(&self, from_tx_id: TxId, to_tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
     fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
     /// Import unvalidated output
@@ -327,6 +327,14 @@ where T: OutputManagerBackend + 'static
         self.db.reinstate_cancelled_inbound_output(tx_id)
     }
 
+    pub fn reassign_encumbered_outputs(
+        &self,
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.reassign_encumbered_outputs(from_tx_id, to_tx_id)
+    }
+
     pub fn apply_encryption(&self, cipher: XChaCha20Poly1305) -> Result<(), OutputManagerStorageError> {
         self.db.apply_encryption(cipher)
     }
@@ -666,6 +666,13 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
         for i in outputs_to_send {
             let output = OutputSql::find_by_commitment_and_cancelled(i.commitment.as_bytes(), false, &conn)?;
+            // Outputs that are already encumbered to be spent by this transaction (such as the inputs of a fee bump
+            // replacement) keep their encumberance
+            if output.status == (OutputStatus::EncumberedToBeSpent as i32) &&
+                output.spent_in_tx_id == Some(tx_id.as_i64_wrapped())
+            {
+                continue;
+            }
             if output.status != (OutputStatus::Unspent as i32) {
                 return Err(OutputManagerStorageError::OutputAlreadySpent);
             }
@@ -1146,6 +1153,29 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn reassign_encumbered_outputs(&self, from_tx_id: TxId, to_tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let num_outputs = OutputSql::reassign_encumbered_to_be_spent(from_tx_id, to_tx_id, &conn)?;
+        if num_outputs == 0 {
+            return Err(OutputManagerStorageError::ValueNotFound);
+        }
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - reassign_encumbered_outputs (TxId: {} -> {}): lock {} + db_op {} = {} ms",
+                from_tx_id,
+                to_tx_id,
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn add_unvalidated_output(&self, output: DbUnblindedOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
@@ -197,14 +197,11 @@ impl OutputSql {
         tip_height: Option<u64>,
         conn: &SqliteConnection,
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
-        let mut query = outputs::table
-            .into_boxed()
-            .filter(outputs::status.eq(OutputStatus::Unspent as i32))
-            .order_by(outputs::spending_priority.desc());
+        let mut query = outputs::table.into_boxed().order_by(outputs::spending_priority.desc());
 
         match &selection_criteria.filter {
             UtxoSelectionFilter::Standard => {
-                query = query.filter(
+                query = query.filter(outputs::status.eq(OutputStatus::Unspent as i32)).filter(
                     outputs::output_type
                         .eq(i32::from(OutputType::Standard.as_byte()))
                         .or(outputs::output_type.eq(i32::from(OutputType::Coinbase.as_byte()))),
@@ -215,6 +212,7 @@ impl OutputSql {
                 }
             },
             UtxoSelectionFilter::SpecificOutputs { commitments } => {
+                query = query.filter(outputs::status.eq(OutputStatus::Unspent as i32));
                 query = match commitments.len() {
                     0 => query,
                     1 => query.filter(outputs::commitment.eq(commitments[0].to_vec())),
@@ -223,6 +221,11 @@ impl OutputSql {
                     ),
                 };
             },
+            UtxoSelectionFilter::EncumberedBy { tx_id } => {
+                query = query
+                    .filter(outputs::status.eq(OutputStatus::EncumberedToBeSpent as i32))
+                    .filter(outputs::spent_in_tx_id.eq(tx_id.as_i64_wrapped()));
+            },
         }
 
         for exclude in &selection_criteria.excluding {
@@ -515,6 +518,25 @@ impl OutputSql {
             .load(conn)?)
     }
 
+    /// Moves the encumberance of the outputs that are to be spent by `from_tx_id` to `to_tx_id` in a single statement,
+    /// returning the number of outputs that were moved
+    pub fn reassign_encumbered_to_be_spent(
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+        conn: &SqliteConnection,
+    ) -> Result<usize, OutputManagerStorageError> {
+        Ok(diesel::update(
+            outputs::table
+                .filter(outputs::spent_in_tx_id.eq(from_tx_id.as_i64_wrapped()))
+                .filter(outputs::status.eq_any(vec![
+                    OutputStatus::EncumberedToBeSpent as i32,
+                    OutputStatus::ShortTermEncumberedToBeSpent as i32,
+                ])),
+        )
+        .set(outputs::spent_in_tx_id.eq(to_tx_id.as_i64_wrapped()))
+        .execute(conn)?)
+    }
+
     /// Find outputs via tx_id that are encumbered. Any outputs that are encumbered cannot be marked as spent.
     pub fn find_by_tx_id_and_encumbered(
         tx_id: TxId,
@@ -54,6 +54,8 @@ pub enum TransactionServiceError {
     InvalidStateError,
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
+    #[error("Fee bump error: `{0}`")]
+    FeeBumpError(String),
     #[error("Transaction Protocol Error: `{0}`")]
     TransactionProtocolError(#[from] TransactionProtocolError),
     #[error("The message being processed is not recognized by the Transaction Manager")]
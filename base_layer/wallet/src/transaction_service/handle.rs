@@ -106,6 +106,11 @@ pub enum TransactionServiceRequest {
     },
     SendShaAtomicSwapTransaction(CommsPublicKey, MicroTari, UtxoSelectionCriteria, MicroTari, String),
     CancelTransaction(TxId),
+    /// Replaces an unmined outbound transaction with one that spends the same inputs at a higher fee per gram.
+    BumpTransactionFee {
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+    },
     ImportUtxoWithStatus {
         amount: MicroTari,
         source_public_key: CommsPublicKey,
@@ -182,6 +187,9 @@ impl fmt::Display for TransactionServiceRequest {
                 f.write_str(&format!("SendShaAtomicSwapTransaction (to {}, {}, {})", k, v, msg))
             },
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
+            Self::BumpTransactionFee { tx_id, fee_per_gram } => {
+                f.write_str(&format!("BumpTransactionFee ({}, {})", tx_id, fee_per_gram))
+            },
             Self::ImportUtxoWithStatus {
                 amount,
                 source_public_key,
@@ -541,6 +549,23 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Replaces an outbound transaction that has not been mined yet with a new transaction that spends the same inputs
+    /// at the given fee per gram, returning the id of the replacement. The original transaction is cancelled.
+    pub async fn bump_transaction_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BumpTransactionFee { tx_id, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_pending_inbound_transactions(
         &mut self,
     ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
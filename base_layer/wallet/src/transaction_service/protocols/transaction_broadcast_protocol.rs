@@ -101,6 +101,13 @@ where
 
             let completed_tx = match self.resources.db.get_completed_transaction(self.tx_id) {
                 Ok(tx) => tx,
+                Err(_) if self.was_cancelled() => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Transaction (TxId: {}) has been cancelled and will stop being broadcast", self.tx_id
+                    );
+                    return Ok(self.tx_id);
+                },
                 Err(e) => {
                     error!(
                         target: LOG_TARGET,
@@ -216,6 +223,10 @@ where
         }
 
         if !response.accepted && response.rejection_reason != TxSubmissionRejectionReason::AlreadyMined {
+            // A transaction that was replaced while this submission was in flight is rejected as a double spend
+            if self.was_cancelled() {
+                return Ok(false);
+            }
             error!(
                 target: LOG_TARGET,
                 "Transaction (TxId: {}) rejected by Base Node for reason: {}", self.tx_id, response.rejection_reason
@@ -417,6 +428,15 @@ where
         }
     }
 
+    /// Returns true if the transaction has been cancelled since the protocol started, e.g. because it was replaced by
+    /// a fee bump
+    fn was_cancelled(&self) -> bool {
+        self.resources
+            .db
+            .get_cancelled_completed_transaction(self.tx_id)
+            .is_ok()
+    }
+
     async fn cancel_transaction(&mut self, reason: TxCancellationReason) {
         if let Err(e) = self
             .resources
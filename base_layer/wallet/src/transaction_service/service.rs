@@ -22,7 +22,7 @@
 
 use std::{
     collections::{HashMap, HashSet},
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -40,6 +40,7 @@ use tari_common_types::{
 use tari_comms::{peer_manager::NodeIdentity, types::CommsPublicKey};
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_core::{
+    base_node::proto::wallet_rpc::TxSubmissionResponse,
     covenants::Covenant,
     mempool::FeePerGramStat,
     proto::base_node as base_node_proto,
@@ -668,6 +669,10 @@ where
                 .cancel_pending_transaction(tx_id)
                 .await
                 .map(|_| TransactionServiceResponse::TransactionCancelled),
+            TransactionServiceRequest::BumpTransactionFee { tx_id, fee_per_gram } => self
+                .bump_transaction_fee(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::GetPendingInboundTransactions => Ok(
                 TransactionServiceResponse::PendingInboundTransactions(self.db.get_pending_inbound_transactions()?),
             ),
@@ -1185,7 +1190,44 @@ where
         script: TariScript,
     ) -> Result<TxId, TransactionServiceError> {
         let tx_id = TxId::new_random();
+        let completed_tx = self
+            .build_one_sided_or_stealth(
+                tx_id,
+                dest_pubkey,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                message,
+                script,
+            )
+            .await?;
+
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        // Broadcast one-sided transaction
+        self.submit_transaction(transaction_broadcast_join_handles, completed_tx)?;
 
+        Ok(tx_id)
+    }
+
+    /// Builds and finalizes a one-sided or stealth transaction. The outputs it spends are encumbered under `tx_id`, but
+    /// the transaction is neither stored nor broadcast.
+    async fn build_one_sided_or_stealth(
+        &mut self,
+        tx_id: TxId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        script: TariScript,
+    ) -> Result<CompletedTransaction, TransactionServiceError> {
         // Prepare sender part of the transaction
         let mut stp = self
             .output_manager_service
@@ -1265,40 +1307,28 @@ where
         })?;
         info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
 
-        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
-        // there are no subscribers.
-        let _result = self
-            .event_publisher
-            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
-
-        // Broadcast one-sided transaction
-
         let tx = stp
             .get_transaction()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
         let fee = stp
             .get_fee_amount()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        self.submit_transaction(
-            transaction_broadcast_join_handles,
-            CompletedTransaction::new(
-                tx_id,
-                self.resources.node_identity.public_key().clone(),
-                dest_pubkey.clone(),
-                amount,
-                fee,
-                tx.clone(),
-                TransactionStatus::Completed,
-                message.clone(),
-                Utc::now().naive_utc(),
-                TransactionDirection::Outbound,
-                None,
-                None,
-                None,
-            ),
-        )?;
 
-        Ok(tx_id)
+        Ok(CompletedTransaction::new(
+            tx_id,
+            self.resources.node_identity.public_key().clone(),
+            dest_pubkey,
+            amount,
+            fee,
+            tx,
+            TransactionStatus::Completed,
+            message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+            None,
+        ))
     }
 
     /// Sends a one side payment transaction to a recipient
@@ -1337,6 +1367,184 @@ where
         .await
     }
 
+    /// Replaces an outbound one-sided transaction that has not been mined yet with a new transaction that spends the
+    /// same inputs at a higher fee per gram. The inputs stay encumbered throughout, as their encumberance is moved
+    /// from the original to the replacement before it is built. The original transaction is only cancelled once the
+    /// base node has accepted the replacement into its mempool, evicting the original under the replace-by-fee policy.
+    /// If the replacement cannot be built or is rejected, the encumberance is moved back and the original transaction
+    /// is left as it was.
+    pub async fn bump_transaction_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let completed_tx = self.db.get_completed_transaction(tx_id)?;
+        if completed_tx.direction != TransactionDirection::Outbound ||
+            !matches!(
+                completed_tx.status,
+                TransactionStatus::Completed | TransactionStatus::Broadcast
+            )
+        {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Transaction {} is not an unmined outbound transaction",
+                tx_id
+            )));
+        }
+
+        // Only one-sided transactions can be rebuilt without the recipient's involvement
+        let recipient_script = script!(PushPubKey(Box::new(completed_tx.destination_public_key.clone())));
+        let output_features = completed_tx
+            .transaction
+            .body
+            .outputs()
+            .iter()
+            .find(|output| output.script == recipient_script)
+            .map(|output| output.features.clone())
+            .ok_or_else(|| {
+                TransactionServiceError::FeeBumpError(format!("Transaction {} is not a one-sided transaction", tx_id))
+            })?;
+
+        // Hand the inputs of the original transaction over to the replacement without ever releasing them, so that no
+        // other transaction can spend them while the replacement is built and submitted
+        let new_tx_id = TxId::new_random();
+        self.output_manager_service
+            .reassign_encumbered_outputs(tx_id, new_tx_id)
+            .await?;
+        let replacement = match self
+            .build_and_submit_replacement(new_tx_id, &completed_tx, output_features, fee_per_gram)
+            .await
+        {
+            Ok(replacement) => replacement,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not replace transaction (TxId: {}), restoring its inputs: {}", tx_id, e
+                );
+                self.restore_replaced_transaction(tx_id, new_tx_id).await?;
+                return Err(e);
+            },
+        };
+
+        // The base node has evicted the original transaction, so only the change it was to receive is still encumbered
+        // by it, and its broadcast protocol stops once it sees it rejected
+        if let Err(e) = self.output_manager_service.cancel_transaction(tx_id).await {
+            debug!(
+                target: LOG_TARGET,
+                "No change outputs to release for replaced transaction (TxId: {}): {}", tx_id, e
+            );
+        }
+        self.db
+            .reject_completed_transaction(tx_id, TxCancellationReason::Replaced)?;
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCancelled(
+                tx_id,
+                TxCancellationReason::Replaced,
+            )))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(new_tx_id)));
+        self.submit_transaction(transaction_broadcast_join_handles, replacement)?;
+
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) replaced by transaction (TxId: {}) at {} per gram", tx_id, new_tx_id, fee_per_gram
+        );
+
+        Ok(new_tx_id)
+    }
+
+    /// Builds the replacement for a one-sided transaction from the inputs already encumbered by `tx_id` and submits it
+    /// to the base node. The replacement is only returned if the base node accepted it.
+    async fn build_and_submit_replacement(
+        &mut self,
+        tx_id: TxId,
+        original: &CompletedTransaction,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+    ) -> Result<CompletedTransaction, TransactionServiceError> {
+        let replacement = self
+            .build_one_sided_or_stealth(
+                tx_id,
+                original.destination_public_key.clone(),
+                original.amount,
+                UtxoSelectionCriteria::encumbered_by(tx_id),
+                output_features,
+                fee_per_gram,
+                original.message.clone(),
+                script!(PushPubKey(Box::new(original.destination_public_key.clone()))),
+            )
+            .await?;
+        if replacement.fee <= original.fee {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "The replacement fee of {} does not exceed the original fee of {}",
+                replacement.fee, original.fee
+            )));
+        }
+
+        let mut client = self
+            .resources
+            .connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or(TransactionServiceError::Shutdown)?;
+        let response = client
+            .submit_transaction(
+                replacement
+                    .transaction
+                    .clone()
+                    .try_into()
+                    .map_err(TransactionServiceError::InvalidMessageError)?,
+            )
+            .await?;
+        let response =
+            TxSubmissionResponse::try_from(response).map_err(TransactionServiceError::InvalidMessageError)?;
+        if !response.is_synced {
+            return Err(TransactionServiceError::FeeBumpError(
+                "Base node is not synced, the replacement was not submitted".to_string(),
+            ));
+        }
+        if !response.accepted {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Base node rejected the replacement: {}",
+                response.rejection_reason
+            )));
+        }
+
+        Ok(replacement)
+    }
+
+    /// Moves the encumberance of the inputs back to the original transaction and releases the change outputs of the
+    /// failed replacement
+    async fn restore_replaced_transaction(
+        &mut self,
+        tx_id: TxId,
+        replacement_tx_id: TxId,
+    ) -> Result<(), TransactionServiceError> {
+        self.output_manager_service
+            .reassign_encumbered_outputs(replacement_tx_id, tx_id)
+            .await?;
+        if let Err(e) = self.output_manager_service.cancel_transaction(replacement_tx_id).await {
+            // The replacement may have failed before it encumbered any change
+            debug!(
+                target: LOG_TARGET,
+                "No outputs to release for replacement transaction (TxId: {}): {}", replacement_tx_id, e
+            );
+        }
+        Ok(())
+    }
+
     /// Creates a transaction to burn some Tari
     /// # Arguments
     /// 'amount': The amount of Tari to send to the recipient
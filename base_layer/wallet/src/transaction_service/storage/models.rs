@@ -339,6 +339,7 @@ pub enum TxCancellationReason {
     TimeLocked,         // 5
     InvalidTransaction, // 6
     AbandonedCoinbase,  // 7
+    Replaced,           // 8
 }
 
 impl TryFrom<u32> for TxCancellationReason {
@@ -354,6 +355,7 @@ impl TryFrom<u32> for TxCancellationReason {
             5 => Ok(TxCancellationReason::TimeLocked),
             6 => Ok(TxCancellationReason::InvalidTransaction),
             7 => Ok(TxCancellationReason::AbandonedCoinbase),
+            8 => Ok(TxCancellationReason::Replaced),
             code => Err(TransactionConversionError { code: code as i32 }),
         }
     }
@@ -372,6 +374,7 @@ impl Display for TxCancellationReason {
             TimeLocked => "TimeLocked",
             InvalidTransaction => "Invalid Transaction",
             AbandonedCoinbase => "Abandoned Coinbase",
+            Replaced => "Replaced",
         };
         fmt.write_str(response)
     }
@@ -50,8 +50,12 @@ use tari_core::{
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            MmrProofRequest,
+            MmrProofResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
+            ScriptIndexQueryRequest,
+            ScriptIndexQueryResponse,
             Signatures as SignaturesProto,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
@@ -110,6 +114,7 @@ pub struct BaseNodeWalletRpcMockState {
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
     blocks: Arc<Mutex<HashMap<u64, BlockHeader>>>,
     get_mempool_fee_per_gram_stats: Arc<Mutex<GetMempoolFeePerGramStatsResponse>>,
+    mmr_proof_response: Arc<Mutex<MmrProofResponse>>,
     utxos_by_block: Arc<Mutex<Vec<UtxosByBlock>>>,
     sync_utxos_by_block_trigger_channel: Arc<Mutex<Option<mpsc::Receiver<usize>>>>,
 }
@@ -153,6 +158,7 @@ impl BaseNodeWalletRpcMockState {
                     accumulated_difficulty: Vec::new(),
                     pruned_height: 0,
                     timestamp: Some(0),
+                    kernel_pruned_height: 0,
                 }),
                 is_synced: true,
             })),
@@ -177,6 +183,7 @@ impl BaseNodeWalletRpcMockState {
             utxos: Arc::new(Mutex::new(Vec::new())),
             blocks: Arc::new(Mutex::new(Default::default())),
             get_mempool_fee_per_gram_stats: Default::default(),
+            mmr_proof_response: Default::default(),
 
             utxos_by_block: Arc::new(Mutex::new(vec![])),
             sync_utxos_by_block_trigger_channel: Arc::new(Mutex::new(None)),
@@ -245,6 +252,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = resp;
     }
 
+    pub fn set_mmr_proof_response(&self, resp: MmrProofResponse) {
+        let mut lock = acquire_lock!(self.mmr_proof_response);
+        *lock = resp;
+    }
+
     pub fn set_utxos_by_block(&self, utxos_by_block: Vec<UtxosByBlock>) {
         let mut lock = acquire_lock!(self.utxos_by_block);
         *lock = utxos_by_block;
@@ -852,6 +864,29 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             acquire_lock!(self.state.get_mempool_fee_per_gram_stats).clone(),
         ))
     }
+
+    async fn fetch_output_mmr_proof(
+        &self,
+        _request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus> {
+        Ok(Response::new(acquire_lock!(self.state.mmr_proof_response).clone()))
+    }
+
+    async fn fetch_kernel_mmr_proof(
+        &self,
+        _request: Request<MmrProofRequest>,
+    ) -> Result<Response<MmrProofResponse>, RpcStatus> {
+        Ok(Response::new(acquire_lock!(self.state.mmr_proof_response).clone()))
+    }
+
+    async fn query_outputs_by_script(
+        &self,
+        _request: Request<ScriptIndexQueryRequest>,
+    ) -> Result<Response<ScriptIndexQueryResponse>, RpcStatus> {
+        Err(RpcStatus::not_implemented(
+            "The mock base node does not have a script index",
+        ))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -942,6 +977,7 @@ mod test {
             accumulated_difficulty: Vec::new(),
             pruned_height: 0,
             timestamp: Some(0),
+            kernel_pruned_height: 0,
         };
         service_state.set_tip_info_response(TipInfoResponse {
             metadata: Some(chain_metadata),
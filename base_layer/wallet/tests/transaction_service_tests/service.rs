@@ -134,7 +134,13 @@ use tari_wallet::{
         service::TransactionService,
         storage::{
             database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+            models::{
+                CompletedTransaction,
+                InboundTransaction,
+                OutboundTransaction,
+                TxCancellationReason,
+                WalletTransaction,
+            },
             sqlite_db::TransactionServiceSqliteDatabase,
         },
         TransactionServiceInitializer,
@@ -5505,3 +5511,257 @@ async fn test_get_fee_per_gram_per_block_basic() {
     assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
     assert_eq!(estimates.stats.len(), 1)
 }
+
+/// Sends a one-sided transaction from a single input of 25,000 µT and waits for its broadcast protocol to finish
+async fn send_one_sided_transaction_to_bump(
+    alice_ts_interface: &mut TransactionServiceNoCommsInterface,
+    factories: &CryptoFactories,
+) -> TxId {
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari::from(25_000), &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_one_sided_transaction(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            MicroTari::from(10_000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(5),
+            "Bump me".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .wait_pop_transaction_query_calls(1, Duration::from_secs(30))
+        .await
+        .unwrap();
+    let _submitted = alice_ts_interface
+        .base_node_rpc_mock_state
+        .take_submit_transaction_calls();
+    tx_id
+}
+
+#[tokio::test]
+async fn test_bump_transaction_fee() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let tx_id = send_one_sided_transaction_to_bump(&mut alice_ts_interface, &factories).await;
+    let original = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+
+    let new_tx_id = alice_ts_interface
+        .transaction_service_handle
+        .bump_transaction_fee(tx_id, MicroTari::from(20))
+        .await
+        .unwrap();
+
+    let replacement = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(new_tx_id)
+        .await
+        .unwrap();
+    assert!(replacement.fee > original.fee);
+    let input_commitments = |tx: &CompletedTransaction| {
+        tx.transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|input| input.commitment().unwrap().clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(input_commitments(&replacement), input_commitments(&original));
+
+    // The replacement was accepted by the base node before the original was cancelled
+    let submitted = alice_ts_interface
+        .base_node_rpc_mock_state
+        .take_submit_transaction_calls();
+    assert!(submitted
+        .iter()
+        .any(|tx| tx.first_kernel_excess_sig() == replacement.transaction.first_kernel_excess_sig()));
+    let cancelled = alice_ts_interface
+        .transaction_service_handle
+        .get_cancelled_completed_transactions()
+        .await
+        .unwrap();
+    assert_eq!(
+        cancelled.get(&tx_id).unwrap().cancelled,
+        Some(TxCancellationReason::Replaced)
+    );
+
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(25_000));
+    assert_eq!(
+        balance.pending_incoming_balance,
+        MicroTari::from(25_000) - MicroTari::from(10_000) - replacement.fee
+    );
+}
+
+#[tokio::test]
+async fn test_failed_bump_transaction_fee_keeps_original() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let tx_id = send_one_sided_transaction_to_bump(&mut alice_ts_interface, &factories).await;
+    let original = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+
+    // The inputs of the original transaction cannot pay for the replacement
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .bump_transaction_fee(tx_id, MicroTari::from(1_000_000))
+        .await
+        .is_err());
+    assert_eq!(
+        alice_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap(),
+        balance
+    );
+
+    // The base node rejects the replacement
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_submit_transaction_response(TxSubmissionResponse {
+            accepted: false,
+            rejection_reason: TxSubmissionRejectionReason::DoubleSpend,
+            is_synced: true,
+        });
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .bump_transaction_fee(tx_id, MicroTari::from(20))
+        .await;
+    assert!(matches!(result, Err(TransactionServiceError::FeeBumpError(_))));
+    assert_eq!(
+        alice_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap(),
+        balance
+    );
+
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(completed_tx.status, original.status);
+    assert!(completed_tx.cancelled.is_none());
+    assert_eq!(
+        alice_ts_interface
+            .transaction_service_handle
+            .get_completed_transactions()
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_failed_bump_transaction_fee_keeps_inputs_encumbered() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let tx_id = send_one_sided_transaction_to_bump(&mut alice_ts_interface, &factories).await;
+    let original = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    let input = original.transaction.body.inputs()[0].commitment().unwrap().clone();
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+
+    // The base node takes a while to reject the replacement
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_submit_transaction_response(TxSubmissionResponse {
+            accepted: false,
+            rejection_reason: TxSubmissionRejectionReason::DoubleSpend,
+            is_synced: true,
+        });
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_response_delay(Some(Duration::from_secs(3)));
+    let mut transaction_service_handle = alice_ts_interface.transaction_service_handle.clone();
+    let bump = task::spawn(async move {
+        transaction_service_handle
+            .bump_transaction_fee(tx_id, MicroTari::from(20))
+            .await
+    });
+    sleep(Duration::from_secs(1)).await;
+
+    // The input cannot be spent by another transaction while the replacement is submitted
+    assert!(alice_ts_interface
+        .output_manager_service_handle
+        .create_coin_split(vec![input.clone()], MicroTari::from(5_000), 2, MicroTari::from(5))
+        .await
+        .is_err());
+
+    let result = bump.await.unwrap();
+    assert!(matches!(result, Err(TransactionServiceError::FeeBumpError(_))));
+    alice_ts_interface.base_node_rpc_mock_state.set_response_delay(None);
+
+    // The input is encumbered by the original transaction again, rather than released
+    assert!(alice_ts_interface
+        .output_manager_service_handle
+        .create_coin_split(vec![input], MicroTari::from(5_000), 2, MicroTari::from(5))
+        .await
+        .is_err());
+    assert_eq!(
+        alice_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap(),
+        balance
+    );
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(completed_tx.status, original.status);
+    assert!(completed_tx.cancelled.is_none());
+
+    // So the original can still be replaced once the base node accepts the replacement
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_submit_transaction_response(TxSubmissionResponse {
+            accepted: true,
+            rejection_reason: TxSubmissionRejectionReason::None,
+            is_synced: true,
+        });
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .bump_transaction_fee(tx_id, MicroTari::from(20))
+        .await
+        .is_ok());
+}
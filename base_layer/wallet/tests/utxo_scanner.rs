@@ -309,6 +309,7 @@ async fn test_utxo_scanner_recovery() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
         metadata: Some(chain_metadata),
@@ -400,6 +401,7 @@ async fn test_utxo_scanner_recovery_with_restart() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
         metadata: Some(chain_metadata.clone()),
@@ -557,6 +559,7 @@ async fn test_utxo_scanner_recovery_with_restart_and_reorg() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
         metadata: Some(chain_metadata.clone()),
@@ -621,6 +624,7 @@ async fn test_utxo_scanner_recovery_with_restart_and_reorg() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface2
         .rpc_service_state
@@ -732,6 +736,7 @@ async fn test_utxo_scanner_scanned_block_cache_clearing() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
         metadata: Some(chain_metadata),
@@ -827,6 +832,7 @@ async fn test_utxo_scanner_one_sided_payments() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
         metadata: Some(chain_metadata),
@@ -932,6 +938,7 @@ async fn test_utxo_scanner_one_sided_payments() {
         accumulated_difficulty: Vec::new(),
         pruned_height: 0,
         timestamp: Some(0),
+        kernel_pruned_height: 0,
     };
 
     test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
@@ -145,6 +145,7 @@ async fn create_wallet(
         auxiliary_tcp_listener_address: None,
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
+        resource_manager: Default::default(),
     };
 
     let sql_database_path = comms_config
@@ -679,6 +680,7 @@ async fn test_import_utxo() {
         auxiliary_tcp_listener_address: None,
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
+        resource_manager: Default::default(),
     };
     let config = WalletConfig {
         p2p: comms_config,
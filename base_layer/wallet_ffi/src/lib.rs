@@ -2937,6 +2937,7 @@ pub unsafe extern "C" fn completed_transaction_get_confirmations(
 /// |   5 | TimeLocked          |
 /// |   6 | InvalidTransaction  |
 /// |   7 | AbandonedCoinbase   |
+/// |   8 | Replaced            |
 /// # Safety
 /// None
 #[no_mangle]
@@ -3919,6 +3920,7 @@ pub unsafe extern "C" fn comms_config_create(
                 user_agent: format!("tari/mobile_wallet/{}", env!("CARGO_PKG_VERSION")),
                 rpc_max_simultaneous_sessions: 0,
                 rpc_max_sessions_per_peer: 0,
+                resource_manager: Default::default(),
             };
 
             Box::into_raw(Box::new(config))
@@ -1672,6 +1672,7 @@ unsigned long long completed_transaction_get_confirmations(TariCompletedTransact
  * |   5 | TimeLocked          |
  * |   6 | InvalidTransaction  |
  * |   7 | AbandonedCoinbase   |
+ * |   8 | Replaced            |
  * # Safety
  * None
  */
@@ -45,6 +45,9 @@ pub enum Network {
     Igor = 0x24,
     Dibbler = 0x25,
     Esmeralda = 0x26,
+    /// A network whose consensus constants, emission and genesis block are loaded from a network definition file at
+    /// startup rather than being hard-coded.
+    Custom = 0x30,
 }
 
 impl Network {
@@ -64,6 +67,7 @@ impl Network {
             Dibbler => "dibbler",
             Esmeralda => "esmeralda",
             LocalNet => "localnet",
+            Custom => "custom",
         }
     }
 }
@@ -91,6 +95,7 @@ impl FromStr for Network {
             "igor" => Ok(Igor),
             "dibbler" => Ok(Dibbler),
             "esmeralda" | "esme" => Ok(Esmeralda),
+            "custom" => Ok(Custom),
             invalid => Err(ConfigurationError::new(
                 "network",
                 Some(value.to_string()),
@@ -134,6 +139,7 @@ mod test {
         let igor = Network::Igor;
         let dibbler = Network::Dibbler;
         let esmeralda = Network::Esmeralda;
+        let custom = Network::Custom;
 
         // test .as_byte()
         assert_eq!(mainnet.as_byte(), 0x00_u8);
@@ -144,6 +150,7 @@ mod test {
         assert_eq!(igor.as_byte(), 0x24_u8);
         assert_eq!(dibbler.as_byte(), 0x25_u8);
         assert_eq!(esmeralda.as_byte(), 0x26_u8);
+        assert_eq!(custom.as_byte(), 0x30_u8);
 
         // test .as_key_str()
         assert_eq!(mainnet.as_key_str(), "mainnet");
@@ -154,6 +161,7 @@ mod test {
         assert_eq!(igor.as_key_str(), "igor");
         assert_eq!(dibbler.as_key_str(), "dibbler");
         assert_eq!(esmeralda.as_key_str(), "esmeralda");
+        assert_eq!(custom.as_key_str(), "custom");
     }
 
     #[test]
@@ -174,6 +182,7 @@ mod test {
         assert_eq!(Network::from_str("dibbler").unwrap(), Network::Dibbler);
         assert_eq!(Network::from_str("esmeralda").unwrap(), Network::Esmeralda);
         assert_eq!(Network::from_str("esme").unwrap(), Network::Esmeralda);
+        assert_eq!(Network::from_str("custom").unwrap(), Network::Custom);
         // catch error case
         let err_network = Network::from_str("invalid network");
         assert!(err_network.is_err());
@@ -50,6 +50,7 @@ use crate::{
         ProtocolNotificationTx,
         Protocols,
     },
+    resource_manager::ResourceManager,
     tor,
     transports::Transport,
     CommsBuilder,
@@ -67,6 +68,7 @@ pub struct UnspawnedCommsNode {
     pub(super) connectivity_requester: ConnectivityRequester,
     pub(super) connectivity_rx: mpsc::Receiver<ConnectivityRequest>,
     pub(super) peer_manager: Arc<PeerManager>,
+    pub(super) resource_manager: ResourceManager,
     pub(super) protocol_extensions: ProtocolExtensions,
     pub(super) protocols: Protocols<Substream>,
     pub(super) shutdown_signal: ShutdownSignal,
@@ -140,6 +142,7 @@ impl UnspawnedCommsNode {
             node_identity,
             shutdown_signal,
             peer_manager,
+            resource_manager,
             protocol_extensions,
             protocols,
         } = self;
@@ -166,6 +169,7 @@ impl UnspawnedCommsNode {
         let mut ext_context = ProtocolExtensionContext::new(
             connectivity_requester.clone(),
             peer_manager.clone(),
+            resource_manager.clone(),
             shutdown_signal.clone(),
         );
 
@@ -192,6 +196,7 @@ impl UnspawnedCommsNode {
             shutdown_signal.clone(),
         );
 
+        connection_manager.set_resource_manager(resource_manager.clone());
         ext_context.register_complete_signal(connection_manager.complete_signal());
         connection_manager.add_protocols(ext_context.take_protocols().expect("Protocols already taken"));
         connection_manager.add_protocols(protocols);
@@ -236,6 +241,7 @@ impl UnspawnedCommsNode {
             listening_info,
             node_identity,
             peer_manager,
+            resource_manager,
             hidden_service,
             complete_signals: ext_context.drain_complete_signals(),
         })
@@ -256,6 +262,11 @@ impl UnspawnedCommsNode {
         self.connectivity_requester.clone()
     }
 
+    /// Return a handle to the per-peer ResourceManager
+    pub fn resource_manager(&self) -> ResourceManager {
+        self.resource_manager.clone()
+    }
+
     /// Returns an owned copy`ShutdownSignal`
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
@@ -278,6 +289,8 @@ pub struct CommsNode {
     node_identity: Arc<NodeIdentity>,
     /// Shared PeerManager instance
     peer_manager: Arc<PeerManager>,
+    /// Shared per-peer resource accounting
+    resource_manager: ResourceManager,
     /// The bind addresses of the listener(s)
     listening_info: ListenerInfo,
     /// `Some` if the comms node is configured to run via a hidden service, otherwise `None`
@@ -327,6 +340,11 @@ impl CommsNode {
         self.connectivity_requester.clone()
     }
 
+    /// Return a handle to the per-peer ResourceManager
+    pub fn resource_manager(&self) -> ResourceManager {
+        self.resource_manager.clone()
+    }
+
     /// Returns a new `ShutdownSignal`
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
@@ -47,6 +47,7 @@ use crate::{
     multiaddr::Multiaddr,
     peer_manager::{NodeIdentity, PeerManager},
     protocol::{NodeNetworkInfo, ProtocolExtensions},
+    resource_manager::{ResourceManager, ResourceManagerConfig},
     tor,
     types::CommsDatabase,
 };
@@ -124,6 +125,7 @@ pub struct CommsBuilder {
     hidden_service_ctl: Option<tor::HiddenServiceController>,
     connection_manager_config: ConnectionManagerConfig,
     connectivity_config: ConnectivityConfig,
+    resource_manager_config: ResourceManagerConfig,
 
     shutdown_signal: Option<ShutdownSignal>,
 }
@@ -138,6 +140,7 @@ impl Default for CommsBuilder {
             hidden_service_ctl: None,
             connection_manager_config: ConnectionManagerConfig::default(),
             connectivity_config: ConnectivityConfig::default(),
+            resource_manager_config: ResourceManagerConfig::default(),
             shutdown_signal: None,
         }
     }
@@ -250,6 +253,12 @@ impl CommsBuilder {
         self
     }
 
+    /// Set the configuration for per-peer resource accounting. Resource accounting is disabled by default.
+    pub fn with_resource_manager_config(mut self, config: ResourceManagerConfig) -> Self {
+        self.resource_manager_config = config;
+        self
+    }
+
     /// Set the peer storage database to use.
     pub fn with_peer_storage(mut self, peer_storage: CommsDatabase, file_lock: Option<File>) -> Self {
         self.peer_storage = Some(peer_storage);
@@ -301,6 +310,10 @@ impl CommsBuilder {
         let (event_tx, _) = broadcast::channel(consts::CONNECTIVITY_MANAGER_EVENTS_BUFFER_SIZE);
         let connectivity_requester = ConnectivityRequester::new(connectivity_tx, event_tx);
 
+        //---------------------------------- ResourceManager --------------------------------------------//
+        let resource_manager =
+            ResourceManager::new(self.resource_manager_config.clone(), connectivity_requester.clone());
+
         Ok(UnspawnedCommsNode {
             protocols: Default::default(),
             node_identity,
@@ -311,6 +324,7 @@ impl CommsBuilder {
             connectivity_requester,
             connectivity_rx,
             peer_manager,
+            resource_manager,
             protocol_extensions: ProtocolExtensions::new(),
         })
     }
@@ -247,11 +247,15 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Conn
             ))
         },
         Protocol::Ip4(_) | Protocol::Ip6(_) => {
-            let tcp = addr_iter.next().ok_or_else(|| {
-                ConnectionManagerError::InvalidMultiaddr("Address does not include a TCP port".to_string())
+            let transport = addr_iter.next().ok_or_else(|| {
+                ConnectionManagerError::InvalidMultiaddr("Address does not include a TCP or UDP port".to_string())
             })?;
 
-            validate_tcp_port(tcp)?;
+            if let Protocol::Udp(port) = transport {
+                validate_quic_port(port, addr_iter.next())?;
+            } else {
+                validate_tcp_port(transport)?;
+            }
             expect_end_of_address(addr_iter)
         },
         Protocol::Memory(0) => Err(ConnectionManagerError::InvalidMultiaddr(
@@ -289,6 +293,25 @@ fn validate_tcp_port(expected_tcp: Protocol) -> Result<(), ConnectionManagerErro
     }
 }
 
+fn validate_quic_port(udp_port: u16, expected_quic: Option<Protocol>) -> Result<(), ConnectionManagerError> {
+    if udp_port == 0 {
+        return Err(ConnectionManagerError::InvalidMultiaddr(
+            "Cannot connect to a zero UDP port".to_string(),
+        ));
+    }
+
+    match expected_quic {
+        Some(Protocol::Quic) => Ok(()),
+        Some(p) => Err(ConnectionManagerError::InvalidMultiaddr(format!(
+            "Expected QUIC address component but got '{}'",
+            p
+        ))),
+        None => Err(ConnectionManagerError::InvalidMultiaddr(
+            "UDP address does not include the QUIC protocol".to_string(),
+        )),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use multiaddr::multiaddr;
@@ -300,6 +323,7 @@ mod test {
         let valid = [
             multiaddr!(Ip4([172, 0, 0, 1]), Tcp(1u16)),
             multiaddr!(Ip6([172, 0, 0, 1, 1, 1, 1, 1]), Tcp(1u16)),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(1u16), Quic),
             "/onion/aaimaq4ygg2iegci:1234".parse().unwrap(),
             "/onion3/vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd:1234"
                 .parse()
@@ -311,6 +335,8 @@ mod test {
             multiaddr!(Ip4([127, 0, 0, 1]), Tcp(1u16)),
             multiaddr!(Ip4([169, 254, 0, 1]), Tcp(1u16)),
             multiaddr!(Ip4([172, 0, 0, 1])),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(1u16)),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(0u16), Quic),
             "/onion/aaimaq4ygg2iegci:1234/http".parse().unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com")),
             multiaddr!(Memory(1234u64)),
@@ -52,7 +52,6 @@ use crate::{
         peer_connection,
     },
     multiaddr::Multiaddr,
-    multiplexing::Yamux,
     noise::{NoiseConfig, NoiseSocket},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManager},
     protocol::ProtocolId,
@@ -431,7 +430,7 @@ where
             peer_node_id.short_str()
         );
 
-        let muxer = Yamux::upgrade_connection(socket, CONNECTION_DIRECTION)
+        let muxer = TTransport::multiplex(socket, CONNECTION_DIRECTION)
             .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
 
         if cancel_signal.is_terminated() {
@@ -548,9 +547,14 @@ where
                             .await
                             .map_err(|_| ConnectionManagerError::WireFormatSendFailed)?;
 
+                        let channel_binding = TTransport::channel_binding(&socket);
                         let noise_socket = time::timeout(
                             Duration::from_secs(40),
-                            noise_config.upgrade_socket(socket, ConnectionDirection::Outbound),
+                            noise_config.upgrade_socket_with_prologue(
+                                socket,
+                                ConnectionDirection::Outbound,
+                                &channel_binding,
+                            ),
                         )
                         .await
                         .map_err(|_| ConnectionManagerError::NoiseProtocolTimeout)??;
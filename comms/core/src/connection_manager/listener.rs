@@ -58,7 +58,6 @@ use crate::{
         wire_mode::{WireMode, LIVENESS_WIRE_MODE},
     },
     multiaddr::Multiaddr,
-    multiplexing::Yamux,
     noise::NoiseConfig,
     peer_manager::{NodeIdentity, PeerFeatures},
     protocol::ProtocolId,
@@ -351,9 +350,10 @@ where
         );
 
         let timer = Instant::now();
+        let channel_binding = TTransport::channel_binding(&socket);
         let mut noise_socket = time::timeout(
             Duration::from_secs(30),
-            noise_config.upgrade_socket(socket, CONNECTION_DIRECTION),
+            noise_config.upgrade_socket_with_prologue(socket, CONNECTION_DIRECTION, &channel_binding),
         )
         .await
         .map_err(|_| ConnectionManagerError::NoiseProtocolTimeout)??;
@@ -411,7 +411,7 @@ where
             peer_node_id.short_str()
         );
 
-        let muxer = Yamux::upgrade_connection(noise_socket, CONNECTION_DIRECTION)
+        let muxer = TTransport::multiplex(noise_socket, CONNECTION_DIRECTION)
             .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
 
         peer_connection::create(
@@ -48,6 +48,7 @@ use crate::{
     noise::NoiseConfig,
     peer_manager::{NodeId, NodeIdentity, PeerManagerError},
     protocol::{NodeNetworkInfo, ProtocolEvent, ProtocolId, Protocols},
+    resource_manager::{ResourceDecision, ResourceManager, ResourceUsage},
     transports::{TcpTransport, Transport},
     PeerManager,
 };
@@ -172,6 +173,7 @@ pub(crate) struct ConnectionManager<TTransport, TBackoff> {
     peer_manager: Arc<PeerManager>,
     shutdown_signal: Option<ShutdownSignal>,
     protocols: Protocols<Substream>,
+    resource_manager: Option<ResourceManager>,
     listener_info: Option<ListenerInfo>,
     listening_notifiers: Vec<oneshot::Sender<ListenerInfo>>,
     connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
@@ -240,6 +242,7 @@ where
             request_rx,
             peer_manager,
             protocols: Protocols::new(),
+            resource_manager: None,
             internal_event_rx,
             dialer_tx,
             dialer: Some(dialer),
@@ -257,6 +260,12 @@ where
         self
     }
 
+    /// Set the resource manager used to account for inbound substreams. If not set, substreams are not limited.
+    pub fn set_resource_manager(&mut self, resource_manager: ResourceManager) -> &mut Self {
+        self.resource_manager = Some(resource_manager);
+        self
+    }
+
     pub fn complete_signal(&self) -> ShutdownSignal {
         self.complete_trigger.to_signal()
     }
@@ -408,6 +417,10 @@ where
                     proto_str
                 );
                 metrics::inbound_substream_counter(&node_id, &protocol).inc();
+                if !self.is_substream_within_budget(&node_id, &protocol) {
+                    // Dropping the substream closes it
+                    return;
+                }
                 let notify_fut = self
                     .protocols
                     .notify(&protocol, ProtocolEvent::NewInboundSubstream(node_id, stream));
@@ -455,6 +468,28 @@ where
         }
     }
 
+    fn is_substream_within_budget(&self, node_id: &NodeId, protocol: &ProtocolId) -> bool {
+        let resource_manager = match self.resource_manager.as_ref() {
+            Some(rm) => rm,
+            None => return true,
+        };
+        match resource_manager.record(node_id, protocol, ResourceUsage::substream()) {
+            ResourceDecision::Allow => true,
+            // Substream requests are cheap to retry, so rather than hold up the connection manager we drop them
+            ResourceDecision::Throttle(_) | ResourceDecision::Deny => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Rejecting inbound substream for peer '{}' speaking protocol '{}' because it has exceeded its \
+                     resource budget",
+                    node_id.short_str(),
+                    String::from_utf8_lossy(protocol)
+                );
+                metrics::rejected_substream_counter(node_id, protocol).inc();
+                false
+            },
+        }
+    }
+
     #[inline]
     async fn send_dialer_request(&mut self, req: DialerRequest) {
         if let Err(err) = self.dialer_tx.send(req).await {
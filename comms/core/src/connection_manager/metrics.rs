@@ -81,3 +81,16 @@ pub fn inbound_substream_counter(peer: &NodeId, protocol: &ProtocolId) -> IntCou
 
     METER.with_label_values(&[peer.to_string().as_str(), String::from_utf8_lossy(protocol).as_ref()])
 }
+
+pub fn rejected_substream_counter(peer: &NodeId, protocol: &ProtocolId) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "comms::connections::rejected_substream_request_count",
+            "Number of substream requests rejected because the peer exceeded its resource budget",
+            &["peer_id", "protocol"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[peer.to_string().as_str(), String::from_utf8_lossy(protocol).as_ref()])
+}
@@ -51,7 +51,7 @@ use crate::{
         node_identity::{build_node_identity, ordered_node_identities},
         test_node::{build_connection_manager, TestNodeConfig},
     },
-    transports::{MemoryTransport, TcpTransport},
+    transports::{MemoryTransport, QuicTransport, TcpTransport},
 };
 
 #[runtime::test]
@@ -295,6 +295,101 @@ async fn dial_success_aux_tcp_listener() {
     assert_eq!(buf, MSG);
 }
 
+#[runtime::test]
+#[allow(clippy::similar_names)]
+async fn dial_success_quic() {
+    static TEST_PROTO: ProtocolId = ProtocolId::from_static(b"/test/valid");
+    let shutdown = Shutdown::new();
+
+    let node_identity1 = build_node_identity(PeerFeatures::empty());
+    let node_identity2 = build_node_identity(PeerFeatures::empty());
+
+    let (proto_tx1, mut proto_rx1) = mpsc::channel(1);
+    let (proto_tx2, mut proto_rx2) = mpsc::channel(1);
+
+    let peer_manager1 = build_peer_manager();
+    let mut protocols = Protocols::new();
+    protocols.add([TEST_PROTO.clone()], &proto_tx1);
+    let mut conn_man1 = build_connection_manager(
+        {
+            let mut config = TestNodeConfig {
+                node_identity: node_identity1.clone(),
+                ..Default::default()
+            };
+            config.connection_manager_config.listener_address = "/ip4/127.0.0.1/udp/0/quic".parse().unwrap();
+            config
+        },
+        QuicTransport::new().unwrap(),
+        peer_manager1.clone(),
+        protocols,
+        shutdown.to_signal(),
+    );
+    conn_man1.wait_until_listening().await.unwrap();
+
+    let peer_manager2 = build_peer_manager();
+    let mut protocols = Protocols::new();
+    protocols.add([TEST_PROTO.clone()], &proto_tx2);
+    let mut conn_man2 = build_connection_manager(
+        {
+            let mut config = TestNodeConfig {
+                node_identity: node_identity2.clone(),
+                ..Default::default()
+            };
+            config.connection_manager_config.listener_address = "/ip4/127.0.0.1/udp/0/quic".parse().unwrap();
+            config
+        },
+        QuicTransport::new().unwrap(),
+        peer_manager2.clone(),
+        protocols,
+        shutdown.to_signal(),
+    );
+    let mut subscription2 = conn_man2.get_event_subscription();
+    let public_address2 = conn_man2.wait_until_listening().await.unwrap().bind_address().clone();
+
+    peer_manager1
+        .add_peer(Peer::new(
+            node_identity2.public_key().clone(),
+            node_identity2.node_id().clone(),
+            vec![public_address2].into(),
+            PeerFlags::empty(),
+            PeerFeatures::COMMUNICATION_CLIENT,
+            Default::default(),
+            Default::default(),
+        ))
+        .await
+        .unwrap();
+
+    let mut conn_out = conn_man1.dial_peer(node_identity2.node_id().clone()).await.unwrap();
+    assert_eq!(conn_out.peer_node_id(), node_identity2.node_id());
+
+    let event = subscription2.recv().await.unwrap();
+    unpack_enum!(ConnectionManagerEvent::PeerConnected(conn_in) = &*event);
+    assert_eq!(conn_in.peer_node_id(), node_identity1.node_id());
+    let mut conn_in = conn_in.clone();
+
+    const MSG: &[u8] = b"Welease Woger!";
+    // Substreams are native QUIC streams and can be opened by either side
+    let mut substream_out = conn_out.open_substream(&TEST_PROTO).await.unwrap();
+    substream_out.stream.write_all(MSG).await.unwrap();
+
+    let protocol_in = proto_rx2.recv().await.unwrap();
+    unpack_enum!(ProtocolEvent::NewInboundSubstream(node_id, substream_in) = protocol_in.event);
+    assert_eq!(&node_id, node_identity1.node_id());
+    let mut buf = [0u8; MSG.len()];
+    substream_in.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, MSG);
+
+    let mut substream_out = conn_in.open_substream(&TEST_PROTO).await.unwrap();
+    substream_out.stream.write_all(MSG).await.unwrap();
+
+    let protocol_in = proto_rx1.recv().await.unwrap();
+    unpack_enum!(ProtocolEvent::NewInboundSubstream(node_id, substream_in) = protocol_in.event);
+    assert_eq!(&node_id, node_identity2.node_id());
+    let mut buf = [0u8; MSG.len()];
+    substream_in.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, MSG);
+}
+
 #[runtime::test]
 async fn simultaneous_dial_events() {
     let mut shutdown = Shutdown::new();
@@ -273,6 +273,22 @@ impl ConnectivityManagerActor {
                 } else {
                 }
             },
+            RecordReputationEvent(node_id, event) => {
+                match self.peer_manager.record_reputation_event(&node_id, event).await {
+                    Ok(score) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Peer `{}` reputation is now {} ({})", node_id, score, event
+                        );
+                    },
+                    Err(err) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Unable to record reputation event {} for peer `{}`: {}", event, node_id, err
+                        );
+                    },
+                }
+            },
             AddPeerToAllowList(node_id) => {
                 if !self.allow_list.contains(&node_id) {
                     self.allow_list.push(node_id)
@@ -456,7 +472,14 @@ impl ConnectivityManagerActor {
             self.pool.count_connected_nodes()
         );
 
-        let conns = selection.select(&self.pool);
+        let connected = self
+            .pool
+            .filter_connection_states(|state| state.is_connected())
+            .into_iter()
+            .map(|conn| conn.peer_node_id().clone())
+            .collect::<Vec<_>>();
+        let reputations = self.peer_manager.reputation_tiers(&connected).await?;
+        let conns = selection.select(&self.pool, &reputations);
         debug!(target: LOG_TARGET, "Selected {} connections(s)", conns.len());
 
         Ok(conns.into_iter().cloned().collect())
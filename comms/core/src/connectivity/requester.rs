@@ -41,7 +41,7 @@ use super::{
 };
 use crate::{
     connection_manager::ConnectionManagerError,
-    peer_manager::{NodeId, Peer},
+    peer_manager::{NodeId, Peer, ReputationEvent},
     PeerConnection,
 };
 
@@ -105,6 +105,7 @@ pub enum ConnectivityRequest {
     AddPeerToAllowList(NodeId),
     RemovePeerFromAllowList(NodeId),
     GetPeerStats(NodeId, oneshot::Sender<Option<Peer>>),
+    RecordReputationEvent(NodeId, ReputationEvent),
 }
 
 /// Handle to make requests and read events from the ConnectivityManager actor.
@@ -269,6 +270,20 @@ impl ConnectivityRequester {
             .await
     }
 
+    /// Records a reputation event for the peer. Peers with a better reputation are preferred when selecting
+    /// connections.
+    pub async fn record_reputation_event(
+        &mut self,
+        node_id: NodeId,
+        event: ReputationEvent,
+    ) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::RecordReputationEvent(node_id, event))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
     /// Adds a peer to an allow list, preventing it from being banned.
     pub async fn add_peer_to_allow_list(&mut self, node_id: NodeId) -> Result<(), ConnectivityError> {
         self.sender
@@ -20,12 +20,16 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, fmt::Display};
+use std::{cmp::Reverse, collections::HashMap, fmt, fmt::Display};
 
 use rand::{rngs::OsRng, seq::SliceRandom};
 
 use super::connection_pool::ConnectionPool;
-use crate::{connectivity::connection_pool::ConnectionStatus, peer_manager::NodeId, PeerConnection};
+use crate::{
+    connectivity::connection_pool::ConnectionStatus,
+    peer_manager::{NodeId, ReputationTier},
+    PeerConnection,
+};
 
 /// Selection query for PeerConnections.
 ///
@@ -80,14 +84,21 @@ impl ConnectivitySelection {
         }
     }
 
-    /// Select peers from the pool according to the ConnectivitySelection
-    pub fn select<'a>(&self, pool: &'a ConnectionPool) -> Vec<&'a PeerConnection> {
+    /// Select peers from the pool according to the ConnectivitySelection. Peers with a better reputation are preferred.
+    /// Peers that are not in `reputations` are considered to have a neutral reputation.
+    pub fn select<'a>(
+        &self,
+        pool: &'a ConnectionPool,
+        reputations: &HashMap<NodeId, ReputationTier>,
+    ) -> Vec<&'a PeerConnection> {
         use SelectionMode::{AllNodes, ClosestTo, RandomNodes};
         match &self.selection_mode {
             AllNodes => select_connected_nodes(pool, &self.excluded_peers),
-            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers),
+            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers, reputations),
             ClosestTo(dest_node_id, n) => {
                 let mut connections = select_closest(pool, dest_node_id, &self.excluded_peers);
+                // Closeness is preserved as far as possible, only peers with a poor reputation are moved to the back
+                connections.sort_by_key(|conn| get_reputation_tier(reputations, conn) == ReputationTier::Poor);
                 connections.truncate(*n);
                 connections.to_vec()
             },
@@ -119,9 +130,22 @@ fn select_closest<'a>(pool: &'a ConnectionPool, node_id: &NodeId, exclude: &[Nod
     nodes
 }
 
-fn select_random_nodes<'a>(pool: &'a ConnectionPool, n: usize, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
-    let nodes = select_connected_nodes(pool, exclude);
-    nodes.choose_multiple(&mut OsRng, n).copied().collect()
+fn select_random_nodes<'a>(
+    pool: &'a ConnectionPool,
+    n: usize,
+    exclude: &[NodeId],
+    reputations: &HashMap<NodeId, ReputationTier>,
+) -> Vec<&'a PeerConnection> {
+    let mut nodes = select_connected_nodes(pool, exclude);
+    nodes.shuffle(&mut OsRng);
+    // Stable sort so that peers are still randomly ordered within each tier
+    nodes.sort_by_key(|conn| Reverse(get_reputation_tier(reputations, conn)));
+    nodes.truncate(n);
+    nodes
+}
+
+fn get_reputation_tier(reputations: &HashMap<NodeId, ReputationTier>, conn: &PeerConnection) -> ReputationTier {
+    reputations.get(conn.peer_node_id()).copied().unwrap_or_default()
 }
 
 impl Display for ConnectivitySelection {
@@ -175,15 +199,50 @@ mod test {
     #[test]
     fn select_random() {
         let (pool, _receivers) = create_pool_with_connections(10);
-        let conns = select_random_nodes(&pool, 500, &[]);
+        let conns = select_random_nodes(&pool, 500, &[], &HashMap::new());
         assert_eq!(conns.len(), 10);
 
         let first_node = conns.first().unwrap().peer_node_id().clone();
-        let conns = select_random_nodes(&pool, 10, &[first_node.clone()]);
+        let conns = select_random_nodes(&pool, 10, &[first_node.clone()], &HashMap::new());
         assert_eq!(conns.len(), 9);
         assert!(conns.iter().all(|c| c.peer_node_id() != &first_node));
     }
 
+    #[test]
+    fn select_random_prefers_reputable_peers() {
+        let (pool, _receivers) = create_pool_with_connections(10);
+        let conns = select_connected_nodes(&pool, &[]);
+        let mut reputations = conns
+            .iter()
+            .take(5)
+            .map(|c| (c.peer_node_id().clone(), ReputationTier::Poor))
+            .collect::<HashMap<_, _>>();
+        let reliable = conns[9].peer_node_id().clone();
+        reputations.insert(reliable.clone(), ReputationTier::Reliable);
+
+        let conns = select_random_nodes(&pool, 3, &[], &reputations);
+        assert_eq!(conns.len(), 3);
+        assert_eq!(conns[0].peer_node_id(), &reliable);
+        assert!(conns
+            .iter()
+            .all(|c| reputations.get(c.peer_node_id()) != Some(&ReputationTier::Poor)));
+    }
+
+    #[test]
+    fn select_closest_moves_poor_peers_to_the_back() {
+        let (pool, _receivers) = create_pool_with_connections(10);
+        let subject_node_identity = build_node_identity(Default::default());
+        let closest = select_closest(&pool, subject_node_identity.node_id(), &[]);
+        let poor = closest[0].peer_node_id().clone();
+        let reputations = Some((poor.clone(), ReputationTier::Poor)).into_iter().collect();
+
+        let selection = ConnectivitySelection::closest_to(subject_node_identity.node_id().clone(), 10, vec![]);
+        let conns = selection.select(&pool, &reputations);
+        assert_eq!(conns.len(), 10);
+        assert_eq!(conns[0].peer_node_id(), closest[1].peer_node_id());
+        assert_eq!(conns[9].peer_node_id(), &poor);
+    }
+
     #[test]
     fn select_closest_ordering() {
         let (pool, _receivers) = create_pool_with_connections(10);
@@ -29,6 +29,8 @@ pub mod framing;
 
 pub mod rate_limit;
 
+pub mod resource_manager;
+
 mod multiplexing;
 pub use multiplexing::Substream;
 
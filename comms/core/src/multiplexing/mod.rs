@@ -21,9 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Stream multiplexers typically used to allow multiplexed substreams over an ordered reliable byte stream.
+//! Connections established over QUIC use native QUIC streams as substreams.
 
 #[cfg(feature = "metrics")]
 mod metrics;
 
+mod quic;
+pub use self::quic::QuicStream;
+
 mod yamux;
 pub use self::yamux::{ConnectionError, Control, IncomingSubstreams, Substream, Yamux};
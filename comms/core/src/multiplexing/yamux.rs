@@ -20,9 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{future::Future, io, pin::Pin, task::Poll};
+use std::{io, pin::Pin, task::Poll};
 
 use futures::{task::Context, Stream};
+use quinn::VarInt;
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf},
     sync::mpsc,
@@ -33,6 +34,7 @@ use tracing::{self, debug, error, event, Level};
 pub use yamux::ConnectionError;
 use yamux::Mode;
 
+use super::quic::{self, QuicStream};
 use crate::{
     connection_manager::ConnectionDirection,
     runtime,
@@ -96,6 +98,23 @@ impl Yamux {
         IncomingSubstreams::new(incoming_rx, counter)
     }
 
+    /// Wrap an established QUIC connection. Substreams are native QUIC bidirectional streams rather than yamux streams
+    /// over a single socket.
+    pub fn from_quic_connection(connection: quinn::Connection, incoming: quinn::IncomingBiStreams) -> Self {
+        let substream_counter = AtomicRefCounter::new();
+        Self {
+            control: Control {
+                inner: ControlInner::Quic(connection),
+                substream_counter: substream_counter.clone(),
+            },
+            incoming: IncomingSubstreams {
+                inner: IncomingInner::Quic(incoming),
+                substream_counter: substream_counter.clone(),
+            },
+            substream_counter,
+        }
+    }
+
     /// Get the yamux control struct
     pub fn get_yamux_control(&self) -> Control {
         self.control.clone()
@@ -124,14 +143,20 @@ impl Yamux {
 
 #[derive(Clone)]
 pub struct Control {
-    inner: yamux::Control,
+    inner: ControlInner,
     substream_counter: AtomicRefCounter,
 }
 
+#[derive(Clone)]
+enum ControlInner {
+    Yamux(yamux::Control),
+    Quic(quinn::Connection),
+}
+
 impl Control {
     pub fn new(inner: yamux::Control, substream_counter: AtomicRefCounter) -> Self {
         Self {
-            inner,
+            inner: ControlInner::Yamux(inner),
             substream_counter,
         }
     }
@@ -140,16 +165,28 @@ impl Control {
     pub async fn open_stream(&mut self) -> Result<Substream, ConnectionError> {
         // Ensure that this counts as used while the substream is being opened
         let counter_guard = self.substream_counter.new_guard();
-        let stream = self.inner.open_stream().await?;
+        let stream = match &mut self.inner {
+            ControlInner::Yamux(control) => SubstreamInner::Yamux(control.open_stream().await?.compat()),
+            ControlInner::Quic(connection) => {
+                let (send, recv) = connection.open_bi().await.map_err(quic::to_connection_error)?;
+                SubstreamInner::Quic(QuicStream::new(send, recv))
+            },
+        };
         Ok(Substream {
-            stream: stream.compat(),
+            stream,
             _counter_guard: counter_guard,
         })
     }
 
     /// Close the connection.
-    pub fn close(&mut self) -> impl Future<Output = Result<(), ConnectionError>> + '_ {
-        self.inner.close()
+    pub async fn close(&mut self) -> Result<(), ConnectionError> {
+        match &mut self.inner {
+            ControlInner::Yamux(control) => control.close().await,
+            ControlInner::Quic(connection) => {
+                connection.close(VarInt::from_u32(0), b"close");
+                Ok(())
+            },
+        }
     }
 
     pub fn substream_count(&self) -> usize {
@@ -162,14 +199,19 @@ impl Control {
 }
 
 pub struct IncomingSubstreams {
-    inner: mpsc::Receiver<yamux::Stream>,
+    inner: IncomingInner,
     substream_counter: AtomicRefCounter,
 }
 
+enum IncomingInner {
+    Yamux(mpsc::Receiver<yamux::Stream>),
+    Quic(quinn::IncomingBiStreams),
+}
+
 impl IncomingSubstreams {
     pub(self) fn new(inner: mpsc::Receiver<yamux::Stream>, substream_counter: AtomicRefCounter) -> Self {
         Self {
-            inner,
+            inner: IncomingInner::Yamux(inner),
             substream_counter,
         }
     }
@@ -183,26 +225,78 @@ impl Stream for IncomingSubstreams {
     type Item = Substream;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match futures::ready!(Pin::new(&mut self.inner).poll_recv(cx)) {
-            Some(stream) => Poll::Ready(Some(Substream {
-                stream: stream.compat(),
-                _counter_guard: self.substream_counter.new_guard(),
-            })),
-            None => Poll::Ready(None),
-        }
+        let stream = match &mut self.inner {
+            IncomingInner::Yamux(receiver) => {
+                futures::ready!(receiver.poll_recv(cx)).map(|s| SubstreamInner::Yamux(s.compat()))
+            },
+            IncomingInner::Quic(incoming) => match futures::ready!(Pin::new(incoming).poll_next(cx)) {
+                Some(Ok((send, recv))) => Some(SubstreamInner::Quic(QuicStream::new(send, recv))),
+                Some(Err(err)) => {
+                    debug!(target: LOG_TARGET, "Incoming QUIC substreams ended because '{}'", err);
+                    None
+                },
+                None => None,
+            },
+        };
+
+        Poll::Ready(stream.map(|stream| Substream {
+            stream,
+            _counter_guard: self.substream_counter.new_guard(),
+        }))
     }
 }
 
-/// A yamux stream wrapper that can be read from and written to.
+/// A substream wrapper that can be read from and written to.
 #[derive(Debug)]
 pub struct Substream {
-    stream: Compat<yamux::Stream>,
+    stream: SubstreamInner,
     _counter_guard: AtomicRefCounterGuard,
 }
 
+#[derive(Debug)]
+enum SubstreamInner {
+    Yamux(Compat<yamux::Stream>),
+    Quic(QuicStream),
+}
+
 impl StreamId for Substream {
     fn stream_id(&self) -> stream_id::Id {
-        self.stream.get_ref().id().into()
+        match &self.stream {
+            SubstreamInner::Yamux(stream) => stream.get_ref().id().into(),
+            SubstreamInner::Quic(stream) => stream.id(),
+        }
+    }
+}
+
+impl tokio::io::AsyncRead for SubstreamInner {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            SubstreamInner::Yamux(stream) => Pin::new(stream).poll_read(cx, buf),
+            SubstreamInner::Quic(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for SubstreamInner {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            SubstreamInner::Yamux(stream) => Pin::new(stream).poll_write(cx, buf),
+            SubstreamInner::Quic(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            SubstreamInner::Yamux(stream) => Pin::new(stream).poll_flush(cx),
+            SubstreamInner::Quic(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            SubstreamInner::Yamux(stream) => Pin::new(stream).poll_shutdown(cx),
+            SubstreamInner::Quic(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
     }
 }
 
@@ -62,19 +62,34 @@ impl NoiseConfig {
 
     /// Upgrades the given socket to using the noise protocol. The upgraded socket and the peer's static key
     /// is returned.
-    #[tracing::instrument(name = "noise::upgrade_socket", skip(self, socket))]
     pub async fn upgrade_socket<TSocket>(
         &self,
         socket: TSocket,
         direction: ConnectionDirection,
     ) -> Result<NoiseSocket<TSocket>, NoiseError>
+    where
+        TSocket: AsyncWrite + AsyncRead + Unpin,
+    {
+        self.upgrade_socket_with_prologue(socket, direction, &[]).await
+    }
+
+    /// Upgrades the given socket to using the noise protocol, mixing the given prologue into the handshake. The
+    /// handshake fails unless both sides provide the same prologue.
+    #[tracing::instrument(name = "noise::upgrade_socket", skip(self, socket, prologue))]
+    pub async fn upgrade_socket_with_prologue<TSocket>(
+        &self,
+        socket: TSocket,
+        direction: ConnectionDirection,
+        prologue: &[u8],
+    ) -> Result<NoiseSocket<TSocket>, NoiseError>
     where
         TSocket: AsyncWrite + AsyncRead + Unpin,
     {
         let handshake_state = {
             let builder =
                 snow::Builder::with_resolver(self.parameters.clone(), Box::new(TariCryptoResolver::default()))
-                    .local_private_key(self.node_identity.secret_key().as_bytes());
+                    .local_private_key(self.node_identity.secret_key().as_bytes())
+                    .prologue(prologue);
 
             match direction {
                 ConnectionDirection::Outbound => {
@@ -157,4 +172,21 @@ mod test {
         socket_out.read_to_end(&mut read_buf).await.unwrap();
         assert_eq!(read_buf, sample);
     }
+
+    #[runtime::test]
+    async fn upgrade_socket_prologue_mismatch() {
+        let config1 = NoiseConfig::new(build_node_identity(PeerFeatures::COMMUNICATION_NODE));
+        let config2 = NoiseConfig::new(build_node_identity(PeerFeatures::COMMUNICATION_NODE));
+
+        let (in_socket, out_socket) = MemorySocket::new_pair();
+        let (result_in, result_out) = future::join(
+            config1.upgrade_socket_with_prologue(in_socket, ConnectionDirection::Inbound, b"binding-a"),
+            config2.upgrade_socket_with_prologue(out_socket, ConnectionDirection::Outbound, b"binding-b"),
+        )
+        .await;
+
+        // The responder cannot detect the mismatch in the IX pattern, the initiator fails to decrypt the response
+        assert!(result_in.is_ok());
+        assert!(result_out.is_err());
+    }
 }
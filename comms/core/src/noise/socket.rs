@@ -158,6 +158,11 @@ impl<TSocket> NoiseSocket<TSocket> {
         self.get_remote_static()
             .and_then(|s| CommsPublicKey::from_bytes(s).ok())
     }
+
+    /// Consumes the NoiseSocket, returning the underlying socket. Any buffered data is discarded.
+    pub(crate) fn into_inner(self) -> TSocket {
+        self.socket
+    }
 }
 
 fn poll_write_all<TSocket>(
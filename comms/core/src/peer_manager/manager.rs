@@ -20,7 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, fs::File, time::Duration};
+use std::{collections::HashMap, fmt, fs::File, time::Duration};
 
 use multiaddr::Multiaddr;
 use tari_storage::{lmdb_store::LMDBDatabase, IterationResult};
@@ -40,6 +40,8 @@ use crate::{
         PeerFeatures,
         PeerManagerError,
         PeerQuery,
+        ReputationEvent,
+        ReputationTier,
     },
     types::{CommsDatabase, CommsPublicKey},
 };
@@ -321,6 +323,23 @@ impl PeerManager {
         Ok(peer.features)
     }
 
+    /// Records a reputation event for the peer. Returns the new reputation score.
+    pub async fn record_reputation_event(
+        &self,
+        node_id: &NodeId,
+        event: ReputationEvent,
+    ) -> Result<i32, PeerManagerError> {
+        self.peer_storage.write().await.record_reputation_event(node_id, event)
+    }
+
+    /// Returns the reputation tiers of the given peers. Peers that are not found are omitted.
+    pub async fn reputation_tiers(
+        &self,
+        node_ids: &[NodeId],
+    ) -> Result<HashMap<NodeId, ReputationTier>, PeerManagerError> {
+        self.peer_storage.read().await.reputation_tiers(node_ids)
+    }
+
     /// This will store metadata inside of the metadata field in the peer provided by the nodeID.
     /// It will return None if the value was empty and the old value if the value was updated
     pub async fn set_peer_metadata(
@@ -604,4 +623,36 @@ mod test {
         assert!(!peer.is_offline());
         assert_eq!(peer.connection_stats.failed_attempts(), 0);
     }
+
+    #[runtime::test]
+    async fn record_reputation_event() {
+        let peer_manager = PeerManager::new(HashmapDatabase::new(), None).unwrap();
+        let peer1 = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        let peer2 = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        let unknown = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        peer_manager.add_peer(peer1.clone()).await.unwrap();
+        peer_manager.add_peer(peer2.clone()).await.unwrap();
+
+        let score = peer_manager
+            .record_reputation_event(&peer1.node_id, ReputationEvent::SyncValidationFailed)
+            .await
+            .unwrap();
+        assert!(score < 0);
+        let peer = peer_manager.find_by_node_id(&peer1.node_id).await.unwrap().unwrap();
+        assert_eq!(peer.reputation.tier(), ReputationTier::Poor);
+
+        let err = peer_manager
+            .record_reputation_event(&unknown.node_id, ReputationEvent::UsefulBlock)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PeerManagerError::PeerNotFoundError));
+
+        let tiers = peer_manager
+            .reputation_tiers(&[peer1.node_id.clone(), peer2.node_id.clone(), unknown.node_id])
+            .await
+            .unwrap();
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(tiers[&peer1.node_id], ReputationTier::Poor);
+        assert_eq!(tiers[&peer2.node_id], ReputationTier::Neutral);
+    }
 }
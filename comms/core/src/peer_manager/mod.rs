@@ -107,6 +107,9 @@ pub use peer_query::{PeerQuery, PeerQuerySortBy};
 mod peer_storage;
 pub use peer_storage::PeerStorage;
 
+mod reputation;
+pub use reputation::{PeerReputation, ReputationEvent, ReputationTier};
+
 mod migrations;
 
 mod or_not_found;
@@ -38,6 +38,7 @@ use super::{
     connection_stats::PeerConnectionStats,
     node_id::{deserialize_node_id_from_hex, NodeId},
     peer_id::PeerId,
+    reputation::{PeerReputation, ReputationEvent},
     PeerFeatures,
 };
 use crate::{
@@ -95,6 +96,9 @@ pub struct Peer {
     /// Signs the peer information with a timestamp to prevent malleability. This is optional for backward
     /// compatibility, but without this, the identity (addresses etc) cannot be updated.
     pub identity_signature: Option<IdentitySignature>,
+    /// Locally observed reputation of the peer
+    #[serde(default)]
+    pub reputation: PeerReputation,
 }
 
 impl Peer {
@@ -125,6 +129,7 @@ impl Peer {
             user_agent,
             metadata: HashMap::new(),
             identity_signature: None,
+            reputation: Default::default(),
         }
     }
 
@@ -305,6 +310,11 @@ impl Peer {
         self
     }
 
+    /// Records a reputation event for this peer and returns the new reputation score
+    pub fn record_reputation_event(&mut self, event: ReputationEvent) -> i32 {
+        self.reputation.record(event)
+    }
+
     pub fn is_seed(&self) -> bool {
         self.flags.contains(PeerFlags::SEED)
     }
@@ -39,6 +39,8 @@ use crate::{
         PeerManagerError,
         PeerQuery,
         PeerQuerySortBy,
+        ReputationEvent,
+        ReputationTier,
     },
     protocol::ProtocolId,
     types::{CommsDatabase, CommsPublicKey},
@@ -568,6 +570,39 @@ where DS: KeyValueStore<PeerId, Peer>
         Ok(result)
     }
 
+    /// Records a reputation event for the peer. Returns the new reputation score.
+    pub fn record_reputation_event(
+        &mut self,
+        node_id: &NodeId,
+        event: ReputationEvent,
+    ) -> Result<i32, PeerManagerError> {
+        let peer_key = *self
+            .node_id_index
+            .get(node_id)
+            .ok_or(PeerManagerError::PeerNotFoundError)?;
+        let mut peer: Peer = self
+            .peer_db
+            .get(&peer_key)
+            .map_err(PeerManagerError::DatabaseError)?
+            .expect("node_id_index is out of sync with peer db");
+        let score = peer.record_reputation_event(event);
+        self.peer_db
+            .insert(peer_key, peer)
+            .map_err(PeerManagerError::DatabaseError)?;
+        Ok(score)
+    }
+
+    /// Returns the reputation tiers of the given peers. Peers that are not found are omitted.
+    pub fn reputation_tiers(&self, node_ids: &[NodeId]) -> Result<HashMap<NodeId, ReputationTier>, PeerManagerError> {
+        let mut tiers = HashMap::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            if let Some(peer) = self.find_by_node_id(node_id)? {
+                tiers.insert(peer.node_id, peer.reputation.tier());
+            }
+        }
+        Ok(tiers)
+    }
+
     pub fn mark_last_seen(&mut self, node_id: &NodeId) -> Result<(), PeerManagerError> {
         let mut peer = self
             .find_by_node_id(node_id)?
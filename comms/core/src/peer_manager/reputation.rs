@@ -0,0 +1,208 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, time::Duration};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The maximum reputation score a peer can accumulate
+const MAX_SCORE: i32 = 1000;
+/// The minimum reputation score a peer can accumulate
+const MIN_SCORE: i32 = -1000;
+/// Peers at or above this score are considered reliable
+const RELIABLE_THRESHOLD: i32 = 100;
+/// Peers at or below this score are considered to have a poor reputation
+const POOR_THRESHOLD: i32 = -100;
+/// The time it takes for a score to decay to half its value. This allows peers to recover from past misbehaviour and
+/// prevents a peer from coasting on good behaviour from long ago.
+const SCORE_HALF_LIFE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Events that affect the reputation of a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    /// The peer provided data that failed validation during chain sync
+    SyncValidationFailed,
+    /// The peer sent or propagated an invalid message
+    InvalidMessage,
+    /// The peer did not respond to an RPC request in time
+    RpcTimeout,
+    /// The peer provided a block that was added to the local chain
+    UsefulBlock,
+    /// The peer delivered valid store and forward messages
+    StoreAndForwardDelivered,
+}
+
+impl ReputationEvent {
+    /// The amount that this event adds to (or subtracts from) the reputation score
+    pub fn score_delta(self) -> i32 {
+        use ReputationEvent::{
+            InvalidMessage,
+            RpcTimeout,
+            StoreAndForwardDelivered,
+            SyncValidationFailed,
+            UsefulBlock,
+        };
+        match self {
+            SyncValidationFailed => -200,
+            InvalidMessage => -100,
+            RpcTimeout => -25,
+            UsefulBlock => 10,
+            StoreAndForwardDelivered => 10,
+        }
+    }
+}
+
+impl fmt::Display for ReputationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Coarse grouping of reputation scores used when preferring one peer over another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReputationTier {
+    Poor,
+    Neutral,
+    Reliable,
+}
+
+impl Default for ReputationTier {
+    fn default() -> Self {
+        ReputationTier::Neutral
+    }
+}
+
+/// Locally observed reputation of a [Peer](super::Peer), combining good and bad behaviour into a single score. The
+/// score decays towards zero over time.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PeerReputation {
+    score: i32,
+    last_updated: Option<NaiveDateTime>,
+}
+
+impl PeerReputation {
+    /// New neutral reputation
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the current reputation score, taking decay into account
+    pub fn score(&self) -> i32 {
+        self.score_at(Utc::now().naive_utc())
+    }
+
+    /// Returns the current reputation tier
+    pub fn tier(&self) -> ReputationTier {
+        match self.score() {
+            s if s >= RELIABLE_THRESHOLD => ReputationTier::Reliable,
+            s if s <= POOR_THRESHOLD => ReputationTier::Poor,
+            _ => ReputationTier::Neutral,
+        }
+    }
+
+    /// The date time (UTC) that the last reputation event was recorded, or None if no events have been recorded
+    pub fn last_updated(&self) -> Option<&NaiveDateTime> {
+        self.last_updated.as_ref()
+    }
+
+    /// Records a reputation event and returns the new score
+    pub fn record(&mut self, event: ReputationEvent) -> i32 {
+        self.record_at(event, Utc::now().naive_utc())
+    }
+
+    fn record_at(&mut self, event: ReputationEvent, now: NaiveDateTime) -> i32 {
+        self.score = self
+            .score_at(now)
+            .saturating_add(event.score_delta())
+            .clamp(MIN_SCORE, MAX_SCORE);
+        self.last_updated = Some(now);
+        self.score
+    }
+
+    fn score_at(&self, now: NaiveDateTime) -> i32 {
+        let elapsed = match self.last_updated {
+            Some(last_updated) => (now - last_updated).to_std().unwrap_or_default(),
+            None => return self.score,
+        };
+        let num_half_lives = elapsed.as_secs_f64() / SCORE_HALF_LIFE.as_secs_f64();
+        (f64::from(self.score) * 0.5f64.powf(num_half_lives)).round() as i32
+    }
+}
+
+impl fmt::Display for PeerReputation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?})", self.score(), self.tier())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_records_events() {
+        let mut reputation = PeerReputation::new();
+        assert_eq!(reputation.score(), 0);
+        assert_eq!(reputation.tier(), ReputationTier::Neutral);
+        assert!(reputation.last_updated().is_none());
+
+        for _ in 0..10 {
+            reputation.record(ReputationEvent::UsefulBlock);
+        }
+        assert_eq!(reputation.score(), 100);
+        assert_eq!(reputation.tier(), ReputationTier::Reliable);
+        assert!(reputation.last_updated().is_some());
+
+        reputation.record(ReputationEvent::SyncValidationFailed);
+        assert_eq!(reputation.score(), -100);
+        assert_eq!(reputation.tier(), ReputationTier::Poor);
+    }
+
+    #[test]
+    fn it_clamps_the_score() {
+        let mut reputation = PeerReputation::new();
+        for _ in 0..10 {
+            reputation.record(ReputationEvent::SyncValidationFailed);
+        }
+        assert_eq!(reputation.score(), MIN_SCORE);
+
+        for _ in 0..200 {
+            reputation.record(ReputationEvent::StoreAndForwardDelivered);
+        }
+        assert_eq!(reputation.score(), MAX_SCORE);
+    }
+
+    #[test]
+    fn it_decays_towards_zero() {
+        let now = Utc::now().naive_utc();
+        let half_life = chrono::Duration::from_std(SCORE_HALF_LIFE).unwrap();
+        let mut reputation = PeerReputation::new();
+        reputation.record_at(ReputationEvent::SyncValidationFailed, now);
+        assert_eq!(reputation.score_at(now), -200);
+        assert_eq!(reputation.score_at(now + half_life), -100);
+        assert_eq!(reputation.score_at(now + half_life * 2), -50);
+
+        // Decay is applied before the event is recorded
+        assert_eq!(reputation.record_at(ReputationEvent::UsefulBlock, now + half_life), -90);
+    }
+}
@@ -27,6 +27,7 @@ use tari_shutdown::ShutdownSignal;
 use crate::{
     connectivity::ConnectivityRequester,
     protocol::{ProtocolId, ProtocolNotificationTx, Protocols},
+    resource_manager::ResourceManager,
     PeerManager,
     Substream,
 };
@@ -111,6 +112,7 @@ impl IntoIterator for ProtocolExtensions {
 pub struct ProtocolExtensionContext {
     connectivity: ConnectivityRequester,
     peer_manager: Arc<PeerManager>,
+    resource_manager: ResourceManager,
     protocols: Option<Protocols<Substream>>,
     complete_signals: Vec<ShutdownSignal>,
     shutdown_signal: ShutdownSignal,
@@ -120,11 +122,13 @@ impl ProtocolExtensionContext {
     pub(crate) fn new(
         connectivity: ConnectivityRequester,
         peer_manager: Arc<PeerManager>,
+        resource_manager: ResourceManager,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         Self {
             connectivity,
             peer_manager,
+            resource_manager,
             protocols: Some(Protocols::new()),
             complete_signals: Vec::new(),
             shutdown_signal,
@@ -160,6 +164,11 @@ impl ProtocolExtensionContext {
         self.peer_manager.clone()
     }
 
+    /// See [ResourceManager](crate::resource_manager::ResourceManager].
+    pub fn resource_manager(&self) -> ResourceManager {
+        self.resource_manager.clone()
+    }
+
     /// Returns the shutdown signal that will trigger on node shutdown.
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
@@ -83,7 +83,8 @@ where
             self.event_tx,
             inbound_message_tx,
             context.shutdown_signal(),
-        );
+        )
+        .with_resource_manager(context.resource_manager());
 
         context.register_complete_signal(messaging.complete_signal());
 
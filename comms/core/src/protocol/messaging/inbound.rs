@@ -27,10 +27,16 @@ use log::*;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     sync::{broadcast, mpsc},
+    time,
 };
 
-use super::{metrics, MessagingEvent, MessagingProtocol};
-use crate::{message::InboundMessage, peer_manager::NodeId, rate_limit::RateLimit};
+use super::{metrics, protocol::MESSAGING_PROTOCOL, MessagingEvent, MessagingProtocol};
+use crate::{
+    message::InboundMessage,
+    peer_manager::NodeId,
+    rate_limit::RateLimit,
+    resource_manager::{ResourceDecision, ResourceManager, ResourceUsage},
+};
 
 const LOG_TARGET: &str = "comms::protocol::messaging::inbound";
 
@@ -41,6 +47,7 @@ pub struct InboundMessaging {
     messaging_events_tx: broadcast::Sender<Arc<MessagingEvent>>,
     rate_limit_capacity: usize,
     rate_limit_restock_interval: Duration,
+    resource_manager: Option<ResourceManager>,
 }
 
 impl InboundMessaging {
@@ -57,9 +64,16 @@ impl InboundMessaging {
             messaging_events_tx,
             rate_limit_capacity,
             rate_limit_restock_interval,
+            resource_manager: None,
         }
     }
 
+    /// Account for inbound messages using the given resource manager
+    pub fn with_resource_manager(mut self, resource_manager: ResourceManager) -> Self {
+        self.resource_manager = Some(resource_manager);
+        self
+    }
+
     pub async fn run<S>(self, socket: S)
     where S: AsyncRead + AsyncWrite + Unpin {
         let peer = &self.peer;
@@ -81,6 +95,9 @@ impl InboundMessaging {
                 Ok(raw_msg) => {
                     inbound_count.inc();
                     let msg_len = raw_msg.len();
+                    if !self.wait_for_budget(msg_len).await {
+                        break;
+                    }
                     let inbound_msg = InboundMessage::new(peer.clone(), raw_msg.freeze());
                     debug!(
                         target: LOG_TARGET,
@@ -126,4 +143,29 @@ impl InboundMessaging {
             peer.short_str()
         );
     }
+
+    /// Records the message against the peer's resource budget, waiting if the peer is being throttled. Returns false
+    /// if the peer has been banned and the session should be closed.
+    async fn wait_for_budget(&self, msg_len: usize) -> bool {
+        let resource_manager = match self.resource_manager.as_ref() {
+            Some(rm) => rm,
+            None => return true,
+        };
+        match resource_manager.record(&self.peer, &MESSAGING_PROTOCOL, ResourceUsage::message(msg_len)) {
+            ResourceDecision::Allow => true,
+            ResourceDecision::Throttle(delay) => {
+                metrics::throttled_count(&self.peer).inc();
+                time::sleep(delay).await;
+                true
+            },
+            ResourceDecision::Deny => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Closing inbound messaging session for peer '{}' because it exceeded its resource budget",
+                    self.peer.short_str()
+                );
+                false
+            },
+        }
+    }
 }
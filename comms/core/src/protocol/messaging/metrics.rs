@@ -73,3 +73,16 @@ pub fn error_count(peer: &NodeId) -> IntCounter {
 
     METER.with_label_values(&[peer.to_string().as_str()])
 }
+
+pub fn throttled_count(peer: &NodeId) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "comms::messaging::throttled_count",
+            "The number of inbound messages delayed because the peer exceeded its resource budget",
+            &["peer_id"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[peer.to_string().as_str()])
+}
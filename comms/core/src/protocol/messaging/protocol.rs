@@ -49,6 +49,7 @@ use crate::{
         ProtocolEvent,
         ProtocolNotification,
     },
+    resource_manager::ResourceManager,
     runtime::task,
 };
 
@@ -111,6 +112,7 @@ pub struct MessagingProtocol {
     internal_messaging_event_rx: mpsc::Receiver<MessagingEvent>,
     retry_queue_tx: mpsc::UnboundedSender<OutboundMessage>,
     retry_queue_rx: mpsc::UnboundedReceiver<OutboundMessage>,
+    resource_manager: Option<ResourceManager>,
     shutdown_signal: ShutdownSignal,
     complete_trigger: Shutdown,
 }
@@ -140,11 +142,18 @@ impl MessagingProtocol {
             retry_queue_tx,
             retry_queue_rx,
             inbound_message_tx,
+            resource_manager: None,
             shutdown_signal,
             complete_trigger: Shutdown::new(),
         }
     }
 
+    /// Account for inbound messages from each peer using the given resource manager
+    pub fn with_resource_manager(mut self, resource_manager: ResourceManager) -> Self {
+        self.resource_manager = Some(resource_manager);
+        self
+    }
+
     /// Returns a signal that resolves when this actor exits.
     pub fn complete_signal(&self) -> ShutdownSignal {
         self.complete_trigger.to_signal()
@@ -295,6 +304,10 @@ impl MessagingProtocol {
             RATE_LIMIT_CAPACITY,
             RATE_LIMIT_RESTOCK_INTERVAL,
         );
+        let inbound_messaging = match self.resource_manager.clone() {
+            Some(resource_manager) => inbound_messaging.with_resource_manager(resource_manager),
+            None => inbound_messaging,
+        };
         task::spawn(inbound_messaging.run(substream));
     }
 
@@ -296,6 +296,35 @@ impl<T: prost::Message + Default + Unpin> Stream for ClientStreaming<T> {
     }
 }
 
+/// The stream of messages sent by the client for a client-streaming or bidirectional method. The stream ends once
+/// the client has sent all of its messages. The server stops reading from the client while this stream is not being
+/// polled, providing flow control for the client.
+#[derive(Debug)]
+pub struct RequestStream<T> {
+    inner: mpsc::Receiver<Bytes>,
+    _in: PhantomData<T>,
+}
+
+impl<T> RequestStream<T> {
+    pub fn new(inner: mpsc::Receiver<Bytes>) -> Self {
+        Self {
+            inner,
+            _in: PhantomData,
+        }
+    }
+}
+
+impl<T: prost::Message + Default + Unpin> Stream for RequestStream<T> {
+    type Item = Result<T, RpcStatus>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match ready!(Pin::new(&mut self.inner).poll_recv(cx)) {
+            Some(bytes) => Poll::Ready(Some(T::decode(bytes).map_err(Into::into))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use bytes::Bytes;
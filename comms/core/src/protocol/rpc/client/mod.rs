@@ -37,13 +37,15 @@ use std::{
     time::{Duration, Instant},
 };
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures::{
     future,
     future::{BoxFuture, Either},
+    stream::BoxStream,
     task::{Context, Poll},
     FutureExt,
     SinkExt,
+    Stream,
     StreamExt,
 };
 use log::*;
@@ -167,6 +169,40 @@ impl RpcClient {
         Ok(ClientStreaming::new(resp))
     }
 
+    /// Perform a client streaming request. Each message of the given stream is sent to the server, which replies with a
+    /// single response once the stream has ended.
+    pub async fn client_streaming<S, T, M, R>(&mut self, requests: S, method: M) -> Result<R, RpcError>
+    where
+        S: Stream<Item = T> + Send + 'static,
+        T: prost::Message + 'static,
+        R: prost::Message + Default + std::fmt::Debug,
+        M: Into<RpcMethod>,
+    {
+        let mut resp = self.call_streaming_inner(requests, method.into()).await?;
+        let resp = resp.recv().await.ok_or(RpcError::ServerClosedRequest)??;
+        let resp = R::decode(resp.into_message())?;
+
+        Ok(resp)
+    }
+
+    /// Perform a bidirectional streaming request. Messages of the given stream are sent to the server while responses
+    /// are streamed back. Dropping the returned stream cancels the request.
+    pub async fn bidirectional_streaming<S, T, M, R>(
+        &mut self,
+        requests: S,
+        method: M,
+    ) -> Result<ClientStreaming<R>, RpcError>
+    where
+        S: Stream<Item = T> + Send + 'static,
+        T: prost::Message + 'static,
+        R: prost::Message + Default,
+        M: Into<RpcMethod>,
+    {
+        let resp = self.call_streaming_inner(requests, method.into()).await?;
+
+        Ok(ClientStreaming::new(resp))
+    }
+
     /// Close the RPC session. Any subsequent calls will error.
     pub async fn close(&mut self) {
         self.connector.close().await;
@@ -194,6 +230,19 @@ impl RpcClient {
         let resp = svc.call(request).await?;
         Ok(resp)
     }
+
+    async fn call_streaming_inner<S, T>(
+        &mut self,
+        requests: S,
+        method: RpcMethod,
+    ) -> Result<mpsc::Receiver<Result<Response<Bytes>, RpcStatus>>, RpcError>
+    where
+        S: Stream<Item = T> + Send + 'static,
+        T: prost::Message + 'static,
+    {
+        let requests = requests.map(|msg| Bytes::from(msg.to_encoded_bytes())).boxed();
+        self.connector.send_streaming_request(method, requests).await
+    }
 }
 
 impl fmt::Debug for RpcClient {
@@ -357,6 +406,24 @@ impl ClientConnector {
         Ok(latency)
     }
 
+    pub async fn send_streaming_request(
+        &mut self,
+        method: RpcMethod,
+        requests: BoxStream<'static, Bytes>,
+    ) -> Result<mpsc::Receiver<Result<Response<Bytes>, RpcStatus>>, RpcError> {
+        let (reply, reply_rx) = oneshot::channel();
+        self.inner
+            .send(ClientRequest::SendStreamingRequest {
+                method,
+                requests,
+                reply,
+            })
+            .await
+            .map_err(|_| RpcError::ClientClosed)?;
+
+        reply_rx.await.map_err(|_| RpcError::RequestCancelled)
+    }
+
     pub fn is_connected(&self) -> bool {
         !self.inner.is_closed()
     }
@@ -522,11 +589,18 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
     }
 
     async fn handle_request(&mut self, req: ClientRequest) -> Result<(), RpcError> {
-        use ClientRequest::{SendPing, SendRequest};
+        use ClientRequest::{SendPing, SendRequest, SendStreamingRequest};
         match req {
             SendRequest { request, reply } => {
                 self.do_request_response(request, reply).await?;
             },
+            SendStreamingRequest {
+                method,
+                requests,
+                reply,
+            } => {
+                self.do_streaming_request(method, requests, reply).await?;
+            },
             SendPing(reply) => {
                 self.do_ping_pong(reply).await?;
             },
@@ -769,6 +843,151 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
         Ok(())
     }
 
+    #[tracing::instrument(level = "trace", name = "rpc_do_streaming_request", skip(self, requests, reply))]
+    async fn do_streaming_request(
+        &mut self,
+        method: RpcMethod,
+        mut requests: BoxStream<'static, Bytes>,
+        reply: oneshot::Sender<mpsc::Receiver<Result<Response<Bytes>, RpcStatus>>>,
+    ) -> Result<(), RpcError> {
+        let request_id = self.next_request_id();
+        let method = method.id();
+
+        let (response_tx, response_rx) = mpsc::channel(5);
+        if reply.send(response_rx).is_err() {
+            warn!(
+                target: LOG_TARGET,
+                "Client streaming request was cancelled before the request was sent (protocol = {})",
+                self.protocol_name(),
+            );
+            return Ok(());
+        }
+
+        let open = proto::rpc::RpcRequest {
+            request_id: u32::from(request_id),
+            method,
+            deadline: self.config.deadline.map(|t| t.as_secs()).unwrap_or(0),
+            flags: RpcMessageFlags::STREAM.bits().into(),
+            payload: Vec::new(),
+        };
+        debug!(target: LOG_TARGET, "Opening request stream: {}", open);
+        if let Err(err) = self.send_request(open).await {
+            warn!(target: LOG_TARGET, "{}", err);
+            metrics::client_errors(&self.node_id, &self.protocol_id).inc();
+            let _result = response_tx.send(Err(err.into())).await;
+            return Ok(());
+        }
+
+        let mut timer = Some(Instant::now());
+        let mut is_input_complete = false;
+        let mut assembler = ResponseAssembler::default();
+        loop {
+            // The timeout applies to the time between messages in either direction
+            let idle_timeout = match self.config.timeout_with_grace_period() {
+                Some(timeout) => Either::Left(time::sleep(timeout)),
+                None => Either::Right(future::pending()),
+            };
+
+            tokio::select! {
+                _ = &mut self.shutdown_signal => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "(stream={}) Client connector closed. Quitting request stream {} early",
+                        self.stream_id(),
+                        request_id
+                    );
+                    break;
+                },
+                _ = response_tx.closed() => {
+                    self.premature_close(request_id, method).await?;
+                    break;
+                },
+                msg = requests.next(), if !is_input_complete => {
+                    is_input_complete = msg.is_none();
+                    if let Err(err) = self.send_stream_message(request_id, method, msg).await {
+                        warn!(target: LOG_TARGET, "{}", err);
+                        metrics::client_errors(&self.node_id, &self.protocol_id).inc();
+                        let _result = response_tx.send(Err(err.into())).await;
+                        self.premature_close(request_id, method).await?;
+                        break;
+                    }
+                },
+                frame = self.framed.next() => {
+                    let frame = frame.ok_or(RpcError::ServerClosedRequest)??;
+                    if let Some(t) = timer.take() {
+                        let _result = self.last_request_latency_tx.send(Some(t.elapsed()));
+                    }
+                    if self.forward_stream_response(request_id, &mut assembler, &response_tx, frame).await? {
+                        break;
+                    }
+                },
+                _ = idle_timeout => {
+                    debug!(target: LOG_TARGET, "Request stream {} (method={}) timed out", request_id, method);
+                    metrics::client_timeouts(&self.node_id, &self.protocol_id).inc();
+                    let _result = response_tx.send(Err(RpcStatus::timed_out("Response timed out"))).await;
+                    self.premature_close(request_id, method).await?;
+                    break;
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forwards a response frame for a request stream to the caller. Returns true once the response is complete.
+    async fn forward_stream_response(
+        &self,
+        request_id: u16,
+        assembler: &mut ResponseAssembler,
+        response_tx: &mpsc::Sender<Result<Response<Bytes>, RpcStatus>>,
+        frame: BytesMut,
+    ) -> Result<bool, RpcError> {
+        let resp = proto::rpc::RpcResponse::decode(frame)?;
+        metrics::inbound_response_bytes(&self.node_id, &self.protocol_id).observe(resp.payload.len() as f64);
+        let resp = match assembler.push(request_id, resp) {
+            Ok(Some(resp)) => resp,
+            Ok(None) => return Ok(false),
+            Err(err @ RpcError::ResponseIdDidNotMatchRequest { .. }) | Err(err @ RpcError::UnexpectedAckResponse) => {
+                // A late response for a previous request, as in `do_request_response`
+                warn!(target: LOG_TARGET, "{}", err);
+                return Ok(false);
+            },
+            Err(err) => return Err(err),
+        };
+
+        match Self::convert_to_result(resp)? {
+            Ok(resp) => {
+                let is_finished = resp.is_finished();
+                let _result = response_tx.send(Ok(resp)).await;
+                Ok(is_finished)
+            },
+            Err(err) => {
+                debug!(target: LOG_TARGET, "Remote service returned error: {}", err);
+                let _result = response_tx.send(Err(err)).await;
+                Ok(true)
+            },
+        }
+    }
+
+    /// Sends a message of the request stream, or FIN once the stream has ended
+    async fn send_stream_message(&mut self, request_id: u16, method: u32, msg: Option<Bytes>) -> Result<(), RpcError> {
+        let (flags, payload) = match msg {
+            Some(msg) => {
+                metrics::outbound_request_bytes(&self.node_id, &self.protocol_id).observe(msg.len() as f64);
+                (RpcMessageFlags::MORE, msg.to_vec())
+            },
+            None => (RpcMessageFlags::FIN, Vec::new()),
+        };
+        let req = proto::rpc::RpcRequest {
+            request_id: u32::from(request_id),
+            method,
+            deadline: self.config.deadline.map(|t| t.as_secs()).unwrap_or(0),
+            flags: flags.bits().into(),
+            payload,
+        };
+        self.send_request(req).await
+    }
+
     async fn premature_close(&mut self, request_id: u16, method: u32) -> Result<(), RpcError> {
         warn!(
             target: LOG_TARGET,
@@ -777,10 +996,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             self.stream_id(),
             self.protocol_name()
         );
+        // FIN is included for servers that do not recognise the CANCEL flag
         let req = proto::rpc::RpcRequest {
             request_id: u32::try_from(request_id).unwrap(),
             method,
-            flags: RpcMessageFlags::FIN.bits().into(),
+            flags: (RpcMessageFlags::FIN | RpcMessageFlags::CANCEL).bits().into(),
             deadline: self.config.deadline.map(|d| d.as_secs()).unwrap_or(0),
             ..Default::default()
         };
@@ -880,6 +1100,11 @@ pub enum ClientRequest {
         request: BaseRequest<Bytes>,
         reply: oneshot::Sender<mpsc::Receiver<Result<Response<Bytes>, RpcStatus>>>,
     },
+    SendStreamingRequest {
+        method: RpcMethod,
+        requests: BoxStream<'static, Bytes>,
+        reply: oneshot::Sender<mpsc::Receiver<Result<Response<Bytes>, RpcStatus>>>,
+    },
     SendPing(oneshot::Sender<Result<Duration, RpcStatus>>),
 }
 
@@ -948,22 +1173,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
     }
 
     fn check_response(&self, resp: &proto::rpc::RpcResponse) -> Result<(), RpcError> {
-        let resp_id = u16::try_from(resp.request_id)
-            .map_err(|_| RpcStatus::protocol_error(&format!("invalid request_id: must be less than {}", u16::MAX)))?;
-
-        let flags = RpcMessageFlags::from_bits_truncate(u8::try_from(resp.flags).unwrap());
-        if flags.contains(RpcMessageFlags::ACK) {
-            return Err(RpcError::UnexpectedAckResponse);
-        }
-
-        if resp_id != self.request_id {
-            return Err(RpcError::ResponseIdDidNotMatchRequest {
-                expected: self.request_id,
-                actual: u16::try_from(resp.request_id).unwrap(),
-            });
-        }
-
-        Ok(())
+        check_response(self.request_id, resp)
     }
 
     async fn next(&mut self) -> Result<proto::rpc::RpcResponse, RpcError> {
@@ -981,3 +1191,66 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         }
     }
 }
+
+/// Reassembles chunked responses from individual frames. Unlike `RpcResponseReader`, no partial response is held in a
+/// future, so reading frames can be interleaved with sending the messages of a request stream.
+#[derive(Default)]
+struct ResponseAssembler {
+    partial: Option<proto::rpc::RpcResponse>,
+    chunk_count: usize,
+}
+
+impl ResponseAssembler {
+    /// Adds a received frame, returning the response once its last chunk has been received
+    pub fn push(
+        &mut self,
+        request_id: u16,
+        resp: proto::rpc::RpcResponse,
+    ) -> Result<Option<proto::rpc::RpcResponse>, RpcError> {
+        check_response(request_id, &resp)?;
+        let is_more = resp.flags().is_more();
+        let resp = match self.partial.take() {
+            Some(mut partial) => {
+                partial.payload.extend(resp.payload);
+                self.chunk_count += 1;
+                partial
+            },
+            None => {
+                self.chunk_count = 1;
+                resp
+            },
+        };
+
+        if !is_more {
+            return Ok(Some(resp));
+        }
+
+        if self.chunk_count >= RPC_CHUNKING_MAX_CHUNKS {
+            return Err(RpcError::ExceededMaxChunkCount {
+                expected: RPC_CHUNKING_MAX_CHUNKS,
+            });
+        }
+
+        self.partial = Some(resp);
+        Ok(None)
+    }
+}
+
+fn check_response(request_id: u16, resp: &proto::rpc::RpcResponse) -> Result<(), RpcError> {
+    let resp_id = u16::try_from(resp.request_id)
+        .map_err(|_| RpcStatus::protocol_error(&format!("invalid request_id: must be less than {}", u16::MAX)))?;
+
+    let flags = RpcMessageFlags::from_bits_truncate(u8::try_from(resp.flags).unwrap());
+    if flags.contains(RpcMessageFlags::ACK) {
+        return Err(RpcError::UnexpectedAckResponse);
+    }
+
+    if resp_id != request_id {
+        return Err(RpcError::ResponseIdDidNotMatchRequest {
+            expected: request_id,
+            actual: resp_id,
+        });
+    }
+
+    Ok(())
+}
@@ -23,11 +23,14 @@
 use std::{fmt, sync::Arc};
 
 use async_trait::async_trait;
+use futures::future::FusedFuture;
+use tari_shutdown::{OptionalShutdownSignal, ShutdownSignal};
 
 use super::RpcError;
 use crate::{
     connectivity::{ConnectivityRequester, ConnectivitySelection},
     peer_manager::{NodeId, OrNotFound, Peer},
+    protocol::ProtocolId,
     PeerConnection,
     PeerManager,
 };
@@ -87,25 +90,55 @@ pub struct RequestContext {
     #[allow(dead_code)]
     backend: Box<dyn RpcCommsProvider>,
     node_id: NodeId,
+    protocol: ProtocolId,
+    cancel_signal: OptionalShutdownSignal,
 }
 
 impl RequestContext {
-    pub(super) fn new(request_id: u32, node_id: NodeId, backend: Box<dyn RpcCommsProvider>) -> Self {
+    pub(super) fn new(
+        request_id: u32,
+        node_id: NodeId,
+        protocol: ProtocolId,
+        backend: Box<dyn RpcCommsProvider>,
+    ) -> Self {
         Self {
             request_id,
             backend,
             node_id,
+            protocol,
+            cancel_signal: OptionalShutdownSignal::none(),
         }
     }
 
+    pub(super) fn with_cancel_signal(mut self, signal: ShutdownSignal) -> Self {
+        self.cancel_signal.set(signal);
+        self
+    }
+
     pub fn peer_node_id(&self) -> &NodeId {
         &self.node_id
     }
 
+    /// The protocol name of the RPC service handling this request
+    pub fn protocol(&self) -> &ProtocolId {
+        &self.protocol
+    }
+
     pub fn request_id(&self) -> u32 {
         self.request_id
     }
 
+    /// Returns a signal that resolves once the client cancels this request or the server stops handling it. Handlers
+    /// that spawn tasks to produce a response stream should stop producing messages once this resolves.
+    pub fn cancel_signal(&self) -> OptionalShutdownSignal {
+        self.cancel_signal.clone()
+    }
+
+    /// Returns true if the client has cancelled this request or the server has stopped handling it
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_signal.is_terminated()
+    }
+
     #[allow(dead_code)]
     pub async fn fetch_peer(&self) -> Result<Peer, RpcError> {
         self.backend.fetch_peer(&self.node_id).await
@@ -126,6 +159,7 @@ impl fmt::Debug for RequestContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RequestContext")
             .field("node_id", &self.node_id)
+            .field("protocol", &String::from_utf8_lossy(&self.protocol))
             .field("backend", &"dyn RpcCommsProvider")
             .finish()
     }
@@ -24,15 +24,17 @@ use std::{convert::TryFrom, fmt, time::Duration};
 
 use bitflags::bitflags;
 use bytes::Bytes;
+use tokio::sync::mpsc;
 
 use super::RpcError;
 use crate::{
     proto,
     proto::rpc::rpc_session_reply::SessionResult,
     protocol::rpc::{
-        body::{Body, IntoBody},
+        body::{Body, IntoBody, RequestStream},
         context::RequestContext,
         error::HandshakeRejectReason,
+        RpcStatus,
         RpcStatusCode,
     },
 };
@@ -41,6 +43,7 @@ use crate::{
 pub struct Request<T> {
     pub(super) context: Option<RequestContext>,
     inner: BaseRequest<T>,
+    stream: Option<mpsc::Receiver<Bytes>>,
 }
 
 impl Request<Bytes> {
@@ -49,6 +52,20 @@ impl Request<Bytes> {
         Ok(Request {
             context: self.context,
             inner: BaseRequest::new(self.inner.method, message),
+            stream: self.stream,
+        })
+    }
+
+    /// Converts this request into a request for a client stream of `T` messages. An error is returned if the client
+    /// did not open a request stream for this call.
+    pub fn into_stream<T: prost::Message + Default>(self) -> Result<Request<RequestStream<T>>, RpcStatus> {
+        let stream = self
+            .stream
+            .ok_or_else(|| RpcStatus::bad_request("This method expects a stream of requests"))?;
+        Ok(Request {
+            context: self.context,
+            inner: BaseRequest::new(self.inner.method, RequestStream::new(stream)),
+            stream: None,
         })
     }
 }
@@ -58,6 +75,7 @@ impl<T> Request<T> {
         Self {
             context: Some(context),
             inner: BaseRequest::new(method, message),
+            stream: None,
         }
     }
 
@@ -65,9 +83,21 @@ impl<T> Request<T> {
         Self {
             context: None,
             inner: BaseRequest::new(method, message),
+            stream: None,
         }
     }
 
+    /// Attaches the receiver for the messages of a client stream to this request
+    pub(super) fn with_stream(mut self, stream: mpsc::Receiver<Bytes>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Returns true if the client opened a request stream for this call
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
     pub fn method(&self) -> RpcMethod {
         self.inner.method
     }
@@ -210,8 +240,14 @@ bitflags! {
         const FIN = 0x01;
         /// Typically sent with empty contents and used to confirm a substream is alive.
         const ACK = 0x02;
-        /// Another chunk to be received
+        /// Another chunk to be received. Request messages of a client stream are also sent with this flag.
         const MORE = 0x04;
+        /// The request was cancelled by the client. This is sent together with FIN so that servers that do not
+        /// understand this flag still interrupt the request.
+        const CANCEL = 0x08;
+        /// The request opens a client stream. The messages of the stream follow with the MORE flag until the client
+        /// sends FIN.
+        const STREAM = 0x10;
     }
 }
 impl RpcMessageFlags {
@@ -226,6 +262,14 @@ impl RpcMessageFlags {
     pub fn is_more(self) -> bool {
         self.contains(Self::MORE)
     }
+
+    pub fn is_cancel(self) -> bool {
+        self.contains(Self::CANCEL)
+    }
+
+    pub fn is_stream(self) -> bool {
+        self.contains(Self::STREAM)
+    }
 }
 
 impl Default for RpcMessageFlags {
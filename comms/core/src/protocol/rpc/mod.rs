@@ -22,7 +22,7 @@
 
 //! # RPC protocol
 //!
-//! Provides a request/response protocol that supports streaming.
+//! Provides a request/response protocol that supports server, client and bidirectional streaming.
 //! Available with the `rpc` crate feature.
 
 #[cfg(test)]
@@ -35,6 +35,8 @@ pub const RPC_MAX_FRAME_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
 const RPC_CHUNKING_MAX_CHUNKS: usize = 16; // 16 x 256 Kib = 4 MiB max combined message size
 const RPC_CHUNKING_THRESHOLD: usize = 256 * 1024;
 const RPC_CHUNKING_SIZE_LIMIT: usize = 384 * 1024;
+/// The number of client stream messages buffered by the server before it stops reading from the client.
+const RPC_REQUEST_STREAM_BUFFER_SIZE: usize = 10;
 
 /// The maximum request payload size
 const fn max_request_size() -> usize {
@@ -58,12 +60,21 @@ const fn max_response_payload_size() -> usize {
 }
 
 mod body;
-pub use body::{Body, ClientStreaming, IntoBody, Streaming};
+pub use body::{Body, ClientStreaming, IntoBody, RequestStream, Streaming};
 
 mod context;
+pub use context::RequestContext;
 
 mod server;
-pub use server::{mock, NamedProtocolService, RpcServer, RpcServerBuilder, RpcServerError, RpcServerHandle};
+pub use server::{
+    interceptor,
+    mock,
+    NamedProtocolService,
+    RpcServer,
+    RpcServerBuilder,
+    RpcServerError,
+    RpcServerHandle,
+};
 
 mod client;
 pub use client::{
@@ -92,7 +103,7 @@ mod not_found;
 
 // Re-exports used to keep things orderly in the #[tari_rpc] proc macro
 pub mod __macro_reexports {
-    pub use futures::{future, future::BoxFuture};
+    pub use futures::{future, future::BoxFuture, Stream};
     pub use tokio::io::{AsyncRead, AsyncWrite};
     pub use tower::Service;
 
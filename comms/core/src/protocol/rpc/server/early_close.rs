@@ -37,6 +37,12 @@ impl<T, TSock: Stream<Item = io::Result<T>> + Unpin> EarlyClose<TSock> {
     pub fn new(inner: TSock) -> Self {
         Self { inner }
     }
+
+    /// Returns the underlying socket. Sending directly to it bypasses the early close check, which is required when the
+    /// remote is expected to send messages while messages are being sent to it.
+    pub fn get_mut(&mut self) -> &mut TSock {
+        &mut self.inner
+    }
 }
 
 impl<TSock: Stream + Unpin> Stream for EarlyClose<TSock> {
@@ -60,6 +60,8 @@ pub enum RpcServerError {
     ServiceCallExceededDeadline,
     #[error("Stream read exceeded deadline")]
     ReadStreamExceededDeadline,
+    #[error("Peer exceeded its resource budget")]
+    ResourceBudgetExceeded,
     #[error("Early close error: {0}")]
     EarlyCloseError(#[from] EarlyCloseError<BytesMut>),
 }
@@ -0,0 +1,162 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use bytes::{Bytes, BytesMut};
+use futures::future;
+use log::*;
+use prost::Message;
+use tari_shutdown::{Shutdown, ShutdownSignal};
+use tokio::sync::mpsc;
+
+use super::RpcServerError;
+use crate::proto;
+
+const LOG_TARGET: &str = "comms::rpc::server::in_flight";
+
+/// Tracks the request that a session is currently handling and routes the frames that the client sends while it is in
+/// progress. These are either cancellations or the messages of a client stream.
+pub(super) struct InFlightRequest {
+    request_id: u32,
+    cancel: Shutdown,
+    is_streaming: bool,
+    is_input_complete: bool,
+    request_tx: Option<mpsc::Sender<Bytes>>,
+    pending_message: Option<Bytes>,
+    next_frame: Option<BytesMut>,
+}
+
+impl InFlightRequest {
+    pub fn new(request_id: u32) -> Self {
+        Self {
+            request_id,
+            cancel: Shutdown::new(),
+            is_streaming: false,
+            is_input_complete: false,
+            request_tx: None,
+            pending_message: None,
+            next_frame: None,
+        }
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    /// Opens the client stream for this request, returning the receiver for the service handler. At most
+    /// `buffer_size` messages are buffered before the server stops reading from the client.
+    pub fn open_stream(&mut self, buffer_size: usize) -> mpsc::Receiver<Bytes> {
+        let (request_tx, request_rx) = mpsc::channel(buffer_size);
+        self.is_streaming = true;
+        self.request_tx = Some(request_tx);
+        request_rx
+    }
+
+    /// Returns a signal that resolves when the client cancels the request. The signal also resolves once this instance
+    /// is dropped i.e. the server has stopped handling the request.
+    pub fn cancel_signal(&self) -> ShutdownSignal {
+        self.cancel.to_signal()
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.is_streaming
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_triggered()
+    }
+
+    /// Returns true if another frame can be read from the client. Reading stops while a message is waiting for the
+    /// handler to consume the request stream, which applies backpressure to the client.
+    pub fn can_read(&self) -> bool {
+        self.pending_message.is_none() && self.next_frame.is_none() && !self.is_cancelled()
+    }
+
+    /// Takes the frame for a subsequent request that was received while this request was in progress, if any
+    pub fn take_next_frame(&mut self) -> Option<BytesMut> {
+        self.next_frame.take()
+    }
+
+    /// Handles a frame received from the client while this request is in progress
+    pub fn handle_frame(&mut self, frame: BytesMut) -> Result<(), RpcServerError> {
+        let decoded_msg = proto::rpc::RpcRequest::decode(&frame[..])?;
+        let flags = decoded_msg.flags();
+
+        if decoded_msg.request_id != self.request_id {
+            if flags.is_fin() || flags.is_more() {
+                trace!(
+                    target: LOG_TARGET,
+                    "Ignoring interruption or request stream message for previous request {}",
+                    decoded_msg.request_id
+                );
+            } else {
+                // The client has moved on to the next request. It is handled once this request is complete.
+                self.next_frame = Some(frame);
+            }
+            return Ok(());
+        }
+
+        // Without a request stream, FIN from the client means that it is no longer interested in the response
+        if flags.is_cancel() || (flags.is_fin() && !self.is_streaming) {
+            debug!(target: LOG_TARGET, "Client cancelled request {}", self.request_id);
+            self.cancel.trigger();
+            return Ok(());
+        }
+
+        if !self.is_streaming || self.is_input_complete {
+            return Err(RpcServerError::UnexpectedIncomingMessage(decoded_msg));
+        }
+
+        if flags.is_fin() {
+            // Dropping the sender ends the request stream for the handler
+            self.is_input_complete = true;
+            self.request_tx = None;
+            return Ok(());
+        }
+
+        // If the handler has dropped the request stream, further messages are discarded
+        if self.request_tx.is_some() {
+            self.pending_message = Some(decoded_msg.payload.into());
+        }
+        Ok(())
+    }
+
+    /// Waits for the handler to make room in the request stream and forwards the pending message to it. This future
+    /// never resolves if there is no pending message, and it is safe to drop.
+    pub async fn forward_pending(&mut self) {
+        let request_tx = match self.request_tx.as_ref() {
+            Some(tx) if self.pending_message.is_some() => tx,
+            _ => return future::pending().await,
+        };
+
+        if let Ok(permit) = request_tx.reserve().await {
+            permit.send(self.pending_message.take().expect("pending_message checked above"));
+            return;
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Handler dropped the request stream for request {}", self.request_id
+        );
+        self.pending_message = None;
+        self.request_tx = None;
+    }
+}
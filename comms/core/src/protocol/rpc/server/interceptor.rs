@@ -0,0 +1,217 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # RPC interceptors
+//!
+//! Interceptors are tower [Layer]s that wrap every service installed on an
+//! [RpcServer](crate::protocol::rpc::RpcServer). They are added using
+//! [RpcServerBuilder::with_interceptor](crate::protocol::rpc::RpcServerBuilder::with_interceptor) and see each request
+//! before it reaches the service. The [RequestContext](crate::protocol::rpc::RequestContext) of the request provides
+//! the peer `NodeId`, protocol name and request id, and the method id is available from the request itself. This allows
+//! cross-cutting concerns such as logging, metrics, allow-lists and quotas to be implemented once for all RPC services
+//! without changing the services themselves.
+//!
+//! Interceptors are applied in the order they are added, i.e. the first interceptor added is the first to see the
+//! request.
+
+use std::{
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use futures::{
+    future,
+    future::{BoxFuture, Either},
+    FutureExt,
+};
+use log::*;
+use tower::{
+    util::{BoxLayer, BoxService},
+    Layer,
+    Service,
+};
+
+use super::metrics;
+use crate::{
+    protocol::rpc::{
+        body::Body,
+        message::{Request, Response},
+        RpcStatus,
+        RpcStatusCode,
+    },
+    Bytes,
+};
+
+const LOG_TARGET: &str = "comms::rpc::server::interceptor";
+
+/// A type-erased RPC service, as seen by interceptors
+pub type BoxRpcService = BoxService<Request<Bytes>, Response<Body>, RpcStatus>;
+
+pub(super) type BoxRpcInterceptor = BoxLayer<BoxRpcService, Request<Bytes>, Response<Body>, RpcStatus>;
+
+/// Wraps the service in the given interceptors. The first interceptor is the outermost service.
+pub(super) fn apply_interceptors<S>(interceptors: &[BoxRpcInterceptor], service: S) -> BoxRpcService
+where
+    S: Service<Request<Bytes>, Response = Response<Body>, Error = RpcStatus> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    interceptors
+        .iter()
+        .rev()
+        .fold(BoxService::new(service), |service, interceptor| {
+            interceptor.layer(service)
+        })
+}
+
+/// An interceptor that rejects requests for which the given predicate returns an error. The error status is returned
+/// to the client and the request does not reach the service. This is useful for allow-lists, authorisation and
+/// per-method quotas.
+#[derive(Clone)]
+pub struct RequestFilterLayer<F> {
+    predicate: F,
+}
+
+impl<F> RequestFilterLayer<F>
+where F: Fn(&Request<Bytes>) -> Result<(), RpcStatus> + Clone
+{
+    pub fn new(predicate: F) -> Self {
+        Self { predicate }
+    }
+}
+
+impl<S, F: Clone> Layer<S> for RequestFilterLayer<F> {
+    type Service = RequestFilter<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestFilter {
+            inner,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+/// The service returned by [RequestFilterLayer]
+pub struct RequestFilter<S, F> {
+    inner: S,
+    predicate: F,
+}
+
+impl<S, F> Service<Request<Bytes>> for RequestFilter<S, F>
+where
+    S: Service<Request<Bytes>, Response = Response<Body>, Error = RpcStatus>,
+    F: Fn(&Request<Bytes>) -> Result<(), RpcStatus>,
+{
+    type Error = RpcStatus;
+    type Future = Either<future::Ready<Result<Response<Body>, RpcStatus>>, S::Future>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        match (self.predicate)(&request) {
+            Ok(()) => Either::Right(self.inner.call(request)),
+            Err(status) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Rejected request {} (method: {}) from peer '{}': {}",
+                    request.context().request_id(),
+                    request.method().id(),
+                    request.context().peer_node_id(),
+                    status
+                );
+                Either::Left(future::ready(Err(status)))
+            },
+        }
+    }
+}
+
+/// An interceptor that logs every call and records the call latency and result per protocol and method.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallMetricsLayer;
+
+impl CallMetricsLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for CallMetricsLayer {
+    type Service = CallMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CallMetrics { inner }
+    }
+}
+
+/// The service returned by [CallMetricsLayer]
+pub struct CallMetrics<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Bytes>> for CallMetrics<S>
+where
+    S: Service<Request<Bytes>, Response = Response<Body>, Error = RpcStatus>,
+    S::Future: Send + 'static,
+{
+    type Error = RpcStatus;
+    type Future = BoxFuture<'static, Result<Response<Body>, RpcStatus>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        let context = request.context();
+        let request_id = context.request_id();
+        let peer = context.peer_node_id().clone();
+        let protocol = context.protocol().clone();
+        let method = request.method();
+        let fut = self.inner.call(request);
+
+        async move {
+            let timer = Instant::now();
+            let result = fut.await;
+            let elapsed = timer.elapsed();
+            metrics::call_latency(&protocol, method).observe(elapsed.as_secs_f64());
+            let status_code = match &result {
+                Ok(_) => RpcStatusCode::Ok,
+                Err(status) => status.as_status_code(),
+            };
+            metrics::call_counter(&protocol, method, status_code).inc();
+            debug!(
+                target: LOG_TARGET,
+                "RPC call {} (protocol: {}, method: {}) from peer '{}' completed in {:.0?} with status {}",
+                request_id,
+                String::from_utf8_lossy(&protocol),
+                method.id(),
+                peer,
+                elapsed,
+                status_code.to_debug_string()
+            );
+            result
+        }
+        .boxed()
+    }
+}
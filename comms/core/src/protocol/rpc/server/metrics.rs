@@ -26,7 +26,7 @@ use tari_metrics::{Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
 use crate::{
     peer_manager::NodeId,
     protocol::{
-        rpc::{RpcServerError, RpcStatusCode},
+        rpc::{message::RpcMethod, RpcServerError, RpcStatusCode},
         ProtocolId,
     },
 };
@@ -116,3 +116,36 @@ pub fn outbound_response_bytes(node_id: &NodeId, protocol: &ProtocolId) -> Histo
 
     METER.with_label_values(&[node_id.to_string().as_str(), String::from_utf8_lossy(protocol).as_ref()])
 }
+
+pub fn call_latency(protocol: &ProtocolId, method: RpcMethod) -> Histogram {
+    static METER: Lazy<HistogramVec> = Lazy::new(|| {
+        tari_metrics::register_histogram_vec(
+            "comms::rpc::server::call_latency",
+            "RPC call latency in seconds per protocol per method",
+            &["protocol", "method"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[
+        String::from_utf8_lossy(protocol).as_ref(),
+        method.id().to_string().as_str(),
+    ])
+}
+
+pub fn call_counter(protocol: &ProtocolId, method: RpcMethod, status_code: RpcStatusCode) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "comms::rpc::server::call_count",
+            "The number of RPC calls by status code per protocol per method",
+            &["protocol", "method", "status"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[
+        String::from_utf8_lossy(protocol).as_ref(),
+        method.id().to_string().as_str(),
+        status_code.to_debug_string().as_str(),
+    ])
+}
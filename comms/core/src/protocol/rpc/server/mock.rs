@@ -87,7 +87,7 @@ impl RpcRequestMock {
     }
 
     pub fn request_with_context<T>(&self, node_id: NodeId, msg: T) -> Request<T> {
-        let context = RequestContext::new(0, node_id, Box::new(self.comms_provider.clone()));
+        let context = RequestContext::new(0, node_id, ProtocolId::new(), Box::new(self.comms_provider.clone()));
         Request::with_context(context, 0.into(), msg)
     }
 
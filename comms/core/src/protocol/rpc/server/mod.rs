@@ -35,6 +35,12 @@ mod metrics;
 pub mod mock;
 
 mod early_close;
+mod in_flight;
+use in_flight::InFlightRequest;
+
+pub mod interceptor;
+use interceptor::{BoxRpcInterceptor, BoxRpcService};
+
 mod router;
 
 use std::{
@@ -44,19 +50,18 @@ use std::{
     convert::TryFrom,
     future::Future,
     io,
-    pin::Pin,
     sync::Arc,
-    task::Poll,
     time::{Duration, Instant},
 };
 
-use futures::{future, stream, stream::FuturesUnordered, SinkExt, StreamExt};
+use bytes::BytesMut;
+use futures::{stream, stream::FuturesUnordered, SinkExt, StreamExt};
 use log::*;
 use prost::Message;
 use router::Router;
+use tari_metrics::Histogram;
 use tokio::{sync::mpsc, task::JoinHandle, time};
-use tokio_stream::Stream;
-use tower::{make::MakeService, Service};
+use tower::{make::MakeService, Layer, Service};
 use tracing::{debug, error, instrument, span, trace, warn, Instrument, Level};
 
 use super::{
@@ -68,6 +73,7 @@ use super::{
     status::RpcStatus,
     Handshake,
     RPC_MAX_FRAME_SIZE,
+    RPC_REQUEST_STREAM_BUFFER_SIZE,
 };
 use crate::{
     bounded_executor::BoundedExecutor,
@@ -87,6 +93,7 @@ use crate::{
         ProtocolNotification,
         ProtocolNotificationRx,
     },
+    resource_manager::{ResourceDecision, ResourceManager, ResourceUsage},
     stream_id::StreamId,
     Bytes,
     Substream,
@@ -177,6 +184,8 @@ pub struct RpcServerBuilder {
     maximum_sessions_per_client: Option<usize>,
     minimum_client_deadline: Duration,
     handshake_timeout: Duration,
+    resource_manager: Option<ResourceManager>,
+    interceptors: Vec<BoxRpcInterceptor>,
 }
 
 impl RpcServerBuilder {
@@ -209,6 +218,25 @@ impl RpcServerBuilder {
         self
     }
 
+    /// Account for inbound RPC calls using the given resource manager. If this is not set, the comms node's resource
+    /// manager is used once the server is installed.
+    pub fn with_resource_manager(mut self, resource_manager: ResourceManager) -> Self {
+        self.resource_manager = Some(resource_manager);
+        self
+    }
+
+    /// Add an interceptor that wraps every service on this server. Interceptors see each request in the order they
+    /// were added. See [interceptor](crate::protocol::rpc::interceptor) for details.
+    pub fn with_interceptor<L>(mut self, interceptor: L) -> Self
+    where
+        L: Layer<BoxRpcService> + Send + Sync + 'static,
+        L::Service: Service<Request<Bytes>, Response = Response<Body>, Error = RpcStatus> + Send + 'static,
+        <L::Service as Service<Request<Bytes>>>::Future: Send + 'static,
+    {
+        self.interceptors.push(BoxRpcInterceptor::new(interceptor));
+        self
+    }
+
     pub fn finish(self) -> RpcServer {
         let (request_tx, request_rx) = mpsc::channel(10);
         RpcServer {
@@ -226,6 +254,8 @@ impl Default for RpcServerBuilder {
             maximum_sessions_per_client: None,
             minimum_client_deadline: Duration::from_secs(1),
             handshake_timeout: Duration::from_secs(15),
+            resource_manager: None,
+            interceptors: Vec::new(),
         }
     }
 }
@@ -452,6 +482,7 @@ where
             "Server negotiated RPC v{} with client node `{}`", version, node_id
         );
 
+        let service = interceptor::apply_interceptors(&self.config.interceptors, service);
         let service = ActivePeerRpcService::new(
             self.config.clone(),
             protocol,
@@ -489,6 +520,8 @@ struct ActivePeerRpcService<TSvc, TCommsProvider> {
     framed: EarlyClose<CanonicalFraming<Substream>>,
     comms_provider: TCommsProvider,
     logging_context_string: Arc<String>,
+    pending_frame: Option<BytesMut>,
+    request_bytes: Histogram,
 }
 
 impl<TSvc, TCommsProvider> ActivePeerRpcService<TSvc, TCommsProvider>
@@ -512,12 +545,14 @@ where
                 String::from_utf8_lossy(&protocol)
             )),
 
+            request_bytes: metrics::inbound_requests_bytes(&node_id, &protocol),
             config,
             protocol,
             node_id,
             service,
             framed: EarlyClose::new(framed),
             comms_provider,
+            pending_frame: None,
         }
     }
 
@@ -544,12 +579,14 @@ where
     }
 
     async fn run(&mut self) -> Result<(), RpcServerError> {
-        let request_bytes = metrics::inbound_requests_bytes(&self.node_id, &self.protocol);
-        while let Some(result) = self.framed.next().await {
+        while let Some(result) = self.next_frame().await {
             match result {
-                Ok(frame) => {
+                Ok((frame, usage)) => {
                     let start = Instant::now();
-                    request_bytes.observe(frame.len() as f64);
+                    if let Err(err) = self.wait_for_budget(usage).await {
+                        self.framed.close().await?;
+                        return Err(err);
+                    }
                     if let Err(err) = self.handle_request(frame.freeze()).await {
                         if let Err(err) = self.framed.close().await {
                             let level = err.io().map(err_to_log_level).unwrap_or(log::Level::Error);
@@ -596,6 +633,62 @@ where
         Ok(())
     }
 
+    /// Returns the next frame from the client and the resources it uses, starting with a frame that was received while
+    /// the previous request was in progress. The bytes of that frame were recorded when it was received, so only the
+    /// call is recorded for it.
+    async fn next_frame(&mut self) -> Option<io::Result<(BytesMut, ResourceUsage)>> {
+        if let Some(frame) = self.pending_frame.take() {
+            let usage = ResourceUsage {
+                rpc_calls: 1,
+                ..Default::default()
+            };
+            return Some(Ok((frame, usage)));
+        }
+        let result = self.framed.next().await?;
+        Some(result.map(|frame| {
+            self.request_bytes.observe(frame.len() as f64);
+            let usage = ResourceUsage::rpc_call(frame.len());
+            (frame, usage)
+        }))
+    }
+
+    /// Records a frame that the client sent while a request is in flight against the peer's resource budget and
+    /// passes it to the request. Without this, a client could send an unlimited number of messages on a request
+    /// stream.
+    async fn handle_in_flight_frame(
+        &mut self,
+        in_flight: &mut InFlightRequest,
+        frame: Option<io::Result<BytesMut>>,
+    ) -> Result<(), RpcServerError> {
+        let frame = frame.ok_or(RpcServerError::StreamClosedByRemote)??;
+        self.request_bytes.observe(frame.len() as f64);
+        self.wait_for_budget(ResourceUsage::message(frame.len())).await?;
+        in_flight.handle_frame(frame)
+    }
+
+    /// Records the usage against the peer's resource budget, waiting if the peer is being throttled. An error is
+    /// returned if the peer has been banned.
+    async fn wait_for_budget(&mut self, usage: ResourceUsage) -> Result<(), RpcServerError> {
+        let resource_manager = match self.config.resource_manager.as_ref() {
+            Some(rm) => rm,
+            None => return Ok(()),
+        };
+        match resource_manager.record(&self.node_id, &self.protocol, usage) {
+            ResourceDecision::Allow => Ok(()),
+            ResourceDecision::Throttle(delay) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "({}) Peer exceeded its resource budget. Delaying request by {:.0?}",
+                    self.logging_context_string,
+                    delay
+                );
+                time::sleep(delay).await;
+                Ok(())
+            },
+            ResourceDecision::Deny => Err(RpcServerError::ResourceBudgetExceeded),
+        }
+    }
+
     #[instrument(name = "rpc::server::handle_req", skip(self, request), err, fields(request_size = request.len()))]
     async fn handle_request(&mut self, mut request: Bytes) -> Result<(), RpcServerError> {
         let decoded_msg = proto::rpc::RpcRequest::decode(&mut request)?;
@@ -632,6 +725,16 @@ where
             debug!(target: LOG_TARGET, "({}) Client sent FIN.", self.logging_context_string);
             return Ok(());
         }
+        if msg_flags.is_more() {
+            // The server may complete a request before the client has finished sending its request stream
+            debug!(
+                target: LOG_TARGET,
+                "({}) Ignoring request stream message for completed request {}",
+                self.logging_context_string,
+                request_id
+            );
+            return Ok(());
+        }
         if msg_flags.contains(RpcMessageFlags::ACK) {
             debug!(
                 target: LOG_TARGET,
@@ -655,42 +758,35 @@ where
             method.id()
         );
 
-        let req = Request::with_context(
-            self.create_request_context(request_id),
-            method,
-            decoded_msg.payload.into(),
-        );
+        let mut in_flight = InFlightRequest::new(request_id);
+        let context = self
+            .create_request_context(request_id)
+            .with_cancel_signal(in_flight.cancel_signal());
+        let mut req = Request::with_context(context, method, decoded_msg.payload.into());
+        if msg_flags.is_stream() {
+            req = req.with_stream(in_flight.open_stream(RPC_REQUEST_STREAM_BUFFER_SIZE));
+        }
 
-        let service_call = log_timing(
-            self.logging_context_string.clone(),
-            request_id,
-            "service call",
-            self.service.call(req),
-        );
-        let service_result = time::timeout(deadline, service_call).await;
-        let service_result = match service_result {
-            Ok(v) => v,
-            Err(_) => {
-                warn!(
-                    target: LOG_TARGET,
-                    "{} RPC service was not able to complete within the deadline ({:.0?}). Request aborted",
-                    self.logging_context_string,
-                    deadline,
-                );
+        let result = self.handle_in_flight(&mut in_flight, deadline, req).await;
+        // A frame for the next request may have been received while this request was in progress
+        self.pending_frame = in_flight.take_next_frame();
+        result
+    }
 
-                metrics::error_counter(
-                    &self.node_id,
-                    &self.protocol,
-                    &RpcServerError::ServiceCallExceededDeadline,
-                )
-                .inc();
-                return Ok(());
-            },
+    async fn handle_in_flight(
+        &mut self,
+        in_flight: &mut InFlightRequest,
+        deadline: Duration,
+        req: Request<Bytes>,
+    ) -> Result<(), RpcServerError> {
+        let service_result = match self.call_service(in_flight, deadline, req).await? {
+            Some(result) => result,
+            None => return Ok(()),
         };
 
         match service_result {
             Ok(body) => {
-                self.process_body(request_id, deadline, body).await?;
+                self.process_body(in_flight, deadline, body).await?;
             },
             Err(err) => {
                 debug!(
@@ -698,7 +794,7 @@ where
                     "{} Service returned an error: {}", self.logging_context_string, err
                 );
                 let resp = proto::rpc::RpcResponse {
-                    request_id,
+                    request_id: in_flight.request_id(),
                     status: err.as_code(),
                     flags: RpcMessageFlags::FIN.bits().into(),
                     payload: err.to_details_bytes(),
@@ -712,16 +808,73 @@ where
         Ok(())
     }
 
+    /// Calls the service, routing frames that the client sends in the meantime. None is returned if the client
+    /// cancelled the request or the service did not complete within the deadline. For streaming requests, the deadline
+    /// applies to the time between client messages rather than to the whole call.
+    async fn call_service(
+        &mut self,
+        in_flight: &mut InFlightRequest,
+        deadline: Duration,
+        req: Request<Bytes>,
+    ) -> Result<Option<Result<Response<Body>, RpcStatus>>, RpcServerError> {
+        let service_call = log_timing(
+            self.logging_context_string.clone(),
+            in_flight.request_id(),
+            "service call",
+            self.service.call(req),
+        );
+        tokio::pin!(service_call);
+        let timeout = time::sleep(deadline);
+        tokio::pin!(timeout);
+
+        loop {
+            tokio::select! {
+                result = &mut service_call => return Ok(Some(result)),
+                _ = &mut timeout => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "{} RPC service was not able to complete within the deadline ({:.0?}). Request aborted",
+                        self.logging_context_string,
+                        deadline,
+                    );
+
+                    metrics::error_counter(
+                        &self.node_id,
+                        &self.protocol,
+                        &RpcServerError::ServiceCallExceededDeadline,
+                    )
+                    .inc();
+                    return Ok(None);
+                },
+                frame = self.framed.next(), if in_flight.can_read() => {
+                    self.handle_in_flight_frame(in_flight, frame).await?;
+                    if in_flight.is_cancelled() {
+                        debug!(
+                            target: LOG_TARGET,
+                            "({}) Request was cancelled by the client", self.logging_context_string
+                        );
+                        return Ok(None);
+                    }
+                    if in_flight.is_streaming() {
+                        timeout.as_mut().reset(time::Instant::now() + deadline);
+                    }
+                },
+                _ = in_flight.forward_pending() => {},
+            }
+        }
+    }
+
     fn protocol_name(&self) -> Cow<'_, str> {
         String::from_utf8_lossy(&self.protocol)
     }
 
     async fn process_body(
         &mut self,
-        request_id: u32,
+        in_flight: &mut InFlightRequest,
         deadline: Duration,
         body: Response<Body>,
     ) -> Result<(), RpcServerError> {
+        let request_id = in_flight.request_id();
         let response_bytes = metrics::outbound_response_bytes(&self.node_id, &self.protocol);
         trace!(target: LOG_TARGET, "Service call succeeded");
 
@@ -748,19 +901,18 @@ where
             let timeout = time::sleep(deadline);
 
             tokio::select! {
-                // Check if the client interrupted the outgoing stream
-                Err(err) = self.check_interruptions() => {
-                    match err {
-                        err @ RpcServerError::ClientInterruptedStream => {
-                            debug!(target: LOG_TARGET, "Stream was interrupted by client: {}", err);
-                            break;
-                        },
-                        err => {
-                            error!(target: LOG_TARGET, "Stream was interrupted: {}", err);
-                            return Err(err);
-                        },
+                // The client may interrupt the outgoing stream or continue to send messages for its request stream
+                frame = self.framed.next(), if in_flight.can_read() => {
+                    self.handle_in_flight_frame(in_flight, frame).await?;
+                    if in_flight.is_cancelled() {
+                        debug!(
+                            target: LOG_TARGET,
+                            "({}) Stream was interrupted by client", self.logging_context_string
+                        );
+                        break;
                     }
                 },
+                _ = in_flight.forward_pending() => {},
                 msg = next_item => {
                      match msg {
                          Some(msg) => {
@@ -772,7 +924,13 @@ where
                                 msg.len()
                             );
 
-                            self.framed.send(msg).await?;
+                            if in_flight.is_streaming() {
+                                // Messages from the client are expected while responses are sent, so these must not
+                                // be treated as an early close
+                                self.framed.get_mut().send(msg).await?;
+                            } else {
+                                self.framed.send(msg).await?;
+                            }
                         },
                         None => {
                             debug!(target: LOG_TARGET, "{} Request complete", self.logging_context_string,);
@@ -802,37 +960,13 @@ where
         Ok(())
     }
 
-    async fn check_interruptions(&mut self) -> Result<(), RpcServerError> {
-        let check = future::poll_fn(|cx| match Pin::new(&mut self.framed).poll_next(cx) {
-            Poll::Ready(Some(Ok(mut msg))) => {
-                let decoded_msg = match proto::rpc::RpcRequest::decode(&mut msg) {
-                    Ok(msg) => msg,
-                    Err(err) => {
-                        error!(target: LOG_TARGET, "Client send MALFORMED response: {}", err);
-                        return Poll::Ready(Some(RpcServerError::UnexpectedIncomingMessageMalformed));
-                    },
-                };
-                let msg_flags = RpcMessageFlags::from_bits_truncate(u8::try_from(decoded_msg.flags).unwrap());
-                if msg_flags.is_fin() {
-                    Poll::Ready(Some(RpcServerError::ClientInterruptedStream))
-                } else {
-                    Poll::Ready(Some(RpcServerError::UnexpectedIncomingMessage(decoded_msg)))
-                }
-            },
-            Poll::Ready(Some(Err(err))) if err.kind() == io::ErrorKind::WouldBlock => Poll::Ready(None),
-            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(RpcServerError::from(err))),
-            Poll::Ready(None) => Poll::Ready(Some(RpcServerError::StreamClosedByRemote)),
-            Poll::Pending => Poll::Ready(None),
-        })
-        .await;
-        match check {
-            Some(err) => Err(err),
-            None => Ok(()),
-        }
-    }
-
     fn create_request_context(&self, request_id: u32) -> RequestContext {
-        RequestContext::new(request_id, self.node_id.clone(), Box::new(self.comms_provider.clone()))
+        RequestContext::new(
+            request_id,
+            self.node_id.clone(),
+            self.protocol.clone(),
+            Box::new(self.comms_provider.clone()),
+        )
     }
 }
 
@@ -211,7 +211,11 @@ where
         let (proto_notif_tx, proto_notif_rx) = mpsc::channel(20);
         context.add_protocol(&self.protocol_names, &proto_notif_tx);
         let rpc_context = RpcCommsBackend::new(context.peer_manager(), context.connectivity());
-        task::spawn(self.serve(proto_notif_rx, rpc_context));
+        let mut router = *self;
+        if router.server.builder.resource_manager.is_none() {
+            router.server.builder.resource_manager = Some(context.resource_manager());
+        }
+        task::spawn(router.serve(proto_notif_rx, rpc_context));
         Ok(())
     }
 }
@@ -31,6 +31,7 @@ use std::{
     time::Duration,
 };
 
+use futures::StreamExt;
 use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, RwLock},
@@ -41,7 +42,7 @@ use tokio::{
 use crate::{
     async_trait,
     protocol::{
-        rpc::{NamedProtocolService, Request, Response, RpcError, RpcServerError, RpcStatus, Streaming},
+        rpc::{NamedProtocolService, Request, RequestStream, Response, RpcError, RpcServerError, RpcStatus, Streaming},
         ProtocolId,
     },
     utils,
@@ -67,12 +68,19 @@ pub trait GreetingRpc: Send + Sync + 'static {
     async fn reply_with_msg_of_size(&self, request: Request<u64>) -> Result<Vec<u8>, RpcStatus>;
     // #[rpc(method = 8)]
     async fn slow_stream(&self, request: Request<SlowStreamRequest>) -> Result<Streaming<Vec<u8>>, RpcStatus>;
+    // #[rpc(method = 9)]
+    async fn sum_numbers(&self, request: Request<RequestStream<u32>>) -> Result<Response<u32>, RpcStatus>;
+    // #[rpc(method = 10)]
+    async fn echo_stream(&self, request: Request<RequestStream<String>>) -> Result<Streaming<String>, RpcStatus>;
+    // #[rpc(method = 11)]
+    async fn wait_for_cancel(&self, request: Request<()>) -> Result<Streaming<String>, RpcStatus>;
 }
 
 #[derive(Clone)]
 pub struct GreetingService {
     greetings: Vec<String>,
     call_count: Arc<AtomicUsize>,
+    cancel_count: Arc<AtomicUsize>,
 }
 
 impl GreetingService {
@@ -83,6 +91,7 @@ impl GreetingService {
         Self {
             greetings: greetings.iter().map(ToString::to_string).collect(),
             call_count: Default::default(),
+            cancel_count: Default::default(),
         }
     }
 
@@ -90,6 +99,10 @@ impl GreetingService {
         self.call_count.load(Ordering::Acquire)
     }
 
+    pub fn cancel_count(&self) -> usize {
+        self.cancel_count.load(Ordering::Acquire)
+    }
+
     fn inc_call_count(&self) {
         self.call_count.fetch_add(1, Ordering::Relaxed);
     }
@@ -184,6 +197,45 @@ impl GreetingRpc for GreetingService {
 
         Ok(Streaming::new(rx))
     }
+
+    async fn sum_numbers(&self, request: Request<RequestStream<u32>>) -> Result<Response<u32>, RpcStatus> {
+        self.inc_call_count();
+        let mut numbers = request.into_message();
+        let mut sum = 0;
+        while let Some(n) = numbers.next().await {
+            sum += n?;
+        }
+        Ok(Response::new(sum))
+    }
+
+    async fn echo_stream(&self, request: Request<RequestStream<String>>) -> Result<Streaming<String>, RpcStatus> {
+        self.inc_call_count();
+        let mut messages = request.into_message();
+        let (tx, rx) = mpsc::channel(1);
+        task::spawn(async move {
+            while let Some(msg) = messages.next().await {
+                if tx.send(msg.map(|msg| format!("Echo: {}", msg))).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Streaming::new(rx))
+    }
+
+    async fn wait_for_cancel(&self, request: Request<()>) -> Result<Streaming<String>, RpcStatus> {
+        self.inc_call_count();
+        let cancel_signal = request.context().cancel_signal();
+        let cancel_count = self.cancel_count.clone();
+        let (tx, rx) = mpsc::channel(1);
+        task::spawn(async move {
+            let _result = tx.send(Ok("Waiting".to_string())).await;
+            cancel_signal.await;
+            cancel_count.fetch_add(1, Ordering::AcqRel);
+        });
+
+        Ok(Streaming::new(rx))
+    }
 }
 
 pub struct SlowGreetingService {
@@ -233,6 +285,18 @@ impl GreetingRpc for SlowGreetingService {
     async fn slow_stream(&self, _: Request<SlowStreamRequest>) -> Result<Streaming<Vec<u8>>, RpcStatus> {
         unimplemented!()
     }
+
+    async fn sum_numbers(&self, _: Request<RequestStream<u32>>) -> Result<Response<u32>, RpcStatus> {
+        unimplemented!()
+    }
+
+    async fn echo_stream(&self, _: Request<RequestStream<String>>) -> Result<Streaming<String>, RpcStatus> {
+        unimplemented!()
+    }
+
+    async fn wait_for_cancel(&self, _: Request<()>) -> Result<Streaming<String>, RpcStatus> {
+        unimplemented!()
+    }
 }
 #[derive(prost::Message)]
 pub struct SlowStreamRequest {
@@ -352,6 +416,30 @@ impl<T: GreetingRpc> __rpc_deps::Service<Request<__rpc_deps::Bytes>> for Greetin
                 };
                 Box::pin(fut)
             },
+            // sum_numbers
+            9 => {
+                let fut = async move {
+                    let resp = inner.sum_numbers(req.into_stream()?).await?;
+                    Ok(resp.map(IntoBody::into_body))
+                };
+                Box::pin(fut)
+            },
+            // echo_stream
+            10 => {
+                let fut = async move {
+                    let resp = inner.echo_stream(req.into_stream()?).await?;
+                    Ok(Response::new(resp.into_body()))
+                };
+                Box::pin(fut)
+            },
+            // wait_for_cancel
+            11 => {
+                let fut = async move {
+                    let resp = inner.wait_for_cancel(req.decode()?).await?;
+                    Ok(Response::new(resp.into_body()))
+                };
+                Box::pin(fut)
+            },
 
             id => Box::pin(__rpc_deps::future::ready(Err(RpcStatus::unsupported_method(&format!(
                 "Method identifier `{}` is not recognised or supported",
@@ -450,6 +538,20 @@ impl GreetingClient {
         self.inner.server_streaming(request, 8).await
     }
 
+    pub async fn sum_numbers<S>(&mut self, requests: S) -> Result<u32, RpcError>
+    where S: __rpc_deps::Stream<Item = u32> + Send + 'static {
+        self.inner.client_streaming(requests, 9).await
+    }
+
+    pub async fn echo_stream<S>(&mut self, requests: S) -> Result<__rpc_deps::ClientStreaming<String>, RpcError>
+    where S: __rpc_deps::Stream<Item = String> + Send + 'static {
+        self.inner.bidirectional_streaming(requests, 10).await
+    }
+
+    pub async fn wait_for_cancel(&mut self) -> Result<__rpc_deps::ClientStreaming<String>, RpcError> {
+        self.inner.server_streaming((), 11).await
+    }
+
     pub fn get_last_request_latency(&mut self) -> Option<Duration> {
         self.inner.get_last_request_latency()
     }
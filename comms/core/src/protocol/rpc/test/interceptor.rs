@@ -0,0 +1,105 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use tari_test_utils::unpack_enum;
+
+use crate::{
+    framing,
+    peer_manager::NodeId,
+    protocol::{
+        rpc::{
+            interceptor::{CallMetricsLayer, RequestFilterLayer},
+            test::{
+                greeting_service::{GreetingClient, GreetingService, SayHelloRequest},
+                smoke::setup_with_builder,
+            },
+            Request,
+            RpcError,
+            RpcServer,
+            RpcStatus,
+            RpcStatusCode,
+        },
+        ProtocolId,
+    },
+    runtime,
+    Bytes,
+};
+
+#[runtime::test]
+async fn interceptors_see_each_call_and_can_reject_it() {
+    let calls = Arc::new(Mutex::new(Vec::<(NodeId, ProtocolId, u32)>::new()));
+    let builder = RpcServer::builder()
+        .with_minimum_client_deadline(Duration::from_secs(0))
+        .with_interceptor(RequestFilterLayer::new({
+            let calls = calls.clone();
+            move |req: &Request<Bytes>| {
+                let context = req.context();
+                calls.lock().unwrap().push((
+                    context.peer_node_id().clone(),
+                    context.protocol().clone(),
+                    req.method().id(),
+                ));
+                Ok(())
+            }
+        }))
+        .with_interceptor(RequestFilterLayer::new(|req: &Request<Bytes>| {
+            // Deny say_hello
+            if req.method().id() == 1 {
+                return Err(RpcStatus::forbidden("Not on the allow-list"));
+            }
+            Ok(())
+        }))
+        .with_interceptor(CallMetricsLayer::new());
+
+    let (mut muxer, _outbound, _, node_identity, _shutdown) =
+        setup_with_builder(GreetingService::default(), builder).await;
+    let socket = muxer.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::connect(framed).await.unwrap();
+
+    let err = client
+        .say_hello(SayHelloRequest {
+            name: "Yathvan".to_string(),
+            language: 1,
+        })
+        .await
+        .unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.as_status_code(), RpcStatusCode::Forbidden);
+    assert_eq!(status.details(), "Not on the allow-list");
+
+    let resp = client.get_greetings(2).await.unwrap();
+    let greetings = resp.map(|r| r.unwrap()).collect::<Vec<_>>().await;
+    assert_eq!(greetings, ["Sawubona", "Jambo"]);
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    let protocol = ProtocolId::from_static(b"/test/greeting/1.0");
+    assert_eq!(calls[0], (node_identity.node_id().clone(), protocol.clone(), 1));
+    assert_eq!(calls[1], (node_identity.node_id().clone(), protocol, 3));
+}
@@ -23,5 +23,7 @@
 mod comms_integration;
 pub(super) mod greeting_service;
 mod handshake;
+mod interceptor;
 pub(super) mod mock;
 mod smoke;
+mod streaming;
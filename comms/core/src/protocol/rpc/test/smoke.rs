@@ -119,7 +119,17 @@ pub(super) async fn setup<T: GreetingRpc>(
     service_impl: T,
     num_concurrent_sessions: usize,
 ) -> (Yamux, Yamux, task::JoinHandle<()>, Arc<NodeIdentity>, Shutdown) {
-    let (notif_tx, server_hnd, context, shutdown) = setup_service(service_impl, num_concurrent_sessions).await;
+    let builder = RpcServer::builder()
+        .with_maximum_simultaneous_sessions(num_concurrent_sessions)
+        .with_minimum_client_deadline(Duration::from_secs(0));
+    setup_with_builder(service_impl, builder).await
+}
+
+pub(super) async fn setup_with_builder<T: GreetingRpc>(
+    service_impl: T,
+    builder: RpcServerBuilder,
+) -> (Yamux, Yamux, task::JoinHandle<()>, Arc<NodeIdentity>, Shutdown) {
+    let (notif_tx, server_hnd, context, shutdown) = setup_service_with_builder(service_impl, builder).await;
     let (_, inbound, outbound) = build_multiplexed_connections().await;
     let substream = outbound.get_yamux_control().open_stream().await.unwrap();
 
@@ -0,0 +1,146 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use futures::{stream, StreamExt};
+use tari_test_utils::async_assert_eventually;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    framing,
+    protocol::rpc::{
+        test::{
+            greeting_service::{GreetingClient, GreetingService, SayHelloRequest},
+            smoke::{setup, setup_with_builder},
+        },
+        RpcServer,
+    },
+    resource_manager::{ResourceBudget, ResourceManager, ResourceManagerConfig},
+    runtime,
+    test_utils::mocks::create_connectivity_mock,
+};
+
+async fn say_hello(client: &mut GreetingClient) -> String {
+    client
+        .say_hello(SayHelloRequest {
+            name: "Yathvan".to_string(),
+            language: 1,
+        })
+        .await
+        .unwrap()
+        .greeting
+}
+
+#[runtime::test]
+async fn client_streaming_request_receives_a_single_response() {
+    let (mut muxer, _outbound, _, _, _shutdown) = setup(GreetingService::default(), 1).await;
+    let socket = muxer.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::connect(framed).await.unwrap();
+
+    let sum = client.sum_numbers(stream::iter(1..=10u32)).await.unwrap();
+    assert_eq!(sum, 55);
+
+    let sum = client.sum_numbers(stream::empty()).await.unwrap();
+    assert_eq!(sum, 0);
+
+    // The session continues to handle requests after the request stream
+    assert_eq!(say_hello(&mut client).await, "Jambo Yathvan");
+}
+
+#[runtime::test]
+async fn bidirectional_stream_responds_to_each_message() {
+    let (mut muxer, _outbound, _, _, _shutdown) = setup(GreetingService::default(), 1).await;
+    let socket = muxer.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::connect(framed).await.unwrap();
+
+    let (requests_tx, requests_rx) = mpsc::channel(1);
+    let mut responses = client.echo_stream(ReceiverStream::new(requests_rx)).await.unwrap();
+    for msg in ["Sawubona", "Jambo", "Bonjour"] {
+        requests_tx.send(msg.to_string()).await.unwrap();
+        let resp = responses.next().await.unwrap().unwrap();
+        assert_eq!(resp, format!("Echo: {}", msg));
+    }
+
+    // Ending the request stream completes the response stream
+    drop(requests_tx);
+    assert!(responses.next().await.is_none());
+
+    assert_eq!(say_hello(&mut client).await, "Jambo Yathvan");
+}
+
+#[runtime::test]
+async fn server_handler_observes_client_cancellation() {
+    let service = GreetingService::default();
+    let (mut muxer, _outbound, _, _, _shutdown) = setup(service.clone(), 1).await;
+    let socket = muxer.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::connect(framed).await.unwrap();
+
+    let mut responses = client.wait_for_cancel().await.unwrap();
+    assert_eq!(responses.next().await.unwrap().unwrap(), "Waiting");
+    assert_eq!(service.cancel_count(), 0);
+
+    drop(responses);
+    async_assert_eventually!(service.cancel_count(), expect = 1);
+
+    assert_eq!(say_hello(&mut client).await, "Jambo Yathvan");
+}
+
+#[runtime::test]
+async fn request_stream_messages_are_recorded_against_the_peer_budget() {
+    let (connectivity, mock) = create_connectivity_mock();
+    let mock_state = mock.spawn();
+    let resource_manager = ResourceManager::new(
+        ResourceManagerConfig {
+            is_enabled: true,
+            default_budget: ResourceBudget {
+                max_messages: 2,
+                ..ResourceBudget::unlimited()
+            },
+            ban_threshold: 2,
+            ban_duration: Duration::from_secs(60),
+            ..Default::default()
+        },
+        connectivity,
+    );
+    let builder = RpcServer::builder()
+        .with_maximum_simultaneous_sessions(1)
+        .with_minimum_client_deadline(Duration::from_secs(0))
+        .with_resource_manager(resource_manager);
+    let (mut muxer, _outbound, _, node_identity, _shutdown) =
+        setup_with_builder(GreetingService::default(), builder).await;
+    let socket = muxer.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::connect(framed).await.unwrap();
+
+    // A single call that sends more messages than the ban threshold allows
+    client.sum_numbers(stream::iter(1..=10u32)).await.unwrap_err();
+
+    mock_state.await_call_count(1).await;
+    let banned = mock_state.take_banned_peers().await;
+    assert_eq!(banned.len(), 1);
+    assert_eq!(&banned[0].0, node_identity.node_id());
+}
@@ -0,0 +1,135 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cmp, collections::HashMap, fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+/// The inbound resources a peer may consume for a single protocol within one accounting window. A limit of zero
+/// means that the resource is not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceBudget {
+    /// The maximum number of inbound bytes
+    pub max_bytes: u64,
+    /// The maximum number of inbound messages
+    pub max_messages: u64,
+    /// The maximum number of inbound RPC calls
+    pub max_rpc_calls: u64,
+    /// The maximum number of inbound substreams
+    pub max_substreams: u64,
+}
+
+impl ResourceBudget {
+    /// A budget that does not limit any resource
+    pub const fn unlimited() -> Self {
+        Self {
+            max_bytes: 0,
+            max_messages: 0,
+            max_rpc_calls: 0,
+            max_substreams: 0,
+        }
+    }
+
+    /// Returns this budget with each limit halved `times` times. Limits are never reduced below one so that a limited
+    /// resource never becomes unlimited.
+    pub(super) fn reduced_by(&self, times: u32) -> Self {
+        let reduce = |limit: u64| {
+            if limit == 0 {
+                return 0;
+            }
+            cmp::max(limit.checked_shr(times).unwrap_or(0), 1)
+        };
+        Self {
+            max_bytes: reduce(self.max_bytes),
+            max_messages: reduce(self.max_messages),
+            max_rpc_calls: reduce(self.max_rpc_calls),
+            max_substreams: reduce(self.max_substreams),
+        }
+    }
+}
+
+impl Default for ResourceBudget {
+    fn default() -> Self {
+        Self {
+            max_bytes: 100 * 1024 * 1024,
+            max_messages: 1_000,
+            max_rpc_calls: 1_000,
+            max_substreams: 200,
+        }
+    }
+}
+
+impl fmt::Display for ResourceBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "max_bytes: {}, max_messages: {}, max_rpc_calls: {}, max_substreams: {}",
+            self.max_bytes, self.max_messages, self.max_rpc_calls, self.max_substreams
+        )
+    }
+}
+
+/// Configuration for the [ResourceManager](crate::resource_manager::ResourceManager)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceManagerConfig {
+    /// True to enable per-peer resource accounting, otherwise all requests are allowed.
+    /// Default: false
+    pub is_enabled: bool,
+    /// The length of the accounting window. Usage is reset at the start of each window.
+    /// Default: 10s
+    #[serde(with = "serializers::seconds")]
+    pub window: Duration,
+    /// The budget applied to protocols that do not have a budget in `protocol_budgets`.
+    pub default_budget: ResourceBudget,
+    /// Budgets for specific protocols, keyed by the protocol id e.g. `t/msg/0.1`
+    pub protocol_budgets: HashMap<String, ResourceBudget>,
+    /// A peer that consumes more than this multiple of its budget within a single window is banned. A value of zero
+    /// disables banning, in which case offending peers are only throttled.
+    /// Default: 4
+    pub ban_threshold: u64,
+    /// The length of time to ban a peer that exceeds the ban threshold.
+    /// Default: 2 hours
+    #[serde(with = "serializers::seconds")]
+    pub ban_duration: Duration,
+    /// The maximum number of consecutive windows that reduce a peer's effective budget. Each window in which a peer
+    /// exceeds its budget halves the budget for the next window, up to this many times. Each window within budget
+    /// restores one step.
+    /// Default: 3
+    pub max_strikes: u32,
+}
+
+impl Default for ResourceManagerConfig {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            window: Duration::from_secs(10),
+            default_budget: ResourceBudget::default(),
+            protocol_budgets: HashMap::new(),
+            ban_threshold: 4,
+            ban_duration: Duration::from_secs(2 * 60 * 60),
+            max_strikes: 3,
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    cmp,
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::*;
+
+use super::{ResourceBudget, ResourceManagerConfig};
+use crate::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::ProtocolId, runtime::task};
+
+const LOG_TARGET: &str = "comms::resource_manager";
+
+/// Peers that have not consumed any resources for this many windows are forgotten
+const STALE_PEER_WINDOWS: u32 = 6;
+
+/// Inbound resources consumed by a peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub bytes: u64,
+    pub messages: u64,
+    pub rpc_calls: u64,
+    pub substreams: u64,
+}
+
+impl ResourceUsage {
+    /// The resources consumed by a single inbound message of `bytes` length
+    pub fn message(bytes: usize) -> Self {
+        Self {
+            bytes: bytes as u64,
+            messages: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The resources consumed by a single inbound RPC request of `bytes` length
+    pub fn rpc_call(bytes: usize) -> Self {
+        Self {
+            bytes: bytes as u64,
+            rpc_calls: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The resources consumed by a single inbound substream
+    pub fn substream() -> Self {
+        Self {
+            substreams: 1,
+            ..Default::default()
+        }
+    }
+
+    fn accumulate(&mut self, other: ResourceUsage) {
+        self.bytes = self.bytes.saturating_add(other.bytes);
+        self.messages = self.messages.saturating_add(other.messages);
+        self.rpc_calls = self.rpc_calls.saturating_add(other.rpc_calls);
+        self.substreams = self.substreams.saturating_add(other.substreams);
+    }
+
+    /// Returns true if any resource exceeds `multiplier` times its limit in the given budget
+    fn exceeds(&self, budget: &ResourceBudget, multiplier: u64) -> bool {
+        let exceeds = |used: u64, limit: u64| limit > 0 && used > limit.saturating_mul(multiplier);
+        exceeds(self.bytes, budget.max_bytes) ||
+            exceeds(self.messages, budget.max_messages) ||
+            exceeds(self.rpc_calls, budget.max_rpc_calls) ||
+            exceeds(self.substreams, budget.max_substreams)
+    }
+}
+
+impl fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bytes: {}, messages: {}, rpc_calls: {}, substreams: {}",
+            self.bytes, self.messages, self.rpc_calls, self.substreams
+        )
+    }
+}
+
+/// The action the caller should take for a request after it has been recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceDecision {
+    /// The peer is within budget and the request should be processed
+    Allow,
+    /// The peer has exceeded its budget. The request should be delayed by the given duration, or dropped if it cannot
+    /// be delayed.
+    Throttle(Duration),
+    /// The peer has been banned for exceeding its budget. The request should be rejected and the session closed.
+    Deny,
+}
+
+/// Tracks the inbound resources consumed by each peer per protocol and decides whether further requests are allowed.
+/// This is cheap to clone and all clones share the same accounting state.
+#[derive(Clone)]
+pub struct ResourceManager {
+    config: Arc<ResourceManagerConfig>,
+    protocol_budgets: Arc<HashMap<ProtocolId, ResourceBudget>>,
+    connectivity: ConnectivityRequester,
+    state: Arc<Mutex<State>>,
+}
+
+impl ResourceManager {
+    pub fn new(config: ResourceManagerConfig, connectivity: ConnectivityRequester) -> Self {
+        let protocol_budgets = config
+            .protocol_budgets
+            .iter()
+            .map(|(protocol, budget)| (ProtocolId::from(protocol.clone()), *budget))
+            .collect();
+        Self {
+            config: Arc::new(config),
+            protocol_budgets: Arc::new(protocol_budgets),
+            connectivity,
+            state: Arc::new(Mutex::new(State {
+                peers: HashMap::new(),
+                last_cleanup: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns true if resource accounting is enabled, otherwise false
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_enabled
+    }
+
+    /// Records the resources consumed by an inbound request from `peer` for `protocol` and returns the action that
+    /// the caller should take. If the peer exceeds the configured ban threshold, it is banned via the
+    /// ConnectivityManager.
+    pub fn record(&self, peer: &NodeId, protocol: &ProtocolId, usage: ResourceUsage) -> ResourceDecision {
+        if !self.config.is_enabled {
+            return ResourceDecision::Allow;
+        }
+
+        let now = Instant::now();
+        let mut state = acquire_lock!(self.state);
+        state.remove_stale_peers(now, self.config.window);
+        let resources = state
+            .peers
+            .entry(peer.clone())
+            .or_insert_with(|| PeerResources::new(now));
+        if resources.is_banned(now) {
+            return ResourceDecision::Deny;
+        }
+        resources.roll_window(now, self.config.window, self.config.max_strikes);
+        resources.last_seen = now;
+
+        let total = resources.add_usage(protocol, usage);
+        let budget = self.budget_for(protocol).reduced_by(resources.strikes);
+        if !total.exceeds(&budget, 1) {
+            return ResourceDecision::Allow;
+        }
+        resources.is_over_budget = true;
+
+        if self.config.ban_threshold > 0 && total.exceeds(&budget, self.config.ban_threshold) {
+            resources.banned_until = Some(now + self.config.ban_duration);
+            drop(state);
+            self.ban_peer(peer, protocol, total, budget);
+            return ResourceDecision::Deny;
+        }
+
+        let delay = resources.remaining_window(now, self.config.window);
+        debug!(
+            target: LOG_TARGET,
+            "Peer '{}' exceeded its budget for protocol '{}' ({}). Throttling for {:.0?}",
+            peer.short_str(),
+            String::from_utf8_lossy(protocol),
+            total,
+            delay
+        );
+        ResourceDecision::Throttle(delay)
+    }
+
+    fn budget_for(&self, protocol: &ProtocolId) -> ResourceBudget {
+        self.protocol_budgets
+            .get(protocol)
+            .copied()
+            .unwrap_or(self.config.default_budget)
+    }
+
+    fn ban_peer(&self, peer: &NodeId, protocol: &ProtocolId, usage: ResourceUsage, budget: ResourceBudget) {
+        let reason = format!(
+            "Exceeded resource budget for protocol '{}' by more than {}x (usage: {}, budget: {})",
+            String::from_utf8_lossy(protocol),
+            self.config.ban_threshold,
+            usage,
+            budget
+        );
+        warn!(
+            target: LOG_TARGET,
+            "Banning peer '{}' for {:.0?}: {}",
+            peer.short_str(),
+            self.config.ban_duration,
+            reason
+        );
+        let mut connectivity = self.connectivity.clone();
+        let peer = peer.clone();
+        let duration = self.config.ban_duration;
+        task::spawn(async move {
+            if let Err(err) = connectivity.ban_peer_until(peer.clone(), duration, reason).await {
+                error!(target: LOG_TARGET, "Failed to ban peer '{}': {}", peer.short_str(), err);
+            }
+        });
+    }
+}
+
+struct State {
+    peers: HashMap<NodeId, PeerResources>,
+    last_cleanup: Instant,
+}
+
+impl State {
+    fn remove_stale_peers(&mut self, now: Instant, window: Duration) {
+        if now.duration_since(self.last_cleanup) < window {
+            return;
+        }
+        self.last_cleanup = now;
+        let stale_after = window * STALE_PEER_WINDOWS;
+        self.peers
+            .retain(|_, resources| resources.is_banned(now) || now.duration_since(resources.last_seen) < stale_after);
+    }
+}
+
+struct PeerResources {
+    window_start: Instant,
+    last_seen: Instant,
+    usage: HashMap<ProtocolId, ResourceUsage>,
+    is_over_budget: bool,
+    strikes: u32,
+    banned_until: Option<Instant>,
+}
+
+impl PeerResources {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            last_seen: now,
+            usage: HashMap::new(),
+            is_over_budget: false,
+            strikes: 0,
+            banned_until: None,
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        matches!(self.banned_until, Some(until) if now < until)
+    }
+
+    /// Starts a new window if the current one has elapsed. A window that ended over budget adds a strike, and every
+    /// window that ended within budget (including idle windows) removes one.
+    fn roll_window(&mut self, now: Instant, window: Duration, max_strikes: u32) {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed < window {
+            return;
+        }
+        let num_windows = elapsed.as_millis() / cmp::max(window.as_millis(), 1);
+        let mut num_within_budget = u32::try_from(num_windows).unwrap_or(u32::MAX);
+        if self.is_over_budget {
+            self.strikes = cmp::min(self.strikes.saturating_add(1), max_strikes);
+            num_within_budget = num_within_budget.saturating_sub(1);
+        }
+        self.strikes = self.strikes.saturating_sub(num_within_budget);
+        self.window_start = now;
+        self.usage.clear();
+        self.is_over_budget = false;
+    }
+
+    fn add_usage(&mut self, protocol: &ProtocolId, usage: ResourceUsage) -> ResourceUsage {
+        let total = self.usage.entry(protocol.clone()).or_default();
+        total.accumulate(usage);
+        *total
+    }
+
+    fn remaining_window(&self, now: Instant, window: Duration) -> Duration {
+        window.saturating_sub(now.duration_since(self.window_start))
+    }
+}
@@ -0,0 +1,177 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, time::Duration};
+
+use tokio::time;
+
+use super::{ResourceBudget, ResourceDecision, ResourceManager, ResourceManagerConfig, ResourceUsage};
+use crate::{
+    protocol::ProtocolId,
+    runtime,
+    test_utils::{
+        mocks::{create_connectivity_mock, ConnectivityManagerMockState},
+        node_id,
+    },
+};
+
+static TEST_PROTOCOL: ProtocolId = ProtocolId::from_static(b"/test/protocol/1.0");
+
+fn message_budget(max_messages: u64) -> ResourceBudget {
+    ResourceBudget {
+        max_messages,
+        ..ResourceBudget::unlimited()
+    }
+}
+
+fn setup(config: ResourceManagerConfig) -> (ResourceManager, ConnectivityManagerMockState) {
+    let (connectivity, mock) = create_connectivity_mock();
+    let mock_state = mock.spawn();
+    (ResourceManager::new(config, connectivity), mock_state)
+}
+
+#[runtime::test]
+async fn it_allows_everything_when_disabled() {
+    let (resource_manager, _) = setup(ResourceManagerConfig {
+        is_enabled: false,
+        default_budget: message_budget(1),
+        ..Default::default()
+    });
+    let peer = node_id::random();
+    for _ in 0..10 {
+        let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+        assert_eq!(decision, ResourceDecision::Allow);
+    }
+}
+
+#[runtime::test]
+async fn it_throttles_peers_that_exceed_their_budget() {
+    let (resource_manager, mock_state) = setup(ResourceManagerConfig {
+        is_enabled: true,
+        default_budget: message_budget(2),
+        ban_threshold: 0,
+        ..Default::default()
+    });
+    let peer = node_id::random();
+    let other_peer = node_id::random();
+    for _ in 0..2 {
+        let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+        assert_eq!(decision, ResourceDecision::Allow);
+    }
+
+    for _ in 0..10 {
+        let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+        match decision {
+            ResourceDecision::Throttle(delay) => assert!(delay <= Duration::from_secs(10)),
+            _ => panic!("Expected throttle but got {:?}", decision),
+        }
+    }
+
+    // Other peers are unaffected
+    let decision = resource_manager.record(&other_peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+    assert_eq!(decision, ResourceDecision::Allow);
+    assert_eq!(mock_state.call_count().await, 0);
+}
+
+#[runtime::test]
+async fn it_bans_peers_that_exceed_the_ban_threshold() {
+    let (resource_manager, mock_state) = setup(ResourceManagerConfig {
+        is_enabled: true,
+        default_budget: message_budget(1),
+        ban_threshold: 2,
+        ban_duration: Duration::from_secs(60),
+        ..Default::default()
+    });
+    let peer = node_id::random();
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+    assert_eq!(decision, ResourceDecision::Allow);
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+    assert!(matches!(decision, ResourceDecision::Throttle(_)));
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(10));
+    assert_eq!(decision, ResourceDecision::Deny);
+
+    // The peer remains denied without being banned again
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::substream());
+    assert_eq!(decision, ResourceDecision::Deny);
+
+    mock_state.await_call_count(1).await;
+    let banned = mock_state.take_banned_peers().await;
+    assert_eq!(banned.len(), 1);
+    assert_eq!(banned[0].0, peer);
+    assert_eq!(banned[0].1, Duration::from_secs(60));
+    assert!(banned[0].2.contains("/test/protocol/1.0"));
+}
+
+#[runtime::test]
+async fn it_uses_protocol_budgets() {
+    let mut protocol_budgets = HashMap::new();
+    protocol_budgets.insert("/test/protocol/1.0".to_string(), ResourceBudget {
+        max_bytes: 100,
+        ..ResourceBudget::unlimited()
+    });
+    let (resource_manager, _) = setup(ResourceManagerConfig {
+        is_enabled: true,
+        default_budget: ResourceBudget::unlimited(),
+        protocol_budgets,
+        ban_threshold: 0,
+        ..Default::default()
+    });
+    let peer = node_id::random();
+    let other_protocol = ProtocolId::from_static(b"/test/other/1.0");
+    let decision = resource_manager.record(&peer, &other_protocol, ResourceUsage::rpc_call(1000));
+    assert_eq!(decision, ResourceDecision::Allow);
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::rpc_call(100));
+    assert_eq!(decision, ResourceDecision::Allow);
+    let decision = resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::rpc_call(1));
+    assert!(matches!(decision, ResourceDecision::Throttle(_)));
+}
+
+#[runtime::test]
+async fn it_reduces_the_budget_of_persistent_offenders() {
+    let window = Duration::from_millis(100);
+    let (resource_manager, _) = setup(ResourceManagerConfig {
+        is_enabled: true,
+        window,
+        default_budget: message_budget(4),
+        ban_threshold: 0,
+        ..Default::default()
+    });
+    let peer = node_id::random();
+    let record = || resource_manager.record(&peer, &TEST_PROTOCOL, ResourceUsage::message(1));
+    for _ in 0..4 {
+        assert_eq!(record(), ResourceDecision::Allow);
+    }
+    assert!(matches!(record(), ResourceDecision::Throttle(_)));
+
+    // The window ended over budget, so the budget is halved for the next window
+    time::sleep(window).await;
+    for _ in 0..2 {
+        assert_eq!(record(), ResourceDecision::Allow);
+    }
+    assert!(matches!(record(), ResourceDecision::Throttle(_)));
+
+    // The window ended over budget again, but the idle windows that follow each remove a strike
+    time::sleep(window * 4).await;
+    for _ in 0..4 {
+        assert_eq!(record(), ResourceDecision::Allow);
+    }
+}
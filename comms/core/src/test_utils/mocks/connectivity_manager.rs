@@ -37,7 +37,7 @@ use crate::{
         ConnectivityRequester,
         ConnectivityStatus,
     },
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent},
     runtime::task,
 };
 
@@ -64,6 +64,7 @@ struct State {
     pending_conns: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     selected_connections: Vec<PeerConnection>,
     banned_peers: Vec<(NodeId, Duration, String)>,
+    reputation_events: Vec<(NodeId, ReputationEvent)>,
     connectivity_status: ConnectivityStatus,
 }
 
@@ -180,6 +181,11 @@ impl ConnectivityManagerMockState {
         self.with_state(|state| state.banned_peers.drain(..).collect()).await
     }
 
+    pub async fn take_reputation_events(&self) -> Vec<(NodeId, ReputationEvent)> {
+        self.with_state(|state| state.reputation_events.drain(..).collect())
+            .await
+    }
+
     pub(self) async fn with_state<F, R>(&self, f: F) -> R
     where F: FnOnce(&mut State) -> R {
         let mut lock = self.inner.lock().await;
@@ -275,6 +281,13 @@ impl ConnectivityManagerMock {
                     })
                     .await
             },
+            RecordReputationEvent(node_id, event) => {
+                self.state
+                    .with_state(|state| {
+                        state.reputation_events.push((node_id, event));
+                    })
+                    .await
+            },
             AddPeerToAllowList(_) => {},
             RemovePeerFromAllowList(_) => {},
             GetActiveConnections(reply) => {
@@ -29,11 +29,17 @@
 //! Provides an abstraction for [Transport](self::Transport)s and several implemenations:
 //! - [TCP](self::TcpTransport) - communication over TCP and IP4/IP6 and DNS
 //! - [SOCKS](self::SocksTransport) - communication over a SOCKS5 proxy.
+//! - [QUIC](self::QuicTransport) - communication over QUIC using native QUIC streams for substreams.
 //! - [Memory](self::MemoryTransport) - in-process communication (mpsc channel), typically for testing.
 
+use std::io;
+
 use multiaddr::Multiaddr;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_stream::Stream;
 
+use crate::{connection_manager::ConnectionDirection, multiplexing::Yamux, noise::NoiseSocket};
+
 mod dns;
 
 pub mod predicate;
@@ -41,6 +47,9 @@ pub mod predicate;
 mod memory;
 pub use memory::MemoryTransport;
 
+mod quic;
+pub use quic::{QuicSocket, QuicTransport};
+
 mod socks;
 pub use socks::{SocksConfig, SocksTransport};
 
@@ -65,4 +74,18 @@ pub trait Transport {
 
     /// Connect (dial) to the given multiaddr
     async fn dial(&self, addr: Multiaddr) -> Result<Self::Output, Self::Error>;
+
+    /// Returns data that is mixed into the noise handshake as the prologue. Transports that establish their own secure
+    /// channel should return data that uniquely identifies that channel to both sides, so that the noise peer
+    /// identity is bound to it. By default, no channel binding is used.
+    fn channel_binding(_socket: &Self::Output) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Upgrades an authenticated socket to a multiplexed connection. By default, substreams are multiplexed over the
+    /// noise socket using yamux.
+    fn multiplex(socket: NoiseSocket<Self::Output>, direction: ConnectionDirection) -> io::Result<Yamux>
+    where Self::Output: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+        Yamux::upgrade_connection(socket, direction)
+    }
 }
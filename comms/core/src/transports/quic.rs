@@ -0,0 +1,483 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+
+use digest::Digest;
+use futures::StreamExt;
+use log::*;
+use multiaddr::{Multiaddr, Protocol};
+use once_cell::sync::OnceCell;
+use quinn::{Endpoint, NewConnection};
+use tari_crypto::{hash::blake2::Blake256, hashing::DomainSeparatedHasher};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::mpsc,
+    time,
+};
+use tokio_stream::Stream;
+
+use super::Transport;
+use crate::{
+    bounded_executor::BoundedExecutor,
+    connection_manager::ConnectionDirection,
+    multiplexing::{QuicStream, Yamux},
+    noise::NoiseSocket,
+    runtime,
+    types::CommsCoreHashDomain,
+};
+
+const LOG_TARGET: &str = "comms::transports::quic";
+
+/// The server name presented in the TLS handshake. Certificates are self-signed, so this is not used to authenticate
+/// the peer.
+const SERVER_NAME: &str = "tari";
+const ALPN_PROTOCOL: &[u8] = b"tari-comms";
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+const INBOUND_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+/// The maximum number of inbound connections that may be waiting for their QUIC handshake and first stream at the same
+/// time. Once this limit is reached, further connections are only accepted once another handshake completes.
+const MAX_SIMULTANEOUS_INBOUND_HANDSHAKES: usize = 100;
+
+/// Transport implementation for QUIC. Supports `/ip4/{addr}/udp/{port}/quic` and `/ip6/{addr}/udp/{port}/quic`
+/// addresses.
+///
+/// Each QUIC connection presents a self-signed certificate that is not used to authenticate the peer. Instead, the
+/// first bidirectional stream of a connection carries the wire mode byte, noise handshake and identity exchange as for
+/// other transports, and the certificates of both sides are mixed into the noise handshake prologue (see
+/// [Transport::channel_binding]). This binds the noise peer identity to the QUIC connection, after which substreams
+/// are opened as native QUIC streams rather than being multiplexed over the noise socket.
+#[derive(Clone)]
+pub struct QuicTransport {
+    server_config: quinn::ServerConfig,
+    client_config: quinn::ClientConfig,
+    certificate: Arc<rustls::Certificate>,
+    listener_endpoint: Arc<OnceCell<Endpoint>>,
+}
+
+impl QuicTransport {
+    /// Create a new QuicTransport with a newly generated self-signed certificate
+    pub fn new() -> io::Result<Self> {
+        let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()]).map_err(to_io_error)?;
+        let certificate = rustls::Certificate(cert.serialize_der().map_err(to_io_error)?);
+        let private_key = rustls::PrivateKey(cert.serialize_private_key_der());
+
+        let mut transport_config = quinn::TransportConfig::default();
+        transport_config.keep_alive_interval(Some(KEEP_ALIVE_INTERVAL));
+        let transport_config = Arc::new(transport_config);
+
+        let mut server_crypto = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(Arc::new(AcceptAnyCertificate))
+            .with_single_cert(vec![certificate.clone()], private_key.clone())
+            .map_err(to_io_error)?;
+        server_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport = transport_config.clone();
+
+        let mut client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+            .with_single_cert(vec![certificate.clone()], private_key)
+            .map_err(to_io_error)?;
+        client_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        let mut client_config = quinn::ClientConfig::new(Arc::new(client_crypto));
+        client_config.transport = transport_config;
+
+        Ok(Self {
+            server_config,
+            client_config,
+            certificate: Arc::new(certificate),
+            listener_endpoint: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Returns the endpoint to dial from. The listener endpoint is used if it is listening on the same IP version as
+    /// the remote address so that peers see our listening port, otherwise a new client endpoint is created.
+    fn dialer_endpoint(&self, remote_addr: &SocketAddr) -> io::Result<Endpoint> {
+        if let Some(endpoint) = self.listener_endpoint.get() {
+            if endpoint.local_addr()?.is_ipv4() == remote_addr.is_ipv4() {
+                return Ok(endpoint.clone());
+            }
+        }
+
+        let bind_addr: SocketAddr = if remote_addr.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        Endpoint::client(bind_addr)
+    }
+}
+
+#[crate::async_trait]
+impl Transport for QuicTransport {
+    type Error = io::Error;
+    type Listener = QuicInbound;
+    type Output = QuicSocket;
+
+    async fn listen(&self, addr: Multiaddr) -> Result<(Self::Listener, Multiaddr), Self::Error> {
+        let socket_addr = multiaddr_to_socketaddr(&addr)?;
+        let (endpoint, incoming) = Endpoint::server(self.server_config.clone(), socket_addr)?;
+        let local_addr = socketaddr_to_multiaddr(&endpoint.local_addr()?);
+        if self.listener_endpoint.set(endpoint).is_err() {
+            debug!(
+                target: LOG_TARGET,
+                "QUIC transport is already listening. Outbound connections will use the first listener endpoint."
+            );
+        }
+
+        let (sender, receiver) = mpsc::channel(10);
+        runtime::task::spawn(accept_connections(incoming, self.certificate.clone(), sender));
+        Ok((QuicInbound { receiver }, local_addr))
+    }
+
+    async fn dial(&self, addr: Multiaddr) -> Result<Self::Output, Self::Error> {
+        let socket_addr = multiaddr_to_socketaddr(&addr)?;
+        let connecting = self
+            .dialer_endpoint(&socket_addr)?
+            .connect_with(self.client_config.clone(), socket_addr, SERVER_NAME)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let NewConnection {
+            connection, bi_streams, ..
+        } = connecting.await?;
+        let (send, recv) = connection.open_bi().await?;
+        QuicSocket::new(
+            connection,
+            bi_streams,
+            QuicStream::new(send, recv),
+            ConnectionDirection::Outbound,
+            &self.certificate,
+        )
+    }
+
+    fn channel_binding(socket: &Self::Output) -> Vec<u8> {
+        socket.channel_binding.clone()
+    }
+
+    fn multiplex(socket: NoiseSocket<Self::Output>, _direction: ConnectionDirection) -> io::Result<Yamux> {
+        let QuicSocket {
+            connection, incoming, ..
+        } = socket.into_inner();
+        Ok(Yamux::from_quic_connection(connection, incoming))
+    }
+}
+
+async fn accept_connections(
+    mut incoming: quinn::Incoming,
+    certificate: Arc<rustls::Certificate>,
+    sender: mpsc::Sender<io::Result<(QuicSocket, Multiaddr)>>,
+) {
+    let bounded_executor = BoundedExecutor::from_current(MAX_SIMULTANEOUS_INBOUND_HANDSHAKES);
+    loop {
+        tokio::select! {
+            _ = sender.closed() => break,
+            connecting = incoming.next() => {
+                match connecting {
+                    Some(connecting) => {
+                        // This will block (asynchronously) if we have reached the maximum simultaneous handshakes,
+                        // creating back-pressure on nodes connecting to this node
+                        bounded_executor
+                            .spawn(accept_connection(connecting, certificate.clone(), sender.clone()))
+                            .await;
+                    },
+                    None => break,
+                }
+            },
+        }
+    }
+}
+
+async fn accept_connection(
+    connecting: quinn::Connecting,
+    certificate: Arc<rustls::Certificate>,
+    sender: mpsc::Sender<io::Result<(QuicSocket, Multiaddr)>>,
+) {
+    let peer_addr = socketaddr_to_multiaddr(&connecting.remote_address());
+    match time::timeout(INBOUND_HANDSHAKE_TIMEOUT, accept_first_stream(connecting, &certificate)).await {
+        Ok(Ok(socket)) => {
+            // Only fails if the listener has been dropped
+            let _result = sender.send(Ok((socket, peer_addr))).await;
+        },
+        Ok(Err(err)) => {
+            debug!(
+                target: LOG_TARGET,
+                "Inbound QUIC connection from '{}' failed because '{}'", peer_addr, err
+            );
+        },
+        Err(_) => {
+            debug!(
+                target: LOG_TARGET,
+                "Inbound QUIC connection from '{}' timed out before opening a stream", peer_addr
+            );
+        },
+    }
+}
+
+async fn accept_first_stream(
+    connecting: quinn::Connecting,
+    certificate: &rustls::Certificate,
+) -> io::Result<QuicSocket> {
+    let NewConnection {
+        connection,
+        mut bi_streams,
+        ..
+    } = connecting.await?;
+    // The dialer opens the first stream as soon as the connection is established
+    let (send, recv) = bi_streams.next().await.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "QUIC connection closed before the first stream was opened",
+        )
+    })??;
+    QuicSocket::new(
+        connection,
+        bi_streams,
+        QuicStream::new(send, recv),
+        ConnectionDirection::Inbound,
+        certificate,
+    )
+}
+
+/// A stream of established inbound QUIC connections
+pub struct QuicInbound {
+    receiver: mpsc::Receiver<io::Result<(QuicSocket, Multiaddr)>>,
+}
+
+impl Stream for QuicInbound {
+    type Item = io::Result<(QuicSocket, Multiaddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// An established QUIC connection. Reads and writes are performed on the first bidirectional stream of the
+/// connection, which is used to establish the peer identity before the connection is multiplexed.
+pub struct QuicSocket {
+    connection: quinn::Connection,
+    incoming: quinn::IncomingBiStreams,
+    stream: QuicStream,
+    channel_binding: Vec<u8>,
+}
+
+impl QuicSocket {
+    fn new(
+        connection: quinn::Connection,
+        incoming: quinn::IncomingBiStreams,
+        stream: QuicStream,
+        direction: ConnectionDirection,
+        local_certificate: &rustls::Certificate,
+    ) -> io::Result<Self> {
+        let remote_certificate = connection
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<rustls::Certificate>>().ok())
+            .and_then(|certs| certs.into_iter().next())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "QUIC peer did not present a certificate",
+                )
+            })?;
+
+        let (dialer_certificate, listener_certificate) = match direction {
+            ConnectionDirection::Outbound => (local_certificate, &remote_certificate),
+            ConnectionDirection::Inbound => (&remote_certificate, local_certificate),
+        };
+        let hasher = DomainSeparatedHasher::<Blake256, CommsCoreHashDomain>::new_with_label("quic.channel_binding");
+        let channel_binding = Digest::finalize(
+            hasher
+                .chain(dialer_certificate.0.as_slice())
+                .chain(listener_certificate.0.as_slice()),
+        )
+        .to_vec();
+
+        Ok(Self {
+            connection,
+            incoming,
+            stream,
+            channel_binding,
+        })
+    }
+
+    /// Returns the socket address of the remote peer
+    pub fn remote_address(&self) -> SocketAddr {
+        self.connection.remote_address()
+    }
+}
+
+impl AsyncRead for QuicSocket {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicSocket {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// Certificates are self-signed and only used to bind the noise handshake to the QUIC connection, so any certificate
+/// is accepted. The TLS handshake still checks that each side holds the private key for the certificate it presents.
+struct AcceptAnyCertificate;
+
+impl rustls::client::ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+impl rustls::server::ClientCertVerifier for AcceptAnyCertificate {
+    fn client_auth_root_subjects(&self) -> Option<rustls::DistinguishedNames> {
+        Some(Vec::new())
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _now: SystemTime,
+    ) -> Result<rustls::server::ClientCertVerified, rustls::Error> {
+        Ok(rustls::server::ClientCertVerified::assertion())
+    }
+}
+
+fn to_io_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+fn multiaddr_to_socketaddr(addr: &Multiaddr) -> io::Result<SocketAddr> {
+    let mut iter = addr.iter();
+    match (iter.next(), iter.next(), iter.next(), iter.next()) {
+        (Some(Protocol::Ip4(host)), Some(Protocol::Udp(port)), Some(Protocol::Quic), None) => Ok((host, port).into()),
+        (Some(Protocol::Ip6(host)), Some(Protocol::Udp(port)), Some(Protocol::Quic), None) => Ok((host, port).into()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid QUIC address '{}'", addr),
+        )),
+    }
+}
+
+fn socketaddr_to_multiaddr(socket_addr: &SocketAddr) -> Multiaddr {
+    let mut addr = crate::utils::multiaddr::socketaddr_to_multiaddr(socket_addr);
+    // Replace the TCP component with the UDP/QUIC components
+    addr.pop();
+    addr.push(Protocol::Udp(socket_addr.port()));
+    addr.push(Protocol::Quic);
+    addr
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[test]
+    fn multiaddr_conversion() {
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/1234/quic".parse().unwrap();
+        let socket_addr = multiaddr_to_socketaddr(&addr).unwrap();
+        assert_eq!(socket_addr, ([127, 0, 0, 1], 1234).into());
+        assert_eq!(socketaddr_to_multiaddr(&socket_addr), addr);
+
+        let addr: Multiaddr = "/ip6/::1/udp/1234/quic".parse().unwrap();
+        assert_eq!(socketaddr_to_multiaddr(&multiaddr_to_socketaddr(&addr).unwrap()), addr);
+
+        multiaddr_to_socketaddr(&"/ip4/127.0.0.1/tcp/1234".parse().unwrap()).unwrap_err();
+        multiaddr_to_socketaddr(&"/ip4/127.0.0.1/udp/1234".parse().unwrap()).unwrap_err();
+        multiaddr_to_socketaddr(&"/ip4/127.0.0.1/udp/1234/quic/p2p-circuit".parse().unwrap()).unwrap_err();
+    }
+
+    #[runtime::test]
+    async fn dial_and_listen() {
+        let transport = QuicTransport::new().unwrap();
+        let (mut inbound, addr) = transport
+            .listen("/ip4/127.0.0.1/udp/0/quic".parse().unwrap())
+            .await
+            .unwrap();
+
+        let mut socket_out = QuicTransport::new().unwrap().dial(addr).await.unwrap();
+        // The listener only sees the connection once the dialer writes to the first stream
+        socket_out.write_all(b"ping").await.unwrap();
+        socket_out.flush().await.unwrap();
+
+        let (mut socket_in, _) = inbound.next().await.unwrap().unwrap();
+        let mut buf = [0u8; 4];
+        socket_in.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        assert_eq!(
+            QuicTransport::channel_binding(&socket_in),
+            QuicTransport::channel_binding(&socket_out)
+        );
+    }
+
+    #[runtime::test]
+    async fn native_substreams() {
+        let transport = QuicTransport::new().unwrap();
+        let (mut inbound, addr) = transport
+            .listen("/ip4/127.0.0.1/udp/0/quic".parse().unwrap())
+            .await
+            .unwrap();
+
+        let mut socket_out = QuicTransport::new().unwrap().dial(addr).await.unwrap();
+        socket_out.write_all(&[0]).await.unwrap();
+        let (socket_in, _) = inbound.next().await.unwrap().unwrap();
+
+        let muxer_out = Yamux::from_quic_connection(socket_out.connection, socket_out.incoming);
+        let mut muxer_in = Yamux::from_quic_connection(socket_in.connection, socket_in.incoming);
+
+        let mut substream_out = muxer_out.get_yamux_control().open_stream().await.unwrap();
+        substream_out.write_all(b"hello").await.unwrap();
+        substream_out.shutdown().await.unwrap();
+
+        let mut substream_in = muxer_in.incoming_mut().next().await.unwrap();
+        let mut buf = Vec::new();
+        substream_in.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"hello");
+        assert_eq!(muxer_in.substream_count(), 1);
+    }
+}
@@ -34,7 +34,7 @@
 mod test;
 
 mod metrics;
-use std::{sync::Arc, time::Instant};
+use std::{cmp::Reverse, sync::Arc, time::Instant};
 
 use log::*;
 pub use metrics::{MetricsCollector, MetricsCollectorHandle};
@@ -829,7 +829,7 @@ impl DhtConnectivity {
             // Fetch double here so that there is a bigger closest peer set that can be ordered by last seen
             .limit(n * 2);
 
-        let peers = peer_manager.perform_query(query).await?;
+        let mut peers = peer_manager.perform_query(query).await?;
         let total_excluded = banned_count + connect_ineligable_count + excluded_count + filtered_out_node_count;
         if total_excluded > 0 {
             debug!(
@@ -849,6 +849,10 @@ impl DhtConnectivity {
             );
         }
 
+        // Prefer reputable peers from the closest peer set. The sort is stable, so the existing order is kept within
+        // each reputation tier.
+        peers.sort_by_key(|p| Reverse(p.reputation.tier()));
+
         Ok(peers.into_iter().map(|p| p.node_id).take(n).collect())
     }
 
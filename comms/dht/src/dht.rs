@@ -328,6 +328,7 @@ impl Dht {
                 self.dht_requester(),
                 Arc::clone(&self.node_identity),
                 self.outbound_requester(),
+                self.connectivity.clone(),
                 self.saf_response_signal_sender.clone(),
             ))
             .layer(inbound::DhtHandlerLayer::new(
@@ -28,7 +28,7 @@ use prost::Message;
 use tari_comms::{
     connectivity::ConnectivityRequester,
     message::EnvelopeBody,
-    peer_manager::NodeIdentity,
+    peer_manager::{NodeIdentity, ReputationEvent},
     pipeline::PipelineError,
 };
 use thiserror::Error;
@@ -200,6 +200,9 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 connectivity
                     .ban_peer_until(source.node_id.clone(), ban_duration, err.to_string())
                     .await?;
+                connectivity
+                    .record_reputation_event(source.node_id.clone(), ReputationEvent::InvalidMessage)
+                    .await?;
                 Err(err.into())
             },
             Err(EnvelopeBodyDecodeFailed) => {
@@ -602,7 +605,7 @@ mod test {
     #[runtime::test]
     async fn decrypt_inbound_fail_no_destination() {
         let (connectivity, mock) = create_connectivity_mock();
-        mock.spawn();
+        let mock_state = mock.spawn();
         let result = Arc::new(Mutex::new(None));
         let service = service_fn({
             let result = result.clone();
@@ -628,6 +631,11 @@ mod test {
         let err = err.downcast::<DecryptionError>().unwrap();
         unpack_enum!(DecryptionError::EncryptedMessageNoDestination = err);
         assert!(result.lock().unwrap().is_none());
+
+        mock_state.await_call_count(2).await;
+        let events = mock_state.take_reputation_events().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1, ReputationEvent::InvalidMessage);
     }
 
     #[runtime::test]
@@ -22,7 +22,7 @@
 
 use std::sync::Arc;
 
-use tari_comms::peer_manager::NodeIdentity;
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeIdentity};
 use tokio::sync::mpsc;
 use tower::layer::Layer;
 
@@ -40,6 +40,7 @@ pub struct MessageHandlerLayer {
     dht_requester: DhtRequester,
     node_identity: Arc<NodeIdentity>,
     outbound_service: OutboundMessageRequester,
+    connectivity: ConnectivityRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
 }
 
@@ -50,6 +51,7 @@ impl MessageHandlerLayer {
         dht_requester: DhtRequester,
         node_identity: Arc<NodeIdentity>,
         outbound_service: OutboundMessageRequester,
+        connectivity: ConnectivityRequester,
         saf_response_signal_sender: mpsc::Sender<()>,
     ) -> Self {
         Self {
@@ -59,6 +61,7 @@ impl MessageHandlerLayer {
             node_identity,
 
             outbound_service,
+            connectivity,
             saf_response_signal_sender,
         }
     }
@@ -75,6 +78,7 @@ impl<S> Layer<S> for MessageHandlerLayer {
             self.dht_requester.clone(),
             Arc::clone(&self.node_identity),
             self.outbound_service.clone(),
+            self.connectivity.clone(),
             self.saf_response_signal_sender.clone(),
         )
     }
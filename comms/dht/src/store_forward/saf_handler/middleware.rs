@@ -23,7 +23,7 @@
 use std::{sync::Arc, task::Poll};
 
 use futures::{future::BoxFuture, task::Context};
-use tari_comms::{peer_manager::NodeIdentity, pipeline::PipelineError};
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeIdentity, pipeline::PipelineError};
 use tokio::sync::mpsc;
 use tower::Service;
 
@@ -43,6 +43,7 @@ pub struct MessageHandlerMiddleware<S> {
     dht_requester: DhtRequester,
     node_identity: Arc<NodeIdentity>,
     outbound_service: OutboundMessageRequester,
+    connectivity: ConnectivityRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
 }
 
@@ -54,6 +55,7 @@ impl<S> MessageHandlerMiddleware<S> {
         dht_requester: DhtRequester,
         node_identity: Arc<NodeIdentity>,
         outbound_service: OutboundMessageRequester,
+        connectivity: ConnectivityRequester,
         saf_response_signal_sender: mpsc::Sender<()>,
     ) -> Self {
         Self {
@@ -64,6 +66,7 @@ impl<S> MessageHandlerMiddleware<S> {
             node_identity,
 
             outbound_service,
+            connectivity,
             saf_response_signal_sender,
         }
     }
@@ -92,6 +95,7 @@ where
                 self.outbound_service.clone(),
                 Arc::clone(&self.node_identity),
                 message,
+                self.connectivity.clone(),
                 self.saf_response_signal_sender.clone(),
             )
             .run(),
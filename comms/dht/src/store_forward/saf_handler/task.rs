@@ -30,8 +30,9 @@ use futures::{future, stream, StreamExt};
 use log::*;
 use prost::Message;
 use tari_comms::{
+    connectivity::ConnectivityRequester,
     message::{EnvelopeBody, MessageTag},
-    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManagerError},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManagerError, ReputationEvent},
     pipeline::PipelineError,
     types::CommsPublicKey,
 };
@@ -75,6 +76,7 @@ pub struct MessageHandlerTask<S> {
     node_identity: Arc<NodeIdentity>,
     message: Option<DecryptedDhtMessage>,
     saf_requester: StoreAndForwardRequester,
+    connectivity: ConnectivityRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
 }
 
@@ -89,6 +91,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         outbound_service: OutboundMessageRequester,
         node_identity: Arc<NodeIdentity>,
         message: DecryptedDhtMessage,
+        connectivity: ConnectivityRequester,
         saf_response_signal_sender: mpsc::Sender<()>,
     ) -> Self {
         Self {
@@ -99,6 +102,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             outbound_service,
             node_identity,
             message: Some(message),
+            connectivity,
             saf_response_signal_sender,
         }
     }
@@ -303,7 +307,8 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .process_incoming_stored_messages(source_peer.clone(), response.messages)
             .await?;
 
-        let successful_msgs_iter = results
+        let mut num_invalid = 0usize;
+        let successful_msgs = results
             .into_iter()
             .map(|result| {
                 match &result {
@@ -345,6 +350,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     // Every other error shouldn't happen if the sending node is behaving
                     Err(err) => {
                         // TODO: #banheuristics
+                        num_invalid += 1;
                         warn!(
                             target: LOG_TARGET,
                             "SECURITY: invalid store and forward message was discarded from NodeId={}. Reason: {}. \
@@ -358,7 +364,10 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 result
             })
             .filter(Result::is_ok)
-            .map(Result::unwrap);
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        self.record_saf_response_reputation(&source_node_id, successful_msgs.len(), num_invalid)
+            .await;
 
         // Let the SAF Service know we got a SAF response.
         let _ = self
@@ -368,7 +377,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .map_err(|e| warn!(target: LOG_TARGET, "Error sending SAF response signal; {:?}", e));
 
         self.next_service
-            .call_all(stream::iter(successful_msgs_iter))
+            .call_all(stream::iter(successful_msgs))
             .unordered()
             .for_each(|service_result| {
                 if let Err(err) = service_result {
@@ -381,6 +390,23 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(())
     }
 
+    /// Records the outcome of a SAF response against the reputation of the peer that sent it
+    async fn record_saf_response_reputation(&mut self, node_id: &NodeId, num_valid: usize, num_invalid: usize) {
+        let event = if num_invalid > 0 {
+            ReputationEvent::InvalidMessage
+        } else if num_valid > 0 {
+            ReputationEvent::StoreAndForwardDelivered
+        } else {
+            return;
+        };
+        if let Err(err) = self.connectivity.record_reputation_event(node_id.clone(), event).await {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record reputation event {} for peer `{}`: {}", event, node_id, err
+            );
+        }
+    }
+
     async fn process_incoming_stored_messages(
         &mut self,
         source_peer: Arc<Peer>,
@@ -617,7 +643,12 @@ mod test {
     use std::time::Duration;
 
     use chrono::Utc;
-    use tari_comms::{message::MessageExt, runtime, wrap_in_envelope_body};
+    use tari_comms::{
+        message::MessageExt,
+        runtime,
+        test_utils::mocks::create_connectivity_mock,
+        wrap_in_envelope_body,
+    };
     use tari_test_utils::collect_recv;
     use tari_utilities::{hex, hex::Hex};
     use tokio::{sync::mpsc, task, time::sleep};
@@ -674,6 +705,8 @@ mod test {
     #[allow(clippy::too_many_lines)]
     async fn request_stored_messages() {
         let spy = service_spy();
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        connectivity_mock.spawn();
         let (requester, mock_state) = create_store_and_forward_mock();
 
         let (outbound_requester, outbound_mock) = create_outbound_service_mock(10);
@@ -724,6 +757,7 @@ mod test {
             outbound_requester.clone(),
             node_identity.clone(),
             message.clone(),
+            connectivity.clone(),
             saf_response_signal_sender.clone(),
         );
 
@@ -781,6 +815,7 @@ mod test {
             outbound_requester.clone(),
             node_identity.clone(),
             message,
+            connectivity,
             saf_response_signal_sender,
         );
 
@@ -820,6 +855,8 @@ mod test {
     #[allow(clippy::similar_names, clippy::too_many_lines)]
     async fn receive_stored_messages() {
         let spy = service_spy();
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        let connectivity_mock_state = connectivity_mock.spawn();
         let (saf_requester, saf_mock_state) = create_store_and_forward_mock();
 
         let peer_manager = build_peer_manager();
@@ -909,6 +946,7 @@ mod test {
             OutboundMessageRequester::new(oms_tx),
             node_identity,
             message,
+            connectivity,
             saf_response_signal_sender,
         );
 
@@ -938,11 +976,18 @@ mod test {
             .unwrap();
 
         assert_eq!(last_saf_received, msg2_time);
+
+        connectivity_mock_state.await_call_count(1).await;
+        let events = connectivity_mock_state.take_reputation_events().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1, ReputationEvent::StoreAndForwardDelivered);
     }
 
     #[runtime::test]
     async fn stored_at_in_future() {
         let spy = service_spy();
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        connectivity_mock.spawn();
         let (requester, _) = create_store_and_forward_mock();
 
         let peer_manager = build_peer_manager();
@@ -995,6 +1040,7 @@ mod test {
             OutboundMessageRequester::new(oms_tx),
             node_identity,
             message,
+            connectivity,
             saf_response_signal_sender,
         );
 
@@ -1016,6 +1062,8 @@ mod test {
     #[runtime::test]
     async fn saf_message_was_requested() {
         let spy = service_spy();
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        connectivity_mock.spawn();
         let (saf_requester, saf_mock_state) = create_store_and_forward_mock();
 
         let peer_manager = build_peer_manager();
@@ -1068,6 +1116,7 @@ mod test {
             OutboundMessageRequester::new(oms_tx.clone()),
             node_identity.clone(),
             message.clone(),
+            connectivity.clone(),
             saf_response_signal_sender.clone(),
         );
 
@@ -1088,6 +1137,7 @@ mod test {
             OutboundMessageRequester::new(oms_tx),
             node_identity,
             message,
+            connectivity,
             saf_response_signal_sender,
         );
 
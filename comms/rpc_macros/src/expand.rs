@@ -65,6 +65,7 @@ impl TraitInfoCollector {
             method_ident: node.sig.ident.clone(),
             method_num: 0,
             is_server_streaming: false,
+            is_client_streaming: false,
             request_type: None,
             return_type: None,
         };
@@ -267,7 +268,16 @@ impl TraitInfoCollector {
                                 .ok_or_else(|| syn_error!(request_arg, "expected Request<T>"))?;
                             match arg {
                                 GenericArgument::Type(ty) => {
-                                    info.request_type = Some((*ty).clone());
+                                    // A `Request<RequestStream<T>>` argument is a stream of `T` requests
+                                    match request_stream_type(ty) {
+                                        Some(ty) => {
+                                            info.is_client_streaming = true;
+                                            info.request_type = Some(ty);
+                                        },
+                                        None => {
+                                            info.request_type = Some((*ty).clone());
+                                        },
+                                    }
                                     Ok(())
                                 },
                                 _ => Err(syn_error!(request_arg, "expected request type")),
@@ -305,6 +315,24 @@ impl Fold for TraitInfoCollector {
     }
 }
 
+/// Returns `T` if the given type is `RequestStream<T>`
+fn request_stream_type(ty: &Type) -> Option<Type> {
+    let segment = match ty {
+        Type::Path(syn::TypePath { path, .. }) => path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "RequestStream" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn extract_u32(ident: &syn::Ident, lit: &syn::Lit) -> syn::Result<u32> {
     match lit {
         syn::Lit::Int(int) => int.base10_parse(),
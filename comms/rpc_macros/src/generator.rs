@@ -67,10 +67,15 @@ impl RpcCodeGenerator {
                 } else {
                     quote!(Ok(resp.map(IntoBody::into_body)))
                 };
+                let req = if m.is_client_streaming {
+                    quote!(req.into_stream()?)
+                } else {
+                    quote!(req.decode()?)
+                };
                 quote! {
                     #method_num => {
                          let fut = async move {
-                            let resp = inner.#method_name(req.decode()?).await?;
+                            let resp = inner.#method_name(#req).await?;
                             #ret
                         };
                         Box::pin(fut)
@@ -168,10 +173,11 @@ impl RpcCodeGenerator {
 
                 let var = if is_unit { quote!(()) } else { quote!(request) };
 
-                let body = if m.is_server_streaming {
-                    quote!(self.inner.server_streaming(#var, #method_num).await)
-                } else {
-                    quote!(self.inner.request_response(#var, #method_num).await)
+                let body = match (m.is_client_streaming, m.is_server_streaming) {
+                    (true, true) => quote!(self.inner.bidirectional_streaming(requests, #method_num).await),
+                    (true, false) => quote!(self.inner.client_streaming(requests, #method_num).await),
+                    (false, true) => quote!(self.inner.server_streaming(#var, #method_num).await),
+                    (false, false) => quote!(self.inner.request_response(#var, #method_num).await),
                 };
 
                 let ok_type = if m.is_server_streaming {
@@ -180,6 +186,15 @@ impl RpcCodeGenerator {
                     quote!(#result_type)
                 };
 
+                if m.is_client_streaming {
+                    return quote! {
+                        pub async fn #name<S>(&mut self, requests: S) -> Result<#ok_type, #dep_mod::RpcError>
+                        where S: #dep_mod::Stream<Item = #request_type> + Send + 'static {
+                            #body
+                        }
+                    };
+                }
+
                 let params = if is_unit {
                     TokenStream::new()
                 } else {
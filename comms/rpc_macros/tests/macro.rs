@@ -22,14 +22,14 @@
 
 use std::{collections::HashMap, ops::AddAssign, sync::Arc};
 
-use futures::StreamExt;
+use futures::{stream, StreamExt};
 use prost::Message;
 use tari_comms::{
     framing,
     message::MessageExt,
     protocol::{
         rpc,
-        rpc::{NamedProtocolService, Request, Response, RpcStatus, RpcStatusCode, Streaming},
+        rpc::{NamedProtocolService, Request, RequestStream, Response, RpcStatus, RpcStatusCode, Streaming},
     },
     test_utils::transport::build_multiplexed_connections,
 };
@@ -50,6 +50,10 @@ pub trait Test: Sync + Send + 'static {
     /// Some docs for unit
     #[rpc(method = 3)]
     async fn unit(&self, request: Request<()>) -> Result<Response<()>, RpcStatus>;
+    #[rpc(method = 4)]
+    async fn client_streaming(&self, request: Request<RequestStream<u32>>) -> Result<Response<u32>, RpcStatus>;
+    #[rpc(method = 5)]
+    async fn bidirectional_streaming(&self, request: Request<RequestStream<u32>>) -> Result<Streaming<u32>, RpcStatus>;
 
     // Although not typically needed, there is no reason why other non-rpc methods can't be included in the resulting
     // trait
@@ -94,6 +98,21 @@ impl Test for TestService {
         Ok(Response::new(()))
     }
 
+    async fn client_streaming(&self, request: Request<RequestStream<u32>>) -> Result<Response<u32>, RpcStatus> {
+        self.add_call("client_streaming").await;
+        let sum = request
+            .into_message()
+            .map(Result::unwrap)
+            .fold(0, |sum, n| async move { sum + n })
+            .await;
+        Ok(Response::new(sum))
+    }
+
+    async fn bidirectional_streaming(&self, _: Request<RequestStream<u32>>) -> Result<Streaming<u32>, RpcStatus> {
+        self.add_call("bidirectional_streaming").await;
+        Ok(Streaming::empty())
+    }
+
     fn some_non_rpc_method(&self) {
         unimplemented!()
     }
@@ -151,6 +170,25 @@ async fn it_returns_an_error_for_invalid_method_nums() {
     unpack_enum!(RpcStatusCode::UnsupportedMethod = err.as_status_code());
 }
 
+#[tokio::test]
+async fn it_rejects_streaming_methods_without_a_request_stream() {
+    let service = TestService::default();
+    let spy = service.state.clone();
+    let mut server = TestServer::new(service);
+    let err = server
+        .call(Request::new(4.into(), 1u32.to_encoded_bytes().into()))
+        .await
+        .unwrap_err();
+    unpack_enum!(RpcStatusCode::BadRequest = err.as_status_code());
+
+    let err = server
+        .call(Request::new(5.into(), 1u32.to_encoded_bytes().into()))
+        .await
+        .unwrap_err();
+    unpack_enum!(RpcStatusCode::BadRequest = err.as_status_code());
+    assert!(spy.read().await.is_empty());
+}
+
 #[tokio::test]
 async fn it_generates_client_calls() {
     let (_, sock_client, mut sock_server) = build_multiplexed_connections().await;
@@ -170,5 +208,11 @@ async fn it_generates_client_calls() {
         let mut streaming_resp = client.server_streaming(CustomMessage).await.unwrap();
         streaming_resp.next().await;
         let _result = client.unit().await;
+        let _result = client.client_streaming(stream::iter(vec![1u32, 2, 3])).await;
+        let mut streaming_resp = client
+            .bidirectional_streaming(stream::iter(vec![1u32, 2, 3]))
+            .await
+            .unwrap();
+        streaming_resp.next().await;
     });
 }
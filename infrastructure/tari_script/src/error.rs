@@ -49,6 +49,10 @@ pub enum ScriptError {
     VerifyFailed,
     #[error("as_hash requires a Digest function that returns at least 32 bytes")]
     InvalidDigest,
+    #[error("The script has too many execution paths to analyse")]
+    TooManyExecutionPaths,
+    #[error("Could not parse the script text at position {position}: {reason}")]
+    ParseError { position: usize, reason: String },
 }
 
 impl From<TryFromIntError> for ScriptError {
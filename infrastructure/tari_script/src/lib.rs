@@ -18,14 +18,17 @@
 mod error;
 mod op_codes;
 mod script;
+mod script_analysis;
 mod script_commitment;
 mod script_context;
+mod script_parser;
 mod serde;
 mod stack;
 
 pub use error::ScriptError;
 pub use op_codes::{slice_to_boxed_hash, slice_to_hash, HashValue, Opcode};
 pub use script::TariScript;
+pub use script_analysis::{PathAnalysis, ScriptAnalysis, StackItemType, MAX_ANALYSIS_PATHS};
 pub use script_commitment::{ScriptCommitment, ScriptCommitmentError, ScriptCommitmentFactory};
 pub use script_context::ScriptContext;
 pub use stack::{ExecutionStack, StackItem};
@@ -77,11 +77,17 @@ pub fn slice_to_vec_pubkeys(slice: &[u8], num: usize) -> Result<Vec<RistrettoPub
     Ok(public_keys)
 }
 
+// Execution cost weights, used to estimate the cost of executing a script
+const COST_DEFAULT: u64 = 1;
+const COST_HASH: u64 = 10;
+const COST_CHECK_SIG: u64 = 100;
+
 // Opcode constants: Block Height Checks
 pub const OP_CHECK_HEIGHT_VERIFY: u8 = 0x66;
 pub const OP_CHECK_HEIGHT: u8 = 0x67;
 pub const OP_COMPARE_HEIGHT_VERIFY: u8 = 0x68;
 pub const OP_COMPARE_HEIGHT: u8 = 0x69;
+pub const OP_CHECK_RELATIVE_HEIGHT_VERIFY: u8 = 0x6a;
 
 // Opcode constants: Stack Manipulation
 pub const OP_DROP: u8 = 0x70;
@@ -142,6 +148,10 @@ pub enum Opcode {
     /// height. Fails with INVALID_INPUT if there is not a valid integer value on top of the stack. Fails with
     /// EMPTY_STACK if the stack is empty.
     CompareHeight,
+    /// Compare the number of blocks since the UTXO being spent was mined to relative_height. Fails with VERIFY_FAILED
+    /// if the current block height < the UTXO's mined height + relative_height. Fails with VALUE_EXCEEDS_BOUNDS if
+    /// the sum overflows.
+    CheckRelativeHeightVerify(u64),
 
     // Stack Manipulation
     /// No op. Does nothing. Never fails.
@@ -279,6 +289,10 @@ impl Opcode {
             },
             OP_COMPARE_HEIGHT_VERIFY => Ok((CompareHeightVerify, &bytes[1..])),
             OP_COMPARE_HEIGHT => Ok((CompareHeight, &bytes[1..])),
+            OP_CHECK_RELATIVE_HEIGHT_VERIFY => {
+                let (height, size) = u64::decode_var(&bytes[1..]).ok_or(ScriptError::InvalidData)?;
+                Ok((CheckRelativeHeightVerify(height), &bytes[size + 1..]))
+            },
             OP_NOP => Ok((Nop, &bytes[1..])),
             OP_PUSH_ZERO => Ok((PushZero, &bytes[1..])),
             OP_PUSH_ONE => Ok((PushOne, &bytes[1..])),
@@ -376,6 +390,21 @@ impl Opcode {
         Ok((*m, *n, keys, msg, end))
     }
 
+    /// The estimated relative cost of executing this opcode. Signature checks dominate, and `CheckMultiSig` is costed
+    /// for the worst case where each of the m signatures is checked against all n public keys.
+    pub fn execution_cost(&self) -> u64 {
+        #[allow(clippy::enum_glob_use)]
+        use Opcode::*;
+        match self {
+            HashBlake256 | HashSha256 | HashSha3 => COST_HASH,
+            CheckSig(_) | CheckSigVerify(_) => COST_CHECK_SIG,
+            CheckMultiSig(m, n, _, _) | CheckMultiSigVerify(m, n, _, _) => {
+                u64::from(*m) * u64::from(*n) * COST_CHECK_SIG
+            },
+            _ => COST_DEFAULT,
+        }
+    }
+
     /// Convert an opcode into its binary representation and append it to the array. The function returns the byte slice
     /// that matches the opcode as a convenience
     pub fn to_bytes<'a>(&self, array: &'a mut Vec<u8>) -> &'a [u8] {
@@ -397,6 +426,12 @@ impl Opcode {
             },
             CompareHeightVerify => array.push(OP_COMPARE_HEIGHT_VERIFY),
             CompareHeight => array.push(OP_COMPARE_HEIGHT),
+            CheckRelativeHeightVerify(height) => {
+                array.push(OP_CHECK_RELATIVE_HEIGHT_VERIFY);
+                let mut buf = [0u8; 10];
+                let used = height.encode_var(&mut buf[..]);
+                array.extend_from_slice(&buf[0..used]);
+            },
             Nop => array.push(OP_NOP),
             PushZero => array.push(OP_PUSH_ZERO),
             PushOne => array.push(OP_PUSH_ONE),
@@ -477,6 +512,7 @@ impl fmt::Display for Opcode {
             CheckHeight(height) => fmt.write_str(&format!("CheckHeight({})", *height)),
             CompareHeightVerify => fmt.write_str("CompareHeightVerify"),
             CompareHeight => fmt.write_str("CompareHeight"),
+            CheckRelativeHeightVerify(height) => fmt.write_str(&format!("CheckRelativeHeightVerify({})", *height)),
             Nop => fmt.write_str("Nop"),
             PushZero => fmt.write_str("PushZero"),
             PushOne => fmt.write_str("PushOne"),
@@ -585,6 +621,11 @@ mod test {
         }
         test_check_height(&Opcode::CheckHeight(63), 0x67, "CheckHeight(63)");
         test_check_height(&Opcode::CheckHeightVerify(63), 0x66, "CheckHeightVerify(63)");
+        test_check_height(
+            &Opcode::CheckRelativeHeightVerify(63),
+            0x6a,
+            "CheckRelativeHeightVerify(63)",
+        );
     }
 
     #[test]
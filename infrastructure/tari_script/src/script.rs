@@ -32,10 +32,12 @@ use tari_utilities::{
 
 use crate::{
     op_codes::Message,
+    script_analysis,
     slice_to_hash,
     ExecutionStack,
     HashValue,
     Opcode,
+    ScriptAnalysis,
     ScriptContext,
     ScriptError,
     StackItem,
@@ -51,7 +53,7 @@ macro_rules! script {
     }}
 }
 
-const MAX_MULTISIG_LIMIT: u8 = 32;
+pub(crate) const MAX_MULTISIG_LIMIT: u8 = 32;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TariScript {
@@ -108,6 +110,13 @@ impl TariScript {
         self.script.len()
     }
 
+    /// Statically analyse the script without executing it. The analysis checks the `IfThen`/`Else`/`EndIf` nesting,
+    /// and for every execution path determines the required input stack items, the maximum stack depth, the
+    /// estimated execution cost and whether the path can ever succeed.
+    pub fn analyse(&self) -> Result<ScriptAnalysis, ScriptError> {
+        script_analysis::analyse(&self.script)
+    }
+
     fn should_execute(&self, opcode: &Opcode, state: &ExecutionState) -> Result<bool, ScriptError> {
         use Opcode::{Else, EndIf, IfThen};
         match opcode {
@@ -198,6 +207,9 @@ impl TariScript {
             CheckHeight(height) => TariScript::handle_check_height(stack, *height, ctx.block_height()),
             CompareHeightVerify => TariScript::handle_compare_height_verify(stack, ctx.block_height()),
             CompareHeight => TariScript::handle_compare_height(stack, ctx.block_height()),
+            CheckRelativeHeightVerify(height) => {
+                TariScript::handle_check_relative_height_verify(*height, ctx.block_height(), ctx.utxo_mined_height())
+            },
             Nop => Ok(()),
             PushZero => stack.push(Number(0)),
             PushOne => stack.push(Number(1)),
@@ -302,6 +314,22 @@ impl TariScript {
         stack.push(item)
     }
 
+    fn handle_check_relative_height_verify(
+        relative_height: u64,
+        block_height: u64,
+        utxo_mined_height: u64,
+    ) -> Result<(), ScriptError> {
+        let spendable_height = utxo_mined_height
+            .checked_add(relative_height)
+            .ok_or(ScriptError::ValueExceedsBounds)?;
+
+        if block_height >= spendable_height {
+            Ok(())
+        } else {
+            Err(ScriptError::VerifyFailed)
+        }
+    }
+
     fn handle_cmp_to_zero(stack: &mut ExecutionStack, valid_orderings: &[Ordering]) -> Result<(), ScriptError> {
         let stack_number = stack.pop_into_number::<i64>()?;
         let ordering = &stack_number.cmp(&0);
@@ -618,7 +646,16 @@ mod test {
     };
 
     fn context_with_height(height: u64) -> ScriptContext {
-        ScriptContext::new(height, &HashValue::default(), &PedersenCommitment::default())
+        ScriptContext::new(height, &HashValue::default(), &PedersenCommitment::default(), 0)
+    }
+
+    fn context_with_mined_height(height: u64, utxo_mined_height: u64) -> ScriptContext {
+        ScriptContext::new(
+            height,
+            &HashValue::default(),
+            &PedersenCommitment::default(),
+            utxo_mined_height,
+        )
     }
 
     #[test]
@@ -816,6 +853,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn op_check_relative_height() {
+        let script = script!(CheckRelativeHeightVerify(5));
+        let inputs = inputs!(1);
+
+        for block_height in 10..15 {
+            let ctx = context_with_mined_height(block_height, 10);
+            let err = script.execute_with_context(&inputs, &ctx).unwrap_err();
+            assert!(matches!(err, ScriptError::VerifyFailed));
+        }
+
+        for block_height in 15..=20 {
+            let ctx = context_with_mined_height(block_height, 10);
+            let result = script.execute_with_context(&inputs, &ctx).unwrap();
+            assert_eq!(result, Number(1));
+        }
+
+        let script = script!(CheckRelativeHeightVerify(u64::MAX));
+        let ctx = context_with_mined_height(u64::MAX, 1);
+        let err = script.execute_with_context(&inputs, &ctx).unwrap_err();
+        assert!(matches!(err, ScriptError::ValueExceedsBounds));
+    }
+
     #[test]
     fn op_drop_push() {
         let inputs = inputs!(420);
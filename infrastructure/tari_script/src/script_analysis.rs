@@ -0,0 +1,681 @@
+// Copyright 2022. The Tari Project
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Static analysis of Tari scripts.
+//!
+//! The analyser walks every execution path of a script without any inputs or context. Each path is executed against a
+//! symbolic stack that only tracks the _type_ of each item. Items that are popped from below the symbolic stack are
+//! taken from the input stack, which lets the analyser infer how many inputs each path needs and of what type.
+
+use std::fmt;
+
+use crate::{script::MAX_MULTISIG_LIMIT, stack::MAX_STACK_SIZE, ExecutionStack, Opcode, ScriptError, StackItem};
+
+/// The maximum number of execution paths the analyser will follow. Every `IfThen` doubles the number of paths, so this
+/// bounds the work done for pathological scripts.
+pub const MAX_ANALYSIS_PATHS: usize = 1024;
+
+/// The type of an item on the execution stack, as far as it can be determined without executing the script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackItemType {
+    Number,
+    Hash,
+    Commitment,
+    PublicKey,
+    Signature,
+    /// The type of the item is not constrained by the script
+    Any,
+}
+
+impl StackItemType {
+    /// Returns true if the stack item is of this type
+    pub fn matches(self, item: &StackItem) -> bool {
+        matches!(
+            (self, item),
+            (StackItemType::Any, _) |
+                (StackItemType::Number, StackItem::Number(_)) |
+                (StackItemType::Hash, StackItem::Hash(_)) |
+                (StackItemType::Commitment, StackItem::Commitment(_)) |
+                (StackItemType::PublicKey, StackItem::PublicKey(_)) |
+                (StackItemType::Signature, StackItem::Signature(_))
+        )
+    }
+}
+
+impl fmt::Display for StackItemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The result of analysing a single execution path through a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathAnalysis {
+    /// The predicate taken at each `IfThen` along this path, in script order. `true` executes the `IfThen` branch.
+    pub branches: Vec<bool>,
+    /// The input stack items this path needs, starting with the _top_ of the input stack.
+    pub required_inputs: Vec<StackItemType>,
+    /// The maximum stack depth reached along this path when executed with exactly the required inputs.
+    pub max_stack_depth: usize,
+    /// The estimated cost of executing this path, given by the sum of the [Opcode::execution_cost] of every executed
+    /// opcode.
+    pub cost: u64,
+    /// The type of the item left on the stack if the path succeeds.
+    pub result: Option<StackItemType>,
+    /// If set, the path fails with this error regardless of the values of the inputs.
+    pub failure: Option<ScriptError>,
+}
+
+impl PathAnalysis {
+    /// Returns true if this path can succeed for some set of inputs
+    pub fn can_succeed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The result of statically analysing a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptAnalysis {
+    paths: Vec<PathAnalysis>,
+}
+
+impl ScriptAnalysis {
+    /// All execution paths through the script.
+    pub fn paths(&self) -> &[PathAnalysis] {
+        &self.paths
+    }
+
+    /// Returns an iterator over the paths that can succeed for some set of inputs
+    pub fn viable_paths(&self) -> impl Iterator<Item = &PathAnalysis> {
+        self.paths.iter().filter(|p| p.can_succeed())
+    }
+
+    /// Returns true if no path through the script can ever succeed. Such a script makes the output it is attached to
+    /// unspendable.
+    pub fn never_succeeds(&self) -> bool {
+        self.viable_paths().next().is_none()
+    }
+
+    /// The maximum stack depth over all viable paths
+    pub fn max_stack_depth(&self) -> usize {
+        self.viable_paths().map(|p| p.max_stack_depth).max().unwrap_or(0)
+    }
+
+    /// The maximum estimated execution cost over all paths
+    pub fn max_cost(&self) -> u64 {
+        self.paths.iter().map(|p| p.cost).max().unwrap_or(0)
+    }
+
+    /// Returns true if at least one viable path produces a result of the given type
+    pub fn can_produce(&self, item_type: StackItemType) -> bool {
+        self.viable_paths().any(|p| match p.result {
+            Some(StackItemType::Any) => true,
+            Some(t) => t == item_type || item_type == StackItemType::Any,
+            None => false,
+        })
+    }
+
+    /// Returns true if any viable path accepts the given input stack, i.e. the stack provides at least the required
+    /// inputs with compatible types. This does not execute the script, so the values of the inputs are not checked.
+    pub fn accepts_inputs(&self, inputs: &ExecutionStack) -> bool {
+        let inputs = inputs.as_slice();
+        self.viable_paths().any(|p| {
+            p.required_inputs.len() <= inputs.len() &&
+                p.required_inputs
+                    .iter()
+                    .zip(inputs.iter().rev())
+                    .all(|(t, item)| t.matches(item))
+        })
+    }
+}
+
+/// The if/else structure of a script
+#[derive(Debug)]
+enum Node<'a> {
+    Op(&'a Opcode),
+    IfThen {
+        then_branch: Vec<Node<'a>>,
+        else_branch: Vec<Node<'a>>,
+    },
+}
+
+/// Checks that every `IfThen` is matched by exactly one `Else` and one `EndIf` and builds the branch structure. The
+/// errors match those returned when executing the script.
+fn parse_structure(script: &[Opcode]) -> Result<Vec<Node<'_>>, ScriptError> {
+    // Each frame holds the nodes of the enclosing block, and for open IfThens, the completed then branch if Else has
+    // been seen
+    struct Frame<'a> {
+        nodes: Vec<Node<'a>>,
+        then_branch: Option<Vec<Node<'a>>>,
+    }
+
+    let mut stack = vec![Frame {
+        nodes: Vec::new(),
+        then_branch: None,
+    }];
+
+    for opcode in script {
+        match opcode {
+            Opcode::IfThen => stack.push(Frame {
+                nodes: Vec::new(),
+                then_branch: None,
+            }),
+            Opcode::Else => {
+                if stack.len() < 2 {
+                    return Err(ScriptError::InvalidOpcode);
+                }
+                let frame = stack.last_mut().expect("stack has at least 2 frames");
+                if frame.then_branch.is_some() {
+                    return Err(ScriptError::InvalidOpcode);
+                }
+                frame.then_branch = Some(std::mem::take(&mut frame.nodes));
+            },
+            Opcode::EndIf => {
+                if stack.len() < 2 {
+                    return Err(ScriptError::InvalidOpcode);
+                }
+                let frame = stack.pop().expect("stack has at least 2 frames");
+                let then_branch = frame.then_branch.ok_or(ScriptError::MissingOpcode)?;
+                stack
+                    .last_mut()
+                    .expect("stack has at least 1 frame")
+                    .nodes
+                    .push(Node::IfThen {
+                        then_branch,
+                        else_branch: frame.nodes,
+                    });
+            },
+            op => stack.last_mut().expect("stack is never empty").nodes.push(Node::Op(op)),
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err(ScriptError::MissingOpcode);
+    }
+
+    Ok(stack.pop().expect("stack has 1 frame").nodes)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    /// The nth item from the top of the input stack
+    Input(usize),
+    Value(StackItemType),
+}
+
+/// The symbolic execution state of a single path
+#[derive(Debug, Clone)]
+struct PathState {
+    branches: Vec<bool>,
+    stack: Vec<Slot>,
+    inputs: Vec<StackItemType>,
+    /// The symbolic stack size and the number of inputs revealed so far, sampled after every push
+    depth_samples: Vec<(usize, usize)>,
+    cost: u64,
+    failure: Option<ScriptError>,
+}
+
+impl PathState {
+    fn new() -> Self {
+        Self {
+            branches: Vec::new(),
+            stack: Vec::new(),
+            inputs: Vec::new(),
+            depth_samples: Vec::new(),
+            cost: 0,
+            failure: None,
+        }
+    }
+
+    fn is_failed(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    fn pop_slot(&mut self) -> Slot {
+        self.stack.pop().unwrap_or_else(|| {
+            self.inputs.push(StackItemType::Any);
+            Slot::Input(self.inputs.len() - 1)
+        })
+    }
+
+    /// Pops the top item, which must be one of the `allowed` types. Returns the resolved type of the item.
+    fn pop_typed(&mut self, allowed: &[StackItemType], err: ScriptError) -> Result<StackItemType, ScriptError> {
+        let slot = self.pop_slot();
+        self.constrain(slot, allowed, err)
+    }
+
+    fn constrain(
+        &mut self,
+        slot: Slot,
+        allowed: &[StackItemType],
+        err: ScriptError,
+    ) -> Result<StackItemType, ScriptError> {
+        let current = match slot {
+            Slot::Input(i) => self.inputs[i],
+            Slot::Value(t) => t,
+        };
+        if current == StackItemType::Any {
+            // Only an exact constraint narrows the type of an unknown item
+            if let (Slot::Input(i), [t]) = (slot, allowed) {
+                self.inputs[i] = *t;
+                return Ok(*t);
+            }
+            return Ok(StackItemType::Any);
+        }
+        if allowed.contains(&current) {
+            Ok(current)
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Pops two items of the same type, which must be one of the `allowed` types
+    fn pop_pair(&mut self, allowed: &[StackItemType]) -> Result<StackItemType, ScriptError> {
+        let top = self.pop_slot();
+        let two = self.pop_slot();
+        let t1 = self.constrain(top, allowed, ScriptError::IncompatibleTypes)?;
+        let t2 = self.constrain(two, allowed, ScriptError::IncompatibleTypes)?;
+        match (t1, t2) {
+            (StackItemType::Any, StackItemType::Any) => Ok(StackItemType::Any),
+            (StackItemType::Any, t) | (t, StackItemType::Any) => {
+                // The unknown item must have the same type as the known one
+                self.constrain(top, &[t], ScriptError::IncompatibleTypes)?;
+                self.constrain(two, &[t], ScriptError::IncompatibleTypes)?;
+                Ok(t)
+            },
+            (t1, t2) if t1 == t2 => Ok(t1),
+            _ => Err(ScriptError::IncompatibleTypes),
+        }
+    }
+
+    fn push(&mut self, item_type: StackItemType) -> Result<(), ScriptError> {
+        self.stack.push(Slot::Value(item_type));
+        self.sample_depth()
+    }
+
+    fn sample_depth(&mut self) -> Result<(), ScriptError> {
+        self.depth_samples.push((self.stack.len(), self.inputs.len()));
+        // The stack can never be smaller than the items pushed by the script itself
+        if self.stack.len() > MAX_STACK_SIZE {
+            return Err(ScriptError::StackOverflow);
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, opcode: &Opcode) {
+        if self.is_failed() {
+            return;
+        }
+        self.cost = self.cost.saturating_add(opcode.execution_cost());
+        if let Err(err) = self.execute_opcode(opcode) {
+            self.failure = Some(err);
+        }
+    }
+
+    fn execute_opcode(&mut self, opcode: &Opcode) -> Result<(), ScriptError> {
+        #[allow(clippy::enum_glob_use)]
+        use Opcode::*;
+        use StackItemType::{Commitment, Hash, Number, PublicKey, Signature};
+
+        match opcode {
+            CheckHeightVerify(_) | CheckRelativeHeightVerify(_) | Nop => Ok(()),
+            CheckHeight(_) | PushZero | PushOne | PushInt(_) => self.push(Number),
+            CompareHeightVerify => self.pop_typed(&[Number], ScriptError::InvalidInput).map(|_| ()),
+            CompareHeight | GeZero | GtZero | LeZero | LtZero => {
+                self.pop_typed(&[Number], ScriptError::InvalidInput)?;
+                self.push(Number)
+            },
+            PushHash(_) => self.push(Hash),
+            PushPubKey(_) => self.push(PublicKey),
+            Drop => {
+                self.pop_slot();
+                Ok(())
+            },
+            Dup => {
+                let slot = self.pop_slot();
+                self.stack.push(slot);
+                self.stack.push(slot);
+                self.sample_depth()
+            },
+            RevRot => {
+                let top = self.pop_slot();
+                let two = self.pop_slot();
+                let three = self.pop_slot();
+                self.stack.extend_from_slice(&[top, three, two]);
+                self.sample_depth()
+            },
+            Add => {
+                let t = self.pop_pair(&[Number, Commitment, PublicKey, Signature])?;
+                self.push(t)
+            },
+            Sub => {
+                let t = self.pop_pair(&[Number, Commitment])?;
+                self.push(t)
+            },
+            Equal => {
+                self.pop_pair(&[Number, Hash, Commitment, PublicKey, Signature])?;
+                self.push(Number)
+            },
+            EqualVerify => self
+                .pop_pair(&[Number, Hash, Commitment, PublicKey, Signature])
+                .map(|_| ()),
+            Or(n) | OrVerify(n) => {
+                let mut item_type = StackItemType::Any;
+                for _ in 0..=*n {
+                    let slot = self.pop_slot();
+                    if item_type == StackItemType::Any {
+                        item_type = self.constrain(
+                            slot,
+                            &[Number, Hash, Commitment, PublicKey, Signature],
+                            ScriptError::InvalidInput,
+                        )?;
+                    } else {
+                        self.constrain(slot, &[item_type], ScriptError::InvalidInput)?;
+                    }
+                }
+                if matches!(opcode, Or(_)) {
+                    self.push(Number)
+                } else {
+                    Ok(())
+                }
+            },
+            HashBlake256 | HashSha256 | HashSha3 => {
+                self.pop_typed(&[Commitment, PublicKey, Hash], ScriptError::IncompatibleTypes)?;
+                self.push(Hash)
+            },
+            CheckSig(_) | CheckSigVerify(_) => {
+                self.pop_typed(&[PublicKey], ScriptError::IncompatibleTypes)?;
+                self.pop_typed(&[Signature], ScriptError::IncompatibleTypes)?;
+                if matches!(opcode, CheckSig(_)) {
+                    self.push(Number)
+                } else {
+                    Ok(())
+                }
+            },
+            CheckMultiSig(m, n, _, _) | CheckMultiSigVerify(m, n, _, _) => {
+                if *m == 0 || *n == 0 || m > n || *n > MAX_MULTISIG_LIMIT {
+                    return Err(ScriptError::InvalidData);
+                }
+                for _ in 0..*m {
+                    self.pop_typed(&[Signature], ScriptError::IncompatibleTypes)?;
+                }
+                if matches!(opcode, CheckMultiSig(..)) {
+                    self.push(Number)
+                } else {
+                    Ok(())
+                }
+            },
+            Return => Err(ScriptError::Return),
+            IfThen | Else | EndIf => unreachable!("branch opcodes are handled by the analyser"),
+        }
+    }
+
+    /// Pops the IfThen predicate and forks the path into its two branches
+    fn branch(mut self) -> (PathState, PathState) {
+        self.cost = self.cost.saturating_add(Opcode::IfThen.execution_cost());
+        if !self.is_failed() {
+            if let Err(err) = self.pop_typed(&[StackItemType::Number], ScriptError::InvalidInput) {
+                self.failure = Some(err);
+            }
+        }
+        let mut then_state = self.clone();
+        then_state.branches.push(true);
+        let mut else_state = self;
+        else_state.branches.push(false);
+        (then_state, else_state)
+    }
+
+    fn finish(mut self) -> PathAnalysis {
+        let mut result = None;
+        if self.failure.is_none() {
+            match self.stack.len() {
+                // The result is an input that the script never touches
+                0 => {
+                    let slot = self.pop_slot();
+                    self.stack.push(slot);
+                    result = Some(StackItemType::Any);
+                },
+                1 => {
+                    result = Some(match self.stack[0] {
+                        Slot::Input(i) => self.inputs[i],
+                        Slot::Value(t) => t,
+                    });
+                },
+                _ => self.failure = Some(ScriptError::NonUnitLengthStack),
+            }
+        }
+
+        let num_inputs = self.inputs.len();
+        let max_stack_depth = self
+            .depth_samples
+            .iter()
+            .map(|(size, revealed)| size + num_inputs - revealed)
+            .chain(Some(num_inputs))
+            .max()
+            .unwrap_or(0);
+        if self.failure.is_none() && max_stack_depth > MAX_STACK_SIZE {
+            self.failure = Some(ScriptError::StackOverflow);
+        }
+
+        PathAnalysis {
+            branches: self.branches,
+            required_inputs: self.inputs,
+            max_stack_depth,
+            cost: self.cost,
+            result: result.filter(|_| self.failure.is_none()),
+            failure: self.failure,
+        }
+    }
+}
+
+fn analyse_nodes(nodes: &[Node<'_>], states: Vec<PathState>) -> Result<Vec<PathState>, ScriptError> {
+    let mut states = states;
+    for node in nodes {
+        match node {
+            Node::Op(op) => {
+                for state in &mut states {
+                    state.execute(op);
+                }
+            },
+            Node::IfThen {
+                then_branch,
+                else_branch,
+            } => {
+                let mut then_states = Vec::with_capacity(states.len());
+                let mut else_states = Vec::with_capacity(states.len());
+                for state in states {
+                    let (then_state, else_state) = state.branch();
+                    then_states.push(then_state);
+                    else_states.push(else_state);
+                }
+                if then_states.len() + else_states.len() > MAX_ANALYSIS_PATHS {
+                    return Err(ScriptError::TooManyExecutionPaths);
+                }
+                let mut then_states = analyse_nodes(then_branch, then_states)?;
+                let else_states = analyse_nodes(else_branch, else_states)?;
+                then_states.extend(else_states);
+                // The Else and EndIf markers are always processed
+                let marker_cost = Opcode::Else.execution_cost() + Opcode::EndIf.execution_cost();
+                for state in &mut then_states {
+                    state.cost = state.cost.saturating_add(marker_cost);
+                }
+                states = then_states;
+            },
+        }
+    }
+    Ok(states)
+}
+
+/// Statically analyse the script. Returns an error if the `IfThen`/`Else`/`EndIf` structure of the script is invalid,
+/// or if the script has more than [MAX_ANALYSIS_PATHS] execution paths.
+pub(crate) fn analyse(script: &[Opcode]) -> Result<ScriptAnalysis, ScriptError> {
+    let nodes = parse_structure(script)?;
+    let states = analyse_nodes(&nodes, vec![PathState::new()])?;
+    Ok(ScriptAnalysis {
+        paths: states.into_iter().map(PathState::finish).collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::{
+        keys::{PublicKey, SecretKey},
+        ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+    };
+    use tari_utilities::ByteArray;
+
+    use super::*;
+    use crate::{inputs, op_codes::slice_to_boxed_message, script, TariScript};
+
+    #[test]
+    fn unbalanced_branches() {
+        let err = script!(IfThen PushOne EndIf).analyse().unwrap_err();
+        assert_eq!(err, ScriptError::MissingOpcode);
+        let err = script!(IfThen PushOne Else PushZero).analyse().unwrap_err();
+        assert_eq!(err, ScriptError::MissingOpcode);
+        let err = script!(PushOne Else PushZero EndIf).analyse().unwrap_err();
+        assert_eq!(err, ScriptError::InvalidOpcode);
+        let err = script!(IfThen PushOne Else PushZero Else PushZero EndIf)
+            .analyse()
+            .unwrap_err();
+        assert_eq!(err, ScriptError::InvalidOpcode);
+        let err = script!(IfThen PushOne Else PushZero EndIf EndIf).analyse().unwrap_err();
+        assert_eq!(err, ScriptError::InvalidOpcode);
+        let analysis = script!(IfThen PushOne IfThen PushOne Else PushZero EndIf Else PushZero EndIf)
+            .analyse()
+            .unwrap();
+        assert_eq!(analysis.paths().len(), 3);
+    }
+
+    #[test]
+    fn required_inputs() {
+        let (_, pk) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+        let script = TariScript::new(vec![
+            Opcode::Dup,
+            Opcode::HashBlake256,
+            Opcode::PushHash(Box::new([0u8; 32])),
+            Opcode::EqualVerify,
+        ]);
+        let analysis = script.analyse().unwrap();
+        assert_eq!(analysis.paths().len(), 1);
+        let path = &analysis.paths()[0];
+        assert!(path.can_succeed());
+        // Dup leaves the type open, but the hash opcode only accepts some types
+        assert_eq!(path.required_inputs, vec![StackItemType::Any]);
+        assert_eq!(path.max_stack_depth, 3);
+        assert!(analysis.accepts_inputs(&inputs!(pk)));
+        assert!(!analysis.accepts_inputs(&ExecutionStack::default()));
+
+        let script = script!(CheckSigVerify(Box::new([0u8; 32])) PushOne);
+        let analysis = script.analyse().unwrap();
+        let path = &analysis.paths()[0];
+        assert_eq!(path.required_inputs, vec![
+            StackItemType::PublicKey,
+            StackItemType::Signature
+        ]);
+        assert_eq!(path.result, Some(StackItemType::Number));
+    }
+
+    #[test]
+    fn per_branch_inputs() {
+        let script = script!(IfThen Drop PushOne Else PushInt(5) Add EndIf);
+        let analysis = script.analyse().unwrap();
+        let then_path = analysis.paths().iter().find(|p| p.branches == [true]).unwrap();
+        let else_path = analysis.paths().iter().find(|p| p.branches == [false]).unwrap();
+        assert_eq!(then_path.required_inputs, vec![
+            StackItemType::Number,
+            StackItemType::Any
+        ]);
+        assert_eq!(else_path.required_inputs, vec![
+            StackItemType::Number,
+            StackItemType::Number
+        ]);
+        assert!(analysis.accepts_inputs(&inputs!(3, 1)));
+    }
+
+    #[test]
+    fn never_succeeds() {
+        assert!(script!(Return).analyse().unwrap().never_succeeds());
+        assert!(script!(PushOne PushOne).analyse().unwrap().never_succeeds());
+        assert!(script!(PushHash(Box::new([0u8; 32])) GeZero)
+            .analyse()
+            .unwrap()
+            .never_succeeds());
+        assert!(script!(PushOne PushHash(Box::new([0u8; 32])) Add)
+            .analyse()
+            .unwrap()
+            .never_succeeds());
+        assert!(script!(PushHash(Box::new([0u8; 32])) IfThen PushOne Else PushOne EndIf)
+            .analyse()
+            .unwrap()
+            .never_succeeds());
+
+        let analysis = script!(IfThen Return Else PushOne EndIf).analyse().unwrap();
+        assert!(!analysis.never_succeeds());
+        assert_eq!(analysis.viable_paths().count(), 1);
+        assert_eq!(analysis.viable_paths().next().unwrap().branches, vec![false]);
+    }
+
+    #[test]
+    fn stack_depth_and_cost() {
+        let script = script!(PushOne PushOne PushOne Drop Drop);
+        let analysis = script.analyse().unwrap();
+        assert_eq!(analysis.max_stack_depth(), 3);
+        assert_eq!(analysis.max_cost(), 5);
+
+        let msg = Box::new([0u8; 32]);
+        let script = script!(IfThen CheckSig(msg.clone()) Else Drop Drop PushOne EndIf);
+        let analysis = script.analyse().unwrap();
+        let sig_cost = Opcode::CheckSig(msg).execution_cost();
+        assert!(analysis.max_cost() > sig_cost);
+        assert!(analysis.paths().iter().any(|p| p.cost < sig_cost));
+    }
+
+    #[test]
+    fn too_many_paths() {
+        let mut ops = Vec::new();
+        for _ in 0..11 {
+            ops.extend([
+                Opcode::PushOne,
+                Opcode::IfThen,
+                Opcode::Nop,
+                Opcode::Else,
+                Opcode::Nop,
+                Opcode::EndIf,
+            ]);
+        }
+        let err = TariScript::new(ops).analyse().unwrap_err();
+        assert_eq!(err, ScriptError::TooManyExecutionPaths);
+    }
+
+    #[test]
+    fn agrees_with_execution() {
+        let mut rng = rand::thread_rng();
+        let (pvt_key, pub_key) = RistrettoPublicKey::random_keypair(&mut rng);
+        let nonce = RistrettoSecretKey::random(&mut rng);
+        let m_key = RistrettoSecretKey::random(&mut rng);
+        let sig = RistrettoSchnorr::sign(pvt_key, nonce, m_key.as_bytes()).unwrap();
+        let msg = slice_to_boxed_message(m_key.as_bytes());
+        let script = script!(CheckSig(msg));
+        let inputs = inputs!(sig.clone(), pub_key.clone());
+        assert_eq!(script.execute(&inputs).unwrap(), StackItem::Number(1));
+
+        let analysis = script.analyse().unwrap();
+        assert!(analysis.accepts_inputs(&inputs));
+        assert!(!analysis.accepts_inputs(&inputs!(pub_key, sig)));
+    }
+}
@@ -29,14 +29,19 @@ pub struct ScriptContext {
     prev_block_hash: HashValue,
     /// The commitment of the UTXO that is attached to this script
     commitment: PedersenCommitment,
+    /// The height of the chain where the UTXO being spent was _mined_. Where this is not known (e.g. when validating a
+    /// transaction without access to the chain database) this is zero, i.e. the UTXO is treated as if it was mined in
+    /// the genesis block.
+    utxo_mined_height: u64,
 }
 
 impl ScriptContext {
-    pub fn new(height: u64, prev_hash: &HashValue, com: &PedersenCommitment) -> Self {
+    pub fn new(height: u64, prev_hash: &HashValue, com: &PedersenCommitment, utxo_mined_height: u64) -> Self {
         ScriptContext {
             block_height: height,
             prev_block_hash: *prev_hash,
             commitment: com.clone(),
+            utxo_mined_height,
         }
     }
 
@@ -51,4 +56,8 @@ impl ScriptContext {
     pub fn commitment(&self) -> &PedersenCommitment {
         &self.commitment
     }
+
+    pub fn utxo_mined_height(&self) -> u64 {
+        self.utxo_mined_height
+    }
 }
@@ -0,0 +1,506 @@
+// Copyright 2022. The Tari Project
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Parser for the text form of Tari scripts.
+//!
+//! The text form is exactly what the `Display` implementations of [TariScript] and [Opcode] produce, so printing a
+//! script and parsing it back always yields the same script. The grammar is:
+//!
+//! ```text
+//! script    = [ opcode { whitespace opcode } ]
+//! opcode    = name [ "(" args ")" ]
+//! args      = arg { "," arg }
+//! arg       = integer | hex32 | "[" [ hex32 { "," hex32 } ] "]"
+//! integer   = [ "-" ] digit { digit }
+//! hex32     = 64 lowercase hexadecimal characters
+//! ```
+//!
+//! Whitespace is permitted between any two tokens. Opcode names are case-sensitive and match the [Opcode] variant
+//! names. The opcodes that take arguments are:
+//!
+//! | Opcode                                   | Arguments                                                      |
+//! |------------------------------------------|----------------------------------------------------------------|
+//! | `CheckHeightVerify(h)`, `CheckHeight(h)` | `h`: unsigned 64-bit block height                              |
+//! | `CheckRelativeHeightVerify(h)`           | `h`: unsigned 64-bit number of blocks                          |
+//! | `PushInt(n)`                             | `n`: signed 64-bit integer                                     |
+//! | `PushHash(h)`                            | `h`: 32-byte hash as hex                                       |
+//! | `PushPubKey(k)`                          | `k`: 32-byte public key as hex                                 |
+//! | `Or(n)`, `OrVerify(n)`                   | `n`: unsigned 8-bit count                                      |
+//! | `CheckSig(m)`, `CheckSigVerify(m)`       | `m`: 32-byte message as hex                                    |
+//! | `CheckMultiSig(m, n, [k, ...], msg)`     | `m`, `n`: unsigned 8-bit; `n` public keys as hex; 32-byte msg  |
+//! | `CheckMultiSigVerify(m, n, [k, ...], msg)` | as for `CheckMultiSig`                                       |
+//!
+//! For `CheckMultiSig` and `CheckMultiSigVerify`, the number of public keys in the list must equal `n`, since this is
+//! how many keys are read back from the binary form.
+//!
+//! # Example
+//! ```edition2018
+//! use tari_script::TariScript;
+//!
+//! let script: TariScript = "Dup HashBlake256 \
+//!                           PushHash(ae2337ce44f9ebb6169c863ec168046cb35ab4ef7aa9ed4f5f1f669bb74b09e5) EqualVerify"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(script.size(), 4);
+//! assert_eq!(script.to_string().parse::<TariScript>().unwrap(), script);
+//! ```
+
+use std::str::FromStr;
+
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_utilities::hex::{from_hex, Hex};
+
+use crate::{
+    op_codes::{slice_to_boxed_hash, slice_to_boxed_message},
+    Opcode,
+    ScriptError,
+    TariScript,
+};
+
+const HEX_32_LENGTH: usize = 64;
+
+impl FromStr for TariScript {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let mut script = Vec::new();
+        loop {
+            parser.skip_whitespace();
+            if parser.is_at_end() {
+                break;
+            }
+            script.push(parser.opcode()?);
+        }
+        Ok(TariScript::new(script))
+    }
+}
+
+impl FromStr for Opcode {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        let opcode = parser.opcode()?;
+        parser.skip_whitespace();
+        if !parser.is_at_end() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(opcode)
+    }
+}
+
+/// A single parsed argument
+#[derive(Debug)]
+enum Arg<'a> {
+    Atom(&'a str, usize),
+    List(Vec<(&'a str, usize)>, usize),
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, reason: &str) -> ScriptError {
+        ScriptError::ParseError {
+            position: self.pos,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn error_at(position: usize, reason: &str) -> ScriptError {
+        ScriptError::ParseError {
+            position,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ScriptError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// Reads a run of alphanumeric characters (or '-' for negative integers)
+    fn token(&mut self) -> Result<(&'a str, usize), ScriptError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name or value"));
+        }
+        self.pos += len;
+        Ok((&rest[..len], start))
+    }
+
+    fn args(&mut self) -> Result<Vec<Arg<'a>>, ScriptError> {
+        self.expect('(')?;
+        let mut args = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('[') {
+                let start = self.pos;
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        items.push(self.token()?);
+                        self.skip_whitespace();
+                        match self.peek() {
+                            Some(',') => self.pos += 1,
+                            Some(']') => {
+                                self.pos += 1;
+                                break;
+                            },
+                            _ => return Err(self.error("expected ',' or ']'")),
+                        }
+                    }
+                }
+                args.push(Arg::List(items, start));
+            } else {
+                let (token, start) = self.token()?;
+                args.push(Arg::Atom(token, start));
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(args);
+                },
+                _ => return Err(self.error("expected ',' or ')'")),
+            }
+        }
+    }
+
+    fn opcode(&mut self) -> Result<Opcode, ScriptError> {
+        #[allow(clippy::enum_glob_use)]
+        use Opcode::*;
+        let (name, start) = self.token()?;
+        let opcode = match name {
+            "CompareHeightVerify" => CompareHeightVerify,
+            "CompareHeight" => CompareHeight,
+            "Nop" => Nop,
+            "PushZero" => PushZero,
+            "PushOne" => PushOne,
+            "Drop" => Drop,
+            "Dup" => Dup,
+            "RevRot" => RevRot,
+            "GeZero" => GeZero,
+            "GtZero" => GtZero,
+            "LeZero" => LeZero,
+            "LtZero" => LtZero,
+            "Add" => Add,
+            "Sub" => Sub,
+            "Equal" => Equal,
+            "EqualVerify" => EqualVerify,
+            "HashBlake256" => HashBlake256,
+            "HashSha256" => HashSha256,
+            "HashSha3" => HashSha3,
+            "Return" => Return,
+            "IfThen" => IfThen,
+            "Else" => Else,
+            "EndIf" => EndIf,
+            "CheckHeightVerify" => CheckHeightVerify(self.single_arg(parse_integer)?),
+            "CheckHeight" => CheckHeight(self.single_arg(parse_integer)?),
+            "CheckRelativeHeightVerify" => CheckRelativeHeightVerify(self.single_arg(parse_integer)?),
+            "PushInt" => PushInt(self.single_arg(parse_integer)?),
+            "Or" => Or(self.single_arg(parse_integer)?),
+            "OrVerify" => OrVerify(self.single_arg(parse_integer)?),
+            "PushHash" => PushHash(slice_to_boxed_hash(&self.single_arg(parse_hex_32)?)),
+            "PushPubKey" => PushPubKey(Box::new(self.single_arg(parse_public_key)?)),
+            "CheckSig" => CheckSig(slice_to_boxed_message(&self.single_arg(parse_hex_32)?)),
+            "CheckSigVerify" => CheckSigVerify(slice_to_boxed_message(&self.single_arg(parse_hex_32)?)),
+            "CheckMultiSig" => {
+                let (m, n, keys, msg) = self.multisig_args()?;
+                CheckMultiSig(m, n, keys, msg)
+            },
+            "CheckMultiSigVerify" => {
+                let (m, n, keys, msg) = self.multisig_args()?;
+                CheckMultiSigVerify(m, n, keys, msg)
+            },
+            _ => return Err(Self::error_at(start, &format!("unknown opcode '{}'", name))),
+        };
+        Ok(opcode)
+    }
+
+    fn single_arg<T, F>(&mut self, parse: F) -> Result<T, ScriptError>
+    where F: Fn(&str, usize) -> Result<T, ScriptError> {
+        let start = self.pos;
+        match self.args()?.as_slice() {
+            [Arg::Atom(s, pos)] => parse(s, *pos),
+            _ => Err(Self::error_at(start, "expected exactly one argument")),
+        }
+    }
+
+    fn multisig_args(&mut self) -> Result<(u8, u8, Vec<RistrettoPublicKey>, Box<[u8; 32]>), ScriptError> {
+        let start = self.pos;
+        match self.args()?.as_slice() {
+            [Arg::Atom(m, m_pos), Arg::Atom(n, n_pos), Arg::List(keys, keys_pos), Arg::Atom(msg, msg_pos)] => {
+                let m = parse_integer(m, *m_pos)?;
+                let n: u8 = parse_integer(n, *n_pos)?;
+                if keys.len() != n as usize {
+                    return Err(Self::error_at(*keys_pos, "the number of public keys must equal n"));
+                }
+                let keys = keys
+                    .iter()
+                    .map(|(k, pos)| parse_public_key(k, *pos))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let msg = slice_to_boxed_message(&parse_hex_32(msg, *msg_pos)?);
+                Ok((m, n, keys, msg))
+            },
+            _ => Err(Self::error_at(
+                start,
+                "expected arguments (m, n, [public keys], message)",
+            )),
+        }
+    }
+}
+
+fn parse_integer<T: FromStr>(s: &str, position: usize) -> Result<T, ScriptError> {
+    // Only accept the canonical form produced by Display, so that parsing and printing round-trip exactly
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let is_canonical = !digits.is_empty() &&
+        digits.chars().all(|c| c.is_ascii_digit()) &&
+        (digits == "0" || !digits.starts_with('0')) &&
+        s != "-0";
+    if !is_canonical {
+        return Err(Parser::error_at(position, "expected an integer"));
+    }
+    s.parse()
+        .map_err(|_| Parser::error_at(position, "integer is out of range"))
+}
+
+fn parse_hex_32(s: &str, position: usize) -> Result<Vec<u8>, ScriptError> {
+    if s.len() != HEX_32_LENGTH {
+        return Err(Parser::error_at(position, "expected 32 bytes of hex"));
+    }
+    // Display only writes lowercase hex, so anything else would not round-trip
+    if !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return Err(Parser::error_at(position, "invalid hex, expected lowercase hex digits"));
+    }
+    from_hex(s).map_err(|_| Parser::error_at(position, "invalid hex"))
+}
+
+fn parse_public_key(s: &str, position: usize) -> Result<RistrettoPublicKey, ScriptError> {
+    parse_hex_32(s, position)?;
+    RistrettoPublicKey::from_hex(s).map_err(|_| Parser::error_at(position, "invalid public key"))
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use tari_crypto::{keys::PublicKey, ristretto::RistrettoSecretKey};
+
+    use super::*;
+    use crate::script;
+
+    #[test]
+    fn parse_all_opcodes() {
+        let p1 = "9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b";
+        let p2 = "56e9f018b138ba843521b3243a29d81730c3a4c25108b108b1ca47c2132db569";
+        let msg = "6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401";
+        let text = format!(
+            "CheckHeightVerify(1) CheckHeight(2) CompareHeightVerify CompareHeight CheckRelativeHeightVerify(3) Nop \
+             PushZero PushOne PushHash({msg}) PushInt(-4) PushPubKey({p1}) Drop Dup RevRot GeZero GtZero LeZero \
+             LtZero Add Sub Equal EqualVerify Or(5) OrVerify(6) HashBlake256 HashSha256 HashSha3 CheckSig({msg}) \
+             CheckSigVerify({msg}) CheckMultiSig(1, 2, [{p1}, {p2}], {msg}) CheckMultiSigVerify(2, 2, [{p1}, {p2}], \
+             {msg}) Return IfThen Else EndIf",
+            msg = msg,
+            p1 = p1,
+            p2 = p2
+        );
+        let script = text.parse::<TariScript>().unwrap();
+        assert_eq!(script.size(), 35);
+        assert_eq!(script.to_string(), text);
+        assert_eq!(TariScript::from_bytes(&script.as_bytes()).unwrap(), script);
+    }
+
+    #[test]
+    fn whitespace_is_flexible() {
+        let script = "  Dup\n\tPushInt( 5 )   CheckMultiSig( 1 ,1,[ \
+                      9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b ] , \
+                      6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401 )  "
+            .parse::<TariScript>()
+            .unwrap();
+        assert_eq!(script.size(), 3);
+        assert_eq!(script.as_slice()[1], Opcode::PushInt(5));
+        assert_eq!("".parse::<TariScript>().unwrap(), TariScript::new(vec![]));
+        assert_eq!(
+            script!(PushOne).to_string().parse::<TariScript>().unwrap(),
+            script!(PushOne)
+        );
+    }
+
+    #[test]
+    fn invalid_text() {
+        fn assert_error_at(text: &str, expected_position: usize) {
+            match text.parse::<TariScript>() {
+                Err(ScriptError::ParseError { position, .. }) => assert_eq!(position, expected_position, "{}", text),
+                res => panic!("Expected a parse error for '{}' but got {:?}", text, res),
+            }
+        }
+        assert_error_at("Dup Foo", 4);
+        assert_error_at("dup", 0);
+        assert_error_at("PushInt", 7);
+        assert_error_at("PushInt()", 8);
+        assert_error_at("PushInt(1, 2)", 7);
+        assert_error_at("PushInt(01)", 8);
+        assert_error_at("PushInt(+1)", 8);
+        assert_error_at("Or(256)", 3);
+        assert_error_at("CheckHeight(-1)", 12);
+        assert_error_at("PushInt(-0)", 8);
+        assert_error_at("PushHash(abcd)", 9);
+        assert_error_at(
+            "PushHash(6C9CB4D3E57351462122310FA22C90B1E6DFB528D64615363D1261A75DA3E401)",
+            9,
+        );
+        assert_error_at("Dup (", 4);
+        assert_error_at(
+            "CheckMultiSig(1, 2, [9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b], \
+             6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
+            20,
+        );
+        assert!(matches!(
+            "Dup Drop".parse::<Opcode>(),
+            Err(ScriptError::ParseError { position: 4, .. })
+        ));
+        assert_eq!(" Dup ".parse::<Opcode>().unwrap(), Opcode::Dup);
+    }
+
+    fn arb_hash() -> impl Strategy<Value = [u8; 32]> {
+        any::<[u8; 32]>()
+    }
+
+    fn arb_public_key() -> impl Strategy<Value = RistrettoPublicKey> {
+        any::<u64>().prop_map(|k| RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(k)))
+    }
+
+    fn arb_multisig_args() -> impl Strategy<Value = (u8, u8, Vec<RistrettoPublicKey>, [u8; 32])> {
+        (1u8..=4).prop_flat_map(|n| {
+            (
+                1..=n,
+                Just(n),
+                prop::collection::vec(arb_public_key(), n as usize),
+                arb_hash(),
+            )
+        })
+    }
+
+    fn arb_opcode() -> impl Strategy<Value = Opcode> {
+        #[allow(clippy::enum_glob_use)]
+        use Opcode::*;
+        let no_arg = prop::sample::select(vec![
+            CompareHeightVerify,
+            CompareHeight,
+            Nop,
+            PushZero,
+            PushOne,
+            Drop,
+            Dup,
+            RevRot,
+            GeZero,
+            GtZero,
+            LeZero,
+            LtZero,
+            Add,
+            Sub,
+            Equal,
+            EqualVerify,
+            HashBlake256,
+            HashSha256,
+            HashSha3,
+            Return,
+            IfThen,
+            Else,
+            EndIf,
+        ]);
+        prop_oneof![
+            no_arg,
+            any::<u64>().prop_map(CheckHeightVerify),
+            any::<u64>().prop_map(CheckHeight),
+            any::<u64>().prop_map(CheckRelativeHeightVerify),
+            any::<i64>().prop_map(PushInt),
+            any::<u8>().prop_map(Or),
+            any::<u8>().prop_map(OrVerify),
+            arb_hash().prop_map(|h| PushHash(Box::new(h))),
+            arb_public_key().prop_map(|k| PushPubKey(Box::new(k))),
+            arb_hash().prop_map(|m| CheckSig(Box::new(m))),
+            arb_hash().prop_map(|m| CheckSigVerify(Box::new(m))),
+            arb_multisig_args().prop_map(|(m, n, keys, msg)| CheckMultiSig(m, n, keys, Box::new(msg))),
+            arb_multisig_args().prop_map(|(m, n, keys, msg)| CheckMultiSigVerify(m, n, keys, Box::new(msg))),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn text_round_trip(ops in prop::collection::vec(arb_opcode(), 0..20)) {
+            let script = TariScript::new(ops);
+            let text = script.to_string();
+            let parsed = text.parse::<TariScript>().unwrap();
+            prop_assert_eq!(&parsed, &script);
+            prop_assert_eq!(parsed.to_string(), text);
+        }
+
+        #[test]
+        fn text_agrees_with_binary(ops in prop::collection::vec(arb_opcode(), 0..20)) {
+            let script = TariScript::new(ops);
+            let from_bytes = Opcode::parse(&script.as_bytes()).unwrap();
+            let from_text = script.to_string().parse::<TariScript>().unwrap();
+            prop_assert_eq!(from_bytes.as_slice(), from_text.as_slice());
+        }
+
+        #[test]
+        fn opcode_round_trip(op in arb_opcode()) {
+            prop_assert_eq!(op.to_string().parse::<Opcode>().unwrap(), op);
+        }
+    }
+}
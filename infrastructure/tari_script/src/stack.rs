@@ -173,6 +173,11 @@ impl ExecutionStack {
         self.items.len()
     }
 
+    /// Returns the items in the stack, bottom first
+    pub fn as_slice(&self) -> &[StackItem] {
+        self.items.as_slice()
+    }
+
     /// Returns a reference to the top entry in the stack without affecting the stack
     pub fn peek(&self) -> Option<&StackItem> {
         self.items.last()